@@ -0,0 +1,46 @@
+//! Appends phrasings to the corpus of the parser test, with what they currently parse to.
+//!
+//! ```shell
+//! $ cargo run --example corpus -- '明日の一時半' 'me after 10min'
+//! ```
+//!
+//! The inputs are the commands without the prefix or the mention. Check the appended lines before
+//! committing them, as they only record the current behavior.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write as _;
+use std::path::Path;
+
+use anyhow::{ensure, Result};
+use kaisantantoudaijin::model::command::Command;
+
+const CORPUS: &str = "src/model/command/corpus.tsv";
+
+fn main() -> Result<()> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join(CORPUS);
+    let existing = fs::read_to_string(&path)?;
+    let mut file = OpenOptions::new().append(true).open(&path)?;
+    for input in std::env::args().skip(1) {
+        let input = input.trim();
+        ensure!(
+            !input.contains(['\t', '\n']),
+            "{:?} contains a tab or a newline",
+            input
+        );
+        if existing
+            .lines()
+            .any(|line| line.split('\t').next() == Some(input))
+        {
+            eprintln!("skipped {:?}, which is already in the corpus", input);
+            continue;
+        }
+        // keep in sync with test_corpus in src/model/command.rs
+        let expected = match input.parse::<Command>() {
+            Ok(command) => format!("{:?}", command),
+            Err(_) => "-".to_owned(),
+        };
+        writeln!(file, "{}\t{}", input, expected)?;
+        println!("{}\t{}", input, expected);
+    }
+    Ok(())
+}
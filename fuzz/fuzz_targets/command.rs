@@ -0,0 +1,17 @@
+//! Parses arbitrary input as a command, which must never panic nor take long.
+//!
+//! ```shell
+//! $ cargo +nightly fuzz run command
+//! ```
+
+#![no_main]
+
+use kaisantantoudaijin::model::command::Command;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &str| {
+    if let Ok(command) = input.parse::<Command>() {
+        // the canonical form parses as well
+        assert!(command.to_string().parse::<Command>().is_ok());
+    }
+});
@@ -0,0 +1,113 @@
+//! The activity of the bot, which shows the next kaisan of all the guilds it is in.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::database::DatabaseHandle;
+use crate::error::Result;
+use crate::model::setting;
+use crate::registry::ScheduleRegistry;
+
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use serenity::{gateway::ActivityData, gateway::ShardManager};
+
+/// Minimum time between the updates of the activity, which Discord rate-limits along with the
+/// other commands sent through the gateway.
+const MIN_UPDATE_INTERVAL: Duration = Duration::from_secs(20);
+
+/// The text of the activity for the next kaisan at `time`, such as `次の解散: 23:00 JST`. The
+/// date is added unless it is on the same day as `now`.
+pub fn activity_text(time: DateTime<Tz>, now: DateTime<Utc>) -> String {
+    let today = now.with_timezone(&time.timezone()).date_naive();
+    if time.date_naive() == today {
+        format!("次の解散: {}", time.format("%H:%M %Z"))
+    } else {
+        format!("次の解散: {}", time.format("%m/%d %H:%M %Z"))
+    }
+}
+
+/// The activity showing the next kaisan in the timezone of its guild, or `None` if no kaisan is
+/// scheduled.
+pub async fn next_kaisan_activity<D: DatabaseHandle + Sync>(
+    registry: &ScheduleRegistry,
+    database: &D,
+) -> Result<Option<ActivityData>> {
+    Ok(next_kaisan_text(registry, database)
+        .await?
+        .map(ActivityData::custom))
+}
+
+async fn next_kaisan_text<D: DatabaseHandle + Sync>(
+    registry: &ScheduleRegistry,
+    database: &D,
+) -> Result<Option<String>> {
+    let Some((guild_id, schedule)) = registry.next_schedule().await else {
+        return Ok(None);
+    };
+    let stored = database.get::<String>(guild_id, "timezone").await?;
+    let timezone = setting::stored_timezone(stored.as_deref());
+    let time = schedule.public_time().with_timezone(&timezone);
+    Ok(Some(activity_text(time, Utc::now())))
+}
+
+/// Keeps the activity of all the shards up to date as the schedules change. Runs until aborted.
+pub async fn update_activity<D: DatabaseHandle + Sync>(
+    shard_manager: Arc<ShardManager>,
+    registry: ScheduleRegistry,
+    database: D,
+) {
+    let mut shown = None;
+    loop {
+        match next_kaisan_text(&registry, &database).await {
+            Ok(text) => {
+                if text != shown {
+                    let activity = text.clone().map(ActivityData::custom);
+                    for runner in shard_manager.runners.lock().await.values() {
+                        runner.runner_tx.set_activity(activity.clone());
+                    }
+                    tracing::debug!(?text, "updated activity");
+                    shown = text;
+                    tokio::time::sleep(MIN_UPDATE_INTERVAL).await;
+                }
+            }
+            Err(e) => tracing::warn!("cannot obtain the next kaisan: {:#}", e),
+        }
+        registry.changed().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::activity_text;
+
+    use chrono::{DateTime, Utc};
+    use chrono_tz::Tz;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_activity_text() {
+        let now = utc("2024-07-20T10:00:00Z");
+        assert_eq!(
+            activity_text(
+                utc("2024-07-20T14:00:00Z").with_timezone(&chrono_tz::Japan),
+                now
+            ),
+            "次の解散: 23:00 JST"
+        );
+        assert_eq!(
+            activity_text(
+                utc("2024-07-20T15:30:00Z").with_timezone(&chrono_tz::Japan),
+                now
+            ),
+            "次の解散: 07/21 00:30 JST"
+        );
+        assert_eq!(
+            activity_text(utc("2024-07-20T23:00:00Z").with_timezone(&Tz::UTC), now),
+            "次の解散: 23:00 UTC"
+        );
+    }
+}
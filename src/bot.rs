@@ -0,0 +1,673 @@
+//! The bot as a whole, for running it from other programs.
+//!
+//! ```no_run
+//! # async fn run() -> anyhow::Result<()> {
+//! use kaisantantoudaijin::{database::InMemoryHandle, Bot};
+//!
+//! let bot = Bot::builder("TOKEN")
+//!     .database(InMemoryHandle::new())
+//!     .command_prefix("!kaisan")
+//!     .build()
+//!     .await?;
+//! bot.run(None, async {
+//!     let _ = tokio::signal::ctrl_c().await;
+//! })
+//! .await
+//! # }
+//! ```
+
+use std::collections::HashSet;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, OnceLock,
+};
+
+use crate::activity;
+use crate::context::{
+    ChannelContext, Context, ContextBuilder, GuildContext, SettingContext,
+    DEFAULT_LATE_GRACE_SECONDS,
+};
+use crate::database::AnyDatabaseHandle;
+use crate::dispatcher::Dispatcher;
+use crate::log::LogFilterHandle;
+use crate::model::message::Message;
+use crate::prefix::{strip_command_prefix, CommandPrefixes};
+use crate::presence::PresenceTracker;
+use crate::registry::ScheduleRegistry;
+use crate::use_case::{AuthorLeft, AutoKaisan, RecordVoiceSession, RestoreSchedule};
+use crate::voice::VoiceAnnouncer;
+use crate::web::{self, CancelLinks};
+
+use anyhow::{Context as _, Result};
+use serenity::{
+    builder::CreateInteractionResponse,
+    client::{Client, ClientBuilder, EventHandler},
+    gateway::ShardManager,
+    model::{
+        application::{ComponentInteractionDataKind, Interaction},
+        gateway::{GatewayIntents, Ready},
+        id::UserId,
+        voice::VoiceState,
+    },
+};
+
+pub const DEFAULT_COMMAND_PREFIX: &str = "!kaisan";
+
+/// The gateway intents the bot needs to work.
+pub const DEFAULT_INTENTS: GatewayIntents = GatewayIntents::GUILDS
+    .union(GatewayIntents::GUILD_MESSAGES)
+    .union(GatewayIntents::GUILD_MESSAGE_REACTIONS)
+    .union(GatewayIntents::GUILD_VOICE_STATES)
+    .union(GatewayIntents::MESSAGE_CONTENT);
+
+fn strip_affix<'a>(content: &'a str, affix: &str) -> Option<&'a str> {
+    content
+        .strip_prefix(affix)
+        .or_else(|| content.strip_suffix(affix))
+}
+
+/// Shows the guild and channel names in the logs of the current span, ignoring failures.
+async fn record_names(ctx: &Context) {
+    let span = tracing::Span::current();
+    match ctx.guild_name().await {
+        Ok(name) => {
+            span.record("guild", name);
+        }
+        Err(e) => tracing::debug!("cannot obtain guild name: {:#}", e),
+    }
+    match ctx.channel_name().await {
+        Ok(name) => {
+            span.record("channel", name);
+        }
+        Err(e) => tracing::debug!("cannot obtain channel name: {:#}", e),
+    }
+}
+
+/// Dispatches the Discord events to the use cases.
+struct Handler {
+    command_prefix: String,
+    database: AnyDatabaseHandle,
+    registry: ScheduleRegistry,
+    presence: PresenceTracker,
+    command_prefixes: CommandPrefixes,
+    cancel_links: Option<CancelLinks>,
+    voice: Option<VoiceAnnouncer>,
+    log_filter: Option<LogFilterHandle>,
+    /// Set once the client is built, which needs the handler first.
+    shard_manager: Arc<OnceLock<Arc<ShardManager>>>,
+    records_disconnects: bool,
+    late_grace: chrono::Duration,
+    owners: Arc<HashSet<UserId>>,
+    shutting_down: Arc<AtomicBool>,
+}
+
+impl Handler {
+    /// A builder with what every context of the handler shares.
+    fn context_builder(&self, ctx: &serenity::client::Context) -> ContextBuilder {
+        let mut builder = ContextBuilder::with_serenity(ctx);
+        builder
+            .database(self.database.clone())
+            .registry(self.registry.clone())
+            .presence(self.presence.clone())
+            .command_prefixes(self.command_prefixes.clone())
+            .cancel_links(self.cancel_links.clone())
+            .voice(self.voice.clone())
+            .log_filter(self.log_filter.clone())
+            .shard_manager(self.shard_manager.get().cloned())
+            .records_disconnects(self.records_disconnects)
+            .late_grace(self.late_grace)
+            .owners(Arc::clone(&self.owners));
+        builder
+    }
+}
+
+#[async_trait::async_trait]
+impl EventHandler for Handler {
+    async fn ready(&self, ctx: serenity::client::Context, ready: Ready) {
+        tracing::info!(
+            shard_id = ctx.shard_id.0,
+            guilds = ready.guilds.len(),
+            user = %ready.user.name,
+            "shard is ready"
+        );
+
+        // the shard may have started after the last update of the activity
+        match activity::next_kaisan_activity(&self.registry, &self.database).await {
+            Ok(activity) => ctx.set_activity(activity),
+            Err(e) => tracing::warn!("cannot obtain the next kaisan: {:#}", e),
+        }
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            shard_id = ctx.shard_id.0,
+            guild_id = msg.guild_id.map(|id| id.get()),
+            channel_id = msg.channel_id.get(),
+            message_id = msg.id.get(),
+            author_id = msg.author.id.get(),
+            guild = tracing::field::Empty,
+            channel = tracing::field::Empty
+        )
+    )]
+    async fn message(
+        &self,
+        ctx: serenity::client::Context,
+        msg: serenity::model::channel::Message,
+    ) {
+        if msg.author.bot {
+            return;
+        }
+
+        let bot_id = ctx.cache.current_user().id;
+        let mentioned = strip_affix(&msg.content, &format!("<@{}>", bot_id))
+            .or_else(|| strip_affix(&msg.content, &format!("<@!{}>", bot_id)));
+
+        let Some(guild_id) = msg.guild_id else {
+            if mentioned
+                .or_else(|| strip_command_prefix(&msg.content, &self.command_prefix))
+                .is_some()
+            {
+                let _ = msg
+                    .channel_id
+                    .say(&ctx.http, "サーバー内で使ってください")
+                    .await;
+            }
+            return;
+        };
+
+        let ctx = self
+            .context_builder(&ctx)
+            .guild_id(guild_id)
+            .message(&msg)
+            .build()
+            .unwrap();
+
+        let command = match mentioned {
+            Some(command) => command,
+            None => {
+                let prefix = match ctx.command_prefix().await {
+                    Ok(prefix) => prefix,
+                    Err(e) => {
+                        tracing::error!("failed to get command prefix: {:#}", e);
+                        None
+                    }
+                };
+                let prefix = prefix.as_deref().unwrap_or(&self.command_prefix);
+                let Some(command) = strip_command_prefix(&msg.content, prefix) else {
+                    return;
+                };
+                command
+            }
+        }
+        .trim();
+        record_names(&ctx).await;
+
+        if self.shutting_down.load(Ordering::SeqCst) {
+            tracing::info!(%command, "ignoring command during shutdown");
+            return;
+        }
+
+        if let Err(e) = ctx.handle_command(command).await {
+            tracing::error!("error in handling command: {:#}", e);
+            let _ = ctx.message(Message::HandleError(e)).await;
+        }
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            shard_id = ctx.shard_id.0,
+            guild_id = reaction.guild_id.map(|id| id.get()),
+            channel_id = reaction.channel_id.get(),
+            message_id = reaction.message_id.get(),
+            author_id = reaction.user_id.map(|id| id.get()),
+            guild = tracing::field::Empty,
+            channel = tracing::field::Empty
+        )
+    )]
+    async fn reaction_add(
+        &self,
+        ctx: serenity::client::Context,
+        reaction: serenity::model::channel::Reaction,
+    ) {
+        let (Some(guild_id), Some(user_id)) = (reaction.guild_id, reaction.user_id) else {
+            return;
+        };
+        let is_bot = reaction.member.as_ref().is_some_and(|m| m.user.bot);
+        if is_bot || user_id == ctx.cache.current_user().id {
+            return;
+        }
+        if self.shutting_down.load(Ordering::SeqCst) {
+            return;
+        }
+
+        let ctx = self
+            .context_builder(&ctx)
+            .guild_id(guild_id)
+            .reaction(&reaction, user_id)
+            .build()
+            .unwrap();
+        record_names(&ctx).await;
+
+        if let Err(e) = ctx.handle_reaction(&reaction.emoji).await {
+            tracing::error!("error in handling reaction: {:#}", e);
+        }
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            shard_id = ctx.shard_id.0,
+            guild_id = tracing::field::Empty,
+            channel_id = tracing::field::Empty,
+            message_id = tracing::field::Empty,
+            author_id = tracing::field::Empty,
+            guild = tracing::field::Empty,
+            channel = tracing::field::Empty
+        )
+    )]
+    async fn interaction_create(&self, ctx: serenity::client::Context, interaction: Interaction) {
+        let Interaction::Component(component) = interaction else {
+            return;
+        };
+        let ComponentInteractionDataKind::StringSelect { values } = &component.data.kind else {
+            return;
+        };
+        let Some(guild_id) = component.guild_id else {
+            return;
+        };
+        let span = tracing::Span::current();
+        span.record("guild_id", guild_id.get());
+        span.record("channel_id", component.channel_id.get());
+        span.record("message_id", component.message.id.get());
+        span.record("author_id", component.user.id.get());
+
+        // the menu is edited by the use case, so just tell Discord that the choice is received
+        if let Err(e) = component
+            .create_response(&ctx.http, CreateInteractionResponse::Acknowledge)
+            .await
+        {
+            tracing::error!("cannot acknowledge the interaction: {:#}", e);
+        }
+
+        let ctx = self
+            .context_builder(&ctx)
+            .guild_id(guild_id)
+            .component(&component)
+            .build()
+            .unwrap();
+        record_names(&ctx).await;
+
+        if self.shutting_down.load(Ordering::SeqCst) {
+            tracing::info!(custom_id = %component.data.custom_id, "ignoring selection during shutdown");
+            return;
+        }
+
+        if let Err(e) = ctx
+            .handle_component(&component.data.custom_id, values)
+            .await
+        {
+            tracing::error!("error in handling selection: {:#}", e);
+            let _ = ctx.message(Message::HandleError(e)).await;
+        }
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(shard_id = ctx.shard_id.0, guild = tracing::field::Empty)
+    )]
+    async fn voice_state_update(
+        &self,
+        ctx: serenity::client::Context,
+        old: Option<VoiceState>,
+        new: VoiceState,
+    ) {
+        let now = chrono::Utc::now();
+        let joined_at = match new.guild_id {
+            Some(guild_id) => {
+                self.presence
+                    .update(guild_id, new.user_id, new.channel_id, now)
+                    .await
+            }
+            None => None,
+        };
+
+        let Some(left_channel_id) = old.and_then(|state| state.channel_id) else {
+            return;
+        };
+        if new.channel_id == Some(left_channel_id) || self.shutting_down.load(Ordering::SeqCst) {
+            return;
+        }
+        let Some(guild_id) = new.guild_id else {
+            return;
+        };
+
+        let voice_ctx = self
+            .context_builder(&ctx)
+            .guild_id(guild_id)
+            .voice_channel(left_channel_id, new.user_id)
+            .build()
+            .unwrap();
+        if let Ok(name) = voice_ctx.guild_name().await {
+            tracing::Span::current().record("guild", name);
+        }
+
+        if let Some(joined_at) = joined_at {
+            if let Err(e) = voice_ctx
+                .record_voice_session(new.user_id, joined_at, now)
+                .await
+            {
+                tracing::warn!("failed to record voice session: {:#}", e);
+            }
+        }
+
+        if let Err(e) = voice_ctx.auto_kaisan(left_channel_id).await {
+            tracing::error!("error in automatic kaisan: {:#}", e);
+        }
+
+        for (id, schedule) in self.registry.schedules(guild_id).await {
+            if schedule.author_id != new.user_id
+                || schedule.voice_channel_id != left_channel_id
+                || schedule.random_until.is_none()
+            {
+                continue;
+            }
+
+            let schedule_ctx = self
+                .context_builder(&ctx)
+                .guild_id(guild_id)
+                .schedule(&schedule)
+                .build()
+                .unwrap();
+
+            if let Err(e) = schedule_ctx.author_left(id).await {
+                tracing::error!(?id, "error in handling the author leaving: {:#}", e);
+            }
+        }
+    }
+
+    #[tracing::instrument(skip_all, fields(shard_id = ctx.shard_id.0))]
+    async fn cache_ready(
+        &self,
+        ctx: serenity::client::Context,
+        guild_ids: Vec<serenity::model::id::GuildId>,
+    ) {
+        tracing::info!(?guild_ids, "cache is ready");
+
+        for guild_id in guild_ids {
+            let schedules = match ScheduleRegistry::take_persisted(&self.database, guild_id).await {
+                Ok(schedules) => schedules,
+                Err(e) => {
+                    tracing::error!(%guild_id, "cannot load persisted schedules: {:#}", e);
+                    continue;
+                }
+            };
+
+            for schedule in schedules {
+                let ctx = self
+                    .context_builder(&ctx)
+                    .guild_id(guild_id)
+                    .schedule(&schedule)
+                    .build()
+                    .unwrap();
+
+                if let Err(e) = ctx.restore_schedule(schedule).await {
+                    tracing::error!(%guild_id, "cannot restore schedule: {:#}", e);
+                }
+            }
+        }
+    }
+}
+
+/// Registers an event handler given to [`BotBuilder::event_handler`] to the client.
+type AddEventHandler = Arc<dyn Fn(ClientBuilder) -> ClientBuilder + Send + Sync>;
+
+#[derive(Clone)]
+pub struct BotBuilder {
+    token: String,
+    command_prefix: String,
+    intents: GatewayIntents,
+    database: Option<AnyDatabaseHandle>,
+    dispatcher: Dispatcher,
+    records_disconnects: bool,
+    late_grace: chrono::Duration,
+    owners: HashSet<UserId>,
+    http_server: Option<(SocketAddr, CancelLinks)>,
+    voice: Option<VoiceAnnouncer>,
+    log_filter: Option<LogFilterHandle>,
+    event_handlers: Vec<AddEventHandler>,
+}
+
+impl BotBuilder {
+    pub fn new(token: impl Into<String>) -> Self {
+        BotBuilder {
+            token: token.into(),
+            command_prefix: DEFAULT_COMMAND_PREFIX.to_owned(),
+            intents: DEFAULT_INTENTS,
+            database: None,
+            dispatcher: Dispatcher::default(),
+            records_disconnects: false,
+            late_grace: chrono::Duration::seconds(DEFAULT_LATE_GRACE_SECONDS),
+            owners: HashSet::new(),
+            http_server: None,
+            voice: None,
+            log_filter: None,
+            event_handlers: Vec::new(),
+        }
+    }
+
+    /// Prefix of the commands in the guilds that have not set their own.
+    pub fn command_prefix(&mut self, command_prefix: impl Into<String>) -> &mut Self {
+        self.command_prefix = command_prefix.into();
+        self
+    }
+
+    /// Gateway intents to connect with. The bot does not work without [`DEFAULT_INTENTS`], so
+    /// add to them rather than replace them.
+    pub fn intents(&mut self, intents: GatewayIntents) -> &mut Self {
+        self.intents = intents;
+        self
+    }
+
+    pub fn database(&mut self, database: impl Into<AnyDatabaseHandle>) -> &mut Self {
+        self.database = Some(database.into());
+        self
+    }
+
+    /// Limits how much scheduled work runs at once.
+    pub fn dispatcher(&mut self, dispatcher: Dispatcher) -> &mut Self {
+        self.dispatcher = dispatcher;
+        self
+    }
+
+    pub fn records_disconnects(&mut self, records_disconnects: bool) -> &mut Self {
+        self.records_disconnects = records_disconnects;
+        self
+    }
+
+    /// Scheduled work later than this is skipped.
+    pub fn late_grace(&mut self, late_grace: chrono::Duration) -> &mut Self {
+        self.late_grace = late_grace;
+        self
+    }
+
+    /// Users allowed to run the commands for the operators of the bot.
+    pub fn owners(&mut self, owners: impl IntoIterator<Item = UserId>) -> &mut Self {
+        self.owners = owners.into_iter().collect();
+        self
+    }
+
+    /// Serves the pages to cancel schedules at `addr`, and sends the links to them.
+    pub fn http_server(&mut self, addr: SocketAddr, links: CancelLinks) -> &mut Self {
+        self.http_server = Some((addr, links));
+        self
+    }
+
+    /// Plays the announcement in the voice channel before the kaisan.
+    pub fn voice(&mut self, voice: Option<VoiceAnnouncer>) -> &mut Self {
+        self.voice = voice;
+        self
+    }
+
+    /// Lets the owners change the filter of the logs with `admin log-level`.
+    pub fn log_filter(&mut self, log_filter: Option<LogFilterHandle>) -> &mut Self {
+        self.log_filter = log_filter;
+        self
+    }
+
+    /// Adds a handler that receives the Discord events along with the bot.
+    pub fn event_handler<H: EventHandler + 'static>(&mut self, handler: H) -> &mut Self {
+        let handler = Arc::new(handler);
+        self.event_handlers.push(Arc::new(move |builder| {
+            builder.event_handler_arc(Arc::clone(&handler))
+        }));
+        self
+    }
+
+    pub async fn build(&self) -> Result<Bot> {
+        let database = self.database.clone().context("no database is given")?;
+        let registry = ScheduleRegistry::with_dispatcher(self.dispatcher.clone());
+        let shutting_down = Arc::new(AtomicBool::new(false));
+        let presence = PresenceTracker::new();
+        let command_prefixes = CommandPrefixes::default();
+        let cancel_links = self.http_server.as_ref().map(|(_, links)| links.clone());
+        let owners = Arc::new(self.owners.clone());
+        let shard_manager = Arc::new(OnceLock::new());
+
+        let builder = Client::builder(&self.token, self.intents);
+        #[cfg(feature = "voice")]
+        let builder = match &self.voice {
+            Some(voice) => voice.register(builder),
+            None => builder,
+        };
+        let builder = self
+            .event_handlers
+            .iter()
+            .fold(builder, |builder, handler| handler(builder));
+        let client = builder
+            .event_handler(Handler {
+                command_prefix: self.command_prefix.clone(),
+                database: database.clone(),
+                registry: registry.clone(),
+                presence: presence.clone(),
+                command_prefixes: command_prefixes.clone(),
+                cancel_links: cancel_links.clone(),
+                voice: self.voice.clone(),
+                log_filter: self.log_filter.clone(),
+                shard_manager: Arc::clone(&shard_manager),
+                records_disconnects: self.records_disconnects,
+                late_grace: self.late_grace,
+                owners: Arc::clone(&owners),
+                shutting_down: Arc::clone(&shutting_down),
+            })
+            .await
+            .context("Failed to create client")?;
+        let _ = shard_manager.set(Arc::clone(&client.shard_manager));
+
+        let http_server = match &self.http_server {
+            Some((addr, links)) => {
+                let bot_id = client
+                    .http
+                    .get_current_user()
+                    .await
+                    .context("cannot obtain the bot user")?
+                    .id;
+                let mut builder = ContextBuilder::with_http(
+                    Arc::clone(&client.http),
+                    Arc::clone(&client.cache),
+                    bot_id,
+                );
+                builder
+                    .database(database.clone())
+                    .registry(registry.clone())
+                    .presence(presence)
+                    .command_prefixes(command_prefixes)
+                    .cancel_links(cancel_links)
+                    .voice(self.voice.clone())
+                    .log_filter(self.log_filter.clone())
+                    .shard_manager(Some(Arc::clone(&client.shard_manager)))
+                    .records_disconnects(self.records_disconnects)
+                    .late_grace(self.late_grace)
+                    .owners(owners);
+                Some((*addr, links.clone(), builder))
+            }
+            None => None,
+        };
+
+        Ok(Bot {
+            client,
+            database,
+            registry,
+            shutting_down,
+            http_server,
+        })
+    }
+}
+
+/// The bot connected to Discord, ready to start.
+pub struct Bot {
+    client: Client,
+    database: AnyDatabaseHandle,
+    registry: ScheduleRegistry,
+    shutting_down: Arc<AtomicBool>,
+    http_server: Option<(SocketAddr, CancelLinks, ContextBuilder)>,
+}
+
+impl Bot {
+    pub fn builder(token: impl Into<String>) -> BotBuilder {
+        BotBuilder::new(token)
+    }
+
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    pub fn registry(&self) -> &ScheduleRegistry {
+        &self.registry
+    }
+
+    /// Runs the bot with the given number of shards, or the number recommended by Discord, until
+    /// `shutdown` completes. The pending schedules are saved on shutdown, to be restored on the
+    /// next start.
+    pub async fn run<F>(mut self, shards: Option<u32>, shutdown: F) -> Result<()>
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        if let Some((addr, links, builder)) = self.http_server.take() {
+            let registry = self.registry.clone();
+            tokio::spawn(async move {
+                if let Err(e) = web::serve(addr, links, builder, registry).await {
+                    tracing::error!("http server stopped: {:#}", e);
+                }
+            });
+        }
+
+        tokio::spawn(activity::update_activity(
+            Arc::clone(&self.client.shard_manager),
+            self.registry.clone(),
+            self.database.clone(),
+        ));
+
+        let shard_manager = Arc::clone(&self.client.shard_manager);
+        let (database, registry) = (self.database.clone(), self.registry.clone());
+        let shutting_down = Arc::clone(&self.shutting_down);
+        tokio::spawn(async move {
+            shutdown.await;
+            tracing::info!("shutting down");
+            shutting_down.store(true, Ordering::SeqCst);
+            if let Err(e) = registry.persist(&database).await {
+                tracing::error!("cannot persist schedules: {:#}", e);
+            }
+            shard_manager.shutdown_all().await;
+        });
+
+        match shards {
+            Some(shards) => self.client.start_shards(shards).await,
+            None => self.client.start_autosharded().await,
+        }
+        .context("Client error")
+    }
+}
@@ -0,0 +1,244 @@
+//! The configuration file given with `--config`, which holds the same settings as the command line
+//! options. The options and the environment variables take precedence over the file.
+
+use std::fmt::{self, Display};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DatabaseKind {
+    Redis,
+    Memory,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RedisConfig {
+    pub uri: Option<String>,
+    pub cluster_nodes: Option<Vec<String>>,
+    pub password_file: Option<PathBuf>,
+    pub prefix: Option<String>,
+    pub connections_per_shard: Option<usize>,
+    pub max_attempts: Option<u32>,
+    pub retry_backoff_ms: Option<u64>,
+    pub cache_ttl_seconds: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DispatcherConfig {
+    pub max_running: Option<usize>,
+    pub max_running_per_guild: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HttpConfig {
+    pub listen: SocketAddr,
+    pub public_url: String,
+    pub link_secret: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    pub command_prefix: Option<String>,
+    pub token: Option<String>,
+    pub token_file: Option<PathBuf>,
+    pub database: Option<DatabaseKind>,
+    #[serde(default)]
+    pub redis: RedisConfig,
+    pub shards: Option<u32>,
+    pub record_disconnects: Option<bool>,
+    #[serde(default)]
+    pub dispatcher: DispatcherConfig,
+    pub late_grace_seconds: Option<i64>,
+    pub http: Option<HttpConfig>,
+    pub voice_announcement: Option<PathBuf>,
+    pub owners: Option<Vec<u64>>,
+    pub log_level: Option<String>,
+}
+
+/// A value in the file that cannot be used, along with the key it is at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError {
+    pub key: &'static str,
+    pub message: &'static str,
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid `{}`: {}", self.key, self.message)
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+fn ensure(ok: bool, key: &'static str, message: &'static str) -> Result<(), ConfigError> {
+    if ok {
+        Ok(())
+    } else {
+        Err(ConfigError { key, message })
+    }
+}
+
+impl Config {
+    pub fn load(path: &Path) -> anyhow::Result<Config> {
+        use anyhow::Context as _;
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("cannot read {}", path.display()))?;
+        let config = Config::parse(&content).with_context(|| format!("in {}", path.display()))?;
+        Ok(config)
+    }
+
+    /// Parses and validates the file. Unknown keys are rejected, so that a typo does not go
+    /// unnoticed.
+    pub fn parse(content: &str) -> anyhow::Result<Config> {
+        let config: Config = toml::from_str(content)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        ensure(
+            !(self.token.is_some() && self.token_file.is_some()),
+            "token",
+            "cannot be given together with `token_file`",
+        )?;
+        if let Some(prefix) = &self.command_prefix {
+            ensure(
+                !prefix.trim().is_empty(),
+                "command_prefix",
+                "must not be empty",
+            )?;
+        }
+        if let Some(shards) = self.shards {
+            ensure(shards > 0, "shards", "must be at least 1")?;
+        }
+        ensure(
+            !(self.redis.uri.is_some() && self.redis.cluster_nodes.is_some()),
+            "redis.uri",
+            "cannot be given together with `redis.cluster_nodes`",
+        )?;
+        if let Some(nodes) = &self.redis.cluster_nodes {
+            ensure(
+                !nodes.is_empty(),
+                "redis.cluster_nodes",
+                "must not be empty",
+            )?;
+        }
+        if let Some(per_shard) = self.redis.connections_per_shard {
+            ensure(
+                per_shard > 0,
+                "redis.connections_per_shard",
+                "must be at least 1",
+            )?;
+        }
+        if let Some(max_attempts) = self.redis.max_attempts {
+            ensure(max_attempts > 0, "redis.max_attempts", "must be at least 1")?;
+        }
+        if let Some(max_running) = self.dispatcher.max_running {
+            ensure(
+                max_running > 0,
+                "dispatcher.max_running",
+                "must be at least 1",
+            )?;
+        }
+        if let Some(per_guild) = self.dispatcher.max_running_per_guild {
+            ensure(
+                per_guild > 0,
+                "dispatcher.max_running_per_guild",
+                "must be at least 1",
+            )?;
+        }
+        if let Some(late_grace) = self.late_grace_seconds {
+            ensure(
+                late_grace >= 0,
+                "late_grace_seconds",
+                "must not be negative",
+            )?;
+        }
+        if let Some(http) = &self.http {
+            ensure(
+                http.public_url.starts_with("http://") || http.public_url.starts_with("https://"),
+                "http.public_url",
+                "must be an http or https URL",
+            )?;
+            ensure(
+                !http.link_secret.is_empty(),
+                "http.link_secret",
+                "must not be empty",
+            )?;
+        }
+        if let Some(owners) = &self.owners {
+            ensure(!owners.contains(&0), "owners", "must be user IDs")?;
+        }
+        if let Some(level) = &self.log_level {
+            ensure(
+                level
+                    .parse::<tracing_subscriber::filter::LevelFilter>()
+                    .is_ok(),
+                "log_level",
+                "must be one of off, error, warn, info, debug and trace",
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Config, DatabaseKind};
+
+    #[test]
+    fn test_parse() {
+        let config = Config::parse(
+            r#"
+            command_prefix = "!kaisan"
+            token_file = "/run/secrets/discord-token"
+            database = "redis"
+            owners = [1234]
+
+            [redis]
+            uri = "redis://localhost"
+            connections_per_shard = 4
+
+            [http]
+            listen = "0.0.0.0:8080"
+            public_url = "https://kaisan.example.com"
+            link_secret = "secret"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.database, Some(DatabaseKind::Redis));
+        assert_eq!(config.redis.uri.as_deref(), Some("redis://localhost"));
+        assert_eq!(config.redis.connections_per_shard, Some(4));
+        assert_eq!(config.http.unwrap().listen.port(), 8080);
+        assert_eq!(config.owners, Some(vec![1234]));
+        assert_eq!(config.shards, None);
+
+        assert_eq!(Config::parse("").unwrap(), Config::default());
+    }
+
+    #[test]
+    fn test_errors_point_to_key() {
+        let error = |content| format!("{:#}", Config::parse(content).unwrap_err());
+        assert!(error("[redis]\nurl = \"redis://localhost\"").contains("url"));
+        assert!(error("shards = \"two\"").contains("shards"));
+        assert!(error("[dispatcher]\nmax_running = 0").contains("`dispatcher.max_running`"));
+        assert!(error("[redis]\nmax_attempts = 0").contains("`redis.max_attempts`"));
+        assert!(error("[redis]\ncluster_nodes = []").contains("`redis.cluster_nodes`"));
+        assert!(
+            error("[redis]\nuri = \"redis://a\"\ncluster_nodes = [\"redis://b\"]")
+                .contains("`redis.uri`")
+        );
+        assert!(error("log_level = \"loud\"").contains("`log_level`"));
+        assert!(error(
+            "[http]\nlisten = \"0.0.0.0:8080\"\npublic_url = \"kaisan.example.com\"\nlink_secret = \"s\""
+        )
+        .contains("`http.public_url`"));
+    }
+}
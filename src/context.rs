@@ -1,45 +1,99 @@
-use std::collections::{HashMap, HashSet};
-use std::hash::Hash;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
+use crate::database::{
+    AnyDatabaseHandle, CacheStats, DatabaseHandle, Entry, Read, RetryStats, StorageUsage, Write,
+};
+use crate::dispatcher::{DispatchPermit, DispatchStats};
 use crate::error::{Error, Result};
-use crate::model::{command::Command, reminder::Reminder};
-use crate::say::SayExt;
+use crate::log::LogFilterHandle;
+use crate::model::{
+    cancel_link::CancelLink,
+    command::Command,
+    event::DisconnectEvent,
+    hint::ParseHint,
+    member::{self, MemberNames},
+    menu::{MenuSelection, SelectMenu},
+    notification::Notification,
+    reaction::{Outcome, ScheduleReaction},
+    reminder::Reminder,
+    schedule::{FrozenSchedule, Schedule, ScheduleId},
+    setting::{
+        stored_timezone, AuthorLeavePolicy, ChannelOverride, ChannelOverrides, DstPolicy,
+        DuplicatePolicy, Locale, RandomDistribution, RevealRandom, Setting, SettingPatch,
+        SettingsSnapshot, WeekStart, DEFAULT_MAX_HORIZON_HOURS, DEFAULT_MAX_SCHEDULES_PER_USER,
+        DEFAULT_TONIGHT_HOUR, DEFAULT_UNDO_WINDOW_MINUTES,
+    },
+    template::ReminderTemplate,
+    time::Hour,
+};
+use crate::prefix::CommandPrefixes;
+use crate::presence::PresenceTracker;
+use crate::registry::ScheduleRegistry;
+use crate::scheduler::{Scheduler as _, SystemScheduler};
+use crate::sink::{NotificationSink, WebhookSink};
+use crate::soak::SoakMonitor;
 use crate::use_case;
+use crate::voice::VoiceAnnouncer;
+use crate::web::CancelLinks;
 
 use anyhow::Context as _;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use chrono_tz::Tz;
 use futures::lock::Mutex;
 use rand::{rngs::SmallRng, Rng, SeedableRng};
-use redis::{AsyncCommands, FromRedisValue, ToRedisArgs};
 use serenity::{
-    builder::EditMember,
+    builder::{
+        CreateActionRow, CreateAllowedMentions, CreateAttachment, CreateMessage, CreateSelectMenu,
+        CreateSelectMenuKind, CreateSelectMenuOption, EditMember, EditMessage,
+    },
     cache::Cache,
+    gateway::ShardManager,
     http::Http,
     model::{
-        channel::{Message, ReactionType},
-        id::{ChannelId, GuildId, MessageId, UserId},
+        application::ComponentInteraction,
+        channel::{Attachment, ChannelType, Message, Reaction, ReactionType},
+        guild::Member,
+        id::{ChannelId, EmojiId, GuildId, MessageId, RoleId, ShardId, UserId},
         permissions::Permissions,
         voice::VoiceState,
     },
 };
+use tokio::task::AbortHandle;
+use tracing::Instrument as _;
 
 mod bot;
 mod channel;
+mod event;
 mod guild;
+mod health;
+mod link;
 mod message;
+mod notification;
+mod owner;
+mod presence;
 mod random;
+mod schedule;
 mod setting;
 mod time;
+mod voice;
 
 pub use bot::BotContext;
 pub use channel::ChannelContext;
+pub use event::EventContext;
 pub use guild::GuildContext;
+pub use health::HealthContext;
+pub use link::LinkContext;
 pub use message::MessageContext;
+pub use notification::NotificationContext;
+pub use owner::OwnerContext;
+pub use presence::PresenceContext;
 pub use random::RandomContext;
+pub use schedule::ScheduleContext;
 pub use setting::SettingContext;
-pub use time::TimeContext;
+pub use time::{TimeContext, DEFAULT_LATE_GRACE_SECONDS};
+pub use voice::VoiceContext;
 
 #[derive(Clone)]
 pub struct Context {
@@ -49,13 +103,44 @@ pub struct Context {
     guild_id: GuildId,
     author_id: UserId,
     channel_id: ChannelId,
-    message_id: MessageId,
-    redis_prefix: String,
-    redis: Arc<Mutex<deadpool_redis::Connection>>,
+    message_id: Option<MessageId>,
+    attachments: Vec<Attachment>,
+    database: AnyDatabaseHandle,
+    registry: ScheduleRegistry,
+    presence: PresenceTracker,
+    command_prefixes: CommandPrefixes,
+    cancel_links: Option<CancelLinks>,
+    voice: Option<VoiceAnnouncer>,
+    log_filter: Option<LogFilterHandle>,
+    shard_id: ShardId,
+    shard_manager: Option<Arc<ShardManager>>,
+    records_disconnects: bool,
+    late_grace: chrono::Duration,
     rng: Arc<Mutex<SmallRng>>,
+    owners: Arc<HashSet<UserId>>,
+    guild_name: Arc<Mutex<Option<String>>>,
+    channel_name: Arc<Mutex<Option<String>>>,
 }
 
 impl Context {
+    async fn react_outcome(&self, outcome: Outcome) -> Result<()> {
+        let (reaction, phrase) = futures::try_join!(self.reaction(outcome), self.phrase(outcome))?;
+        let default = outcome.default_reaction();
+        if let Err(e) = self.react(reaction.clone()).await {
+            if reaction == default {
+                return Err(e);
+            }
+            // the custom emoji may have been deleted since it was set
+            tracing::warn!(error = %e, %reaction, "cannot react with the reaction of the guild");
+            self.react(default).await?;
+        }
+        if let Some(phrase) = phrase {
+            self.message(crate::model::message::Message::Phrase(phrase))
+                .await?;
+        }
+        Ok(())
+    }
+
     async fn voice_states(&self) -> Result<HashMap<UserId, VoiceState>> {
         Ok(self
             .cache
@@ -65,85 +150,36 @@ impl Context {
             .clone())
     }
 
-    fn redis_key(&self, key: &str) -> String {
-        format!("{}:{}:{}", self.redis_prefix, u64::from(self.guild_id), key)
-    }
-
-    async fn redis_get<T: FromRedisValue>(&self, key: &str) -> Result<Option<T>> {
-        let r = self
-            .redis
-            .lock()
-            .await
-            .get(self.redis_key(key))
-            .await
-            .context("cannot read from redis")?;
-        Ok(r)
-    }
-
-    async fn redis_set<T: ToRedisArgs + Send + Sync>(&self, key: &str, value: T) -> Result<()> {
-        self.redis
-            .lock()
-            .await
-            .set(self.redis_key(key), value)
-            .await
-            .context("cannot write to redis")?;
-        Ok(())
-    }
-
-    async fn redis_set_members<T: Eq + Hash + FromRedisValue>(
-        &self,
-        key: &str,
-    ) -> Result<HashSet<T>> {
-        let r = self
-            .redis
-            .lock()
-            .await
-            .smembers(self.redis_key(key))
-            .await
-            .context("cannot read from redis")?;
-        Ok(r)
-    }
-
-    async fn redis_set_add<T: ToRedisArgs + Send + Sync>(
-        &self,
-        key: &str,
-        value: T,
-    ) -> Result<bool> {
-        let n: i32 = self
-            .redis
-            .lock()
-            .await
-            .sadd(self.redis_key(key), value)
-            .await
-            .context("cannot write to redis")?;
-        Ok(n != 0)
-    }
-
-    async fn redis_set_remove<T: ToRedisArgs + Send + Sync>(
-        &self,
-        key: &str,
-        value: T,
-    ) -> Result<bool> {
-        let n: i32 = self
-            .redis
-            .lock()
-            .await
-            .srem(self.redis_key(key), value)
-            .await
-            .context("cannot write to redis")?;
-        Ok(n != 0)
-    }
-
-    async fn redis_flag_get(&self, key: &str, default: bool) -> Result<bool> {
-        Ok(match self.redis_get::<u32>(key).await? {
-            None => default,
-            Some(r) => r != 0,
-        })
+    /// Every message goes through here, so this also keeps it in the log in its structured form.
+    /// It is logged at the debug level, as it carries the mentions and the content.
+    async fn render(&self, message: &crate::model::message::Message) -> String {
+        tracing::debug!(
+            target: "kaisantantoudaijin::message",
+            guild_id = %self.guild_id,
+            channel_id = %self.channel_id,
+            message = %message.to_json(),
+            "message"
+        );
+        // errors are reported as messages too, so fall back rather than fail here
+        let locale = SettingContext::locale(self).await.unwrap_or_else(|e| {
+            tracing::warn!(error = %e, "cannot obtain locale");
+            Locale::default()
+        });
+        message.render(locale)
     }
+}
 
-    async fn redis_flag_set(&self, key: &str, flag: bool) -> Result<()> {
-        self.redis_set(key, flag as u32).await
-    }
+fn action_rows(menu: Option<SelectMenu>) -> Vec<CreateActionRow> {
+    let Some(menu) = menu else {
+        return Vec::new();
+    };
+    let options = menu
+        .options
+        .into_iter()
+        .map(|option| CreateSelectMenuOption::new(option.label, option.value))
+        .collect();
+    let menu = CreateSelectMenu::new(menu.custom_id, CreateSelectMenuKind::String { options });
+    vec![CreateActionRow::SelectMenu(menu)]
 }
 
 impl BotContext for Context {
@@ -154,6 +190,33 @@ impl BotContext for Context {
 
 #[async_trait::async_trait]
 impl GuildContext for Context {
+    fn guild_id(&self) -> GuildId {
+        self.guild_id
+    }
+
+    #[tracing::instrument(level = "debug", skip_all)]
+    async fn guild_name(&self) -> Result<String> {
+        let mut name = self.guild_name.lock().await;
+        if let Some(name) = &*name {
+            return Ok(name.clone());
+        }
+
+        let cached = self.cache.guild(self.guild_id).map(|g| g.name.clone());
+        let fetched = match cached {
+            Some(fetched) => fetched,
+            None => {
+                self.guild_id
+                    .to_partial_guild(&self.http)
+                    .await
+                    .context("cannot obtain guild")?
+                    .name
+            }
+        };
+        *name = Some(fetched.clone());
+        Ok(fetched)
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
     async fn member_permissions(&self, user_id: UserId) -> Result<Permissions> {
         let member = self
             .guild_id
@@ -166,6 +229,16 @@ impl GuildContext for Context {
         }
     }
 
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn member_roles(&self, user_id: UserId) -> Result<Vec<RoleId>> {
+        let member = self
+            .guild_id
+            .member((&self.cache, &*self.http), user_id)
+            .await
+            .context("cannot obtain member")?;
+        Ok(member.roles)
+    }
+
     async fn connected_voice_channel(&self, user_id: UserId) -> Result<Option<ChannelId>> {
         let voice_states = self.voice_states().await?;
 
@@ -183,7 +256,8 @@ impl GuildContext for Context {
 
         let mut users = Vec::new();
         for (user_id, state) in &voice_states {
-            if state.channel_id == Some(channel_id) {
+            // the bot itself is there while playing the announcement
+            if state.channel_id == Some(channel_id) && *user_id != self.bot_id {
                 users.push(*user_id);
             }
         }
@@ -191,6 +265,7 @@ impl GuildContext for Context {
         Ok(users)
     }
 
+    #[tracing::instrument(level = "debug", skip(self))]
     async fn disconnect_user(&self, user_id: UserId) -> Result<()> {
         let builder = EditMember::new().disconnect_member();
         self.guild_id
@@ -199,6 +274,82 @@ impl GuildContext for Context {
             .context("cannot edit member for disconnection")?;
         Ok(())
     }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn has_emoji(&self, emoji_id: EmojiId) -> Result<bool> {
+        let cached = self
+            .cache
+            .guild(self.guild_id)
+            .map(|g| g.emojis.contains_key(&emoji_id));
+        if let Some(cached) = cached {
+            return Ok(cached);
+        }
+        let emojis = self
+            .guild_id
+            .emojis(&self.http)
+            .await
+            .context("cannot obtain emojis")?;
+        Ok(emojis.iter().any(|emoji| emoji.id == emoji_id))
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn can_post_to(&self, channel_id: ChannelId) -> Result<bool> {
+        let member = self
+            .guild_id
+            .member((&self.cache, &*self.http), self.bot_id)
+            .await
+            .context("cannot obtain member")?;
+        let Some(guild) = self.cache.guild(self.guild_id) else {
+            return Err(Error::InaccessibleGuild);
+        };
+        // only the channels of this guild, not any channel the bot happens to be in
+        let Some(channel) = guild.channels.get(&channel_id) else {
+            return Ok(false);
+        };
+        if !matches!(channel.kind, ChannelType::Text | ChannelType::News) {
+            return Ok(false);
+        }
+        Ok(guild.user_permissions_in(channel, &member).send_messages())
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn find_member_by_name(&self, name: &str) -> Result<Vec<MemberNames>> {
+        fn names(m: &Member) -> MemberNames {
+            MemberNames {
+                user_id: m.user.id,
+                username: m.user.name.clone(),
+                global_name: m.user.global_name.clone(),
+                nick: m.nick.clone(),
+            }
+        }
+        fn best(name: &str, members: &[MemberNames]) -> Vec<MemberNames> {
+            member::find_by_name(name, members)
+                .into_iter()
+                .cloned()
+                .collect()
+        }
+
+        // without the members intent, the cache only has members who have been around lately
+        let cached: Vec<_> = self
+            .cache
+            .guild(self.guild_id)
+            .map(|g| g.members.values().map(names).collect())
+            .unwrap_or_default();
+        // a looser match in the cache must not win over an exact match on a member not cached
+        let exact = member::exact_matches(name, &cached);
+        if !exact.is_empty() {
+            return Ok(exact.into_iter().cloned().collect());
+        }
+        let searched: Vec<_> = self
+            .guild_id
+            .search_members(&self.http, name, Some(100))
+            .await
+            .context("cannot search members")?
+            .iter()
+            .map(names)
+            .collect();
+        Ok(best(name, &member::merge(cached, searched)))
+    }
 }
 
 #[async_trait::async_trait]
@@ -207,11 +358,156 @@ impl ChannelContext for Context {
         self.channel_id
     }
 
+    #[tracing::instrument(level = "debug", skip_all)]
+    async fn channel_name(&self) -> Result<String> {
+        let mut name = self.channel_name.lock().await;
+        if let Some(name) = &*name {
+            return Ok(name.clone());
+        }
+
+        let cached = self
+            .cache
+            .guild(self.guild_id)
+            .and_then(|g| g.channels.get(&self.channel_id).map(|c| c.name.clone()));
+        let fetched = match cached {
+            Some(fetched) => fetched,
+            None => {
+                self.channel_id
+                    .to_channel(&self.http)
+                    .await
+                    .context("cannot obtain channel")?
+                    .guild()
+                    .context("not a guild channel")?
+                    .name
+            }
+        };
+        *name = Some(fetched.clone());
+        Ok(fetched)
+    }
+
+    #[tracing::instrument(level = "debug", skip_all)]
     async fn message(&self, message: crate::model::message::Message) -> Result<()> {
-        let message = message.display_say();
+        let message = self.render(&message).await;
         tracing::debug!(%message, "send message");
         self.channel_id
-            .say(&self.http, message.to_string())
+            .say(&self.http, message)
+            .await
+            .context("cannot create a message")?;
+        Ok(())
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, fields(%channel_id))]
+    async fn message_to(
+        &self,
+        channel_id: ChannelId,
+        message: crate::model::message::Message,
+    ) -> Result<()> {
+        let message = self.render(&message).await;
+        tracing::debug!(%message, %channel_id, "send message to another channel");
+        let builder = CreateMessage::new()
+            .content(message)
+            .allowed_mentions(CreateAllowedMentions::new());
+        channel_id
+            .send_message(&self.http, builder)
+            .await
+            .context("cannot create a message")?;
+        Ok(())
+    }
+
+    #[tracing::instrument(level = "debug", skip_all)]
+    async fn post_message(&self, message: crate::model::message::Message) -> Result<MessageId> {
+        let message = self.render(&message).await;
+        tracing::debug!(%message, "post message");
+        let posted = self
+            .channel_id
+            .say(&self.http, message)
+            .await
+            .context("cannot create a message")?;
+        Ok(posted.id)
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, fields(%message_id))]
+    async fn edit_message(
+        &self,
+        message_id: MessageId,
+        message: crate::model::message::Message,
+    ) -> Result<()> {
+        let message = self.render(&message).await;
+        tracing::debug!(%message, %message_id, "edit message");
+        self.channel_id
+            .edit_message(&self.http, message_id, EditMessage::new().content(message))
+            .await
+            .context("cannot edit a message")?;
+        Ok(())
+    }
+
+    #[tracing::instrument(level = "debug", skip_all)]
+    async fn post_menu(
+        &self,
+        message: crate::model::message::Message,
+        menu: SelectMenu,
+    ) -> Result<MessageId> {
+        let message = self.render(&message).await;
+        tracing::debug!(%message, menu = %menu.custom_id, "post menu");
+        let builder = CreateMessage::new()
+            .content(message)
+            .components(action_rows(Some(menu)));
+        let posted = self
+            .channel_id
+            .send_message(&self.http, builder)
+            .await
+            .context("cannot create a message")?;
+        Ok(posted.id)
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, fields(%message_id))]
+    async fn edit_menu(
+        &self,
+        message_id: MessageId,
+        message: crate::model::message::Message,
+        menu: Option<SelectMenu>,
+    ) -> Result<()> {
+        let message = self.render(&message).await;
+        tracing::debug!(%message, %message_id, "edit menu");
+        let builder = EditMessage::new()
+            .content(message)
+            .components(action_rows(menu));
+        self.channel_id
+            .edit_message(&self.http, message_id, builder)
+            .await
+            .context("cannot edit a message")?;
+        Ok(())
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, fields(%user_id))]
+    async fn direct_message(
+        &self,
+        user_id: UserId,
+        message: crate::model::message::Message,
+    ) -> Result<()> {
+        let message = self.render(&message).await;
+        tracing::debug!(%message, %user_id, "send direct message");
+        user_id
+            .direct_message(&self.http, CreateMessage::new().content(message))
+            .await
+            .context("cannot send a direct message")?;
+        Ok(())
+    }
+
+    #[tracing::instrument(level = "debug", skip_all)]
+    async fn message_with_attachment(
+        &self,
+        message: crate::model::message::Message,
+        filename: &str,
+        data: Vec<u8>,
+    ) -> Result<()> {
+        let message = self.render(&message).await;
+        tracing::debug!(%message, %filename, "send message with attachment");
+        let builder = CreateMessage::new()
+            .content(message)
+            .add_file(CreateAttachment::bytes(data, filename));
+        self.channel_id
+            .send_message(&self.http, builder)
             .await
             .context("cannot create a message")?;
         Ok(())
@@ -224,14 +520,55 @@ impl MessageContext for Context {
         self.author_id
     }
 
+    fn message_id(&self) -> Option<MessageId> {
+        self.message_id
+    }
+
+    #[tracing::instrument(level = "debug", skip_all)]
     async fn react(&self, reaction: impl Into<ReactionType> + 'async_trait + Send) -> Result<()> {
         let reaction = reaction.into();
+        let message_id = self
+            .message_id
+            .context("the context is not created from a message")?;
         self.channel_id
-            .create_reaction(&self.http, self.message_id, reaction)
+            .create_reaction(&self.http, message_id, reaction)
             .await
             .context("cannot create reaction")?;
         Ok(())
     }
+
+    async fn react_success(&self) -> Result<()> {
+        self.react_outcome(Outcome::Success).await
+    }
+
+    async fn react_failure(&self) -> Result<()> {
+        self.react_outcome(Outcome::Failure).await
+    }
+
+    async fn attachment(&self, max_bytes: u32) -> Result<Option<Vec<u8>>> {
+        let Some(attachment) = self.attachments.first() else {
+            return Ok(None);
+        };
+        // checked before downloading, not to fetch a large file only to reject it
+        if attachment.size > max_bytes {
+            return Err(Error::AttachmentTooLarge { max_bytes });
+        }
+        let is_json = attachment
+            .content_type
+            .as_deref()
+            .map_or(true, |content_type| {
+                content_type.starts_with("application/json")
+                    || content_type.starts_with("text/plain")
+            });
+        if !is_json {
+            return Err(Error::UnsupportedAttachment);
+        }
+        let data = attachment
+            .download()
+            .await
+            .context("cannot download attachment")?;
+        Ok(Some(data))
+    }
 }
 
 #[async_trait::async_trait]
@@ -244,134 +581,1461 @@ impl RandomContext for Context {
 #[async_trait::async_trait]
 impl TimeContext for Context {
     fn current_time(&self) -> DateTime<Utc> {
-        Utc::now()
+        SystemScheduler.now()
     }
 
     async fn delay_until(&self, time: DateTime<Utc>) {
-        let now = self.current_time();
-        if let Ok(duration) = (time - now).to_std() {
-            tokio::time::sleep(duration).await;
-        }
+        SystemScheduler.sleep_until(time).await;
+    }
+
+    fn late_grace(&self) -> chrono::Duration {
+        self.late_grace
     }
 }
 
 #[async_trait::async_trait]
 impl SettingContext for Context {
     async fn set_timezone(&self, timezone: Tz) -> Result<()> {
-        self.redis_set("timezone", timezone.name()).await
+        self.database
+            .set(self.guild_id, "timezone", timezone.name())
+            .await
+    }
+
+    async fn replace_timezone(&self, current: Tz, timezone: Tz) -> Result<bool> {
+        let stored = self
+            .database
+            .get::<String>(self.guild_id, "timezone")
+            .await?;
+        if stored_timezone(stored.as_deref()) != current {
+            return Ok(false);
+        }
+        self.database
+            .compare_and_set(
+                self.guild_id,
+                "timezone",
+                stored.as_deref(),
+                timezone.name(),
+            )
+            .await
     }
 
     async fn timezone(&self) -> Result<Tz> {
-        Ok(match self.redis_get::<String>("timezone").await? {
-            None => chrono_tz::Japan,
-            Some(tz_str) => tz_str.parse().unwrap(),
-        })
+        let stored = self
+            .database
+            .get::<String>(self.guild_id, "timezone")
+            .await?;
+        Ok(stored_timezone(stored.as_deref()))
+    }
+
+    async fn has_timezone(&self) -> Result<bool> {
+        Ok(self
+            .database
+            .get::<String>(self.guild_id, "timezone")
+            .await?
+            .is_some())
     }
 
     async fn set_requires_permission(&self, requires_permission: bool) -> Result<()> {
-        self.redis_flag_set("requires_permission", requires_permission)
+        self.database
+            .set_flag(self.guild_id, "requires_permission", requires_permission)
             .await
     }
 
     async fn requires_permission(&self) -> Result<bool> {
-        self.redis_flag_get("requires_permission", true).await
+        self.database
+            .get_flag(self.guild_id, "requires_permission", true)
+            .await
+    }
+
+    async fn set_requires_permission_self(&self, requires_permission_self: bool) -> Result<()> {
+        self.database
+            .set_flag(
+                self.guild_id,
+                "requires_permission_self",
+                requires_permission_self,
+            )
+            .await
+    }
+
+    async fn requires_permission_self(&self) -> Result<bool> {
+        self.database
+            .get_flag(self.guild_id, "requires_permission_self", false)
+            .await
     }
 
     async fn reminders(&self) -> Result<HashSet<Reminder>> {
-        self.redis_set_members("reminders").await
+        self.database.set_members(self.guild_id, "reminders").await
     }
 
     async fn add_reminder(&self, reminder: Reminder) -> Result<bool> {
-        self.redis_set_add("reminders", reminder).await
+        self.database
+            .set_add(self.guild_id, "reminders", reminder)
+            .await
     }
 
     async fn remove_reminder(&self, reminder: Reminder) -> Result<bool> {
-        self.redis_set_remove("reminders", reminder).await
+        self.database
+            .set_remove(self.guild_id, "reminders", reminder)
+            .await
     }
 
     async fn reminds_random_kaisan(&self) -> Result<bool> {
-        self.redis_flag_get("reminds_random_kaisan", false).await
+        self.database
+            .get_flag(self.guild_id, "reminds_random_kaisan", false)
+            .await
     }
 
     async fn set_reminds_random_kaisan(&self, reminds_random_kaisan: bool) -> Result<()> {
-        self.redis_flag_set("reminds_random_kaisan", reminds_random_kaisan)
+        self.database
+            .set_flag(
+                self.guild_id,
+                "reminds_random_kaisan",
+                reminds_random_kaisan,
+            )
             .await
     }
-}
 
-impl Context {
-    pub async fn handle_command(&self, command: &str) -> Result<()> {
-        let command = command.parse()?;
-        tracing::debug!(?command, "parsed message as command");
+    async fn auto_kaisan_hour(&self) -> Result<Option<Hour>> {
+        match self
+            .database
+            .get::<u8>(self.guild_id, "auto_kaisan_hour")
+            .await?
+        {
+            None => Ok(None),
+            Some(hour) => Ok(Some(Hour::from_u8(hour).context("invalid hour is stored")?)),
+        }
+    }
 
-        match command {
-            Command::Help => use_case::Help::help(self).await,
-            Command::ShowSetting => use_case::ShowSetting::show_setting(self).await,
-            Command::TimeZone(tz) => use_case::SetTimeZone::set_timezone(self, tz).await,
-            Command::RequirePermission(b) => {
-                use_case::SetRequiresPermission::set_requires_permission(self, b).await
+    async fn set_auto_kaisan_hour(&self, hour: Option<Hour>) -> Result<()> {
+        match hour {
+            None => {
+                self.database
+                    .delete(self.guild_id, "auto_kaisan_hour")
+                    .await
             }
-            Command::AddReminder(r) => use_case::AddReminder::add_reminder(self, r).await,
-            Command::RemoveReminder(r) => use_case::RemoveReminder::remove_reminder(self, r).await,
-            Command::RemindRandomKaisan(b) => {
-                use_case::SetRemindsRandomKaisan::set_reminds_random_kaisan(self, b).await
+            Some(hour) => {
+                self.database
+                    .set(self.guild_id, "auto_kaisan_hour", u8::from(hour))
+                    .await
             }
-            Command::Kaisan {
-                kaisanee,
-                time_range,
-            } => use_case::ScheduleKaisan::schedule_kaisan(self, kaisanee, time_range).await,
         }
     }
-}
 
-#[derive(Clone)]
-pub struct ContextBuilder {
-    http: Arc<Http>,
-    cache: Arc<Cache>,
-    bot_id: UserId,
-    guild_id: Option<GuildId>,
-    author_id: Option<UserId>,
-    channel_id: Option<ChannelId>,
-    message_id: Option<MessageId>,
-    redis_prefix: Option<String>,
-    redis_conn: Option<Arc<Mutex<deadpool_redis::Connection>>>,
-}
+    async fn max_horizon_hours(&self) -> Result<u8> {
+        Ok(self
+            .database
+            .get(self.guild_id, "max_horizon_hours")
+            .await?
+            .unwrap_or(DEFAULT_MAX_HORIZON_HOURS))
+    }
 
-impl ContextBuilder {
-    pub fn with_serenity(ctx: &serenity::client::Context) -> Self {
-        let bot_id = ctx.cache.current_user().id;
-        Self {
-            http: Arc::clone(&ctx.http),
-            cache: Arc::clone(&ctx.cache),
-            bot_id,
-            guild_id: None,
-            author_id: None,
-            channel_id: None,
-            message_id: None,
-            redis_prefix: None,
-            redis_conn: None,
+    async fn set_max_horizon_hours(&self, hours: u8) -> Result<()> {
+        self.database
+            .set(self.guild_id, "max_horizon_hours", hours)
+            .await
+    }
+
+    async fn tonight_hour(&self) -> Result<Hour> {
+        let hour = self
+            .database
+            .get(self.guild_id, "tonight_hour")
+            .await?
+            .unwrap_or(DEFAULT_TONIGHT_HOUR);
+        Ok(Hour::from_u8(hour).context("invalid hour is stored")?)
+    }
+
+    async fn set_tonight_hour(&self, hour: Hour) -> Result<()> {
+        self.database
+            .set(self.guild_id, "tonight_hour", u8::from(hour))
+            .await
+    }
+
+    async fn max_schedules_per_user(&self) -> Result<u8> {
+        Ok(self
+            .database
+            .get(self.guild_id, "max_schedules_per_user")
+            .await?
+            .unwrap_or(DEFAULT_MAX_SCHEDULES_PER_USER))
+    }
+
+    async fn set_max_schedules_per_user(&self, count: u8) -> Result<()> {
+        self.database
+            .set(self.guild_id, "max_schedules_per_user", count)
+            .await
+    }
+
+    async fn undo_window_minutes(&self) -> Result<u8> {
+        Ok(self
+            .database
+            .get(self.guild_id, "undo_window_minutes")
+            .await?
+            .unwrap_or(DEFAULT_UNDO_WINDOW_MINUTES))
+    }
+
+    async fn set_undo_window_minutes(&self, minutes: u8) -> Result<()> {
+        self.database
+            .set(self.guild_id, "undo_window_minutes", minutes)
+            .await
+    }
+
+    async fn countdown(&self) -> Result<bool> {
+        self.database
+            .get_flag(self.guild_id, "countdown", false)
+            .await
+    }
+
+    async fn set_countdown(&self, countdown: bool) -> Result<()> {
+        self.database
+            .set_flag(self.guild_id, "countdown", countdown)
+            .await
+    }
+
+    async fn author_leave_policy(&self) -> Result<AuthorLeavePolicy> {
+        match self
+            .database
+            .get::<String>(self.guild_id, "author_leave_policy")
+            .await?
+        {
+            None => Ok(AuthorLeavePolicy::default()),
+            Some(policy) => Ok(policy
+                .parse()
+                .ok()
+                .context("invalid author leave policy is stored")?),
         }
     }
 
-    pub fn redis_prefix(&mut self, prefix: String) -> &mut Self {
-        self.redis_prefix = Some(prefix);
-        self
+    async fn set_author_leave_policy(&self, policy: AuthorLeavePolicy) -> Result<()> {
+        self.database
+            .set(self.guild_id, "author_leave_policy", policy.as_str())
+            .await
     }
 
-    pub fn redis_conn(&mut self, conn: deadpool_redis::Connection) -> &mut Self {
-        self.redis_conn = Some(Arc::new(Mutex::new(conn)));
-        self
+    async fn reveal_random(&self) -> Result<RevealRandom> {
+        match self
+            .database
+            .get::<String>(self.guild_id, "reveal_random")
+            .await?
+        {
+            None => Ok(RevealRandom::default()),
+            Some(reveal_random) => Ok(reveal_random
+                .parse()
+                .ok()
+                .context("invalid reveal-random setting is stored")?),
+        }
     }
 
-    pub fn guild_id(&mut self, guild_id: GuildId) -> &mut Self {
-        self.guild_id = Some(guild_id);
-        self
+    async fn set_reveal_random(&self, reveal_random: RevealRandom) -> Result<()> {
+        self.database
+            .set(self.guild_id, "reveal_random", reveal_random.as_str())
+            .await
     }
 
-    pub fn message(&mut self, message: &Message) -> &mut Self {
-        self.author_id = Some(message.author.id);
-        self.channel_id = Some(message.channel_id);
-        self.message_id = Some(message.id);
+    async fn random_distribution(&self) -> Result<RandomDistribution> {
+        match self
+            .database
+            .get::<String>(self.guild_id, "random_distribution")
+            .await?
+        {
+            None => Ok(RandomDistribution::default()),
+            Some(distribution) => Ok(distribution
+                .parse()
+                .ok()
+                .context("invalid random distribution is stored")?),
+        }
+    }
+
+    async fn set_random_distribution(&self, distribution: RandomDistribution) -> Result<()> {
+        self.database
+            .set(self.guild_id, "random_distribution", distribution.as_str())
+            .await
+    }
+
+    async fn dst_policy(&self) -> Result<DstPolicy> {
+        match self
+            .database
+            .get::<String>(self.guild_id, "dst_policy")
+            .await?
+        {
+            None => Ok(DstPolicy::default()),
+            Some(policy) => Ok(policy
+                .parse()
+                .ok()
+                .context("invalid DST policy is stored")?),
+        }
+    }
+
+    async fn set_dst_policy(&self, policy: DstPolicy) -> Result<()> {
+        self.database
+            .set(self.guild_id, "dst_policy", policy.as_str())
+            .await
+    }
+
+    async fn week_start(&self) -> Result<WeekStart> {
+        match self
+            .database
+            .get::<String>(self.guild_id, "week_start")
+            .await?
+        {
+            None => Ok(WeekStart::default()),
+            Some(s) => Ok(s.parse().ok().context("invalid week start is stored")?),
+        }
+    }
+
+    async fn set_week_start(&self, week_start: WeekStart) -> Result<()> {
+        self.database
+            .set(self.guild_id, "week_start", week_start.as_str())
+            .await
+    }
+
+    async fn locale(&self) -> Result<Locale> {
+        match self.database.get::<String>(self.guild_id, "locale").await? {
+            None => Ok(Locale::default()),
+            Some(locale) => Ok(locale.parse().ok().context("invalid locale is stored")?),
+        }
+    }
+
+    async fn set_locale(&self, locale: Locale) -> Result<()> {
+        self.database
+            .set(self.guild_id, "locale", locale.as_str())
+            .await
+    }
+
+    async fn on_duplicate(&self) -> Result<DuplicatePolicy> {
+        match self
+            .database
+            .get::<String>(self.guild_id, "on_duplicate")
+            .await?
+        {
+            None => Ok(DuplicatePolicy::default()),
+            Some(s) => Ok(s
+                .parse()
+                .ok()
+                .context("invalid duplicate policy is stored")?),
+        }
+    }
+
+    async fn set_on_duplicate(&self, on_duplicate: DuplicatePolicy) -> Result<()> {
+        self.database
+            .set(self.guild_id, "on_duplicate", on_duplicate.as_str())
+            .await
+    }
+
+    async fn allowed_channels(&self) -> Result<HashSet<ChannelId>> {
+        let ids: HashSet<u64> = self
+            .database
+            .set_members(self.guild_id, "allowed_channels")
+            .await?;
+        Ok(ids.into_iter().map(ChannelId::new).collect())
+    }
+
+    async fn allow_channel(&self, channel_id: ChannelId) -> Result<bool> {
+        self.database
+            .set_add(self.guild_id, "allowed_channels", channel_id.get())
+            .await
+    }
+
+    async fn deny_channel(&self, channel_id: ChannelId) -> Result<bool> {
+        self.database
+            .set_remove(self.guild_id, "allowed_channels", channel_id.get())
+            .await
+    }
+
+    async fn allowed_roles(&self) -> Result<HashSet<RoleId>> {
+        let roles: HashSet<u64> = self
+            .database
+            .set_members(self.guild_id, "allowed_roles")
+            .await?;
+        Ok(roles.into_iter().map(RoleId::new).collect())
+    }
+
+    async fn allow_role(&self, role_id: RoleId) -> Result<bool> {
+        self.database
+            .set_add(self.guild_id, "allowed_roles", role_id.get())
+            .await
+    }
+
+    async fn deny_role(&self, role_id: RoleId) -> Result<bool> {
+        self.database
+            .set_remove(self.guild_id, "allowed_roles", role_id.get())
+            .await
+    }
+
+    async fn command_prefix(&self) -> Result<Option<String>> {
+        if let Some(prefix) = self.command_prefixes.get(self.guild_id) {
+            return Ok(prefix);
+        }
+        let prefix: Option<String> = self.database.get(self.guild_id, "command_prefix").await?;
+        self.command_prefixes.store(self.guild_id, prefix.clone());
+        Ok(prefix)
+    }
+
+    async fn set_command_prefix(&self, prefix: Option<String>) -> Result<()> {
+        match &prefix {
+            None => {
+                self.database
+                    .delete(self.guild_id, "command_prefix")
+                    .await?
+            }
+            Some(prefix) => {
+                self.database
+                    .set(self.guild_id, "command_prefix", prefix)
+                    .await?
+            }
+        }
+        self.command_prefixes.store(self.guild_id, prefix);
+        Ok(())
+    }
+
+    async fn reminder_template(&self) -> Result<Option<ReminderTemplate>> {
+        match self
+            .database
+            .get::<String>(self.guild_id, "reminder_text")
+            .await?
+        {
+            None => Ok(None),
+            Some(source) => Ok(Some(
+                ReminderTemplate::parse(&source).context("invalid reminder template is stored")?,
+            )),
+        }
+    }
+
+    async fn set_reminder_template(&self, template: Option<ReminderTemplate>) -> Result<()> {
+        match template {
+            None => self.database.delete(self.guild_id, "reminder_text").await,
+            Some(template) => {
+                self.database
+                    .set(self.guild_id, "reminder_text", template.as_str())
+                    .await
+            }
+        }
+    }
+
+    async fn frozen_schedules(&self) -> Result<Option<Vec<FrozenSchedule>>> {
+        match self
+            .database
+            .get::<String>(self.guild_id, "frozen_schedules")
+            .await?
+        {
+            None => Ok(None),
+            Some(data) => Ok(Some(
+                serde_json::from_str(&data).context("cannot deserialize frozen schedules")?,
+            )),
+        }
+    }
+
+    async fn set_frozen_schedules(&self, frozen: Option<Vec<FrozenSchedule>>) -> Result<()> {
+        match frozen {
+            None => {
+                self.database
+                    .delete(self.guild_id, "frozen_schedules")
+                    .await
+            }
+            Some(frozen) => {
+                let data =
+                    serde_json::to_string(&frozen).context("cannot serialize frozen schedules")?;
+                self.database
+                    .set(self.guild_id, "frozen_schedules", data)
+                    .await
+            }
+        }
+    }
+
+    async fn api_token_hash(&self) -> Result<Option<String>> {
+        self.database.get(self.guild_id, "api_token_hash").await
+    }
+
+    async fn webhook_url(&self) -> Result<Option<String>> {
+        self.database.get(self.guild_id, "webhook_url").await
+    }
+
+    async fn set_webhook_url(&self, url: Option<String>) -> Result<()> {
+        match url {
+            None => self.database.delete(self.guild_id, "webhook_url").await,
+            Some(url) => self.database.set(self.guild_id, "webhook_url", url).await,
+        }
+    }
+
+    async fn log_channel(&self) -> Result<Option<ChannelId>> {
+        let id: Option<u64> = self.database.get(self.guild_id, "log_channel").await?;
+        Ok(id.map(ChannelId::new))
+    }
+
+    async fn set_log_channel(&self, channel_id: Option<ChannelId>) -> Result<()> {
+        match channel_id {
+            None => self.database.delete(self.guild_id, "log_channel").await,
+            Some(channel_id) => {
+                self.database
+                    .set(self.guild_id, "log_channel", channel_id.get())
+                    .await
+            }
+        }
+    }
+
+    async fn reaction(&self, outcome: Outcome) -> Result<ReactionType> {
+        let key = format!("{}_reaction", outcome.name());
+        match self.database.get::<String>(self.guild_id, &key).await? {
+            None => Ok(outcome.default_reaction()),
+            Some(reaction) => Ok(reaction.parse().context("invalid reaction is stored")?),
+        }
+    }
+
+    async fn set_reaction(&self, outcome: Outcome, reaction: Option<ReactionType>) -> Result<()> {
+        let key = format!("{}_reaction", outcome.name());
+        match reaction {
+            None => self.database.delete(self.guild_id, &key).await,
+            Some(reaction) => {
+                self.database
+                    .set(self.guild_id, &key, reaction.to_string())
+                    .await
+            }
+        }
+    }
+
+    async fn phrase(&self, outcome: Outcome) -> Result<Option<String>> {
+        let key = format!("{}_phrase", outcome.name());
+        self.database.get(self.guild_id, &key).await
+    }
+
+    async fn set_phrase(&self, outcome: Outcome, phrase: Option<String>) -> Result<()> {
+        let key = format!("{}_phrase", outcome.name());
+        match phrase {
+            None => self.database.delete(self.guild_id, &key).await,
+            Some(phrase) => self.database.set(self.guild_id, &key, phrase).await,
+        }
+    }
+
+    async fn set_api_token_hash(&self, hash: Option<String>) -> Result<()> {
+        match hash {
+            None => self.database.delete(self.guild_id, "api_token_hash").await,
+            Some(hash) => {
+                self.database
+                    .set(self.guild_id, "api_token_hash", hash)
+                    .await
+            }
+        }
+    }
+
+    async fn profile_names(&self) -> Result<HashSet<String>> {
+        self.database.set_members(self.guild_id, "profiles").await
+    }
+
+    async fn profile(&self, name: &str) -> Result<Option<Setting>> {
+        let key = format!("profile_{}", name);
+        match self.database.get::<String>(self.guild_id, &key).await? {
+            None => Ok(None),
+            Some(data) => Ok(Some(
+                serde_json::from_str(&data).context("invalid profile is stored")?,
+            )),
+        }
+    }
+
+    async fn save_profile(&self, name: &str, setting: &Setting) -> Result<()> {
+        let key = format!("profile_{}", name);
+        let data = serde_json::to_string(setting).context("cannot serialize profile")?;
+        self.database.set(self.guild_id, &key, data).await?;
+        self.database
+            .set_add(self.guild_id, "profiles", name)
+            .await?;
+        Ok(())
+    }
+
+    async fn channel_overrides(&self, voice_channel_id: ChannelId) -> Result<ChannelOverrides> {
+        let keys = CHANNEL_OVERRIDE_NAMES.map(|name| channel_override_key(voice_channel_id, name));
+        let reads = keys.each_ref().map(|key| Read::Value(key));
+        let Ok([max_horizon_hours, countdown, author_leave_policy, on_duplicate]) =
+            <[_; 4]>::try_from(self.database.read_many(self.guild_id, &reads).await?)
+        else {
+            return Err(anyhow::anyhow!("unexpected number of results from the database").into());
+        };
+        Ok(ChannelOverrides {
+            max_horizon_hours: max_horizon_hours.value()?,
+            countdown: countdown.value::<u32>()?.map(|r| r != 0),
+            author_leave_policy: author_leave_policy
+                .value::<String>()?
+                .map(|policy| {
+                    policy
+                        .parse()
+                        .ok()
+                        .context("invalid author leave policy is stored")
+                })
+                .transpose()?,
+            on_duplicate: on_duplicate
+                .value::<String>()?
+                .map(|policy| {
+                    policy
+                        .parse()
+                        .ok()
+                        .context("invalid duplicate policy is stored")
+                })
+                .transpose()?,
+        })
+    }
+
+    async fn set_channel_override(
+        &self,
+        voice_channel_id: ChannelId,
+        setting: ChannelOverride,
+    ) -> Result<()> {
+        let (name, value) = match setting {
+            ChannelOverride::MaxHorizon(hours) => {
+                ("max_horizon_hours", hours.map(|h| h.to_string()))
+            }
+            ChannelOverride::Countdown(countdown) => {
+                ("countdown", countdown.map(|b| u32::from(b).to_string()))
+            }
+            ChannelOverride::AuthorLeave(policy) => {
+                ("author_leave_policy", policy.map(|p| p.as_str().to_owned()))
+            }
+            ChannelOverride::OnDuplicate(policy) => {
+                ("on_duplicate", policy.map(|p| p.as_str().to_owned()))
+            }
+        };
+        let key = channel_override_key(voice_channel_id, name);
+        match value {
+            None => self.database.delete(self.guild_id, &key).await,
+            Some(value) => self.database.set(self.guild_id, &key, value).await,
+        }
+    }
+
+    async fn settings_snapshot(&self) -> Result<SettingsSnapshot> {
+        let reads = [
+            Read::Value("timezone"),
+            Read::Value("requires_permission"),
+            Read::Value("requires_permission_self"),
+            Read::Members("reminders"),
+            Read::Value("reminds_random_kaisan"),
+        ];
+        let Ok(
+            [timezone, requires_permission, requires_permission_self, reminders, reminds_random_kaisan],
+        ) = <[_; 5]>::try_from(self.database.read_many(self.guild_id, &reads).await?)
+        else {
+            return Err(anyhow::anyhow!("unexpected number of results from the database").into());
+        };
+        // the same defaults as the getters of each
+        Ok(SettingsSnapshot {
+            timezone: stored_timezone(timezone.value::<String>()?.as_deref()),
+            requires_permission: requires_permission.flag(true)?,
+            requires_permission_self: requires_permission_self.flag(false)?,
+            reminders: reminders.members()?,
+            reminds_random_kaisan: reminds_random_kaisan.flag(false)?,
+        })
+    }
+
+    async fn overwrite_settings(&self, setting: SettingPatch) -> Result<()> {
+        // the values are encoded as the setters of each do
+        fn put_or_delete<T>(
+            key: &str,
+            value: Option<T>,
+            entry: impl FnOnce(T) -> Entry,
+        ) -> Write<'_> {
+            match value {
+                None => Write::Delete(key),
+                Some(value) => Write::Put(key, entry(value)),
+            }
+        }
+        fn members<T>(key: &str, members: BTreeSet<T>, member: impl Fn(T) -> u64) -> Write<'_> {
+            // an empty set does not exist in Redis
+            let members = Some(members).filter(|members| !members.is_empty());
+            put_or_delete(key, members, |members| {
+                Entry::set(members.into_iter().map(member))
+            })
+        }
+
+        let mut writes = Vec::new();
+        if let Some(timezone) = setting.timezone {
+            writes.push(Write::Put("timezone", Entry::value(timezone.name())));
+        }
+        if let Some(requires_permission) = setting.requires_permission {
+            writes.push(Write::Put(
+                "requires_permission",
+                Entry::flag(requires_permission),
+            ));
+        }
+        if let Some(requires_permission_self) = setting.requires_permission_self {
+            writes.push(Write::Put(
+                "requires_permission_self",
+                Entry::flag(requires_permission_self),
+            ));
+        }
+        if let Some(reminders) = setting.reminders {
+            let reminders = Some(reminders).filter(|reminders| !reminders.is_empty());
+            writes.push(put_or_delete("reminders", reminders, Entry::set));
+        }
+        if let Some(reminds_random_kaisan) = setting.reminds_random_kaisan {
+            writes.push(Write::Put(
+                "reminds_random_kaisan",
+                Entry::flag(reminds_random_kaisan),
+            ));
+        }
+        if let Some(hour) = setting.auto_kaisan_hour {
+            writes.push(put_or_delete("auto_kaisan_hour", hour, |hour| {
+                Entry::value(u8::from(hour))
+            }));
+        }
+        if let Some(hours) = setting.max_horizon_hours {
+            writes.push(Write::Put("max_horizon_hours", Entry::value(hours)));
+        }
+        if let Some(hour) = setting.tonight_hour {
+            writes.push(Write::Put("tonight_hour", Entry::value(u8::from(hour))));
+        }
+        if let Some(count) = setting.max_schedules_per_user {
+            writes.push(Write::Put("max_schedules_per_user", Entry::value(count)));
+        }
+        if let Some(minutes) = setting.undo_window_minutes {
+            writes.push(Write::Put("undo_window_minutes", Entry::value(minutes)));
+        }
+        if let Some(countdown) = setting.countdown {
+            writes.push(Write::Put("countdown", Entry::flag(countdown)));
+        }
+        if let Some(policy) = setting.author_leave_policy {
+            writes.push(Write::Put(
+                "author_leave_policy",
+                Entry::value(policy.as_str()),
+            ));
+        }
+        if let Some(reveal_random) = setting.reveal_random {
+            writes.push(Write::Put(
+                "reveal_random",
+                Entry::value(reveal_random.as_str()),
+            ));
+        }
+        if let Some(distribution) = setting.random_distribution {
+            writes.push(Write::Put(
+                "random_distribution",
+                Entry::value(distribution.as_str()),
+            ));
+        }
+        if let Some(policy) = setting.dst_policy {
+            writes.push(Write::Put("dst_policy", Entry::value(policy.as_str())));
+        }
+        if let Some(week_start) = setting.week_start {
+            writes.push(Write::Put("week_start", Entry::value(week_start.as_str())));
+        }
+        if let Some(locale) = setting.locale {
+            writes.push(Write::Put("locale", Entry::value(locale.as_str())));
+        }
+        if let Some(on_duplicate) = setting.on_duplicate {
+            writes.push(Write::Put(
+                "on_duplicate",
+                Entry::value(on_duplicate.as_str()),
+            ));
+        }
+        if let Some(channels) = setting.allowed_channels {
+            writes.push(members("allowed_channels", channels, ChannelId::get));
+        }
+        if let Some(roles) = setting.allowed_roles {
+            writes.push(members("allowed_roles", roles, RoleId::get));
+        }
+        if let Some(prefix) = &setting.command_prefix {
+            writes.push(put_or_delete(
+                "command_prefix",
+                prefix.as_deref(),
+                Entry::value,
+            ));
+        }
+        if let Some(template) = setting.reminder_text {
+            writes.push(put_or_delete("reminder_text", template, |template| {
+                Entry::value(template.as_str())
+            }));
+        }
+
+        self.database.write_many(self.guild_id, &writes).await?;
+        if let Some(prefix) = setting.command_prefix {
+            self.command_prefixes.store(self.guild_id, prefix);
+        }
+        Ok(())
+    }
+}
+
+/// The settings that can be overridden in a voice channel, in the order of [`ChannelOverrides`].
+const CHANNEL_OVERRIDE_NAMES: [&str; 4] = [
+    "max_horizon_hours",
+    "countdown",
+    "author_leave_policy",
+    "on_duplicate",
+];
+
+fn channel_override_key(voice_channel_id: ChannelId, name: &str) -> String {
+    format!("channel_{}_{}", voice_channel_id, name)
+}
+
+const DISCONNECT_EVENTS_KEY: &str = "disconnect_events";
+/// Number of the latest disconnections kept for each guild.
+pub const DISCONNECT_EVENTS_CAPACITY: usize = 1000;
+const COMPLAINED_EVENTS_KEY: &str = "complained_events";
+const COMPLAINTS_KEY: &str = "complaints";
+const COMMAND_COUNT_KEY_PREFIX: &str = "command_count";
+const NOTICES_KEY: &str = "notices";
+const VOICE_SECONDS_KEY_PREFIX: &str = "voice_seconds";
+const KAISANED_KEY_PREFIX: &str = "kaisaned";
+/// The daily statistics are read for the last week at most.
+const DAILY_STATS_EXPIRY: Duration = Duration::from_secs(8 * 24 * 60 * 60);
+
+impl Context {
+    async fn user_counts(&self, key: &str) -> Result<HashMap<UserId, u64>> {
+        let items: HashMap<String, u64> = self.database.hash_items(self.guild_id, key).await?;
+        let counts = items
+            .into_iter()
+            .map(|(id, count)| {
+                let id: u64 = id.parse().context("invalid user id")?;
+                Ok((UserId::new(id), count))
+            })
+            .collect::<Result<_>>()?;
+        Ok(counts)
+    }
+}
+
+#[async_trait::async_trait]
+impl EventContext for Context {
+    async fn record_disconnect(&self, event: DisconnectEvent) -> Result<()> {
+        if !self.records_disconnects {
+            return Ok(());
+        }
+        let data = serde_json::to_string(&event).context("cannot serialize event")?;
+        self.database
+            .list_push(
+                self.guild_id,
+                DISCONNECT_EVENTS_KEY,
+                data,
+                DISCONNECT_EVENTS_CAPACITY,
+            )
+            .await
+    }
+
+    async fn disconnect_events(&self) -> Result<Vec<DisconnectEvent>> {
+        let items: Vec<String> = self
+            .database
+            .list_items(self.guild_id, DISCONNECT_EVENTS_KEY)
+            .await?;
+        let events = items
+            .iter()
+            .map(|data| serde_json::from_str(data))
+            .collect::<std::result::Result<_, _>>()
+            .context("cannot deserialize event")?;
+        Ok(events)
+    }
+
+    async fn add_complaint(&self, event: &DisconnectEvent, since: DateTime<Utc>) -> Result<bool> {
+        let complained: HashSet<String> = self
+            .database
+            .set_members(self.guild_id, COMPLAINED_EVENTS_KEY)
+            .await?;
+        for key in complained {
+            if DisconnectEvent::key_time(&key).map_or(true, |t| t < since) {
+                self.database
+                    .set_remove(self.guild_id, COMPLAINED_EVENTS_KEY, key)
+                    .await?;
+            }
+        }
+        if !self
+            .database
+            .set_add(self.guild_id, COMPLAINED_EVENTS_KEY, event.key())
+            .await?
+        {
+            return Ok(false);
+        }
+        self.database
+            .hash_incr(
+                self.guild_id,
+                COMPLAINTS_KEY,
+                &event.requester.to_string(),
+                1,
+            )
+            .await?;
+        Ok(true)
+    }
+
+    async fn complaint_counts(&self) -> Result<HashMap<UserId, u64>> {
+        self.user_counts(COMPLAINTS_KEY).await
+    }
+
+    async fn record_command(&self, user_id: UserId, window: std::time::Duration) -> Result<u64> {
+        let key = format!("{}:{}", COMMAND_COUNT_KEY_PREFIX, user_id);
+        let count = self
+            .database
+            .incr_with_expiry(self.guild_id, &key, window)
+            .await?;
+        Ok(count.max(0) as u64)
+    }
+
+    async fn record_timezone_notice(&self) -> Result<bool> {
+        self.database
+            .set_add(self.guild_id, NOTICES_KEY, "timezone")
+            .await
+    }
+
+    async fn record_voice_time(
+        &self,
+        user_id: UserId,
+        date: NaiveDate,
+        seconds: u64,
+    ) -> Result<()> {
+        let key = format!("{}:{}", VOICE_SECONDS_KEY_PREFIX, date);
+        self.database
+            .hash_incr_with_expiry(
+                self.guild_id,
+                &key,
+                &user_id.to_string(),
+                seconds as i64,
+                DAILY_STATS_EXPIRY,
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn voice_times(&self, date: NaiveDate) -> Result<HashMap<UserId, u64>> {
+        let key = format!("{}:{}", VOICE_SECONDS_KEY_PREFIX, date);
+        self.user_counts(&key).await
+    }
+
+    async fn record_kaisaned(&self, user_id: UserId, date: NaiveDate) -> Result<()> {
+        let key = format!("{}:{}", KAISANED_KEY_PREFIX, date);
+        self.database
+            .hash_incr_with_expiry(
+                self.guild_id,
+                &key,
+                &user_id.to_string(),
+                1,
+                DAILY_STATS_EXPIRY,
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn kaisaned_counts(&self, date: NaiveDate) -> Result<HashMap<UserId, u64>> {
+        let key = format!("{}:{}", KAISANED_KEY_PREFIX, date);
+        self.user_counts(&key).await
+    }
+}
+
+impl LinkContext for Context {
+    fn cancel_url(&self, id: ScheduleId, schedule: &Schedule) -> Option<String> {
+        let links = self.cancel_links.as_ref()?;
+        Some(links.url(&CancelLink::new(self.guild_id, id, schedule)))
+    }
+}
+
+impl NotificationContext for Context {
+    fn notify(&self, notification: Notification) {
+        let ctx = self.clone();
+        let span = tracing::info_span!("notify", ?notification);
+        tokio::spawn(
+            async move {
+                let url = match ctx.webhook_url().await {
+                    Ok(Some(url)) => url,
+                    Ok(None) => return,
+                    Err(e) => {
+                        tracing::warn!(error = %e, "failed to read webhook url");
+                        return;
+                    }
+                };
+                let sink = WebhookSink::new(url);
+                if let Err(e) = sink.send(ctx.guild_id, &notification).await {
+                    tracing::warn!(error = format!("{:#}", e), "failed to send notification");
+                }
+            }
+            .instrument(span),
+        );
+    }
+}
+
+#[async_trait::async_trait]
+impl VoiceContext for Context {
+    async fn announce_in_voice(&self, voice_channel_id: ChannelId) -> Result<bool> {
+        let Some(voice) = &self.voice else {
+            return Ok(false);
+        };
+        voice
+            .announce(self.guild_id, voice_channel_id)
+            .await
+            .context("cannot play the announcement")?;
+        Ok(true)
+    }
+}
+
+#[async_trait::async_trait]
+impl PresenceContext for Context {
+    async fn joined_at(&self, user_id: UserId) -> Option<DateTime<Utc>> {
+        self.presence.joined_at(self.guild_id, user_id).await
+    }
+}
+
+#[async_trait::async_trait]
+impl ScheduleContext for Context {
+    async fn register_schedule(&self, schedule: Schedule) -> ScheduleId {
+        self.registry.register(self.guild_id, schedule).await
+    }
+
+    async fn attach_schedule_tasks(&self, id: ScheduleId, tasks: Vec<AbortHandle>) {
+        self.registry.attach_tasks(self.guild_id, id, tasks).await
+    }
+
+    async fn schedules(&self) -> Vec<(ScheduleId, Schedule)> {
+        self.registry.schedules(self.guild_id).await
+    }
+
+    async fn update_schedule(
+        &self,
+        id: ScheduleId,
+        f: impl for<'s> FnOnce(&'s mut Schedule) + Send + 'async_trait,
+    ) -> Option<Schedule> {
+        self.registry.update(self.guild_id, id, f).await
+    }
+
+    async fn take_schedule(&self, id: ScheduleId) -> Option<Schedule> {
+        self.registry.take(self.guild_id, id).await
+    }
+
+    async fn cancel_schedule(&self, id: ScheduleId) -> Option<Schedule> {
+        self.registry.cancel(self.guild_id, id).await
+    }
+
+    async fn dispatch(&self) -> DispatchPermit {
+        self.registry.dispatch(self.guild_id).await
+    }
+}
+
+#[async_trait::async_trait]
+impl HealthContext for Context {
+    async fn rest_latency(&self) -> Result<Duration> {
+        let start = Instant::now();
+        self.http
+            .get_current_user()
+            .await
+            .context("cannot reach the REST API")?;
+        Ok(start.elapsed())
+    }
+
+    async fn gateway_latency(&self) -> Option<Duration> {
+        let runners = self.shard_manager.as_ref()?.runners.lock().await;
+        runners.get(&self.shard_id)?.latency
+    }
+
+    async fn database_latency(&self) -> Result<Duration> {
+        let start = Instant::now();
+        self.database.probe(self.guild_id).await?;
+        Ok(start.elapsed())
+    }
+}
+
+#[async_trait::async_trait]
+impl OwnerContext for Context {
+    fn is_owner(&self, user_id: UserId) -> bool {
+        self.owners.contains(&user_id)
+    }
+
+    async fn all_schedules(&self) -> Vec<(GuildId, ScheduleId, Schedule)> {
+        self.registry.all_schedules().await
+    }
+
+    fn dispatch_stats(&self) -> DispatchStats {
+        self.registry.dispatch_stats()
+    }
+
+    fn soak(&self) -> SoakMonitor {
+        self.registry.soak().clone()
+    }
+
+    fn database_retry_stats(&self) -> RetryStats {
+        self.database.retry_stats()
+    }
+
+    fn database_cache_stats(&self) -> CacheStats {
+        self.database.cache_stats()
+    }
+
+    fn log_filter(&self) -> Option<LogFilterHandle> {
+        self.log_filter.clone()
+    }
+
+    async fn guild_ids(&self) -> Vec<GuildId> {
+        self.cache.guilds()
+    }
+
+    #[tracing::instrument(level = "debug", skip(self, text))]
+    async fn announce(&self, guild_id: GuildId, text: &str) -> Result<bool> {
+        // prefer the channels for commands, which the guild expects the bot to speak in
+        let allowed: HashSet<u64> = self
+            .database
+            .set_members(guild_id, "allowed_channels")
+            .await?;
+        let channel_id = match allowed.into_iter().min() {
+            Some(id) => Some(ChannelId::new(id)),
+            None => self
+                .cache
+                .guild(guild_id)
+                .and_then(|guild| guild.system_channel_id),
+        };
+        let Some(channel_id) = channel_id else {
+            return Ok(false);
+        };
+        channel_id
+            .say(&self.http, text)
+            .await
+            .context("cannot send an announcement")?;
+        Ok(true)
+    }
+
+    async fn storage_usage(&self, guild_id: GuildId) -> Result<StorageUsage> {
+        self.database.usage(guild_id).await
+    }
+}
+
+impl Context {
+    pub async fn handle_command(&self, command: &str) -> Result<()> {
+        let command = match command.parse() {
+            Ok(command) => command,
+            Err(e) => {
+                let Some(hint) = ParseHint::suggest(command) else {
+                    return Err(Error::InvalidCommand(e));
+                };
+                tracing::debug!(?hint, "suggesting a command for the unparsable input");
+                return self
+                    .message(crate::model::message::Message::ParseHint(hint))
+                    .await;
+            }
+        };
+        tracing::debug!(?command, "parsed message as command");
+        self.run_command(command).await
+    }
+
+    /// Runs the command in a span of its kind, under which the database and Discord calls and
+    /// the schedules made by the command are logged.
+    #[tracing::instrument(skip_all, fields(kind = command.kind()))]
+    async fn run_command(&self, command: Command) -> Result<()> {
+        if !use_case::CheckRateLimit::check_rate_limit(self).await? {
+            return Ok(());
+        }
+
+        // the allowlist itself can be edited anywhere not to lock the guild out, and the
+        // operators of the bot are not bound by it
+        if !matches!(command, Command::AllowChannel(_) | Command::DenyChannel(_))
+            && !command.is_admin()
+        {
+            use_case::CheckChannel::check_channel(self).await?;
+        }
+
+        // `resume` is the way out, and `help` tells about it
+        if !matches!(command, Command::Resume | Command::Help) && !command.is_admin() {
+            use_case::CheckPaused::check_paused(self).await?;
+        }
+
+        match command {
+            Command::Help => use_case::Help::help(self).await,
+            Command::ShowSetting => use_case::ShowSetting::show_setting(self).await,
+            Command::SaveProfile(name) => use_case::SaveProfile::save_profile_as(self, name).await,
+            Command::LoadProfile(name) => use_case::LoadProfile::load_profile(self, name).await,
+            Command::ListProfiles => use_case::ListProfiles::list_profiles(self).await,
+            Command::ExportSetting => use_case::ExportSetting::export_setting(self).await,
+            Command::ImportSetting => use_case::ImportSetting::import_setting(self).await,
+            Command::GenerateApiToken => use_case::ManageApiToken::generate_api_token(self).await,
+            Command::RevokeApiToken => use_case::ManageApiToken::revoke_api_token(self).await,
+            Command::TimeZone(tz) => use_case::SetTimeZone::set_timezone(self, tz).await,
+            Command::TimeZoneWizard => use_case::TimeZoneWizard::start_timezone_wizard(self).await,
+            Command::RequirePermission(b) => {
+                use_case::SetRequiresPermission::set_requires_permission(self, b).await
+            }
+            Command::RequirePermissionSelf(b) => {
+                use_case::SetRequiresPermissionSelf::set_requires_permission_self(self, b).await
+            }
+            Command::AddReminder(r) => use_case::AddReminder::add_reminder(self, r).await,
+            Command::RemoveReminder(r) => use_case::RemoveReminder::remove_reminder(self, r).await,
+            Command::ClearReminders => use_case::ClearReminders::clear_reminders(self).await,
+            Command::ListReminders => use_case::ListReminders::list_reminders(self).await,
+            Command::RemindRandomKaisan(b) => {
+                use_case::SetRemindsRandomKaisan::set_reminds_random_kaisan(self, b).await
+            }
+            Command::AutoKaisan(h) => use_case::SetAutoKaisan::set_auto_kaisan(self, h).await,
+            Command::MaxHorizon(n) => use_case::SetMaxHorizon::set_max_horizon(self, n).await,
+            Command::MaxSchedules(n) => use_case::SetMaxSchedules::set_max_schedules(self, n).await,
+            Command::UndoWindow(n) => use_case::SetUndoWindow::set_undo_window(self, n).await,
+            Command::Countdown(b) => use_case::SetCountdown::set_countdown(self, b).await,
+            Command::AuthorLeave(policy) => {
+                use_case::SetAuthorLeavePolicy::set_author_leave_policy(self, policy).await
+            }
+            Command::RevealRandom(reveal_random) => {
+                use_case::SetRevealRandom::set_reveal_random(self, reveal_random).await
+            }
+            Command::RandomDistribution(distribution) => {
+                use_case::SetRandomDistribution::set_random_distribution(self, distribution).await
+            }
+            Command::Dst(policy) => use_case::SetDstPolicy::set_dst_policy(self, policy).await,
+            Command::WeekStart(week_start) => {
+                use_case::SetWeekStart::set_week_start(self, week_start).await
+            }
+            Command::Locale(locale) => use_case::SetLocale::set_locale(self, locale).await,
+            Command::OnDuplicate(on_duplicate) => {
+                use_case::SetOnDuplicate::set_on_duplicate(self, on_duplicate).await
+            }
+            Command::AllowChannel(id) => use_case::AllowChannel::allow_channel(self, id).await,
+            Command::DenyChannel(id) => use_case::DenyChannel::deny_channel(self, id).await,
+            Command::ChannelSetting(id, setting) => {
+                use_case::SetChannelSetting::set_channel_setting(self, id, setting).await
+            }
+            Command::AllowRole(id) => use_case::AllowRole::allow_role(self, id).await,
+            Command::DenyRole(id) => use_case::DenyRole::deny_role(self, id).await,
+            Command::ListRoles => use_case::ListRoles::list_roles(self).await,
+            Command::Webhook(url) => use_case::SetWebhook::set_webhook(self, url).await,
+            Command::LogChannel(id) => use_case::SetLogChannel::set_log_channel(self, id).await,
+            Command::Reaction(outcome, reaction) => {
+                use_case::SetReaction::set_reaction(self, outcome, reaction).await
+            }
+            Command::Phrase(outcome, phrase) => {
+                use_case::SetPhrase::set_phrase(self, outcome, phrase).await
+            }
+            Command::Prefix(prefix) => {
+                use_case::SetCommandPrefix::set_command_prefix(self, prefix).await
+            }
+            Command::Tonight(hour) => use_case::SetTonightHour::set_tonight_hour(self, hour).await,
+            Command::Pause => use_case::Pause::pause(self).await,
+            Command::Resume => use_case::Resume::resume(self).await,
+            Command::CancelMine => use_case::CancelMine::cancel_mine(self).await,
+            Command::Undo => use_case::Undo::undo(self).await,
+            Command::Now(id) => use_case::KaisanNow::kaisan_now(self, id).await,
+            Command::ReminderText(text) => {
+                use_case::SetReminderText::set_reminder_text(self, text).await
+            }
+            Command::Kaisan {
+                kaisanee,
+                time_range,
+                options,
+            } => {
+                use_case::ScheduleKaisan::schedule_kaisan(self, kaisanee, time_range, options).await
+            }
+            Command::KaisanMulti { stages, options } => {
+                use_case::ScheduleKaisan::schedule_kaisan_stages(self, stages, options).await
+            }
+            Command::Preview {
+                kaisanee,
+                time_range,
+            } => use_case::PreviewKaisan::preview_kaisan(self, kaisanee, time_range).await,
+            Command::AbortAll => use_case::AbortAll::abort_all(self).await,
+            Command::AdminSchedules => {
+                use_case::ShowPendingSchedules::show_pending_schedules(self).await
+            }
+            Command::AdminBroadcast(text) => {
+                use_case::AdminBroadcast::admin_broadcast(self, text).await
+            }
+            Command::AdminStats => use_case::AdminStats::admin_stats(self).await,
+            Command::AdminStorage(guild_id) => {
+                use_case::AdminStorage::admin_storage(self, guild_id).await
+            }
+            Command::AdminLogLevel(directives) => {
+                use_case::AdminLogLevel::admin_log_level(self, directives).await
+            }
+            Command::AdminSoak(interval_minutes) => {
+                use_case::AdminSoak::admin_soak(self, interval_minutes).await
+            }
+            Command::When => use_case::When::when(self).await,
+            Command::Ping => use_case::Ping::ping(self).await,
+            Command::WhoKickedMe => use_case::WhoKickedMe::who_kicked_me(self).await,
+            Command::Complain => use_case::Complain::complain(self).await,
+            Command::ShowComplaints => use_case::ShowComplaints::show_complaints(self).await,
+            Command::ShowStats => use_case::ShowStats::show_stats(self).await,
+        }
+    }
+
+    /// Handles a reaction added to a message, which may be the one that scheduled a kaisan.
+    pub async fn handle_reaction(&self, reaction: &ReactionType) -> Result<()> {
+        let Some(reaction) = ScheduleReaction::from_reaction(reaction) else {
+            return Ok(());
+        };
+        tracing::debug!(?reaction, "parsed reaction");
+
+        match reaction {
+            ScheduleReaction::OptOut => use_case::OptOut::opt_out(self).await,
+            ScheduleReaction::OptIn => use_case::OptIn::opt_in(self).await,
+        }
+    }
+
+    /// Handles a choice made in a select menu that the bot has posted.
+    pub async fn handle_component(&self, custom_id: &str, values: &[String]) -> Result<()> {
+        let Some(selection) = values
+            .first()
+            .and_then(|value| MenuSelection::parse(custom_id, value))
+        else {
+            tracing::debug!(%custom_id, ?values, "ignoring unknown selection");
+            return Ok(());
+        };
+        tracing::debug!(?selection, "parsed selection");
+
+        match selection {
+            MenuSelection::TimeZoneRegion { region, current } => {
+                use_case::TimeZoneWizard::select_timezone_region(self, region, current).await
+            }
+            MenuSelection::TimeZone { timezone, current } => {
+                use_case::TimeZoneWizard::select_timezone(self, timezone, current).await
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ContextBuilder {
+    http: Arc<Http>,
+    cache: Arc<Cache>,
+    bot_id: UserId,
+    guild_id: Option<GuildId>,
+    author_id: Option<UserId>,
+    channel_id: Option<ChannelId>,
+    message_id: Option<MessageId>,
+    attachments: Vec<Attachment>,
+    database: Option<AnyDatabaseHandle>,
+    registry: Option<ScheduleRegistry>,
+    presence: PresenceTracker,
+    command_prefixes: CommandPrefixes,
+    cancel_links: Option<CancelLinks>,
+    voice: Option<VoiceAnnouncer>,
+    log_filter: Option<LogFilterHandle>,
+    shard_id: ShardId,
+    shard_manager: Option<Arc<ShardManager>>,
+    records_disconnects: bool,
+    late_grace: chrono::Duration,
+    owners: Arc<HashSet<UserId>>,
+}
+
+impl ContextBuilder {
+    pub fn with_serenity(ctx: &serenity::client::Context) -> Self {
+        let bot_id = ctx.cache.current_user().id;
+        let mut builder = Self::with_http(Arc::clone(&ctx.http), Arc::clone(&ctx.cache), bot_id);
+        builder.shard_id = ctx.shard_id;
+        builder
+    }
+
+    /// For the contexts created outside the event handler, such as in the HTTP server.
+    pub fn with_http(http: Arc<Http>, cache: Arc<Cache>, bot_id: UserId) -> Self {
+        Self {
+            http,
+            cache,
+            bot_id,
+            guild_id: None,
+            author_id: None,
+            channel_id: None,
+            message_id: None,
+            attachments: Vec::new(),
+            database: None,
+            registry: None,
+            presence: PresenceTracker::new(),
+            command_prefixes: CommandPrefixes::default(),
+            cancel_links: None,
+            voice: None,
+            log_filter: None,
+            shard_id: ShardId(0),
+            shard_manager: None,
+            records_disconnects: false,
+            late_grace: chrono::Duration::seconds(DEFAULT_LATE_GRACE_SECONDS),
+            owners: Arc::new(HashSet::new()),
+        }
+    }
+
+    pub fn database(&mut self, database: impl Into<AnyDatabaseHandle>) -> &mut Self {
+        self.database = Some(database.into());
+        self
+    }
+
+    pub fn registry(&mut self, registry: ScheduleRegistry) -> &mut Self {
+        self.registry = Some(registry);
+        self
+    }
+
+    pub fn presence(&mut self, presence: PresenceTracker) -> &mut Self {
+        self.presence = presence;
+        self
+    }
+
+    /// Shares the command prefixes read from the database between the contexts.
+    pub fn command_prefixes(&mut self, command_prefixes: CommandPrefixes) -> &mut Self {
+        self.command_prefixes = command_prefixes;
+        self
+    }
+
+    /// Lets the schedules be cancelled with signed links, served by [`crate::web`].
+    pub fn cancel_links(&mut self, cancel_links: Option<CancelLinks>) -> &mut Self {
+        self.cancel_links = cancel_links;
+        self
+    }
+
+    /// Plays the announcement in the voice channel before the kaisan.
+    pub fn voice(&mut self, voice: Option<VoiceAnnouncer>) -> &mut Self {
+        self.voice = voice;
+        self
+    }
+
+    /// Lets the owners change the filter of the logs.
+    pub fn log_filter(&mut self, log_filter: Option<LogFilterHandle>) -> &mut Self {
+        self.log_filter = log_filter;
+        self
+    }
+
+    /// Lets the latency of the gateway be reported, which is kept by the shard manager.
+    pub fn shard_manager(&mut self, shard_manager: Option<Arc<ShardManager>>) -> &mut Self {
+        self.shard_manager = shard_manager;
+        self
+    }
+
+    pub fn records_disconnects(&mut self, records_disconnects: bool) -> &mut Self {
+        self.records_disconnects = records_disconnects;
+        self
+    }
+
+    /// Scheduled work later than this is skipped.
+    pub fn late_grace(&mut self, late_grace: chrono::Duration) -> &mut Self {
+        self.late_grace = late_grace;
+        self
+    }
+
+    /// Users allowed to run the commands for the operators of the bot.
+    pub fn owners(&mut self, owners: Arc<HashSet<UserId>>) -> &mut Self {
+        self.owners = owners;
+        self
+    }
+
+    pub fn guild_id(&mut self, guild_id: GuildId) -> &mut Self {
+        self.guild_id = Some(guild_id);
+        self
+    }
+
+    pub fn message(&mut self, message: &Message) -> &mut Self {
+        self.author_id = Some(message.author.id);
+        self.channel_id = Some(message.channel_id);
+        self.message_id = Some(message.id);
+        self.attachments.clone_from(&message.attachments);
+        self
+    }
+
+    /// Sets up the context for a choice made in a select menu on the given message.
+    pub fn component(&mut self, interaction: &ComponentInteraction) -> &mut Self {
+        self.author_id = Some(interaction.user.id);
+        self.channel_id = Some(interaction.channel_id);
+        self.message_id = Some(interaction.message.id);
+        self.attachments = Vec::new();
+        self
+    }
+
+    /// Sets up the context for a reaction, as if the reacting user wrote the message.
+    pub fn reaction(&mut self, reaction: &Reaction, user_id: UserId) -> &mut Self {
+        self.author_id = Some(user_id);
+        self.channel_id = Some(reaction.channel_id);
+        self.message_id = Some(reaction.message_id);
+        self.attachments = Vec::new();
+        self
+    }
+
+    /// Sets up the context for an event in the voice channel, caused by the given user.
+    pub fn voice_channel(&mut self, channel_id: ChannelId, user_id: UserId) -> &mut Self {
+        self.author_id = Some(user_id);
+        self.channel_id = Some(channel_id);
+        self.message_id = None;
+        self.attachments = Vec::new();
+        self
+    }
+
+    /// Sets up the context as if it were created from the message that requested the schedule.
+    pub fn schedule(&mut self, schedule: &Schedule) -> &mut Self {
+        self.author_id = Some(schedule.author_id);
+        self.channel_id = Some(schedule.channel_id);
+        self.message_id = schedule.message_id;
+        self.attachments = Vec::new();
         self
     }
 
@@ -383,10 +2047,23 @@ impl ContextBuilder {
             guild_id: self.guild_id?,
             author_id: self.author_id?,
             channel_id: self.channel_id?,
-            message_id: self.message_id?,
-            redis_prefix: self.redis_prefix.clone()?,
-            redis: Arc::clone(self.redis_conn.as_ref()?),
+            message_id: self.message_id,
+            attachments: self.attachments.clone(),
+            database: self.database.clone()?,
+            registry: self.registry.clone()?,
+            presence: self.presence.clone(),
+            command_prefixes: self.command_prefixes.clone(),
+            cancel_links: self.cancel_links.clone(),
+            voice: self.voice.clone(),
+            log_filter: self.log_filter.clone(),
+            shard_id: self.shard_id,
+            shard_manager: self.shard_manager.clone(),
+            records_disconnects: self.records_disconnects,
+            late_grace: self.late_grace,
             rng: Arc::new(Mutex::new(SmallRng::from_entropy())),
+            owners: Arc::clone(&self.owners),
+            guild_name: Arc::new(Mutex::new(None)),
+            channel_name: Arc::new(Mutex::new(None)),
         })
     }
 }
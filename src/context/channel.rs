@@ -1,10 +1,34 @@
 use crate::error::Result;
-use crate::model::message::Message;
+use crate::model::{menu::SelectMenu, message::Message};
 
-use serenity::model::id::ChannelId;
+use serenity::model::id::{ChannelId, MessageId, UserId};
 
 #[async_trait::async_trait]
 pub trait ChannelContext {
     fn channel_id(&self) -> ChannelId;
+    /// Human-readable name of the channel, for logs and reports.
+    async fn channel_name(&self) -> Result<String>;
     async fn message(&self, message: Message) -> Result<()>;
+    /// Sends a message to another channel of the guild, without pinging the mentioned users.
+    async fn message_to(&self, channel_id: ChannelId, message: Message) -> Result<()>;
+    /// Sends a message that can be edited later with [`ChannelContext::edit_message`].
+    async fn post_message(&self, message: Message) -> Result<MessageId>;
+    async fn edit_message(&self, message_id: MessageId, message: Message) -> Result<()>;
+    /// Sends a message with a select menu, whose choices come back through
+    /// [`Context::handle_component`](crate::context::Context::handle_component).
+    async fn post_menu(&self, message: Message, menu: SelectMenu) -> Result<MessageId>;
+    /// Replaces the message and its select menu, removing the menu if `menu` is `None`.
+    async fn edit_menu(
+        &self,
+        message_id: MessageId,
+        message: Message,
+        menu: Option<SelectMenu>,
+    ) -> Result<()>;
+    async fn direct_message(&self, user_id: UserId, message: Message) -> Result<()>;
+    async fn message_with_attachment(
+        &self,
+        message: Message,
+        filename: &str,
+        data: Vec<u8>,
+    ) -> Result<()>;
 }
@@ -0,0 +1,31 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::error::Result;
+use crate::model::event::DisconnectEvent;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serenity::model::id::UserId;
+
+#[async_trait::async_trait]
+pub trait EventContext {
+    async fn record_disconnect(&self, event: DisconnectEvent) -> Result<()>;
+    /// Recorded disconnections, most recent first.
+    async fn disconnect_events(&self) -> Result<Vec<DisconnectEvent>>;
+    /// Returns `false` if the event has already been complained about. The complaints about the
+    /// events before `since` are forgotten.
+    async fn add_complaint(&self, event: &DisconnectEvent, since: DateTime<Utc>) -> Result<bool>;
+    async fn complaint_counts(&self) -> Result<HashMap<UserId, u64>>;
+    /// Counts a command from the user, returning the number of commands they have run in the
+    /// window, which starts at the first of them.
+    async fn record_command(&self, user_id: UserId, window: Duration) -> Result<u64>;
+    /// Returns `false` if the notice about the default timezone has already been shown.
+    async fn record_timezone_notice(&self) -> Result<bool>;
+    /// Adds to the seconds the user has spent in voice on the date, in the timezone of the guild.
+    async fn record_voice_time(&self, user_id: UserId, date: NaiveDate, seconds: u64)
+        -> Result<()>;
+    async fn voice_times(&self, date: NaiveDate) -> Result<HashMap<UserId, u64>>;
+    /// Counts a disconnection of the user by a kaisan on the date.
+    async fn record_kaisaned(&self, user_id: UserId, date: NaiveDate) -> Result<()>;
+    async fn kaisaned_counts(&self, date: NaiveDate) -> Result<HashMap<UserId, u64>>;
+}
@@ -1,14 +1,25 @@
 use crate::error::Result;
+use crate::model::member::MemberNames;
 
 use serenity::model::{
-    id::{ChannelId, UserId},
+    id::{ChannelId, EmojiId, GuildId, RoleId, UserId},
     permissions::Permissions,
 };
 
 #[async_trait::async_trait]
 pub trait GuildContext {
+    fn guild_id(&self) -> GuildId;
+    /// Human-readable name of the guild, for logs and reports.
+    async fn guild_name(&self) -> Result<String>;
     async fn connected_voice_channel(&self, user_id: UserId) -> Result<Option<ChannelId>>;
     async fn member_permissions(&self, user_id: UserId) -> Result<Permissions>;
+    async fn member_roles(&self, user_id: UserId) -> Result<Vec<RoleId>>;
     async fn voice_channel_users(&self, channel_id: ChannelId) -> Result<Vec<UserId>>;
     async fn disconnect_user(&self, user_id: UserId) -> Result<()>;
+    /// Whether the custom emoji belongs to the guild.
+    async fn has_emoji(&self, emoji_id: EmojiId) -> Result<bool>;
+    /// Whether the channel is a text channel of the guild that the bot can send messages to.
+    async fn can_post_to(&self, channel_id: ChannelId) -> Result<bool>;
+    /// Members the name may refer to, best matches only.
+    async fn find_member_by_name(&self, name: &str) -> Result<Vec<MemberNames>>;
 }
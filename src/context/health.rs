@@ -0,0 +1,15 @@
+use crate::error::Result;
+
+use std::time::Duration;
+
+/// Round trips to the services the bot depends on, to tell which one is slow.
+#[async_trait::async_trait]
+pub trait HealthContext {
+    /// Time taken by a request to the REST API of Discord.
+    async fn rest_latency(&self) -> Result<Duration>;
+    /// Latency of the last heartbeat on the gateway of the current shard, or `None` if it has not
+    /// been measured yet.
+    async fn gateway_latency(&self) -> Option<Duration>;
+    /// Time taken by reading a key from the database.
+    async fn database_latency(&self) -> Result<Duration>;
+}
@@ -0,0 +1,6 @@
+use crate::model::schedule::{Schedule, ScheduleId};
+
+pub trait LinkContext {
+    /// URL to cancel the schedule from outside Discord, if the HTTP server is enabled.
+    fn cancel_url(&self, id: ScheduleId, schedule: &Schedule) -> Option<String>;
+}
@@ -1,9 +1,20 @@
 use crate::error::Result;
 
-use serenity::model::{channel::ReactionType, id::UserId};
+use serenity::model::{
+    channel::ReactionType,
+    id::{MessageId, UserId},
+};
 
 #[async_trait::async_trait]
 pub trait MessageContext {
     fn author_id(&self) -> UserId;
+    /// The message which the context is created from, if any.
+    fn message_id(&self) -> Option<MessageId>;
     async fn react(&self, reaction: impl Into<ReactionType> + 'async_trait + Send) -> Result<()>;
+    /// Reacts with the success reaction of the guild, and says its phrase if any.
+    async fn react_success(&self) -> Result<()>;
+    /// Reacts with the failure reaction of the guild, and says its phrase if any.
+    async fn react_failure(&self) -> Result<()>;
+    /// The JSON file attached to the message, which must not be larger than `max_bytes`.
+    async fn attachment(&self, max_bytes: u32) -> Result<Option<Vec<u8>>>;
 }
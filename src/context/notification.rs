@@ -0,0 +1,7 @@
+use crate::model::notification::Notification;
+
+pub trait NotificationContext {
+    /// Sends the notification to the sinks of the guild in the background. Failures are only
+    /// logged, not to hold up the kaisan.
+    fn notify(&self, notification: Notification);
+}
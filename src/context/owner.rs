@@ -0,0 +1,27 @@
+use crate::database::{CacheStats, RetryStats, StorageUsage};
+use crate::dispatcher::DispatchStats;
+use crate::error::Result;
+use crate::log::LogFilterHandle;
+use crate::model::schedule::{Schedule, ScheduleId};
+use crate::soak::SoakMonitor;
+
+use serenity::model::id::{GuildId, UserId};
+
+/// Operations for the operators of the bot, which reach beyond the current guild.
+#[async_trait::async_trait]
+pub trait OwnerContext {
+    fn is_owner(&self, user_id: UserId) -> bool;
+    async fn all_schedules(&self) -> Vec<(GuildId, ScheduleId, Schedule)>;
+    fn dispatch_stats(&self) -> DispatchStats;
+    fn soak(&self) -> SoakMonitor;
+    fn database_retry_stats(&self) -> RetryStats;
+    fn database_cache_stats(&self) -> CacheStats;
+    /// `None` if the filter cannot be changed at runtime.
+    fn log_filter(&self) -> Option<LogFilterHandle>;
+    /// Guilds the bot is in.
+    async fn guild_ids(&self) -> Vec<GuildId>;
+    /// Posts the text where the guild reads the bot, returning `false` if there is no such
+    /// channel.
+    async fn announce(&self, guild_id: GuildId, text: &str) -> Result<bool>;
+    async fn storage_usage(&self, guild_id: GuildId) -> Result<StorageUsage>;
+}
@@ -0,0 +1,8 @@
+use chrono::{DateTime, Utc};
+use serenity::model::id::UserId;
+
+#[async_trait::async_trait]
+pub trait PresenceContext {
+    /// When the user joined the voice channel they are in, if it is known.
+    async fn joined_at(&self, user_id: UserId) -> Option<DateTime<Utc>>;
+}
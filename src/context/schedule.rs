@@ -0,0 +1,21 @@
+use crate::dispatcher::DispatchPermit;
+use crate::model::schedule::{Schedule, ScheduleId};
+
+use tokio::task::AbortHandle;
+
+#[async_trait::async_trait]
+pub trait ScheduleContext {
+    async fn register_schedule(&self, schedule: Schedule) -> ScheduleId;
+    async fn attach_schedule_tasks(&self, id: ScheduleId, tasks: Vec<AbortHandle>);
+    async fn schedules(&self) -> Vec<(ScheduleId, Schedule)>;
+    /// Changes the pending schedule, returning `None` if it is no longer registered.
+    async fn update_schedule(
+        &self,
+        id: ScheduleId,
+        f: impl for<'s> FnOnce(&'s mut Schedule) + Send + 'async_trait,
+    ) -> Option<Schedule>;
+    async fn take_schedule(&self, id: ScheduleId) -> Option<Schedule>;
+    async fn cancel_schedule(&self, id: ScheduleId) -> Option<Schedule>;
+    /// Waits until the due work of the schedules may run, which lasts while the permit is held.
+    async fn dispatch(&self) -> DispatchPermit;
+}
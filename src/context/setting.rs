@@ -1,19 +1,284 @@
 use std::collections::HashSet;
 
 use crate::error::Result;
-use crate::model::reminder::Reminder;
+use crate::model::{
+    reaction::Outcome,
+    reminder::Reminder,
+    schedule::FrozenSchedule,
+    setting::{
+        AuthorLeavePolicy, ChannelOverride, ChannelOverrides, DstPolicy, DuplicatePolicy, Locale,
+        RandomDistribution, RevealRandom, Setting, SettingPatch, SettingsSnapshot, WeekStart,
+    },
+    template::ReminderTemplate,
+    time::Hour,
+};
 
 use chrono_tz::Tz;
+use serenity::model::{
+    channel::ReactionType,
+    id::{ChannelId, RoleId},
+};
 
 #[async_trait::async_trait]
 pub trait SettingContext {
     async fn timezone(&self) -> Result<Tz>;
     async fn set_timezone(&self, timezone: Tz) -> Result<()>;
+    /// Sets the timezone only if it is still `current`. Returns whether it has been set.
+    async fn replace_timezone(&self, current: Tz, timezone: Tz) -> Result<bool>;
+    /// Whether the timezone has been set, rather than being the default.
+    async fn has_timezone(&self) -> Result<bool>;
     async fn requires_permission(&self) -> Result<bool>;
     async fn set_requires_permission(&self, requires_permission: bool) -> Result<()>;
+    /// Whether kaisan of only oneself also requires the permission.
+    async fn requires_permission_self(&self) -> Result<bool>;
+    async fn set_requires_permission_self(&self, requires_permission_self: bool) -> Result<()>;
     async fn reminders(&self) -> Result<HashSet<Reminder>>;
     async fn add_reminder(&self, reminder: Reminder) -> Result<bool>;
     async fn remove_reminder(&self, reminder: Reminder) -> Result<bool>;
     async fn reminds_random_kaisan(&self) -> Result<bool>;
     async fn set_reminds_random_kaisan(&self, reminds_random_kaisan: bool) -> Result<()>;
+    async fn auto_kaisan_hour(&self) -> Result<Option<Hour>>;
+    async fn set_auto_kaisan_hour(&self, hour: Option<Hour>) -> Result<()>;
+    async fn max_horizon_hours(&self) -> Result<u8>;
+    async fn set_max_horizon_hours(&self, hours: u8) -> Result<()>;
+    /// Hour that `今夜` and `tonight` refer to.
+    async fn tonight_hour(&self) -> Result<Hour>;
+    async fn set_tonight_hour(&self, hour: Hour) -> Result<()>;
+    async fn max_schedules_per_user(&self) -> Result<u8>;
+    async fn set_max_schedules_per_user(&self, count: u8) -> Result<()>;
+    /// How long a schedule can be undone after it is made, or zero if it cannot.
+    async fn undo_window_minutes(&self) -> Result<u8>;
+    async fn set_undo_window_minutes(&self, minutes: u8) -> Result<()>;
+    async fn countdown(&self) -> Result<bool>;
+    async fn set_countdown(&self, countdown: bool) -> Result<()>;
+    async fn author_leave_policy(&self) -> Result<AuthorLeavePolicy>;
+    async fn set_author_leave_policy(&self, policy: AuthorLeavePolicy) -> Result<()>;
+    async fn reveal_random(&self) -> Result<RevealRandom>;
+    async fn set_reveal_random(&self, reveal_random: RevealRandom) -> Result<()>;
+    async fn random_distribution(&self) -> Result<RandomDistribution>;
+    async fn set_random_distribution(&self, distribution: RandomDistribution) -> Result<()>;
+    async fn dst_policy(&self) -> Result<DstPolicy>;
+    async fn set_dst_policy(&self, policy: DstPolicy) -> Result<()>;
+    async fn week_start(&self) -> Result<WeekStart>;
+    async fn set_week_start(&self, week_start: WeekStart) -> Result<()>;
+    async fn locale(&self) -> Result<Locale>;
+    async fn set_locale(&self, locale: Locale) -> Result<()>;
+    async fn on_duplicate(&self) -> Result<DuplicatePolicy>;
+    async fn set_on_duplicate(&self, on_duplicate: DuplicatePolicy) -> Result<()>;
+    /// Text channels where commands are accepted. Empty means all channels.
+    async fn allowed_channels(&self) -> Result<HashSet<ChannelId>>;
+    async fn allow_channel(&self, channel_id: ChannelId) -> Result<bool>;
+    async fn deny_channel(&self, channel_id: ChannelId) -> Result<bool>;
+    /// Roles whose members may kaisan others without the permission.
+    async fn allowed_roles(&self) -> Result<HashSet<RoleId>>;
+    async fn allow_role(&self, role_id: RoleId) -> Result<bool>;
+    async fn deny_role(&self, role_id: RoleId) -> Result<bool>;
+    /// Guild-specific command prefix, which replaces the global one if set.
+    async fn command_prefix(&self) -> Result<Option<String>>;
+    async fn set_command_prefix(&self, prefix: Option<String>) -> Result<()>;
+    async fn reminder_template(&self) -> Result<Option<ReminderTemplate>>;
+    async fn set_reminder_template(&self, template: Option<ReminderTemplate>) -> Result<()>;
+    /// Schedules stopped by `pause`, or `None` unless the guild is paused.
+    async fn frozen_schedules(&self) -> Result<Option<Vec<FrozenSchedule>>>;
+    async fn set_frozen_schedules(&self, frozen: Option<Vec<FrozenSchedule>>) -> Result<()>;
+    /// Hash of the API token of the guild, see [`ApiToken::hash`].
+    ///
+    /// [`ApiToken::hash`]: crate::model::api_token::ApiToken::hash
+    async fn api_token_hash(&self) -> Result<Option<String>>;
+    async fn set_api_token_hash(&self, hash: Option<String>) -> Result<()>;
+    /// URL that receives the notifications of the guild as JSON.
+    async fn webhook_url(&self) -> Result<Option<String>>;
+    async fn set_webhook_url(&self, url: Option<String>) -> Result<()>;
+    /// Channel that every schedule, kaisan, cancellation and failure is mirrored to.
+    async fn log_channel(&self) -> Result<Option<ChannelId>>;
+    async fn set_log_channel(&self, channel_id: Option<ChannelId>) -> Result<()>;
+    /// Reaction that tells the outcome, [`Outcome::default_reaction`] unless set.
+    async fn reaction(&self, outcome: Outcome) -> Result<ReactionType>;
+    async fn set_reaction(&self, outcome: Outcome, reaction: Option<ReactionType>) -> Result<()>;
+    /// Phrase said along with the reaction.
+    async fn phrase(&self, outcome: Outcome) -> Result<Option<String>>;
+    async fn set_phrase(&self, outcome: Outcome, phrase: Option<String>) -> Result<()>;
+    /// Names of the profiles saved with [`SettingContext::save_profile`].
+    async fn profile_names(&self) -> Result<HashSet<String>>;
+    async fn profile(&self, name: &str) -> Result<Option<Setting>>;
+    /// Saves the settings under the name, replacing the profile of the same name if any.
+    async fn save_profile(&self, name: &str, setting: &Setting) -> Result<()>;
+    /// Settings overridden in the voice channel, see [`ChannelOverrides`].
+    async fn channel_overrides(&self, voice_channel_id: ChannelId) -> Result<ChannelOverrides>;
+    async fn set_channel_override(
+        &self,
+        voice_channel_id: ChannelId,
+        setting: ChannelOverride,
+    ) -> Result<()>;
+
+    /// The settings in effect in the voice channel are the overrides for it if any, and those of
+    /// the guild otherwise.
+    async fn max_horizon_hours_in(&self, voice_channel_id: ChannelId) -> Result<u8> {
+        match self
+            .channel_overrides(voice_channel_id)
+            .await?
+            .max_horizon_hours
+        {
+            Some(hours) => Ok(hours),
+            None => self.max_horizon_hours().await,
+        }
+    }
+
+    async fn countdown_in(&self, voice_channel_id: ChannelId) -> Result<bool> {
+        match self.channel_overrides(voice_channel_id).await?.countdown {
+            Some(countdown) => Ok(countdown),
+            None => self.countdown().await,
+        }
+    }
+
+    async fn author_leave_policy_in(
+        &self,
+        voice_channel_id: ChannelId,
+    ) -> Result<AuthorLeavePolicy> {
+        match self
+            .channel_overrides(voice_channel_id)
+            .await?
+            .author_leave_policy
+        {
+            Some(policy) => Ok(policy),
+            None => self.author_leave_policy().await,
+        }
+    }
+
+    async fn on_duplicate_in(&self, voice_channel_id: ChannelId) -> Result<DuplicatePolicy> {
+        match self.channel_overrides(voice_channel_id).await?.on_duplicate {
+            Some(policy) => Ok(policy),
+            None => self.on_duplicate().await,
+        }
+    }
+
+    /// Reads the settings that a kaisan command needs, which the database may do in a single
+    /// call rather than one for each.
+    async fn settings_snapshot(&self) -> Result<SettingsSnapshot> {
+        let (
+            timezone,
+            requires_permission,
+            requires_permission_self,
+            reminders,
+            reminds_random_kaisan,
+        ) = futures::try_join!(
+            self.timezone(),
+            self.requires_permission(),
+            self.requires_permission_self(),
+            self.reminders(),
+            self.reminds_random_kaisan(),
+        )?;
+        Ok(SettingsSnapshot {
+            timezone,
+            requires_permission,
+            requires_permission_self,
+            reminders,
+            reminds_random_kaisan,
+        })
+    }
+
+    /// Overwrites the settings given in `setting` and leaves the others as they are, which the
+    /// database may do in a single call so that it does not stop halfway.
+    async fn overwrite_settings(&self, setting: SettingPatch) -> Result<()> {
+        if let Some(timezone) = setting.timezone {
+            self.set_timezone(timezone).await?;
+        }
+        if let Some(requires_permission) = setting.requires_permission {
+            self.set_requires_permission(requires_permission).await?;
+        }
+        if let Some(requires_permission_self) = setting.requires_permission_self {
+            self.set_requires_permission_self(requires_permission_self)
+                .await?;
+        }
+        if let Some(reminds_random_kaisan) = setting.reminds_random_kaisan {
+            self.set_reminds_random_kaisan(reminds_random_kaisan)
+                .await?;
+        }
+        if let Some(hour) = setting.auto_kaisan_hour {
+            self.set_auto_kaisan_hour(hour).await?;
+        }
+        if let Some(hours) = setting.max_horizon_hours {
+            self.set_max_horizon_hours(hours).await?;
+        }
+        if let Some(hour) = setting.tonight_hour {
+            self.set_tonight_hour(hour).await?;
+        }
+        if let Some(count) = setting.max_schedules_per_user {
+            self.set_max_schedules_per_user(count).await?;
+        }
+        if let Some(minutes) = setting.undo_window_minutes {
+            self.set_undo_window_minutes(minutes).await?;
+        }
+        if let Some(countdown) = setting.countdown {
+            self.set_countdown(countdown).await?;
+        }
+        if let Some(policy) = setting.author_leave_policy {
+            self.set_author_leave_policy(policy).await?;
+        }
+        if let Some(reveal_random) = setting.reveal_random {
+            self.set_reveal_random(reveal_random).await?;
+        }
+        if let Some(distribution) = setting.random_distribution {
+            self.set_random_distribution(distribution).await?;
+        }
+        if let Some(policy) = setting.dst_policy {
+            self.set_dst_policy(policy).await?;
+        }
+        if let Some(week_start) = setting.week_start {
+            self.set_week_start(week_start).await?;
+        }
+        if let Some(locale) = setting.locale {
+            self.set_locale(locale).await?;
+        }
+        if let Some(on_duplicate) = setting.on_duplicate {
+            self.set_on_duplicate(on_duplicate).await?;
+        }
+        if let Some(prefix) = setting.command_prefix {
+            self.set_command_prefix(prefix).await?;
+        }
+        if let Some(template) = setting.reminder_text {
+            self.set_reminder_template(template).await?;
+        }
+
+        if let Some(reminders) = setting.reminders {
+            let current = self.reminders().await?;
+            for reminder in &current {
+                if !reminders.contains(reminder) {
+                    self.remove_reminder(*reminder).await?;
+                }
+            }
+            for reminder in reminders {
+                if !current.contains(&reminder) {
+                    self.add_reminder(reminder).await?;
+                }
+            }
+        }
+        if let Some(channels) = setting.allowed_channels {
+            let current = self.allowed_channels().await?;
+            for channel_id in &current {
+                if !channels.contains(channel_id) {
+                    self.deny_channel(*channel_id).await?;
+                }
+            }
+            for channel_id in channels {
+                if !current.contains(&channel_id) {
+                    self.allow_channel(channel_id).await?;
+                }
+            }
+        }
+        if let Some(roles) = setting.allowed_roles {
+            let current = self.allowed_roles().await?;
+            for role_id in &current {
+                if !roles.contains(role_id) {
+                    self.deny_role(*role_id).await?;
+                }
+            }
+            for role_id in roles {
+                if !current.contains(&role_id) {
+                    self.allow_role(role_id).await?;
+                }
+            }
+        }
+        Ok(())
+    }
 }
@@ -1,7 +1,11 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
+
+pub const DEFAULT_LATE_GRACE_SECONDS: i64 = 5 * 60;
 
 #[async_trait::async_trait]
 pub trait TimeContext {
     fn current_time(&self) -> DateTime<Utc>;
     async fn delay_until(&self, time: DateTime<Utc>);
+    /// How late scheduled work may still run, after the host was suspended for example.
+    fn late_grace(&self) -> Duration;
 }
@@ -0,0 +1,10 @@
+use crate::error::Result;
+
+use serenity::model::id::ChannelId;
+
+#[async_trait::async_trait]
+pub trait VoiceContext {
+    /// Joins the voice channel and starts playing the announcement of the kaisan. Returns
+    /// `Ok(false)` if the bot has no announcement to play.
+    async fn announce_in_voice(&self, voice_channel_id: ChannelId) -> Result<bool>;
+}
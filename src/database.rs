@@ -0,0 +1,555 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::hash::Hash;
+use std::time::Duration;
+
+use crate::error::Result;
+
+use ::redis::{FromRedisValue, ToRedisArgs, Value};
+use anyhow::Context as _;
+use serenity::model::id::GuildId;
+
+mod cached;
+mod memory;
+mod migrate;
+mod redis;
+mod retrying;
+
+pub use self::redis::{RedisHandle, RedisOptions, RedisTopology};
+pub use cached::{CacheStats, Cached, DEFAULT_CACHE_TTL};
+pub use memory::InMemoryHandle;
+pub use migrate::{migrate, MigrationSummary};
+pub use retrying::{
+    RetryPolicy, RetryStats, Retrying, DEFAULT_INITIAL_BACKOFF, DEFAULT_MAX_ATTEMPTS,
+};
+
+/// Approximate amount of data a guild takes in the database.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StorageUsage {
+    pub keys: usize,
+    pub bytes: u64,
+}
+
+/// A key to read in [`DatabaseHandle::read_many`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Read<'a> {
+    /// A value, as with [`DatabaseHandle::get`]
+    Value(&'a str),
+    /// A set, as with [`DatabaseHandle::set_members`]
+    Members(&'a str),
+}
+
+/// The result of a [`Read`], to be decoded into the type of the value.
+#[derive(Debug, Clone)]
+pub enum ReadResult {
+    Value(Option<Value>),
+    Members(Vec<Vec<u8>>),
+}
+
+impl ReadResult {
+    pub fn value<T: FromRedisValue>(self) -> Result<Option<T>> {
+        match self {
+            ReadResult::Value(None) => Ok(None),
+            ReadResult::Value(Some(value)) => {
+                let value = T::from_redis_value(&value).context("cannot decode stored value")?;
+                Ok(Some(value))
+            }
+            ReadResult::Members(_) => Err(anyhow::anyhow!("read a set as a value").into()),
+        }
+    }
+
+    /// Decodes the value written with [`DatabaseHandle::set_flag`].
+    pub fn flag(self, default: bool) -> Result<bool> {
+        Ok(self.value::<u32>()?.map_or(default, |r| r != 0))
+    }
+
+    pub fn members<T: Eq + Hash + FromRedisValue>(self) -> Result<HashSet<T>> {
+        match self {
+            ReadResult::Members(members) => members
+                .into_iter()
+                .map(|member| {
+                    let member = T::from_redis_value(&Value::Data(member))
+                        .context("cannot decode stored value")?;
+                    Ok(member)
+                })
+                .collect(),
+            ReadResult::Value(_) => Err(anyhow::anyhow!("read a value as a set").into()),
+        }
+    }
+}
+
+/// A change to a key in [`DatabaseHandle::write_many`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Write<'a> {
+    /// Replaces the key, as with [`DatabaseHandle::restore_key`]
+    Put(&'a str, Entry),
+    /// Deletes the key, as with [`DatabaseHandle::delete`]
+    Delete(&'a str),
+}
+
+impl<'a> Write<'a> {
+    pub fn key(&self) -> &'a str {
+        match self {
+            Write::Put(key, _) | Write::Delete(key) => key,
+        }
+    }
+}
+
+/// A key with its value of whichever type, to copy it to another database as is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Entry {
+    Value(Vec<u8>),
+    /// The items from the most recently pushed one.
+    List(Vec<Vec<u8>>),
+    Hash(HashMap<String, i64>),
+    Set(HashSet<Vec<u8>>),
+    /// A value that expires, such as one written with [`DatabaseHandle::set_with_ttl`] or a count
+    /// made with [`DatabaseHandle::incr_with_expiry`], with the time left until it expires.
+    Expiring(Vec<u8>, Duration),
+}
+
+impl Entry {
+    /// A value as written with [`DatabaseHandle::set`].
+    pub fn value<T: ToRedisArgs>(value: T) -> Entry {
+        Entry::Value(value.to_redis_args().concat())
+    }
+
+    /// A flag as written with [`DatabaseHandle::set_flag`].
+    pub fn flag(flag: bool) -> Entry {
+        Entry::value(flag as u32)
+    }
+
+    /// A set as made with [`DatabaseHandle::set_add`].
+    pub fn set<T: ToRedisArgs>(members: impl IntoIterator<Item = T>) -> Entry {
+        Entry::Set(
+            members
+                .into_iter()
+                .map(|member| member.to_redis_args().concat())
+                .collect(),
+        )
+    }
+}
+
+#[async_trait::async_trait]
+pub trait DatabaseHandle {
+    async fn get<T: FromRedisValue + Send>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+    ) -> Result<Option<T>>;
+    async fn set<T: ToRedisArgs + Send + Sync>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        value: T,
+    ) -> Result<()>;
+    /// Sets the value, which is deleted after `ttl`.
+    async fn set_with_ttl<T: ToRedisArgs + Send + Sync>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        value: T,
+        ttl: Duration,
+    ) -> Result<()>;
+    /// Sets the value only if the current one is still `expected`, where `None` stands for an
+    /// absent key. Returns whether the value has been set.
+    async fn compare_and_set<T: ToRedisArgs + Send + Sync>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        expected: Option<T>,
+        value: T,
+    ) -> Result<bool>;
+    async fn delete(&self, guild_id: GuildId, key: &str) -> Result<()>;
+    /// Pushes the value to the front of the list, keeping at most `capacity` elements.
+    async fn list_push<T: ToRedisArgs + Send + Sync>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        value: T,
+        capacity: usize,
+    ) -> Result<()>;
+    async fn list_items<T: FromRedisValue + Send>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+    ) -> Result<Vec<T>>;
+    async fn hash_incr(&self, guild_id: GuildId, key: &str, field: &str, delta: i64)
+        -> Result<i64>;
+    /// Increments the field of the hash, which is deleted after `expiry` from its last increment.
+    async fn hash_incr_with_expiry(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        field: &str,
+        delta: i64,
+        expiry: Duration,
+    ) -> Result<i64>;
+    async fn hash_items<T: FromRedisValue + Send>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+    ) -> Result<HashMap<String, T>>;
+    async fn set_members<T: Eq + Hash + FromRedisValue + Send>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+    ) -> Result<HashSet<T>>;
+    async fn set_add<T: ToRedisArgs + Send + Sync>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        value: T,
+    ) -> Result<bool>;
+    async fn set_remove<T: ToRedisArgs + Send + Sync>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        value: T,
+    ) -> Result<bool>;
+    /// Increments the counter, which is reset after `expiry` from its first increment.
+    async fn incr_with_expiry(&self, guild_id: GuildId, key: &str, expiry: Duration)
+        -> Result<i64>;
+    /// Counts the keys of the guild. The size is what `MEMORY USAGE` reports for Redis, which
+    /// includes its own overhead.
+    async fn usage(&self, guild_id: GuildId) -> Result<StorageUsage>;
+    /// Reads the keys at once, in a single round trip for Redis, returning the results in the
+    /// order of `reads`.
+    async fn read_many(&self, guild_id: GuildId, reads: &[Read<'_>]) -> Result<Vec<ReadResult>>;
+
+    /// Applies all of the writes at once, so that either all or none of them take effect.
+    async fn write_many(&self, guild_id: GuildId, writes: &[Write<'_>]) -> Result<()>;
+    /// Lists all the keys in the database, of every guild.
+    async fn keys(&self) -> Result<Vec<(GuildId, String)>>;
+    /// Reads the key whatever the type of its value is.
+    async fn dump_key(&self, guild_id: GuildId, key: &str) -> Result<Option<Entry>>;
+    /// Replaces the key with the entry read with [`DatabaseHandle::dump_key`].
+    async fn restore_key(&self, guild_id: GuildId, key: &str, entry: Entry) -> Result<()>;
+    /// Reads all the keys of the guild, such as to back them up.
+    async fn dump(&self, guild_id: GuildId) -> Result<BTreeMap<String, Entry>>;
+
+    /// Lists the guilds that have any key in the database.
+    async fn scan_guilds(&self) -> Result<Vec<GuildId>> {
+        let mut guilds: Vec<_> = self
+            .keys()
+            .await?
+            .into_iter()
+            .map(|(guild_id, _)| guild_id)
+            .collect();
+        guilds.sort_unstable();
+        guilds.dedup();
+        Ok(guilds)
+    }
+
+    async fn get_flag(&self, guild_id: GuildId, key: &str, default: bool) -> Result<bool> {
+        Ok(match self.get::<u32>(guild_id, key).await? {
+            None => default,
+            Some(r) => r != 0,
+        })
+    }
+
+    async fn set_flag(&self, guild_id: GuildId, key: &str, flag: bool) -> Result<()> {
+        self.set(guild_id, key, flag as u32).await
+    }
+
+    /// Sets the flag, which reads as the default after `ttl`.
+    async fn set_flag_with_ttl(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        flag: bool,
+        ttl: Duration,
+    ) -> Result<()> {
+        self.set_with_ttl(guild_id, key, flag as u32, ttl).await
+    }
+}
+
+#[derive(Clone)]
+pub enum AnyDatabaseHandle {
+    Redis(Cached<Retrying<RedisHandle>>),
+    InMemory(InMemoryHandle),
+}
+
+impl AnyDatabaseHandle {
+    /// Retries made on the database so far, which are always zero in memory.
+    pub fn retry_stats(&self) -> RetryStats {
+        match self {
+            AnyDatabaseHandle::Redis(h) => h.inner().stats(),
+            AnyDatabaseHandle::InMemory(_) => RetryStats::default(),
+        }
+    }
+
+    /// Reads of the cache in front of the database so far, which are always zero in memory.
+    pub fn cache_stats(&self) -> CacheStats {
+        match self {
+            AnyDatabaseHandle::Redis(h) => h.stats(),
+            AnyDatabaseHandle::InMemory(_) => CacheStats::default(),
+        }
+    }
+
+    /// Reads a key that is never written, going around the cache, to see how long the database
+    /// takes to respond.
+    pub async fn probe(&self, guild_id: GuildId) -> Result<()> {
+        const PROBE_KEY: &str = "health_probe";
+        match self {
+            AnyDatabaseHandle::Redis(h) => h.inner().get::<String>(guild_id, PROBE_KEY).await?,
+            AnyDatabaseHandle::InMemory(h) => h.get::<String>(guild_id, PROBE_KEY).await?,
+        };
+        Ok(())
+    }
+}
+
+impl From<RedisHandle> for AnyDatabaseHandle {
+    fn from(handle: RedisHandle) -> Self {
+        Retrying::new(handle, RetryPolicy::default()).into()
+    }
+}
+
+impl From<Retrying<RedisHandle>> for AnyDatabaseHandle {
+    fn from(handle: Retrying<RedisHandle>) -> Self {
+        Cached::new(handle, DEFAULT_CACHE_TTL).into()
+    }
+}
+
+impl From<Cached<Retrying<RedisHandle>>> for AnyDatabaseHandle {
+    fn from(handle: Cached<Retrying<RedisHandle>>) -> Self {
+        AnyDatabaseHandle::Redis(handle)
+    }
+}
+
+impl From<InMemoryHandle> for AnyDatabaseHandle {
+    fn from(handle: InMemoryHandle) -> Self {
+        AnyDatabaseHandle::InMemory(handle)
+    }
+}
+
+#[async_trait::async_trait]
+impl DatabaseHandle for AnyDatabaseHandle {
+    async fn get<T: FromRedisValue + Send>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+    ) -> Result<Option<T>> {
+        match self {
+            AnyDatabaseHandle::Redis(h) => h.get(guild_id, key).await,
+            AnyDatabaseHandle::InMemory(h) => h.get(guild_id, key).await,
+        }
+    }
+
+    async fn set<T: ToRedisArgs + Send + Sync>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        value: T,
+    ) -> Result<()> {
+        match self {
+            AnyDatabaseHandle::Redis(h) => h.set(guild_id, key, value).await,
+            AnyDatabaseHandle::InMemory(h) => h.set(guild_id, key, value).await,
+        }
+    }
+
+    async fn set_with_ttl<T: ToRedisArgs + Send + Sync>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        value: T,
+        ttl: Duration,
+    ) -> Result<()> {
+        match self {
+            AnyDatabaseHandle::Redis(h) => h.set_with_ttl(guild_id, key, value, ttl).await,
+            AnyDatabaseHandle::InMemory(h) => h.set_with_ttl(guild_id, key, value, ttl).await,
+        }
+    }
+
+    async fn compare_and_set<T: ToRedisArgs + Send + Sync>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        expected: Option<T>,
+        value: T,
+    ) -> Result<bool> {
+        match self {
+            AnyDatabaseHandle::Redis(h) => h.compare_and_set(guild_id, key, expected, value).await,
+            AnyDatabaseHandle::InMemory(h) => {
+                h.compare_and_set(guild_id, key, expected, value).await
+            }
+        }
+    }
+
+    async fn delete(&self, guild_id: GuildId, key: &str) -> Result<()> {
+        match self {
+            AnyDatabaseHandle::Redis(h) => h.delete(guild_id, key).await,
+            AnyDatabaseHandle::InMemory(h) => h.delete(guild_id, key).await,
+        }
+    }
+
+    async fn list_push<T: ToRedisArgs + Send + Sync>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        value: T,
+        capacity: usize,
+    ) -> Result<()> {
+        match self {
+            AnyDatabaseHandle::Redis(h) => h.list_push(guild_id, key, value, capacity).await,
+            AnyDatabaseHandle::InMemory(h) => h.list_push(guild_id, key, value, capacity).await,
+        }
+    }
+
+    async fn list_items<T: FromRedisValue + Send>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+    ) -> Result<Vec<T>> {
+        match self {
+            AnyDatabaseHandle::Redis(h) => h.list_items(guild_id, key).await,
+            AnyDatabaseHandle::InMemory(h) => h.list_items(guild_id, key).await,
+        }
+    }
+
+    async fn hash_incr(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        field: &str,
+        delta: i64,
+    ) -> Result<i64> {
+        match self {
+            AnyDatabaseHandle::Redis(h) => h.hash_incr(guild_id, key, field, delta).await,
+            AnyDatabaseHandle::InMemory(h) => h.hash_incr(guild_id, key, field, delta).await,
+        }
+    }
+
+    async fn hash_items<T: FromRedisValue + Send>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+    ) -> Result<HashMap<String, T>> {
+        match self {
+            AnyDatabaseHandle::Redis(h) => h.hash_items(guild_id, key).await,
+            AnyDatabaseHandle::InMemory(h) => h.hash_items(guild_id, key).await,
+        }
+    }
+
+    async fn set_members<T: Eq + Hash + FromRedisValue + Send>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+    ) -> Result<HashSet<T>> {
+        match self {
+            AnyDatabaseHandle::Redis(h) => h.set_members(guild_id, key).await,
+            AnyDatabaseHandle::InMemory(h) => h.set_members(guild_id, key).await,
+        }
+    }
+
+    async fn set_add<T: ToRedisArgs + Send + Sync>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        value: T,
+    ) -> Result<bool> {
+        match self {
+            AnyDatabaseHandle::Redis(h) => h.set_add(guild_id, key, value).await,
+            AnyDatabaseHandle::InMemory(h) => h.set_add(guild_id, key, value).await,
+        }
+    }
+
+    async fn set_remove<T: ToRedisArgs + Send + Sync>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        value: T,
+    ) -> Result<bool> {
+        match self {
+            AnyDatabaseHandle::Redis(h) => h.set_remove(guild_id, key, value).await,
+            AnyDatabaseHandle::InMemory(h) => h.set_remove(guild_id, key, value).await,
+        }
+    }
+
+    async fn hash_incr_with_expiry(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        field: &str,
+        delta: i64,
+        expiry: Duration,
+    ) -> Result<i64> {
+        match self {
+            AnyDatabaseHandle::Redis(h) => {
+                h.hash_incr_with_expiry(guild_id, key, field, delta, expiry)
+                    .await
+            }
+            AnyDatabaseHandle::InMemory(h) => {
+                h.hash_incr_with_expiry(guild_id, key, field, delta, expiry)
+                    .await
+            }
+        }
+    }
+
+    async fn incr_with_expiry(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        expiry: Duration,
+    ) -> Result<i64> {
+        match self {
+            AnyDatabaseHandle::Redis(h) => h.incr_with_expiry(guild_id, key, expiry).await,
+            AnyDatabaseHandle::InMemory(h) => h.incr_with_expiry(guild_id, key, expiry).await,
+        }
+    }
+
+    async fn usage(&self, guild_id: GuildId) -> Result<StorageUsage> {
+        match self {
+            AnyDatabaseHandle::Redis(h) => h.usage(guild_id).await,
+            AnyDatabaseHandle::InMemory(h) => h.usage(guild_id).await,
+        }
+    }
+
+    async fn read_many(&self, guild_id: GuildId, reads: &[Read<'_>]) -> Result<Vec<ReadResult>> {
+        match self {
+            AnyDatabaseHandle::Redis(h) => h.read_many(guild_id, reads).await,
+            AnyDatabaseHandle::InMemory(h) => h.read_many(guild_id, reads).await,
+        }
+    }
+
+    async fn write_many(&self, guild_id: GuildId, writes: &[Write<'_>]) -> Result<()> {
+        match self {
+            AnyDatabaseHandle::Redis(h) => h.write_many(guild_id, writes).await,
+            AnyDatabaseHandle::InMemory(h) => h.write_many(guild_id, writes).await,
+        }
+    }
+
+    async fn keys(&self) -> Result<Vec<(GuildId, String)>> {
+        match self {
+            AnyDatabaseHandle::Redis(h) => h.keys().await,
+            AnyDatabaseHandle::InMemory(h) => h.keys().await,
+        }
+    }
+
+    async fn dump_key(&self, guild_id: GuildId, key: &str) -> Result<Option<Entry>> {
+        match self {
+            AnyDatabaseHandle::Redis(h) => h.dump_key(guild_id, key).await,
+            AnyDatabaseHandle::InMemory(h) => h.dump_key(guild_id, key).await,
+        }
+    }
+
+    async fn restore_key(&self, guild_id: GuildId, key: &str, entry: Entry) -> Result<()> {
+        match self {
+            AnyDatabaseHandle::Redis(h) => h.restore_key(guild_id, key, entry).await,
+            AnyDatabaseHandle::InMemory(h) => h.restore_key(guild_id, key, entry).await,
+        }
+    }
+
+    async fn dump(&self, guild_id: GuildId) -> Result<BTreeMap<String, Entry>> {
+        match self {
+            AnyDatabaseHandle::Redis(h) => h.dump(guild_id).await,
+            AnyDatabaseHandle::InMemory(h) => h.dump(guild_id).await,
+        }
+    }
+
+    async fn scan_guilds(&self) -> Result<Vec<GuildId>> {
+        match self {
+            AnyDatabaseHandle::Redis(h) => h.scan_guilds().await,
+            AnyDatabaseHandle::InMemory(h) => h.scan_guilds().await,
+        }
+    }
+}
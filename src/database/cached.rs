@@ -0,0 +1,541 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::hash::Hash;
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, Mutex,
+};
+use std::time::{Duration, Instant};
+
+use super::{DatabaseHandle, Entry, Read, ReadResult, StorageUsage, Write};
+use crate::error::Result;
+
+use ::redis::{FromRedisValue, ToRedisArgs, Value};
+use anyhow::Context as _;
+use serde::Serialize;
+use serenity::model::id::GuildId;
+
+pub const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// Expired entries are dropped once the cache grows this large.
+const PRUNE_THRESHOLD: usize = 100_000;
+
+#[derive(Clone)]
+enum CachedData {
+    Value(Option<Value>),
+    Members(Vec<Vec<u8>>),
+}
+
+struct CacheEntry {
+    cached: CachedData,
+    expires_at: Instant,
+}
+
+#[derive(Default)]
+struct State {
+    entries: HashMap<(GuildId, String), CacheEntry>,
+    /// Bumped on every write, so that a value read before a write is not cached after it.
+    generation: u64,
+    /// The keys written with an expiry, until when they expire, which are not cached not to
+    /// outlive them.
+    expiring: HashMap<(GuildId, String), Instant>,
+}
+
+#[derive(Default)]
+struct Metrics {
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+/// Keeps the values and the sets read from the inner handle for a while, so that the settings read
+/// on every command do not go to the database each time.
+///
+/// The writes through this handle drop the entry of the key they write to, but the writes by
+/// another process sharing the database are seen only after the entry expires.
+#[derive(Clone)]
+pub struct Cached<H> {
+    inner: H,
+    ttl: Duration,
+    state: Arc<Mutex<State>>,
+    metrics: Arc<Metrics>,
+}
+
+impl<H> Cached<H> {
+    /// Does not cache anything if `ttl` is zero.
+    pub fn new(inner: H, ttl: Duration) -> Self {
+        Cached {
+            inner,
+            ttl,
+            state: Arc::new(Mutex::new(State::default())),
+            metrics: Arc::new(Metrics::default()),
+        }
+    }
+
+    /// The handle without the cache, to see how the database itself responds.
+    pub fn inner(&self) -> &H {
+        &self.inner
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.metrics.hits.load(Ordering::Relaxed),
+            misses: self.metrics.misses.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Returns the cached entry, or the current generation to pass to [`Cached::store`] on a miss.
+    fn lookup(&self, guild_id: GuildId, key: &str) -> std::result::Result<CachedData, u64> {
+        let state = self.state.lock().unwrap();
+        match state.entries.get(&(guild_id, key.to_owned())) {
+            Some(entry) if entry.expires_at > Instant::now() => {
+                self.metrics.hits.fetch_add(1, Ordering::Relaxed);
+                Ok(entry.cached.clone())
+            }
+            _ => {
+                self.metrics.misses.fetch_add(1, Ordering::Relaxed);
+                Err(state.generation)
+            }
+        }
+    }
+
+    fn store(&self, guild_id: GuildId, key: &str, generation: u64, cached: CachedData) {
+        if self.ttl.is_zero() {
+            return;
+        }
+        let mut state = self.state.lock().unwrap();
+        if state.generation != generation {
+            return;
+        }
+        let now = Instant::now();
+        let key = (guild_id, key.to_owned());
+        if matches!(state.expiring.get(&key), Some(&expires_at) if expires_at > now) {
+            return;
+        }
+        if state.entries.len() >= PRUNE_THRESHOLD {
+            state.entries.retain(|_, entry| entry.expires_at > now);
+            state.expiring.retain(|_, expires_at| *expires_at > now);
+        }
+        state.entries.insert(
+            key,
+            CacheEntry {
+                cached,
+                expires_at: now + self.ttl,
+            },
+        );
+    }
+
+    /// Keeps the key out of the cache until it expires in the database.
+    fn invalidate_expiring(&self, guild_id: GuildId, key: &str, ttl: Duration) {
+        self.invalidate(guild_id, key);
+        let mut state = self.state.lock().unwrap();
+        state
+            .expiring
+            .insert((guild_id, key.to_owned()), Instant::now() + ttl);
+    }
+
+    fn invalidate(&self, guild_id: GuildId, key: &str) {
+        let mut state = self.state.lock().unwrap();
+        state.generation += 1;
+        state.entries.remove(&(guild_id, key.to_owned()));
+    }
+}
+
+fn decode<T: FromRedisValue>(value: &Value) -> Result<T> {
+    let value = T::from_redis_value(value).context("cannot decode cached value")?;
+    Ok(value)
+}
+
+#[async_trait::async_trait]
+impl<H: DatabaseHandle + Send + Sync> DatabaseHandle for Cached<H> {
+    async fn get<T: FromRedisValue + Send>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+    ) -> Result<Option<T>> {
+        let value = match self.lookup(guild_id, key) {
+            Ok(CachedData::Value(value)) => value,
+            Ok(CachedData::Members(_)) => {
+                self.invalidate(guild_id, key);
+                return self.inner.get(guild_id, key).await;
+            }
+            Err(generation) => {
+                let value = self.inner.get::<Value>(guild_id, key).await?;
+                self.store(guild_id, key, generation, CachedData::Value(value.clone()));
+                value
+            }
+        };
+        value.as_ref().map(decode).transpose()
+    }
+
+    async fn set<T: ToRedisArgs + Send + Sync>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        value: T,
+    ) -> Result<()> {
+        let result = self.inner.set(guild_id, key, value).await;
+        self.invalidate(guild_id, key);
+        result
+    }
+
+    async fn set_with_ttl<T: ToRedisArgs + Send + Sync>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        value: T,
+        ttl: Duration,
+    ) -> Result<()> {
+        let result = self.inner.set_with_ttl(guild_id, key, value, ttl).await;
+        self.invalidate_expiring(guild_id, key, ttl);
+        result
+    }
+
+    async fn compare_and_set<T: ToRedisArgs + Send + Sync>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        expected: Option<T>,
+        value: T,
+    ) -> Result<bool> {
+        let result = self
+            .inner
+            .compare_and_set(guild_id, key, expected, value)
+            .await;
+        self.invalidate(guild_id, key);
+        result
+    }
+
+    async fn delete(&self, guild_id: GuildId, key: &str) -> Result<()> {
+        let result = self.inner.delete(guild_id, key).await;
+        self.invalidate(guild_id, key);
+        result
+    }
+
+    async fn list_push<T: ToRedisArgs + Send + Sync>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        value: T,
+        capacity: usize,
+    ) -> Result<()> {
+        self.inner.list_push(guild_id, key, value, capacity).await
+    }
+
+    async fn list_items<T: FromRedisValue + Send>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+    ) -> Result<Vec<T>> {
+        self.inner.list_items(guild_id, key).await
+    }
+
+    async fn hash_incr(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        field: &str,
+        delta: i64,
+    ) -> Result<i64> {
+        self.inner.hash_incr(guild_id, key, field, delta).await
+    }
+
+    async fn hash_incr_with_expiry(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        field: &str,
+        delta: i64,
+        expiry: Duration,
+    ) -> Result<i64> {
+        self.inner
+            .hash_incr_with_expiry(guild_id, key, field, delta, expiry)
+            .await
+    }
+
+    async fn hash_items<T: FromRedisValue + Send>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+    ) -> Result<HashMap<String, T>> {
+        self.inner.hash_items(guild_id, key).await
+    }
+
+    async fn set_members<T: Eq + Hash + FromRedisValue + Send>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+    ) -> Result<HashSet<T>> {
+        let members = match self.lookup(guild_id, key) {
+            Ok(CachedData::Members(members)) => members,
+            Ok(CachedData::Value(_)) => {
+                self.invalidate(guild_id, key);
+                return self.inner.set_members(guild_id, key).await;
+            }
+            Err(generation) => {
+                let members: Vec<Vec<u8>> = self
+                    .inner
+                    .set_members::<Vec<u8>>(guild_id, key)
+                    .await?
+                    .into_iter()
+                    .collect();
+                self.store(
+                    guild_id,
+                    key,
+                    generation,
+                    CachedData::Members(members.clone()),
+                );
+                members
+            }
+        };
+        members
+            .into_iter()
+            .map(|member| decode(&Value::Data(member)))
+            .collect()
+    }
+
+    async fn set_add<T: ToRedisArgs + Send + Sync>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        value: T,
+    ) -> Result<bool> {
+        let result = self.inner.set_add(guild_id, key, value).await;
+        self.invalidate(guild_id, key);
+        result
+    }
+
+    async fn set_remove<T: ToRedisArgs + Send + Sync>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        value: T,
+    ) -> Result<bool> {
+        let result = self.inner.set_remove(guild_id, key, value).await;
+        self.invalidate(guild_id, key);
+        result
+    }
+
+    async fn incr_with_expiry(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        expiry: Duration,
+    ) -> Result<i64> {
+        self.inner.incr_with_expiry(guild_id, key, expiry).await
+    }
+
+    async fn usage(&self, guild_id: GuildId) -> Result<StorageUsage> {
+        self.inner.usage(guild_id).await
+    }
+
+    async fn read_many(&self, guild_id: GuildId, reads: &[Read<'_>]) -> Result<Vec<ReadResult>> {
+        let mut results = Vec::with_capacity(reads.len());
+        let mut misses = Vec::new();
+        for (i, read) in reads.iter().enumerate() {
+            let key = match read {
+                Read::Value(key) | Read::Members(key) => key,
+            };
+            match (read, self.lookup(guild_id, key)) {
+                (Read::Value(_), Ok(CachedData::Value(value))) => {
+                    results.push(Some(ReadResult::Value(value)))
+                }
+                (Read::Members(_), Ok(CachedData::Members(members))) => {
+                    results.push(Some(ReadResult::Members(members)))
+                }
+                (_, Ok(_)) => {
+                    self.invalidate(guild_id, key);
+                    results.push(None);
+                    misses.push((i, *read, self.state.lock().unwrap().generation));
+                }
+                (_, Err(generation)) => {
+                    results.push(None);
+                    misses.push((i, *read, generation));
+                }
+            }
+        }
+        if !misses.is_empty() {
+            let reads: Vec<_> = misses.iter().map(|(_, read, _)| *read).collect();
+            let fetched = self.inner.read_many(guild_id, &reads).await?;
+            for ((i, read, generation), result) in misses.into_iter().zip(fetched) {
+                let (key, cached) = match (read, &result) {
+                    (Read::Value(key), ReadResult::Value(value)) => {
+                        (key, CachedData::Value(value.clone()))
+                    }
+                    (Read::Members(key), ReadResult::Members(members)) => {
+                        (key, CachedData::Members(members.clone()))
+                    }
+                    _ => continue,
+                };
+                self.store(guild_id, key, generation, cached);
+                results[i] = Some(result);
+            }
+        }
+        results
+            .into_iter()
+            .map(|result| result.context("missing result from the database"))
+            .collect::<anyhow::Result<_>>()
+            .map_err(Into::into)
+    }
+
+    async fn keys(&self) -> Result<Vec<(GuildId, String)>> {
+        self.inner.keys().await
+    }
+
+    async fn dump_key(&self, guild_id: GuildId, key: &str) -> Result<Option<Entry>> {
+        self.inner.dump_key(guild_id, key).await
+    }
+
+    async fn write_many(&self, guild_id: GuildId, writes: &[Write<'_>]) -> Result<()> {
+        let result = self.inner.write_many(guild_id, writes).await;
+        for write in writes {
+            match write {
+                Write::Put(key, Entry::Expiring(_, ttl)) => {
+                    self.invalidate_expiring(guild_id, key, *ttl)
+                }
+                Write::Put(key, _) | Write::Delete(key) => self.invalidate(guild_id, key),
+            }
+        }
+        result
+    }
+
+    async fn restore_key(&self, guild_id: GuildId, key: &str, entry: Entry) -> Result<()> {
+        let ttl = match &entry {
+            Entry::Expiring(_, ttl) => Some(*ttl),
+            _ => None,
+        };
+        let result = self.inner.restore_key(guild_id, key, entry).await;
+        match ttl {
+            Some(ttl) => self.invalidate_expiring(guild_id, key, ttl),
+            None => self.invalidate(guild_id, key),
+        }
+        result
+    }
+
+    async fn dump(&self, guild_id: GuildId) -> Result<BTreeMap<String, Entry>> {
+        self.inner.dump(guild_id).await
+    }
+
+    async fn scan_guilds(&self) -> Result<Vec<GuildId>> {
+        self.inner.scan_guilds().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CacheStats, Cached, DEFAULT_CACHE_TTL};
+    use crate::database::{DatabaseHandle, InMemoryHandle};
+    use crate::test::MOCK_GUILD_ID;
+
+    use std::collections::HashSet;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_read_through() {
+        let inner = InMemoryHandle::new();
+        let cached = Cached::new(inner.clone(), DEFAULT_CACHE_TTL);
+
+        assert_eq!(
+            cached
+                .get::<String>(MOCK_GUILD_ID, "timezone")
+                .await
+                .unwrap(),
+            None
+        );
+        // only the writes through the cache are seen before the entry expires
+        inner.set(MOCK_GUILD_ID, "timezone", "UTC").await.unwrap();
+        assert_eq!(
+            cached
+                .get::<String>(MOCK_GUILD_ID, "timezone")
+                .await
+                .unwrap(),
+            None
+        );
+        cached
+            .set(MOCK_GUILD_ID, "timezone", "Asia/Tokyo")
+            .await
+            .unwrap();
+        assert_eq!(
+            cached
+                .get::<String>(MOCK_GUILD_ID, "timezone")
+                .await
+                .unwrap(),
+            Some("Asia/Tokyo".to_owned())
+        );
+        assert_eq!(cached.stats(), CacheStats { hits: 1, misses: 2 });
+    }
+
+    #[tokio::test]
+    async fn test_expiring_not_cached() {
+        let cached = Cached::new(InMemoryHandle::new(), DEFAULT_CACHE_TTL);
+        let ttl = Duration::from_millis(50);
+        cached
+            .set_flag_with_ttl(MOCK_GUILD_ID, "paused", true, ttl)
+            .await
+            .unwrap();
+        assert!(cached
+            .get_flag(MOCK_GUILD_ID, "paused", false)
+            .await
+            .unwrap());
+        tokio::time::sleep(ttl).await;
+        assert!(!cached
+            .get_flag(MOCK_GUILD_ID, "paused", false)
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_set_members() {
+        let cached = Cached::new(InMemoryHandle::new(), DEFAULT_CACHE_TTL);
+        cached
+            .set_add(MOCK_GUILD_ID, "reminders", 300)
+            .await
+            .unwrap();
+        assert_eq!(
+            cached
+                .set_members::<u32>(MOCK_GUILD_ID, "reminders")
+                .await
+                .unwrap(),
+            HashSet::from([300])
+        );
+        cached
+            .set_add(MOCK_GUILD_ID, "reminders", 60)
+            .await
+            .unwrap();
+        cached
+            .set_members::<u32>(MOCK_GUILD_ID, "reminders")
+            .await
+            .unwrap();
+        assert_eq!(
+            cached
+                .set_members::<u32>(MOCK_GUILD_ID, "reminders")
+                .await
+                .unwrap(),
+            HashSet::from([300, 60])
+        );
+        assert_eq!(cached.stats(), CacheStats { hits: 1, misses: 2 });
+    }
+
+    #[tokio::test]
+    async fn test_expiry() {
+        let inner = InMemoryHandle::new();
+        let cached = Cached::new(inner.clone(), Duration::from_millis(1));
+        cached
+            .get_flag(MOCK_GUILD_ID, "countdown", false)
+            .await
+            .unwrap();
+        inner
+            .set_flag(MOCK_GUILD_ID, "countdown", true)
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        assert!(cached
+            .get_flag(MOCK_GUILD_ID, "countdown", false)
+            .await
+            .unwrap());
+    }
+}
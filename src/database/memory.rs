@@ -0,0 +1,711 @@
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use super::{DatabaseHandle, Entry, Read, ReadResult, StorageUsage, Write};
+use crate::error::Result;
+
+use ::redis::{FromRedisValue, ToRedisArgs, Value};
+use anyhow::Context as _;
+use futures::lock::{Mutex, MutexGuard};
+use serenity::model::id::GuildId;
+
+type Key = (GuildId, String);
+
+/// A value, which reads as absent from `expires_at` if any, as a key expired in Redis.
+struct StoredValue {
+    data: Vec<u8>,
+    expires_at: Option<Instant>,
+}
+
+impl StoredValue {
+    fn new(data: Vec<u8>) -> Self {
+        StoredValue {
+            data,
+            expires_at: None,
+        }
+    }
+
+    fn expiring(data: Vec<u8>, ttl: Duration) -> Self {
+        StoredValue {
+            data,
+            expires_at: Some(Instant::now() + ttl),
+        }
+    }
+
+    fn is_live(&self) -> bool {
+        self.expires_at
+            .map_or(true, |expires_at| expires_at > Instant::now())
+    }
+}
+
+fn live_value<'a>(values: &'a HashMap<Key, StoredValue>, key: &Key) -> Option<&'a StoredValue> {
+    values.get(key).filter(|value| value.is_live())
+}
+
+#[derive(Clone, Default)]
+pub struct InMemoryHandle {
+    values: Arc<Mutex<HashMap<Key, StoredValue>>>,
+    sets: Arc<Mutex<HashMap<Key, HashSet<Vec<u8>>>>>,
+    lists: Arc<Mutex<HashMap<Key, VecDeque<Vec<u8>>>>>,
+    hashes: Arc<Mutex<HashMap<Key, HashMap<String, i64>>>>,
+    /// Until when the hashes written with an expiry are kept.
+    hash_expiry: Arc<Mutex<HashMap<Key, Instant>>>,
+}
+
+impl InMemoryHandle {
+    pub fn new() -> Self {
+        InMemoryHandle::default()
+    }
+
+    /// Locks the hashes after dropping the expired ones, as Redis would have deleted them.
+    async fn live_hashes(&self) -> MutexGuard<'_, HashMap<Key, HashMap<String, i64>>> {
+        let mut hashes = self.hashes.lock().await;
+        let now = Instant::now();
+        self.hash_expiry.lock().await.retain(|key, expires_at| {
+            if *expires_at > now {
+                return true;
+            }
+            hashes.remove(key);
+            false
+        });
+        hashes
+    }
+}
+
+fn scoped_key(guild_id: GuildId, key: &str) -> Key {
+    (guild_id, key.to_owned())
+}
+
+fn encode<T: ToRedisArgs>(value: &T) -> Vec<u8> {
+    value.to_redis_args().concat()
+}
+
+fn decode<T: FromRedisValue>(data: &[u8]) -> Result<T> {
+    let value =
+        T::from_redis_value(&Value::Data(data.to_vec())).context("cannot decode stored value")?;
+    Ok(value)
+}
+
+#[async_trait::async_trait]
+impl DatabaseHandle for InMemoryHandle {
+    async fn get<T: FromRedisValue + Send>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+    ) -> Result<Option<T>> {
+        match live_value(&*self.values.lock().await, &scoped_key(guild_id, key)) {
+            Some(value) => decode(&value.data).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    async fn set<T: ToRedisArgs + Send + Sync>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        value: T,
+    ) -> Result<()> {
+        self.values
+            .lock()
+            .await
+            .insert(scoped_key(guild_id, key), StoredValue::new(encode(&value)));
+        Ok(())
+    }
+
+    async fn set_with_ttl<T: ToRedisArgs + Send + Sync>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        value: T,
+        ttl: Duration,
+    ) -> Result<()> {
+        self.values.lock().await.insert(
+            scoped_key(guild_id, key),
+            StoredValue::expiring(encode(&value), ttl),
+        );
+        Ok(())
+    }
+
+    async fn compare_and_set<T: ToRedisArgs + Send + Sync>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        expected: Option<T>,
+        value: T,
+    ) -> Result<bool> {
+        let mut values = self.values.lock().await;
+        let key = scoped_key(guild_id, key);
+        let current = live_value(&values, &key).map(|value| &value.data);
+        if current != expected.as_ref().map(encode).as_ref() {
+            return Ok(false);
+        }
+        values.insert(key, StoredValue::new(encode(&value)));
+        Ok(true)
+    }
+
+    async fn delete(&self, guild_id: GuildId, key: &str) -> Result<()> {
+        let key = scoped_key(guild_id, key);
+        self.values.lock().await.remove(&key);
+        self.sets.lock().await.remove(&key);
+        self.lists.lock().await.remove(&key);
+        self.live_hashes().await.remove(&key);
+        self.hash_expiry.lock().await.remove(&key);
+        Ok(())
+    }
+
+    async fn list_push<T: ToRedisArgs + Send + Sync>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        value: T,
+        capacity: usize,
+    ) -> Result<()> {
+        let mut lists = self.lists.lock().await;
+        let list = lists.entry(scoped_key(guild_id, key)).or_default();
+        list.push_front(encode(&value));
+        list.truncate(capacity);
+        Ok(())
+    }
+
+    async fn list_items<T: FromRedisValue + Send>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+    ) -> Result<Vec<T>> {
+        match self.lists.lock().await.get(&scoped_key(guild_id, key)) {
+            Some(list) => list.iter().map(|data| decode(data)).collect(),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    async fn hash_incr(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        field: &str,
+        delta: i64,
+    ) -> Result<i64> {
+        let mut hashes = self.live_hashes().await;
+        let value = hashes
+            .entry(scoped_key(guild_id, key))
+            .or_default()
+            .entry(field.to_owned())
+            .or_default();
+        *value += delta;
+        Ok(*value)
+    }
+
+    async fn hash_incr_with_expiry(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        field: &str,
+        delta: i64,
+        expiry: Duration,
+    ) -> Result<i64> {
+        let mut hashes = self.live_hashes().await;
+        let key = scoped_key(guild_id, key);
+        let value = hashes
+            .entry(key.clone())
+            .or_default()
+            .entry(field.to_owned())
+            .or_default();
+        *value += delta;
+        let value = *value;
+        self.hash_expiry
+            .lock()
+            .await
+            .insert(key, Instant::now() + expiry);
+        Ok(value)
+    }
+
+    async fn hash_items<T: FromRedisValue + Send>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+    ) -> Result<HashMap<String, T>> {
+        match self.live_hashes().await.get(&scoped_key(guild_id, key)) {
+            Some(hash) => hash
+                .iter()
+                .map(|(field, value)| Ok((field.clone(), decode(&encode(value))?)))
+                .collect(),
+            None => Ok(HashMap::new()),
+        }
+    }
+
+    async fn set_members<T: Eq + Hash + FromRedisValue + Send>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+    ) -> Result<HashSet<T>> {
+        match self.sets.lock().await.get(&scoped_key(guild_id, key)) {
+            Some(set) => set.iter().map(|data| decode(data)).collect(),
+            None => Ok(HashSet::new()),
+        }
+    }
+
+    async fn set_add<T: ToRedisArgs + Send + Sync>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        value: T,
+    ) -> Result<bool> {
+        Ok(self
+            .sets
+            .lock()
+            .await
+            .entry(scoped_key(guild_id, key))
+            .or_default()
+            .insert(encode(&value)))
+    }
+
+    async fn set_remove<T: ToRedisArgs + Send + Sync>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        value: T,
+    ) -> Result<bool> {
+        Ok(
+            match self.sets.lock().await.get_mut(&scoped_key(guild_id, key)) {
+                Some(set) => set.remove(&encode(&value)),
+                None => false,
+            },
+        )
+    }
+
+    async fn incr_with_expiry(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        expiry: Duration,
+    ) -> Result<i64> {
+        let mut values = self.values.lock().await;
+        let key = scoped_key(guild_id, key);
+        // as in Redis, the expiry is set when the count is created and is kept by the increments
+        let (count, expires_at) = match live_value(&values, &key) {
+            Some(value) => (decode::<i64>(&value.data)?, value.expires_at),
+            None => (0, Some(Instant::now() + expiry)),
+        };
+        values.insert(
+            key,
+            StoredValue {
+                data: encode(&(count + 1)),
+                expires_at,
+            },
+        );
+        Ok(count + 1)
+    }
+
+    async fn usage(&self, guild_id: GuildId) -> Result<StorageUsage> {
+        let mut usage = StorageUsage::default();
+        let mut add = |key: &Key, bytes: usize| {
+            usage.keys += 1;
+            usage.bytes += (key.1.len() + bytes) as u64;
+        };
+        for (key, value) in self.values.lock().await.iter() {
+            if key.0 == guild_id && value.is_live() {
+                add(key, value.data.len());
+            }
+        }
+        for (key, set) in self.sets.lock().await.iter() {
+            if key.0 == guild_id {
+                add(key, set.iter().map(Vec::len).sum());
+            }
+        }
+        for (key, list) in self.lists.lock().await.iter() {
+            if key.0 == guild_id {
+                add(key, list.iter().map(Vec::len).sum());
+            }
+        }
+        for (key, hash) in self.live_hashes().await.iter() {
+            if key.0 == guild_id {
+                add(key, hash.keys().map(|field| field.len() + 8).sum());
+            }
+        }
+        Ok(usage)
+    }
+
+    async fn read_many(&self, guild_id: GuildId, reads: &[Read<'_>]) -> Result<Vec<ReadResult>> {
+        let mut results = Vec::with_capacity(reads.len());
+        for read in reads {
+            results.push(match read {
+                Read::Value(key) => ReadResult::Value(self.get(guild_id, key).await?),
+                Read::Members(key) => ReadResult::Members(
+                    self.set_members(guild_id, key).await?.into_iter().collect(),
+                ),
+            });
+        }
+        Ok(results)
+    }
+
+    async fn write_many(&self, guild_id: GuildId, writes: &[Write<'_>]) -> Result<()> {
+        // none of the writes fails in memory, so they are applied either all or none as well
+        for write in writes {
+            match write {
+                Write::Put(key, entry) => self.restore_key(guild_id, key, entry.clone()).await?,
+                Write::Delete(key) => self.delete(guild_id, key).await?,
+            }
+        }
+        Ok(())
+    }
+
+    async fn keys(&self) -> Result<Vec<(GuildId, String)>> {
+        let mut keys = Vec::new();
+        keys.extend(
+            self.values
+                .lock()
+                .await
+                .iter()
+                .filter(|(_, value)| value.is_live())
+                .map(|(key, _)| key.clone()),
+        );
+        keys.extend(self.sets.lock().await.keys().cloned());
+        keys.extend(self.lists.lock().await.keys().cloned());
+        keys.extend(self.live_hashes().await.keys().cloned());
+        Ok(keys)
+    }
+
+    async fn dump_key(&self, guild_id: GuildId, key: &str) -> Result<Option<Entry>> {
+        let key = scoped_key(guild_id, key);
+        if let Some(value) = live_value(&*self.values.lock().await, &key) {
+            let data = value.data.clone();
+            return Ok(Some(match value.expires_at {
+                Some(expires_at) => {
+                    Entry::Expiring(data, expires_at.saturating_duration_since(Instant::now()))
+                }
+                None => Entry::Value(data),
+            }));
+        }
+        if let Some(set) = self.sets.lock().await.get(&key) {
+            return Ok(Some(Entry::Set(set.clone())));
+        }
+        if let Some(list) = self.lists.lock().await.get(&key) {
+            return Ok(Some(Entry::List(list.iter().cloned().collect())));
+        }
+        Ok(self
+            .live_hashes()
+            .await
+            .get(&key)
+            .map(|hash| Entry::Hash(hash.clone())))
+    }
+
+    async fn restore_key(&self, guild_id: GuildId, key: &str, entry: Entry) -> Result<()> {
+        self.delete(guild_id, key).await?;
+        let key = scoped_key(guild_id, key);
+        match entry {
+            Entry::Value(data) => {
+                self.values.lock().await.insert(key, StoredValue::new(data));
+            }
+            Entry::Expiring(data, ttl) => {
+                self.values
+                    .lock()
+                    .await
+                    .insert(key, StoredValue::expiring(data, ttl));
+            }
+            Entry::List(items) => {
+                self.lists.lock().await.insert(key, items.into());
+            }
+            Entry::Hash(hash) => {
+                self.live_hashes().await.insert(key, hash);
+            }
+            Entry::Set(set) => {
+                self.sets.lock().await.insert(key, set);
+            }
+        }
+        Ok(())
+    }
+
+    async fn dump(&self, guild_id: GuildId) -> Result<BTreeMap<String, Entry>> {
+        let mut entries = BTreeMap::new();
+        for (key_guild_id, key) in self.keys().await? {
+            if key_guild_id != guild_id {
+                continue;
+            }
+            if let Some(entry) = self.dump_key(guild_id, &key).await? {
+                entries.insert(key, entry);
+            }
+        }
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::InMemoryHandle;
+    use crate::database::{DatabaseHandle, Entry, Read, StorageUsage, Write};
+    use crate::model::reminder::Reminder;
+
+    use std::collections::HashSet;
+
+    use serenity::model::id::GuildId;
+    use std::time::Duration;
+
+    const GUILD_1: GuildId = GuildId::new(1);
+    const GUILD_2: GuildId = GuildId::new(2);
+
+    #[tokio::test]
+    async fn test_get_set() {
+        let db = InMemoryHandle::new();
+        assert_eq!(db.get::<String>(GUILD_1, "timezone").await.unwrap(), None);
+        db.set(GUILD_1, "timezone", "UTC").await.unwrap();
+        assert_eq!(
+            db.get::<String>(GUILD_1, "timezone").await.unwrap(),
+            Some("UTC".to_owned())
+        );
+        assert_eq!(db.get::<String>(GUILD_2, "timezone").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_compare_and_set() {
+        let db = InMemoryHandle::new();
+        assert!(db
+            .compare_and_set(GUILD_1, "timezone", None, "UTC")
+            .await
+            .unwrap());
+        assert!(!db
+            .compare_and_set(GUILD_1, "timezone", None, "Asia/Tokyo")
+            .await
+            .unwrap());
+        assert!(!db
+            .compare_and_set(GUILD_1, "timezone", Some("Europe/London"), "Asia/Tokyo")
+            .await
+            .unwrap());
+        assert!(db
+            .compare_and_set(GUILD_1, "timezone", Some("UTC"), "Asia/Tokyo")
+            .await
+            .unwrap());
+        assert_eq!(
+            db.get::<String>(GUILD_1, "timezone").await.unwrap(),
+            Some("Asia/Tokyo".to_owned())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_flag() {
+        let db = InMemoryHandle::new();
+        assert!(db.get_flag(GUILD_1, "flag", true).await.unwrap());
+        db.set_flag(GUILD_1, "flag", false).await.unwrap();
+        assert!(!db.get_flag(GUILD_1, "flag", true).await.unwrap());
+        assert_eq!(db.get::<u32>(GUILD_1, "flag").await.unwrap(), Some(0));
+    }
+
+    #[tokio::test]
+    async fn test_read_many() {
+        let db = InMemoryHandle::new();
+        db.set_flag(GUILD_1, "flag", false).await.unwrap();
+        db.set_add(GUILD_1, "reminders", Reminder::before_minutes(5))
+            .await
+            .unwrap();
+        let [flag, missing, reminders] = <[_; 3]>::try_from(
+            db.read_many(
+                GUILD_1,
+                &[
+                    Read::Value("flag"),
+                    Read::Value("timezone"),
+                    Read::Members("reminders"),
+                ],
+            )
+            .await
+            .unwrap(),
+        )
+        .unwrap();
+        assert!(!flag.flag(true).unwrap());
+        assert_eq!(missing.value::<String>().unwrap(), None);
+        assert_eq!(
+            reminders.members::<Reminder>().unwrap(),
+            HashSet::from([Reminder::before_minutes(5)])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_write_many() {
+        let db = InMemoryHandle::new();
+        db.set(GUILD_1, "command_prefix", "!k").await.unwrap();
+        db.set_add(GUILD_1, "reminders", Reminder::before_minutes(5))
+            .await
+            .unwrap();
+        db.write_many(
+            GUILD_1,
+            &[
+                Write::Put("timezone", Entry::value("UTC")),
+                Write::Delete("command_prefix"),
+                Write::Put("reminders", Entry::set([Reminder::before_minutes(10)])),
+            ],
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            db.get::<String>(GUILD_1, "timezone").await.unwrap(),
+            Some("UTC".to_owned())
+        );
+        assert_eq!(
+            db.get::<String>(GUILD_1, "command_prefix").await.unwrap(),
+            None
+        );
+        assert_eq!(
+            db.set_members::<Reminder>(GUILD_1, "reminders")
+                .await
+                .unwrap(),
+            HashSet::from([Reminder::before_minutes(10)])
+        );
+        assert_eq!(db.get::<String>(GUILD_2, "timezone").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_set_members() {
+        let db = InMemoryHandle::new();
+        let reminder = Reminder::before_minutes(5);
+        assert!(db.set_add(GUILD_1, "reminders", reminder).await.unwrap());
+        assert!(!db.set_add(GUILD_1, "reminders", reminder).await.unwrap());
+        assert_eq!(
+            db.set_members::<Reminder>(GUILD_1, "reminders")
+                .await
+                .unwrap(),
+            vec![reminder].into_iter().collect()
+        );
+        assert!(db
+            .set_members::<Reminder>(GUILD_2, "reminders")
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(db.set_remove(GUILD_1, "reminders", reminder).await.unwrap());
+        assert!(!db.set_remove(GUILD_1, "reminders", reminder).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_list() {
+        let db = InMemoryHandle::new();
+        for i in 0..5u32 {
+            db.list_push(GUILD_1, "list", i, 3).await.unwrap();
+        }
+        assert_eq!(
+            db.list_items::<u32>(GUILD_1, "list").await.unwrap(),
+            vec![4, 3, 2]
+        );
+        assert!(db
+            .list_items::<u32>(GUILD_2, "list")
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_hash() {
+        let db = InMemoryHandle::new();
+        assert_eq!(db.hash_incr(GUILD_1, "hash", "a", 1).await.unwrap(), 1);
+        assert_eq!(db.hash_incr(GUILD_1, "hash", "a", 2).await.unwrap(), 3);
+        assert_eq!(db.hash_incr(GUILD_1, "hash", "b", 1).await.unwrap(), 1);
+        let items = db.hash_items::<u64>(GUILD_1, "hash").await.unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items["a"], 3);
+        assert!(db
+            .hash_items::<u64>(GUILD_2, "hash")
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_incr_with_expiry() {
+        let db = InMemoryHandle::new();
+        let expiry = Duration::from_millis(50);
+        assert_eq!(db.incr_with_expiry(GUILD_1, "c", expiry).await.unwrap(), 1);
+        assert_eq!(db.incr_with_expiry(GUILD_1, "c", expiry).await.unwrap(), 2);
+        assert_eq!(db.incr_with_expiry(GUILD_2, "c", expiry).await.unwrap(), 1);
+        tokio::time::sleep(expiry).await;
+        assert_eq!(db.incr_with_expiry(GUILD_1, "c", expiry).await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_hash_incr_with_expiry() {
+        let db = InMemoryHandle::new();
+        let expiry = Duration::from_millis(50);
+        assert_eq!(
+            db.hash_incr_with_expiry(GUILD_1, "h", "a", 2, expiry)
+                .await
+                .unwrap(),
+            2
+        );
+        assert_eq!(
+            db.hash_incr_with_expiry(GUILD_1, "h", "a", 3, expiry)
+                .await
+                .unwrap(),
+            5
+        );
+        assert_eq!(db.keys().await.unwrap().len(), 1);
+        tokio::time::sleep(expiry).await;
+        assert!(db.hash_items::<i64>(GUILD_1, "h").await.unwrap().is_empty());
+        assert!(db.keys().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_set_with_ttl() {
+        let db = InMemoryHandle::new();
+        let ttl = Duration::from_millis(50);
+        db.set_flag_with_ttl(GUILD_1, "paused", true, ttl)
+            .await
+            .unwrap();
+        assert!(db.get_flag(GUILD_1, "paused", false).await.unwrap());
+        assert!(matches!(
+            db.dump_key(GUILD_1, "paused").await.unwrap(),
+            Some(Entry::Expiring(_, left)) if left <= ttl
+        ));
+        tokio::time::sleep(ttl).await;
+        assert!(!db.get_flag(GUILD_1, "paused", false).await.unwrap());
+        assert!(db.keys().await.unwrap().is_empty());
+        assert_eq!(db.usage(GUILD_1).await.unwrap(), StorageUsage::default());
+
+        // a value set without the expiry stays
+        db.set_with_ttl(GUILD_1, "timezone", "UTC", ttl)
+            .await
+            .unwrap();
+        db.set(GUILD_1, "timezone", "UTC").await.unwrap();
+        tokio::time::sleep(ttl).await;
+        assert_eq!(
+            db.get::<String>(GUILD_1, "timezone").await.unwrap(),
+            Some("UTC".to_owned())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dump() {
+        let db = InMemoryHandle::new();
+        db.set(GUILD_1, "timezone", "UTC").await.unwrap();
+        db.hash_incr(GUILD_1, "stats", "kaisan", 1).await.unwrap();
+        db.list_push(GUILD_2, "list", 1u32, 3).await.unwrap();
+
+        assert_eq!(db.scan_guilds().await.unwrap(), vec![GUILD_1, GUILD_2]);
+        let dump = db.dump(GUILD_1).await.unwrap();
+        assert_eq!(dump.keys().collect::<Vec<_>>(), vec!["stats", "timezone"]);
+        assert_eq!(dump["timezone"], Entry::Value(b"UTC".to_vec()));
+
+        let other = InMemoryHandle::new();
+        other
+            .restore_key(GUILD_1, "stats", dump["stats"].clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            other.hash_items::<i64>(GUILD_1, "stats").await.unwrap()["kaisan"],
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_usage() {
+        let db = InMemoryHandle::new();
+        db.set(GUILD_1, "timezone", "UTC").await.unwrap();
+        db.list_push(GUILD_1, "list", 1u32, 3).await.unwrap();
+        db.set(GUILD_2, "timezone", "Asia/Tokyo").await.unwrap();
+
+        let usage = db.usage(GUILD_1).await.unwrap();
+        assert_eq!(usage.keys, 2);
+        assert_eq!(
+            usage.bytes,
+            ("timezone".len() + 3 + "list".len() + 1) as u64
+        );
+        db.delete(GUILD_2, "timezone").await.unwrap();
+        assert_eq!(db.usage(GUILD_2).await.unwrap(), StorageUsage::default());
+    }
+}
@@ -0,0 +1,104 @@
+use super::DatabaseHandle;
+use crate::error::Result;
+
+/// What [`migrate`] copied.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MigrationSummary {
+    pub guilds: usize,
+    pub keys: usize,
+}
+
+/// Copies every key of every guild from `from` to `to`, such as to another prefix, replacing the
+/// keys of the same names in `to`. The keys are left in `from`.
+///
+/// The keys written to `from` while this runs may or may not be copied, so the bot should be
+/// stopped meanwhile.
+pub async fn migrate<F, T>(from: &F, to: &T) -> Result<MigrationSummary>
+where
+    F: DatabaseHandle + Sync,
+    T: DatabaseHandle + Sync,
+{
+    let mut summary = MigrationSummary::default();
+    for guild_id in from.scan_guilds().await? {
+        let entries = from.dump(guild_id).await?;
+        if entries.is_empty() {
+            // the keys are deleted since the guild was listed
+            continue;
+        }
+        summary.guilds += 1;
+        summary.keys += entries.len();
+        for (key, entry) in entries {
+            to.restore_key(guild_id, &key, entry).await?;
+        }
+        tracing::debug!(%guild_id, "migrated guild");
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{migrate, MigrationSummary};
+    use crate::database::{DatabaseHandle, InMemoryHandle};
+    use crate::model::reminder::Reminder;
+
+    use std::collections::HashSet;
+    use std::time::Duration;
+
+    use serenity::model::id::GuildId;
+
+    const GUILD_1: GuildId = GuildId::new(1);
+    const GUILD_2: GuildId = GuildId::new(2);
+
+    #[tokio::test]
+    async fn test_migrate() {
+        let from = InMemoryHandle::new();
+        from.set(GUILD_1, "timezone", "Asia/Tokyo").await.unwrap();
+        from.set_add(GUILD_1, "reminders", Reminder::before_minutes(5))
+            .await
+            .unwrap();
+        for i in 0..3u32 {
+            from.list_push(GUILD_1, "history", i, 10).await.unwrap();
+        }
+        from.hash_incr(GUILD_2, "stats", "kaisan", 2).await.unwrap();
+        from.incr_with_expiry(GUILD_2, "rate", Duration::from_secs(60))
+            .await
+            .unwrap();
+
+        let to = InMemoryHandle::new();
+        to.set(GUILD_1, "timezone", "UTC").await.unwrap();
+        assert_eq!(
+            migrate(&from, &to).await.unwrap(),
+            MigrationSummary { guilds: 2, keys: 5 }
+        );
+
+        assert_eq!(
+            to.get::<String>(GUILD_1, "timezone").await.unwrap(),
+            Some("Asia/Tokyo".to_owned())
+        );
+        assert_eq!(
+            to.set_members::<Reminder>(GUILD_1, "reminders")
+                .await
+                .unwrap(),
+            HashSet::from([Reminder::before_minutes(5)])
+        );
+        assert_eq!(
+            to.list_items::<u32>(GUILD_1, "history").await.unwrap(),
+            vec![2, 1, 0]
+        );
+        assert_eq!(
+            to.hash_items::<i64>(GUILD_2, "stats").await.unwrap()["kaisan"],
+            2
+        );
+        assert_eq!(
+            to.incr_with_expiry(GUILD_2, "rate", Duration::from_secs(60))
+                .await
+                .unwrap(),
+            2
+        );
+        // the source is left as is
+        assert_eq!(
+            from.get::<String>(GUILD_1, "timezone").await.unwrap(),
+            Some("Asia/Tokyo".to_owned())
+        );
+    }
+}
@@ -0,0 +1,627 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::hash::Hash;
+use std::time::Duration;
+
+use super::{DatabaseHandle, Entry, Read, ReadResult, StorageUsage, Write};
+use crate::error::Result;
+
+use ::redis::{
+    aio::ConnectionLike,
+    cluster_async::ClusterConnection,
+    cluster_routing::{get_slot, Route, RoutingInfo, SingleNodeRoutingInfo, SlotAddr},
+    AsyncCommands, Cmd, FromRedisValue, Pipeline, RedisFuture, ToRedisArgs, Value,
+};
+use anyhow::Context as _;
+use serenity::model::id::GuildId;
+
+mod connect;
+
+pub use connect::{RedisOptions, RedisTopology};
+
+/// `ARGV[1]` is the new value, and `ARGV[2]` is the expected one, which is omitted if the key is
+/// expected to be absent.
+const COMPARE_AND_SET_SCRIPT: &str = r#"
+local current = redis.call('GET', KEYS[1])
+if current ~= (ARGV[2] or false) then
+  return 0
+end
+redis.call('SET', KEYS[1], ARGV[1])
+return 1
+"#;
+
+#[derive(Clone)]
+enum Backend {
+    Single(deadpool_redis::Pool),
+    Cluster(ClusterConnection),
+}
+
+enum Connection {
+    Single(deadpool_redis::Connection),
+    Cluster(ClusterConnection),
+}
+
+impl ConnectionLike for Connection {
+    fn req_packed_command<'a>(&'a mut self, cmd: &'a Cmd) -> RedisFuture<'a, Value> {
+        match self {
+            Connection::Single(conn) => conn.req_packed_command(cmd),
+            Connection::Cluster(conn) => conn.req_packed_command(cmd),
+        }
+    }
+
+    fn req_packed_commands<'a>(
+        &'a mut self,
+        cmd: &'a Pipeline,
+        offset: usize,
+        count: usize,
+    ) -> RedisFuture<'a, Vec<Value>> {
+        match self {
+            Connection::Single(conn) => conn.req_packed_commands(cmd, offset, count),
+            Connection::Cluster(conn) => conn.req_packed_commands(cmd, offset, count),
+        }
+    }
+
+    fn get_db(&self) -> i64 {
+        match self {
+            Connection::Single(conn) => conn.get_db(),
+            Connection::Cluster(conn) => conn.get_db(),
+        }
+    }
+}
+
+/// The key of `key` for the guild. On a cluster, the guild ID is a hash tag so that all the keys
+/// of a guild are in the same slot, which the pipelines and the scripts need.
+fn guild_key(prefix: &str, guild_id: GuildId, key: &str, cluster: bool) -> String {
+    if cluster {
+        format!("{}:{{{}}}:{}", prefix, u64::from(guild_id), key)
+    } else {
+        format!("{}:{}:{}", prefix, u64::from(guild_id), key)
+    }
+}
+
+/// The reverse of [`guild_key`], which leaves out the keys of the other formats that happen to
+/// share the prefix.
+fn parse_guild_key(prefix: &str, raw: &str, cluster: bool) -> Option<(GuildId, String)> {
+    let (guild_id, key) = raw
+        .strip_prefix(prefix)?
+        .strip_prefix(':')?
+        .split_once(':')?;
+    let guild_id = if cluster {
+        guild_id.strip_prefix('{')?.strip_suffix('}')?
+    } else {
+        guild_id
+    };
+    let guild_id = guild_id.parse::<u64>().ok().filter(|&id| id != 0)?;
+    Some((GuildId::new(guild_id), key.to_owned()))
+}
+
+#[derive(Clone)]
+pub struct RedisHandle {
+    prefix: String,
+    backend: Backend,
+}
+
+impl RedisHandle {
+    pub fn new(pool: deadpool_redis::Pool, prefix: String) -> Self {
+        RedisHandle {
+            prefix,
+            backend: Backend::Single(pool),
+        }
+    }
+
+    pub fn with_cluster(conn: ClusterConnection, prefix: String) -> Self {
+        RedisHandle {
+            prefix,
+            backend: Backend::Cluster(conn),
+        }
+    }
+
+    fn is_cluster(&self) -> bool {
+        matches!(self.backend, Backend::Cluster(_))
+    }
+
+    fn key(&self, guild_id: GuildId, key: &str) -> String {
+        guild_key(&self.prefix, guild_id, key, self.is_cluster())
+    }
+
+    async fn conn(&self) -> Result<Connection> {
+        let conn = match &self.backend {
+            Backend::Single(pool) => {
+                Connection::Single(pool.get().await.context("cannot get redis connection")?)
+            }
+            // the cluster connection is multiplexed, and reconnects to the nodes by itself
+            Backend::Cluster(conn) => Connection::Cluster(conn.clone()),
+        };
+        Ok(conn)
+    }
+
+    /// The keys of the guild, looked up on the node that has them in a cluster, since a `SCAN`
+    /// covers only a single node.
+    async fn scan_keys(&self, conn: &mut Connection, guild_id: GuildId) -> Result<Vec<String>> {
+        let pattern = self.key(guild_id, "*");
+        let mut keys = Vec::new();
+        match conn {
+            Connection::Single(conn) => {
+                let mut iter = conn
+                    .scan_match::<_, String>(pattern)
+                    .await
+                    .context("cannot read from redis")?;
+                while let Some(key) = iter.next_item().await {
+                    keys.push(key);
+                }
+            }
+            Connection::Cluster(conn) => {
+                let route = Route::new(get_slot(pattern.as_bytes()), SlotAddr::Master);
+                let routing = RoutingInfo::SingleNode(SingleNodeRoutingInfo::SpecificNode(route));
+                let mut cursor = 0u64;
+                loop {
+                    let reply = conn
+                        .route_command(
+                            ::redis::cmd("SCAN").arg(cursor).arg("MATCH").arg(&pattern),
+                            routing.clone(),
+                        )
+                        .await
+                        .context("cannot read from redis")?;
+                    let (next, batch): (u64, Vec<String>) =
+                        FromRedisValue::from_redis_value(&reply)
+                            .context("unexpected reply from redis")?;
+                    keys.extend(batch);
+                    if next == 0 {
+                        break;
+                    }
+                    cursor = next;
+                }
+            }
+        }
+        Ok(keys)
+    }
+}
+
+#[async_trait::async_trait]
+impl DatabaseHandle for RedisHandle {
+    async fn get<T: FromRedisValue + Send>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+    ) -> Result<Option<T>> {
+        let r = self
+            .conn()
+            .await?
+            .get(self.key(guild_id, key))
+            .await
+            .context("cannot read from redis")?;
+        Ok(r)
+    }
+
+    async fn set<T: ToRedisArgs + Send + Sync>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        value: T,
+    ) -> Result<()> {
+        self.conn()
+            .await?
+            .set(self.key(guild_id, key), value)
+            .await
+            .context("cannot write to redis")?;
+        Ok(())
+    }
+
+    async fn set_with_ttl<T: ToRedisArgs + Send + Sync>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        value: T,
+        ttl: Duration,
+    ) -> Result<()> {
+        self.conn()
+            .await?
+            .pset_ex(
+                self.key(guild_id, key),
+                value,
+                ttl.as_millis().max(1) as u64,
+            )
+            .await
+            .context("cannot write to redis")?;
+        Ok(())
+    }
+
+    async fn compare_and_set<T: ToRedisArgs + Send + Sync>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        expected: Option<T>,
+        value: T,
+    ) -> Result<bool> {
+        let mut cmd = ::redis::cmd("EVAL");
+        cmd.arg(COMPARE_AND_SET_SCRIPT)
+            .arg(1)
+            .arg(self.key(guild_id, key))
+            .arg(value);
+        if let Some(expected) = expected {
+            cmd.arg(expected);
+        }
+        let n: u32 = cmd
+            .query_async(&mut self.conn().await?)
+            .await
+            .context("cannot write to redis")?;
+        Ok(n != 0)
+    }
+
+    async fn delete(&self, guild_id: GuildId, key: &str) -> Result<()> {
+        self.conn()
+            .await?
+            .del(self.key(guild_id, key))
+            .await
+            .context("cannot write to redis")?;
+        Ok(())
+    }
+
+    async fn list_push<T: ToRedisArgs + Send + Sync>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        value: T,
+        capacity: usize,
+    ) -> Result<()> {
+        let key = self.key(guild_id, key);
+        ::redis::pipe()
+            .atomic()
+            .lpush(&key, value)
+            .ignore()
+            .ltrim(&key, 0, capacity as isize - 1)
+            .ignore()
+            .query_async(&mut self.conn().await?)
+            .await
+            .context("cannot write to redis")?;
+        Ok(())
+    }
+
+    async fn list_items<T: FromRedisValue + Send>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+    ) -> Result<Vec<T>> {
+        let r = self
+            .conn()
+            .await?
+            .lrange(self.key(guild_id, key), 0, -1)
+            .await
+            .context("cannot read from redis")?;
+        Ok(r)
+    }
+
+    async fn hash_incr(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        field: &str,
+        delta: i64,
+    ) -> Result<i64> {
+        let r = self
+            .conn()
+            .await?
+            .hincr(self.key(guild_id, key), field, delta)
+            .await
+            .context("cannot write to redis")?;
+        Ok(r)
+    }
+
+    async fn hash_incr_with_expiry(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        field: &str,
+        delta: i64,
+        expiry: Duration,
+    ) -> Result<i64> {
+        let key = self.key(guild_id, key);
+        let (value,): (i64,) = ::redis::pipe()
+            .atomic()
+            .hincr(&key, field, delta)
+            .expire(&key, expiry.as_secs().max(1) as i64)
+            .ignore()
+            .query_async(&mut self.conn().await?)
+            .await
+            .context("cannot write to redis")?;
+        Ok(value)
+    }
+
+    async fn hash_items<T: FromRedisValue + Send>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+    ) -> Result<HashMap<String, T>> {
+        let r = self
+            .conn()
+            .await?
+            .hgetall(self.key(guild_id, key))
+            .await
+            .context("cannot read from redis")?;
+        Ok(r)
+    }
+
+    async fn set_members<T: Eq + Hash + FromRedisValue + Send>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+    ) -> Result<HashSet<T>> {
+        let r = self
+            .conn()
+            .await?
+            .smembers(self.key(guild_id, key))
+            .await
+            .context("cannot read from redis")?;
+        Ok(r)
+    }
+
+    async fn set_add<T: ToRedisArgs + Send + Sync>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        value: T,
+    ) -> Result<bool> {
+        let n: i32 = self
+            .conn()
+            .await?
+            .sadd(self.key(guild_id, key), value)
+            .await
+            .context("cannot write to redis")?;
+        Ok(n != 0)
+    }
+
+    async fn set_remove<T: ToRedisArgs + Send + Sync>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        value: T,
+    ) -> Result<bool> {
+        let n: i32 = self
+            .conn()
+            .await?
+            .srem(self.key(guild_id, key), value)
+            .await
+            .context("cannot write to redis")?;
+        Ok(n != 0)
+    }
+
+    async fn incr_with_expiry(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        expiry: Duration,
+    ) -> Result<i64> {
+        let key = self.key(guild_id, key);
+        // creating the key with the expiry first keeps increments from extending it
+        let (count,): (i64,) = ::redis::pipe()
+            .atomic()
+            .cmd("SET")
+            .arg(&key)
+            .arg(0)
+            .arg("EX")
+            .arg(expiry.as_secs().max(1))
+            .arg("NX")
+            .ignore()
+            .incr(&key, 1)
+            .query_async(&mut self.conn().await?)
+            .await
+            .context("cannot write to redis")?;
+        Ok(count)
+    }
+
+    async fn usage(&self, guild_id: GuildId) -> Result<StorageUsage> {
+        let mut conn = self.conn().await?;
+        let keys = self.scan_keys(&mut conn, guild_id).await?;
+
+        let mut usage = StorageUsage {
+            keys: keys.len(),
+            bytes: 0,
+        };
+        for key in keys {
+            // the key may have been deleted since the scan
+            let bytes: Option<u64> = ::redis::cmd("MEMORY")
+                .arg("USAGE")
+                .arg(&key)
+                .query_async(&mut conn)
+                .await
+                .context("cannot read from redis")?;
+            usage.bytes += bytes.unwrap_or(0);
+        }
+        Ok(usage)
+    }
+
+    async fn read_many(&self, guild_id: GuildId, reads: &[Read<'_>]) -> Result<Vec<ReadResult>> {
+        let mut pipe = ::redis::pipe();
+        for read in reads {
+            match read {
+                Read::Value(key) => pipe.get(self.key(guild_id, key)),
+                Read::Members(key) => pipe.smembers(self.key(guild_id, key)),
+            };
+        }
+        let values: Vec<Value> = pipe
+            .query_async(&mut self.conn().await?)
+            .await
+            .context("cannot read from redis")?;
+
+        reads
+            .iter()
+            .zip(values)
+            .map(|(read, value)| match read {
+                Read::Value(_) => Ok(ReadResult::Value(Option::from_redis_value(&value)?)),
+                Read::Members(_) => Ok(ReadResult::Members(Vec::from_redis_value(&value)?)),
+            })
+            .collect::<::redis::RedisResult<_>>()
+            .context("unexpected reply from redis")
+            .map_err(Into::into)
+    }
+
+    async fn keys(&self) -> Result<Vec<(GuildId, String)>> {
+        if self.is_cluster() {
+            // a SCAN covers only a single node, and the nodes are not known here
+            return Err(
+                anyhow::anyhow!("cannot list the keys of all guilds on redis cluster").into(),
+            );
+        }
+        let mut conn = self.conn().await?;
+        let mut iter = conn
+            .scan_match::<_, String>(format!("{}:*", self.prefix))
+            .await
+            .context("cannot read from redis")?;
+        let mut keys = Vec::new();
+        while let Some(raw) = iter.next_item().await {
+            if let Some(key) = parse_guild_key(&self.prefix, &raw, false) {
+                keys.push(key);
+            }
+        }
+        Ok(keys)
+    }
+
+    async fn dump_key(&self, guild_id: GuildId, key: &str) -> Result<Option<Entry>> {
+        let key = self.key(guild_id, key);
+        let mut conn = self.conn().await?;
+        let kind: String = ::redis::cmd("TYPE")
+            .arg(&key)
+            .query_async(&mut conn)
+            .await
+            .context("cannot read from redis")?;
+        // the key may be deleted or expire in between, which reads as empty
+        let entry = match kind.as_str() {
+            "none" => return Ok(None),
+            "string" => {
+                let (data, ttl): (Option<Vec<u8>>, i64) = ::redis::pipe()
+                    .get(&key)
+                    .pttl(&key)
+                    .query_async(&mut conn)
+                    .await
+                    .context("cannot read from redis")?;
+                let Some(data) = data else {
+                    return Ok(None);
+                };
+                if ttl > 0 {
+                    Entry::Expiring(data, Duration::from_millis(ttl as u64))
+                } else {
+                    Entry::Value(data)
+                }
+            }
+            "list" => Entry::List(
+                conn.lrange(&key, 0, -1)
+                    .await
+                    .context("cannot read from redis")?,
+            ),
+            "hash" => Entry::Hash(conn.hgetall(&key).await.context("cannot read from redis")?),
+            "set" => Entry::Set(
+                conn.smembers(&key)
+                    .await
+                    .context("cannot read from redis")?,
+            ),
+            kind => {
+                return Err(anyhow::anyhow!("unexpected type {} of {} in redis", kind, key).into())
+            }
+        };
+        Ok(Some(entry))
+    }
+
+    async fn write_many(&self, guild_id: GuildId, writes: &[Write<'_>]) -> Result<()> {
+        // the keys of a guild share a hash slot, so that they can be written in a transaction
+        // even on a cluster
+        let mut pipe = ::redis::pipe();
+        pipe.atomic();
+        for write in writes {
+            let key = self.key(guild_id, write.key());
+            match write {
+                Write::Put(_, entry) => restore_entry(&mut pipe, &key, entry.clone()),
+                Write::Delete(_) => pipe.del(&key).ignore(),
+            };
+        }
+        pipe.query_async::<_, ()>(&mut self.conn().await?)
+            .await
+            .context("cannot write to redis")?;
+        Ok(())
+    }
+
+    async fn restore_key(&self, guild_id: GuildId, key: &str, entry: Entry) -> Result<()> {
+        let key = self.key(guild_id, key);
+        let mut pipe = ::redis::pipe();
+        pipe.atomic();
+        restore_entry(&mut pipe, &key, entry);
+        pipe.query_async::<_, ()>(&mut self.conn().await?)
+            .await
+            .context("cannot write to redis")?;
+        Ok(())
+    }
+
+    async fn dump(&self, guild_id: GuildId) -> Result<BTreeMap<String, Entry>> {
+        let raw_keys = self.scan_keys(&mut self.conn().await?, guild_id).await?;
+        let mut entries = BTreeMap::new();
+        for raw in raw_keys {
+            let Some((_, key)) = parse_guild_key(&self.prefix, &raw, self.is_cluster()) else {
+                continue;
+            };
+            if let Some(entry) = self.dump_key(guild_id, &key).await? {
+                entries.insert(key, entry);
+            }
+        }
+        Ok(entries)
+    }
+}
+
+/// Adds the commands to `pipe` that replace `key` with `entry`.
+fn restore_entry<'a>(pipe: &'a mut Pipeline, key: &str, entry: Entry) -> &'a mut Pipeline {
+    pipe.del(key).ignore();
+    match entry {
+        Entry::Value(data) => pipe.set(key, data).ignore(),
+        Entry::List(items) if items.is_empty() => pipe,
+        Entry::List(items) => pipe.rpush(key, items).ignore(),
+        Entry::Hash(hash) if hash.is_empty() => pipe,
+        Entry::Hash(hash) => pipe
+            .hset_multiple(key, &hash.into_iter().collect::<Vec<_>>())
+            .ignore(),
+        Entry::Set(set) if set.is_empty() => pipe,
+        Entry::Set(set) => pipe.sadd(key, set.into_iter().collect::<Vec<_>>()).ignore(),
+        Entry::Expiring(data, ttl) => pipe
+            .pset_ex(key, data, ttl.as_millis().max(1) as u64)
+            .ignore(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{guild_key, parse_guild_key};
+    use serenity::model::id::GuildId;
+
+    #[test]
+    fn test_guild_key() {
+        let guild_id = GuildId::new(1234);
+        assert_eq!(
+            guild_key("kaisandaijin", guild_id, "timezone", false),
+            "kaisandaijin:1234:timezone"
+        );
+        assert_eq!(
+            guild_key("kaisandaijin", guild_id, "timezone", true),
+            "kaisandaijin:{1234}:timezone"
+        );
+    }
+
+    #[test]
+    fn test_parse_guild_key() {
+        let guild_id = GuildId::new(1234);
+        for cluster in [false, true] {
+            let raw = guild_key("kaisandaijin", guild_id, "schedule:1", cluster);
+            assert_eq!(
+                parse_guild_key("kaisandaijin", &raw, cluster),
+                Some((guild_id, "schedule:1".to_owned()))
+            );
+        }
+        assert_eq!(
+            parse_guild_key("kaisan", "kaisandaijin:1234:timezone", false),
+            None
+        );
+        assert_eq!(
+            parse_guild_key("kaisandaijin", "kaisandaijin:0:x", false),
+            None
+        );
+        assert_eq!(
+            parse_guild_key("kaisandaijin", "kaisandaijin:status", false),
+            None
+        );
+    }
+}
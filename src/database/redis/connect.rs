@@ -0,0 +1,113 @@
+//! How to reach Redis, which takes more than a URL when it is a cluster or when the password is
+//! kept apart from it.
+
+use std::path::{Path, PathBuf};
+
+use super::RedisHandle;
+
+use ::redis::{cluster::ClusterClient, ConnectionInfo, IntoConnectionInfo};
+use anyhow::{ensure, Context as _, Result};
+
+/// The Redis servers to connect to. The URLs may be `rediss://` ones to connect over TLS.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RedisTopology {
+    Single(String),
+    /// Some of the nodes of a Redis Cluster, from which the rest are discovered.
+    Cluster(Vec<String>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RedisOptions {
+    pub topology: RedisTopology,
+    /// File holding the password, which replaces the one in the URLs if any.
+    pub password_file: Option<PathBuf>,
+    /// Connections in the pool to a single server. The connection to a cluster is multiplexed and
+    /// is not pooled.
+    pub pool_size: Option<usize>,
+    pub prefix: String,
+}
+
+impl RedisOptions {
+    pub async fn connect(self) -> Result<RedisHandle> {
+        let password = match &self.password_file {
+            Some(path) => Some(read_password(path).await?),
+            None => None,
+        };
+        match self.topology {
+            RedisTopology::Single(url) => {
+                let info = connection_info(&url, password)?;
+                let mut config = deadpool_redis::Config::from_connection_info(info);
+                if let Some(size) = self.pool_size {
+                    config.pool = Some(deadpool_redis::PoolConfig::new(size));
+                }
+                let pool = config.create_pool(Some(deadpool_redis::Runtime::Tokio1))?;
+                Ok(RedisHandle::new(pool, self.prefix))
+            }
+            RedisTopology::Cluster(urls) => {
+                ensure!(!urls.is_empty(), "no redis cluster nodes are given");
+                let nodes = urls
+                    .iter()
+                    .map(|url| connection_info(url, password.clone()))
+                    .collect::<Result<Vec<_>>>()?;
+                let conn = ClusterClient::new(nodes)
+                    .context("invalid redis cluster nodes")?
+                    .get_async_connection()
+                    .await
+                    .context("cannot connect to redis cluster")?;
+                Ok(RedisHandle::with_cluster(conn, self.prefix))
+            }
+        }
+    }
+}
+
+fn connection_info(url: &str, password: Option<String>) -> Result<ConnectionInfo> {
+    let mut info = url.into_connection_info().context("invalid redis URL")?;
+    if password.is_some() {
+        info.redis.password = password;
+    }
+    Ok(info)
+}
+
+async fn read_password(path: &Path) -> Result<String> {
+    let content = tokio::fs::read_to_string(path)
+        .await
+        .with_context(|| format!("cannot read {}", path.display()))?;
+    let password = content.trim();
+    ensure!(!password.is_empty(), "{} is empty", path.display());
+    Ok(password.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{connection_info, read_password};
+
+    use ::redis::ConnectionAddr;
+
+    #[test]
+    fn test_connection_info() {
+        let info = connection_info("rediss://:old@redis.example.com:6380/2", None).unwrap();
+        assert!(matches!(
+            info.addr,
+            ConnectionAddr::TcpTls { ref host, port: 6380, .. } if host == "redis.example.com"
+        ));
+        assert_eq!(info.redis.password.as_deref(), Some("old"));
+        assert_eq!(info.redis.db, 2);
+
+        let info = connection_info("redis://:old@localhost", Some("new".to_owned())).unwrap();
+        assert!(matches!(info.addr, ConnectionAddr::Tcp(..)));
+        assert_eq!(info.redis.password.as_deref(), Some("new"));
+
+        assert!(connection_info("http://localhost", None).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_read_password() {
+        let path =
+            std::env::temp_dir().join(format!("kaisandaijin-password-{}", std::process::id()));
+        std::fs::write(&path, "secret\n").unwrap();
+        assert_eq!(read_password(&path).await.unwrap(), "secret");
+        std::fs::write(&path, "\n").unwrap();
+        assert!(read_password(&path).await.is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,484 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::future::Future;
+use std::hash::Hash;
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+use std::time::Duration;
+
+use super::{DatabaseHandle, Entry, Read, ReadResult, StorageUsage, Write};
+use crate::error::{Error, Result};
+
+use ::redis::{FromRedisValue, RedisError, ToRedisArgs};
+use rand::Rng;
+use serde::Serialize;
+use serenity::model::id::GuildId;
+use tracing::Instrument as _;
+
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+pub const DEFAULT_INITIAL_BACKOFF: Duration = Duration::from_millis(50);
+const MAX_BACKOFF: Duration = Duration::from_secs(2);
+
+/// How many times and how long apart the operations are tried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Including the first try, so that `1` never retries.
+    pub max_attempts: u32,
+    /// Wait before the first retry, which doubles on each retry up to 2 seconds.
+    pub initial_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            initial_backoff: DEFAULT_INITIAL_BACKOFF,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// The wait before the given retry, counted from 1, with a random jitter of up to a half of it
+    /// so that the retries after a hiccup are spread out.
+    fn backoff(&self, retry: u32) -> Duration {
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(2u32.saturating_pow(retry - 1))
+            .min(MAX_BACKOFF);
+        let jitter = rand::thread_rng().gen_range(0.0..=0.5);
+        backoff.mul_f64(1.0 - jitter)
+    }
+}
+
+#[derive(Default)]
+struct Metrics {
+    retried: AtomicU64,
+    recovered: AtomicU64,
+    gave_up: AtomicU64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct RetryStats {
+    /// Retries made, counting each one of an operation
+    pub retried: u64,
+    /// Operations that succeeded after a retry
+    pub recovered: u64,
+    /// Operations that failed even after all the attempts
+    pub gave_up: u64,
+}
+
+/// Whether the error is from the connection to the database rather than the operation itself,
+/// such that trying again may succeed.
+fn is_transient(error: &Error) -> bool {
+    let Error::Other(error) = error else {
+        return false;
+    };
+    error.chain().any(|cause| {
+        if let Some(e) = cause.downcast_ref::<RedisError>() {
+            return e.is_io_error()
+                || e.is_timeout()
+                || e.is_connection_refusal()
+                || e.is_connection_dropped();
+        }
+        cause.is::<deadpool_redis::PoolError>()
+    })
+}
+
+/// Whether the error happened before the operation reached the database, such as while waiting for
+/// a connection, so that even an operation that is not idempotent can be tried again.
+fn is_unsent(error: &Error) -> bool {
+    let Error::Other(error) = error else {
+        return false;
+    };
+    error
+        .chain()
+        .any(|cause| cause.is::<deadpool_redis::PoolError>())
+}
+
+/// Retries the operations of the inner handle that failed for a transient reason, such as a dropped
+/// connection, with an exponential backoff.
+///
+/// The increments and the pushes are retried only when they were never sent, since they would be
+/// applied twice if the database did apply them but the reply was lost. So are the operations that
+/// report whether they changed anything, which would report no change on such a retry.
+#[derive(Clone)]
+pub struct Retrying<H> {
+    inner: H,
+    policy: RetryPolicy,
+    metrics: Arc<Metrics>,
+}
+
+impl<H> Retrying<H> {
+    pub fn new(inner: H, policy: RetryPolicy) -> Self {
+        Retrying {
+            inner,
+            policy,
+            metrics: Arc::new(Metrics::default()),
+        }
+    }
+
+    pub fn stats(&self) -> RetryStats {
+        RetryStats {
+            retried: self.metrics.retried.load(Ordering::Relaxed),
+            recovered: self.metrics.recovered.load(Ordering::Relaxed),
+            gave_up: self.metrics.gave_up.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Runs the operation in a span of its own, which covers all the attempts.
+    async fn retry<T, F, Fut>(&self, operation: &'static str, idempotent: bool, f: F) -> Result<T>
+    where
+        F: Fn() -> Fut + Send,
+        Fut: Future<Output = Result<T>> + Send,
+    {
+        self.retry_attempts(operation, idempotent, f)
+            .instrument(tracing::debug_span!("database", operation))
+            .await
+    }
+
+    async fn retry_attempts<T, F, Fut>(
+        &self,
+        operation: &'static str,
+        idempotent: bool,
+        f: F,
+    ) -> Result<T>
+    where
+        F: Fn() -> Fut + Send,
+        Fut: Future<Output = Result<T>> + Send,
+    {
+        let mut attempt = 1;
+        loop {
+            let error = match f().await {
+                Ok(value) => {
+                    if attempt > 1 {
+                        self.metrics.recovered.fetch_add(1, Ordering::Relaxed);
+                    }
+                    return Ok(value);
+                }
+                Err(e) => e,
+            };
+            let retriable = if idempotent {
+                is_transient(&error)
+            } else {
+                is_unsent(&error)
+            };
+            if !retriable {
+                return Err(error);
+            }
+            if attempt >= self.policy.max_attempts {
+                self.metrics.gave_up.fetch_add(1, Ordering::Relaxed);
+                tracing::warn!(operation, attempt, "giving up the database operation");
+                return Err(error);
+            }
+
+            let backoff = self.policy.backoff(attempt);
+            tracing::debug!(
+                operation,
+                attempt,
+                ?backoff,
+                error = %error,
+                "retrying the database operation"
+            );
+            self.metrics.retried.fetch_add(1, Ordering::Relaxed);
+            tokio::time::sleep(backoff).await;
+            attempt += 1;
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<H: DatabaseHandle + Send + Sync> DatabaseHandle for Retrying<H> {
+    async fn get<T: FromRedisValue + Send>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+    ) -> Result<Option<T>> {
+        self.retry("get", true, || self.inner.get(guild_id, key))
+            .await
+    }
+
+    async fn set<T: ToRedisArgs + Send + Sync>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        value: T,
+    ) -> Result<()> {
+        self.retry("set", true, || self.inner.set(guild_id, key, &value))
+            .await
+    }
+
+    async fn set_with_ttl<T: ToRedisArgs + Send + Sync>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        value: T,
+        ttl: Duration,
+    ) -> Result<()> {
+        self.retry("set_with_ttl", true, || {
+            self.inner.set_with_ttl(guild_id, key, &value, ttl)
+        })
+        .await
+    }
+
+    async fn compare_and_set<T: ToRedisArgs + Send + Sync>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        expected: Option<T>,
+        value: T,
+    ) -> Result<bool> {
+        self.retry("compare_and_set", false, || {
+            self.inner
+                .compare_and_set(guild_id, key, expected.as_ref(), &value)
+        })
+        .await
+    }
+
+    async fn delete(&self, guild_id: GuildId, key: &str) -> Result<()> {
+        self.retry("delete", true, || self.inner.delete(guild_id, key))
+            .await
+    }
+
+    async fn list_push<T: ToRedisArgs + Send + Sync>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        value: T,
+        capacity: usize,
+    ) -> Result<()> {
+        self.retry("list_push", false, || {
+            self.inner.list_push(guild_id, key, &value, capacity)
+        })
+        .await
+    }
+
+    async fn list_items<T: FromRedisValue + Send>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+    ) -> Result<Vec<T>> {
+        self.retry("list_items", true, || self.inner.list_items(guild_id, key))
+            .await
+    }
+
+    async fn hash_incr(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        field: &str,
+        delta: i64,
+    ) -> Result<i64> {
+        self.retry("hash_incr", false, || {
+            self.inner.hash_incr(guild_id, key, field, delta)
+        })
+        .await
+    }
+
+    async fn hash_items<T: FromRedisValue + Send>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+    ) -> Result<HashMap<String, T>> {
+        self.retry("hash_items", true, || self.inner.hash_items(guild_id, key))
+            .await
+    }
+
+    async fn set_members<T: Eq + Hash + FromRedisValue + Send>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+    ) -> Result<HashSet<T>> {
+        self.retry("set_members", true, || {
+            self.inner.set_members(guild_id, key)
+        })
+        .await
+    }
+
+    async fn set_add<T: ToRedisArgs + Send + Sync>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        value: T,
+    ) -> Result<bool> {
+        self.retry("set_add", false, || {
+            self.inner.set_add(guild_id, key, &value)
+        })
+        .await
+    }
+
+    async fn set_remove<T: ToRedisArgs + Send + Sync>(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        value: T,
+    ) -> Result<bool> {
+        self.retry("set_remove", false, || {
+            self.inner.set_remove(guild_id, key, &value)
+        })
+        .await
+    }
+
+    async fn hash_incr_with_expiry(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        field: &str,
+        delta: i64,
+        expiry: Duration,
+    ) -> Result<i64> {
+        self.retry("hash_incr_with_expiry", false, || {
+            self.inner
+                .hash_incr_with_expiry(guild_id, key, field, delta, expiry)
+        })
+        .await
+    }
+
+    async fn incr_with_expiry(
+        &self,
+        guild_id: GuildId,
+        key: &str,
+        expiry: Duration,
+    ) -> Result<i64> {
+        self.retry("incr_with_expiry", false, || {
+            self.inner.incr_with_expiry(guild_id, key, expiry)
+        })
+        .await
+    }
+
+    async fn usage(&self, guild_id: GuildId) -> Result<StorageUsage> {
+        self.retry("usage", true, || self.inner.usage(guild_id))
+            .await
+    }
+
+    async fn read_many(&self, guild_id: GuildId, reads: &[Read<'_>]) -> Result<Vec<ReadResult>> {
+        self.retry("read_many", true, || self.inner.read_many(guild_id, reads))
+            .await
+    }
+
+    async fn write_many(&self, guild_id: GuildId, writes: &[Write<'_>]) -> Result<()> {
+        // every key is replaced or deleted as a whole, so that writing them twice does no harm
+        self.retry("write_many", true, || {
+            self.inner.write_many(guild_id, writes)
+        })
+        .await
+    }
+
+    async fn keys(&self) -> Result<Vec<(GuildId, String)>> {
+        self.retry("keys", true, || self.inner.keys()).await
+    }
+
+    async fn dump_key(&self, guild_id: GuildId, key: &str) -> Result<Option<Entry>> {
+        self.retry("dump_key", true, || self.inner.dump_key(guild_id, key))
+            .await
+    }
+
+    async fn restore_key(&self, guild_id: GuildId, key: &str, entry: Entry) -> Result<()> {
+        // the key is replaced as a whole, so that restoring it twice does no harm
+        self.retry("restore_key", true, || {
+            self.inner.restore_key(guild_id, key, entry.clone())
+        })
+        .await
+    }
+
+    async fn dump(&self, guild_id: GuildId) -> Result<BTreeMap<String, Entry>> {
+        self.retry("dump", true, || self.inner.dump(guild_id)).await
+    }
+
+    async fn scan_guilds(&self) -> Result<Vec<GuildId>> {
+        self.retry("scan_guilds", true, || self.inner.scan_guilds())
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RetryPolicy, RetryStats, Retrying};
+    use crate::database::InMemoryHandle;
+    use crate::error::{Error, Result};
+
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::time::Duration;
+
+    fn connection_dropped() -> Error {
+        let e = std::io::Error::from(std::io::ErrorKind::ConnectionReset);
+        anyhow::Error::new(::redis::RedisError::from(e))
+            .context("cannot read from redis")
+            .into()
+    }
+
+    fn retrying(max_attempts: u32) -> Retrying<InMemoryHandle> {
+        Retrying::new(
+            InMemoryHandle::new(),
+            RetryPolicy {
+                max_attempts,
+                initial_backoff: Duration::from_millis(1),
+            },
+        )
+    }
+
+    async fn fail_times(
+        handle: &Retrying<InMemoryHandle>,
+        fails: u32,
+        idempotent: bool,
+    ) -> Result<u32> {
+        let tries = AtomicU32::new(0);
+        handle
+            .retry("test", idempotent, || async {
+                let tries = tries.fetch_add(1, Ordering::Relaxed) + 1;
+                if tries <= fails {
+                    Err(connection_dropped())
+                } else {
+                    Ok(tries)
+                }
+            })
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_recover() {
+        let handle = retrying(3);
+        assert_eq!(fail_times(&handle, 2, true).await.unwrap(), 3);
+        assert_eq!(
+            handle.stats(),
+            RetryStats {
+                retried: 2,
+                recovered: 1,
+                gave_up: 0,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_give_up() {
+        let handle = retrying(3);
+        assert!(fail_times(&handle, 3, true).await.is_err());
+        assert_eq!(handle.stats().retried, 2);
+        assert_eq!(handle.stats().gave_up, 1);
+    }
+
+    #[tokio::test]
+    async fn test_not_retried() {
+        let handle = retrying(3);
+        // the increment may have been applied before the connection was dropped
+        assert!(fail_times(&handle, 1, false).await.is_err());
+        assert!(matches!(
+            handle
+                .retry("test", true, || async { Err::<(), _>(Error::NotOwner) })
+                .await,
+            Err(Error::NotOwner)
+        ));
+        assert_eq!(handle.stats(), RetryStats::default());
+    }
+
+    #[test]
+    fn test_backoff() {
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            initial_backoff: Duration::from_millis(100),
+        };
+        let third = policy.backoff(3);
+        assert!(third >= Duration::from_millis(200) && third <= Duration::from_millis(400));
+        assert!(policy.backoff(10) <= Duration::from_secs(2));
+    }
+}
@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, Mutex,
+};
+use std::time::{Duration, Instant};
+
+use serenity::model::id::GuildId;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+pub const DEFAULT_MAX_RUNNING: usize = 64;
+pub const DEFAULT_MAX_RUNNING_PER_GUILD: usize = 4;
+
+/// Waits longer than this are logged as warnings.
+const SLOW_DISPATCH: Duration = Duration::from_secs(5);
+
+#[derive(Default)]
+struct Metrics {
+    dispatched: AtomicU64,
+    waiting: AtomicU64,
+    total_wait_micros: AtomicU64,
+    max_wait_micros: AtomicU64,
+}
+
+/// Decrements the number of waiting tasks even if the task is aborted while waiting.
+struct Waiting<'a>(&'a AtomicU64);
+
+impl<'a> Waiting<'a> {
+    fn new(counter: &'a AtomicU64) -> Self {
+        counter.fetch_add(1, Ordering::Relaxed);
+        Waiting(counter)
+    }
+}
+
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DispatchStats {
+    pub dispatched: u64,
+    pub waiting: u64,
+    pub mean_wait: Duration,
+    pub max_wait: Duration,
+}
+
+/// Bounds the scheduled work running at once, so that a popular minute does not flood the
+/// process and Discord with requests.
+///
+/// Each guild can hold at most its own share of the slots, and the slots are handed out in
+/// the order of requests, so that a guild with many schedules cannot keep the others waiting.
+#[derive(Clone)]
+pub struct Dispatcher {
+    running: Arc<Semaphore>,
+    max_running_per_guild: usize,
+    guilds: Arc<Mutex<HashMap<GuildId, Arc<Semaphore>>>>,
+    metrics: Arc<Metrics>,
+}
+
+/// Keeps the slot until dropped.
+pub struct DispatchPermit {
+    _guild: OwnedSemaphorePermit,
+    _running: OwnedSemaphorePermit,
+}
+
+impl Default for Dispatcher {
+    fn default() -> Self {
+        Dispatcher::new(DEFAULT_MAX_RUNNING, DEFAULT_MAX_RUNNING_PER_GUILD)
+    }
+}
+
+impl Dispatcher {
+    pub fn new(max_running: usize, max_running_per_guild: usize) -> Self {
+        Dispatcher {
+            running: Arc::new(Semaphore::new(max_running)),
+            max_running_per_guild,
+            guilds: Arc::new(Mutex::new(HashMap::new())),
+            metrics: Arc::new(Metrics::default()),
+        }
+    }
+
+    pub async fn acquire(&self, guild_id: GuildId) -> DispatchPermit {
+        let start = Instant::now();
+        let waiting = Waiting::new(&self.metrics.waiting);
+
+        let guild = Arc::clone(
+            self.guilds
+                .lock()
+                .unwrap()
+                .entry(guild_id)
+                .or_insert_with(|| Arc::new(Semaphore::new(self.max_running_per_guild))),
+        );
+        // taking the guild's own slot first keeps the guild from filling the queue for the
+        // shared ones
+        let guild = guild
+            .acquire_owned()
+            .await
+            .expect("semaphore is never closed");
+        let running = Arc::clone(&self.running)
+            .acquire_owned()
+            .await
+            .expect("semaphore is never closed");
+        drop(waiting);
+
+        let waited = start.elapsed();
+        let micros = waited.as_micros() as u64;
+        self.metrics.dispatched.fetch_add(1, Ordering::Relaxed);
+        self.metrics
+            .total_wait_micros
+            .fetch_add(micros, Ordering::Relaxed);
+        self.metrics
+            .max_wait_micros
+            .fetch_max(micros, Ordering::Relaxed);
+        if waited >= SLOW_DISPATCH {
+            tracing::warn!(%guild_id, ?waited, "scheduled work was delayed by the dispatcher");
+        } else {
+            tracing::debug!(%guild_id, ?waited, "dispatched scheduled work");
+        }
+
+        DispatchPermit {
+            _guild: guild,
+            _running: running,
+        }
+    }
+
+    pub fn stats(&self) -> DispatchStats {
+        let dispatched = self.metrics.dispatched.load(Ordering::Relaxed);
+        let total = self.metrics.total_wait_micros.load(Ordering::Relaxed);
+        DispatchStats {
+            dispatched,
+            waiting: self.metrics.waiting.load(Ordering::Relaxed),
+            mean_wait: Duration::from_micros(total.checked_div(dispatched).unwrap_or(0)),
+            max_wait: Duration::from_micros(self.metrics.max_wait_micros.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Dispatcher;
+
+    use futures::FutureExt as _;
+    use serenity::model::id::GuildId;
+
+    const GUILD_1: GuildId = GuildId::new(1);
+    const GUILD_2: GuildId = GuildId::new(2);
+
+    #[tokio::test]
+    async fn test_limit_per_guild() {
+        let dispatcher = Dispatcher::new(3, 2);
+        let _a = dispatcher.acquire(GUILD_1).await;
+        let _b = dispatcher.acquire(GUILD_1).await;
+        assert!(dispatcher.acquire(GUILD_1).now_or_never().is_none());
+
+        // the other guild still gets the remaining slot
+        let c = dispatcher.acquire(GUILD_2).await;
+        assert!(dispatcher.acquire(GUILD_2).now_or_never().is_none());
+        drop(c);
+        assert!(dispatcher.acquire(GUILD_2).now_or_never().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_fairness() {
+        let dispatcher = Dispatcher::new(1, 1);
+        let first = dispatcher.acquire(GUILD_1).await;
+
+        let mut busy = Box::pin(dispatcher.acquire(GUILD_1));
+        let mut other = Box::pin(dispatcher.acquire(GUILD_2));
+        assert!((&mut busy).now_or_never().is_none());
+        assert!((&mut other).now_or_never().is_none());
+        assert_eq!(dispatcher.stats().waiting, 2);
+
+        // the waiting guild goes first though the busy guild asked earlier
+        drop(first);
+        let second = (&mut other).now_or_never().unwrap();
+        assert!((&mut busy).now_or_never().is_none());
+        drop(second);
+        assert!(busy.now_or_never().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_stats() {
+        let dispatcher = Dispatcher::new(1, 1);
+        drop(dispatcher.acquire(GUILD_1).await);
+        drop(dispatcher.acquire(GUILD_2).await);
+
+        let stats = dispatcher.stats();
+        assert_eq!(stats.dispatched, 2);
+        assert_eq!(stats.waiting, 0);
+        assert!(stats.max_wait >= stats.mean_wait);
+    }
+}
@@ -1,11 +1,21 @@
 use std::sync::Arc;
 
-use crate::model::{command::ParseCommandError, reminder::Reminder, time::TimeSpecifier};
-use crate::say::{fmt, Say};
+use crate::model::{
+    command::ParseCommandError,
+    reminder::Reminder,
+    schedule::ScheduleId,
+    template::InvalidTemplateError,
+    time::{CalculateTimeError, TimeSpecifier},
+};
+use crate::say::{fmt, DisplayExt, IntoIteratorSayExt, Say};
 
 use chrono::{DateTime, Utc};
 use chrono_tz::Tz;
-use serenity::model::permissions::Permissions;
+use serenity::model::{
+    channel::ReactionType,
+    id::{ChannelId, RoleId},
+    permissions::Permissions,
+};
 use thiserror::Error;
 
 #[derive(Clone, Debug, Error)]
@@ -23,16 +33,87 @@ pub enum Error {
         specified: DateTime<Utc>,
         at: DateTime<Utc>,
     },
-    #[error("invalid time {specifier:?} at {at} in {timezone}")]
+    #[error("invalid time {specifier:?} at {at} in {timezone}: {reason}")]
     InvalidTime {
         specifier: TimeSpecifier,
         at: DateTime<Utc>,
         timezone: Tz,
+        reason: CalculateTimeError,
     },
+    #[error("{specified} is too far from {at}, the limit is {max_horizon_hours} hours")]
+    TooFarInFuture {
+        specified: DateTime<Utc>,
+        at: DateTime<Utc>,
+        max_horizon_hours: u8,
+    },
+    #[error("kaisans {0:?} are already scheduled in the voice channel")]
+    DuplicateSchedule(Vec<ScheduleId>),
+    #[error("the user already has {max_schedules_per_user} schedules")]
+    TooManySchedules { max_schedules_per_user: u8 },
     #[error("no such reminder for {}", .0.before_duration())]
     NoSuchReminder(Reminder),
     #[error("reminder for {} already exists", .0.before_duration())]
     DuplicatedReminders(Reminder),
+    #[error("no such schedule {0:?}")]
+    NoSuchSchedule(ScheduleId),
+    #[error("no schedule made in the last {undo_window_minutes} minutes")]
+    NothingToUndo { undo_window_minutes: u8 },
+    #[error("no profile named {0}")]
+    NoSuchProfile(String),
+    #[error("the guild already has {0} profiles")]
+    TooManyProfiles(usize),
+    #[error("the guild is paused")]
+    Paused,
+    #[error("the guild is not paused")]
+    NotPaused,
+    #[error("no member named {0}")]
+    NoSuchMember(String),
+    #[error("{name} may refer to any of {candidates:?}")]
+    AmbiguousMember {
+        name: String,
+        candidates: Vec<String>,
+    },
+    #[error("{0} is already allowed")]
+    DuplicatedAllowedChannel(ChannelId),
+    #[error("{0} is not in the allowed channels")]
+    NoSuchAllowedChannel(ChannelId),
+    #[error("{0} is already allowed")]
+    DuplicatedAllowedRole(RoleId),
+    #[error("{0} is not in the allowed roles")]
+    NoSuchAllowedRole(RoleId),
+    #[error("commands are not allowed in this channel")]
+    ChannelNotAllowed(Vec<ChannelId>),
+    #[error("the user ran more than {max_commands} commands in {window_seconds} seconds")]
+    RateLimited {
+        max_commands: u64,
+        window_seconds: u64,
+    },
+    #[error("the user is not an owner of the bot")]
+    NotOwner,
+    #[error("no attachment is given")]
+    MissingAttachment,
+    #[error("invalid reminder text: {0}")]
+    InvalidReminderText(#[from] InvalidTemplateError),
+    #[error("invalid setting file: {0}")]
+    InvalidSettingFile(Arc<serde_json::Error>),
+    #[error("invalid value of {0} in the setting file")]
+    InvalidSettingValue(&'static str),
+    #[error("the attachment is larger than {max_bytes} bytes")]
+    AttachmentTooLarge { max_bytes: u32 },
+    #[error("the attachment is not a JSON file")]
+    UnsupportedAttachment,
+    #[error("the setting has been changed by someone else in the meantime")]
+    SettingConflict,
+    #[error("invalid webhook url {0}")]
+    InvalidWebhookUrl(String),
+    #[error("{0} is not a text channel of the guild the bot can post to")]
+    InvalidLogChannel(ChannelId),
+    #[error("{0} is not an emoji usable in the guild")]
+    UnusableEmoji(ReactionType),
+    #[error("the phrase is longer than {max_length} characters")]
+    PhraseTooLong { max_length: usize },
+    #[error("invalid log filter {0}")]
+    InvalidLogFilter(String),
     #[error(transparent)]
     Other(Arc<anyhow::Error>),
 }
@@ -49,10 +130,127 @@ impl Say for Error {
             Error::NotInVoiceChannel => f.write_str("ボイスチャンネルに入った状態で使ってほしい"),
             Error::InvalidCommand(_) => f.write_str("コマンドがわからない"),
             Error::UnreachableTime { .. } => f.write_str("過去を変えることはできない"),
-            Error::InvalidTime { .. } => f.write_str("そんな時刻はない"),
+            Error::InvalidTime {
+                reason: CalculateTimeError::Nonexistent(time),
+                timezone,
+                ..
+            } => say!(
+                f,
+                "{} の {} は夏時間の切り替えで飛ばされる時刻です（`dst earliest` で直後の時刻に繰り下げられます）",
+                timezone,
+                time.format("%m/%d %H:%M").say_display()
+            ),
+            Error::InvalidTime {
+                reason: CalculateTimeError::Ambiguous(time),
+                timezone,
+                ..
+            } => say!(
+                f,
+                "{} の {} は夏時間の切り替えで2回ある時刻です（`dst earliest` か `dst latest` でどちらにするか選べます）",
+                timezone,
+                time.format("%m/%d %H:%M").say_display()
+            ),
+            Error::InvalidTime {
+                reason: CalculateTimeError::OutOfRange,
+                ..
+            } => f.write_str("そんな日付はない"),
+            Error::TooFarInFuture {
+                max_horizon_hours, ..
+            } => write!(
+                f,
+                "{}時間より先の解散は予約できない（`max-horizon` で変更できます）",
+                max_horizon_hours
+            ),
+            Error::TooManySchedules {
+                max_schedules_per_user,
+            } => write!(
+                f,
+                "同時に予約できる解散は一人{}件までです（`cancel mine` で自分の予約を取り消せます）",
+                max_schedules_per_user
+            ),
+            Error::DuplicateSchedule(ids) => say!(
+                f,
+                "この通話にはすでに {} の解散が予約されている（`cancel mine` で取り消すか、`on-duplicate` で変更できます）",
+                ids.say_joined("、")
+            ),
             Error::InsufficientPermission(p) => write!(f, "{} の権限が必要です", p),
             Error::NoSuchReminder(_) => f.write_str("そんなリマインダはない"),
             Error::DuplicatedReminders(_) => f.write_str("それはすでにある"),
+            Error::NoSuchSchedule(id) => say!(f, "{} という解散は予定されていない", id),
+            Error::NothingToUndo {
+                undo_window_minutes: 0,
+            } => f.write_str("undo は無効になっている（`undo-window` で有効にできる）"),
+            Error::NothingToUndo {
+                undo_window_minutes,
+            } => write!(
+                f,
+                "直近{}分以内に予約した解散はない（`cancel mine` ならすべて取り消せる）",
+                undo_window_minutes
+            ),
+            Error::NoSuchProfile(name) => write!(
+                f,
+                "{} というプロファイルはない（`profile list` で一覧できる）",
+                name
+            ),
+            Error::TooManyProfiles(max) => {
+                write!(f, "プロファイルは{}件までしか保存できない", max)
+            }
+            Error::Paused => f.write_str("一時停止中です（`resume` で再開できる）"),
+            Error::NotPaused => f.write_str("一時停止していない"),
+            Error::NoSuchMember(name) => write!(f, "{} という人は見つからない", name),
+            Error::AmbiguousMember { name, candidates } => write!(
+                f,
+                "{} が誰なのかわからない（{} のどれか）",
+                name,
+                candidates.join("、")
+            ),
+            Error::DuplicatedAllowedChannel(_) => f.write_str("それはすでにある"),
+            Error::NoSuchAllowedChannel(_) => f.write_str("そんなチャンネルはない"),
+            Error::DuplicatedAllowedRole(_) => f.write_str("それはすでにある"),
+            Error::NoSuchAllowedRole(_) => f.write_str("そんなロールはない"),
+            Error::ChannelNotAllowed(ids) => say!(
+                f,
+                "このチャンネルでは使えません（{} で使ってほしい）",
+                ids.say_mentions_ref()
+            ),
+            Error::RateLimited {
+                max_commands,
+                window_seconds,
+            } => write!(
+                f,
+                "コマンドは{}秒に{}回までにしてほしい。少し待ってからもう一度どうぞ",
+                window_seconds, max_commands
+            ),
+            Error::NotOwner => f.write_str("ボットの運用者しか使えない"),
+            Error::MissingAttachment => f.write_str("設定ファイルを添付してほしい"),
+            Error::InvalidReminderText(InvalidTemplateError::UnknownPlaceholder(name)) => write!(
+                f,
+                "{{{}}} は使えない（使えるのは {{remaining}}、{{targets}}、{{time}} です）",
+                name
+            ),
+            Error::InvalidReminderText(InvalidTemplateError::Unclosed) => {
+                f.write_str("リマインド文の { が閉じられていない")
+            }
+            Error::InvalidSettingFile(_) => f.write_str("設定ファイルが読めない"),
+            Error::InvalidSettingValue(field) => write!(f, "設定ファイルの `{}` の値が正しくない", field),
+            Error::AttachmentTooLarge { max_bytes } => {
+                write!(f, "添付ファイルは {} KiB までにしてほしい", max_bytes / 1024)
+            }
+            Error::UnsupportedAttachment => f.write_str("JSON ファイルを添付してほしい"),
+            Error::InvalidWebhookUrl(_) => f.write_str("https:// で始まる、外部からアクセスできる URL を指定してほしい"),
+            Error::InvalidLogChannel(_) => f.write_str("このサーバーの書き込めるテキストチャンネルを指定してほしい"),
+            Error::UnusableEmoji(emoji) => {
+                write!(f, "{} はこのサーバーで使える絵文字ではない", emoji)
+            }
+            Error::PhraseTooLong { max_length } => {
+                write!(f, "ひとことは{}文字以内にしてほしい", max_length)
+            }
+            Error::InvalidLogFilter(_) => {
+                f.write_str("ログのフィルタが読めない（`info,kaisantantoudaijin=debug` の形で指定してほしい）")
+            }
+            Error::SettingConflict => {
+                f.write_str("他の人が同時に設定を変更しました。もう一度試してください")
+            }
             _ => f.write_str("ダメそう"),
         }
     }
@@ -6,11 +6,27 @@ macro_rules! sayln {
     ($dst:expr, $fmt:literal, $($arg:expr),*) => { writeln!($dst, $fmt, $( crate::say::SayExt::display_say($arg) ),*) }
 }
 
+pub mod activity;
+pub mod bot;
+pub mod config;
 pub mod context;
+pub mod database;
+pub mod dispatcher;
 pub mod error;
+pub mod log;
 pub mod model;
+pub mod prefix;
+pub mod presence;
+pub mod registry;
 pub mod say;
+pub mod scheduler;
+pub mod sink;
+pub mod soak;
 pub mod use_case;
+pub mod voice;
+pub mod web;
+
+pub use bot::{Bot, BotBuilder};
 
 #[cfg(test)]
 mod test;
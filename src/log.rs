@@ -0,0 +1,27 @@
+use std::sync::Arc;
+
+use tracing_subscriber::EnvFilter;
+
+type Reload = dyn Fn(EnvFilter) -> anyhow::Result<()> + Send + Sync;
+
+/// Replaces the filter of the logs while the bot is running, such as with
+/// `tracing_subscriber::reload`.
+#[derive(Clone)]
+pub struct LogFilterHandle {
+    reload: Arc<Reload>,
+}
+
+impl LogFilterHandle {
+    pub fn new(reload: impl Fn(EnvFilter) -> anyhow::Result<()> + Send + Sync + 'static) -> Self {
+        LogFilterHandle {
+            reload: Arc::new(reload),
+        }
+    }
+
+    /// Parses the directives in the syntax of `KAISANDAIJIN_LOG`, such as
+    /// `info,kaisantantoudaijin=debug`, and replaces the whole filter with them.
+    pub fn set(&self, directives: &str) -> anyhow::Result<()> {
+        let filter = EnvFilter::try_new(directives)?;
+        (self.reload)(filter)
+    }
+}
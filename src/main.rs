@@ -1,101 +1,108 @@
+use std::net::SocketAddr;
 use std::path::PathBuf;
+use std::time::Duration;
 
 use anyhow::{Context as _, Result};
-use clap::Parser;
-use serenity::{
-    client::{Client, EventHandler},
-    model::gateway::GatewayIntents,
+use clap::{
+    parser::ValueSource, ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum,
 };
+use serenity::{http::Http, model::id::UserId};
+use tracing_subscriber::{layer::SubscriberExt as _, util::SubscriberInitExt as _};
 
 use kaisantantoudaijin::{
-    context::{ChannelContext, ContextBuilder},
-    model::message::Message,
+    bot::DEFAULT_COMMAND_PREFIX,
+    config::{Config, DatabaseKind},
+    context::DEFAULT_LATE_GRACE_SECONDS,
+    database::{
+        migrate, AnyDatabaseHandle, Cached, InMemoryHandle, RedisOptions, RedisTopology,
+        RetryPolicy, Retrying, DEFAULT_CACHE_TTL, DEFAULT_INITIAL_BACKOFF, DEFAULT_MAX_ATTEMPTS,
+    },
+    dispatcher::{Dispatcher, DEFAULT_MAX_RUNNING, DEFAULT_MAX_RUNNING_PER_GUILD},
+    log::LogFilterHandle,
+    voice::VoiceAnnouncer,
+    web::CancelLinks,
+    Bot,
 };
 
-fn strip_affix<'a>(content: &'a str, affix: &str) -> Option<&'a str> {
-    content
-        .strip_prefix(affix)
-        .or_else(|| content.strip_suffix(affix))
+async fn shutdown_signal() -> Result<()> {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        let mut sigterm = signal(SignalKind::terminate())?;
+        tokio::select! {
+            r = tokio::signal::ctrl_c() => r?,
+            _ = sigterm.recv() => {},
+        }
+    }
+    #[cfg(not(unix))]
+    tokio::signal::ctrl_c().await?;
+    Ok(())
 }
 
-struct Handler {
-    command_prefix: String,
-    redis_prefix: String,
-    redis: deadpool_redis::Pool,
+#[derive(Clone, Copy, ValueEnum)]
+enum Database {
+    Redis,
+    /// Keep everything in memory; all settings are lost on exit
+    Memory,
 }
 
-#[async_trait::async_trait]
-impl EventHandler for Handler {
-    async fn message(
-        &self,
-        ctx: serenity::client::Context,
-        msg: serenity::model::channel::Message,
-    ) {
-        if msg.author.bot {
-            return;
-        }
-
-        let bot_id = ctx.cache.current_user().id;
-        let command = strip_affix(&msg.content, &format!("<@{}>", bot_id))
-            .or_else(|| strip_affix(&msg.content, &format!("<@!{}>", bot_id)))
-            .or_else(|| msg.content.strip_prefix(&self.command_prefix))
-            .map(str::trim);
-
-        let Some(command) = command else {
-            return;
-        };
-
-        let Some(guild_id) = msg.guild_id else {
-            let _ = msg
-                .channel_id
-                .say(&ctx.http, "サーバー内で使ってください")
-                .await;
-            return;
-        };
-
-        let redis_conn = match self.redis.get().await {
-            Ok(x) => x,
-            Err(e) => {
-                tracing::error!("error in getting redis connection: {:#}", e);
-                let _ = msg.channel_id.say(&ctx.http, "エラーが発生しました").await;
-                return;
-            }
-        };
-
-        let ctx = ContextBuilder::with_serenity(&ctx)
-            .redis_prefix(self.redis_prefix.clone())
-            .redis_conn(redis_conn)
-            .guild_id(guild_id)
-            .message(&msg)
-            .build()
-            .unwrap();
-
-        if let Err(e) = ctx.handle_command(command).await {
-            tracing::error!("error in handling command: {:#}", e);
-            let _ = ctx.message(Message::HandleError(e)).await;
-        }
-    }
+#[derive(Subcommand)]
+enum Command {
+    /// Copy the keys of all guilds to another prefix or redis server, and exit. The bot should be
+    /// stopped meanwhile
+    Migrate(MigrateArgs),
+}
 
-    async fn cache_ready(
-        &self,
-        _ctx: serenity::client::Context,
-        guild_ids: Vec<serenity::model::id::GuildId>,
-    ) {
-        tracing::info!(?guild_ids, "cache is ready");
-    }
+#[derive(clap::Args)]
+struct MigrateArgs {
+    /// Prefix to copy the keys from, defaults to `--redis-prefix`
+    #[arg(long)]
+    from_prefix: Option<String>,
+    /// Prefix to copy the keys to
+    #[arg(long)]
+    to_prefix: String,
+    /// Redis server to copy the keys to, defaults to the one the keys are copied from
+    #[arg(long)]
+    to_redis_uri: Option<String>,
+    /// File holding the password of the redis server given with `--to-redis-uri`
+    #[arg(long, requires = "to_redis_uri")]
+    to_redis_password_file: Option<PathBuf>,
 }
 
 #[derive(Parser)]
-#[command(group(clap::ArgGroup::new("tokens").required(true).multiple(false).args(["token", "token_file"])))]
+#[command(group(clap::ArgGroup::new("tokens").multiple(false).args(["token", "token_file"])))]
 struct Args {
-    #[arg(long, default_value = "!kaisan", env = "KAISANDAIJIN_COMMAND_PREFIX")]
+    /// Read the settings from this TOML file. The options and the environment variables take
+    /// precedence over it
+    #[arg(long, env = "KAISANDAIJIN_CONFIG")]
+    config: Option<PathBuf>,
+    #[arg(long, default_value = DEFAULT_COMMAND_PREFIX, env = "KAISANDAIJIN_COMMAND_PREFIX")]
     command_prefix: String,
     #[arg(long, env = "KAISANDAIJIN_DISCORD_TOKEN", hide_env_values = true)]
     token: Option<String>,
     #[arg(long, env = "KAISANDAIJIN_DISCORD_TOKEN_FILE")]
     token_file: Option<PathBuf>,
+    #[arg(
+        long,
+        value_enum,
+        default_value = "redis",
+        env = "KAISANDAIJIN_DATABASE"
+    )]
+    database: Database,
+    /// URL of the redis server, which may be a `rediss://` one to connect over TLS
     #[arg(short, long, env = "KAISANDAIJIN_REDIS_URI")]
-    redis_uri: String,
+    redis_uri: Option<String>,
+    /// URLs of some of the nodes of a Redis Cluster to use instead of a single server
+    #[arg(
+        long = "redis-cluster-node",
+        env = "KAISANDAIJIN_REDIS_CLUSTER_NODES",
+        value_delimiter = ',',
+        conflicts_with = "redis_uri"
+    )]
+    redis_cluster_nodes: Vec<String>,
+    /// File holding the password of redis, which takes precedence over the one in the URL
+    #[arg(long, env = "KAISANDAIJIN_REDIS_PASSWORD_FILE")]
+    redis_password_file: Option<PathBuf>,
     #[arg(
         short = 'p',
         long,
@@ -103,24 +110,302 @@ struct Args {
         env = "KAISANDAIJIN_REDIS_PREFIX"
     )]
     redis_prefix: String,
+    /// Size the redis connection pool by this many connections per shard, instead of the default
+    #[arg(long, env = "KAISANDAIJIN_REDIS_CONNECTIONS_PER_SHARD")]
+    redis_connections_per_shard: Option<usize>,
+    /// Times to try a redis operation that failed on the connection, including the first one
+    #[arg(
+        long,
+        default_value_t = DEFAULT_MAX_ATTEMPTS,
+        value_parser = clap::value_parser!(u32).range(1..),
+        env = "KAISANDAIJIN_REDIS_MAX_ATTEMPTS"
+    )]
+    redis_max_attempts: u32,
+    /// Wait before the first retry of a redis operation, doubled on each retry
+    #[arg(
+        long,
+        default_value_t = DEFAULT_INITIAL_BACKOFF.as_millis() as u64,
+        env = "KAISANDAIJIN_REDIS_RETRY_BACKOFF_MS"
+    )]
+    redis_retry_backoff_ms: u64,
+    /// Keep the settings read from redis in memory for this long; changes made by another process
+    /// sharing the database are seen only after that. `0` disables the cache
+    #[arg(
+        long,
+        default_value_t = DEFAULT_CACHE_TTL.as_secs(),
+        env = "KAISANDAIJIN_REDIS_CACHE_TTL_SECONDS"
+    )]
+    redis_cache_ttl_seconds: u64,
+    /// Number of shards to start, defaults to the number recommended by Discord
+    #[arg(long, env = "KAISANDAIJIN_SHARDS")]
+    shards: Option<u32>,
+    /// Record every disconnection in the database in addition to the log
+    #[arg(long, env = "KAISANDAIJIN_RECORD_DISCONNECTS")]
+    record_disconnects: bool,
+    /// Number of scheduled kaisans, reminders and countdowns that can run at once
+    #[arg(long, default_value_t = DEFAULT_MAX_RUNNING, env = "KAISANDAIJIN_MAX_RUNNING")]
+    max_running: usize,
+    /// Number of the slots for scheduled work that a single guild can take
+    #[arg(
+        long,
+        default_value_t = DEFAULT_MAX_RUNNING_PER_GUILD,
+        env = "KAISANDAIJIN_MAX_RUNNING_PER_GUILD"
+    )]
+    max_running_per_guild: usize,
+    /// Skip scheduled kaisans and reminders that are late by more than this, such as after the
+    /// host was suspended
+    #[arg(
+        long,
+        default_value_t = DEFAULT_LATE_GRACE_SECONDS,
+        value_parser = clap::value_parser!(i64).range(0..),
+        env = "KAISANDAIJIN_LATE_GRACE_SECONDS"
+    )]
+    late_grace_seconds: i64,
+    /// Serve the pages to cancel schedules with the links sent by DM at this address, such as
+    /// `0.0.0.0:8080`
+    #[arg(
+        long,
+        env = "KAISANDAIJIN_HTTP_LISTEN",
+        requires_all = ["public_url", "link_secret"]
+    )]
+    http_listen: Option<SocketAddr>,
+    /// URL of the HTTP server as seen from the users, which the cancel links point to
+    #[arg(long, env = "KAISANDAIJIN_PUBLIC_URL")]
+    public_url: Option<String>,
+    /// Secret to sign the cancel links with; changing it invalidates the links already sent
+    #[arg(long, env = "KAISANDAIJIN_LINK_SECRET")]
+    link_secret: Option<String>,
+    /// Audio file, such as a countdown, to play in the voice channel 10 seconds before each kaisan.
+    /// Needs the bot to be built with the `voice` feature
+    #[arg(long, env = "KAISANDAIJIN_VOICE_ANNOUNCEMENT")]
+    voice_announcement: Option<PathBuf>,
+    /// Users who can run the commands for operators, such as `admin schedules`
+    #[arg(
+        long = "owner",
+        env = "KAISANDAIJIN_OWNERS",
+        value_delimiter = ',',
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    owners: Vec<u64>,
     /// Specify log level filter, configured in conjunction with KAISANDAIJIN_LOG environment variable
     #[arg(short, long)]
     log_level: Option<tracing_subscriber::filter::LevelFilter>,
+    #[command(subcommand)]
+    command: Option<Command>,
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    let args = Args::parse();
+impl Args {
+    fn parse_with_config() -> Result<Args> {
+        let matches = Args::command().get_matches();
+        let mut args = Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+        if let Some(path) = &args.config {
+            let config = Config::load(path)?;
+            args.merge(config, &matches);
+        }
+        Ok(args)
+    }
+
+    /// Takes the settings from the file unless they are given on the command line or in the
+    /// environment.
+    fn merge(&mut self, config: Config, matches: &ArgMatches) {
+        let unset = |id: &str| {
+            matches!(
+                matches.value_source(id),
+                None | Some(ValueSource::DefaultValue)
+            )
+        };
+
+        if unset("token") && unset("token_file") {
+            self.token = config.token;
+            self.token_file = config.token_file;
+        }
+        if let Some(prefix) = config.command_prefix.filter(|_| unset("command_prefix")) {
+            self.command_prefix = prefix;
+        }
+        if let Some(database) = config.database.filter(|_| unset("database")) {
+            self.database = match database {
+                DatabaseKind::Redis => Database::Redis,
+                DatabaseKind::Memory => Database::Memory,
+            };
+        }
+        if unset("redis_uri") && unset("redis_cluster_nodes") {
+            if let Some(uri) = config.redis.uri {
+                self.redis_uri = Some(uri);
+            }
+            if let Some(nodes) = config.redis.cluster_nodes {
+                self.redis_cluster_nodes = nodes;
+            }
+        }
+        if let Some(path) = config
+            .redis
+            .password_file
+            .filter(|_| unset("redis_password_file"))
+        {
+            self.redis_password_file = Some(path);
+        }
+        if let Some(prefix) = config.redis.prefix.filter(|_| unset("redis_prefix")) {
+            self.redis_prefix = prefix;
+        }
+        if let Some(per_shard) = config
+            .redis
+            .connections_per_shard
+            .filter(|_| unset("redis_connections_per_shard"))
+        {
+            self.redis_connections_per_shard = Some(per_shard);
+        }
+        if let Some(max_attempts) = config
+            .redis
+            .max_attempts
+            .filter(|_| unset("redis_max_attempts"))
+        {
+            self.redis_max_attempts = max_attempts;
+        }
+        if let Some(backoff) = config
+            .redis
+            .retry_backoff_ms
+            .filter(|_| unset("redis_retry_backoff_ms"))
+        {
+            self.redis_retry_backoff_ms = backoff;
+        }
+        if let Some(ttl) = config
+            .redis
+            .cache_ttl_seconds
+            .filter(|_| unset("redis_cache_ttl_seconds"))
+        {
+            self.redis_cache_ttl_seconds = ttl;
+        }
+        if let Some(shards) = config.shards.filter(|_| unset("shards")) {
+            self.shards = Some(shards);
+        }
+        if let Some(record) = config
+            .record_disconnects
+            .filter(|_| unset("record_disconnects"))
+        {
+            self.record_disconnects = record;
+        }
+        if let Some(max_running) = config
+            .dispatcher
+            .max_running
+            .filter(|_| unset("max_running"))
+        {
+            self.max_running = max_running;
+        }
+        if let Some(per_guild) = config
+            .dispatcher
+            .max_running_per_guild
+            .filter(|_| unset("max_running_per_guild"))
+        {
+            self.max_running_per_guild = per_guild;
+        }
+        if let Some(late_grace) = config
+            .late_grace_seconds
+            .filter(|_| unset("late_grace_seconds"))
+        {
+            self.late_grace_seconds = late_grace;
+        }
+        if let Some(http) = config
+            .http
+            .filter(|_| unset("http_listen") && unset("public_url") && unset("link_secret"))
+        {
+            self.http_listen = Some(http.listen);
+            self.public_url = Some(http.public_url);
+            self.link_secret = Some(http.link_secret);
+        }
+        if let Some(audio) = config
+            .voice_announcement
+            .filter(|_| unset("voice_announcement"))
+        {
+            self.voice_announcement = Some(audio);
+        }
+        if let Some(owners) = config.owners.filter(|_| unset("owners")) {
+            self.owners = owners;
+        }
+        // validated when the config is loaded
+        if let Some(level) = config
+            .log_level
+            .and_then(|level| level.parse().ok())
+            .filter(|_| unset("log_level"))
+        {
+            self.log_level = Some(level);
+        }
+    }
+}
+
+#[cfg(feature = "voice")]
+fn voice_announcer(audio: Option<PathBuf>) -> Option<VoiceAnnouncer> {
+    audio.map(VoiceAnnouncer::new)
+}
+
+#[cfg(not(feature = "voice"))]
+fn voice_announcer(audio: Option<PathBuf>) -> Option<VoiceAnnouncer> {
+    if audio.is_some() {
+        tracing::warn!("built without the voice feature, ignoring --voice-announcement");
+    }
+    None
+}
+
+fn redis_topology(uri: Option<String>, cluster_nodes: Vec<String>) -> Result<RedisTopology> {
+    match uri {
+        Some(uri) => Ok(RedisTopology::Single(uri)),
+        None if !cluster_nodes.is_empty() => Ok(RedisTopology::Cluster(cluster_nodes)),
+        None => {
+            anyhow::bail!("--redis-uri or --redis-cluster-node is required to use redis database")
+        }
+    }
+}
 
-    let redis = deadpool_redis::Config::from_url(args.redis_uri)
-        .create_pool(Some(deadpool_redis::Runtime::Tokio1))?;
+async fn run_migrate(args: Args, migrate_args: MigrateArgs) -> Result<()> {
+    anyhow::ensure!(
+        matches!(args.database, Database::Redis),
+        "only redis database can be migrated"
+    );
+    let from_prefix = migrate_args.from_prefix.unwrap_or(args.redis_prefix);
+    anyhow::ensure!(
+        from_prefix != migrate_args.to_prefix || migrate_args.to_redis_uri.is_some(),
+        "the keys cannot be copied onto themselves"
+    );
 
-    let token = if let Some(token) = args.token {
-        token
-    } else {
-        tokio::fs::read_to_string(args.token_file.unwrap()).await?
+    let topology = redis_topology(args.redis_uri, args.redis_cluster_nodes)?;
+    let (to_topology, to_password_file) = match migrate_args.to_redis_uri {
+        Some(uri) => (
+            RedisTopology::Single(uri),
+            migrate_args.to_redis_password_file,
+        ),
+        None => (topology.clone(), args.redis_password_file.clone()),
     };
-    let token = token.trim();
+    let policy = RetryPolicy {
+        max_attempts: args.redis_max_attempts,
+        initial_backoff: Duration::from_millis(args.redis_retry_backoff_ms),
+    };
+    let from = RedisOptions {
+        topology,
+        password_file: args.redis_password_file,
+        pool_size: None,
+        prefix: from_prefix,
+    }
+    .connect()
+    .await?;
+    let to = RedisOptions {
+        topology: to_topology,
+        password_file: to_password_file,
+        pool_size: None,
+        prefix: migrate_args.to_prefix,
+    }
+    .connect()
+    .await?;
+
+    let summary = migrate(&Retrying::new(from, policy), &Retrying::new(to, policy)).await?;
+    tracing::info!(
+        guilds = summary.guilds,
+        keys = summary.keys,
+        "migration finished"
+    );
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let mut args = Args::parse_with_config()?;
 
     let env_filter = tracing_subscriber::EnvFilter::from_env("KAISANDAIJIN_LOG");
     let env_filter = args
@@ -129,27 +414,85 @@ async fn main() -> Result<()> {
         .fold(env_filter, |filter, level| {
             filter.add_directive(level.into())
         });
-    tracing_subscriber::fmt()
-        .with_env_filter(env_filter)
-        .with_writer(std::io::stderr)
+    let (env_filter, reload) = tracing_subscriber::reload::Layer::new(env_filter);
+    tracing_subscriber::registry()
+        .with(env_filter)
+        .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
         .init();
+    let log_filter = LogFilterHandle::new(move |filter| Ok(reload.reload(filter)?));
+
+    if let Some(Command::Migrate(migrate_args)) = args.command.take() {
+        return run_migrate(args, migrate_args).await;
+    }
 
-    let intents = [
-        GatewayIntents::GUILDS,
-        GatewayIntents::GUILD_MESSAGES,
-        GatewayIntents::GUILD_VOICE_STATES,
-        GatewayIntents::MESSAGE_CONTENT,
-    ]
-    .into_iter()
-    .collect();
-    let mut client = Client::builder(token, intents)
-        .event_handler(Handler {
-            command_prefix: args.command_prefix,
-            redis_prefix: args.redis_prefix,
-            redis,
-        })
-        .await
-        .context("Failed to create client")?;
-
-    client.start().await.context("Client error")
+    let token = match (args.token, args.token_file) {
+        (Some(token), _) => token,
+        (None, Some(token_file)) => tokio::fs::read_to_string(token_file).await?,
+        (None, None) => anyhow::bail!("either --token or --token-file is required"),
+    };
+    let token = token.trim();
+    let late_grace =
+        chrono::Duration::try_seconds(args.late_grace_seconds).context("late grace is too long")?;
+
+    let shards = match args.shards {
+        Some(shards) => shards,
+        None => {
+            Http::new(token)
+                .get_bot_gateway()
+                .await
+                .context("cannot obtain the recommended number of shards")?
+                .shards
+        }
+    };
+    tracing::info!(shards, "starting bot");
+
+    let database: AnyDatabaseHandle = match args.database {
+        Database::Redis => {
+            let redis = RedisOptions {
+                topology: redis_topology(args.redis_uri, args.redis_cluster_nodes)?,
+                password_file: args.redis_password_file,
+                pool_size: args
+                    .redis_connections_per_shard
+                    .map(|per_shard| per_shard * shards as usize),
+                prefix: args.redis_prefix,
+            }
+            .connect()
+            .await?;
+            let policy = RetryPolicy {
+                max_attempts: args.redis_max_attempts,
+                initial_backoff: Duration::from_millis(args.redis_retry_backoff_ms),
+            };
+            let redis = Retrying::new(redis, policy);
+            Cached::new(redis, Duration::from_secs(args.redis_cache_ttl_seconds)).into()
+        }
+        Database::Memory => InMemoryHandle::new().into(),
+    };
+
+    let mut builder = Bot::builder(token);
+    builder
+        .command_prefix(args.command_prefix)
+        .database(database)
+        .dispatcher(Dispatcher::new(
+            args.max_running,
+            args.max_running_per_guild,
+        ))
+        .records_disconnects(args.record_disconnects)
+        .late_grace(late_grace)
+        .owners(args.owners.into_iter().map(UserId::new))
+        .voice(voice_announcer(args.voice_announcement))
+        .log_filter(Some(log_filter));
+    if let (Some(addr), Some(public_url), Some(secret)) =
+        (args.http_listen, args.public_url, args.link_secret)
+    {
+        builder.http_server(addr, CancelLinks::new(public_url, &secret));
+    }
+    let bot = builder.build().await?;
+
+    bot.run(Some(shards), async {
+        if let Err(e) = shutdown_signal().await {
+            tracing::error!("cannot listen for shutdown signal: {:#}", e);
+            std::future::pending::<()>().await;
+        }
+    })
+    .await
 }
@@ -1,5 +1,18 @@
+pub mod api_token;
+pub mod cancel_link;
 pub mod command;
+pub mod event;
+pub mod health;
+pub mod hint;
 pub mod kaisanee;
+pub mod member;
+pub mod menu;
 pub mod message;
+pub mod notification;
+pub mod permission;
+pub mod reaction;
 pub mod reminder;
+pub mod schedule;
+pub mod setting;
+pub mod template;
 pub mod time;
@@ -0,0 +1,82 @@
+use std::fmt::{self, Write as _};
+
+use rand::{distributions::Alphanumeric, rngs::OsRng, Rng};
+use ring::{hmac, rand::SystemRandom};
+
+const TOKEN_PREFIX: &str = "kst_";
+const TOKEN_LENGTH: usize = 40;
+
+/// Secret that lets external clients access the resources of a guild. Only its hash is stored.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ApiToken(String);
+
+impl ApiToken {
+    pub fn generate() -> ApiToken {
+        let random: String = OsRng
+            .sample_iter(&Alphanumeric)
+            .take(TOKEN_LENGTH)
+            .map(char::from)
+            .collect();
+        ApiToken(format!("{}{}", TOKEN_PREFIX, random))
+    }
+
+    pub fn from_string(token: String) -> ApiToken {
+        ApiToken(token)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Hex-encoded SHA-256 of the token, which is what is stored in the database.
+    pub fn hash(&self) -> String {
+        let digest = ring::digest::digest(&ring::digest::SHA256, self.0.as_bytes());
+        let mut hash = String::new();
+        for b in digest.as_ref() {
+            write!(hash, "{:02x}", b).unwrap();
+        }
+        hash
+    }
+
+    pub fn matches(&self, hash: &str) -> bool {
+        // compares the tags of both under a throwaway key, which takes the same time wherever they
+        // differ
+        let Ok(key) = hmac::Key::generate(hmac::HMAC_SHA256, &SystemRandom::new()) else {
+            return false;
+        };
+        let tag = hmac::sign(&key, hash.as_bytes());
+        hmac::verify(&key, self.hash().as_bytes(), tag.as_ref()).is_ok()
+    }
+}
+
+// not to leak the token into the logs
+impl fmt::Debug for ApiToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ApiToken(..)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ApiToken;
+
+    #[test]
+    fn test_hash() {
+        let token = ApiToken::from_string("kst_abc".to_owned());
+        assert_eq!(
+            token.hash(),
+            "fd451823367528deebdf6988a2e9b387b24f35c8f56f6845fcc32413a1f433e2"
+        );
+    }
+
+    #[test]
+    fn test_generate() {
+        let token = ApiToken::generate();
+        assert!(token.as_str().starts_with("kst_"));
+        assert_eq!(token.as_str().len(), 44);
+        assert_ne!(token, ApiToken::generate());
+        assert!(token.matches(&token.hash()));
+        assert!(!ApiToken::generate().matches(&token.hash()));
+        assert_eq!(format!("{:?}", token), "ApiToken(..)");
+    }
+}
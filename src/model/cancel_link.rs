@@ -0,0 +1,128 @@
+use std::fmt::Write as _;
+
+use crate::model::schedule::{Schedule, ScheduleId};
+
+use ring::hmac;
+use serenity::model::id::GuildId;
+
+/// Secret to sign the cancel links with.
+#[derive(Clone)]
+pub struct CancelLinkKey(hmac::Key);
+
+impl CancelLinkKey {
+    pub fn new(secret: &[u8]) -> Self {
+        CancelLinkKey(hmac::Key::new(hmac::HMAC_SHA256, secret))
+    }
+}
+
+/// Reference to a schedule that lets whoever has its signed token cancel it without Discord.
+///
+/// The link is bound to the time of the schedule as well as its id, so that it cannot cancel
+/// another schedule that happens to get the same id later.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CancelLink {
+    pub guild_id: GuildId,
+    pub schedule_id: ScheduleId,
+    /// [`Schedule::public_time`] in seconds, not to reveal a random time.
+    pub timestamp: i64,
+}
+
+impl CancelLink {
+    pub fn new(guild_id: GuildId, schedule_id: ScheduleId, schedule: &Schedule) -> Self {
+        CancelLink {
+            guild_id,
+            schedule_id,
+            timestamp: schedule.public_time().timestamp(),
+        }
+    }
+
+    /// Whether the link was made for the schedule.
+    pub fn matches(&self, schedule: &Schedule) -> bool {
+        schedule.public_time().timestamp() == self.timestamp
+    }
+
+    fn payload(&self) -> String {
+        format!(
+            "{}-{}-{}",
+            self.guild_id,
+            self.schedule_id.as_u32(),
+            self.timestamp
+        )
+    }
+
+    pub fn token(&self, key: &CancelLinkKey) -> String {
+        let payload = self.payload();
+        let tag = hmac::sign(&key.0, payload.as_bytes());
+        let mut token = payload;
+        token.push('-');
+        for b in tag.as_ref() {
+            write!(token, "{:02x}", b).unwrap();
+        }
+        token
+    }
+
+    /// Returns the link if the token has been signed with the key.
+    pub fn verify(key: &CancelLinkKey, token: &str) -> Option<CancelLink> {
+        let (payload, tag) = token.rsplit_once('-')?;
+        let tag = decode_hex(tag)?;
+        hmac::verify(&key.0, payload.as_bytes(), &tag).ok()?;
+
+        let mut parts = payload.splitn(3, '-');
+        let guild_id = parts.next()?.parse().ok()?;
+        let schedule_id = parts.next()?.parse().ok()?;
+        let timestamp = parts.next()?.parse().ok()?;
+        Some(CancelLink {
+            guild_id: GuildId::new(guild_id),
+            schedule_id: ScheduleId::new(schedule_id),
+            timestamp,
+        })
+    }
+}
+
+fn decode_hex(s: &str) -> Option<Vec<u8>> {
+    if s.len() % 2 != 0 {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CancelLink, CancelLinkKey};
+    use crate::model::schedule::ScheduleId;
+
+    use serenity::model::id::GuildId;
+
+    fn link() -> CancelLink {
+        CancelLink {
+            guild_id: GuildId::new(1234),
+            schedule_id: ScheduleId::new(3),
+            timestamp: 1_700_000_000,
+        }
+    }
+
+    #[test]
+    fn test_verify() {
+        let key = CancelLinkKey::new(b"secret");
+        let token = link().token(&key);
+        assert!(token.starts_with("1234-3-1700000000-"));
+        assert_eq!(CancelLink::verify(&key, &token), Some(link()));
+    }
+
+    #[test]
+    fn test_tampered() {
+        let key = CancelLinkKey::new(b"secret");
+        let token = link().token(&key);
+        let tampered = token.replacen("1234-3-", "1234-4-", 1);
+        assert_eq!(CancelLink::verify(&key, &tampered), None);
+        assert_eq!(
+            CancelLink::verify(&CancelLinkKey::new(b"other"), &token),
+            None
+        );
+        assert_eq!(CancelLink::verify(&key, "1234-3-1700000000-zz"), None);
+        assert_eq!(CancelLink::verify(&key, ""), None);
+    }
+}
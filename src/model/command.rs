@@ -1,16 +1,29 @@
+use std::borrow::Cow;
 use std::error::Error;
 use std::fmt::{self, Display};
 use std::str::FromStr;
 
-use chrono::DateTime;
+use chrono::{DateTime, Weekday};
 use chrono_tz::Tz;
-use serenity::model::id::UserId;
+use serenity::model::{
+    channel::ReactionType,
+    id::{ChannelId, GuildId, RoleId, UserId},
+};
 
 use crate::model::{
     kaisanee::KaisaneeSpecifier,
+    reaction::{parse_emoji, Outcome},
     reminder::Reminder,
-    time::{AfterTimeSpecifier, AtTimeSpecifier, Hour, Minute, TimeSpecifier},
+    schedule::ScheduleId,
+    setting::{
+        AuthorLeavePolicy, ChannelOverride, DstPolicy, DuplicatePolicy, Locale, RandomDistribution,
+        RevealRandom, WeekStart,
+    },
+    time::{AfterTimeSpecifier, AtTimeSpecifier, DateSpecifier, Hour, Minute, TimeSpecifier},
 };
+use crate::soak::MAX_SOAK_INTERVAL_MINUTES;
+
+mod format;
 
 #[derive(Debug, Clone, PartialEq, Eq, Copy)]
 pub enum TimeRangeSpecifier {
@@ -19,18 +32,92 @@ pub enum TimeRangeSpecifier {
     Now,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct KaisanOptions {
+    pub remind_only_me: bool,
+    /// Words between the mentions of the targets that were skipped, such as `<@1> foo <@2>`
+    pub ignored_tokens: Vec<String>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Command {
     Kaisan {
         kaisanee: KaisaneeSpecifier,
         time_range: TimeRangeSpecifier,
+        options: KaisanOptions,
+    },
+    /// Several kaisans at once, such as `me at 23:00, all at 24:00`
+    KaisanMulti {
+        stages: Vec<(KaisaneeSpecifier, TimeRangeSpecifier)>,
+        options: KaisanOptions,
+    },
+    Preview {
+        kaisanee: KaisaneeSpecifier,
+        time_range: TimeRangeSpecifier,
     },
     ShowSetting,
+    SaveProfile(String),
+    LoadProfile(String),
+    ListProfiles,
+    ExportSetting,
+    ImportSetting,
+    GenerateApiToken,
+    RevokeApiToken,
     TimeZone(Tz),
+    TimeZoneWizard,
     RequirePermission(bool),
+    RequirePermissionSelf(bool),
     AddReminder(Reminder),
     RemoveReminder(Reminder),
+    ClearReminders,
+    ListReminders,
     RemindRandomKaisan(bool),
+    Countdown(bool),
+    AuthorLeave(AuthorLeavePolicy),
+    RevealRandom(RevealRandom),
+    RandomDistribution(RandomDistribution),
+    Dst(DstPolicy),
+    WeekStart(WeekStart),
+    Locale(Locale),
+    OnDuplicate(DuplicatePolicy),
+    AutoKaisan(Option<Hour>),
+    MaxHorizon(u8),
+    Tonight(Hour),
+    MaxSchedules(u8),
+    /// Minutes a schedule can be undone in, or zero to disable `undo`
+    UndoWindow(u8),
+    ReminderText(Option<String>),
+    Prefix(Option<String>),
+    Webhook(Option<String>),
+    LogChannel(Option<ChannelId>),
+    Reaction(Outcome, Option<ReactionType>),
+    Phrase(Outcome, Option<String>),
+    When,
+    Ping,
+    AbortAll,
+    Pause,
+    Resume,
+    AdminSchedules,
+    AdminBroadcast(String),
+    AdminStats,
+    AdminStorage(Option<GuildId>),
+    AdminLogLevel(String),
+    /// Minutes between the canaries, or `None` to stop
+    AdminSoak(Option<u32>),
+    CancelMine,
+    Undo,
+    AllowChannel(Option<ChannelId>),
+    DenyChannel(Option<ChannelId>),
+    /// Overrides a setting in the voice channel, or shows the overrides if `None`
+    ChannelSetting(ChannelId, Option<ChannelOverride>),
+    AllowRole(RoleId),
+    DenyRole(RoleId),
+    ListRoles,
+    Now(ScheduleId),
+    WhoKickedMe,
+    Complain,
+    ShowComplaints,
+    ShowStats,
     Help,
 }
 
@@ -52,17 +139,122 @@ impl Display for ParseCommandError {
 
 impl Error for ParseCommandError {}
 
+impl Command {
+    /// Whether the command is for the operators of the bot rather than the guild.
+    pub fn is_admin(&self) -> bool {
+        matches!(
+            self,
+            Command::AdminSchedules
+                | Command::AdminBroadcast(_)
+                | Command::AdminStats
+                | Command::AdminStorage(_)
+                | Command::AdminLogLevel(_)
+                | Command::AdminSoak(_)
+        )
+    }
+
+    /// The name of the kind of the command in the logs, such as `add_reminder`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Command::Kaisan { .. } => "kaisan",
+            Command::KaisanMulti { .. } => "kaisan_multi",
+            Command::Preview { .. } => "preview",
+            Command::ShowSetting => "show_setting",
+            Command::SaveProfile(..) => "save_profile",
+            Command::LoadProfile(..) => "load_profile",
+            Command::ListProfiles => "list_profiles",
+            Command::ExportSetting => "export_setting",
+            Command::ImportSetting => "import_setting",
+            Command::GenerateApiToken => "generate_api_token",
+            Command::RevokeApiToken => "revoke_api_token",
+            Command::TimeZone(..) => "time_zone",
+            Command::TimeZoneWizard => "time_zone_wizard",
+            Command::RequirePermission(..) => "require_permission",
+            Command::RequirePermissionSelf(..) => "require_permission_self",
+            Command::AddReminder(..) => "add_reminder",
+            Command::RemoveReminder(..) => "remove_reminder",
+            Command::ClearReminders => "clear_reminders",
+            Command::ListReminders => "list_reminders",
+            Command::RemindRandomKaisan(..) => "remind_random_kaisan",
+            Command::Countdown(..) => "countdown",
+            Command::AuthorLeave(..) => "author_leave",
+            Command::RevealRandom(..) => "reveal_random",
+            Command::RandomDistribution(..) => "random_distribution",
+            Command::Dst(..) => "dst",
+            Command::WeekStart(..) => "week_start",
+            Command::Locale(..) => "locale",
+            Command::OnDuplicate(..) => "on_duplicate",
+            Command::AutoKaisan(..) => "auto_kaisan",
+            Command::MaxHorizon(..) => "max_horizon",
+            Command::Tonight(..) => "tonight",
+            Command::MaxSchedules(..) => "max_schedules",
+            Command::UndoWindow(..) => "undo_window",
+            Command::ReminderText(..) => "reminder_text",
+            Command::Prefix(..) => "prefix",
+            Command::Webhook(..) => "webhook",
+            Command::LogChannel(..) => "log_channel",
+            Command::Reaction(..) => "reaction",
+            Command::Phrase(..) => "phrase",
+            Command::When => "when",
+            Command::Ping => "ping",
+            Command::AbortAll => "abort_all",
+            Command::Pause => "pause",
+            Command::Resume => "resume",
+            Command::AdminSchedules => "admin_schedules",
+            Command::AdminBroadcast(..) => "admin_broadcast",
+            Command::AdminStats => "admin_stats",
+            Command::AdminStorage(..) => "admin_storage",
+            Command::AdminLogLevel(..) => "admin_log_level",
+            Command::AdminSoak(..) => "admin_soak",
+            Command::CancelMine => "cancel_mine",
+            Command::Undo => "undo",
+            Command::AllowChannel(..) => "allow_channel",
+            Command::DenyChannel(..) => "deny_channel",
+            Command::ChannelSetting(..) => "channel_setting",
+            Command::AllowRole(..) => "allow_role",
+            Command::DenyRole(..) => "deny_role",
+            Command::ListRoles => "list_roles",
+            Command::Now(..) => "now",
+            Command::WhoKickedMe => "who_kicked_me",
+            Command::Complain => "complain",
+            Command::ShowComplaints => "show_complaints",
+            Command::ShowStats => "show_stats",
+            Command::Help => "help",
+        }
+    }
+}
+
 impl FromStr for Command {
     type Err = ParseCommandError;
 
     fn from_str(input: &str) -> Result<Command, Self::Err> {
-        parser::command(input).map_err(|e| ParseCommandError {
+        let input = normalize_width(input);
+        parser::command(&input).map_err(|e| ParseCommandError {
             got: input.get(e.location.offset..).map(ToOwned::to_owned),
             expected: e.expected,
         })
     }
 }
 
+/// Replaces the full-width digits, colons and spaces, which Japanese input methods often produce,
+/// with the ASCII ones the grammar expects. Free text such as phrases is replaced as well.
+fn normalize_width(input: &str) -> Cow<'_, str> {
+    let is_full_width = |c: char| matches!(c, '０'..='９' | '：' | '　');
+    if !input.contains(is_full_width) {
+        return Cow::Borrowed(input);
+    }
+    let normalized = input
+        .chars()
+        .map(|c| match c {
+            '０'..='９' => char::from(b'0' + (c as u32 - '０' as u32) as u8),
+            '：' => ':',
+            '　' => ' ',
+            c => c,
+        })
+        .collect();
+    Cow::Owned(normalized)
+}
+
 peg::parser! {
   grammar parser() for str {
     rule _() = quiet! { [' ']* }
@@ -81,25 +273,74 @@ peg::parser! {
       } / expected!("all")
 
     rule user() -> UserId
-      = "<@!" n:$(['0'..='9']+) ">" { UserId::new(n.parse().unwrap()) }
-      / "<@" n:$(['0'..='9']+) ">" { UserId::new(n.parse().unwrap()) }
+      = "<@" "!"? n:$(['0'..='9']+) ">" {? n.parse().ok().filter(|n| *n != 0).map(UserId::new).ok_or("user id") }
+
+    rule channel() -> ChannelId
+      = "<#" n:$(['0'..='9']+) ">" {? n.parse().ok().filter(|n| *n != 0).map(ChannelId::new).ok_or("channel id") }
+
+    rule role() -> RoleId
+      = "<@&" n:$(['0'..='9']+) ">" {? n.parse().ok().filter(|n| *n != 0).map(RoleId::new).ok_or("role id") }
+
+    rule user_separator()
+      = _ ([',' | '，' | '、'] / "と" / "and" !['a'..='z' | 'A'..='Z']) _
+      / _
+
+    // anything up to the next separator or mention, skipped when followed by more users
+    rule ignored_token() -> &'input str
+      = !time_range() t:$((!user() !user_separator_char() [_])+) { t }
+
+    rule user_separator_char() = [' ' | ',' | '，' | '、']
 
-    rule users() -> Vec<UserId>
-      = l:user() ** _ {? if l.is_empty() { Err("non-empty list of users") } else { Ok(l) } }
+    rule users() -> (Vec<UserId>, Vec<&'input str>)
+      = first:user()
+        rest:(user_separator() i:(t:ignored_token() user_separator() { t })* u:user() { (i, u) })* {
+          let mut users = vec![first];
+          let mut ignored = Vec::new();
+          for (tokens, user) in rest {
+              ignored.extend(tokens);
+              users.push(user);
+          }
+          (users, ignored)
+      }
+
+    rule this_channel()
+      = quiet! {
+          "この部屋" / "このチャンネル" / "this channel" / "This channel"
+      } / expected!("this channel")
+
+    rule kaisanee_ignoring() -> (KaisaneeSpecifier, Vec<&'input str>)
+      = me() { (KaisaneeSpecifier::Me, Vec::new()) }
+      / all() { (KaisaneeSpecifier::All, Vec::new()) }
+      / l:users() _ "以外の" _ this_channel() { (KaisaneeSpecifier::ThisChannel { except: l.0 }, l.1) }
+      / this_channel() _ ("except" / "but") _ l:users() {
+          (KaisaneeSpecifier::ThisChannel { except: l.0 }, l.1)
+      }
+      / this_channel() { (KaisaneeSpecifier::ThisChannel { except: Vec::new() }, Vec::new()) }
+      / l:users() { (KaisaneeSpecifier::Users(l.0), l.1) }
 
     pub rule kaisanee() -> KaisaneeSpecifier
-      = me() { KaisaneeSpecifier::Me }
-      / all() { KaisaneeSpecifier::All }
-      / l:users() { KaisaneeSpecifier::Users(l) }
+      = k:kaisanee_ignoring() { k.0 }
 
-    rule second_suffix()
-      = "seconds" / "second" / "sec" / "s" / "秒"
+    rule en_second_suffix() = "seconds" / "second" / "sec" / "s"
+    rule en_minute_suffix() = "minutes" / "minute" / "min" / "m"
+    rule en_hour_suffix() = "hours" / "hour" / "hr" / "h"
+    rule en_day_suffix() = "days" / "day"
 
-    rule minute_suffix()
-      = "minutes" / "minute" / "min" / "m" / "分"
+    rule second_suffix() = en_second_suffix() / "秒"
+    rule minute_suffix() = en_minute_suffix() / "分"
+    rule hour_suffix() = en_hour_suffix() / "時間"
+    rule day_suffix() = en_day_suffix() / "日"
 
-    rule hour_suffix()
-      = "hours" / "hour" / "hr" / "h" / "時間"
+    rule word_end() = !['a'..='z' | 'A'..='Z']
+
+    // a duration without 後, which is only read in English as 10分 is a minute of the hour
+    rule en_after(x: u8) -> AfterTimeSpecifier
+      = en_second_suffix() word_end() { AfterTimeSpecifier::Second(x) }
+      / en_minute_suffix() word_end() { AfterTimeSpecifier::Minute(x) }
+      / en_hour_suffix() word_end() _ m:(m:number() _ en_minute_suffix() word_end() { m })? {
+          AfterTimeSpecifier::with_hour(x, m)
+      }
+      / en_day_suffix() word_end() { AfterTimeSpecifier::Day(x) }
 
     rule kanji_number_digit() -> u8
       = ['一'] { 1 }
@@ -115,11 +356,10 @@ peg::parser! {
     rule kanji_number_tail(x: u8) -> u8
       = ['十'] d:kanji_number_digit()? { x * 10 + d.unwrap_or(0) }
       / ['百'] d:kanji_number()? {?
-          if d < Some(100) {
-              Ok(x * 100 + d.unwrap_or(0))
-          } else {
-              Err("kanji number")
-          }
+          x.checked_mul(100)
+              .filter(|_| d < Some(100))
+              .and_then(|n| n.checked_add(d.unwrap_or(0)))
+              .ok_or("kanji number")
       }
 
     rule kanji_number() -> u8
@@ -160,12 +400,82 @@ peg::parser! {
       = ['半'] _ { Minute::from_u8(30).unwrap() }
       / m:minute() _ ['分'] _ { m }
 
-    rule spec_at_tomorrow() -> TimeSpecifier
-      = "明日の" _ h:hour() s:(
-          [':'] m:minute() _ { AtTimeSpecifier::HourMinute { hour: h, minute: m, is_tomorrow: true } }
-          / _ ['時'] _ m:spec_minute()? { AtTimeSpecifier::with_hour(h, m, true) }
+    rule outcome() -> Outcome
+      = "success" { Outcome::Success }
+      / "failure" { Outcome::Failure }
+
+    rule reset() = ("default" / "reset") ![_]
+
+    rule profile_name() -> String
+      = n:$(['a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_']*<1, 32>) ![_] { n.to_ascii_lowercase() }
+      / expected!("profile name")
+
+    rule channel_override() -> ChannelOverride
+      = "max-horizon" _ reset() { ChannelOverride::MaxHorizon(None) }
+      / "max-horizon" _ n:number() _ hour_suffix()? {?
+          if n == 0 {
+              Err("positive number of hours")
+          } else {
+              Ok(ChannelOverride::MaxHorizon(Some(n)))
+          }
+      }
+      / "countdown" _ reset() { ChannelOverride::Countdown(None) }
+      / "countdown" _ b:boolean() { ChannelOverride::Countdown(Some(b)) }
+      / "author-leave" _ reset() { ChannelOverride::AuthorLeave(None) }
+      / "author-leave" _ p:$(['a'..='z']+) {?
+          p.parse().map(|p| ChannelOverride::AuthorLeave(Some(p))).map_err(|_| "keep, reroll, cancel or default")
+      }
+      / "on-duplicate" _ reset() { ChannelOverride::OnDuplicate(None) }
+      / "on-duplicate" _ p:$(['a'..='z']+) {?
+          p.parse().map(|p| ChannelOverride::OnDuplicate(Some(p))).map_err(|_| "replace, stack, reject or default")
+      }
+
+    rule weekday() -> Weekday
+      = quiet! {
+          w:$(['月' | '火' | '水' | '木' | '金' | '土' | '日']) "曜" "日"? {
+              match w {
+                  "月" => Weekday::Mon,
+                  "火" => Weekday::Tue,
+                  "水" => Weekday::Wed,
+                  "木" => Weekday::Thu,
+                  "金" => Weekday::Fri,
+                  "土" => Weekday::Sat,
+                  "日" => Weekday::Sun,
+                  _ => unreachable!(),
+              }
+          }
+          / w:$(['a'..='z' | 'A'..='Z']+) {? w.parse().map_err(|_| "weekday") }
+      } / expected!("weekday")
+
+    rule month_day() -> (u32, u32)
+      = m:$(['0'..='9']*<1,2>) ['/'] d:$(['0'..='9']*<1,2>) { (m.parse().unwrap(), d.parse().unwrap()) }
+      / m:number() ['月'] _ d:number() ['日'] { (m.into(), d.into()) }
+
+    rule date() -> DateSpecifier
+      = "明後日" { DateSpecifier::InDays(2) }
+      / "明日" { DateSpecifier::Tomorrow }
+      / ("tomorrow" / "Tomorrow") { DateSpecifier::Tomorrow }
+      / n:number() _ day_suffix() ['後'] { DateSpecifier::InDays(n) }
+      / ("来週" _ ['の']? / "next" _) w:weekday() { DateSpecifier::NextWeek(w) }
+      / w:weekday() { DateSpecifier::Weekday(w) }
+      / d:month_day() { DateSpecifier::Date { month: d.0, day: d.1 } }
+
+    rule noon() = quiet! { "正午" / "noon" } / expected!("noon")
+
+    rule spec_at_date() -> TimeSpecifier
+      = d:date() _ ['の']? _ s:(
+          h:hour() s:(
+              [':'] m:minute() _ { AtTimeSpecifier::HourMinute { hour: h, minute: m, date: d } }
+              / _ ['時'] _ m:spec_minute()? { AtTimeSpecifier::with_hour(h, m, d) }
+          ) { s }
+          / noon() _ { AtTimeSpecifier::Hour { hour: Hour::from_u8(12).unwrap(), date: d } }
       ) { TimeSpecifier::At(s) }
 
+    rule spec_at_keyword() -> TimeSpecifier
+      = noon() _ { TimeSpecifier::At(AtTimeSpecifier::Hour { hour: Hour::from_u8(12).unwrap(), date: DateSpecifier::Today }) }
+      / ("真夜中" / "midnight") _ { TimeSpecifier::At(AtTimeSpecifier::Hour { hour: Hour::from_u8(0).unwrap(), date: DateSpecifier::Tomorrow }) }
+      / ("今夜" / "今晩" / "tonight") _ { TimeSpecifier::At(AtTimeSpecifier::Tonight) }
+
     rule spec_at_rfc3339() -> TimeSpecifier
       = "rfc3339" _ t:$(['T' | 'Z' | '+' | '-' | '.' | ':' | '0'..='9']+) _ {?
           match DateTime::parse_from_rfc3339(t) {
@@ -177,7 +487,7 @@ peg::parser! {
     rule spec_at_tail(x: u8) -> TimeSpecifier
       = [':'] m:minute() _ t:("tomorrow" _)? {?
           Hour::from_u8(x).map(|hour| {
-              TimeSpecifier::At(AtTimeSpecifier::HourMinute { hour, minute: m, is_tomorrow: t.is_some() })
+              TimeSpecifier::At(AtTimeSpecifier::HourMinute { hour, minute: m, date: if t.is_some() { DateSpecifier::Tomorrow } else { DateSpecifier::Today } })
           }).map_err(|_| "hour")
       }
       / _ ['分'] _ {?
@@ -187,7 +497,7 @@ peg::parser! {
       }
       / _ ['時'] _ m:spec_minute()? {?
           Hour::from_u8(x).map(|h| {
-              TimeSpecifier::At(AtTimeSpecifier::with_hour(h, m, false))
+              TimeSpecifier::At(AtTimeSpecifier::with_hour(h, m, DateSpecifier::Today))
           }).map_err(|_| "hour")
       }
 
@@ -196,16 +506,44 @@ peg::parser! {
 
     rule spec_at() -> TimeSpecifier
       = x:number() spec:spec_at_tail(x) { spec }
-      / spec_at_tomorrow()
+      / spec_at_date()
+      / spec_at_keyword()
       / spec_at_rfc3339()
       / spec_at_half()
 
+    // 1.5時間 or 90.5min, rounded to seconds
+    rule decimal_after() -> AfterTimeSpecifier
+      = x:number() ['.'] f:$(['0'..='9']+) _ unit:(
+          hour_suffix() { 60 * 60 }
+          / minute_suffix() { 60 }
+          / day_suffix() { 24 * 60 * 60 }
+      ) {
+          // digits beyond nanoseconds do not change the rounded seconds
+          let f = &f[..f.len().min(9)];
+          let denominator = 10u64.pow(f.len() as u32);
+          let fraction = (f.parse::<u64>().unwrap() * unit + denominator / 2) / denominator;
+          AfterTimeSpecifier::Fractional(u32::from(x) * unit as u32 + fraction as u32)
+      }
+
+    // the minutes after the hours, where 半 is 30 minutes
+    rule after_minute() -> u8
+      = m:number() _ minute_suffix() _ { m }
+      / ['半'] _ { 30 }
+
     rule spec_after() -> TimeSpecifier
-      = x:number() _ spec:(
-          minute_suffix() _ { AfterTimeSpecifier::with_minute(x, None) }
+      = d:decimal_after() _ { TimeSpecifier::After(d) }
+      / x:number() _ spec:(
+          minute_suffix() _ h:(['半'] _)? { AfterTimeSpecifier::with_minute_and_half(x, h.is_some()) }
           / second_suffix() _ { AfterTimeSpecifier::Second(x) }
-          / hour_suffix() _ m:(m:number() _ minute_suffix() _ { m })? { AfterTimeSpecifier::with_hour(x, m) }
+          / day_suffix() _ { AfterTimeSpecifier::Day(x) }
+          / hour_suffix() _ m:after_minute()? { AfterTimeSpecifier::with_hour(x, m) }
       ) { TimeSpecifier::After(spec) }
+      / ("an" / "a") _ spec:(
+          en_second_suffix() { AfterTimeSpecifier::Second(1) }
+          / en_minute_suffix() { AfterTimeSpecifier::Minute(1) }
+          / en_hour_suffix() { AfterTimeSpecifier::Hour(1) }
+          / en_day_suffix() { AfterTimeSpecifier::Day(1) }
+      ) word_end() _ { TimeSpecifier::After(spec) }
 
     rule spec_after_suffix(spec: AfterTimeSpecifier) -> TimeRangeSpecifier
       = s:$("後まで" / ['後'] / "以内") {
@@ -218,10 +556,13 @@ peg::parser! {
       }
 
     pub rule time_range() -> TimeRangeSpecifier
-      = x:number() spec:(
+      = d:decimal_after() _ spec:spec_after_suffix(d) { spec }
+      / x:number() spec:(
           _ second_suffix() _ spec:spec_after_suffix((AfterTimeSpecifier::Second(x))) { spec }
-          / _ minute_suffix() _ spec:spec_after_suffix((AfterTimeSpecifier::Minute(x))) { spec }
-          / _ hour_suffix() _ m:(m:number() _ minute_suffix() _ { m })? spec:spec_after_suffix((AfterTimeSpecifier::with_hour(x, m))) { spec }
+          / _ minute_suffix() _ h:(['半'] _)? spec:spec_after_suffix((AfterTimeSpecifier::with_minute_and_half(x, h.is_some()))) { spec }
+          / _ hour_suffix() _ m:after_minute()? spec:spec_after_suffix((AfterTimeSpecifier::with_hour(x, m))) { spec }
+          / _ day_suffix() _ spec:spec_after_suffix((AfterTimeSpecifier::Day(x))) !(_ ['の']) { spec }
+          / _ a:en_after(x) _ { TimeRangeSpecifier::At(TimeSpecifier::After(a)) }
           / spec:spec_at_tail(x) s:"まで"? {
               if s.is_some() {
                   TimeRangeSpecifier::By(spec)
@@ -230,7 +571,7 @@ peg::parser! {
               }
           }
         ) { spec }
-      / spec:(spec_at_tomorrow() / spec_at_rfc3339() / spec_at_half()) s:"まで"? {
+      / spec:(spec_at_date() / spec_at_keyword() / spec_at_rfc3339() / spec_at_half()) s:"まで"? {
           if s.is_some() {
               TimeRangeSpecifier::By(spec)
           } else {
@@ -242,32 +583,202 @@ peg::parser! {
       / "by" _ spec:spec_at() { TimeRangeSpecifier::By(spec) }
       / "after" _ spec:spec_after() { TimeRangeSpecifier::At(spec) }
       / "within" _ spec:spec_after() { TimeRangeSpecifier::By(spec) }
+      / "in" _ spec:spec_after() { TimeRangeSpecifier::At(spec) }
+
+    rule reminder_duration() -> Reminder
+        = n:number() _ second_suffix() { Reminder::before_seconds(n.into()) }
+        / n:number() _ hour_suffix() { Reminder::before_hours(n.into()) }
+        / n:number() _ minute_suffix()? { Reminder::before_minutes(n.into()) }
 
     pub rule reminder() -> Reminder
-        = m:number() _ "分前"? { Reminder::before_minutes(m.into()) }
-        / "before" _ m:number() _ minute_suffix() { Reminder::before_minutes(m.into()) }
+        = r:reminder_duration() _ "前"? { r }
+        / "before" _ r:reminder_duration() { r }
 
-    rule spec_kaisanee() -> KaisaneeSpecifier
-       = k:kaisanee() _ (['を'] _)? { k }
+    rule remind_only_me()
+      = quiet! {
+          "remind only me" / "私にだけリマインド" / "私にだけ"
+      } / expected!("remind only me")
+
+    rule name_char()
+      = !user_separator_char() !['を' | '<' | '@'] !"解散" [_]
+
+    // `@` followed by anything, or a bare name that does not start with an ASCII character so
+    // that misspelled English keywords are still reported as such
+    rule name() -> String
+      = !remind_only_me() !time_range() n:(
+          "@" n:$(name_char()+) { n }
+          / n:$(!"解散" [c if !c.is_ascii()] name_char()*) { n }
+        ) { n.to_owned() }
+
+    rule names() -> Vec<String>
+      = n:(name() ++ (_ [',' | '，' | '、'] _ / " "+)) { n }
+
+    rule spec_kaisanee() -> (KaisaneeSpecifier, Vec<&'input str>)
+       = !remind_only_me() k:kaisanee_ignoring() _ (['を'] _)? { k }
+       / n:names() _ (['を'] _)? { (KaisaneeSpecifier::Named(n), Vec::new()) }
+
+    rule kaisan() -> (KaisaneeSpecifier, TimeRangeSpecifier, Vec<String>)
+      = kaisanee1:spec_kaisanee()? time_range:time_range() separator:$(_ (['に'] _)?)
+        kaisanee2:spec_kaisanee()? "解散"? {?
+          // otherwise the rest of a malformed time such as `3時間` is taken as a name
+          if separator.is_empty() && matches!(kaisanee2, Some((KaisaneeSpecifier::Named(_), _))) {
+              return Err("separator before the name");
+          }
+          match (kaisanee1, kaisanee2) {
+              (Some((kaisanee, ignored)), None) | (None, Some((kaisanee, ignored))) => {
+                  let ignored = ignored.into_iter().map(ToOwned::to_owned).collect();
+                  Ok((kaisanee, time_range, ignored))
+              }
+              (None, None) => Ok((KaisaneeSpecifier::default(), time_range, Vec::new())),
+              (Some(_), Some(_)) => Err("kaisanee specified twice"),
+          }
+      }
+
+    rule kaisan_options() -> KaisanOptions
+      = _ r:(remind_only_me() _)? {
+          KaisanOptions { remind_only_me: r.is_some(), ..KaisanOptions::default() }
+      }
 
     pub rule command() -> Command
       = "help" { Command::Help }
+      / "require-permission-self" _ b:boolean() { Command::RequirePermissionSelf(b) }
       / "require-permission" _ b:boolean() { Command::RequirePermission(b) }
-      / "timezone" _ tz:$(['a'..='z' | 'A'..='Z' | '0'..='9' | '+' | '-' | '/' ]+) {?
+      / "timezone" _ tz:$(['a'..='z' | 'A'..='Z' | '0'..='9' | '+' | '-' | '_' | '/' ]+) {?
           match tz.parse() {
               Ok(tz) => Ok(Command::TimeZone(tz)),
               Err(_) => Err("timezone")
           }
       }
+      / "timezone" { Command::TimeZoneWizard }
       / "add-reminder" _ r:reminder() { Command::AddReminder(r) }
       / "remove-reminder" _ r:reminder() { Command::RemoveReminder(r) }
+      / "clear-reminders" { Command::ClearReminders }
+      / "list-reminders" { Command::ListReminders }
+      / "list-roles" { Command::ListRoles }
       / "remind-random" _ b:boolean() { Command::RemindRandomKaisan(b) }
+      / "countdown" _ b:boolean() { Command::Countdown(b) }
+      / "reveal-random" _ r:$(['a'..='z']+) {?
+          r.parse().map(Command::RevealRandom).map_err(|_| "off, channel or dm")
+      }
+      / "random-distribution" _ d:$(['a'..='z' | '-']+) {?
+          d.parse().map(Command::RandomDistribution).map_err(|_| "uniform, late-biased or early-biased")
+      }
+      / "dst" _ p:$(['a'..='z']+) {?
+          p.parse().map(Command::Dst).map_err(|_| "earliest, latest or reject")
+      }
+      / "week-start" _ w:weekday() {?
+          match w {
+              Weekday::Mon => Ok(Command::WeekStart(WeekStart::Monday)),
+              Weekday::Sun => Ok(Command::WeekStart(WeekStart::Sunday)),
+              _ => Err("monday or sunday"),
+          }
+      }
+      / "locale" _ l:$(['a'..='z']+) {?
+          l.parse().map(Command::Locale).map_err(|_| "ja, en or both")
+      }
+      / "on-duplicate" _ p:$(['a'..='z']+) {?
+          p.parse().map(Command::OnDuplicate).map_err(|_| "replace, stack or reject")
+      }
+      / "author-leave" _ p:$(['a'..='z']+) {?
+          p.parse().map(Command::AuthorLeave).map_err(|_| "keep, reroll or cancel")
+      }
+      / "prefix" _ ("default" / "reset") ![_] { Command::Prefix(None) }
+      / "prefix" _ p:$((!" " [_])+) { Command::Prefix(Some(p.to_owned())) }
+      / "webhook" _ ("off" / "none") ![_] { Command::Webhook(None) }
+      // Discord users wrap URLs in <> not to embed them
+      / "webhook" _ "<"? u:$((!(" " / ">") [_])+) ">"? ![_] { Command::Webhook(Some(u.to_owned())) }
+      / "log-channel" _ ("off" / "none") ![_] { Command::LogChannel(None) }
+      / "log-channel" _ c:channel() { Command::LogChannel(Some(c)) }
+      / "reaction" _ o:outcome() _ ("default" / "reset") ![_] { Command::Reaction(o, None) }
+      / "reaction" _ o:outcome() _ e:$((!" " [_])+) ![_] {?
+          parse_emoji(e).map(|r| Command::Reaction(o, Some(r))).ok_or("emoji")
+      }
+      / "phrase" _ o:outcome() _ ("off" / "none") ![_] { Command::Phrase(o, None) }
+      / "phrase" _ o:outcome() _ t:$([_]+) { Command::Phrase(o, Some(t.to_owned())) }
+      / "reminder-text" _ ("default" / "reset") ![_] { Command::ReminderText(None) }
+      / "reminder-text" _ t:$([_]+) { Command::ReminderText(Some(t.to_owned())) }
+      / "max-schedules" _ n:number() {?
+          if n == 0 {
+              Err("positive number")
+          } else {
+              Ok(Command::MaxSchedules(n))
+          }
+      }
+      / "max-horizon" _ n:number() _ hour_suffix()? {?
+          if n == 0 {
+              Err("positive number of hours")
+          } else {
+              Ok(Command::MaxHorizon(n))
+          }
+      }
+      / "tonight" _ h:hour() _ ['時']? { Command::Tonight(h) }
+      / "auto-kaisan" _ h:(
+          ("off" / "無効") { None }
+          / h:hour() _ ['時']? { Some(h) }
+      ) { Command::AutoKaisan(h) }
       / "show-setting" { Command::ShowSetting }
-      / kaisanee1:spec_kaisanee()? time_range:time_range() _ (['に'] _)? kaisanee2:spec_kaisanee()? "解散"? {?
-          match (kaisanee1, kaisanee2) {
-              (Some(kaisanee), None) | (None, Some(kaisanee)) => Ok(Command::Kaisan { kaisanee, time_range }),
-              (None, None) => Ok(Command::Kaisan { kaisanee: KaisaneeSpecifier::default(), time_range }),
-              (Some(_), Some(_)) => Err("kaisanee specified twice"),
+      / "profile" _ "save" _ n:profile_name() { Command::SaveProfile(n) }
+      / "profile" _ "load" _ n:profile_name() { Command::LoadProfile(n) }
+      / "profile" _ "list" { Command::ListProfiles }
+      / "export-setting" { Command::ExportSetting }
+      / "import-setting" { Command::ImportSetting }
+      / "api-token" _ "generate" { Command::GenerateApiToken }
+      / "api-token" _ "revoke" { Command::RevokeApiToken }
+      / "when" { Command::When }
+      / "ping" { Command::Ping }
+      / "abort-all" { Command::AbortAll }
+      / "pause" { Command::Pause }
+      / "resume" { Command::Resume }
+      / "admin" _ "schedules" { Command::AdminSchedules }
+      / "admin" _ "broadcast" _ t:$([_]+) { Command::AdminBroadcast(t.to_owned()) }
+      / "admin" _ "stats" { Command::AdminStats }
+      / "admin" _ "storage" g:(_ n:$(['0'..='9']+) {?
+            n.parse().ok().filter(|n| *n != 0).map(GuildId::new).ok_or("guild id")
+        })? { Command::AdminStorage(g) }
+      / "admin" _ "log-level" _ d:$([_]+) { Command::AdminLogLevel(d.to_owned()) }
+      / "admin" _ "soak" _ "off" { Command::AdminSoak(None) }
+      / "admin" _ "soak" _ n:$(['0'..='9']+) {?
+            n.parse()
+                .ok()
+                .filter(|n| (1..=MAX_SOAK_INTERVAL_MINUTES).contains(n))
+                .map(|n| Command::AdminSoak(Some(n)))
+                .ok_or("minutes")
+        }
+      / "cancel" _ "mine" { Command::CancelMine }
+      / "undo-window" _ ("off" / "無効") ![_] { Command::UndoWindow(0) }
+      / "undo-window" _ n:number() _ minute_suffix()? { Command::UndoWindow(n) }
+      / ("undo" / "取り消し") { Command::Undo }
+      / "allow-channel" c:(_ c:channel() { c })? { Command::AllowChannel(c) }
+      / "channel-setting" _ c:channel() _ o:channel_override() { Command::ChannelSetting(c, Some(o)) }
+      / "channel-setting" _ c:channel() { Command::ChannelSetting(c, None) }
+      / "deny-channel" c:(_ c:channel() { c })? { Command::DenyChannel(c) }
+      / "allow-role" _ r:role() { Command::AllowRole(r) }
+      / "deny-role" _ r:role() { Command::DenyRole(r) }
+      / "now" _ "#"? n:$(['0'..='9']+) {?
+          n.parse().map(|n| Command::Now(ScheduleId::new(n))).map_err(|_| "schedule id")
+      }
+      / ("who-kicked-me" / "why") { Command::WhoKickedMe }
+      / "complaints" { Command::ShowComplaints }
+      / "stats" { Command::ShowStats }
+      / "complain" { Command::Complain }
+      / "preview" _ k:kaisan() kaisan_options() { Command::Preview { kaisanee: k.0, time_range: k.1 } }
+      / first:kaisan() rest:(_ [',' | '，' | '、'] _ k:kaisan() { k })+ o:kaisan_options() {
+          let mut ignored_tokens = first.2;
+          let mut stages = vec![(first.0, first.1)];
+          for (kaisanee, time_range, ignored) in rest {
+              ignored_tokens.extend(ignored);
+              stages.push((kaisanee, time_range));
+          }
+          Command::KaisanMulti {
+              stages,
+              options: KaisanOptions { ignored_tokens, ..o },
+          }
+      }
+      / k:kaisan() o:kaisan_options() {
+          Command::Kaisan {
+              kaisanee: k.0,
+              time_range: k.1,
+              options: KaisanOptions { ignored_tokens: k.2, ..o },
           }
       }
   }
@@ -275,21 +786,179 @@ peg::parser! {
 
 #[cfg(test)]
 mod tests {
-    use super::{parser, Command, TimeRangeSpecifier};
+    use super::{parser, Command, KaisanOptions, TimeRangeSpecifier};
     use crate::model::{
         kaisanee::KaisaneeSpecifier,
+        reaction::Outcome,
         reminder::Reminder,
-        time::{AfterTimeSpecifier, AtTimeSpecifier, Hour, Minute, TimeSpecifier},
+        schedule::ScheduleId,
+        setting::{
+            AuthorLeavePolicy, ChannelOverride, DstPolicy, DuplicatePolicy, Locale,
+            RandomDistribution, RevealRandom, WeekStart,
+        },
+        time::{AfterTimeSpecifier, AtTimeSpecifier, DateSpecifier, Hour, Minute, TimeSpecifier},
     };
 
+    use chrono::Weekday;
     use chrono_tz::Tz;
-    use serenity::model::id::UserId;
+    use proptest::{prelude::*, sample::select};
+    use serenity::model::{
+        channel::ReactionType,
+        id::{ChannelId, GuildId, RoleId, UserId},
+    };
 
     #[test]
     fn test_help_command() {
         assert_eq!(parser::command("help"), Ok(Command::Help));
     }
 
+    #[test]
+    fn test_who_kicked_me_command() {
+        assert_eq!(parser::command("who-kicked-me"), Ok(Command::WhoKickedMe));
+        assert_eq!(parser::command("why"), Ok(Command::WhoKickedMe));
+        assert_eq!(parser::command("when"), Ok(Command::When));
+        assert_eq!(parser::command("ping"), Ok(Command::Ping));
+        assert_eq!(parser::command("abort-all"), Ok(Command::AbortAll));
+        assert_eq!(parser::command("pause"), Ok(Command::Pause));
+        assert_eq!(parser::command("resume"), Ok(Command::Resume));
+        assert_eq!(
+            parser::command("admin schedules"),
+            Ok(Command::AdminSchedules)
+        );
+        assert_eq!(parser::command("admin stats"), Ok(Command::AdminStats));
+        assert_eq!(
+            parser::command("admin broadcast 今夜メンテナンスします"),
+            Ok(Command::AdminBroadcast("今夜メンテナンスします".to_owned()))
+        );
+        assert!(parser::command("admin broadcast").is_err());
+        assert_eq!(
+            parser::command("admin storage"),
+            Ok(Command::AdminStorage(None))
+        );
+        assert_eq!(
+            parser::command("admin storage 1234"),
+            Ok(Command::AdminStorage(Some(GuildId::new(1234))))
+        );
+        assert!(parser::command("admin storage 0").is_err());
+        assert_eq!(
+            parser::command("admin soak 5"),
+            Ok(Command::AdminSoak(Some(5)))
+        );
+        assert_eq!(
+            parser::command("admin soak off"),
+            Ok(Command::AdminSoak(None))
+        );
+        assert!(parser::command("admin soak 0").is_err());
+        assert!(parser::command("admin soak 1441").is_err());
+        assert_eq!(parser::command("cancel mine"), Ok(Command::CancelMine));
+        assert_eq!(parser::command("undo"), Ok(Command::Undo));
+        assert_eq!(parser::command("取り消し"), Ok(Command::Undo));
+        assert_eq!(
+            parser::command("undo-window 5分"),
+            Ok(Command::UndoWindow(5))
+        );
+        assert_eq!(
+            parser::command("undo-window off"),
+            Ok(Command::UndoWindow(0))
+        );
+        assert_eq!(
+            parser::command("allow-channel <#1234>"),
+            Ok(Command::AllowChannel(Some(ChannelId::new(1234))))
+        );
+        assert_eq!(
+            parser::command("allow-channel"),
+            Ok(Command::AllowChannel(None))
+        );
+        assert_eq!(
+            parser::command("channel-setting <#1234>"),
+            Ok(Command::ChannelSetting(ChannelId::new(1234), None))
+        );
+        assert_eq!(
+            parser::command("channel-setting <#1234> max-horizon 24時間"),
+            Ok(Command::ChannelSetting(
+                ChannelId::new(1234),
+                Some(ChannelOverride::MaxHorizon(Some(24)))
+            ))
+        );
+        assert_eq!(
+            parser::command("channel-setting <#1234> countdown default"),
+            Ok(Command::ChannelSetting(
+                ChannelId::new(1234),
+                Some(ChannelOverride::Countdown(None))
+            ))
+        );
+        assert_eq!(
+            parser::command("channel-setting <#1234> on-duplicate reject"),
+            Ok(Command::ChannelSetting(
+                ChannelId::new(1234),
+                Some(ChannelOverride::OnDuplicate(Some(DuplicatePolicy::Reject)))
+            ))
+        );
+        assert!(parser::command("channel-setting <#1234> author-leave later").is_err());
+        assert!(parser::command("channel-setting <#1234> max-horizon 0").is_err());
+        assert_eq!(
+            parser::command("deny-channel <#1234>"),
+            Ok(Command::DenyChannel(Some(ChannelId::new(1234))))
+        );
+        assert_eq!(
+            parser::command("allow-role <@&1234>"),
+            Ok(Command::AllowRole(RoleId::new(1234)))
+        );
+        assert_eq!(
+            parser::command("deny-role <@&1234>"),
+            Ok(Command::DenyRole(RoleId::new(1234)))
+        );
+        assert!(parser::command("allow-role").is_err());
+        assert_eq!(parser::command("list-roles"), Ok(Command::ListRoles));
+        assert_eq!(
+            parser::command("now 3"),
+            Ok(Command::Now(ScheduleId::new(3)))
+        );
+        assert_eq!(
+            parser::command("now #12"),
+            Ok(Command::Now(ScheduleId::new(12)))
+        );
+    }
+
+    /// Phrasings seen in the wild, one per line as `input<TAB>expected`, where `expected` is the
+    /// Debug form of the command or `-` if it must not parse. `examples/corpus.rs` appends to it.
+    const CORPUS: &str = include_str!("command/corpus.tsv");
+
+    #[test]
+    fn test_corpus() {
+        let mut failures = Vec::new();
+        for (index, line) in CORPUS.lines().enumerate() {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (input, expected) = line
+                .split_once('\t')
+                .unwrap_or_else(|| panic!("no tab in line {} of the corpus", index + 1));
+            let actual = match parser::command(input) {
+                Ok(command) => format!("{:?}", command),
+                Err(_) => "-".to_owned(),
+            };
+            if actual != expected {
+                failures.push(format!(
+                    "line {}: {:?}\n  expected: {}\n  actual:   {}",
+                    index + 1,
+                    input,
+                    expected,
+                    actual
+                ));
+            }
+        }
+        // all at once, to see the whole extent of a regression
+        assert!(failures.is_empty(), "{}", failures.join("\n"));
+    }
+
+    #[test]
+    fn test_complain_command() {
+        assert_eq!(parser::command("complain"), Ok(Command::Complain));
+        assert_eq!(parser::command("complaints"), Ok(Command::ShowComplaints));
+        assert_eq!(parser::command("stats"), Ok(Command::ShowStats));
+    }
+
     #[test]
     fn test_setting_command() {
         assert_eq!(
@@ -301,6 +970,7 @@ mod tests {
             Ok(Command::TimeZone(Tz::Etc__GMTPlus0))
         );
         assert!(parser::command("timezone NoSuchTZ").is_err());
+        assert_eq!(parser::command("timezone"), Ok(Command::TimeZoneWizard));
         assert_eq!(
             parser::command("require-permission はい"),
             Ok(Command::RequirePermission(true))
@@ -309,6 +979,10 @@ mod tests {
             parser::command("require-permission no"),
             Ok(Command::RequirePermission(false))
         );
+        assert_eq!(
+            parser::command("require-permission-self yes"),
+            Ok(Command::RequirePermissionSelf(true))
+        );
         assert_eq!(
             parser::command("add-reminder 三分前"),
             Ok(Command::AddReminder(Reminder::before_minutes(3)))
@@ -317,7 +991,235 @@ mod tests {
             parser::command("remove-reminder before 20m"),
             Ok(Command::RemoveReminder(Reminder::before_minutes(20)))
         );
+        assert_eq!(
+            parser::command("countdown true"),
+            Ok(Command::Countdown(true))
+        );
+        assert_eq!(
+            parser::command("author-leave reroll"),
+            Ok(Command::AuthorLeave(AuthorLeavePolicy::Reroll))
+        );
+        assert!(parser::command("author-leave later").is_err());
+        assert_eq!(
+            parser::command("reveal-random dm"),
+            Ok(Command::RevealRandom(RevealRandom::DirectMessage))
+        );
+        assert_eq!(
+            parser::command("random-distribution late-biased"),
+            Ok(Command::RandomDistribution(RandomDistribution::LateBiased))
+        );
+        assert!(parser::command("random-distribution normal").is_err());
+        assert_eq!(
+            parser::command("dst latest"),
+            Ok(Command::Dst(DstPolicy::Latest))
+        );
+        assert!(parser::command("dst never").is_err());
+        assert_eq!(
+            parser::command("week-start sunday"),
+            Ok(Command::WeekStart(WeekStart::Sunday))
+        );
+        assert_eq!(
+            parser::command("week-start 日曜"),
+            Ok(Command::WeekStart(WeekStart::Sunday))
+        );
+        assert!(parser::command("week-start friday").is_err());
+        assert_eq!(
+            parser::command("locale both"),
+            Ok(Command::Locale(Locale::Both))
+        );
+        assert!(parser::command("locale fr").is_err());
+        assert_eq!(
+            parser::command("on-duplicate replace"),
+            Ok(Command::OnDuplicate(DuplicatePolicy::Replace))
+        );
+        assert!(parser::command("on-duplicate merge").is_err());
+        assert_eq!(
+            parser::command("clear-reminders"),
+            Ok(Command::ClearReminders)
+        );
+        assert_eq!(
+            parser::command("list-reminders"),
+            Ok(Command::ListReminders)
+        );
+        assert_eq!(
+            parser::command("add-reminder 30s"),
+            Ok(Command::AddReminder(Reminder::before_seconds(30)))
+        );
+        assert_eq!(
+            parser::command("add-reminder 1h"),
+            Ok(Command::AddReminder(Reminder::before_hours(1)))
+        );
+        assert_eq!(
+            parser::command("add-reminder 十秒前"),
+            Ok(Command::AddReminder(Reminder::before_seconds(10)))
+        );
+        assert_eq!(
+            parser::command("add-reminder 5"),
+            Ok(Command::AddReminder(Reminder::before_minutes(5)))
+        );
+        assert_eq!(
+            parser::command("auto-kaisan 23"),
+            Ok(Command::AutoKaisan(Some(Hour::from_u8(23).unwrap())))
+        );
+        assert_eq!(
+            parser::command("auto-kaisan 二十二時"),
+            Ok(Command::AutoKaisan(Some(Hour::from_u8(22).unwrap())))
+        );
+        assert_eq!(
+            parser::command("auto-kaisan off"),
+            Ok(Command::AutoKaisan(None))
+        );
+        assert!(parser::command("auto-kaisan 24").is_err());
+        assert_eq!(
+            parser::command("max-horizon 24h"),
+            Ok(Command::MaxHorizon(24))
+        );
+        assert_eq!(
+            parser::command("max-horizon 三時間"),
+            Ok(Command::MaxHorizon(3))
+        );
+        assert!(parser::command("max-horizon 0").is_err());
+        assert_eq!(
+            parser::command("max-schedules 5"),
+            Ok(Command::MaxSchedules(5))
+        );
+        assert!(parser::command("max-schedules 0").is_err());
+        assert_eq!(
+            parser::command("reminder-text あと{remaining}で解散だよ"),
+            Ok(Command::ReminderText(Some(
+                "あと{remaining}で解散だよ".to_owned()
+            )))
+        );
+        assert_eq!(
+            parser::command("tonight 22"),
+            Ok(Command::Tonight(Hour::from_u8(22).unwrap()))
+        );
+        assert!(parser::command("tonight 25").is_err());
+        assert_eq!(
+            parser::command("prefix ?kaisan"),
+            Ok(Command::Prefix(Some("?kaisan".to_owned())))
+        );
+        assert_eq!(parser::command("prefix default"), Ok(Command::Prefix(None)));
+        assert!(parser::command("prefix ? kaisan").is_err());
+        assert_eq!(
+            parser::command("webhook <https://example.com/hook>"),
+            Ok(Command::Webhook(Some(
+                "https://example.com/hook".to_owned()
+            )))
+        );
+        assert_eq!(
+            parser::command("webhook https://example.com/hook"),
+            Ok(Command::Webhook(Some(
+                "https://example.com/hook".to_owned()
+            )))
+        );
+        assert_eq!(parser::command("webhook off"), Ok(Command::Webhook(None)));
+        assert_eq!(
+            parser::command("log-channel <#1234>"),
+            Ok(Command::LogChannel(Some(ChannelId::new(1234))))
+        );
+        assert_eq!(
+            parser::command("log-channel off"),
+            Ok(Command::LogChannel(None))
+        );
+        assert!(parser::command("log-channel").is_err());
+        assert_eq!(
+            parser::command("reaction success 🎉"),
+            Ok(Command::Reaction(
+                Outcome::Success,
+                Some(ReactionType::Unicode("🎉".to_owned()))
+            ))
+        );
+        assert_eq!(
+            parser::command("reaction failure default"),
+            Ok(Command::Reaction(Outcome::Failure, None))
+        );
+        assert!(parser::command("reaction success ok").is_err());
+        assert_eq!(
+            parser::command("phrase success おつかれ！"),
+            Ok(Command::Phrase(
+                Outcome::Success,
+                Some("おつかれ！".to_owned())
+            ))
+        );
+        assert_eq!(
+            parser::command("phrase failure off"),
+            Ok(Command::Phrase(Outcome::Failure, None))
+        );
+        assert_eq!(
+            parser::command("reminder-text default"),
+            Ok(Command::ReminderText(None))
+        );
+        assert_eq!(
+            parser::command("reminder-text defaults"),
+            Ok(Command::ReminderText(Some("defaults".to_owned())))
+        );
         assert_eq!(parser::command("show-setting"), Ok(Command::ShowSetting));
+        assert_eq!(
+            parser::command("profile save Exam-Week"),
+            Ok(Command::SaveProfile("exam-week".to_owned()))
+        );
+        assert_eq!(
+            parser::command("profile load weekend"),
+            Ok(Command::LoadProfile("weekend".to_owned()))
+        );
+        assert_eq!(parser::command("profile list"), Ok(Command::ListProfiles));
+        assert!(parser::command("profile save 週末").is_err());
+        assert!(parser::command("profile save weekend night").is_err());
+        assert_eq!(
+            parser::command("export-setting"),
+            Ok(Command::ExportSetting)
+        );
+        assert_eq!(
+            parser::command("import-setting"),
+            Ok(Command::ImportSetting)
+        );
+        assert_eq!(
+            parser::command("api-token generate"),
+            Ok(Command::GenerateApiToken)
+        );
+        assert_eq!(
+            parser::command("api-token revoke"),
+            Ok(Command::RevokeApiToken)
+        );
+    }
+
+    #[test]
+    fn test_remind_only_me() {
+        let options = KaisanOptions {
+            remind_only_me: true,
+            ..KaisanOptions::default()
+        };
+        assert_eq!(
+            parser::command("10分後 私にだけ"),
+            Ok(Command::Kaisan {
+                kaisanee: KaisaneeSpecifier::All,
+                time_range: TimeRangeSpecifier::At(TimeSpecifier::After(
+                    AfterTimeSpecifier::Minute(10)
+                )),
+                options: options.clone(),
+            })
+        );
+        assert_eq!(
+            parser::command("all after 10min remind only me"),
+            Ok(Command::Kaisan {
+                kaisanee: KaisaneeSpecifier::All,
+                time_range: TimeRangeSpecifier::At(TimeSpecifier::After(
+                    AfterTimeSpecifier::Minute(10)
+                )),
+                options: options.clone(),
+            })
+        );
+        assert_eq!(
+            parser::command("10分後に私を解散 私にだけリマインド"),
+            Ok(Command::Kaisan {
+                kaisanee: KaisaneeSpecifier::Me,
+                time_range: TimeRangeSpecifier::At(TimeSpecifier::After(
+                    AfterTimeSpecifier::Minute(10)
+                )),
+                options,
+            })
+        );
     }
 
     #[test]
@@ -328,8 +1230,9 @@ mod tests {
                 kaisanee: KaisaneeSpecifier::All,
                 time_range: TimeRangeSpecifier::At(TimeSpecifier::At(AtTimeSpecifier::Hour {
                     hour: Hour::from_u8(1).unwrap(),
-                    is_tomorrow: true,
-                }))
+                    date: DateSpecifier::Tomorrow,
+                })),
+                options: KaisanOptions::default()
             })
         );
         assert_eq!(
@@ -338,7 +1241,8 @@ mod tests {
                 kaisanee: KaisaneeSpecifier::Me,
                 time_range: TimeRangeSpecifier::At(TimeSpecifier::After(
                     AfterTimeSpecifier::Minute(10)
-                ))
+                )),
+                options: KaisanOptions::default()
             })
         );
         assert_eq!(
@@ -348,6 +1252,7 @@ mod tests {
                 time_range: TimeRangeSpecifier::At(TimeSpecifier::At(AtTimeSpecifier::Minute(
                     Minute::from_u8(10).unwrap()
                 ))),
+                options: KaisanOptions::default()
             })
         );
         assert_eq!(
@@ -356,7 +1261,8 @@ mod tests {
                 kaisanee: KaisaneeSpecifier::All,
                 time_range: TimeRangeSpecifier::At(TimeSpecifier::After(
                     AfterTimeSpecifier::Minute(1)
-                ))
+                )),
+                options: KaisanOptions::default()
             })
         );
     }
@@ -371,9 +1277,10 @@ mod tests {
                     AtTimeSpecifier::HourMinute {
                         hour: Hour::from_u8(10).unwrap(),
                         minute: Minute::from_u8(10).unwrap(),
-                        is_tomorrow: false,
+                        date: DateSpecifier::Today,
                     }
-                ))
+                )),
+                options: KaisanOptions::default()
             })
         );
         assert_eq!(
@@ -384,11 +1291,36 @@ mod tests {
                     AtTimeSpecifier::HourMinute {
                         hour: Hour::from_u8(10).unwrap(),
                         minute: Minute::from_u8(10).unwrap(),
-                        is_tomorrow: true,
+                        date: DateSpecifier::Tomorrow,
                     }
+                )),
+                options: KaisanOptions::default()
+            })
+        );
+    }
+
+    #[test]
+    fn test_preview_command() {
+        assert_eq!(
+            parser::command("preview me after 10min"),
+            Ok(Command::Preview {
+                kaisanee: KaisaneeSpecifier::Me,
+                time_range: TimeRangeSpecifier::At(TimeSpecifier::After(
+                    AfterTimeSpecifier::Minute(10)
                 ))
             })
         );
+        assert_eq!(
+            parser::command("preview 23時まで"),
+            Ok(Command::Preview {
+                kaisanee: KaisaneeSpecifier::All,
+                time_range: TimeRangeSpecifier::By(TimeSpecifier::At(AtTimeSpecifier::Hour {
+                    hour: Hour::from_u8(23).unwrap(),
+                    date: DateSpecifier::Today,
+                }))
+            })
+        );
+        assert!(parser::command("preview").is_err());
     }
 
     #[test]
@@ -421,6 +1353,146 @@ mod tests {
                 UserId::new(99999)
             ]))
         );
+        // the nickname form from old clients mixed with the plain one
+        assert_eq!(
+            parser::kaisanee("<@!12345> <@45678>"),
+            Ok(KaisaneeSpecifier::Users(vec![
+                UserId::new(12345),
+                UserId::new(45678)
+            ]))
+        );
+        assert!(parser::kaisanee("<@!>").is_err());
+        assert!(parser::kaisanee("<@!!12345>").is_err());
+    }
+
+    #[test]
+    fn test_kaisanee_this_channel() {
+        assert_eq!(
+            parser::kaisanee("この部屋"),
+            Ok(KaisaneeSpecifier::ThisChannel { except: vec![] })
+        );
+        assert_eq!(
+            parser::kaisanee("this channel except <@1> <@2>"),
+            Ok(KaisaneeSpecifier::ThisChannel {
+                except: vec![UserId::new(1), UserId::new(2)]
+            })
+        );
+        assert_eq!(
+            parser::command("<@1>以外のこの部屋を10分後に解散"),
+            Ok(Command::Kaisan {
+                kaisanee: KaisaneeSpecifier::ThisChannel {
+                    except: vec![UserId::new(1)]
+                },
+                time_range: TimeRangeSpecifier::At(TimeSpecifier::After(
+                    AfterTimeSpecifier::Minute(10)
+                )),
+                options: KaisanOptions::default(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_kaisanee_users_with_separators() {
+        assert_eq!(
+            parser::kaisanee("<@1>、<@2>と<@3>, <@4> and <@5>"),
+            Ok(KaisaneeSpecifier::Users((1..=5).map(UserId::new).collect()))
+        );
+        assert_eq!(
+            parser::command("<@!12345> garbage <@!678> 10分後"),
+            Ok(Command::Kaisan {
+                kaisanee: KaisaneeSpecifier::Users(vec![UserId::new(12345), UserId::new(678)]),
+                time_range: TimeRangeSpecifier::At(TimeSpecifier::After(
+                    AfterTimeSpecifier::Minute(10)
+                )),
+                options: KaisanOptions {
+                    ignored_tokens: vec!["garbage".to_owned()],
+                    ..KaisanOptions::default()
+                },
+            })
+        );
+        // words after the last user are not skipped
+        assert!(parser::command("<@1> <@2> garbage 10分後").is_err());
+    }
+
+    #[test]
+    fn test_kaisanee_named() {
+        let named = |names: &[&str]| Command::Kaisan {
+            kaisanee: KaisaneeSpecifier::Named(names.iter().map(|n| (*n).to_owned()).collect()),
+            time_range: parser::time_range("23:00").unwrap(),
+            options: KaisanOptions::default(),
+        };
+        assert_eq!(parser::command("田中 at 23:00"), Ok(named(&["田中"])));
+        assert_eq!(parser::command("田中を23:00に解散"), Ok(named(&["田中"])));
+        assert_eq!(
+            parser::command("23:00に田中、佐藤を解散"),
+            Ok(named(&["田中", "佐藤"]))
+        );
+        assert_eq!(
+            parser::command("@tanaka @sato.2 23:00"),
+            Ok(named(&["tanaka", "sato.2"]))
+        );
+        // names need something in between the time
+        assert!(parser::command("23:00田中").is_err());
+        assert!(parser::command("田中23:00").is_err());
+        // bare ASCII words are not names
+        assert!(parser::command("tanaka at 23:00").is_err());
+        assert_eq!(
+            parser::command("田中 23:00 私にだけリマインド"),
+            Ok(Command::Kaisan {
+                kaisanee: KaisaneeSpecifier::Named(vec!["田中".to_owned()]),
+                time_range: parser::time_range("23:00").unwrap(),
+                options: KaisanOptions {
+                    remind_only_me: true,
+                    ..KaisanOptions::default()
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn test_kaisan_multi() {
+        let time = |s| parser::time_range(s).unwrap();
+        assert_eq!(
+            parser::command("me at 23:00, all at 23:30"),
+            Ok(Command::KaisanMulti {
+                stages: vec![
+                    (KaisaneeSpecifier::Me, time("at 23:00")),
+                    (KaisaneeSpecifier::All, time("at 23:30")),
+                ],
+                options: KaisanOptions::default(),
+            })
+        );
+        assert_eq!(
+            parser::command("私を23時に解散、全員を23時半に解散 私にだけ"),
+            Ok(Command::KaisanMulti {
+                stages: vec![
+                    (KaisaneeSpecifier::Me, time("23時")),
+                    (KaisaneeSpecifier::All, time("23時半")),
+                ],
+                options: KaisanOptions {
+                    remind_only_me: true,
+                    ..KaisanOptions::default()
+                },
+            })
+        );
+        // a time after a mention starts the next stage rather than being skipped
+        assert_eq!(
+            parser::command("23時に<@1>、23時半に<@2>"),
+            Ok(Command::KaisanMulti {
+                stages: vec![
+                    (KaisaneeSpecifier::Users(vec![UserId::new(1)]), time("23時")),
+                    (
+                        KaisaneeSpecifier::Users(vec![UserId::new(2)]),
+                        time("23時半")
+                    ),
+                ],
+                options: KaisanOptions::default(),
+            })
+        );
+        assert!(matches!(
+            parser::command("<@1>、<@2> 23時"),
+            Ok(Command::Kaisan { .. })
+        ));
     }
 
     #[test]
@@ -447,7 +1519,7 @@ mod tests {
                 AtTimeSpecifier::HourMinute {
                     hour: Hour::from_u8(0).unwrap(),
                     minute: Minute::from_u8(15).unwrap(),
-                    is_tomorrow: false,
+                    date: DateSpecifier::Today,
                 }
             )))
         );
@@ -457,7 +1529,7 @@ mod tests {
             Ok(TimeRangeSpecifier::At(TimeSpecifier::At(
                 AtTimeSpecifier::Hour {
                     hour: Hour::from_u8(10).unwrap(),
-                    is_tomorrow: false,
+                    date: DateSpecifier::Today,
                 }
             )))
         );
@@ -467,7 +1539,7 @@ mod tests {
                 AtTimeSpecifier::HourMinute {
                     hour: Hour::from_u8(1).unwrap(),
                     minute: Minute::from_u8(30).unwrap(),
-                    is_tomorrow: false,
+                    date: DateSpecifier::Today,
                 }
             )))
         );
@@ -477,7 +1549,7 @@ mod tests {
             Ok(TimeRangeSpecifier::At(TimeSpecifier::At(
                 AtTimeSpecifier::Hour {
                     hour: Hour::from_u8(1).unwrap(),
-                    is_tomorrow: true
+                    date: DateSpecifier::Tomorrow
                 }
             )))
         );
@@ -487,13 +1559,109 @@ mod tests {
                 AtTimeSpecifier::HourMinute {
                     hour: Hour::from_u8(10).unwrap(),
                     minute: Minute::from_u8(15).unwrap(),
-                    is_tomorrow: true,
+                    date: DateSpecifier::Tomorrow,
                 }
             )))
         );
         assert!(parser::time_range("明日の15分").is_err());
     }
 
+    #[test]
+    fn test_at_date_ja() {
+        let at = |hour, minute: Option<u8>, date| {
+            Ok(TimeRangeSpecifier::At(TimeSpecifier::At(
+                AtTimeSpecifier::with_hour(
+                    Hour::from_u8(hour).unwrap(),
+                    minute.map(|m| Minute::from_u8(m).unwrap()),
+                    date,
+                ),
+            )))
+        };
+        assert_eq!(
+            parser::time_range("金曜の21時"),
+            at(21, None, DateSpecifier::Weekday(Weekday::Fri))
+        );
+        assert_eq!(
+            parser::time_range("日曜日の9時半"),
+            at(9, Some(30), DateSpecifier::Weekday(Weekday::Sun))
+        );
+        assert_eq!(
+            parser::time_range("来週金曜の21時"),
+            at(21, None, DateSpecifier::NextWeek(Weekday::Fri))
+        );
+        assert_eq!(
+            parser::time_range("来週の月曜日9時"),
+            at(9, None, DateSpecifier::NextWeek(Weekday::Mon))
+        );
+        assert_eq!(
+            parser::time_range("12/24 22:00"),
+            at(22, Some(0), DateSpecifier::Date { month: 12, day: 24 })
+        );
+        assert_eq!(
+            parser::time_range("12月24日の22時"),
+            at(22, None, DateSpecifier::Date { month: 12, day: 24 })
+        );
+        assert_eq!(
+            parser::time_range("3日後の21時"),
+            at(21, None, DateSpecifier::InDays(3))
+        );
+        assert_eq!(
+            parser::time_range("明日 10:15"),
+            at(10, Some(15), DateSpecifier::Tomorrow)
+        );
+        assert!(parser::time_range("金曜の30時").is_err());
+    }
+
+    #[test]
+    fn test_at_keyword() {
+        let at = |hour, date| {
+            Ok(TimeRangeSpecifier::At(TimeSpecifier::At(
+                AtTimeSpecifier::Hour {
+                    hour: Hour::from_u8(hour).unwrap(),
+                    date,
+                },
+            )))
+        };
+        assert_eq!(parser::time_range("正午"), at(12, DateSpecifier::Today));
+        assert_eq!(parser::time_range("at noon"), at(12, DateSpecifier::Today));
+        assert_eq!(
+            parser::time_range("明後日の正午"),
+            at(12, DateSpecifier::InDays(2))
+        );
+        assert_eq!(
+            parser::time_range("明後日の9時"),
+            at(9, DateSpecifier::InDays(2))
+        );
+        assert_eq!(parser::time_range("真夜中"), at(0, DateSpecifier::Tomorrow));
+        assert_eq!(
+            parser::time_range("by midnight"),
+            Ok(TimeRangeSpecifier::By(TimeSpecifier::At(
+                AtTimeSpecifier::Hour {
+                    hour: Hour::from_u8(0).unwrap(),
+                    date: DateSpecifier::Tomorrow,
+                }
+            )))
+        );
+        assert_eq!(
+            parser::time_range("今夜"),
+            Ok(TimeRangeSpecifier::At(TimeSpecifier::At(
+                AtTimeSpecifier::Tonight
+            )))
+        );
+        assert_eq!(
+            parser::time_range("今夜まで"),
+            Ok(TimeRangeSpecifier::By(TimeSpecifier::At(
+                AtTimeSpecifier::Tonight
+            )))
+        );
+        assert_eq!(
+            parser::time_range("at tonight"),
+            Ok(TimeRangeSpecifier::At(TimeSpecifier::At(
+                AtTimeSpecifier::Tonight
+            )))
+        );
+    }
+
     #[test]
     fn test_after_ja() {
         assert_eq!(
@@ -520,6 +1688,12 @@ mod tests {
                 AfterTimeSpecifier::Second(3)
             )))
         );
+        assert_eq!(
+            parser::time_range("3日後"),
+            Ok(TimeRangeSpecifier::At(TimeSpecifier::After(
+                AfterTimeSpecifier::Day(3)
+            )))
+        );
     }
 
     #[test]
@@ -530,7 +1704,7 @@ mod tests {
                 AtTimeSpecifier::HourMinute {
                     hour: Hour::from_u8(12).unwrap(),
                     minute: Minute::from_u8(12).unwrap(),
-                    is_tomorrow: false
+                    date: DateSpecifier::Today
                 }
             )))
         );
@@ -545,7 +1719,7 @@ mod tests {
             Ok(TimeRangeSpecifier::By(TimeSpecifier::At(
                 AtTimeSpecifier::Hour {
                     hour: Hour::from_u8(12).unwrap(),
-                    is_tomorrow: false
+                    date: DateSpecifier::Today
                 }
             )))
         );
@@ -554,7 +1728,7 @@ mod tests {
             Ok(TimeRangeSpecifier::By(TimeSpecifier::At(
                 AtTimeSpecifier::Hour {
                     hour: Hour::from_u8(1).unwrap(),
-                    is_tomorrow: true
+                    date: DateSpecifier::Tomorrow
                 }
             )))
         );
@@ -564,7 +1738,7 @@ mod tests {
                 AtTimeSpecifier::HourMinute {
                     hour: Hour::from_u8(3).unwrap(),
                     minute: Minute::from_u8(22).unwrap(),
-                    is_tomorrow: true
+                    date: DateSpecifier::Tomorrow
                 }
             )))
         );
@@ -606,7 +1780,7 @@ mod tests {
                 AtTimeSpecifier::HourMinute {
                     hour: Hour::from_u8(12).unwrap(),
                     minute: Minute::from_u8(00).unwrap(),
-                    is_tomorrow: false
+                    date: DateSpecifier::Today
                 }
             )))
         );
@@ -617,12 +1791,67 @@ mod tests {
                 AtTimeSpecifier::HourMinute {
                     hour: Hour::from_u8(10).unwrap(),
                     minute: Minute::from_u8(15).unwrap(),
-                    is_tomorrow: true
+                    date: DateSpecifier::Tomorrow
                 }
             )))
         );
     }
 
+    #[test]
+    fn test_at_date_en() {
+        assert_eq!(
+            parser::time_range("at friday 21:00"),
+            Ok(TimeRangeSpecifier::At(TimeSpecifier::At(
+                AtTimeSpecifier::HourMinute {
+                    hour: Hour::from_u8(21).unwrap(),
+                    minute: Minute::from_u8(0).unwrap(),
+                    date: DateSpecifier::Weekday(Weekday::Fri)
+                }
+            )))
+        );
+        assert_eq!(
+            parser::time_range("by 12/24 22:00"),
+            Ok(TimeRangeSpecifier::By(TimeSpecifier::At(
+                AtTimeSpecifier::HourMinute {
+                    hour: Hour::from_u8(22).unwrap(),
+                    minute: Minute::from_u8(0).unwrap(),
+                    date: DateSpecifier::Date { month: 12, day: 24 }
+                }
+            )))
+        );
+        assert_eq!(
+            parser::time_range("in 3 days"),
+            Ok(TimeRangeSpecifier::At(TimeSpecifier::After(
+                AfterTimeSpecifier::Day(3)
+            )))
+        );
+        assert_eq!(
+            parser::time_range("at next fri 21:00"),
+            Ok(TimeRangeSpecifier::At(TimeSpecifier::At(
+                AtTimeSpecifier::HourMinute {
+                    hour: Hour::from_u8(21).unwrap(),
+                    minute: Minute::from_u8(0).unwrap(),
+                    date: DateSpecifier::NextWeek(Weekday::Fri)
+                }
+            )))
+        );
+        assert!(parser::time_range("at someday 21:00").is_err());
+        assert_eq!(
+            parser::command("all at fri 21:00"),
+            Ok(Command::Kaisan {
+                kaisanee: KaisaneeSpecifier::All,
+                time_range: TimeRangeSpecifier::At(TimeSpecifier::At(
+                    AtTimeSpecifier::HourMinute {
+                        hour: Hour::from_u8(21).unwrap(),
+                        minute: Minute::from_u8(0).unwrap(),
+                        date: DateSpecifier::Weekday(Weekday::Fri)
+                    }
+                )),
+                options: KaisanOptions::default(),
+            })
+        );
+    }
+
     #[test]
     fn test_after_en() {
         assert_eq!(
@@ -665,7 +1894,7 @@ mod tests {
                 AtTimeSpecifier::HourMinute {
                     hour: Hour::from_u8(12).unwrap(),
                     minute: Minute::from_u8(12).unwrap(),
-                    is_tomorrow: false
+                    date: DateSpecifier::Today
                 }
             )))
         );
@@ -675,7 +1904,7 @@ mod tests {
                 AtTimeSpecifier::HourMinute {
                     hour: Hour::from_u8(23).unwrap(),
                     minute: Minute::from_u8(25).unwrap(),
-                    is_tomorrow: true
+                    date: DateSpecifier::Tomorrow
                 }
             )))
         );
@@ -708,4 +1937,262 @@ mod tests {
             )))
         );
     }
+
+    #[test]
+    fn test_relative_en() {
+        let after = |spec| Ok(TimeRangeSpecifier::At(TimeSpecifier::After(spec)));
+        assert_eq!(
+            parser::time_range("in 10 minutes"),
+            after(AfterTimeSpecifier::Minute(10))
+        );
+        assert_eq!(
+            parser::time_range("in 1h30m"),
+            after(AfterTimeSpecifier::HourMinute(1, 30))
+        );
+        assert_eq!(
+            parser::time_range("in an hour"),
+            after(AfterTimeSpecifier::Hour(1))
+        );
+        assert_eq!(
+            parser::time_range("in a minute"),
+            after(AfterTimeSpecifier::Minute(1))
+        );
+        assert_eq!(
+            parser::time_range("after an hour"),
+            after(AfterTimeSpecifier::Hour(1))
+        );
+        assert_eq!(
+            parser::time_range("10 min"),
+            after(AfterTimeSpecifier::Minute(10))
+        );
+        assert_eq!(
+            parser::time_range("30s"),
+            after(AfterTimeSpecifier::Second(30))
+        );
+        assert_eq!(
+            parser::time_range("2 hours 15 minutes"),
+            after(AfterTimeSpecifier::HourMinute(2, 15))
+        );
+        assert_eq!(
+            parser::command("me 10min"),
+            Ok(Command::Kaisan {
+                kaisanee: KaisaneeSpecifier::Me,
+                time_range: TimeRangeSpecifier::At(TimeSpecifier::After(
+                    AfterTimeSpecifier::Minute(10)
+                )),
+                options: KaisanOptions::default(),
+            })
+        );
+        assert!(parser::time_range("in an").is_err());
+        assert!(parser::command("10 mine").is_err());
+
+        // without 後, Japanese suffixes keep meaning the time
+        assert_eq!(
+            parser::time_range("10分"),
+            Ok(TimeRangeSpecifier::At(TimeSpecifier::At(
+                AtTimeSpecifier::Minute(Minute::from_u8(10).unwrap())
+            )))
+        );
+        assert!(parser::command("3時間").is_err());
+    }
+
+    #[test]
+    fn test_fractional() {
+        let after = |spec| Ok(TimeRangeSpecifier::At(TimeSpecifier::After(spec)));
+        assert_eq!(
+            parser::time_range("1時間半後"),
+            after(AfterTimeSpecifier::HourMinute(1, 30))
+        );
+        assert_eq!(
+            parser::time_range("1.5時間後"),
+            after(AfterTimeSpecifier::Fractional(90 * 60))
+        );
+        assert_eq!(
+            parser::time_range("5分半後"),
+            after(AfterTimeSpecifier::Fractional(5 * 60 + 30))
+        );
+        assert_eq!(
+            parser::time_range("after 90.5min"),
+            after(AfterTimeSpecifier::Fractional(90 * 60 + 30))
+        );
+        assert_eq!(
+            parser::time_range("after 0.33333m"),
+            after(AfterTimeSpecifier::Fractional(20))
+        );
+        assert_eq!(
+            parser::time_range("after 1.5 days"),
+            after(AfterTimeSpecifier::Fractional(36 * 60 * 60))
+        );
+        assert_eq!(
+            parser::time_range("within 1時間半"),
+            Ok(TimeRangeSpecifier::By(TimeSpecifier::After(
+                AfterTimeSpecifier::HourMinute(1, 30)
+            )))
+        );
+        assert_eq!(
+            parser::time_range("2.25h以内"),
+            Ok(TimeRangeSpecifier::By(TimeSpecifier::After(
+                AfterTimeSpecifier::Fractional(135 * 60)
+            )))
+        );
+        assert!(parser::time_range("1.5後").is_err());
+    }
+
+    #[test]
+    fn test_full_width() {
+        let at_23 = Command::Kaisan {
+            kaisanee: KaisaneeSpecifier::All,
+            time_range: TimeRangeSpecifier::At(TimeSpecifier::At(AtTimeSpecifier::Hour {
+                hour: Hour::from_u8(23).unwrap(),
+                date: DateSpecifier::Today,
+            })),
+            options: KaisanOptions::default(),
+        };
+        assert_eq!("２３時".parse::<Command>().unwrap(), at_23);
+        assert_eq!("全員を　２３時に解散".parse::<Command>().unwrap(), at_23);
+        assert_eq!(
+            "me １０：３０".parse::<Command>().unwrap(),
+            Command::Kaisan {
+                kaisanee: KaisaneeSpecifier::Me,
+                time_range: TimeRangeSpecifier::At(TimeSpecifier::At(
+                    AtTimeSpecifier::HourMinute {
+                        hour: Hour::from_u8(10).unwrap(),
+                        minute: Minute::from_u8(30).unwrap(),
+                        date: DateSpecifier::Today,
+                    }
+                )),
+                options: KaisanOptions::default(),
+            }
+        );
+        assert_eq!(
+            "add-reminder　５分前".parse::<Command>().unwrap(),
+            Command::AddReminder(Reminder::before_minutes(5))
+        );
+        assert!("２５時".parse::<Command>().is_err());
+    }
+
+    #[test]
+    fn test_out_of_range_numbers() {
+        assert_eq!(
+            parser::time_range("二百五十五分後"),
+            Ok(TimeRangeSpecifier::At(TimeSpecifier::After(
+                AfterTimeSpecifier::Minute(255)
+            )))
+        );
+        assert!(parser::time_range("二百五十六分後").is_err());
+        assert!(parser::time_range("三百分後").is_err());
+        assert!(parser::kaisanee("<@99999999999999999999>").is_err());
+        assert!(parser::kaisanee("<@0>").is_err());
+        assert!(parser::command("allow-channel <#99999999999999999999>").is_err());
+    }
+
+    #[test]
+    fn test_long_input() {
+        let kanji = "一".repeat(10_000);
+        assert!(parser::command(&format!("{}分後", kanji)).is_err());
+        let users = "<@1> ".repeat(1_000);
+        assert!(parser::command(&format!("{}今すぐ", users)).is_ok());
+    }
+
+    fn token() -> impl Strategy<Value = String> {
+        prop_oneof![
+            "[0-9]{1,4}",
+            "[一二三四五六七八九十百]{1,6}",
+            "<@[!&]?[0-9]{1,25}>",
+            "<#[0-9]{1,25}>",
+            select(vec![
+                "時",
+                "分",
+                "半",
+                "秒",
+                ".",
+                "時間",
+                "日",
+                "後",
+                "まで",
+                "以内",
+                "の",
+                ":",
+                "/",
+                "月",
+                "曜",
+                "明日",
+                "明後日",
+                "来週",
+                "正午",
+                "今夜",
+                "h",
+                "m",
+                "s",
+                "min",
+                "hour",
+                "day",
+                "at",
+                "by",
+                "in",
+                "after",
+                "within",
+                "tomorrow",
+                "next",
+                "noon",
+                "friday",
+                "rfc3339",
+                "全員",
+                "me",
+                "この部屋",
+                "以外の",
+                "を",
+                "に",
+                "解散",
+                "preview",
+            ])
+            .prop_map(ToOwned::to_owned),
+            select(vec!["", " ", "  "]).prop_map(ToOwned::to_owned),
+        ]
+    }
+
+    fn time_expression() -> impl Strategy<Value = String> {
+        prop::collection::vec(token(), 1..10).prop_map(|tokens| tokens.concat())
+    }
+
+    fn assert_in_bounds(time_range: &TimeRangeSpecifier) {
+        let spec = match time_range {
+            TimeRangeSpecifier::At(TimeSpecifier::At(spec))
+            | TimeRangeSpecifier::By(TimeSpecifier::At(spec)) => spec,
+            _ => return,
+        };
+        let (hour, minute) = match *spec {
+            AtTimeSpecifier::Hour { hour, .. } => (Some(hour), None),
+            AtTimeSpecifier::Minute(minute) => (None, Some(minute)),
+            AtTimeSpecifier::HourMinute { hour, minute, .. } => (Some(hour), Some(minute)),
+            AtTimeSpecifier::Tonight => (None, None),
+        };
+        assert!(hour.map_or(true, |h| h.as_u32() < 24), "{:?}", spec);
+        assert!(minute.map_or(true, |m| m.as_u32() < 60), "{:?}", spec);
+    }
+
+    proptest! {
+        // a panic in an action of the grammar fails these as well as the assertions
+
+        #[test]
+        fn test_time_range_never_panics(input in time_expression()) {
+            if let Ok(time_range) = parser::time_range(&input) {
+                assert_in_bounds(&time_range);
+            }
+        }
+
+        #[test]
+        fn test_command_never_panics(input in time_expression()) {
+            if let Ok(Command::Kaisan { time_range, .. } | Command::Preview { time_range, .. }) =
+                parser::command(&input)
+            {
+                assert_in_bounds(&time_range);
+            }
+        }
+
+        #[test]
+        fn test_arbitrary_input_never_panics(input in "\\PC{0,40}") {
+            let _ = parser::command(&input);
+        }
+    }
 }
@@ -0,0 +1,661 @@
+//! Formats the commands back into the syntax they are parsed from, which tells the users how the
+//! bot has understood what they wrote.
+
+use std::fmt::{self, Display};
+
+use chrono::Weekday;
+
+use super::{Command, TimeRangeSpecifier};
+use crate::model::{
+    kaisanee::KaisaneeSpecifier,
+    reminder::Reminder,
+    setting::ChannelOverride,
+    time::{AfterTimeSpecifier, AtTimeSpecifier, DateSpecifier, TimeSpecifier},
+};
+
+/// Formats the command so that it parses back to the same command, such as `全員を 23:00 に解散`.
+/// The words skipped in [`KaisanOptions::ignored_tokens`](super::KaisanOptions) are left out.
+impl Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Command::Kaisan {
+                kaisanee,
+                time_range,
+                options,
+            } => {
+                write_kaisan(f, kaisanee, time_range)?;
+                if options.remind_only_me {
+                    f.write_str(" 私にだけリマインド")?;
+                }
+                Ok(())
+            }
+            Command::KaisanMulti { stages, options } => {
+                for (index, (kaisanee, time_range)) in stages.iter().enumerate() {
+                    if index != 0 {
+                        f.write_str("、")?;
+                    }
+                    write_kaisan(f, kaisanee, time_range)?;
+                }
+                if options.remind_only_me {
+                    f.write_str(" 私にだけリマインド")?;
+                }
+                Ok(())
+            }
+            Command::Preview {
+                kaisanee,
+                time_range,
+            } => {
+                f.write_str("preview ")?;
+                write_kaisan(f, kaisanee, time_range)
+            }
+            Command::ShowSetting => f.write_str("show-setting"),
+            Command::SaveProfile(name) => write!(f, "profile save {}", name),
+            Command::LoadProfile(name) => write!(f, "profile load {}", name),
+            Command::ListProfiles => f.write_str("profile list"),
+            Command::ExportSetting => f.write_str("export-setting"),
+            Command::ImportSetting => f.write_str("import-setting"),
+            Command::GenerateApiToken => f.write_str("api-token generate"),
+            Command::RevokeApiToken => f.write_str("api-token revoke"),
+            Command::TimeZone(tz) => write!(f, "timezone {}", tz.name()),
+            Command::TimeZoneWizard => f.write_str("timezone"),
+            Command::RequirePermission(b) => write!(f, "require-permission {}", b),
+            Command::RequirePermissionSelf(b) => write!(f, "require-permission-self {}", b),
+            Command::AddReminder(r) => {
+                f.write_str("add-reminder ")?;
+                write_reminder(f, r)
+            }
+            Command::RemoveReminder(r) => {
+                f.write_str("remove-reminder ")?;
+                write_reminder(f, r)
+            }
+            Command::ClearReminders => f.write_str("clear-reminders"),
+            Command::ListReminders => f.write_str("list-reminders"),
+            Command::RemindRandomKaisan(b) => write!(f, "remind-random {}", b),
+            Command::Countdown(b) => write!(f, "countdown {}", b),
+            Command::AuthorLeave(p) => write!(f, "author-leave {}", p.as_str()),
+            Command::RevealRandom(r) => write!(f, "reveal-random {}", r.as_str()),
+            Command::RandomDistribution(d) => write!(f, "random-distribution {}", d.as_str()),
+            Command::Dst(p) => write!(f, "dst {}", p.as_str()),
+            Command::WeekStart(w) => write!(f, "week-start {}", w.as_str()),
+            Command::Locale(l) => write!(f, "locale {}", l.as_str()),
+            Command::OnDuplicate(p) => write!(f, "on-duplicate {}", p.as_str()),
+            Command::AutoKaisan(None) => f.write_str("auto-kaisan off"),
+            Command::AutoKaisan(Some(h)) => write!(f, "auto-kaisan {}", h.as_u32()),
+            Command::MaxHorizon(n) => write!(f, "max-horizon {}", n),
+            Command::Tonight(h) => write!(f, "tonight {}", h.as_u32()),
+            Command::MaxSchedules(n) => write!(f, "max-schedules {}", n),
+            Command::UndoWindow(0) => f.write_str("undo-window off"),
+            Command::UndoWindow(n) => write!(f, "undo-window {}", n),
+            Command::ReminderText(None) => f.write_str("reminder-text default"),
+            Command::ReminderText(Some(t)) => write!(f, "reminder-text {}", t),
+            Command::Prefix(None) => f.write_str("prefix default"),
+            Command::Prefix(Some(p)) => write!(f, "prefix {}", p),
+            Command::Webhook(None) => f.write_str("webhook off"),
+            Command::Webhook(Some(u)) => write!(f, "webhook {}", u),
+            Command::LogChannel(None) => f.write_str("log-channel off"),
+            Command::LogChannel(Some(c)) => write!(f, "log-channel <#{}>", c),
+            Command::Reaction(o, None) => write!(f, "reaction {} default", o.name()),
+            Command::Reaction(o, Some(r)) => write!(f, "reaction {} {}", o.name(), r),
+            Command::Phrase(o, None) => write!(f, "phrase {} off", o.name()),
+            Command::Phrase(o, Some(t)) => write!(f, "phrase {} {}", o.name(), t),
+            Command::When => f.write_str("when"),
+            Command::Ping => f.write_str("ping"),
+            Command::AbortAll => f.write_str("abort-all"),
+            Command::Pause => f.write_str("pause"),
+            Command::Resume => f.write_str("resume"),
+            Command::AdminSchedules => f.write_str("admin schedules"),
+            Command::AdminBroadcast(t) => write!(f, "admin broadcast {}", t),
+            Command::AdminStats => f.write_str("admin stats"),
+            Command::AdminStorage(None) => f.write_str("admin storage"),
+            Command::AdminStorage(Some(g)) => write!(f, "admin storage {}", g),
+            Command::AdminLogLevel(d) => write!(f, "admin log-level {}", d),
+            Command::AdminSoak(None) => f.write_str("admin soak off"),
+            Command::AdminSoak(Some(n)) => write!(f, "admin soak {}", n),
+            Command::CancelMine => f.write_str("cancel mine"),
+            Command::Undo => f.write_str("undo"),
+            Command::AllowChannel(None) => f.write_str("allow-channel"),
+            Command::AllowChannel(Some(c)) => write!(f, "allow-channel <#{}>", c),
+            Command::ChannelSetting(c, None) => write!(f, "channel-setting <#{}>", c),
+            Command::ChannelSetting(c, Some(o)) => {
+                write!(f, "channel-setting <#{}> ", c)?;
+                match o {
+                    ChannelOverride::MaxHorizon(None) => f.write_str("max-horizon default"),
+                    ChannelOverride::MaxHorizon(Some(n)) => write!(f, "max-horizon {}", n),
+                    ChannelOverride::Countdown(None) => f.write_str("countdown default"),
+                    ChannelOverride::Countdown(Some(b)) => write!(f, "countdown {}", b),
+                    ChannelOverride::AuthorLeave(None) => f.write_str("author-leave default"),
+                    ChannelOverride::AuthorLeave(Some(p)) => {
+                        write!(f, "author-leave {}", p.as_str())
+                    }
+                    ChannelOverride::OnDuplicate(None) => f.write_str("on-duplicate default"),
+                    ChannelOverride::OnDuplicate(Some(p)) => {
+                        write!(f, "on-duplicate {}", p.as_str())
+                    }
+                }
+            }
+            Command::DenyChannel(None) => f.write_str("deny-channel"),
+            Command::DenyChannel(Some(c)) => write!(f, "deny-channel <#{}>", c),
+            Command::AllowRole(r) => write!(f, "allow-role <@&{}>", r),
+            Command::DenyRole(r) => write!(f, "deny-role <@&{}>", r),
+            Command::ListRoles => f.write_str("list-roles"),
+            Command::Now(id) => write!(f, "now #{}", id.as_u32()),
+            Command::WhoKickedMe => f.write_str("who-kicked-me"),
+            Command::Complain => f.write_str("complain"),
+            Command::ShowComplaints => f.write_str("complaints"),
+            Command::ShowStats => f.write_str("stats"),
+            Command::Help => f.write_str("help"),
+        }
+    }
+}
+
+/// Formats the time range such as `23:00`, `5分後` or `今夜まで`.
+impl Display for TimeRangeSpecifier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TimeRangeSpecifier::Now => f.write_str("今すぐ"),
+            TimeRangeSpecifier::At(TimeSpecifier::After(after)) => {
+                write_after(f, after)?;
+                f.write_str("後")
+            }
+            TimeRangeSpecifier::By(TimeSpecifier::After(after)) => {
+                write_after(f, after)?;
+                f.write_str("以内")
+            }
+            TimeRangeSpecifier::At(spec) => write_time(f, spec),
+            TimeRangeSpecifier::By(spec) => {
+                write_time(f, spec)?;
+                f.write_str("まで")
+            }
+        }
+    }
+}
+
+fn write_kaisan(
+    f: &mut fmt::Formatter,
+    kaisanee: &KaisaneeSpecifier,
+    time_range: &TimeRangeSpecifier,
+) -> fmt::Result {
+    write_kaisanee(f, kaisanee)?;
+    write!(f, "を {} に解散", time_range)
+}
+
+fn write_users(f: &mut fmt::Formatter, users: &[impl Display]) -> fmt::Result {
+    for (index, user) in users.iter().enumerate() {
+        if index != 0 {
+            f.write_str(" ")?;
+        }
+        write!(f, "<@{}>", user)?;
+    }
+    Ok(())
+}
+
+fn write_kaisanee(f: &mut fmt::Formatter, kaisanee: &KaisaneeSpecifier) -> fmt::Result {
+    match kaisanee {
+        KaisaneeSpecifier::Me => f.write_str("私"),
+        KaisaneeSpecifier::All => f.write_str("全員"),
+        KaisaneeSpecifier::Users(users) => write_users(f, users),
+        KaisaneeSpecifier::Named(names) => {
+            for (index, name) in names.iter().enumerate() {
+                if index != 0 {
+                    f.write_str(" ")?;
+                }
+                write!(f, "@{}", name)?;
+            }
+            Ok(())
+        }
+        KaisaneeSpecifier::ThisChannel { except } if except.is_empty() => f.write_str("この部屋"),
+        KaisaneeSpecifier::ThisChannel { except } => {
+            write_users(f, except)?;
+            f.write_str(" 以外のこの部屋")
+        }
+    }
+}
+
+fn write_reminder(f: &mut fmt::Formatter, reminder: &Reminder) -> fmt::Result {
+    let seconds = reminder.before_duration().num_seconds();
+    if seconds % (60 * 60) == 0 {
+        write!(f, "{}時間前", seconds / (60 * 60))
+    } else if seconds % 60 == 0 {
+        write!(f, "{}分前", seconds / 60)
+    } else {
+        write!(f, "{}秒前", seconds)
+    }
+}
+
+fn write_after(f: &mut fmt::Formatter, after: &AfterTimeSpecifier) -> fmt::Result {
+    match *after {
+        AfterTimeSpecifier::Hour(h) => write!(f, "{}時間", h),
+        AfterTimeSpecifier::Minute(m) => write!(f, "{}分", m),
+        AfterTimeSpecifier::HourMinute(h, m) => write!(f, "{}時間{}分", h, m),
+        AfterTimeSpecifier::Second(s) => write!(f, "{}秒", s),
+        AfterTimeSpecifier::Day(d) => write!(f, "{}日", d),
+        AfterTimeSpecifier::Fractional(seconds) => write_fractional(f, seconds),
+    }
+}
+
+/// Writes the seconds as a decimal in the smallest unit the grammar can count up to, such as
+/// `90.5分`. The fraction is always written, so that it does not parse as the other variants.
+fn write_fractional(f: &mut fmt::Formatter, seconds: u32) -> fmt::Result {
+    let (unit, suffix) = [(60, "分"), (60 * 60, "時間")]
+        .into_iter()
+        .find(|(unit, _)| seconds / unit <= u32::from(u8::MAX))
+        .unwrap_or((24 * 60 * 60, "日"));
+    let fraction = u64::from(seconds % unit) * 1_000_000_000 / u64::from(unit);
+    let fraction = format!("{:09}", fraction);
+    let fraction = match fraction.trim_end_matches('0') {
+        "" => "0",
+        trimmed => trimmed,
+    };
+    write!(f, "{}.{}{}", seconds / unit, fraction, suffix)
+}
+
+fn weekday_ja(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "月曜日",
+        Weekday::Tue => "火曜日",
+        Weekday::Wed => "水曜日",
+        Weekday::Thu => "木曜日",
+        Weekday::Fri => "金曜日",
+        Weekday::Sat => "土曜日",
+        Weekday::Sun => "日曜日",
+    }
+}
+
+/// Writes the day followed by `の`, or nothing for today.
+fn write_date(f: &mut fmt::Formatter, date: &DateSpecifier) -> fmt::Result {
+    match *date {
+        DateSpecifier::Today => Ok(()),
+        DateSpecifier::Tomorrow => f.write_str("明日の"),
+        DateSpecifier::InDays(n) => write!(f, "{}日後の", n),
+        DateSpecifier::Weekday(w) => write!(f, "{}の", weekday_ja(w)),
+        DateSpecifier::NextWeek(w) => write!(f, "来週の{}の", weekday_ja(w)),
+        DateSpecifier::Date { month, day } => write!(f, "{}/{}の", month, day),
+    }
+}
+
+fn write_time(f: &mut fmt::Formatter, spec: &TimeSpecifier) -> fmt::Result {
+    match spec {
+        TimeSpecifier::At(AtTimeSpecifier::Hour { hour, date }) => {
+            write_date(f, date)?;
+            write!(f, "{}時", hour.as_u32())
+        }
+        TimeSpecifier::At(AtTimeSpecifier::Minute(minute)) => write!(f, "{}分", minute.as_u32()),
+        TimeSpecifier::At(AtTimeSpecifier::HourMinute { hour, minute, date }) => {
+            write_date(f, date)?;
+            write!(f, "{}:{:02}", hour.as_u32(), minute.as_u32())
+        }
+        TimeSpecifier::At(AtTimeSpecifier::Tonight) => f.write_str("今夜"),
+        TimeSpecifier::After(after) => {
+            write_after(f, after)?;
+            f.write_str("後")
+        }
+        TimeSpecifier::Exactly(time) => write!(f, "rfc3339 {}", time.to_rfc3339()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{parser, Command, KaisanOptions, TimeRangeSpecifier};
+    use crate::model::{
+        kaisanee::KaisaneeSpecifier,
+        reaction::Outcome,
+        reminder::Reminder,
+        schedule::ScheduleId,
+        setting::{
+            AuthorLeavePolicy, ChannelOverride, DstPolicy, DuplicatePolicy, Locale,
+            RandomDistribution, RevealRandom, WeekStart,
+        },
+        time::{AfterTimeSpecifier, AtTimeSpecifier, DateSpecifier, Hour, Minute, TimeSpecifier},
+    };
+    use crate::soak::MAX_SOAK_INTERVAL_MINUTES;
+
+    use chrono::{DateTime, FixedOffset, Weekday};
+    use proptest::{prelude::*, sample::select};
+    use serenity::model::{
+        channel::ReactionType,
+        id::{ChannelId, EmojiId, GuildId, RoleId, UserId},
+    };
+
+    fn hour() -> impl Strategy<Value = Hour> {
+        (0..24u8).prop_map(|h| Hour::from_u8(h).unwrap())
+    }
+
+    fn minute() -> impl Strategy<Value = Minute> {
+        (0..60u8).prop_map(|m| Minute::from_u8(m).unwrap())
+    }
+
+    fn weekday() -> impl Strategy<Value = Weekday> {
+        select(vec![
+            Weekday::Mon,
+            Weekday::Tue,
+            Weekday::Wed,
+            Weekday::Thu,
+            Weekday::Fri,
+            Weekday::Sat,
+            Weekday::Sun,
+        ])
+    }
+
+    fn date() -> impl Strategy<Value = DateSpecifier> {
+        prop_oneof![
+            Just(DateSpecifier::Today),
+            Just(DateSpecifier::Tomorrow),
+            any::<u8>().prop_map(DateSpecifier::InDays),
+            weekday().prop_map(DateSpecifier::Weekday),
+            weekday().prop_map(DateSpecifier::NextWeek),
+            (1..=12u32, 1..=31u32).prop_map(|(month, day)| DateSpecifier::Date { month, day }),
+        ]
+    }
+
+    fn after() -> impl Strategy<Value = AfterTimeSpecifier> {
+        prop_oneof![
+            any::<u8>().prop_map(AfterTimeSpecifier::Hour),
+            any::<u8>().prop_map(AfterTimeSpecifier::Minute),
+            (any::<u8>(), any::<u8>()).prop_map(|(h, m)| AfterTimeSpecifier::HourMinute(h, m)),
+            any::<u8>().prop_map(AfterTimeSpecifier::Second),
+            any::<u8>().prop_map(AfterTimeSpecifier::Day),
+            (0..256 * 24 * 60 * 60u32).prop_map(AfterTimeSpecifier::Fractional),
+        ]
+    }
+
+    fn exactly() -> impl Strategy<Value = DateTime<FixedOffset>> {
+        (
+            0..4_000_000_000i64,
+            0..1_000_000_000u32,
+            -23 * 60..=23 * 60i32,
+        )
+            .prop_map(|(secs, nanos, offset_minutes)| {
+                DateTime::from_timestamp(secs, nanos)
+                    .unwrap()
+                    .with_timezone(&FixedOffset::east_opt(offset_minutes * 60).unwrap())
+            })
+    }
+
+    fn time() -> impl Strategy<Value = TimeSpecifier> {
+        prop_oneof![
+            (hour(), date())
+                .prop_map(|(hour, date)| TimeSpecifier::At(AtTimeSpecifier::Hour { hour, date })),
+            minute().prop_map(|m| TimeSpecifier::At(AtTimeSpecifier::Minute(m))),
+            (hour(), minute(), date()).prop_map(|(hour, minute, date)| TimeSpecifier::At(
+                AtTimeSpecifier::HourMinute { hour, minute, date }
+            )),
+            Just(TimeSpecifier::At(AtTimeSpecifier::Tonight)),
+            after().prop_map(TimeSpecifier::After),
+            exactly().prop_map(TimeSpecifier::Exactly),
+        ]
+    }
+
+    fn time_range() -> impl Strategy<Value = TimeRangeSpecifier> {
+        prop_oneof![
+            time().prop_map(TimeRangeSpecifier::At),
+            time().prop_map(TimeRangeSpecifier::By),
+            Just(TimeRangeSpecifier::Now),
+        ]
+    }
+
+    fn users() -> impl Strategy<Value = Vec<UserId>> {
+        prop::collection::vec((1..=u64::MAX).prop_map(UserId::new), 1..4)
+    }
+
+    fn kaisanee() -> impl Strategy<Value = KaisaneeSpecifier> {
+        prop_oneof![
+            Just(KaisaneeSpecifier::Me),
+            Just(KaisaneeSpecifier::All),
+            users().prop_map(KaisaneeSpecifier::Users),
+            prop::collection::vec("[a-z0-9_.ぁ-んァ-ン田中佐藤]{1,8}", 1..4)
+                .prop_filter("を is a particle", |names| names
+                    .iter()
+                    .all(|n| !n.contains('を')))
+                .prop_map(KaisaneeSpecifier::Named),
+            prop::collection::vec((1..=u64::MAX).prop_map(UserId::new), 0..4)
+                .prop_map(|except| KaisaneeSpecifier::ThisChannel { except }),
+        ]
+    }
+
+    fn reminder() -> impl Strategy<Value = Reminder> {
+        prop_oneof![
+            any::<u8>().prop_map(|s| Reminder::before_seconds(s.into())),
+            any::<u8>().prop_map(|m| Reminder::before_minutes(m.into())),
+            any::<u8>().prop_map(|h| Reminder::before_hours(h.into())),
+        ]
+    }
+
+    fn outcome() -> impl Strategy<Value = Outcome> {
+        select(vec![Outcome::Success, Outcome::Failure])
+    }
+
+    fn reaction() -> impl Strategy<Value = ReactionType> {
+        prop_oneof![
+            select(vec!["✅", "❌", "🙆", "👍🏽", "1️⃣"])
+                .prop_map(|e| ReactionType::Unicode(e.to_owned())),
+            (any::<bool>(), 1..=u64::MAX, "[a-z_]{2,10}").prop_map(|(animated, id, name)| {
+                ReactionType::Custom {
+                    animated,
+                    id: EmojiId::new(id),
+                    name: Some(name),
+                }
+            }),
+        ]
+    }
+
+    /// Free text, except for the words that turn the setting off.
+    fn text() -> impl Strategy<Value = String> {
+        "[a-zA-Z0-9ぁ-んァ-ン漢字！][a-zA-Z0-9ぁ-んァ-ン漢字！ ]{0,20}"
+            .prop_filter("keyword", |t| {
+                !["off", "none", "default", "reset"].contains(&t.as_str())
+            })
+    }
+
+    fn kaisan() -> impl Strategy<Value = Command> {
+        (kaisanee(), time_range(), any::<bool>()).prop_map(|(kaisanee, time_range, only_me)| {
+            Command::Kaisan {
+                kaisanee,
+                time_range,
+                options: KaisanOptions {
+                    remind_only_me: only_me,
+                    ..KaisanOptions::default()
+                },
+            }
+        })
+    }
+
+    fn kaisan_multi() -> impl Strategy<Value = Command> {
+        (
+            prop::collection::vec((kaisanee(), time_range()), 2..4),
+            any::<bool>(),
+        )
+            .prop_map(|(stages, only_me)| Command::KaisanMulti {
+                stages,
+                options: KaisanOptions {
+                    remind_only_me: only_me,
+                    ..KaisanOptions::default()
+                },
+            })
+    }
+
+    fn setting() -> impl Strategy<Value = Command> {
+        prop_oneof![
+            select(chrono_tz::TZ_VARIANTS.to_vec()).prop_map(Command::TimeZone),
+            any::<bool>().prop_map(Command::RequirePermission),
+            any::<bool>().prop_map(Command::RequirePermissionSelf),
+            reminder().prop_map(Command::AddReminder),
+            reminder().prop_map(Command::RemoveReminder),
+            any::<bool>().prop_map(Command::RemindRandomKaisan),
+            any::<bool>().prop_map(Command::Countdown),
+            select(vec![
+                Command::AuthorLeave(AuthorLeavePolicy::Keep),
+                Command::AuthorLeave(AuthorLeavePolicy::Reroll),
+                Command::AuthorLeave(AuthorLeavePolicy::Cancel),
+                Command::RevealRandom(RevealRandom::Off),
+                Command::RevealRandom(RevealRandom::Channel),
+                Command::RevealRandom(RevealRandom::DirectMessage),
+                Command::RandomDistribution(RandomDistribution::Uniform),
+                Command::RandomDistribution(RandomDistribution::LateBiased),
+                Command::RandomDistribution(RandomDistribution::EarlyBiased),
+                Command::Dst(DstPolicy::Earliest),
+                Command::Dst(DstPolicy::Latest),
+                Command::Dst(DstPolicy::Reject),
+                Command::WeekStart(WeekStart::Monday),
+                Command::WeekStart(WeekStart::Sunday),
+                Command::Locale(Locale::Japanese),
+                Command::Locale(Locale::English),
+                Command::Locale(Locale::Both),
+                Command::OnDuplicate(DuplicatePolicy::Stack),
+                Command::OnDuplicate(DuplicatePolicy::Replace),
+                Command::OnDuplicate(DuplicatePolicy::Reject),
+            ]),
+            prop::option::of(hour()).prop_map(Command::AutoKaisan),
+            (1..=u8::MAX).prop_map(Command::MaxHorizon),
+            hour().prop_map(Command::Tonight),
+            (1..=u8::MAX).prop_map(Command::MaxSchedules),
+            any::<u8>().prop_map(Command::UndoWindow),
+            prop::option::of(text()).prop_map(Command::ReminderText),
+            prop::option::of("[a-z!?$%.]{1,5}".prop_filter("keyword", |p| p != "reset"))
+                .prop_map(Command::Prefix),
+            prop::option::of("https://[a-z]{1,10}\\.example/[a-z0-9/]{0,10}")
+                .prop_map(Command::Webhook),
+            prop::option::of((1..=u64::MAX).prop_map(ChannelId::new)).prop_map(Command::LogChannel),
+            (outcome(), prop::option::of(reaction())).prop_map(|(o, r)| Command::Reaction(o, r)),
+            (outcome(), prop::option::of(text())).prop_map(|(o, t)| Command::Phrase(o, t)),
+        ]
+    }
+
+    fn channel_override() -> impl Strategy<Value = ChannelOverride> {
+        prop_oneof![
+            prop::option::of(1..=u8::MAX).prop_map(ChannelOverride::MaxHorizon),
+            prop::option::of(any::<bool>()).prop_map(ChannelOverride::Countdown),
+            prop::option::of(select(vec![
+                AuthorLeavePolicy::Keep,
+                AuthorLeavePolicy::Reroll,
+                AuthorLeavePolicy::Cancel,
+            ]))
+            .prop_map(ChannelOverride::AuthorLeave),
+            prop::option::of(select(vec![
+                DuplicatePolicy::Stack,
+                DuplicatePolicy::Replace,
+                DuplicatePolicy::Reject,
+            ]))
+            .prop_map(ChannelOverride::OnDuplicate),
+        ]
+    }
+
+    fn other() -> impl Strategy<Value = Command> {
+        prop_oneof![
+            select(vec![
+                Command::ShowSetting,
+                Command::ListProfiles,
+                Command::ExportSetting,
+                Command::ImportSetting,
+                Command::GenerateApiToken,
+                Command::RevokeApiToken,
+                Command::TimeZoneWizard,
+                Command::ClearReminders,
+                Command::ListReminders,
+                Command::When,
+                Command::Ping,
+                Command::AbortAll,
+                Command::Pause,
+                Command::Resume,
+                Command::AdminSchedules,
+                Command::AdminStats,
+                Command::CancelMine,
+                Command::Undo,
+                Command::ListRoles,
+                Command::WhoKickedMe,
+                Command::Complain,
+                Command::ShowComplaints,
+                Command::ShowStats,
+                Command::Help,
+            ]),
+            text().prop_map(Command::AdminBroadcast),
+            "[a-z0-9_-]{1,32}".prop_map(Command::SaveProfile),
+            "[a-z0-9_-]{1,32}".prop_map(Command::LoadProfile),
+            prop::option::of((1..=u64::MAX).prop_map(GuildId::new)).prop_map(Command::AdminStorage),
+            "[a-z_=,]{1,30}".prop_map(Command::AdminLogLevel),
+            prop::option::of(1..=MAX_SOAK_INTERVAL_MINUTES).prop_map(Command::AdminSoak),
+            prop::option::of((1..=u64::MAX).prop_map(ChannelId::new))
+                .prop_map(Command::AllowChannel),
+            prop::option::of((1..=u64::MAX).prop_map(ChannelId::new))
+                .prop_map(Command::DenyChannel),
+            (
+                (1..=u64::MAX).prop_map(ChannelId::new),
+                prop::option::of(channel_override())
+            )
+                .prop_map(|(c, o)| Command::ChannelSetting(c, o)),
+            (1..=u64::MAX).prop_map(|r| Command::AllowRole(RoleId::new(r))),
+            (1..=u64::MAX).prop_map(|r| Command::DenyRole(RoleId::new(r))),
+            any::<u32>().prop_map(|n| Command::Now(ScheduleId::new(n))),
+        ]
+    }
+
+    #[test]
+    fn test_format() {
+        let command = Command::Kaisan {
+            kaisanee: KaisaneeSpecifier::All,
+            time_range: TimeRangeSpecifier::At(TimeSpecifier::At(AtTimeSpecifier::HourMinute {
+                hour: Hour::from_u8(23).unwrap(),
+                minute: Minute::from_u8(0).unwrap(),
+                date: DateSpecifier::Today,
+            })),
+            options: KaisanOptions::default(),
+        };
+        assert_eq!(command.to_string(), "全員を 23:00 に解散");
+        assert_eq!(
+            parser::command("<@1> and <@2> within 1h30m remind only me")
+                .unwrap()
+                .to_string(),
+            "<@1> <@2>を 1時間30分以内 に解散 私にだけリマインド"
+        );
+        assert_eq!(
+            parser::command("この部屋 except <@1> at next friday 5:30")
+                .unwrap()
+                .to_string(),
+            "<@1> 以外のこの部屋を 来週の金曜日の5:30 に解散"
+        );
+        assert_eq!(
+            parser::command("me after 90.5min").unwrap().to_string(),
+            "私を 90.5分後 に解散"
+        );
+        assert_eq!(
+            parser::command("me within 1.5日").unwrap().to_string(),
+            "私を 36.0時間以内 に解散"
+        );
+        assert_eq!(
+            parser::command("add-reminder 120m").unwrap().to_string(),
+            "add-reminder 2時間前"
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn test_roundtrip_kaisan(command in kaisan()) {
+            prop_assert_eq!(parser::command(&command.to_string()), Ok(command));
+        }
+
+        #[test]
+        fn test_roundtrip_kaisan_multi(command in kaisan_multi()) {
+            prop_assert_eq!(parser::command(&command.to_string()), Ok(command));
+        }
+
+        #[test]
+        fn test_roundtrip_preview(kaisanee in kaisanee(), time_range in time_range()) {
+            let command = Command::Preview { kaisanee, time_range };
+            prop_assert_eq!(parser::command(&command.to_string()), Ok(command));
+        }
+
+        #[test]
+        fn test_roundtrip_setting(command in setting()) {
+            prop_assert_eq!(parser::command(&command.to_string()), Ok(command));
+        }
+
+        #[test]
+        fn test_roundtrip_other(command in other()) {
+            prop_assert_eq!(parser::command(&command.to_string()), Ok(command));
+        }
+
+        #[test]
+        fn test_roundtrip_time_range(time_range in time_range()) {
+            prop_assert_eq!(parser::time_range(&time_range.to_string()), Ok(time_range));
+        }
+    }
+}
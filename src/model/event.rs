@@ -0,0 +1,29 @@
+use crate::model::schedule::ScheduleId;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serenity::model::id::{ChannelId, UserId};
+
+/// A record of a user disconnected by the bot.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DisconnectEvent {
+    pub time: DateTime<Utc>,
+    pub channel_id: ChannelId,
+    pub voice_channel_id: ChannelId,
+    pub target: UserId,
+    pub requester: UserId,
+    pub schedule_id: Option<ScheduleId>,
+}
+
+impl DisconnectEvent {
+    /// Identifies the event among the recorded ones.
+    pub fn key(&self) -> String {
+        format!("{}:{}", self.target, self.time.timestamp_millis())
+    }
+
+    /// The time of the event identified by the key made with [`DisconnectEvent::key`].
+    pub fn key_time(key: &str) -> Option<DateTime<Utc>> {
+        let (_, millis) = key.rsplit_once(':')?;
+        DateTime::from_timestamp_millis(millis.parse().ok()?)
+    }
+}
@@ -0,0 +1,68 @@
+use std::time::Duration;
+
+/// Latencies above these are reported as slow.
+pub const SLOW_REST_LATENCY: Duration = Duration::from_secs(1);
+pub const SLOW_GATEWAY_LATENCY: Duration = Duration::from_secs(1);
+pub const SLOW_DATABASE_LATENCY: Duration = Duration::from_millis(200);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComponentHealth {
+    Ok(Duration),
+    Slow(Duration),
+    /// The component did not respond or returned an error.
+    Failing,
+    /// The latency has not been measured yet, such as before the first heartbeat.
+    Unknown,
+}
+
+impl ComponentHealth {
+    pub fn from_latency(latency: Duration, slow: Duration) -> ComponentHealth {
+        if latency > slow {
+            ComponentHealth::Slow(latency)
+        } else {
+            ComponentHealth::Ok(latency)
+        }
+    }
+
+    pub fn latency(&self) -> Option<Duration> {
+        match self {
+            ComponentHealth::Ok(latency) | ComponentHealth::Slow(latency) => Some(*latency),
+            ComponentHealth::Failing | ComponentHealth::Unknown => None,
+        }
+    }
+
+    pub fn status(&self) -> &'static str {
+        match self {
+            ComponentHealth::Ok(_) => "ok",
+            ComponentHealth::Slow(_) => "slow",
+            ComponentHealth::Failing => "failing",
+            ComponentHealth::Unknown => "unknown",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Health {
+    pub rest: ComponentHealth,
+    pub gateway: ComponentHealth,
+    pub database: ComponentHealth,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ComponentHealth, SLOW_DATABASE_LATENCY};
+    use std::time::Duration;
+
+    #[test]
+    fn test_from_latency() {
+        assert_eq!(
+            ComponentHealth::from_latency(Duration::from_millis(5), SLOW_DATABASE_LATENCY),
+            ComponentHealth::Ok(Duration::from_millis(5))
+        );
+        assert_eq!(
+            ComponentHealth::from_latency(Duration::from_millis(500), SLOW_DATABASE_LATENCY),
+            ComponentHealth::Slow(Duration::from_millis(500))
+        );
+        assert_eq!(ComponentHealth::Failing.latency(), None);
+    }
+}
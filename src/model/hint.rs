@@ -0,0 +1,251 @@
+use crate::model::command::Command;
+
+use serde::Serialize;
+
+/// Words of commands that can be misspelled, with usage examples shown when they are used.
+const KEYWORDS: &[(&str, &[&str])] = &[
+    ("at", &["at 22:30", "me at 23時", "at 12/24 22:00"]),
+    ("after", &["after 10min", "me after 1h30m"]),
+    ("by", &["by 23:00", "me by midnight"]),
+    ("within", &["within 30min"]),
+    ("preview", &["preview after 10min"]),
+    ("cancel", &["cancel mine"]),
+    ("mine", &["cancel mine"]),
+    ("undo", &["undo"]),
+    ("now", &["now 3"]),
+    ("when", &["when"]),
+    ("ping", &["ping"]),
+    ("who-kicked-me", &["who-kicked-me"]),
+    ("complain", &["complain"]),
+    ("complaints", &["complaints"]),
+    ("stats", &["stats"]),
+    ("help", &["help"]),
+    ("show-setting", &["show-setting"]),
+    (
+        "profile",
+        &["profile save NAME", "profile load NAME", "profile list"],
+    ),
+    ("export-setting", &["export-setting"]),
+    ("import-setting", &["import-setting"]),
+    ("api-token", &["api-token generate", "api-token revoke"]),
+    (
+        "webhook",
+        &["webhook https://example.com/hook", "webhook off"],
+    ),
+    ("log-channel", &["log-channel #mod-log", "log-channel off"]),
+    (
+        "reaction",
+        &["reaction success 🎉", "reaction failure default"],
+    ),
+    ("phrase", &["phrase success おつかれ", "phrase failure off"]),
+    ("timezone", &["timezone Asia/Tokyo"]),
+    ("require-permission", &["require-permission yes"]),
+    ("require-permission-self", &["require-permission-self yes"]),
+    ("add-reminder", &["add-reminder 5", "add-reminder 30s"]),
+    ("remove-reminder", &["remove-reminder 5"]),
+    ("clear-reminders", &["clear-reminders"]),
+    ("list-reminders", &["list-reminders"]),
+    ("remind-random", &["remind-random yes"]),
+    ("reveal-random", &["reveal-random channel"]),
+    ("random-distribution", &["random-distribution late-biased"]),
+    ("countdown", &["countdown yes"]),
+    ("author-leave", &["author-leave reroll"]),
+    ("dst", &["dst earliest"]),
+    ("week-start", &["week-start sunday"]),
+    ("locale", &["locale both"]),
+    ("on-duplicate", &["on-duplicate replace"]),
+    ("tonight", &["tonight", "tonight 22"]),
+    ("max-horizon", &["max-horizon 12"]),
+    ("max-schedules", &["max-schedules 3"]),
+    ("undo-window", &["undo-window 2", "undo-window off"]),
+    (
+        "reminder-text",
+        &[
+            "reminder-text {targets} {remaining}",
+            "reminder-text default",
+        ],
+    ),
+    ("auto-kaisan", &["auto-kaisan 2", "auto-kaisan off"]),
+    ("allow-channel", &["allow-channel"]),
+    ("deny-channel", &["deny-channel"]),
+    (
+        "channel-setting",
+        &[
+            "channel-setting #CHANNEL",
+            "channel-setting #CHANNEL max-horizon 24",
+            "channel-setting #CHANNEL countdown default",
+        ],
+    ),
+    ("allow-role", &["allow-role @ROLE"]),
+    ("deny-role", &["deny-role @ROLE"]),
+    ("list-roles", &["list-roles"]),
+    ("prefix", &["prefix !k", "prefix default"]),
+    ("abort-all", &["abort-all"]),
+    ("pause", &["pause"]),
+    ("resume", &["resume"]),
+    (
+        "admin",
+        &[
+            "admin schedules",
+            "admin stats",
+            "admin storage",
+            "admin broadcast TEXT",
+            "admin log-level debug",
+        ],
+    ),
+    ("tomorrow", &["at tomorrow 10:00"]),
+    ("midnight", &["by midnight"]),
+    ("noon", &["at noon"]),
+];
+
+/// Units of durations. Shorter ones are left out as they are too easy to match by accident.
+const UNITS: &[&str] = &[
+    "seconds", "second", "sec", "minutes", "minute", "min", "hours", "hour", "days", "day",
+];
+
+/// A guess at what a command that cannot be parsed was meant to be.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ParseHint {
+    /// The input with the likely mistakes fixed, if it parses after that.
+    pub corrected: Option<String>,
+    pub examples: Vec<&'static str>,
+}
+
+impl ParseHint {
+    /// Returns `None` if nothing in the input looks familiar.
+    pub fn suggest(input: &str) -> Option<ParseHint> {
+        let mut changed = false;
+        let mut tokens = Vec::new();
+        for token in input.split_whitespace() {
+            let fixed = correct_token(token);
+            changed |= fixed != token;
+            tokens.push(fixed);
+        }
+
+        let examples = tokens
+            .iter()
+            .find_map(|t| KEYWORDS.iter().find(|(k, _)| k == t))
+            .map(|(_, examples)| examples.to_vec())
+            .unwrap_or_default();
+        let corrected = tokens.join(" ");
+        let corrected = (changed && corrected.parse::<Command>().is_ok()).then_some(corrected);
+
+        if corrected.is_none() && examples.is_empty() {
+            return None;
+        }
+        Some(ParseHint {
+            corrected,
+            examples,
+        })
+    }
+}
+
+fn correct_token(token: &str) -> String {
+    if token.chars().all(|c| c.is_ascii_lowercase() || c == '-') {
+        let words = KEYWORDS
+            .iter()
+            .map(|(k, _)| *k)
+            .chain(UNITS.iter().copied());
+        return closest(token, words).unwrap_or(token).to_owned();
+    }
+
+    // durations like `1h30minites`
+    if token.starts_with(|c: char| c.is_ascii_digit())
+        && token.chars().all(|c| c.is_ascii_alphanumeric())
+    {
+        let mut corrected = String::new();
+        let mut rest = token;
+        while !rest.is_empty() {
+            let is_digit = rest.starts_with(|c: char| c.is_ascii_digit());
+            let end = rest
+                .find(|c: char| c.is_ascii_digit() != is_digit)
+                .unwrap_or(rest.len());
+            let (run, next) = rest.split_at(end);
+            if is_digit {
+                corrected.push_str(run);
+            } else {
+                corrected.push_str(closest(run, UNITS.iter().copied()).unwrap_or(run));
+            }
+            rest = next;
+        }
+        return corrected;
+    }
+
+    token.to_owned()
+}
+
+/// Finds the word closest to `input`, allowing about one typo per four characters.
+fn closest<'a>(input: &'a str, words: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    let max_distance = (input.chars().count() + 1) / 4;
+    let mut best = None;
+    for word in words {
+        let distance = edit_distance(input, word);
+        if distance == 0 {
+            return Some(word);
+        }
+        if distance <= max_distance && best.map_or(true, |(d, _)| distance < d) {
+            best = Some((distance, word));
+        }
+    }
+    best.map(|(_, word)| word)
+}
+
+pub(crate) fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substituted = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substituted.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{edit_distance, ParseHint};
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("minites", "minutes"), 1);
+        assert_eq!(edit_distance("afer", "after"), 1);
+        assert_eq!(edit_distance("", "at"), 2);
+        assert_eq!(edit_distance("same", "same"), 0);
+    }
+
+    #[test]
+    fn test_misspelled_unit() {
+        let hint = ParseHint::suggest("me after 10minites").unwrap();
+        assert_eq!(hint.corrected.as_deref(), Some("me after 10minutes"));
+        assert_eq!(hint.examples, vec!["after 10min", "me after 1h30m"]);
+
+        let hint = ParseHint::suggest("within 1hour30minuts").unwrap();
+        assert_eq!(hint.corrected.as_deref(), Some("within 1hour30minutes"));
+    }
+
+    #[test]
+    fn test_misspelled_subcommand() {
+        let hint = ParseHint::suggest("shwo-setting").unwrap();
+        assert_eq!(hint.corrected.as_deref(), Some("show-setting"));
+
+        let hint = ParseHint::suggest("afer 10min").unwrap();
+        assert_eq!(hint.corrected.as_deref(), Some("after 10min"));
+    }
+
+    #[test]
+    fn test_examples_only() {
+        let hint = ParseHint::suggest("timezone Asia/Tokio").unwrap();
+        assert_eq!(hint.corrected, None);
+        assert_eq!(hint.examples, vec!["timezone Asia/Tokyo"]);
+    }
+
+    #[test]
+    fn test_unfamiliar() {
+        assert_eq!(ParseHint::suggest("こんにちは"), None);
+        assert_eq!(ParseHint::suggest("xyzzy 42"), None);
+    }
+}
@@ -1,20 +1,29 @@
 use crate::say::{fmt, IntoIteratorSayExt, Say};
 
+use serde::{Deserialize, Serialize};
 use serenity::model::id::UserId;
 
-#[derive(Debug, PartialEq, Eq, Clone, Default)]
+#[derive(Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
 pub enum KaisaneeSpecifier {
     Me,
     #[default]
     All,
     Users(Vec<UserId>),
+    /// Members referred to by their names, which are resolved into `Users` before scheduling
+    Named(Vec<String>),
+    /// Everyone in the voice channel the author is in when the kaisan is carried out
+    ThisChannel {
+        except: Vec<UserId>,
+    },
 }
 
 impl KaisaneeSpecifier {
     pub fn may_include_others(&self, user_id: UserId) -> bool {
         match self {
             KaisaneeSpecifier::Me => false,
-            KaisaneeSpecifier::All => true,
+            KaisaneeSpecifier::All
+            | KaisaneeSpecifier::ThisChannel { .. }
+            | KaisaneeSpecifier::Named(_) => true,
             KaisaneeSpecifier::Users(users) => users != &[user_id],
         }
     }
@@ -26,6 +35,13 @@ impl Say for KaisaneeSpecifier {
             KaisaneeSpecifier::Me => f.write_str("あなた"),
             KaisaneeSpecifier::All => f.write_str("全員"),
             KaisaneeSpecifier::Users(ids) => ids.say_mentions_ref().fmt(f),
+            KaisaneeSpecifier::Named(names) => f.write_str(&names.join("、")),
+            KaisaneeSpecifier::ThisChannel { except } if except.is_empty() => {
+                f.write_str("この部屋の全員")
+            }
+            KaisaneeSpecifier::ThisChannel { except } => {
+                say!(f, "{}を除くこの部屋の全員", except.say_mentions_ref())
+            }
         }
     }
 }
@@ -0,0 +1,151 @@
+use crate::model::hint::edit_distance;
+
+use serenity::model::id::UserId;
+
+/// The names a member of the guild can be referred to by.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemberNames {
+    pub user_id: UserId,
+    pub username: String,
+    pub global_name: Option<String>,
+    pub nick: Option<String>,
+}
+
+impl MemberNames {
+    /// The name shown in the guild, which is also used to list candidates.
+    pub fn display_name(&self) -> &str {
+        self.nick
+            .as_deref()
+            .or(self.global_name.as_deref())
+            .unwrap_or(&self.username)
+    }
+
+    fn names(&self) -> impl Iterator<Item = String> + '_ {
+        std::iter::once(self.username.as_str())
+            .chain(self.global_name.as_deref())
+            .chain(self.nick.as_deref())
+            .map(str::to_lowercase)
+    }
+}
+
+/// Members with `name` as one of their names, ignoring case.
+pub fn exact_matches<'a>(name: &str, members: &'a [MemberNames]) -> Vec<&'a MemberNames> {
+    let name = name.to_lowercase();
+    members
+        .iter()
+        .filter(|member| member.names().any(|candidate| candidate == name))
+        .collect()
+}
+
+/// Members in either list, once each. The entry in `primary` wins for a member in both.
+pub fn merge(primary: Vec<MemberNames>, secondary: Vec<MemberNames>) -> Vec<MemberNames> {
+    let mut merged = primary;
+    for member in secondary {
+        if !merged.iter().any(|m| m.user_id == member.user_id) {
+            merged.push(member);
+        }
+    }
+    merged
+}
+
+/// Members that `name` may refer to, best matches only.
+///
+/// Exact matches on any of the names win over names containing `name`, which in turn win over
+/// names one typo away from it. An empty result means nobody matched, and more than one means
+/// the name is ambiguous.
+pub fn find_by_name<'a>(name: &str, members: &'a [MemberNames]) -> Vec<&'a MemberNames> {
+    let name = name.to_lowercase();
+    let tiers: [&dyn Fn(&str) -> bool; 3] = [
+        &|candidate| candidate == name,
+        &|candidate| candidate.contains(&name),
+        &|candidate| name.chars().count() >= 3 && edit_distance(&name, candidate) <= 1,
+    ];
+    for matches in tiers {
+        let found: Vec<_> = members
+            .iter()
+            .filter(|member| member.names().any(|candidate| matches(&candidate)))
+            .collect();
+        if !found.is_empty() {
+            return found;
+        }
+    }
+    Vec::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{exact_matches, find_by_name, merge, MemberNames};
+
+    use serenity::model::id::UserId;
+
+    fn member(id: u64, username: &str, nick: Option<&str>) -> MemberNames {
+        MemberNames {
+            user_id: UserId::new(id),
+            username: username.to_owned(),
+            global_name: None,
+            nick: nick.map(ToOwned::to_owned),
+        }
+    }
+
+    fn found(name: &str, members: &[MemberNames]) -> Vec<u64> {
+        find_by_name(name, members)
+            .into_iter()
+            .map(|m| m.user_id.get())
+            .collect()
+    }
+
+    #[test]
+    fn test_find_by_name() {
+        let members = [
+            member(1, "tanaka", Some("田中")),
+            member(2, "tanakayama", Some("田中山")),
+            member(3, "suzuki", None),
+            member(4, "Sato", Some("佐藤")),
+        ];
+        assert_eq!(found("田中", &members), vec![1]);
+        assert_eq!(found("TANAKA", &members), vec![1]);
+        assert_eq!(found("sato", &members), vec![4]);
+        assert_eq!(found("山", &members), vec![2]);
+        assert_eq!(found("tana", &members), vec![1, 2]);
+        assert_eq!(found("suzuky", &members), vec![3]);
+        assert_eq!(found("sz", &members), Vec::<u64>::new());
+        assert_eq!(found("高橋", &members), Vec::<u64>::new());
+    }
+
+    #[test]
+    fn test_exact_matches() {
+        let members = [
+            member(1, "tanaka", Some("田中")),
+            member(2, "tanakataro", None),
+        ];
+        let exact: Vec<_> = exact_matches("Tanaka", &members)
+            .into_iter()
+            .map(|m| m.user_id.get())
+            .collect();
+        assert_eq!(exact, vec![1]);
+        assert!(exact_matches("tana", &members).is_empty());
+    }
+
+    #[test]
+    fn test_merge_exact_wins_over_cached_fuzzy() {
+        let cached = vec![member(1, "taro", Some("田中太郎"))];
+        // the cache only has a member whose name contains the given one
+        assert!(exact_matches("田中", &cached).is_empty());
+        let searched = vec![
+            member(1, "taro", Some("田中太郎")),
+            member(2, "tanaka", Some("田中")),
+        ];
+        let merged = merge(cached, searched);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(found("田中", &merged), vec![2]);
+    }
+
+    #[test]
+    fn test_display_name() {
+        assert_eq!(member(1, "tanaka", Some("田中")).display_name(), "田中");
+        let mut without_nick = member(1, "tanaka", None);
+        assert_eq!(without_nick.display_name(), "tanaka");
+        without_nick.global_name = Some("Tanaka".to_owned());
+        assert_eq!(without_nick.display_name(), "Tanaka");
+    }
+}
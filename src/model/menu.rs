@@ -0,0 +1,251 @@
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use chrono_tz::{Tz, TZ_VARIANTS};
+
+pub const TIMEZONE_REGION_MENU_ID: &str = "timezone-region";
+pub const TIMEZONE_MENU_ID: &str = "timezone";
+
+/// Discord shows at most this many options in a select menu.
+pub const MAX_MENU_OPTIONS: usize = 25;
+
+const TIMEZONE_REGIONS: &[&str] = &[
+    "Africa",
+    "America",
+    "Antarctica",
+    "Asia",
+    "Atlantic",
+    "Australia",
+    "Europe",
+    "Indian",
+    "Pacific",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MenuOption {
+    pub label: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectMenu {
+    pub custom_id: String,
+    pub options: Vec<MenuOption>,
+}
+
+/// The timezone menus carry the timezone at the start of the wizard, so that the choice does not
+/// overwrite a change made by someone else in the meantime.
+fn timezone_menu_id(menu_id: &str, current: Tz) -> String {
+    format!("{}:{}", menu_id, current.name())
+}
+
+impl SelectMenu {
+    pub fn timezone_regions(current: Tz) -> SelectMenu {
+        SelectMenu {
+            custom_id: timezone_menu_id(TIMEZONE_REGION_MENU_ID, current),
+            options: TimeZoneRegion::all()
+                .into_iter()
+                .map(|region| MenuOption {
+                    label: region.label(),
+                    value: region.to_string(),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn timezones(region: TimeZoneRegion, current: Tz) -> SelectMenu {
+        SelectMenu {
+            custom_id: timezone_menu_id(TIMEZONE_MENU_ID, current),
+            options: region
+                .timezones()
+                .into_iter()
+                .map(|tz| MenuOption {
+                    label: city_name(tz),
+                    value: tz.name().to_owned(),
+                })
+                .collect(),
+        }
+    }
+}
+
+fn city_name(tz: Tz) -> String {
+    let name = tz.name();
+    let city = name.split_once('/').map_or(name, |(_, city)| city);
+    city.replace('_', " ")
+}
+
+/// Part of the timezones in a region, split so that each fits in a select menu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeZoneRegion {
+    name: &'static str,
+    index: usize,
+}
+
+impl TimeZoneRegion {
+    pub fn all() -> Vec<TimeZoneRegion> {
+        TIMEZONE_REGIONS
+            .iter()
+            .flat_map(|&name| {
+                let count = region_timezones(name).len().div_ceil(MAX_MENU_OPTIONS);
+                (0..count).map(move |index| TimeZoneRegion { name, index })
+            })
+            .collect()
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn timezones(&self) -> Vec<Tz> {
+        region_timezones(self.name)
+            .chunks(MAX_MENU_OPTIONS)
+            .nth(self.index)
+            .map(<[Tz]>::to_vec)
+            .unwrap_or_default()
+    }
+
+    /// Name of the region, followed by the range of the cities if the region is split.
+    pub fn label(&self) -> String {
+        if region_timezones(self.name).len() <= MAX_MENU_OPTIONS {
+            return self.name.to_owned();
+        }
+        let timezones = self.timezones();
+        match (timezones.first(), timezones.last()) {
+            (Some(&first), Some(&last)) => {
+                format!("{} ({} – {})", self.name, city_name(first), city_name(last))
+            }
+            _ => self.name.to_owned(),
+        }
+    }
+}
+
+fn region_timezones(region: &str) -> Vec<Tz> {
+    let mut timezones: Vec<_> = TZ_VARIANTS
+        .iter()
+        .copied()
+        .filter(|tz| {
+            tz.name()
+                .strip_prefix(region)
+                .is_some_and(|rest| rest.starts_with('/'))
+        })
+        .collect();
+    timezones.sort_by_key(|tz| tz.name());
+    timezones
+}
+
+impl Display for TimeZoneRegion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.name, self.index)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseTimeZoneRegionError;
+
+impl Display for ParseTimeZoneRegionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("unknown timezone region")
+    }
+}
+
+impl std::error::Error for ParseTimeZoneRegionError {}
+
+impl FromStr for TimeZoneRegion {
+    type Err = ParseTimeZoneRegionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, index) = s.split_once('/').ok_or(ParseTimeZoneRegionError)?;
+        let index = index
+            .parse::<usize>()
+            .map_err(|_| ParseTimeZoneRegionError)?;
+        TimeZoneRegion::all()
+            .into_iter()
+            .find(|region| region.name == name && region.index == index)
+            .ok_or(ParseTimeZoneRegionError)
+    }
+}
+
+/// Choice made in one of the select menus posted by the bot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuSelection {
+    TimeZoneRegion { region: TimeZoneRegion, current: Tz },
+    TimeZone { timezone: Tz, current: Tz },
+}
+
+impl MenuSelection {
+    pub fn parse(custom_id: &str, value: &str) -> Option<MenuSelection> {
+        let (menu_id, current) = custom_id.split_once(':')?;
+        let current = current.parse().ok()?;
+        match menu_id {
+            TIMEZONE_REGION_MENU_ID => Some(MenuSelection::TimeZoneRegion {
+                region: value.parse().ok()?,
+                current,
+            }),
+            TIMEZONE_MENU_ID => Some(MenuSelection::TimeZone {
+                timezone: value.parse().ok()?,
+                current,
+            }),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MenuSelection, SelectMenu, TimeZoneRegion, MAX_MENU_OPTIONS};
+    use chrono_tz::Tz;
+
+    #[test]
+    fn test_regions_fit_in_menu() {
+        let regions = TimeZoneRegion::all();
+        assert!(regions.len() <= MAX_MENU_OPTIONS);
+        for region in regions {
+            let timezones = region.timezones();
+            assert!(!timezones.is_empty());
+            assert!(timezones.len() <= MAX_MENU_OPTIONS);
+            assert_eq!(region.to_string().parse(), Ok(region));
+        }
+    }
+
+    #[test]
+    fn test_tokyo() {
+        let region = TimeZoneRegion::all()
+            .into_iter()
+            .find(|region| region.timezones().contains(&Tz::Asia__Tokyo))
+            .unwrap();
+        assert_eq!(region.name(), "Asia");
+        let menu = SelectMenu::timezones(region, Tz::UTC);
+        assert!(menu
+            .options
+            .iter()
+            .any(|option| option.label == "Tokyo" && option.value == "Asia/Tokyo"));
+        assert_eq!(
+            MenuSelection::parse(&menu.custom_id, "Asia/Tokyo"),
+            Some(MenuSelection::TimeZone {
+                timezone: Tz::Asia__Tokyo,
+                current: Tz::UTC
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            MenuSelection::parse("timezone-region:Asia/Tokyo", "Europe/0"),
+            Some(MenuSelection::TimeZoneRegion {
+                region: TimeZoneRegion {
+                    name: "Europe",
+                    index: 0
+                },
+                current: Tz::Asia__Tokyo
+            })
+        );
+        assert_eq!(
+            MenuSelection::parse("timezone-region:Asia/Tokyo", "Europe/9"),
+            None
+        );
+        assert_eq!(MenuSelection::parse("timezone:UTC", "Asia/Tokio"), None);
+        assert_eq!(MenuSelection::parse("timezone", "Asia/Tokyo"), None);
+        assert_eq!(MenuSelection::parse("unknown:UTC", "Asia/Tokyo"), None);
+    }
+}
@@ -1,42 +1,239 @@
 use std::collections::HashSet;
 
+use crate::database::{CacheStats, RetryStats};
+use crate::dispatcher::DispatchStats;
 use crate::error::Error;
-use crate::model::{kaisanee::KaisaneeSpecifier, reminder::Reminder, time::TimeSpecifier};
-use crate::say::{fmt, IntoIteratorSayExt, Say};
+use crate::model::{
+    api_token::ApiToken,
+    health::{ComponentHealth, Health},
+    hint::ParseHint,
+    kaisanee::KaisaneeSpecifier,
+    menu::TimeZoneRegion,
+    reminder::Reminder,
+    schedule::{Schedule, ScheduleId},
+    setting::{
+        AuthorLeavePolicy, ChannelOverrides, DstPolicy, DuplicatePolicy, Locale,
+        RandomDistribution, RevealRandom, WeekStart,
+    },
+    template::ReminderTemplate,
+    time::{Hour, TimeSpecifier},
+};
+use crate::say::{fmt, DisplayExt, IntoIteratorSayExt, Say, SayExt};
+use crate::soak::{SoakStats, SOAK_TOLERANCE};
 
-use chrono::{DateTime, Datelike, Timelike};
+use chrono::{DateTime, Datelike, Duration, Timelike, Utc};
 use chrono_tz::Tz;
-use serenity::model::id::UserId;
+use serde::Serialize;
+use serenity::model::{
+    id::{ChannelId, GuildId, RoleId, UserId},
+    mention::Mentionable,
+};
+
+mod english;
+mod json;
+
+pub use english::English;
 
-#[derive(Clone, Debug)]
+/// Serialized for machine consumers as `{"kind": ..., "data": ...}`, with the times in RFC 3339
+/// and the durations in seconds. See [`Message::to_json`].
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "kind", content = "data", rename_all = "snake_case")]
 pub enum Message {
     Help,
     Scheduled {
         calculated_time: CalculatedDateTime,
         kaisanee: KaisaneeSpecifier,
+        /// Number of the target users currently in the voice channel.
+        head_count: usize,
+        /// Randomly chosen time, if it is revealed in the channel.
+        revealed_time: Option<DateTime<Tz>>,
+        /// Words skipped in the list of the targets.
+        ignored_tokens: Vec<String>,
+    },
+    RevealedTime(DateTime<Tz>),
+    /// Shown once when a guild schedules a kaisan without setting the timezone
+    DefaultTimezone(Tz),
+    TimeZoneRegionMenu,
+    TimeZoneCityMenu(#[serde(serialize_with = "json::display")] TimeZoneRegion),
+    TimeZoneChosen(Tz),
+    Preview {
+        time: Option<DateTime<Tz>>,
+        is_random: bool,
+        kaisanee: KaisaneeSpecifier,
+        target_users: Vec<UserId>,
     },
     Kaisan(Vec<UserId>),
-    Remind(Vec<UserId>, Reminder),
+    /// Posted after a kaisan, with how long the call lasted if it is known.
+    KaisanSummary {
+        count: usize,
+        #[serde(serialize_with = "json::option_seconds")]
+        session: Option<Duration>,
+    },
+    /// The kaisan was not carried out since it was too late.
+    KaisanSkipped {
+        time: DateTime<Tz>,
+        #[serde(serialize_with = "json::seconds")]
+        late: Duration,
+    },
+    AutoKaisan(UserId),
+    Remind {
+        users: Vec<UserId>,
+        reminder: Reminder,
+        time: DateTime<Tz>,
+        template: Option<ReminderTemplate>,
+    },
     Setting {
         requires_permission: bool,
+        requires_permission_self: bool,
         timezone: Tz,
         reminders: HashSet<Reminder>,
         reminds_random_kaisan: bool,
+        auto_kaisan_hour: Option<Hour>,
+        max_horizon_hours: u8,
+        tonight_hour: Hour,
+        max_schedules_per_user: u8,
+        undo_window_minutes: u8,
+        countdown: bool,
+        author_leave_policy: AuthorLeavePolicy,
+        reveal_random: RevealRandom,
+        random_distribution: RandomDistribution,
+        dst_policy: DstPolicy,
+        week_start: WeekStart,
+        locale: Locale,
+        on_duplicate: DuplicatePolicy,
+        allowed_channels: HashSet<ChannelId>,
+        allowed_roles: HashSet<RoleId>,
+        command_prefix: Option<String>,
+        reminder_text: Option<ReminderTemplate>,
+    },
+    ExportedSetting,
+    Profiles(Vec<String>),
+    Reminders(Vec<Reminder>),
+    AllowedRoles(Vec<RoleId>),
+    ChannelSetting {
+        voice_channel_id: ChannelId,
+        overrides: ChannelOverrides,
+    },
+    LastDisconnect {
+        requester: UserId,
+        time: DateTime<Tz>,
+    },
+    NoDisconnectRecord,
+    NextKaisan {
+        id: ScheduleId,
+        #[serde(serialize_with = "json::seconds")]
+        remaining: Duration,
+        time: DateTime<Tz>,
+        is_random: bool,
+    },
+    NoPendingKaisan,
+    AbortedAll(usize),
+    CancelledMine(usize),
+    /// The schedule cancelled by `undo`
+    Undone(ScheduleId),
+    /// The number of schedules frozen by `pause`
+    Paused(usize),
+    /// The number of schedules started again by `resume`
+    Resumed(usize),
+    /// Schedules cancelled in favor of a new one under [`DuplicatePolicy::Replace`]
+    ReplacedSchedules(Vec<ScheduleId>),
+    Countdown(i64),
+    AuthorLeft {
+        author: UserId,
+        id: ScheduleId,
+        policy: AuthorLeavePolicy,
     },
-    HandleError(Error),
-    KaisanError(Error),
-    RemindError(Error),
+    Complained {
+        requester: UserId,
+        count: u64,
+    },
+    AlreadyComplained,
+    NoComplaintTarget,
+    ComplaintRanking(Vec<(UserId, u64)>),
+    ApiTokenGenerated(#[serde(serialize_with = "json::redacted")] ApiToken),
+    /// Sent to the author by DM, with the link to cancel the schedule without Discord.
+    CancelLink {
+        id: ScheduleId,
+        #[serde(serialize_with = "json::redacted")]
+        url: String,
+    },
+    CancelledByLink(ScheduleId),
+    WeeklyStats {
+        #[serde(serialize_with = "json::user_seconds")]
+        voice_times: Vec<(UserId, Duration)>,
+        /// How many times each user has been disconnected, the most first
+        kaisaned: Vec<(UserId, u64)>,
+    },
+    ParseHint(ParseHint),
+    PendingSchedules {
+        now: DateTime<Utc>,
+        schedules: Vec<(GuildId, ScheduleId, Schedule)>,
+    },
+    Broadcasted {
+        delivered: usize,
+        /// Guilds without a channel to post in
+        skipped: usize,
+        failed: usize,
+    },
+    BotStats {
+        guilds: usize,
+        schedules: usize,
+        /// Guilds with any pending schedule
+        scheduling_guilds: usize,
+        overdue_schedules: usize,
+        #[serde(serialize_with = "json::dispatch_stats")]
+        dispatch: DispatchStats,
+        retries: RetryStats,
+        cache: CacheStats,
+        #[serde(serialize_with = "json::soak_stats")]
+        soak: SoakStats,
+    },
+    Health(#[serde(serialize_with = "json::health")] Health),
+    StorageUsage {
+        guild_id: GuildId,
+        keys: usize,
+        bytes: u64,
+        /// Number of disconnections kept for the guild
+        event_capacity: usize,
+    },
+    /// Said by the bot along with the reaction, as set by the guild
+    Phrase(String),
+    /// Mirrored to the log channel of the guild
+    Log(LogEntry),
+    HandleError(#[serde(serialize_with = "json::display")] Error),
+    KaisanError(#[serde(serialize_with = "json::display")] Error),
+    RemindError(#[serde(serialize_with = "json::display")] Error),
 }
 
+/// Schedules listed at most in [`Message::PendingSchedules`], to fit in a Discord message.
+const MAX_LISTED_SCHEDULES: usize = 20;
+
 const HELP_MESSAGE: &str = "メンションか `!kaisan` でコマンドが実行できます。
 
 ・`!kaisan help`: ヘルプ
 
 **解散コマンド** 省略された場合、`TARGET` は全員になります
 ・`!kaisan [TARGET] at TIME`: `TARGET` を `TIME` に解散する
-・`!kaisan [TARGET] after DURATION`: `TARGET` を `DURATION` 後に解散する
+・`!kaisan [TARGET] after DURATION`: `TARGET` を `DURATION` 後に解散する（`in DURATION` や `10min` だけでもよい）
 ・`!kaisan [TARGET] by TIME`: `TARGET` を `TIME` までのランダムな時間に解散する
 ・`!kaisan [TARGET] within DURATION`: `TARGET` を `DURATION` 後までのランダムな時間に解散する
+・`!kaisan ... 私にだけ`: リマインドを対象全員ではなく自分にだけ送る
+・`!kaisan me at 23:00, all at 23:30`: `,` や `、` で区切って複数の解散を一度に予約する
+・`TARGET` には `田中` や `@tanaka` のように名前も使える
+・`TARGET` に `この部屋` / `this channel` を指定すると、解散時に予約した人がいる通話の全員を解散する（`@someone以外のこの部屋` / `this channel except @someone` で除外できる）
+・全員の解散を予約したメッセージに 🙅 でリアクションすると、その解散から外れる
+・解散を予約したメッセージに ✋ でリアクションすると、その解散の対象に加わる
+・`!kaisan preview ...`: 解散コマンドを実行せず、解散される時刻と現在の対象を表示する
+・`!kaisan list-reminders`: 設定されているリマインドの一覧を表示する
+・`!kaisan cancel mine`: 自分が予約した解散をすべて取り消す
+・`!kaisan undo`: 直前に予約した解散を取り消す
+・`!kaisan when`: いる通話の次の解散までの時間と番号を表示する
+・`!kaisan now ID`: 番号 `ID` の解散を今すぐ実行する
+・`!kaisan who-kicked-me`: 最後に自分を解散させた人と時刻を表示する
+・`!kaisan complain`: 直近 1 時間以内に自分を解散させた人に文句を言う
+・`!kaisan complaints`: 文句を言われた回数のランキングを表示する
+・`!kaisan stats`: 今週の通話時間と解散された回数のランキングを表示する
+・`!kaisan ping`: Discord とデータベースの応答時間を表示する
 ・その他さまざまな糖衣構文
 
 *解散コマンド例*
@@ -44,14 +241,50 @@ const HELP_MESSAGE: &str = "メンションか `!kaisan` でコマンドが実
 ・`!kaisan me after 10min`
 ・`明日の一時 @解散担当大臣`
 ・`!kaisan @someone at 10:30`
+・`!kaisan 金曜の21時` `!kaisan at 12/24 22:00`
+・`!kaisan 明後日の正午` `!kaisan 今夜` `!kaisan by midnight`
 
 **設定コマンド** 設定には Manage Guild 権限が必要です
 ・`!kaisan show-setting`: 設定表示
-・`!kaisan timezone TIMEZONE`: タイムゾーンを設定
+・`!kaisan timezone TIMEZONE`: タイムゾーンを設定（`TIMEZONE` を省略すると一覧から選べる）
 ・`!kaisan require-permission BOOLEAN`: 他人を解散するのに Move Members 権限を必要とするか設定
-・`!kaisan add-reminder N`: 解散の `N` 分前にリマインドを設定
-・`!kaisan remove-reminder N`: 解散の `N` 分前のリマインドを削除
+・`!kaisan require-permission-self BOOLEAN`: 自分だけを解散するのにも Move Members 権限を必要とするか設定
+・`!kaisan add-reminder DURATION`: 解散の `DURATION` 前にリマインドを設定（`30s` `1h` など、単位を省略すると分）
+・`!kaisan remove-reminder DURATION`: 解散の `DURATION` 前のリマインドを削除
+・`!kaisan clear-reminders`: リマインドをすべて削除
 ・`!kaisan remind-random BOOLEAN`: 解散時刻がランダムな場合にもリマインダを使うかどうか設定
+・`!kaisan reveal-random (off|channel|dm)`: ランダムに決まった解散時刻を知らせるかどうか、知らせる場合はチャンネルか予約した人への DM か設定
+・`!kaisan random-distribution (uniform|late-biased|early-biased)`: ランダムな解散時刻を一様に決めるか、期限の近くや早めに偏らせるか設定
+・`!kaisan countdown BOOLEAN`: 解散前の最後の1分間にカウントダウンするかどうか設定
+・`!kaisan author-leave (keep|reroll|cancel)`: ランダムな解散の予約者が先に通話を抜けたとき、そのまま解散するか、解散時刻を引き直すか、取り消すか設定
+・`!kaisan dst (earliest|latest|reject)`: 夏時間の切り替えで重複する時刻を早い方と遅い方のどちらにするか、またはエラーにするか設定（存在しない時刻は直後に繰り下げる）
+・`!kaisan week-start (monday|sunday)`: 「来週」や `next` で数える週を月曜始まりにするか日曜始まりにするか設定
+・`!kaisan locale (ja|en|both)`: メッセージを日本語、英語、または日本語と英語の両方で送るか設定
+・`!kaisan on-duplicate (stack|replace|reject)`: 同じボイスチャンネルで解散を重ねて予約したとき、両方残すか、前の予約を取り消すか、新しい予約を断るか設定
+・`!kaisan tonight HOUR`: 「今夜」や `tonight` で解散する時刻を `HOUR` 時にする
+・`!kaisan max-horizon N`: `N` 時間より先の解散を予約できないようにする
+・`!kaisan max-schedules N`: 一人が同時に予約できる解散を `N` 件までにする（管理者を除く）
+・`!kaisan undo-window N`: `undo` で取り消せるのを予約から `N` 分以内にする（`off` で無効）
+・`!kaisan reminder-text TEXT`: リマインドの文面を変える（`{remaining}` `{targets}` `{time}` が使える、`default` で元に戻す）
+・`!kaisan auto-kaisan HOUR`: `HOUR` 時以降、通話に一人だけ残った人を自動で解散する（`off` で無効）
+・`!kaisan allow-channel [CHANNEL]`: コマンドを使えるチャンネルに `CHANNEL`（省略するとこのチャンネル）を加える
+・`!kaisan deny-channel [CHANNEL]`: コマンドを使えるチャンネルから `CHANNEL` を外す（一つもなければすべてのチャンネルで使える）
+・`!kaisan channel-setting VOICE_CHANNEL [SETTING VALUE]`: `VOICE_CHANNEL` でだけ `max-horizon`、`countdown`、`author-leave`、`on-duplicate` の設定を上書きする（`default` で元に戻す）
+・`!kaisan allow-role ROLE`: `ROLE` のメンバーが Move Members 権限なしで他人を解散させられるようにする
+・`!kaisan deny-role ROLE`: `allow-role` で加えた `ROLE` を外す
+・`!kaisan list-roles`: 他人を解散させられるロールの一覧を表示する
+・`!kaisan prefix PREFIX`: このサーバーでのコマンドの接頭辞を `PREFIX` にする（後ろには空白を空ける、メンションはいつでも使える、`default` で元に戻す）
+・`!kaisan export-setting`: 設定を JSON ファイルとして書き出す
+・`!kaisan import-setting`: 添付した JSON ファイルの設定を読み込む
+・`!kaisan profile save NAME`: 現在の設定をプロファイル `NAME` として保存する（`load` で切り替え、`list` で一覧。`export-setting` と同じ項目で、チャンネルごとの設定やリアクションなどは含まない）
+・`!kaisan api-token generate`: このサーバー用の API トークンを発行して DM で送る（`revoke` で無効にする）
+・`!kaisan webhook URL`: 予約・リマインド・解散を JSON で `URL` に送る（`off` でやめる）
+・`!kaisan log-channel CHANNEL`: 予約・解散・取り消し・失敗を `CHANNEL` に記録する（`off` でやめる）
+・`!kaisan reaction success|failure EMOJI`: 成功・失敗を知らせるリアクションを変える（`default` で戻す）
+・`!kaisan phrase success|failure TEXT`: 成功・失敗のリアクションと一緒にひとこと言う（`off` でやめる）
+・`!kaisan abort-all`: サーバーで予定されているすべての解散を取り消す
+・`!kaisan pause`: `resume` までコマンドを受け付けず、予定されている解散を残り時間ごと止めておく
+・`!kaisan resume`: 一時停止を解除し、止めていた解散を残り時間のまま再開する
 ";
 
 impl Say for Message {
@@ -61,25 +294,149 @@ impl Say for Message {
             Message::Scheduled {
                 calculated_time,
                 kaisanee,
-            } => say!(f, "{}に{}を解散します", calculated_time, kaisanee),
+                head_count,
+                revealed_time,
+                ignored_tokens,
+            } => {
+                say!(
+                    f,
+                    "{}に{}を解散します（現在{}人）",
+                    calculated_time,
+                    kaisanee,
+                    head_count.say_display()
+                )?;
+                if let Some(time) = revealed_time {
+                    say!(
+                        f,
+                        "\n抽選の結果、{}に解散します",
+                        time.format("%H:%M:%S").say_display()
+                    )?;
+                }
+                if !ignored_tokens.is_empty() {
+                    say!(
+                        f,
+                        "\n{} はユーザーとして読めなかったので無視しました",
+                        ignored_tokens
+                            .iter()
+                            .map(|token| format!("`{}`", token))
+                            .say_joined(" ")
+                    )?;
+                }
+                Ok(())
+            }
+            Message::RevealedTime(time) => say!(
+                f,
+                "抽選の結果、{} ({}) に解散します",
+                time.format("%Y/%m/%d %H:%M:%S").say_display(),
+                time.timezone()
+            ),
+            Message::DefaultTimezone(tz) => say!(
+                f,
+                "タイムゾーンは {} として解釈しています。変更は `timezone` で",
+                tz
+            ),
+            Message::TimeZoneRegionMenu => f.write_str("タイムゾーンの地域を選んでください"),
+            Message::TimeZoneCityMenu(region) => {
+                say!(f, "{} の都市を選んでください", region.name())
+            }
+            Message::TimeZoneChosen(tz) => say!(f, "タイムゾーンを {} に設定しました", tz),
+            Message::Preview {
+                time,
+                is_random,
+                kaisanee,
+                target_users,
+            } => {
+                f.write_str("[プレビュー] ")?;
+                match time {
+                    None => f.write_str("今すぐ")?,
+                    Some(time) => {
+                        say!(
+                            f,
+                            "{} ({})",
+                            time.format("%Y/%m/%d %H:%M:%S").say_display(),
+                            time.timezone()
+                        )?;
+                        if *is_random {
+                            f.write_str("までのランダムな時刻")?;
+                        }
+                        f.write_str("に")?;
+                    }
+                }
+                say!(
+                    f,
+                    "{}を解散します（現在の対象: {}）",
+                    kaisanee,
+                    target_users.say_mentions_ref().with_alternative("なし")
+                )
+            }
             Message::Kaisan(ids) => say!(f, "{} 解散！", ids.say_mentions_ref()),
-            Message::Remind(ids, reminder) => say!(
+            Message::KaisanSummary { count, session } => {
+                say!(f, "{}人を解散しました", count.say_display())?;
+                if let Some(session) = session {
+                    say!(f, "（通話時間 {}）", session)?;
+                }
+                Ok(())
+            }
+            Message::KaisanSkipped { time, late } => say!(
                 f,
-                "{} あと{}で解散です",
-                ids.say_mentions_ref(),
-                reminder.before_duration()
+                "{} に予定していた解散は{}遅れてしまったので取りやめました",
+                time.format("%m/%d %H:%M").say_display(),
+                late
             ),
+            Message::AutoKaisan(id) => {
+                say!(f, "{} 一人になったので解散！", id.mention().say_display())
+            }
+            Message::Remind {
+                users,
+                reminder,
+                time,
+                template,
+            } => match template {
+                Some(template) => Say::fmt(
+                    &template.render(reminder.before_duration(), users, *time),
+                    f,
+                ),
+                None => say!(
+                    f,
+                    "{} あと{}で解散です",
+                    users.say_mentions_ref(),
+                    reminder.before_duration()
+                ),
+            },
             Message::Setting {
                 requires_permission,
+                requires_permission_self,
                 timezone,
                 reminders,
                 reminds_random_kaisan,
+                auto_kaisan_hour,
+                max_horizon_hours,
+                tonight_hour,
+                max_schedules_per_user,
+                undo_window_minutes,
+                countdown,
+                author_leave_policy,
+                reveal_random,
+                random_distribution,
+                dst_policy,
+                week_start,
+                locale,
+                on_duplicate,
+                allowed_channels,
+                allowed_roles,
+                command_prefix,
+                reminder_text,
             } => {
                 sayln!(
                     f,
                     "他人を解散させるのに権限を必要とする: {}",
                     requires_permission
                 )?;
+                sayln!(
+                    f,
+                    "自分だけを解散させるのにも権限を必要とする: {}",
+                    requires_permission_self
+                )?;
                 sayln!(f, "タイムゾーン: {}", timezone)?;
                 sayln!(
                     f,
@@ -93,24 +450,529 @@ impl Say for Message {
                     "解散時刻がランダムな場合にもリマインダを使う: {}",
                     reminds_random_kaisan
                 )?;
+                match auto_kaisan_hour {
+                    None => f.write_str("自動解散: 無効\n")?,
+                    Some(hour) => writeln!(
+                        f,
+                        "自動解散: {}時以降、通話に一人だけ残ったら解散",
+                        hour.as_u32()
+                    )?,
+                }
+                writeln!(f, "解散を予約できる最大時間: {}時間", max_horizon_hours)?;
+                writeln!(f, "「今夜」の時刻: {}時", tonight_hour.as_u32())?;
+                writeln!(
+                    f,
+                    "一人が同時に予約できる解散の数: {}件（管理者を除く）",
+                    max_schedules_per_user
+                )?;
+                if *undo_window_minutes == 0 {
+                    f.write_str("予約した解散を undo で取り消せる時間: 無効\n")?;
+                } else {
+                    writeln!(
+                        f,
+                        "予約した解散を undo で取り消せる時間: {}分",
+                        undo_window_minutes
+                    )?;
+                }
+                sayln!(f, "解散前の最後の1分間にカウントダウンする: {}", countdown)?;
+                sayln!(
+                    f,
+                    "ランダムな解散の予約者が先に抜けたとき: {}",
+                    author_leave_policy
+                )?;
+                sayln!(f, "ランダムに決まった解散時刻: {}", reveal_random)?;
+                sayln!(f, "ランダムな解散時刻の分布: {}", random_distribution)?;
+                sayln!(f, "夏時間の切り替えで重複・欠落する時刻: {}", dst_policy)?;
+                sayln!(f, "週の始まり: {}", week_start)?;
+                sayln!(f, "言語: {}", locale)?;
+                sayln!(f, "同じボイスチャンネルで解散を重ねて予約したとき: {}", on_duplicate)?;
+                sayln!(
+                    f,
+                    "コマンドを使えるチャンネル: {}",
+                    allowed_channels
+                        .say_mentions_ref()
+                        .with_alternative("すべて")
+                )?;
+                sayln!(
+                    f,
+                    "他人を解散させられるロール: {}",
+                    allowed_roles.say_mentions_ref().with_alternative("なし")
+                )?;
+                match command_prefix {
+                    None => f.write_str("コマンドの接頭辞: デフォルト\n")?,
+                    Some(prefix) => writeln!(f, "コマンドの接頭辞: `{}`", prefix)?,
+                }
+                match reminder_text {
+                    None => f.write_str("リマインド文: デフォルト\n")?,
+                    Some(template) => writeln!(f, "リマインド文: {}", template.as_str())?,
+                }
 
                 Ok(())
             }
+            Message::ExportedSetting => f.write_str("現在の設定です"),
+            Message::Profiles(names) if names.is_empty() => {
+                f.write_str("保存されたプロファイルはありません（`profile save` で保存できます）")
+            }
+            Message::Profiles(names) => write!(f, "プロファイル: {}", names.join("、")),
+            Message::Reminders(reminders) if reminders.is_empty() => {
+                f.write_str("リマインダは設定されていません")
+            }
+            Message::AllowedRoles(roles) if roles.is_empty() => f.write_str(
+                "他人を解散させられるロールはありません（`require-permission` と Move Members 権限に従います）",
+            ),
+            Message::AllowedRoles(roles) => say!(
+                f,
+                "他人を解散させられるロール: {}",
+                roles.say_mentions_ref()
+            ),
+            Message::ChannelSetting {
+                voice_channel_id,
+                overrides,
+            } if overrides.is_empty() => say!(
+                f,
+                "{} で上書きされている設定はありません（サーバーの設定に従います）",
+                voice_channel_id.mention().say_display()
+            ),
+            Message::ChannelSetting {
+                voice_channel_id,
+                overrides,
+            } => {
+                sayln!(
+                    f,
+                    "{} で上書きされている設定（ほかはサーバーの設定に従います）:",
+                    voice_channel_id.mention().say_display()
+                )?;
+                if let Some(max_horizon_hours) = overrides.max_horizon_hours {
+                    writeln!(f, "解散を予約できる最大時間: {}時間", max_horizon_hours)?;
+                }
+                if let Some(countdown) = overrides.countdown {
+                    sayln!(f, "解散前の最後の1分間にカウントダウンする: {}", countdown)?;
+                }
+                if let Some(policy) = overrides.author_leave_policy {
+                    sayln!(f, "ランダムな解散の予約者が先に抜けたとき: {}", policy)?;
+                }
+                if let Some(policy) = overrides.on_duplicate {
+                    sayln!(f, "同じボイスチャンネルで解散を重ねて予約したとき: {}", policy)?;
+                }
+                Ok(())
+            }
+            Message::Reminders(reminders) => {
+                for reminder in reminders {
+                    sayln!(f, "・{}", reminder)?;
+                }
+                Ok(())
+            }
+            Message::LastDisconnect { requester, time } => say!(
+                f,
+                "{} ({}) に {} があなたを解散しました",
+                time.format("%Y/%m/%d %H:%M:%S").say_display(),
+                time.timezone(),
+                requester.mention().say_display()
+            ),
+            Message::NoDisconnectRecord => f.write_str("あなたが解散された記録はありません"),
+            Message::NextKaisan {
+                id,
+                remaining,
+                time,
+                is_random: false,
+            } => say!(
+                f,
+                "あと{}、{}に解散予定（{}）",
+                remaining,
+                time.format("%H:%M").say_display(),
+                id
+            ),
+            Message::NextKaisan {
+                id,
+                remaining,
+                time,
+                is_random: true,
+            } => say!(
+                f,
+                "あと{}以内、{}までのどこかで解散予定（{}）",
+                remaining,
+                time.format("%H:%M").say_display(),
+                id
+            ),
+            Message::NoPendingKaisan => f.write_str("この通話に予定されている解散はありません"),
+            Message::AbortedAll(0) | Message::CancelledMine(0) => {
+                f.write_str("取り消す解散はありません")
+            }
+            Message::AuthorLeft {
+                author,
+                id,
+                policy: AuthorLeavePolicy::Reroll,
+            } => say!(
+                f,
+                "{} が通話を抜けたので {} の解散時刻を引き直しました",
+                author.mention().say_display(),
+                id
+            ),
+            Message::AuthorLeft { author, id, .. } => say!(
+                f,
+                "{} が通話を抜けたので {} の解散を取り消しました",
+                author.mention().say_display(),
+                id
+            ),
+            Message::Countdown(seconds) => write!(f, "解散まであと{}秒", seconds),
+            Message::CancelledMine(count) => {
+                write!(f, "あなたが予約した解散 {} 件を取り消しました", count)
+            }
+            Message::Undone(id) => say!(f, "{} の解散を取り消しました", id),
+            Message::Paused(count) => write!(
+                f,
+                "一時停止しました（予定されていた解散 {} 件は `resume` まで止めておきます）",
+                count
+            ),
+            Message::Resumed(count) => {
+                write!(f, "再開しました（止めていた解散 {} 件を再開しました）", count)
+            }
+            Message::ReplacedSchedules(ids) => say!(
+                f,
+                "前に予約した解散 {} は取り消しました",
+                ids.say_joined("、")
+            ),
+            Message::AbortedAll(count) => {
+                write!(f, "予定されていた解散 {} 件をすべて取り消しました", count)
+            }
+            Message::Complained { requester, count } => say!(
+                f,
+                "{} への文句を受け付けました（累計 {} 件）",
+                requester.mention().say_display(),
+                count.say_display()
+            ),
+            Message::AlreadyComplained => f.write_str("その解散にはもう文句を言っています"),
+            Message::NoComplaintTarget => f.write_str("文句を言える解散がありません"),
+            Message::ComplaintRanking(ranking) => {
+                if ranking.is_empty() {
+                    return f.write_str("まだ文句は寄せられていません");
+                }
+                f.write_str("文句ランキング")?;
+                for (i, (user_id, count)) in ranking.iter().enumerate() {
+                    say!(
+                        f,
+                        "\n{}. {}: {} 件",
+                        (i + 1).say_display(),
+                        user_id.mention().say_display(),
+                        count.say_display()
+                    )?;
+                }
+                Ok(())
+            }
+            // <> keeps Discord from opening the link to make a preview
+            Message::CancelLink { id, url } => say!(
+                f,
+                "{} の解散を予約しました。Discord を閉じたあとでも、このリンクから取り消せます\n<{}>",
+                id,
+                url
+            ),
+            Message::CancelledByLink(id) => say!(f, "{} の解散がリンクから取り消されました", id),
+            Message::ApiTokenGenerated(token) => write!(
+                f,
+                "このサーバーの API トークンです。他の人には見せないでください（前のトークンはもう使えません）\n`{}`",
+                token.as_str()
+            ),
+            Message::WeeklyStats {
+                voice_times,
+                kaisaned,
+            } => {
+                if voice_times.is_empty() && kaisaned.is_empty() {
+                    return f.write_str("今週はまだ記録がありません");
+                }
+                f.write_str("今週の通話時間")?;
+                for (user_id, duration) in voice_times {
+                    say!(f, "\n・{}: {}", user_id.mention().say_display(), duration)?;
+                }
+                f.write_str("\n\n夜更かしランキング")?;
+                for (i, (user_id, count)) in kaisaned.iter().enumerate() {
+                    say!(
+                        f,
+                        "\n{}. {}: {} 回解散",
+                        (i + 1).say_display(),
+                        user_id.mention().say_display(),
+                        count.say_display()
+                    )?;
+                }
+                Ok(())
+            }
+            Message::ParseHint(ParseHint {
+                corrected,
+                examples,
+            }) => {
+                f.write_str("コマンドがわからない")?;
+                if let Some(corrected) = corrected {
+                    write!(f, "（もしかして `{}`？）", corrected)?;
+                }
+                if !examples.is_empty() {
+                    f.write_str("\n使い方の例:")?;
+                    for example in examples {
+                        write!(f, " `{}`", example)?;
+                    }
+                }
+                Ok(())
+            }
+            Message::PendingSchedules { now, schedules } => {
+                let guilds: HashSet<_> = schedules.iter().map(|(g, _, _)| g).collect();
+                let overdue = schedules
+                    .iter()
+                    .filter(|(_, _, s)| s.is_overdue(*now))
+                    .count();
+                write!(
+                    f,
+                    "予定されている解散: {} 件（{} サーバー）、うち予定時刻を過ぎたもの {} 件",
+                    schedules.len(),
+                    guilds.len(),
+                    overdue
+                )?;
+                for (guild_id, id, schedule) in schedules.iter().take(MAX_LISTED_SCHEDULES) {
+                    say!(
+                        f,
+                        "\n・{} {} {} UTC",
+                        guild_id.say_display(),
+                        id,
+                        schedule.time.format("%Y/%m/%d %H:%M:%S").say_display()
+                    )?;
+                    if schedule.random_until.is_some() {
+                        f.write_str("（ランダム）")?;
+                    }
+                    if schedule.is_overdue(*now) {
+                        f.write_str(" ⚠ 時刻超過")?;
+                    }
+                }
+                if let Some(rest) = schedules.len().checked_sub(MAX_LISTED_SCHEDULES) {
+                    if rest > 0 {
+                        write!(f, "\n…ほか {} 件", rest)?;
+                    }
+                }
+                Ok(())
+            }
+            Message::Broadcasted {
+                delivered,
+                skipped,
+                failed,
+            } => write!(
+                f,
+                "{} サーバーに送信しました（送り先のチャンネルがない {} サーバー、失敗 {} サーバー）",
+                delivered, skipped, failed
+            ),
+            Message::BotStats {
+                guilds,
+                schedules,
+                scheduling_guilds,
+                overdue_schedules,
+                dispatch,
+                retries,
+                cache,
+                soak,
+            } => {
+                writeln!(f, "サーバー数: {}", guilds)?;
+                writeln!(
+                    f,
+                    "予定されている解散: {} 件（{} サーバー）",
+                    schedules, scheduling_guilds
+                )?;
+                writeln!(f, "予定時刻を過ぎたもの: {} 件", overdue_schedules)?;
+                write!(
+                    f,
+                    "実行待ち: {} 件（これまで {} 件、待ち時間は平均 {} ミリ秒、最大 {} ミリ秒）",
+                    dispatch.waiting,
+                    dispatch.dispatched,
+                    dispatch.mean_wait.as_millis(),
+                    dispatch.max_wait.as_millis()
+                )?;
+                write!(
+                    f,
+                    "\nデータベースの再試行: {} 回（再試行で成功したもの {} 件、諦めたもの {} 件）",
+                    retries.retried, retries.recovered, retries.gave_up
+                )?;
+                write!(
+                    f,
+                    "\n設定のキャッシュ: ヒット {} 回、ミス {} 回",
+                    cache.hits, cache.misses
+                )?;
+                if soak.interval_minutes.is_none() && soak.fired == 0 {
+                    return Ok(());
+                }
+                match soak.interval_minutes {
+                    Some(minutes) => write!(f, "\nソーク: {} 分ごと", minutes)?,
+                    None => f.write_str("\nソーク: 停止中")?,
+                }
+                write!(
+                    f,
+                    "（これまで {} 回、時刻から {} 秒以上ずれたもの {} 回、ずれは平均 {} ミリ秒、最大 {} ミリ秒）",
+                    soak.fired,
+                    SOAK_TOLERANCE.as_secs(),
+                    soak.off_time,
+                    soak.mean_drift.as_millis(),
+                    soak.max_drift.as_millis()
+                )
+            }
+            Message::Health(health) => {
+                say!(f, "Discord API: {}\n", health.rest)?;
+                say!(f, "ゲートウェイ: {}\n", health.gateway)?;
+                say!(f, "データベース: {}", health.database)
+            }
+            Message::StorageUsage {
+                guild_id,
+                keys,
+                bytes,
+                event_capacity,
+            } => {
+                writeln!(f, "サーバー {} のデータ", guild_id)?;
+                writeln!(f, "キー: {} 個、およそ {} バイト", keys, bytes)?;
+                write!(f, "切断の記録は最新の {} 件まで残る", event_capacity)
+            }
+            Message::Phrase(phrase) => f.write_str(phrase),
             Message::HandleError(e) => Say::fmt(e, f),
+            Message::Log(entry) => Say::fmt(entry, f),
             Message::KaisanError(e) => say!(f, "解散できませんでした: {}", e),
             Message::RemindError(e) => say!(f, "リマインドできませんでした: {}", e),
         }
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+impl Message {
+    /// Renders the message in the given locale. `Both` puts English after Japanese unless
+    /// they are identical, as with user-written reminder text.
+    pub fn render(&self, locale: Locale) -> String {
+        match locale {
+            Locale::Japanese => self.display_say().to_string(),
+            Locale::English => English(self).to_string(),
+            Locale::Both => {
+                let japanese = self.display_say().to_string();
+                let english = English(self).to_string();
+                if japanese == english {
+                    japanese
+                } else {
+                    format!("{}\n\n{}", japanese.trim_end(), english)
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
 pub struct CalculatedDateTime {
     pub time: DateTime<Tz>,
     pub now: DateTime<Tz>,
+    /// How the time was written, which is left out of the JSON as it follows the grammar
+    #[serde(skip)]
     pub spec: TimeSpecifier,
     pub is_random: bool,
 }
 
+/// What happened to a schedule, as a line in the log channel.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum LogEntry {
+    Scheduled {
+        schedule_id: ScheduleId,
+        voice_channel_id: ChannelId,
+        author_id: UserId,
+        /// The end of the range for a random kaisan.
+        time: DateTime<Tz>,
+        random: bool,
+    },
+    Kaisan {
+        schedule_id: Option<ScheduleId>,
+        voice_channel_id: ChannelId,
+        users: Vec<UserId>,
+    },
+    Cancelled {
+        schedule_id: ScheduleId,
+        voice_channel_id: ChannelId,
+        /// `None` if the bot cancelled it by itself, such as when the voice channel got empty.
+        by: Option<UserId>,
+    },
+    Failed {
+        schedule_id: ScheduleId,
+        voice_channel_id: ChannelId,
+        #[serde(serialize_with = "json::display")]
+        error: Error,
+    },
+}
+
+impl Say for LogEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LogEntry::Scheduled {
+                schedule_id,
+                voice_channel_id,
+                author_id,
+                time,
+                random,
+            } => say!(
+                f,
+                "[予約] {} {}: {} が {}{}に解散を予約",
+                schedule_id,
+                voice_channel_id.mention().say_display(),
+                author_id.mention().say_display(),
+                time.format("%m/%d %H:%M").say_display(),
+                if *random { "までのどこか" } else { "" }
+            ),
+            LogEntry::Kaisan {
+                schedule_id,
+                voice_channel_id,
+                users,
+            } => {
+                f.write_str("[解散] ")?;
+                if let Some(id) = schedule_id {
+                    say!(f, "{} ", id)?;
+                }
+                say!(
+                    f,
+                    "{}: {} を解散",
+                    voice_channel_id.mention().say_display(),
+                    users.say_mentions_ref()
+                )
+            }
+            LogEntry::Cancelled {
+                schedule_id,
+                voice_channel_id,
+                by: Some(by),
+            } => say!(
+                f,
+                "[取消] {} {}: {} が取り消し",
+                schedule_id,
+                voice_channel_id.mention().say_display(),
+                by.mention().say_display()
+            ),
+            LogEntry::Cancelled {
+                schedule_id,
+                voice_channel_id,
+                by: None,
+            } => say!(
+                f,
+                "[取消] {} {}: 通話に誰もいなくなったので取り消し",
+                schedule_id,
+                voice_channel_id.mention().say_display()
+            ),
+            LogEntry::Failed {
+                schedule_id,
+                voice_channel_id,
+                error,
+            } => say!(
+                f,
+                "[失敗] {} {}: {}",
+                schedule_id,
+                voice_channel_id.mention().say_display(),
+                error
+            ),
+        }
+    }
+}
+
+impl Say for ComponentHealth {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ComponentHealth::Ok(latency) => write!(f, "{} ミリ秒", latency.as_millis()),
+            ComponentHealth::Slow(latency) => write!(f, "{} ミリ秒（遅い）", latency.as_millis()),
+            ComponentHealth::Failing => f.write_str("応答なし"),
+            ComponentHealth::Unknown => f.write_str("不明"),
+        }
+    }
+}
+
 impl Say for CalculatedDateTime {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let CalculatedDateTime {
@@ -0,0 +1,1144 @@
+use std::collections::HashSet;
+use std::fmt::{self, Display};
+
+use super::{CalculatedDateTime, LogEntry, Message, MAX_LISTED_SCHEDULES};
+use crate::error::Error;
+use crate::model::{
+    health::ComponentHealth,
+    hint::ParseHint,
+    kaisanee::KaisaneeSpecifier,
+    setting::{
+        AuthorLeavePolicy, DstPolicy, DuplicatePolicy, Locale, RandomDistribution, RevealRandom,
+        WeekStart,
+    },
+    template::InvalidTemplateError,
+    time::CalculateTimeError,
+};
+use crate::say::{Counted, EnglishDuration, IntoIteratorSayExt, SayExt};
+use crate::soak::SOAK_TOLERANCE;
+
+use chrono::{DateTime, Datelike};
+use chrono_tz::Tz;
+use serenity::model::mention::Mentionable;
+
+const HELP_MESSAGE: &str = "Commands are run with a mention or `!kaisan`.
+
+・`!kaisan help`: show this help
+
+**Kaisan commands** `TARGET` defaults to everyone
+・`!kaisan [TARGET] at TIME`: disconnect `TARGET` at `TIME`
+・`!kaisan [TARGET] after DURATION`: disconnect `TARGET` in `DURATION` (or `in 10 minutes`, `in an hour`, just `10min`)
+・`!kaisan [TARGET] by TIME`: disconnect `TARGET` at a random time until `TIME`
+・`!kaisan [TARGET] within DURATION`: disconnect `TARGET` at a random time within `DURATION`
+・`!kaisan ... 私にだけ`: send reminders only to yourself instead of all the targets
+・`!kaisan me at 23:00, all at 23:30`: schedule several kaisans at once, separated by `,`
+・`TARGET` can also be names like `@tanaka` (or `田中`)
+・`this channel` as `TARGET` disconnects everyone in your voice channel at the time of the kaisan (`this channel except @someone` to exclude)
+・react 🙅 to a message that scheduled a kaisan for everyone to be left out of it
+・react ✋ to a message that scheduled a kaisan to join it
+・`!kaisan preview ...`: show when and who a kaisan command would disconnect without running it
+・`!kaisan list-reminders`: list the reminders
+・`!kaisan cancel mine`: cancel all the kaisans you scheduled
+・`!kaisan undo`: cancel the kaisan you just scheduled
+・`!kaisan when`: show the time and number of the next kaisan in your voice channel
+・`!kaisan now ID`: carry out the kaisan number `ID` right now
+・`!kaisan who-kicked-me`: show who disconnected you last and when
+・`!kaisan complain`: complain to whoever disconnected you within the last hour
+・`!kaisan complaints`: show the ranking of complaints
+・`!kaisan stats`: show the time in voice this week and the ranking of kaisans
+・`!kaisan ping`: show how long Discord and the database take to respond
+
+*Examples*
+・`!kaisan me after 10min`
+・`!kaisan @someone at 10:30`
+・`!kaisan at 12/24 22:00` `!kaisan tonight` `!kaisan by midnight`
+
+**Setting commands** require the Manage Guild permission
+・`!kaisan show-setting`: show the setting
+・`!kaisan timezone TIMEZONE`: set the timezone (choose from a list if `TIMEZONE` is omitted)
+・`!kaisan require-permission BOOLEAN`: whether disconnecting others requires the Move Members permission
+・`!kaisan require-permission-self BOOLEAN`: whether disconnecting only yourself requires the Move Members permission, too
+・`!kaisan add-reminder DURATION`: remind `DURATION` before kaisans (`30s`, `1h`, minutes if no unit)
+・`!kaisan remove-reminder DURATION`: remove the reminder `DURATION` before kaisans
+・`!kaisan clear-reminders`: remove all the reminders
+・`!kaisan remind-random BOOLEAN`: whether to remind of kaisans at random times too
+・`!kaisan reveal-random (off|channel|dm)`: whether to reveal randomly chosen times, in the channel or by DM to the author
+・`!kaisan random-distribution (uniform|late-biased|early-biased)`: draw random times uniformly, or biased toward the deadline or now
+・`!kaisan countdown BOOLEAN`: whether to count down in the last minute before kaisans
+・`!kaisan author-leave (keep|reroll|cancel)`: what to do when the author of a random kaisan leaves first
+・`!kaisan dst (earliest|latest|reject)`: resolve times repeated by DST to the earlier or later one, or reject them (skipped times roll forward)
+・`!kaisan week-start (monday|sunday)`: start weeks on Monday or Sunday for `next` and 来週
+・`!kaisan locale (ja|en|both)`: send messages in Japanese, English, or both
+・`!kaisan on-duplicate (stack|replace|reject)`: when you schedule another kaisan in the same voice channel, keep both, cancel the earlier one, or refuse the new one
+・`!kaisan tonight HOUR`: make `tonight` mean `HOUR` o'clock
+・`!kaisan max-horizon N`: refuse kaisans more than `N` hours ahead
+・`!kaisan max-schedules N`: let each user schedule up to `N` kaisans at once (except admins)
+・`!kaisan undo-window N`: allow `undo` within `N` minutes of scheduling (`off` to disable)
+・`!kaisan reminder-text TEXT`: change the reminder text (`{remaining}` `{targets}` `{time}` are available, `default` to reset)
+・`!kaisan auto-kaisan HOUR`: after `HOUR` o'clock, disconnect whoever is left alone in a voice channel (`off` to disable)
+・`!kaisan allow-channel [CHANNEL]`: allow commands in `CHANNEL` (this channel if omitted)
+・`!kaisan deny-channel [CHANNEL]`: stop allowing commands in `CHANNEL` (all channels are allowed if none is)
+・`!kaisan channel-setting VOICE_CHANNEL [SETTING VALUE]`: override `max-horizon`, `countdown`, `author-leave` or `on-duplicate` only in `VOICE_CHANNEL` (`default` removes the override)
+・`!kaisan allow-role ROLE`: let members of `ROLE` kaisan others without the Move Members permission
+・`!kaisan deny-role ROLE`: remove `ROLE` added with `allow-role`
+・`!kaisan list-roles`: list the roles allowed to kaisan others
+・`!kaisan prefix PREFIX`: use `PREFIX` as the command prefix in this server, followed by a space (mentions always work, `default` to reset)
+・`!kaisan export-setting`: export the setting as a JSON file
+・`!kaisan import-setting`: import the setting from an attached JSON file
+・`!kaisan profile save NAME`: save the setting as the profile `NAME` (`load` to switch to it, `list` to list them; the same items as `export-setting`, without the per-channel settings, reactions and so on)
+・`!kaisan api-token generate`: issue an API token for this server and send it by DM (`revoke` to disable it)
+・`!kaisan webhook URL`: post schedules, reminders and kaisans to `URL` as JSON (`off` to stop)
+・`!kaisan log-channel CHANNEL`: record schedules, kaisans, cancellations and failures in `CHANNEL` (`off` to stop)
+・`!kaisan reaction success|failure EMOJI`: change the reaction for success or failure (`default` to reset)
+・`!kaisan phrase success|failure TEXT`: say a phrase along with the reaction for success or failure (`off` to stop)
+・`!kaisan abort-all`: cancel all the kaisans scheduled in this server
+・`!kaisan pause`: ignore commands until `resume`, holding the scheduled kaisans with the time left
+・`!kaisan resume`: end the pause and restart the held kaisans with the time they had left
+";
+
+/// English rendering of a [`Message`], generated from the same data as the Japanese one.
+pub struct English<'a>(pub &'a Message);
+
+impl Display for English<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            Message::Help => f.write_str(HELP_MESSAGE),
+            Message::Scheduled {
+                calculated_time,
+                kaisanee,
+                head_count,
+                revealed_time,
+                ignored_tokens,
+            } => {
+                write!(
+                    f,
+                    "Disconnecting {} {} ({} in the channel now)",
+                    EnglishKaisanee(kaisanee),
+                    EnglishCalculatedDateTime(calculated_time),
+                    head_count
+                )?;
+                if let Some(time) = revealed_time {
+                    write!(f, "\nThe lottery picked {}", time.format("%H:%M:%S"))?;
+                }
+                if !ignored_tokens.is_empty() {
+                    let tokens: Vec<_> = ignored_tokens
+                        .iter()
+                        .map(|token| format!("`{}`", token))
+                        .collect();
+                    write!(f, "\nIgnored {}, which are not users", tokens.join(" "))?;
+                }
+                Ok(())
+            }
+            Message::RevealedTime(time) => {
+                write!(f, "The lottery picked {}", FullTime(time))
+            }
+            Message::DefaultTimezone(tz) => write!(
+                f,
+                "Times are read in {}. Change it with `timezone`",
+                tz.name()
+            ),
+            Message::TimeZoneRegionMenu => f.write_str("Choose the region of the timezone"),
+            Message::TimeZoneCityMenu(region) => {
+                write!(f, "Choose the city in {}", region.name())
+            }
+            Message::TimeZoneChosen(tz) => write!(f, "Set the timezone to {}", tz.name()),
+            Message::Preview {
+                time,
+                is_random,
+                kaisanee,
+                target_users,
+            } => {
+                write!(f, "[Preview] Disconnecting {} ", EnglishKaisanee(kaisanee))?;
+                match time {
+                    None => f.write_str("right now")?,
+                    Some(time) if *is_random => {
+                        write!(f, "at a random time by {}", FullTime(time))?
+                    }
+                    Some(time) => write!(f, "at {}", FullTime(time))?,
+                }
+                f.write_str(" (current targets: ")?;
+                if target_users.is_empty() {
+                    f.write_str("none")?;
+                } else {
+                    write!(f, "{}", target_users.say_mentions_ref().display_say())?;
+                }
+                f.write_str(")")
+            }
+            Message::Kaisan(ids) => write!(f, "{} Kaisan!", ids.say_mentions_ref().display_say()),
+            Message::KaisanSummary { count, session } => {
+                write!(
+                    f,
+                    "Disconnected {}",
+                    Counted::new(*count as i64, "member", "members")
+                )?;
+                if let Some(session) = session {
+                    write!(f, " after a call of {}", EnglishDuration(*session))?;
+                }
+                Ok(())
+            }
+            Message::KaisanSkipped { time, late } => write!(
+                f,
+                "Skipped the kaisan planned at {}, since it was {} late",
+                FullTime(time),
+                EnglishDuration(*late)
+            ),
+            Message::AutoKaisan(id) => {
+                write!(
+                    f,
+                    "{} Kaisan, since you are the only one left!",
+                    id.mention()
+                )
+            }
+            Message::Remind {
+                users,
+                reminder,
+                time,
+                template,
+            } => match template {
+                Some(template) => write!(
+                    f,
+                    "{}",
+                    template
+                        .render(reminder.before_duration(), users, *time)
+                        .display_say()
+                ),
+                None => write!(
+                    f,
+                    "{} Kaisan in {}",
+                    users.say_mentions_ref().display_say(),
+                    EnglishDuration(reminder.before_duration())
+                ),
+            },
+            Message::Setting {
+                requires_permission,
+                requires_permission_self,
+                timezone,
+                reminders,
+                reminds_random_kaisan,
+                auto_kaisan_hour,
+                max_horizon_hours,
+                tonight_hour,
+                max_schedules_per_user,
+                undo_window_minutes,
+                countdown,
+                author_leave_policy,
+                reveal_random,
+                random_distribution,
+                dst_policy,
+                week_start,
+                locale,
+                on_duplicate,
+                allowed_channels,
+                allowed_roles,
+                command_prefix,
+                reminder_text,
+            } => {
+                writeln!(
+                    f,
+                    "Disconnecting others requires permission: {}",
+                    YesNo(*requires_permission)
+                )?;
+                writeln!(
+                    f,
+                    "Disconnecting only yourself requires permission: {}",
+                    YesNo(*requires_permission_self)
+                )?;
+                writeln!(f, "Timezone: {}", timezone.name())?;
+                f.write_str("Reminders: ")?;
+                if reminders.is_empty() {
+                    f.write_str("none")?;
+                }
+                for (i, reminder) in reminders.iter().enumerate() {
+                    if i != 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{} before", EnglishDuration(reminder.before_duration()))?;
+                }
+                f.write_str("\n")?;
+                writeln!(
+                    f,
+                    "Remind of kaisans at random times: {}",
+                    YesNo(*reminds_random_kaisan)
+                )?;
+                match auto_kaisan_hour {
+                    None => f.write_str("Auto kaisan: disabled\n")?,
+                    Some(hour) => writeln!(
+                        f,
+                        "Auto kaisan: disconnect whoever is left alone after {}:00",
+                        hour.as_u32()
+                    )?,
+                }
+                writeln!(
+                    f,
+                    "Furthest kaisan that can be scheduled: {}",
+                    Counted::new((*max_horizon_hours).into(), "hour", "hours")
+                )?;
+                writeln!(f, "Time of tonight: {}:00", tonight_hour.as_u32())?;
+                writeln!(
+                    f,
+                    "Kaisans each user can schedule at once: {} (except admins)",
+                    max_schedules_per_user
+                )?;
+                if *undo_window_minutes == 0 {
+                    f.write_str("Undo a scheduled kaisan: disabled\n")?;
+                } else {
+                    writeln!(
+                        f,
+                        "Undo a scheduled kaisan within: {}",
+                        Counted::new((*undo_window_minutes).into(), "minute", "minutes")
+                    )?;
+                }
+                writeln!(
+                    f,
+                    "Count down in the last minute before kaisans: {}",
+                    YesNo(*countdown)
+                )?;
+                writeln!(
+                    f,
+                    "When the author of a random kaisan leaves first: {}",
+                    author_leave_policy_str(*author_leave_policy)
+                )?;
+                writeln!(
+                    f,
+                    "Randomly chosen times: {}",
+                    match reveal_random {
+                        RevealRandom::Off => "not revealed",
+                        RevealRandom::Channel => "revealed in the channel",
+                        RevealRandom::DirectMessage => "revealed to the author by DM",
+                    }
+                )?;
+                writeln!(
+                    f,
+                    "Distribution of random times: {}",
+                    match random_distribution {
+                        RandomDistribution::Uniform => "uniform",
+                        RandomDistribution::LateBiased => "biased toward the deadline",
+                        RandomDistribution::EarlyBiased => "biased toward now",
+                    }
+                )?;
+                writeln!(
+                    f,
+                    "Times repeated or skipped by DST: {}",
+                    match dst_policy {
+                        DstPolicy::Earliest => "take the earlier, roll skipped ones forward",
+                        DstPolicy::Latest => "take the later, roll skipped ones forward",
+                        DstPolicy::Reject => "reject",
+                    }
+                )?;
+                writeln!(
+                    f,
+                    "First day of the week: {}",
+                    match week_start {
+                        WeekStart::Monday => "Monday",
+                        WeekStart::Sunday => "Sunday",
+                    }
+                )?;
+                writeln!(
+                    f,
+                    "Language: {}",
+                    match locale {
+                        Locale::Japanese => "Japanese",
+                        Locale::English => "English",
+                        Locale::Both => "Japanese and English",
+                    }
+                )?;
+                writeln!(
+                    f,
+                    "Scheduling another kaisan in the same voice channel: {}",
+                    duplicate_policy_str(*on_duplicate)
+                )?;
+                f.write_str("Channels where commands are allowed: ")?;
+                if allowed_channels.is_empty() {
+                    f.write_str("all\n")?;
+                } else {
+                    writeln!(f, "{}", allowed_channels.say_mentions_ref().display_say())?;
+                }
+                f.write_str("Roles allowed to kaisan others: ")?;
+                if allowed_roles.is_empty() {
+                    f.write_str("none
+")?;
+                } else {
+                    writeln!(f, "{}", allowed_roles.say_mentions_ref().display_say())?;
+                }
+                match command_prefix {
+                    None => f.write_str("Command prefix: default\n")?,
+                    Some(prefix) => writeln!(f, "Command prefix: `{}`", prefix)?,
+                }
+                match reminder_text {
+                    None => f.write_str("Reminder text: default\n")?,
+                    Some(template) => writeln!(f, "Reminder text: {}", template.as_str())?,
+                }
+
+                Ok(())
+            }
+            Message::ExportedSetting => f.write_str("Here is the current setting"),
+            Message::Profiles(names) if names.is_empty() => {
+                f.write_str("No profiles are saved (`profile save` saves one)")
+            }
+            Message::Profiles(names) => write!(f, "Profiles: {}", names.join(", ")),
+            Message::Reminders(reminders) if reminders.is_empty() => {
+                f.write_str("No reminders are set")
+            }
+            Message::AllowedRoles(roles) if roles.is_empty() => f.write_str(
+                "No roles are allowed to kaisan others (`require-permission` and the Move Members permission apply)",
+            ),
+            Message::AllowedRoles(roles) => write!(
+                f,
+                "Roles allowed to kaisan others: {}",
+                roles.say_mentions_ref().display_say()
+            ),
+            Message::ChannelSetting {
+                voice_channel_id,
+                overrides,
+            } if overrides.is_empty() => write!(
+                f,
+                "No settings are overridden in {} (the server settings apply)",
+                voice_channel_id.mention()
+            ),
+            Message::ChannelSetting {
+                voice_channel_id,
+                overrides,
+            } => {
+                writeln!(
+                    f,
+                    "Settings overridden in {} (the server settings apply otherwise):",
+                    voice_channel_id.mention()
+                )?;
+                if let Some(max_horizon_hours) = overrides.max_horizon_hours {
+                    writeln!(
+                        f,
+                        "Furthest kaisan that can be scheduled: {}",
+                        Counted::new(max_horizon_hours.into(), "hour", "hours")
+                    )?;
+                }
+                if let Some(countdown) = overrides.countdown {
+                    writeln!(
+                        f,
+                        "Count down in the last minute before kaisans: {}",
+                        YesNo(countdown)
+                    )?;
+                }
+                if let Some(policy) = overrides.author_leave_policy {
+                    writeln!(
+                        f,
+                        "When the author of a random kaisan leaves first: {}",
+                        author_leave_policy_str(policy)
+                    )?;
+                }
+                if let Some(policy) = overrides.on_duplicate {
+                    writeln!(
+                        f,
+                        "Scheduling another kaisan in the same voice channel: {}",
+                        duplicate_policy_str(policy)
+                    )?;
+                }
+                Ok(())
+            }
+            Message::Reminders(reminders) => {
+                for reminder in reminders {
+                    writeln!(
+                        f,
+                        "・{} before",
+                        EnglishDuration(reminder.before_duration())
+                    )?;
+                }
+                Ok(())
+            }
+            Message::LastDisconnect { requester, time } => write!(
+                f,
+                "{} disconnected you at {}",
+                requester.mention(),
+                FullTime(time)
+            ),
+            Message::NoDisconnectRecord => {
+                f.write_str("There is no record of you being disconnected")
+            }
+            Message::NextKaisan {
+                id,
+                remaining,
+                time,
+                is_random: false,
+            } => write!(
+                f,
+                "Kaisan in {}, at {} ({})",
+                EnglishDuration(*remaining),
+                time.format("%H:%M"),
+                id.display_say()
+            ),
+            Message::NextKaisan {
+                id,
+                remaining,
+                time,
+                is_random: true,
+            } => write!(
+                f,
+                "Kaisan within {}, sometime by {} ({})",
+                EnglishDuration(*remaining),
+                time.format("%H:%M"),
+                id.display_say()
+            ),
+            Message::NoPendingKaisan => f.write_str("No kaisan is scheduled in this voice channel"),
+            Message::AbortedAll(0) | Message::CancelledMine(0) => {
+                f.write_str("There is no kaisan to cancel")
+            }
+            Message::AuthorLeft {
+                author,
+                id,
+                policy: AuthorLeavePolicy::Reroll,
+            } => write!(
+                f,
+                "{} left the voice channel, so the time of {} was redrawn",
+                author.mention(),
+                id.display_say()
+            ),
+            Message::AuthorLeft { author, id, .. } => write!(
+                f,
+                "{} left the voice channel, so {} was cancelled",
+                author.mention(),
+                id.display_say()
+            ),
+            Message::Countdown(seconds) => write!(
+                f,
+                "Kaisan in {}",
+                Counted::new(*seconds, "second", "seconds")
+            ),
+            Message::ReplacedSchedules(ids) => write!(
+                f,
+                "Cancelled {} scheduled earlier in favor of this one",
+                ids.say_joined(", ").display_say()
+            ),
+            Message::Undone(id) => write!(f, "Cancelled kaisan {}", id.display_say()),
+            Message::Paused(count) => write!(
+                f,
+                "Paused, holding {} until `resume`",
+                Counted::new(*count as i64, "scheduled kaisan", "scheduled kaisans")
+            ),
+            Message::Resumed(count) => write!(
+                f,
+                "Resumed, with {} restarted",
+                Counted::new(*count as i64, "kaisan", "kaisans")
+            ),
+            Message::CancelledMine(count) => write!(
+                f,
+                "Cancelled {} you scheduled",
+                Counted::new(*count as i64, "kaisan", "kaisans")
+            ),
+            Message::AbortedAll(count) => write!(
+                f,
+                "Cancelled all the {} scheduled",
+                Counted::new(*count as i64, "kaisan", "kaisans")
+            ),
+            Message::Complained { requester, count } => write!(
+                f,
+                "Your complaint to {} has been received ({} in total)",
+                requester.mention(),
+                count
+            ),
+            Message::AlreadyComplained => {
+                f.write_str("You have already complained about that kaisan")
+            }
+            Message::NoComplaintTarget => f.write_str("There is no kaisan to complain about"),
+            Message::ComplaintRanking(ranking) => {
+                if ranking.is_empty() {
+                    return f.write_str("No complaints yet");
+                }
+                f.write_str("Complaint ranking")?;
+                for (i, (user_id, count)) in ranking.iter().enumerate() {
+                    write!(f, "\n{}. {}: {}", i + 1, user_id.mention(), count)?;
+                }
+                Ok(())
+            }
+            Message::CancelLink { id, url } => write!(
+                f,
+                "Scheduled the kaisan {}. You can cancel it with this link even after closing Discord\n<{}>",
+                id.display_say(),
+                url
+            ),
+            Message::CancelledByLink(id) => write!(
+                f,
+                "The kaisan {} has been cancelled with its link",
+                id.display_say()
+            ),
+            Message::ApiTokenGenerated(token) => write!(
+                f,
+                "Here is the API token of this server. Keep it secret; the previous one no longer works\n`{}`",
+                token.as_str()
+            ),
+            Message::WeeklyStats {
+                voice_times,
+                kaisaned,
+            } => {
+                if voice_times.is_empty() && kaisaned.is_empty() {
+                    return f.write_str("Nothing is recorded this week yet");
+                }
+                f.write_str("Time in voice this week")?;
+                for (user_id, duration) in voice_times {
+                    write!(
+                        f,
+                        "\n・{}: {}",
+                        user_id.mention(),
+                        EnglishDuration(*duration)
+                    )?;
+                }
+                f.write_str("\n\nNight owl ranking")?;
+                for (i, (user_id, count)) in kaisaned.iter().enumerate() {
+                    write!(
+                        f,
+                        "\n{}. {}: disconnected {}",
+                        i + 1,
+                        user_id.mention(),
+                        Counted::new(*count as i64, "time", "times")
+                    )?;
+                }
+                Ok(())
+            }
+            Message::ParseHint(ParseHint {
+                corrected,
+                examples,
+            }) => {
+                f.write_str("I don't understand the command")?;
+                if let Some(corrected) = corrected {
+                    write!(f, " (did you mean `{}`?)", corrected)?;
+                }
+                if !examples.is_empty() {
+                    f.write_str("\nExamples:")?;
+                    for example in examples {
+                        write!(f, " `{}`", example)?;
+                    }
+                }
+                Ok(())
+            }
+            Message::PendingSchedules { now, schedules } => {
+                let guilds: HashSet<_> = schedules.iter().map(|(g, _, _)| g).collect();
+                let overdue = schedules
+                    .iter()
+                    .filter(|(_, _, s)| s.is_overdue(*now))
+                    .count();
+                write!(
+                    f,
+                    "Pending kaisans: {} in {}, {} past their time",
+                    schedules.len(),
+                    Counted::new(guilds.len() as i64, "server", "servers"),
+                    overdue
+                )?;
+                for (guild_id, id, schedule) in schedules.iter().take(MAX_LISTED_SCHEDULES) {
+                    write!(
+                        f,
+                        "\n・{} {} {} UTC",
+                        guild_id,
+                        id.display_say(),
+                        schedule.time.format("%Y/%m/%d %H:%M:%S")
+                    )?;
+                    if schedule.random_until.is_some() {
+                        f.write_str(" (random)")?;
+                    }
+                    if schedule.is_overdue(*now) {
+                        f.write_str(" ⚠ overdue")?;
+                    }
+                }
+                if let Some(rest) = schedules.len().checked_sub(MAX_LISTED_SCHEDULES) {
+                    if rest > 0 {
+                        write!(f, "\n…and {} more", rest)?;
+                    }
+                }
+                Ok(())
+            }
+            Message::Broadcasted {
+                delivered,
+                skipped,
+                failed,
+            } => write!(
+                f,
+                "Sent to {} ({} without a channel to post in, {} failed)",
+                Counted::new(*delivered as i64, "server", "servers"),
+                skipped,
+                failed
+            ),
+            Message::BotStats {
+                guilds,
+                schedules,
+                scheduling_guilds,
+                overdue_schedules,
+                dispatch,
+                retries,
+                cache,
+                soak,
+            } => {
+                writeln!(f, "Servers: {}", guilds)?;
+                writeln!(
+                    f,
+                    "Pending kaisans: {} in {}",
+                    schedules,
+                    Counted::new(*scheduling_guilds as i64, "server", "servers")
+                )?;
+                writeln!(f, "Past their time: {}", overdue_schedules)?;
+                write!(
+                    f,
+                    "Waiting to run: {} ({} so far, waited {}ms on average and {}ms at most)",
+                    dispatch.waiting,
+                    dispatch.dispatched,
+                    dispatch.mean_wait.as_millis(),
+                    dispatch.max_wait.as_millis()
+                )?;
+                write!(
+                    f,
+                    "\nDatabase retries: {} ({} succeeded on a retry, {} gave up)",
+                    retries.retried, retries.recovered, retries.gave_up
+                )?;
+                write!(
+                    f,
+                    "\nSetting cache: {} hits, {} misses",
+                    cache.hits, cache.misses
+                )?;
+                if soak.interval_minutes.is_none() && soak.fired == 0 {
+                    return Ok(());
+                }
+                match soak.interval_minutes {
+                    Some(minutes) => write!(f, "\nSoak: every {}", Counted::new(minutes.into(), "minute", "minutes"))?,
+                    None => f.write_str("\nSoak: stopped")?,
+                }
+                write!(
+                    f,
+                    " ({} fired, {} off by more than {}s, off by {}ms on average and {}ms at most)",
+                    soak.fired,
+                    soak.off_time,
+                    SOAK_TOLERANCE.as_secs(),
+                    soak.mean_drift.as_millis(),
+                    soak.max_drift.as_millis()
+                )
+            }
+            Message::Health(health) => {
+                writeln!(f, "Discord API: {}", EnglishHealth(health.rest))?;
+                writeln!(f, "Gateway: {}", EnglishHealth(health.gateway))?;
+                write!(f, "Database: {}", EnglishHealth(health.database))
+            }
+            Message::StorageUsage {
+                guild_id,
+                keys,
+                bytes,
+                event_capacity,
+            } => {
+                writeln!(f, "Data of server {}", guild_id)?;
+                writeln!(
+                    f,
+                    "{}, about {}",
+                    Counted::new(*keys as i64, "key", "keys"),
+                    Counted::new(*bytes as i64, "byte", "bytes")
+                )?;
+                write!(f, "Keeps the latest {} disconnections", event_capacity)
+            }
+            Message::Phrase(phrase) => f.write_str(phrase),
+            Message::HandleError(e) => EnglishError(e).fmt(f),
+            Message::Log(LogEntry::Scheduled {
+                schedule_id,
+                voice_channel_id,
+                author_id,
+                time,
+                random,
+            }) => write!(
+                f,
+                "[scheduled] {} {}: {} scheduled a kaisan {} {}",
+                schedule_id.display_say(),
+                voice_channel_id.mention(),
+                author_id.mention(),
+                if *random { "sometime by" } else { "at" },
+                time.format("%m/%d %H:%M")
+            ),
+            Message::Log(LogEntry::Kaisan {
+                schedule_id,
+                voice_channel_id,
+                users,
+            }) => {
+                f.write_str("[kaisan] ")?;
+                if let Some(id) = schedule_id {
+                    write!(f, "{} ", id.display_say())?;
+                }
+                write!(
+                    f,
+                    "{}: disconnected {}",
+                    voice_channel_id.mention(),
+                    users.say_mentions_ref().display_say()
+                )
+            }
+            Message::Log(LogEntry::Cancelled {
+                schedule_id,
+                voice_channel_id,
+                by: Some(by),
+            }) => write!(
+                f,
+                "[cancelled] {} {}: cancelled by {}",
+                schedule_id.display_say(),
+                voice_channel_id.mention(),
+                by.mention()
+            ),
+            Message::Log(LogEntry::Cancelled {
+                schedule_id,
+                voice_channel_id,
+                by: None,
+            }) => write!(
+                f,
+                "[cancelled] {} {}: cancelled as the voice channel got empty",
+                schedule_id.display_say(),
+                voice_channel_id.mention()
+            ),
+            Message::Log(LogEntry::Failed {
+                schedule_id,
+                voice_channel_id,
+                error,
+            }) => write!(
+                f,
+                "[failed] {} {}: {}",
+                schedule_id.display_say(),
+                voice_channel_id.mention(),
+                EnglishError(error)
+            ),
+            Message::KaisanError(e) => write!(f, "Could not kaisan: {}", EnglishError(e)),
+            Message::RemindError(e) => write!(f, "Could not remind: {}", EnglishError(e)),
+        }
+    }
+}
+
+struct EnglishError<'a>(&'a Error);
+
+impl Display for EnglishError<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            Error::NotInVoiceChannel => f.write_str("Please use this in a voice channel"),
+            Error::InvalidCommand(_) => f.write_str("I don't understand the command"),
+            Error::UnreachableTime { .. } => f.write_str("You can't change the past"),
+            Error::NothingToUndo {
+                undo_window_minutes: 0,
+            } => f.write_str("Undo is disabled (`undo-window` enables it)"),
+            Error::NothingToUndo {
+                undo_window_minutes,
+            } => write!(
+                f,
+                "You have not scheduled a kaisan in the last {} (`cancel mine` cancels all of yours)",
+                Counted::new((*undo_window_minutes).into(), "minute", "minutes")
+            ),
+            Error::NoSuchProfile(name) => write!(
+                f,
+                "There is no profile named {} (`profile list` lists them)",
+                name
+            ),
+            Error::TooManyProfiles(max) => write!(
+                f,
+                "No more than {} can be saved",
+                Counted::new(*max as i64, "profile", "profiles")
+            ),
+            Error::Paused => f.write_str("I'm paused (`resume` resumes me)"),
+            Error::NotPaused => f.write_str("I'm not paused"),
+            Error::NoSuchMember(name) => write!(f, "I can't find anyone named {}", name),
+            Error::AmbiguousMember { name, candidates } => write!(
+                f,
+                "I'm not sure who {} is (one of {})",
+                name,
+                candidates.join(", ")
+            ),
+            Error::InvalidTime {
+                reason: CalculateTimeError::Nonexistent(time),
+                timezone,
+                ..
+            } => write!(
+                f,
+                "{} is skipped by the DST change in {} (`dst earliest` rolls it forward)",
+                time.format("%m/%d %H:%M"),
+                timezone.name()
+            ),
+            Error::InvalidTime {
+                reason: CalculateTimeError::Ambiguous(time),
+                timezone,
+                ..
+            } => write!(
+                f,
+                "{} happens twice because of the DST change in {} (choose one with `dst earliest` or `dst latest`)",
+                time.format("%m/%d %H:%M"),
+                timezone.name()
+            ),
+            Error::InvalidTime {
+                reason: CalculateTimeError::OutOfRange,
+                ..
+            } => f.write_str("There is no such date"),
+            Error::TooFarInFuture {
+                max_horizon_hours, ..
+            } => write!(
+                f,
+                "Kaisans more than {} ahead can't be scheduled (change it with `max-horizon`)",
+                Counted::new((*max_horizon_hours).into(), "hour", "hours")
+            ),
+            Error::TooManySchedules {
+                max_schedules_per_user,
+            } => write!(
+                f,
+                "Each user can schedule up to {} at once (`cancel mine` cancels yours)",
+                Counted::new((*max_schedules_per_user).into(), "kaisan", "kaisans")
+            ),
+            Error::DuplicateSchedule(ids) => write!(
+                f,
+                "Kaisan {} is already scheduled in this voice channel (cancel it with `cancel mine` or change this with `on-duplicate`)",
+                ids.say_joined(", ").display_say()
+            ),
+            Error::InsufficientPermission(p) => write!(f, "You need the {} permission", p),
+            Error::NoSuchReminder(_) => f.write_str("There is no such reminder"),
+            Error::DuplicatedReminders(_) => f.write_str("It already exists"),
+            Error::NoSuchSchedule(id) => {
+                write!(f, "No kaisan {} is scheduled", id.display_say())
+            }
+            Error::DuplicatedAllowedChannel(_) => f.write_str("It is already allowed"),
+            Error::NoSuchAllowedChannel(_) => f.write_str("There is no such channel"),
+            Error::DuplicatedAllowedRole(_) => f.write_str("It is already allowed"),
+            Error::NoSuchAllowedRole(_) => f.write_str("There is no such role"),
+            Error::ChannelNotAllowed(ids) => write!(
+                f,
+                "Commands are not allowed in this channel (please use {})",
+                ids.say_mentions_ref().display_say()
+            ),
+            Error::RateLimited {
+                max_commands,
+                window_seconds,
+            } => write!(
+                f,
+                "Please slow down; up to {} commands can be used in {} seconds. Try again in a moment",
+                max_commands, window_seconds
+            ),
+            Error::NotOwner => f.write_str("Only the operators of the bot can use this"),
+            Error::MissingAttachment => f.write_str("Please attach a setting file"),
+            Error::InvalidReminderText(InvalidTemplateError::UnknownPlaceholder(name)) => write!(
+                f,
+                "{{{}}} is not available (use {{remaining}}, {{targets}} or {{time}})",
+                name
+            ),
+            Error::InvalidReminderText(InvalidTemplateError::Unclosed) => {
+                f.write_str("A { in the reminder text is not closed")
+            }
+            Error::InvalidSettingFile(_) => f.write_str("Cannot read the setting file"),
+            Error::InvalidSettingValue(field) => {
+                write!(f, "The value of `{}` in the setting file is not valid", field)
+            }
+            Error::AttachmentTooLarge { max_bytes } => write!(
+                f,
+                "Please attach a file of up to {} KiB",
+                max_bytes / 1024
+            ),
+            Error::UnsupportedAttachment => f.write_str("Please attach a JSON file"),
+            Error::InvalidWebhookUrl(_) => f.write_str("Please give a public URL starting with https://"),
+            Error::InvalidLogChannel(_) => f.write_str("Please give a text channel of this server that I can post to"),
+            Error::UnusableEmoji(emoji) => {
+                write!(f, "{} is not an emoji usable in this server", emoji)
+            }
+            Error::PhraseTooLong { max_length } => {
+                write!(f, "Please keep the phrase within {} characters", max_length)
+            }
+            Error::InvalidLogFilter(_) => f.write_str(
+                "Cannot read the log filter (give it like `info,kaisantantoudaijin=debug`)",
+            ),
+            Error::SettingConflict => {
+                f.write_str("Someone else changed the setting at the same time. Please try again")
+            }
+            _ => f.write_str("Something went wrong"),
+        }
+    }
+}
+
+struct EnglishKaisanee<'a>(&'a KaisaneeSpecifier);
+
+impl Display for EnglishKaisanee<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            KaisaneeSpecifier::Me => f.write_str("you"),
+            KaisaneeSpecifier::All => f.write_str("everyone"),
+            KaisaneeSpecifier::Users(ids) => write!(f, "{}", ids.say_mentions_ref().display_say()),
+            KaisaneeSpecifier::Named(names) => f.write_str(&names.join(", ")),
+            KaisaneeSpecifier::ThisChannel { except } if except.is_empty() => {
+                f.write_str("everyone in your voice channel")
+            }
+            KaisaneeSpecifier::ThisChannel { except } => write!(
+                f,
+                "everyone in your voice channel except {}",
+                except.say_mentions_ref().display_say()
+            ),
+        }
+    }
+}
+
+struct EnglishCalculatedDateTime<'a>(&'a CalculatedDateTime);
+
+impl Display for EnglishCalculatedDateTime<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let CalculatedDateTime {
+            spec,
+            time,
+            now,
+            is_random,
+        } = *self.0;
+
+        if spec.is_interested_in_time() {
+            f.write_str(if is_random { "by " } else { "at " })?;
+            if time.date_naive() != now.date_naive() {
+                write!(f, "{}/{} ", time.month(), time.day())?;
+            }
+            write!(f, "{}", time.format("%H:%M"))?;
+        }
+
+        if spec.is_interested_in_time() && spec.is_interested_in_duration() {
+            f.write_str(", ")?;
+        }
+
+        if spec.is_interested_in_duration() {
+            f.write_str(if is_random { "within " } else { "in " })?;
+            write!(f, "{}", EnglishDuration(time - now))?;
+        }
+
+        Ok(())
+    }
+}
+
+struct FullTime<'a>(&'a DateTime<Tz>);
+
+impl Display for FullTime<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} ({})",
+            self.0.format("%Y/%m/%d %H:%M:%S"),
+            self.0.timezone().name()
+        )
+    }
+}
+
+struct EnglishHealth(ComponentHealth);
+
+impl Display for EnglishHealth {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            ComponentHealth::Ok(latency) => write!(f, "{}ms", latency.as_millis()),
+            ComponentHealth::Slow(latency) => write!(f, "{}ms (slow)", latency.as_millis()),
+            ComponentHealth::Failing => f.write_str("not responding"),
+            ComponentHealth::Unknown => f.write_str("unknown"),
+        }
+    }
+}
+
+fn author_leave_policy_str(policy: AuthorLeavePolicy) -> &'static str {
+    match policy {
+        AuthorLeavePolicy::Keep => "kaisan anyway",
+        AuthorLeavePolicy::Reroll => "redraw the time",
+        AuthorLeavePolicy::Cancel => "cancel the kaisan",
+    }
+}
+
+fn duplicate_policy_str(policy: DuplicatePolicy) -> &'static str {
+    match policy {
+        DuplicatePolicy::Stack => "keep both",
+        DuplicatePolicy::Replace => "cancel the earlier one",
+        DuplicatePolicy::Reject => "refuse the new one",
+    }
+}
+
+struct YesNo(bool);
+
+impl Display for YesNo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(if self.0 { "yes" } else { "no" })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::English;
+    use crate::error::Error;
+    use crate::model::{
+        kaisanee::KaisaneeSpecifier,
+        message::{CalculatedDateTime, Message},
+        reminder::Reminder,
+        schedule::ScheduleId,
+        setting::Locale,
+        template::ReminderTemplate,
+        time::{AfterTimeSpecifier, CalculateTimeError, TimeSpecifier},
+    };
+
+    use chrono::{Duration, TimeZone, Utc};
+    use serenity::model::id::UserId;
+
+    #[test]
+    fn test_scheduled() {
+        let now = chrono_tz::Japan
+            .with_ymd_and_hms(2024, 7, 20, 21, 0, 0)
+            .unwrap();
+        let message = Message::Scheduled {
+            calculated_time: CalculatedDateTime {
+                time: now + Duration::minutes(90),
+                now,
+                spec: TimeSpecifier::After(AfterTimeSpecifier::HourMinute(1, 30)),
+                is_random: false,
+            },
+            kaisanee: KaisaneeSpecifier::All,
+            head_count: 3,
+            revealed_time: None,
+            ignored_tokens: Vec::new(),
+        };
+        assert_eq!(
+            English(&message).to_string(),
+            "Disconnecting everyone at 22:30 (3 in the channel now)"
+        );
+    }
+
+    #[test]
+    fn test_plurals() {
+        assert_eq!(
+            English(&Message::CancelledMine(1)).to_string(),
+            "Cancelled 1 kaisan you scheduled"
+        );
+        assert_eq!(
+            English(&Message::AbortedAll(2)).to_string(),
+            "Cancelled all the 2 kaisans scheduled"
+        );
+        assert_eq!(
+            English(&Message::Countdown(1)).to_string(),
+            "Kaisan in 1 second"
+        );
+    }
+
+    #[test]
+    fn test_error() {
+        let message = Message::HandleError(Error::NoSuchSchedule(ScheduleId::new(3)));
+        assert_eq!(English(&message).to_string(), "No kaisan #3 is scheduled");
+
+        let message = Message::HandleError(Error::InvalidTime {
+            specifier: TimeSpecifier::After(AfterTimeSpecifier::Minute(1)),
+            at: Utc::now(),
+            timezone: chrono_tz::US::Eastern,
+            reason: CalculateTimeError::Nonexistent("2024-03-10T02:30:00".parse().unwrap()),
+        });
+        assert_eq!(
+            English(&message).to_string(),
+            "03/10 02:30 is skipped by the DST change in US/Eastern (`dst earliest` rolls it forward)"
+        );
+        assert_eq!(
+            message.render(Locale::Japanese),
+            "US/Eastern の 03/10 02:30 は夏時間の切り替えで飛ばされる時刻です（`dst earliest` で直後の時刻に繰り下げられます）"
+        );
+    }
+
+    #[test]
+    fn test_render_both() {
+        let message = Message::Reminders(vec![Reminder::before_minutes(5)]);
+        assert_eq!(message.render(Locale::Japanese), "・5分前\n");
+        assert_eq!(message.render(Locale::English), "・5 minutes before\n");
+        assert_eq!(
+            message.render(Locale::Both),
+            "・5分前\n\n・5 minutes before\n"
+        );
+
+        // user-written text is not repeated
+        let message = Message::Remind {
+            users: vec![UserId::new(1)],
+            reminder: Reminder::before_minutes(5),
+            time: chrono_tz::Japan
+                .with_ymd_and_hms(2024, 7, 20, 21, 0, 0)
+                .unwrap(),
+            template: Some(ReminderTemplate::parse("{targets} see you").unwrap()),
+        };
+        assert_eq!(message.render(Locale::Both), "<@1> see you");
+    }
+}
@@ -0,0 +1,188 @@
+//! Helpers to serialize the fields of [`Message`] that have no JSON form of their own.
+
+use std::fmt::Display;
+
+use super::Message;
+use crate::dispatcher::DispatchStats;
+use crate::model::health::{ComponentHealth, Health};
+use crate::soak::SoakStats;
+
+use chrono::Duration;
+use serde::{ser::SerializeSeq, Serialize, Serializer};
+use serenity::model::id::UserId;
+
+impl Message {
+    /// Structured form of the message, so that the consumers do not have to read the prose.
+    pub fn to_json(&self) -> serde_json::Value {
+        // every field serializes to a JSON value with string keys
+        serde_json::to_value(self).expect("message is serializable")
+    }
+}
+
+pub(super) fn display<T: Display, S: Serializer>(
+    value: &T,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+}
+
+pub(super) fn seconds<S: Serializer>(
+    duration: &Duration,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_i64(duration.num_seconds())
+}
+
+pub(super) fn option_seconds<S: Serializer>(
+    duration: &Option<Duration>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match duration {
+        Some(duration) => seconds(duration, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+pub(super) fn user_seconds<S: Serializer>(
+    durations: &[(UserId, Duration)],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let mut seq = serializer.serialize_seq(Some(durations.len()))?;
+    for (user_id, duration) in durations {
+        seq.serialize_element(&(user_id, duration.num_seconds()))?;
+    }
+    seq.end()
+}
+
+/// For the secrets, which must not leak into the logs.
+pub(super) fn redacted<T, S: Serializer>(_: &T, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_none()
+}
+
+pub(super) fn dispatch_stats<S: Serializer>(
+    stats: &DispatchStats,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    #[derive(Serialize)]
+    struct Stats {
+        dispatched: u64,
+        waiting: u64,
+        mean_wait: f64,
+        max_wait: f64,
+    }
+    Stats {
+        dispatched: stats.dispatched,
+        waiting: stats.waiting,
+        mean_wait: stats.mean_wait.as_secs_f64(),
+        max_wait: stats.max_wait.as_secs_f64(),
+    }
+    .serialize(serializer)
+}
+
+pub(super) fn soak_stats<S: Serializer>(
+    stats: &SoakStats,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    #[derive(Serialize)]
+    struct Stats {
+        interval_minutes: Option<u32>,
+        fired: u64,
+        off_time: u64,
+        mean_drift: f64,
+        max_drift: f64,
+    }
+    Stats {
+        interval_minutes: stats.interval_minutes,
+        fired: stats.fired,
+        off_time: stats.off_time,
+        mean_drift: stats.mean_drift.as_secs_f64(),
+        max_drift: stats.max_drift.as_secs_f64(),
+    }
+    .serialize(serializer)
+}
+
+pub(super) fn health<S: Serializer>(health: &Health, serializer: S) -> Result<S::Ok, S::Error> {
+    #[derive(Serialize)]
+    struct Component {
+        status: &'static str,
+        latency: Option<f64>,
+    }
+    #[derive(Serialize)]
+    struct Components {
+        rest: Component,
+        gateway: Component,
+        database: Component,
+    }
+    let component = |health: ComponentHealth| Component {
+        status: health.status(),
+        latency: health.latency().map(|latency| latency.as_secs_f64()),
+    };
+    Components {
+        rest: component(health.rest),
+        gateway: component(health.gateway),
+        database: component(health.database),
+    }
+    .serialize(serializer)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::Error;
+    use crate::model::{api_token::ApiToken, message::Message, schedule::ScheduleId};
+
+    use chrono::{Duration, TimeZone};
+    use serde_json::json;
+    use serenity::model::id::UserId;
+
+    #[test]
+    fn test_to_json() {
+        assert_eq!(Message::Help.to_json(), json!({ "kind": "help" }));
+        assert_eq!(
+            Message::Kaisan(vec![UserId::new(1), UserId::new(2)]).to_json(),
+            json!({ "kind": "kaisan", "data": ["1", "2"] })
+        );
+        assert_eq!(
+            Message::NextKaisan {
+                id: ScheduleId::new(3),
+                remaining: Duration::minutes(5),
+                time: chrono_tz::Japan
+                    .with_ymd_and_hms(2024, 7, 20, 21, 0, 0)
+                    .unwrap(),
+                is_random: false,
+            }
+            .to_json(),
+            json!({
+                "kind": "next_kaisan",
+                "data": {
+                    "id": 3,
+                    "remaining": 300,
+                    "time": "2024-07-20T21:00:00+09:00",
+                    "is_random": false,
+                },
+            })
+        );
+        assert_eq!(
+            Message::WeeklyStats {
+                voice_times: vec![(UserId::new(1), Duration::hours(1))],
+                kaisaned: vec![(UserId::new(1), 2)],
+            }
+            .to_json(),
+            json!({
+                "kind": "weekly_stats",
+                "data": { "voice_times": [["1", 3600]], "kaisaned": [["1", 2]] },
+            })
+        );
+        assert_eq!(
+            Message::HandleError(Error::NoSuchSchedule(ScheduleId::new(3))).to_json(),
+            json!({ "kind": "handle_error", "data": "no such schedule ScheduleId(3)" })
+        );
+    }
+
+    #[test]
+    fn test_token_redacted() {
+        let token = ApiToken::generate();
+        let json = Message::ApiTokenGenerated(token.clone()).to_json();
+        assert_eq!(json, json!({ "kind": "api_token_generated", "data": null }));
+        assert!(!json.to_string().contains(token.as_str()));
+    }
+}
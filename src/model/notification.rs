@@ -0,0 +1,56 @@
+use crate::model::schedule::ScheduleId;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serenity::model::id::{ChannelId, UserId};
+
+/// Event sent to the notification sinks of a guild, such as a webhook.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Notification {
+    Scheduled {
+        schedule_id: ScheduleId,
+        voice_channel_id: ChannelId,
+        author_id: UserId,
+        /// The end of the range for a random kaisan, whose time is kept secret.
+        time: DateTime<Utc>,
+        random: bool,
+    },
+    Remind {
+        schedule_id: ScheduleId,
+        voice_channel_id: ChannelId,
+        users: Vec<UserId>,
+        time: DateTime<Utc>,
+    },
+    Kaisan {
+        schedule_id: Option<ScheduleId>,
+        voice_channel_id: ChannelId,
+        users: Vec<UserId>,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Notification;
+    use crate::model::schedule::ScheduleId;
+
+    use serenity::model::id::{ChannelId, UserId};
+
+    #[test]
+    fn test_serialize() {
+        let notification = Notification::Kaisan {
+            schedule_id: Some(ScheduleId::new(3)),
+            voice_channel_id: ChannelId::new(1),
+            users: vec![UserId::new(2)],
+        };
+        assert_eq!(
+            serde_json::to_value(notification).unwrap(),
+            serde_json::json!({
+                "event": "kaisan",
+                "schedule_id": 3,
+                "voice_channel_id": "1",
+                "users": ["2"],
+            })
+        );
+    }
+}
@@ -0,0 +1,79 @@
+use std::collections::HashSet;
+
+use crate::model::kaisanee::KaisaneeSpecifier;
+
+use serenity::model::{
+    id::{RoleId, UserId},
+    permissions::Permissions,
+};
+
+/// Who may kaisan whom in a guild, put together from its setting.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PermissionPolicy {
+    /// Kaisan of others requires the permission.
+    pub requires_permission: bool,
+    /// Kaisan of only oneself requires the permission, too.
+    pub requires_permission_self: bool,
+    /// Roles whose members are treated as having the permission.
+    pub allowed_roles: HashSet<RoleId>,
+}
+
+impl PermissionPolicy {
+    /// Whether `author_id` needs the permission to kaisan `kaisanee`.
+    pub fn is_restricted(&self, kaisanee: &KaisaneeSpecifier, author_id: UserId) -> bool {
+        if kaisanee.may_include_others(author_id) {
+            self.requires_permission
+        } else {
+            self.requires_permission_self
+        }
+    }
+
+    /// Whether a member with `permissions` and `roles` may kaisan when it is restricted.
+    pub fn grants(&self, permissions: Permissions, roles: &[RoleId]) -> bool {
+        permissions.move_members() || roles.iter().any(|role| self.allowed_roles.contains(role))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PermissionPolicy;
+    use crate::model::kaisanee::KaisaneeSpecifier;
+
+    use serenity::model::{
+        id::{RoleId, UserId},
+        permissions::Permissions,
+    };
+
+    const AUTHOR: UserId = UserId::new(1);
+
+    #[test]
+    fn test_is_restricted() {
+        let policy = PermissionPolicy {
+            requires_permission: true,
+            ..Default::default()
+        };
+        assert!(policy.is_restricted(&KaisaneeSpecifier::All, AUTHOR));
+        assert!(policy.is_restricted(&KaisaneeSpecifier::Users(vec![UserId::new(2)]), AUTHOR));
+        assert!(!policy.is_restricted(&KaisaneeSpecifier::Me, AUTHOR));
+        assert!(!policy.is_restricted(&KaisaneeSpecifier::Users(vec![AUTHOR]), AUTHOR));
+
+        let policy = PermissionPolicy {
+            requires_permission_self: true,
+            ..Default::default()
+        };
+        assert!(!policy.is_restricted(&KaisaneeSpecifier::All, AUTHOR));
+        assert!(policy.is_restricted(&KaisaneeSpecifier::Me, AUTHOR));
+    }
+
+    #[test]
+    fn test_grants() {
+        let policy = PermissionPolicy {
+            allowed_roles: [RoleId::new(1)].into_iter().collect(),
+            ..Default::default()
+        };
+        assert!(policy.grants(Permissions::MOVE_MEMBERS, &[]));
+        assert!(policy.grants(Permissions::empty(), &[RoleId::new(2), RoleId::new(1)]));
+        assert!(!policy.grants(Permissions::empty(), &[RoleId::new(2)]));
+        assert!(!policy.grants(Permissions::MANAGE_GUILD, &[]));
+    }
+}
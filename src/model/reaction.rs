@@ -0,0 +1,120 @@
+use serenity::model::channel::ReactionType;
+
+/// Reactions to the message of a scheduled kaisan that change its targets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScheduleReaction {
+    /// 🙅, leave the reacting user out of a kaisan for everyone
+    OptOut,
+    /// ✋, add the reacting user to the targets
+    OptIn,
+}
+
+const OPT_OUT_EMOJI: &str = "🙅";
+const OPT_IN_EMOJI: &str = "✋";
+
+impl ScheduleReaction {
+    pub fn from_reaction(reaction: &ReactionType) -> Option<ScheduleReaction> {
+        let ReactionType::Unicode(emoji) = reaction else {
+            return None;
+        };
+        // accept the variations with skin tones and genders as well
+        if emoji.starts_with(OPT_OUT_EMOJI) {
+            Some(ScheduleReaction::OptOut)
+        } else if emoji.starts_with(OPT_IN_EMOJI) {
+            Some(ScheduleReaction::OptIn)
+        } else {
+            None
+        }
+    }
+}
+
+/// Whether a command or a scheduled kaisan has gone well, told with a reaction to its message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Outcome {
+    Success,
+    Failure,
+}
+
+/// Phrases said along with the reactions are limited to this many characters.
+pub const MAX_PHRASE_LENGTH: usize = 100;
+
+impl Outcome {
+    /// Name in the commands and the keys of the settings.
+    pub fn name(self) -> &'static str {
+        match self {
+            Outcome::Success => "success",
+            Outcome::Failure => "failure",
+        }
+    }
+
+    /// Reaction used unless the guild sets another one.
+    pub fn default_reaction(self) -> ReactionType {
+        match self {
+            Outcome::Success => '✅'.into(),
+            Outcome::Failure => '❌'.into(),
+        }
+    }
+}
+
+/// Parses an emoji in a message, which is either a Unicode emoji or a custom one like
+/// `<:name:id>`. Whether Discord accepts it is only known by reacting with it.
+pub fn parse_emoji(s: &str) -> Option<ReactionType> {
+    if s.starts_with('<') {
+        return s.parse().ok();
+    }
+    // keycaps such as 1️⃣ contain an ASCII character, but no emoji contains a letter
+    let is_emoji = s.chars().count() <= 16
+        && !s.is_ascii()
+        && !s.chars().any(|c| c.is_alphabetic() || c.is_whitespace());
+    is_emoji.then(|| ReactionType::Unicode(s.to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_emoji, ScheduleReaction};
+    use serenity::model::{channel::ReactionType, id::EmojiId};
+
+    #[test]
+    fn test_from_reaction() {
+        assert_eq!(
+            ScheduleReaction::from_reaction(&ReactionType::Unicode("🙅".to_owned())),
+            Some(ScheduleReaction::OptOut)
+        );
+        assert_eq!(
+            ScheduleReaction::from_reaction(&ReactionType::Unicode("🙅🏽‍♂️".to_owned())),
+            Some(ScheduleReaction::OptOut)
+        );
+        assert_eq!(
+            ScheduleReaction::from_reaction(&ReactionType::Unicode("✋🏻".to_owned())),
+            Some(ScheduleReaction::OptIn)
+        );
+        assert_eq!(
+            ScheduleReaction::from_reaction(&ReactionType::Unicode("👍".to_owned())),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_emoji() {
+        assert_eq!(
+            parse_emoji("🎉"),
+            Some(ReactionType::Unicode("🎉".to_owned()))
+        );
+        assert_eq!(
+            parse_emoji("1️⃣"),
+            Some(ReactionType::Unicode("1️⃣".to_owned()))
+        );
+        assert_eq!(
+            parse_emoji("<:kaisan:123>"),
+            Some(ReactionType::Custom {
+                animated: false,
+                id: EmojiId::new(123),
+                name: Some("kaisan".to_owned()),
+            })
+        );
+        assert_eq!(parse_emoji("<:kaisan>"), None);
+        assert_eq!(parse_emoji("ok"), None);
+        assert_eq!(parse_emoji("了解"), None);
+        assert_eq!(parse_emoji(""), None);
+    }
+}
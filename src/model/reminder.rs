@@ -2,17 +2,73 @@ use crate::say::{fmt, Say};
 
 use chrono::Duration;
 use redis::{FromRedisValue, RedisResult, RedisWrite, ToRedisArgs, Value};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Copy)]
+/// Reminder before the kaisan, in seconds.
+///
+/// Reminders of whole minutes are encoded as a number of minutes, which is compatible with the
+/// format before seconds were supported. The others are encoded as a string like `"30s"`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Serialize, Deserialize)]
+#[serde(try_from = "ReminderRepr", into = "ReminderRepr")]
 pub struct Reminder(u32);
 
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum ReminderRepr {
+    Minutes(u32),
+    Seconds(String),
+}
+
+#[derive(Debug, Clone, Error)]
+#[error("invalid reminder: {0}")]
+pub struct InvalidReminderError(String);
+
+impl TryFrom<ReminderRepr> for Reminder {
+    type Error = InvalidReminderError;
+
+    fn try_from(repr: ReminderRepr) -> Result<Reminder, InvalidReminderError> {
+        match repr {
+            ReminderRepr::Minutes(minutes) => minutes
+                .checked_mul(60)
+                .map(Reminder::before_seconds)
+                .ok_or_else(|| InvalidReminderError(minutes.to_string())),
+            ReminderRepr::Seconds(s) => s
+                .strip_suffix('s')
+                .and_then(|secs| secs.parse().ok())
+                .map(Reminder::before_seconds)
+                .ok_or(InvalidReminderError(s)),
+        }
+    }
+}
+
+impl From<Reminder> for ReminderRepr {
+    fn from(reminder: Reminder) -> ReminderRepr {
+        if reminder.0 % 60 == 0 {
+            ReminderRepr::Minutes(reminder.0 / 60)
+        } else {
+            ReminderRepr::Seconds(format!("{}s", reminder.0))
+        }
+    }
+}
+
 impl Reminder {
+    pub const fn before_seconds(seconds: u32) -> Reminder {
+        Reminder(seconds)
+    }
+
+    /// Saturates at the longest reminder that can be represented.
     pub const fn before_minutes(minutes: u32) -> Reminder {
-        Reminder(minutes)
+        Reminder(minutes.saturating_mul(60))
+    }
+
+    /// Saturates at the longest reminder that can be represented.
+    pub const fn before_hours(hours: u32) -> Reminder {
+        Reminder(hours.saturating_mul(60 * 60))
     }
 
     pub fn before_duration(&self) -> Duration {
-        Duration::minutes(self.0.into())
+        Duration::seconds(self.0.into())
     }
 }
 
@@ -21,13 +77,27 @@ impl ToRedisArgs for Reminder {
     where
         W: RedisWrite + ?Sized,
     {
-        self.0.write_redis_args(out);
+        match ReminderRepr::from(*self) {
+            ReminderRepr::Minutes(minutes) => minutes.write_redis_args(out),
+            ReminderRepr::Seconds(s) => s.write_redis_args(out),
+        }
     }
 }
 
 impl FromRedisValue for Reminder {
     fn from_redis_value(v: &Value) -> RedisResult<Self> {
-        u32::from_redis_value(v).map(Reminder)
+        let repr = match u32::from_redis_value(v) {
+            Ok(minutes) => ReminderRepr::Minutes(minutes),
+            Err(_) => ReminderRepr::Seconds(String::from_redis_value(v)?),
+        };
+        Reminder::try_from(repr).map_err(|e| {
+            (
+                redis::ErrorKind::TypeError,
+                "invalid reminder",
+                e.to_string(),
+            )
+                .into()
+        })
     }
 }
 
@@ -36,3 +106,77 @@ impl Say for Reminder {
         say!(f, "{}前", self.before_duration())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Reminder;
+    use crate::say::SayExt;
+    use redis::{FromRedisValue, ToRedisArgs, Value};
+
+    fn roundtrip_redis(reminder: Reminder) -> Reminder {
+        let args = reminder.to_redis_args();
+        Reminder::from_redis_value(&Value::Data(args[0].clone())).unwrap()
+    }
+
+    #[test]
+    fn test_redis() {
+        assert_eq!(
+            Reminder::before_minutes(5).to_redis_args(),
+            5u32.to_redis_args()
+        );
+        assert_eq!(
+            roundtrip_redis(Reminder::before_minutes(5)),
+            Reminder::before_minutes(5)
+        );
+        assert_eq!(
+            roundtrip_redis(Reminder::before_seconds(30)),
+            Reminder::before_seconds(30)
+        );
+        assert_eq!(
+            roundtrip_redis(Reminder::before_hours(1)),
+            Reminder::before_minutes(60)
+        );
+    }
+
+    #[test]
+    fn test_serde() {
+        let reminders = vec![Reminder::before_minutes(10), Reminder::before_seconds(90)];
+        let json = serde_json::to_string(&reminders).unwrap();
+        assert_eq!(json, r#"[10,"90s"]"#);
+        assert_eq!(
+            serde_json::from_str::<Vec<Reminder>>(&json).unwrap(),
+            reminders
+        );
+        assert!(serde_json::from_str::<Reminder>(r#""90""#).is_err());
+        assert!(serde_json::from_str::<Reminder>("100000000").is_err());
+    }
+
+    #[test]
+    fn test_out_of_range() {
+        assert!(Reminder::from_redis_value(&Value::Int(100_000_000)).is_err());
+        assert_eq!(
+            Reminder::before_minutes(u32::MAX),
+            Reminder::before_seconds(u32::MAX)
+        );
+        assert_eq!(
+            Reminder::before_hours(u32::MAX),
+            Reminder::before_seconds(u32::MAX)
+        );
+    }
+
+    #[test]
+    fn test_say() {
+        assert_eq!(
+            Reminder::before_minutes(5).display_say().to_string(),
+            "5分前"
+        );
+        assert_eq!(
+            Reminder::before_seconds(30).display_say().to_string(),
+            "30秒前"
+        );
+        assert_eq!(
+            Reminder::before_seconds(90).display_say().to_string(),
+            "1分30秒前"
+        );
+    }
+}
@@ -0,0 +1,137 @@
+use crate::model::{kaisanee::KaisaneeSpecifier, reminder::Reminder};
+use crate::say::{fmt, Say};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use serenity::model::id::{ChannelId, MessageId, UserId};
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ScheduleId(u32);
+
+impl ScheduleId {
+    pub const fn new(id: u32) -> ScheduleId {
+        ScheduleId(id)
+    }
+
+    pub fn as_u32(&self) -> u32 {
+        self.0
+    }
+}
+
+impl Say for ScheduleId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+/// How long a schedule may remain registered after its time before it is considered stuck.
+const OVERDUE_GRACE_SECONDS: i64 = 60;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Schedule {
+    pub author_id: UserId,
+    pub channel_id: ChannelId,
+    pub message_id: Option<MessageId>,
+    pub voice_channel_id: ChannelId,
+    pub kaisanee: KaisaneeSpecifier,
+    pub time: DateTime<Utc>,
+    /// Upper bound of the time range, if the time is randomly chosen
+    #[serde(default)]
+    pub random_until: Option<DateTime<Utc>>,
+    pub reminders: Vec<Reminder>,
+    #[serde(default)]
+    pub remind_only_me: bool,
+    /// Users who reacted to the message to be left out of a kaisan for everyone
+    #[serde(default)]
+    pub opted_out: Vec<UserId>,
+    /// When the schedule was made, which limits how long it can be undone
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+impl Schedule {
+    /// The time that can be shown to the users, which is the end of the range for a random
+    /// kaisan.
+    pub fn public_time(&self) -> DateTime<Utc> {
+        self.random_until.unwrap_or(self.time)
+    }
+
+    /// Whether the schedule should have been carried out by `now` but still remains.
+    pub fn is_overdue(&self, now: DateTime<Utc>) -> bool {
+        self.time + Duration::seconds(OVERDUE_GRACE_SECONDS) < now
+    }
+}
+
+/// A schedule stopped by `pause`, which keeps how long was left until it so that it can be
+/// started again by `resume` without losing the time it was paused for.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FrozenSchedule {
+    pub schedule: Schedule,
+    pub remaining_seconds: i64,
+}
+
+impl FrozenSchedule {
+    pub fn freeze(schedule: Schedule, now: DateTime<Utc>) -> FrozenSchedule {
+        let remaining_seconds = (schedule.time - now).num_seconds().max(0);
+        FrozenSchedule {
+            schedule,
+            remaining_seconds,
+        }
+    }
+
+    /// The schedule moved so that the same time is left from `now` as when it was frozen.
+    pub fn thaw(self, now: DateTime<Utc>) -> Schedule {
+        let FrozenSchedule {
+            mut schedule,
+            remaining_seconds,
+        } = self;
+        let shift = now + Duration::seconds(remaining_seconds) - schedule.time;
+        schedule.time += shift;
+        schedule.random_until = schedule.random_until.map(|until| until + shift);
+        schedule
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FrozenSchedule, Schedule};
+    use crate::{
+        model::kaisanee::KaisaneeSpecifier,
+        test::{mock_schedule, MOCK_AUTHOR_1},
+    };
+
+    use chrono::{Duration, Utc};
+
+    #[test]
+    fn test_freeze_thaw() {
+        let now = Utc::now();
+        let schedule = Schedule {
+            random_until: Some(now + Duration::minutes(20)),
+            created_at: Some(now),
+            ..mock_schedule(MOCK_AUTHOR_1, now + Duration::minutes(10))
+        };
+
+        let frozen = FrozenSchedule::freeze(schedule.clone(), now);
+        assert_eq!(frozen.remaining_seconds, 600);
+
+        let later = now + Duration::hours(1);
+        let thawed = frozen.thaw(later);
+        assert_eq!(thawed.time, later + Duration::minutes(10));
+        assert_eq!(thawed.random_until, Some(later + Duration::minutes(20)));
+        assert_eq!(thawed.created_at, schedule.created_at);
+    }
+
+    #[test]
+    fn test_freeze_overdue() {
+        let now = Utc::now();
+        let schedule = Schedule {
+            kaisanee: KaisaneeSpecifier::Me,
+            ..mock_schedule(MOCK_AUTHOR_1, now - Duration::seconds(5))
+        };
+
+        let frozen = FrozenSchedule::freeze(schedule, now);
+        assert_eq!(frozen.remaining_seconds, 0);
+        assert_eq!(frozen.thaw(now).time, now);
+    }
+}
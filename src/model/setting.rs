@@ -0,0 +1,542 @@
+use std::collections::{BTreeSet, HashSet};
+use std::str::FromStr;
+
+use crate::model::{reminder::Reminder, template::ReminderTemplate, time::Hour};
+use crate::say::{fmt, Say};
+
+use chrono::Weekday;
+use chrono_tz::Tz;
+use serde::{Deserialize, Deserializer, Serialize};
+use serenity::model::id::{ChannelId, RoleId};
+
+/// The settings of a guild that are exported and saved in a profile. The reactions, the phrases,
+/// the webhook, the log channel and the overrides per voice channel are not part of it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Setting {
+    pub timezone: Tz,
+    pub requires_permission: bool,
+    pub requires_permission_self: bool,
+    pub reminders: BTreeSet<Reminder>,
+    pub reminds_random_kaisan: bool,
+    pub auto_kaisan_hour: Option<Hour>,
+    pub max_horizon_hours: u8,
+    pub tonight_hour: Hour,
+    pub max_schedules_per_user: u8,
+    pub undo_window_minutes: u8,
+    pub countdown: bool,
+    pub author_leave_policy: AuthorLeavePolicy,
+    pub reveal_random: RevealRandom,
+    pub random_distribution: RandomDistribution,
+    pub dst_policy: DstPolicy,
+    pub week_start: WeekStart,
+    pub locale: Locale,
+    pub on_duplicate: DuplicatePolicy,
+    pub allowed_channels: BTreeSet<ChannelId>,
+    pub allowed_roles: BTreeSet<RoleId>,
+    pub command_prefix: Option<String>,
+    pub reminder_text: Option<ReminderTemplate>,
+}
+
+/// The settings to overwrite as imported from a file, where a missing field leaves the setting as
+/// it is and `null` resets it.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Deserialize)]
+#[serde(default)]
+pub struct SettingPatch {
+    pub timezone: Option<Tz>,
+    pub requires_permission: Option<bool>,
+    pub requires_permission_self: Option<bool>,
+    pub reminders: Option<BTreeSet<Reminder>>,
+    pub reminds_random_kaisan: Option<bool>,
+    #[serde(deserialize_with = "nullable")]
+    pub auto_kaisan_hour: Option<Option<Hour>>,
+    pub max_horizon_hours: Option<u8>,
+    pub tonight_hour: Option<Hour>,
+    pub max_schedules_per_user: Option<u8>,
+    pub undo_window_minutes: Option<u8>,
+    pub countdown: Option<bool>,
+    pub author_leave_policy: Option<AuthorLeavePolicy>,
+    pub reveal_random: Option<RevealRandom>,
+    pub random_distribution: Option<RandomDistribution>,
+    pub dst_policy: Option<DstPolicy>,
+    pub week_start: Option<WeekStart>,
+    pub locale: Option<Locale>,
+    pub on_duplicate: Option<DuplicatePolicy>,
+    pub allowed_channels: Option<BTreeSet<ChannelId>>,
+    pub allowed_roles: Option<BTreeSet<RoleId>>,
+    #[serde(deserialize_with = "nullable")]
+    pub command_prefix: Option<Option<String>>,
+    #[serde(deserialize_with = "nullable")]
+    pub reminder_text: Option<Option<ReminderTemplate>>,
+}
+
+/// Reads `null` as `Some(None)`, which a missing field is told apart from by `#[serde(default)]`.
+fn nullable<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    Option::deserialize(deserializer).map(Some)
+}
+
+/// The settings read on every kaisan command, read at once with
+/// [`SettingContext::settings_snapshot`].
+///
+/// [`SettingContext::settings_snapshot`]: crate::context::SettingContext::settings_snapshot
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SettingsSnapshot {
+    pub timezone: Tz,
+    pub requires_permission: bool,
+    pub requires_permission_self: bool,
+    pub reminders: HashSet<Reminder>,
+    pub reminds_random_kaisan: bool,
+}
+
+/// What to do with a random kaisan when its requester leaves the voice channel before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuthorLeavePolicy {
+    #[default]
+    Keep,
+    Reroll,
+    Cancel,
+}
+
+impl AuthorLeavePolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuthorLeavePolicy::Keep => "keep",
+            AuthorLeavePolicy::Reroll => "reroll",
+            AuthorLeavePolicy::Cancel => "cancel",
+        }
+    }
+}
+
+impl FromStr for AuthorLeavePolicy {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<AuthorLeavePolicy, ()> {
+        match s {
+            "keep" => Ok(AuthorLeavePolicy::Keep),
+            "reroll" => Ok(AuthorLeavePolicy::Reroll),
+            "cancel" => Ok(AuthorLeavePolicy::Cancel),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Say for AuthorLeavePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AuthorLeavePolicy::Keep => f.write_str("そのまま解散する"),
+            AuthorLeavePolicy::Reroll => f.write_str("解散時刻を引き直す"),
+            AuthorLeavePolicy::Cancel => f.write_str("解散を取り消す"),
+        }
+    }
+}
+
+/// Where to announce the randomly chosen time of a kaisan.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum RevealRandom {
+    #[default]
+    #[serde(rename = "off")]
+    Off,
+    #[serde(rename = "channel")]
+    Channel,
+    #[serde(rename = "dm")]
+    DirectMessage,
+}
+
+impl RevealRandom {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RevealRandom::Off => "off",
+            RevealRandom::Channel => "channel",
+            RevealRandom::DirectMessage => "dm",
+        }
+    }
+}
+
+impl FromStr for RevealRandom {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<RevealRandom, ()> {
+        match s {
+            "off" => Ok(RevealRandom::Off),
+            "channel" => Ok(RevealRandom::Channel),
+            "dm" => Ok(RevealRandom::DirectMessage),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Say for RevealRandom {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RevealRandom::Off => f.write_str("知らせない"),
+            RevealRandom::Channel => f.write_str("チャンネルで知らせる"),
+            RevealRandom::DirectMessage => f.write_str("予約した人にだけ DM で知らせる"),
+        }
+    }
+}
+
+/// How the time of a random kaisan is drawn between now and the deadline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RandomDistribution {
+    #[default]
+    Uniform,
+    /// More likely to be close to the deadline
+    LateBiased,
+    /// More likely to be close to now
+    EarlyBiased,
+}
+
+impl RandomDistribution {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RandomDistribution::Uniform => "uniform",
+            RandomDistribution::LateBiased => "late-biased",
+            RandomDistribution::EarlyBiased => "early-biased",
+        }
+    }
+}
+
+impl FromStr for RandomDistribution {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<RandomDistribution, ()> {
+        match s {
+            "uniform" => Ok(RandomDistribution::Uniform),
+            "late-biased" => Ok(RandomDistribution::LateBiased),
+            "early-biased" => Ok(RandomDistribution::EarlyBiased),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Say for RandomDistribution {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RandomDistribution::Uniform => f.write_str("一様"),
+            RandomDistribution::LateBiased => f.write_str("期限の近くに偏らせる"),
+            RandomDistribution::EarlyBiased => f.write_str("早めに偏らせる"),
+        }
+    }
+}
+
+/// How a local time that is ambiguous or skipped around a DST transition is resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DstPolicy {
+    /// Takes the earlier of repeated times, and rolls skipped times forward
+    #[default]
+    Earliest,
+    /// Takes the later of repeated times, and rolls skipped times forward
+    Latest,
+    /// Rejects both of them
+    Reject,
+}
+
+impl DstPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DstPolicy::Earliest => "earliest",
+            DstPolicy::Latest => "latest",
+            DstPolicy::Reject => "reject",
+        }
+    }
+}
+
+impl FromStr for DstPolicy {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<DstPolicy, ()> {
+        match s {
+            "earliest" => Ok(DstPolicy::Earliest),
+            "latest" => Ok(DstPolicy::Latest),
+            "reject" => Ok(DstPolicy::Reject),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Say for DstPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DstPolicy::Earliest => f.write_str("重複する時刻は早い方、存在しない時刻は繰り下げる"),
+            DstPolicy::Latest => f.write_str("重複する時刻は遅い方、存在しない時刻は繰り下げる"),
+            DstPolicy::Reject => f.write_str("エラーにする"),
+        }
+    }
+}
+
+/// Language of the messages the bot sends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Locale {
+    #[default]
+    #[serde(rename = "ja")]
+    Japanese,
+    #[serde(rename = "en")]
+    English,
+    /// Japanese followed by English
+    #[serde(rename = "both")]
+    Both,
+}
+
+impl Locale {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Locale::Japanese => "ja",
+            Locale::English => "en",
+            Locale::Both => "both",
+        }
+    }
+}
+
+impl FromStr for Locale {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Locale, ()> {
+        match s {
+            "ja" => Ok(Locale::Japanese),
+            "en" => Ok(Locale::English),
+            "both" => Ok(Locale::Both),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Say for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Locale::Japanese => f.write_str("日本語"),
+            Locale::English => f.write_str("英語"),
+            Locale::Both => f.write_str("日本語と英語"),
+        }
+    }
+}
+
+/// What to do when a user schedules a kaisan in a voice channel where they already have one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicatePolicy {
+    /// Keep both
+    #[default]
+    Stack,
+    /// Cancel the existing ones
+    Replace,
+    /// Refuse the new one
+    Reject,
+}
+
+impl DuplicatePolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DuplicatePolicy::Stack => "stack",
+            DuplicatePolicy::Replace => "replace",
+            DuplicatePolicy::Reject => "reject",
+        }
+    }
+}
+
+impl FromStr for DuplicatePolicy {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<DuplicatePolicy, ()> {
+        match s {
+            "stack" => Ok(DuplicatePolicy::Stack),
+            "replace" => Ok(DuplicatePolicy::Replace),
+            "reject" => Ok(DuplicatePolicy::Reject),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Say for DuplicatePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DuplicatePolicy::Stack => f.write_str("両方予約する"),
+            DuplicatePolicy::Replace => f.write_str("前の予約を取り消す"),
+            DuplicatePolicy::Reject => f.write_str("新しい予約を断る"),
+        }
+    }
+}
+
+/// The first day of a week, which decides what `来週` and `next` refer to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WeekStart {
+    #[default]
+    Monday,
+    Sunday,
+}
+
+impl WeekStart {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WeekStart::Monday => "monday",
+            WeekStart::Sunday => "sunday",
+        }
+    }
+
+    pub fn weekday(&self) -> Weekday {
+        match self {
+            WeekStart::Monday => Weekday::Mon,
+            WeekStart::Sunday => Weekday::Sun,
+        }
+    }
+}
+
+impl FromStr for WeekStart {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<WeekStart, ()> {
+        match s {
+            "monday" => Ok(WeekStart::Monday),
+            "sunday" => Ok(WeekStart::Sunday),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Say for WeekStart {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WeekStart::Monday => f.write_str("月曜日"),
+            WeekStart::Sunday => f.write_str("日曜日"),
+        }
+    }
+}
+
+/// A setting of the guild overridden in a voice channel, or `None` to follow the guild again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChannelOverride {
+    MaxHorizon(Option<u8>),
+    Countdown(Option<bool>),
+    AuthorLeave(Option<AuthorLeavePolicy>),
+    OnDuplicate(Option<DuplicatePolicy>),
+}
+
+/// Settings overridden in a voice channel, which take precedence over those of the guild.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub struct ChannelOverrides {
+    pub max_horizon_hours: Option<u8>,
+    pub countdown: Option<bool>,
+    pub author_leave_policy: Option<AuthorLeavePolicy>,
+    pub on_duplicate: Option<DuplicatePolicy>,
+}
+
+impl ChannelOverrides {
+    pub fn is_empty(&self) -> bool {
+        *self == ChannelOverrides::default()
+    }
+
+    pub fn apply(&mut self, setting: ChannelOverride) {
+        match setting {
+            ChannelOverride::MaxHorizon(hours) => self.max_horizon_hours = hours,
+            ChannelOverride::Countdown(countdown) => self.countdown = countdown,
+            ChannelOverride::AuthorLeave(policy) => self.author_leave_policy = policy,
+            ChannelOverride::OnDuplicate(policy) => self.on_duplicate = policy,
+        }
+    }
+}
+
+pub const DEFAULT_TIMEZONE: Tz = chrono_tz::Japan;
+pub const DEFAULT_MAX_HORIZON_HOURS: u8 = 12;
+pub const DEFAULT_TONIGHT_HOUR: u8 = 21;
+pub const DEFAULT_MAX_SCHEDULES_PER_USER: u8 = 3;
+pub const DEFAULT_UNDO_WINDOW_MINUTES: u8 = 2;
+
+impl SettingPatch {
+    /// The first field with a value that its command would not accept, if any. An imported file
+    /// does not go through the grammar of the commands.
+    pub fn invalid_field(&self) -> Option<&'static str> {
+        if self.max_horizon_hours == Some(0) {
+            return Some("max_horizon_hours");
+        }
+        if self.max_schedules_per_user == Some(0) {
+            return Some("max_schedules_per_user");
+        }
+        if let Some(Some(prefix)) = &self.command_prefix {
+            if !is_valid_command_prefix(prefix) {
+                return Some("command_prefix");
+            }
+        }
+        None
+    }
+}
+
+/// All the settings, to replace the current ones as loading a profile does.
+impl From<Setting> for SettingPatch {
+    fn from(setting: Setting) -> SettingPatch {
+        SettingPatch {
+            timezone: Some(setting.timezone),
+            requires_permission: Some(setting.requires_permission),
+            requires_permission_self: Some(setting.requires_permission_self),
+            reminders: Some(setting.reminders),
+            reminds_random_kaisan: Some(setting.reminds_random_kaisan),
+            auto_kaisan_hour: Some(setting.auto_kaisan_hour),
+            max_horizon_hours: Some(setting.max_horizon_hours),
+            tonight_hour: Some(setting.tonight_hour),
+            max_schedules_per_user: Some(setting.max_schedules_per_user),
+            undo_window_minutes: Some(setting.undo_window_minutes),
+            countdown: Some(setting.countdown),
+            author_leave_policy: Some(setting.author_leave_policy),
+            reveal_random: Some(setting.reveal_random),
+            random_distribution: Some(setting.random_distribution),
+            dst_policy: Some(setting.dst_policy),
+            week_start: Some(setting.week_start),
+            locale: Some(setting.locale),
+            on_duplicate: Some(setting.on_duplicate),
+            allowed_channels: Some(setting.allowed_channels),
+            allowed_roles: Some(setting.allowed_roles),
+            command_prefix: Some(setting.command_prefix),
+            reminder_text: Some(setting.reminder_text),
+        }
+    }
+}
+
+/// The timezone stored under the `timezone` key, or the default when it is missing or invalid.
+pub fn stored_timezone(stored: Option<&str>) -> Tz {
+    stored
+        .and_then(|tz_str| tz_str.parse().ok())
+        .unwrap_or(DEFAULT_TIMEZONE)
+}
+
+/// Whether `prefix` can be set with `prefix`: a word without spaces, other than the keywords
+/// that reset it.
+pub fn is_valid_command_prefix(prefix: &str) -> bool {
+    !prefix.is_empty()
+        && !prefix.chars().any(char::is_whitespace)
+        && !matches!(prefix, "default" | "reset")
+}
+
+impl Default for Setting {
+    fn default() -> Setting {
+        Setting {
+            timezone: DEFAULT_TIMEZONE,
+            requires_permission: true,
+            requires_permission_self: false,
+            reminders: BTreeSet::new(),
+            reminds_random_kaisan: false,
+            auto_kaisan_hour: None,
+            max_horizon_hours: DEFAULT_MAX_HORIZON_HOURS,
+            tonight_hour: Hour::from_u8(DEFAULT_TONIGHT_HOUR).unwrap(),
+            max_schedules_per_user: DEFAULT_MAX_SCHEDULES_PER_USER,
+            undo_window_minutes: DEFAULT_UNDO_WINDOW_MINUTES,
+            countdown: false,
+            author_leave_policy: AuthorLeavePolicy::default(),
+            reveal_random: RevealRandom::default(),
+            random_distribution: RandomDistribution::default(),
+            dst_policy: DstPolicy::default(),
+            week_start: WeekStart::default(),
+            locale: Locale::default(),
+            on_duplicate: DuplicatePolicy::default(),
+            allowed_channels: BTreeSet::new(),
+            allowed_roles: BTreeSet::new(),
+            command_prefix: None,
+            reminder_text: None,
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use crate::say::{fmt, IntoIteratorSayExt, Say};
+
+use chrono::{DateTime, Duration};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use serenity::model::id::UserId;
+use thiserror::Error;
+
+const REMAINING: &str = "remaining";
+const TARGETS: &str = "targets";
+const TIME: &str = "time";
+const PLACEHOLDERS: &[&str] = &[REMAINING, TARGETS, TIME];
+
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum InvalidTemplateError {
+    #[error("unknown placeholder {{{0}}}")]
+    UnknownPlaceholder(String),
+    #[error("unclosed placeholder")]
+    Unclosed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Text(String),
+    Placeholder(&'static str),
+}
+
+/// User-defined text of reminder messages, with `{remaining}`, `{targets}` and `{time}`
+/// placeholders.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ReminderTemplate {
+    source: String,
+    segments: Vec<Segment>,
+}
+
+impl ReminderTemplate {
+    pub fn parse(source: &str) -> Result<ReminderTemplate, InvalidTemplateError> {
+        let mut segments = Vec::new();
+        let mut rest = source;
+        while let Some(start) = rest.find('{') {
+            if start > 0 {
+                segments.push(Segment::Text(rest[..start].to_owned()));
+            }
+            let Some(len) = rest[start..].find('}') else {
+                return Err(InvalidTemplateError::Unclosed);
+            };
+            let name = &rest[start + 1..start + len];
+            let Some(placeholder) = PLACEHOLDERS.iter().find(|p| **p == name) else {
+                return Err(InvalidTemplateError::UnknownPlaceholder(name.to_owned()));
+            };
+            segments.push(Segment::Placeholder(placeholder));
+            rest = &rest[start + len + 1..];
+        }
+        if !rest.is_empty() {
+            segments.push(Segment::Text(rest.to_owned()));
+        }
+        Ok(ReminderTemplate {
+            source: source.to_owned(),
+            segments,
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    pub fn render<'a>(
+        &'a self,
+        remaining: Duration,
+        targets: &'a [UserId],
+        time: DateTime<Tz>,
+    ) -> RenderedReminder<'a> {
+        RenderedReminder {
+            template: self,
+            remaining,
+            targets,
+            time,
+        }
+    }
+}
+
+impl TryFrom<String> for ReminderTemplate {
+    type Error = InvalidTemplateError;
+
+    fn try_from(source: String) -> Result<ReminderTemplate, InvalidTemplateError> {
+        ReminderTemplate::parse(&source)
+    }
+}
+
+impl From<ReminderTemplate> for String {
+    fn from(template: ReminderTemplate) -> String {
+        template.source
+    }
+}
+
+pub struct RenderedReminder<'a> {
+    template: &'a ReminderTemplate,
+    remaining: Duration,
+    targets: &'a [UserId],
+    time: DateTime<Tz>,
+}
+
+impl Say for RenderedReminder<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let segments = &self.template.segments;
+        // make sure that the targets are notified even if the template does not mention them
+        if !segments.contains(&Segment::Placeholder(TARGETS)) {
+            say!(f, "{} ", self.targets.say_mentions_ref())?;
+        }
+        for segment in segments {
+            match segment {
+                Segment::Text(text) => f.write_str(text)?,
+                Segment::Placeholder(REMAINING) => Say::fmt(&self.remaining, f)?,
+                Segment::Placeholder(TARGETS) => Say::fmt(&self.targets.say_mentions_ref(), f)?,
+                Segment::Placeholder(TIME) => write!(f, "{}", self.time.format("%H:%M"))?,
+                Segment::Placeholder(_) => unreachable!(),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InvalidTemplateError, ReminderTemplate};
+    use crate::say::SayExt;
+
+    use chrono::{Duration, TimeZone};
+    use chrono_tz::Tz;
+    use serenity::model::id::UserId;
+
+    #[test]
+    fn test_parse() {
+        assert!(ReminderTemplate::parse("あと{remaining}で解散だよ").is_ok());
+        assert!(ReminderTemplate::parse("no placeholder").is_ok());
+        assert_eq!(
+            ReminderTemplate::parse("{foo}"),
+            Err(InvalidTemplateError::UnknownPlaceholder("foo".to_owned()))
+        );
+        assert_eq!(
+            ReminderTemplate::parse("あと{remaining"),
+            Err(InvalidTemplateError::Unclosed)
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let time = Tz::Japan.with_ymd_and_hms(2024, 7, 20, 23, 30, 0).unwrap();
+        let users = [UserId::new(1), UserId::new(2)];
+
+        let template = ReminderTemplate::parse("{targets} {time}まであと{remaining}").unwrap();
+        assert_eq!(
+            template
+                .render(Duration::minutes(5), &users, time)
+                .display_say()
+                .to_string(),
+            "<@1> <@2> 23:30まであと5分"
+        );
+
+        let template = ReminderTemplate::parse("あと{remaining}で解散だよ").unwrap();
+        assert_eq!(
+            template
+                .render(Duration::minutes(5), &users, time)
+                .display_say()
+                .to_string(),
+            "<@1> <@2> あと5分で解散だよ"
+        );
+    }
+}
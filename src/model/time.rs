@@ -1,11 +1,18 @@
-use chrono::{DateTime, Duration, FixedOffset, TimeZone, Timelike, Utc};
+use crate::model::setting::{DstPolicy, WeekStart, DEFAULT_TONIGHT_HOUR};
+
+use chrono::{
+    DateTime, Datelike, Duration, FixedOffset, LocalResult, NaiveDate, NaiveDateTime, TimeZone,
+    Timelike, Utc, Weekday,
+};
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 #[derive(Debug, Clone, Error)]
 #[error("invalid hour")]
 pub struct InvalidHourError(());
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Copy)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Serialize, Deserialize)]
+#[serde(try_from = "u8", into = "u8")]
 pub struct Hour(u8);
 
 impl Hour {
@@ -22,6 +29,20 @@ impl Hour {
     }
 }
 
+impl TryFrom<u8> for Hour {
+    type Error = InvalidHourError;
+
+    fn try_from(x: u8) -> Result<Hour, InvalidHourError> {
+        Hour::from_u8(x)
+    }
+}
+
+impl From<Hour> for u8 {
+    fn from(hour: Hour) -> u8 {
+        hour.0
+    }
+}
+
 #[derive(Debug, Clone, Error)]
 #[error("invalid hour")]
 pub struct InvalidMinuteError(());
@@ -49,6 +70,9 @@ pub enum AfterTimeSpecifier {
     Minute(u8),
     HourMinute(u8, u8),
     Second(u8),
+    Day(u8),
+    /// A duration given with a fraction such as `1.5時間`, in seconds.
+    Fractional(u32),
 }
 
 impl AfterTimeSpecifier {
@@ -66,6 +90,15 @@ impl AfterTimeSpecifier {
         }
     }
 
+    /// Minutes such as `5分`, or `5分半` with 30 more seconds.
+    pub fn with_minute_and_half(m: u8, half: bool) -> AfterTimeSpecifier {
+        if half {
+            AfterTimeSpecifier::Fractional(u32::from(m) * 60 + 30)
+        } else {
+            AfterTimeSpecifier::Minute(m)
+        }
+    }
+
     fn calculate_duration(&self) -> Duration {
         match *self {
             AfterTimeSpecifier::Hour(h) => Duration::hours(h.into()),
@@ -74,6 +107,53 @@ impl AfterTimeSpecifier {
                 Duration::hours(h.into()) + Duration::minutes(m.into())
             }
             AfterTimeSpecifier::Second(s) => Duration::seconds(s.into()),
+            AfterTimeSpecifier::Day(d) => Duration::days(d.into()),
+            AfterTimeSpecifier::Fractional(s) => Duration::seconds(s.into()),
+        }
+    }
+}
+
+/// Day of an [`AtTimeSpecifier`], relative to the day it is specified.
+#[derive(Debug, Clone, PartialEq, Eq, Copy)]
+pub enum DateSpecifier {
+    Today,
+    Tomorrow,
+    InDays(u8),
+    /// The nearest day of the week, including today.
+    Weekday(Weekday),
+    /// The day of the week in the week after this one.
+    NextWeek(Weekday),
+    /// The nearest date, which is in the next year if it has passed in this year.
+    Date {
+        month: u32,
+        day: u32,
+    },
+}
+
+impl DateSpecifier {
+    fn calculate_date(&self, today: NaiveDate, week_start: WeekStart) -> Option<NaiveDate> {
+        match *self {
+            DateSpecifier::Today => Some(today),
+            DateSpecifier::Tomorrow => today.succ_opt(),
+            DateSpecifier::InDays(n) => today.checked_add_signed(Duration::days(n.into())),
+            DateSpecifier::Weekday(weekday) => {
+                let days = weekday.days_since(today.weekday());
+                today.checked_add_signed(Duration::days(days.into()))
+            }
+            DateSpecifier::NextWeek(weekday) => {
+                let first = week_start.weekday();
+                let days = 7 - i64::from(today.weekday().days_since(first))
+                    + i64::from(weekday.days_since(first));
+                today.checked_add_signed(Duration::days(days))
+            }
+            DateSpecifier::Date { month, day } => {
+                let date = NaiveDate::from_ymd_opt(today.year(), month, day);
+                match date {
+                    Some(date) if date >= today => Some(date),
+                    // 2/29 may exist only in this year, so do not return early on None
+                    _ => NaiveDate::from_ymd_opt(today.year() + 1, month, day),
+                }
+            }
         }
     }
 }
@@ -82,25 +162,23 @@ impl AfterTimeSpecifier {
 pub enum AtTimeSpecifier {
     Hour {
         hour: Hour,
-        is_tomorrow: bool,
+        date: DateSpecifier,
     },
     Minute(Minute),
     HourMinute {
         hour: Hour,
         minute: Minute,
-        is_tomorrow: bool,
+        date: DateSpecifier,
     },
+    /// Today at the hour configured for each guild.
+    Tonight,
 }
 
 impl AtTimeSpecifier {
-    pub fn with_hour(hour: Hour, minute: Option<Minute>, is_tomorrow: bool) -> AtTimeSpecifier {
+    pub fn with_hour(hour: Hour, minute: Option<Minute>, date: DateSpecifier) -> AtTimeSpecifier {
         match minute {
-            Some(minute) => AtTimeSpecifier::HourMinute {
-                hour,
-                minute,
-                is_tomorrow,
-            },
-            None => AtTimeSpecifier::Hour { hour, is_tomorrow },
+            Some(minute) => AtTimeSpecifier::HourMinute { hour, minute, date },
+            None => AtTimeSpecifier::Hour { hour, date },
         }
     }
 
@@ -109,13 +187,27 @@ impl AtTimeSpecifier {
             Some(hour) => AtTimeSpecifier::HourMinute {
                 hour,
                 minute,
-                is_tomorrow: false,
+                date: DateSpecifier::Today,
             },
             None => AtTimeSpecifier::Minute(minute),
         }
     }
 }
 
+/// Why a [`TimeSpecifier`] does not name a time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum CalculateTimeError {
+    /// The local time is skipped by a DST transition.
+    #[error("{0} is skipped in the timezone")]
+    Nonexistent(NaiveDateTime),
+    /// The local time is repeated by a DST transition.
+    #[error("{0} is repeated in the timezone")]
+    Ambiguous(NaiveDateTime),
+    /// The date does not exist, or is beyond what can be represented.
+    #[error("the date is out of range")]
+    OutOfRange,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Copy)]
 pub enum TimeSpecifier {
     After(AfterTimeSpecifier),
@@ -124,42 +216,67 @@ pub enum TimeSpecifier {
 }
 
 impl TimeSpecifier {
-    pub fn calculate_time<T: TimeZone>(&self, now: DateTime<Utc>, tz: T) -> Option<DateTime<Utc>> {
+    pub fn calculate_time<T: TimeZone>(
+        &self,
+        now: DateTime<Utc>,
+        tz: T,
+    ) -> Result<DateTime<Utc>, CalculateTimeError> {
+        self.calculate_time_with_policy(now, tz, DstPolicy::default())
+    }
+
+    pub fn calculate_time_with_policy<T: TimeZone>(
+        &self,
+        now: DateTime<Utc>,
+        tz: T,
+        policy: DstPolicy,
+    ) -> Result<DateTime<Utc>, CalculateTimeError> {
+        self.calculate_time_with_setting(now, tz, policy, WeekStart::default())
+    }
+
+    pub fn calculate_time_with_setting<T: TimeZone>(
+        &self,
+        now: DateTime<Utc>,
+        tz: T,
+        policy: DstPolicy,
+        week_start: WeekStart,
+    ) -> Result<DateTime<Utc>, CalculateTimeError> {
         match self {
-            TimeSpecifier::After(dur) => Some(now + dur.calculate_duration()),
+            TimeSpecifier::After(dur) => now
+                .checked_add_signed(dur.calculate_duration())
+                .ok_or(CalculateTimeError::OutOfRange),
             TimeSpecifier::At(time) => {
                 let now = now.with_timezone(&tz);
                 let now_date = now.date_naive();
-                match time {
-                    AtTimeSpecifier::Hour { hour, is_tomorrow } => {
-                        let t = now_date.and_hms_opt(hour.as_u32(), 0, 0)?;
-                        if *is_tomorrow {
-                            t + Duration::days(1)
-                        } else {
-                            t
-                        }
-                    }
-                    AtTimeSpecifier::Minute(m) => {
-                        now_date.and_hms_opt(now.hour(), m.as_u32(), 0)?
-                    }
-                    AtTimeSpecifier::HourMinute {
-                        hour,
-                        minute,
-                        is_tomorrow,
-                    } => {
-                        let t = now_date.and_hms_opt(hour.as_u32(), minute.as_u32(), 0)?;
-                        if *is_tomorrow {
-                            t + Duration::days(1)
-                        } else {
-                            t
-                        }
+                let naive = match *time {
+                    AtTimeSpecifier::Hour { hour, date } => date
+                        .calculate_date(now_date, week_start)
+                        .and_then(|d| d.and_hms_opt(hour.as_u32(), 0, 0)),
+                    AtTimeSpecifier::Minute(m) => now_date.and_hms_opt(now.hour(), m.as_u32(), 0),
+                    AtTimeSpecifier::HourMinute { hour, minute, date } => date
+                        .calculate_date(now_date, week_start)
+                        .and_then(|d| d.and_hms_opt(hour.as_u32(), minute.as_u32(), 0)),
+                    // resolved with the guild setting by `with_tonight_hour` beforehand
+                    AtTimeSpecifier::Tonight => {
+                        now_date.and_hms_opt(DEFAULT_TONIGHT_HOUR.into(), 0, 0)
                     }
                 }
-                .and_local_timezone(tz)
-                .single()
-                .map(|t| t.to_utc())
+                .ok_or(CalculateTimeError::OutOfRange)?;
+                resolve_local_time(naive, &tz, policy)
             }
-            TimeSpecifier::Exactly(time) => Some(time.with_timezone(&Utc)),
+            TimeSpecifier::Exactly(time) => Ok(time.with_timezone(&Utc)),
+        }
+    }
+
+    /// Replaces [`AtTimeSpecifier::Tonight`] with the given hour of today.
+    pub fn with_tonight_hour(self, hour: Hour) -> TimeSpecifier {
+        match self {
+            TimeSpecifier::At(AtTimeSpecifier::Tonight) => {
+                TimeSpecifier::At(AtTimeSpecifier::Hour {
+                    hour,
+                    date: DateSpecifier::Today,
+                })
+            }
+            spec => spec,
         }
     }
 
@@ -172,11 +289,44 @@ impl TimeSpecifier {
     }
 }
 
+/// DST gaps are no longer than this anywhere in the tz database.
+const MAX_DST_GAP_MINUTES: i64 = 3 * 60;
+
+fn resolve_local_time<T: TimeZone>(
+    naive: NaiveDateTime,
+    tz: &T,
+    policy: DstPolicy,
+) -> Result<DateTime<Utc>, CalculateTimeError> {
+    match (naive.and_local_timezone(tz.clone()), policy) {
+        (LocalResult::Single(t), _) => Ok(t.to_utc()),
+        (LocalResult::Ambiguous(..), DstPolicy::Reject) => {
+            Err(CalculateTimeError::Ambiguous(naive))
+        }
+        (LocalResult::Ambiguous(earliest, _), DstPolicy::Earliest) => Ok(earliest.to_utc()),
+        (LocalResult::Ambiguous(_, latest), DstPolicy::Latest) => Ok(latest.to_utc()),
+        (LocalResult::None, DstPolicy::Reject) => Err(CalculateTimeError::Nonexistent(naive)),
+        // skipped by a DST gap; take the first local time that exists after it
+        (LocalResult::None, _) => (1..=MAX_DST_GAP_MINUTES)
+            .find_map(|m| {
+                (naive + Duration::minutes(m))
+                    .and_local_timezone(tz.clone())
+                    .earliest()
+                    .map(|t| t.to_utc())
+            })
+            .ok_or(CalculateTimeError::Nonexistent(naive)),
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{AfterTimeSpecifier, AtTimeSpecifier, Hour, Minute, TimeSpecifier};
+    use super::{
+        AfterTimeSpecifier, AtTimeSpecifier, CalculateTimeError, DateSpecifier, Hour, Minute,
+        TimeSpecifier,
+    };
+    use crate::model::setting::{DstPolicy, WeekStart};
 
-    use chrono::{DateTime, Duration, FixedOffset, Utc};
+    use chrono::{DateTime, Duration, FixedOffset, NaiveDateTime, Utc, Weekday};
+    use chrono_tz::{Europe::Berlin, US::Eastern};
 
     #[test]
     fn test_calculate_time_after() {
@@ -187,10 +337,10 @@ mod tests {
         let expected = DateTime::parse_from_rfc3339("2024-07-20T16:30:00Z")
             .unwrap()
             .to_utc();
-        assert_eq!(spec.calculate_time(now, Utc), Some(expected));
+        assert_eq!(spec.calculate_time(now, Utc), Ok(expected));
         assert_eq!(
             spec.calculate_time(now, FixedOffset::east_opt(3600).unwrap()),
-            Some(expected)
+            Ok(expected)
         );
     }
 
@@ -202,12 +352,12 @@ mod tests {
         let spec = TimeSpecifier::At(AtTimeSpecifier::HourMinute {
             hour: Hour::from_u8(12).unwrap(),
             minute: Minute::from_u8(35).unwrap(),
-            is_tomorrow: false,
+            date: DateSpecifier::Today,
         });
         let expected = DateTime::parse_from_rfc3339("2024-07-20T12:35:00Z")
             .unwrap()
             .to_utc();
-        assert_eq!(spec.calculate_time(now, Utc), Some(expected));
+        assert_eq!(spec.calculate_time(now, Utc), Ok(expected));
     }
 
     #[test]
@@ -220,7 +370,7 @@ mod tests {
         let expected = DateTime::parse_from_rfc3339("2024-07-20T13:35:00+09:00")
             .unwrap()
             .to_utc();
-        assert_eq!(spec.calculate_time(now_utc, tz), Some(expected));
+        assert_eq!(spec.calculate_time(now_utc, tz), Ok(expected));
     }
 
     #[test]
@@ -231,12 +381,159 @@ mod tests {
         let spec = TimeSpecifier::At(AtTimeSpecifier::HourMinute {
             hour: Hour::from_u8(23).unwrap(),
             minute: Minute::from_u8(25).unwrap(),
-            is_tomorrow: true,
+            date: DateSpecifier::Tomorrow,
         });
         let expected = DateTime::parse_from_rfc3339("2024-07-21T23:25:00Z")
             .unwrap()
             .to_utc();
-        assert_eq!(spec.calculate_time(now, Utc), Some(expected));
+        assert_eq!(spec.calculate_time(now, Utc), Ok(expected));
+    }
+
+    #[test]
+    fn test_calculate_time_at_date() {
+        let at = |date| {
+            TimeSpecifier::At(AtTimeSpecifier::HourMinute {
+                hour: Hour::from_u8(21).unwrap(),
+                minute: Minute::from_u8(0).unwrap(),
+                date,
+            })
+        };
+        let expected = |s| DateTime::parse_from_rfc3339(s).unwrap().to_utc();
+
+        // Wednesday, at the end of the month
+        let now = expected("2024-07-31T13:15:00Z");
+        assert_eq!(
+            at(DateSpecifier::Weekday(Weekday::Fri)).calculate_time(now, Utc),
+            Ok(expected("2024-08-02T21:00:00Z"))
+        );
+        assert_eq!(
+            at(DateSpecifier::Weekday(Weekday::Wed)).calculate_time(now, Utc),
+            Ok(expected("2024-07-31T21:00:00Z"))
+        );
+        assert_eq!(
+            at(DateSpecifier::Weekday(Weekday::Tue)).calculate_time(now, Utc),
+            Ok(expected("2024-08-06T21:00:00Z"))
+        );
+        assert_eq!(
+            at(DateSpecifier::Tomorrow).calculate_time(now, Utc),
+            Ok(expected("2024-08-01T21:00:00Z"))
+        );
+        assert_eq!(
+            at(DateSpecifier::InDays(3)).calculate_time(now, Utc),
+            Ok(expected("2024-08-03T21:00:00Z"))
+        );
+        assert_eq!(
+            at(DateSpecifier::Date { month: 8, day: 1 }).calculate_time(now, Utc),
+            Ok(expected("2024-08-01T21:00:00Z"))
+        );
+        // already passed in this year
+        assert_eq!(
+            at(DateSpecifier::Date { month: 7, day: 1 }).calculate_time(now, Utc),
+            Ok(expected("2025-07-01T21:00:00Z"))
+        );
+        assert_eq!(
+            at(DateSpecifier::Date { month: 2, day: 30 }).calculate_time(now, Utc),
+            Err(CalculateTimeError::OutOfRange)
+        );
+    }
+
+    #[test]
+    fn test_next_week() {
+        let at = |date| {
+            TimeSpecifier::At(AtTimeSpecifier::Hour {
+                hour: Hour::from_u8(21).unwrap(),
+                date,
+            })
+        };
+        let expected = |s| DateTime::parse_from_rfc3339(s).unwrap().to_utc();
+        let calculate = |spec: TimeSpecifier, now, week_start| {
+            spec.calculate_time_with_setting(now, Utc, DstPolicy::default(), week_start)
+        };
+
+        // Wednesday
+        let now = expected("2024-07-31T13:15:00Z");
+        for week_start in [WeekStart::Monday, WeekStart::Sunday] {
+            assert_eq!(
+                calculate(at(DateSpecifier::NextWeek(Weekday::Fri)), now, week_start),
+                Ok(expected("2024-08-09T21:00:00Z"))
+            );
+            assert_eq!(
+                calculate(at(DateSpecifier::NextWeek(Weekday::Wed)), now, week_start),
+                Ok(expected("2024-08-07T21:00:00Z"))
+            );
+        }
+        assert_eq!(
+            calculate(
+                at(DateSpecifier::NextWeek(Weekday::Sun)),
+                now,
+                WeekStart::Monday
+            ),
+            Ok(expected("2024-08-11T21:00:00Z"))
+        );
+        assert_eq!(
+            calculate(
+                at(DateSpecifier::NextWeek(Weekday::Sun)),
+                now,
+                WeekStart::Sunday
+            ),
+            Ok(expected("2024-08-04T21:00:00Z"))
+        );
+
+        // Sunday, which ends the week or starts it
+        let now = expected("2024-08-04T13:15:00Z");
+        assert_eq!(
+            calculate(
+                at(DateSpecifier::NextWeek(Weekday::Mon)),
+                now,
+                WeekStart::Monday
+            ),
+            Ok(expected("2024-08-05T21:00:00Z"))
+        );
+        assert_eq!(
+            calculate(
+                at(DateSpecifier::NextWeek(Weekday::Mon)),
+                now,
+                WeekStart::Sunday
+            ),
+            Ok(expected("2024-08-12T21:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn test_calculate_time_at_date_with_tz() {
+        let tz = FixedOffset::east_opt(9 * 3600).unwrap();
+        // already Friday in +09:00 while it is Thursday in UTC
+        let now = DateTime::parse_from_rfc3339("2024-12-26T20:00:00Z")
+            .unwrap()
+            .to_utc();
+        let spec = TimeSpecifier::At(AtTimeSpecifier::Hour {
+            hour: Hour::from_u8(21).unwrap(),
+            date: DateSpecifier::Weekday(Weekday::Fri),
+        });
+        assert_eq!(
+            spec.calculate_time(now, tz),
+            Ok(DateTime::parse_from_rfc3339("2024-12-27T21:00:00+09:00")
+                .unwrap()
+                .to_utc())
+        );
+        assert_eq!(
+            spec.calculate_time(now, Utc),
+            Ok(DateTime::parse_from_rfc3339("2024-12-27T21:00:00Z")
+                .unwrap()
+                .to_utc())
+        );
+
+        // the date crosses the year boundary
+        let spec = TimeSpecifier::At(AtTimeSpecifier::Hour {
+            hour: Hour::from_u8(0).unwrap(),
+            date: DateSpecifier::InDays(6),
+        });
+        assert_eq!(
+            spec.calculate_time(now, tz),
+            Ok(DateTime::parse_from_rfc3339("2025-01-02T00:00:00+09:00")
+                .unwrap()
+                .to_utc())
+        );
     }
 
     #[test]
@@ -247,13 +544,107 @@ mod tests {
         let spec = TimeSpecifier::At(AtTimeSpecifier::HourMinute {
             hour: Hour::from_u8(7).unwrap(),
             minute: Minute::from_u8(15).unwrap(),
-            is_tomorrow: false,
+            date: DateSpecifier::Today,
         });
         let tz = FixedOffset::east_opt(9 * 3600).unwrap();
         let expected = DateTime::parse_from_rfc3339("2024-07-20T07:15:00+09:00")
             .unwrap()
             .to_utc();
-        assert_eq!(spec.calculate_time(now, tz), Some(expected));
+        assert_eq!(spec.calculate_time(now, tz), Ok(expected));
+    }
+
+    fn at_hour_minute(hour: u8, minute: u8) -> TimeSpecifier {
+        TimeSpecifier::At(AtTimeSpecifier::HourMinute {
+            hour: Hour::from_u8(hour).unwrap(),
+            minute: Minute::from_u8(minute).unwrap(),
+            date: DateSpecifier::Today,
+        })
+    }
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().to_utc()
+    }
+
+    fn naive(s: &str) -> NaiveDateTime {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_calculate_time_dst_gap_us_eastern() {
+        // 2024-03-10 02:00 EST jumps to 03:00 EDT
+        let now = utc("2024-03-10T00:00:00-05:00");
+        let spec = at_hour_minute(2, 30);
+        for policy in [DstPolicy::Earliest, DstPolicy::Latest] {
+            assert_eq!(
+                spec.calculate_time_with_policy(now, Eastern, policy),
+                Ok(utc("2024-03-10T03:00:00-04:00"))
+            );
+        }
+        assert_eq!(
+            spec.calculate_time_with_policy(now, Eastern, DstPolicy::Reject),
+            Err(CalculateTimeError::Nonexistent(naive(
+                "2024-03-10T02:30:00"
+            )))
+        );
+        assert_eq!(
+            at_hour_minute(3, 30).calculate_time_with_policy(now, Eastern, DstPolicy::Reject),
+            Ok(utc("2024-03-10T03:30:00-04:00"))
+        );
+    }
+
+    #[test]
+    fn test_calculate_time_dst_ambiguous_us_eastern() {
+        // 2024-11-03 02:00 EDT falls back to 01:00 EST
+        let now = utc("2024-11-03T00:00:00-04:00");
+        let spec = at_hour_minute(1, 30);
+        assert_eq!(
+            spec.calculate_time(now, Eastern),
+            Ok(utc("2024-11-03T01:30:00-04:00"))
+        );
+        assert_eq!(
+            spec.calculate_time_with_policy(now, Eastern, DstPolicy::Latest),
+            Ok(utc("2024-11-03T01:30:00-05:00"))
+        );
+        assert_eq!(
+            spec.calculate_time_with_policy(now, Eastern, DstPolicy::Reject),
+            Err(CalculateTimeError::Ambiguous(naive("2024-11-03T01:30:00")))
+        );
+    }
+
+    #[test]
+    fn test_calculate_time_dst_gap_europe_berlin() {
+        // 2024-03-31 02:00 CET jumps to 03:00 CEST
+        let now = utc("2024-03-31T00:00:00+01:00");
+        let spec = at_hour_minute(2, 15);
+        assert_eq!(
+            spec.calculate_time(now, Berlin),
+            Ok(utc("2024-03-31T03:00:00+02:00"))
+        );
+        assert_eq!(
+            spec.calculate_time_with_policy(now, Berlin, DstPolicy::Reject),
+            Err(CalculateTimeError::Nonexistent(naive(
+                "2024-03-31T02:15:00"
+            )))
+        );
+    }
+
+    #[test]
+    fn test_calculate_time_dst_ambiguous_europe_berlin() {
+        // 2024-10-27 03:00 CEST falls back to 02:00 CET
+        let now = utc("2024-10-27T00:00:00+02:00");
+        let spec = at_hour_minute(2, 30);
+        assert_eq!(
+            spec.calculate_time(now, Berlin),
+            Ok(utc("2024-10-27T02:30:00+02:00"))
+        );
+        assert_eq!(
+            spec.calculate_time_with_policy(now, Berlin, DstPolicy::Latest),
+            Ok(utc("2024-10-27T02:30:00+01:00"))
+        );
+        assert_eq!(
+            spec.calculate_time_with_policy(now, Berlin, DstPolicy::Reject),
+            Err(CalculateTimeError::Ambiguous(naive("2024-10-27T02:30:00")))
+        );
     }
 
     #[test]
@@ -263,10 +654,10 @@ mod tests {
         let spec = TimeSpecifier::Exactly(
             expected.with_timezone(&FixedOffset::east_opt(5 * 3600).unwrap()),
         );
-        assert_eq!(spec.calculate_time(now, Utc), Some(expected));
+        assert_eq!(spec.calculate_time(now, Utc), Ok(expected));
         assert_eq!(
             spec.calculate_time(now, FixedOffset::east_opt(3600).unwrap()),
-            Some(expected)
+            Ok(expected)
         );
     }
 }
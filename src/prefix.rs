@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::database::DEFAULT_CACHE_TTL;
+
+use serenity::model::id::GuildId;
+
+struct Entry {
+    prefix: Option<String>,
+    expires_at: Instant,
+}
+
+/// The command prefixes of the guilds, kept for a while so that every message does not read the
+/// database to see whether it is a command.
+///
+/// The prefixes set through [`CommandPrefixes::store`] are seen at once, but those set by another
+/// process sharing the database are seen only after the entry expires.
+#[derive(Clone)]
+pub struct CommandPrefixes {
+    ttl: Duration,
+    guilds: Arc<Mutex<HashMap<GuildId, Entry>>>,
+}
+
+impl Default for CommandPrefixes {
+    fn default() -> Self {
+        CommandPrefixes::new(DEFAULT_CACHE_TTL)
+    }
+}
+
+impl CommandPrefixes {
+    pub fn new(ttl: Duration) -> Self {
+        CommandPrefixes {
+            ttl,
+            guilds: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// The prefix of the guild, where `None` stands for the default one, or `None` if it is not
+    /// known.
+    pub fn get(&self, guild_id: GuildId) -> Option<Option<String>> {
+        let guilds = self.guilds.lock().unwrap();
+        guilds
+            .get(&guild_id)
+            .filter(|entry| entry.expires_at > Instant::now())
+            .map(|entry| entry.prefix.clone())
+    }
+
+    pub fn store(&self, guild_id: GuildId, prefix: Option<String>) {
+        let entry = Entry {
+            prefix,
+            expires_at: Instant::now() + self.ttl,
+        };
+        self.guilds.lock().unwrap().insert(guild_id, entry);
+    }
+}
+
+/// Strips the prefix from the message, which has to be followed by a space or the end of the
+/// message so that `!k` does not match `!kaisan`.
+pub fn strip_command_prefix<'a>(content: &'a str, prefix: &str) -> Option<&'a str> {
+    content
+        .strip_prefix(prefix)
+        .filter(|rest| rest.is_empty() || rest.starts_with(char::is_whitespace))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{strip_command_prefix, CommandPrefixes};
+
+    use std::time::Duration;
+
+    use serenity::model::id::GuildId;
+
+    const GUILD: GuildId = GuildId::new(1);
+
+    #[test]
+    fn test_store() {
+        let prefixes = CommandPrefixes::new(Duration::from_secs(30));
+        assert_eq!(prefixes.get(GUILD), None);
+        prefixes.store(GUILD, None);
+        assert_eq!(prefixes.get(GUILD), Some(None));
+        prefixes.store(GUILD, Some("!k".to_owned()));
+        assert_eq!(prefixes.get(GUILD), Some(Some("!k".to_owned())));
+    }
+
+    #[test]
+    fn test_expire() {
+        let prefixes = CommandPrefixes::new(Duration::ZERO);
+        prefixes.store(GUILD, Some("!k".to_owned()));
+        assert_eq!(prefixes.get(GUILD), None);
+    }
+
+    #[test]
+    fn test_strip_command_prefix() {
+        assert_eq!(strip_command_prefix("!k 10分後", "!k"), Some(" 10分後"));
+        assert_eq!(strip_command_prefix("!k", "!k"), Some(""));
+        assert_eq!(strip_command_prefix("!kaisan 10分後", "!k"), None);
+        assert_eq!(strip_command_prefix("hello", "!k"), None);
+    }
+}
@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use futures::lock::Mutex;
+use serenity::model::id::{ChannelId, GuildId, UserId};
+
+#[derive(Debug, Clone, Copy)]
+struct Presence {
+    channel_id: ChannelId,
+    joined_at: DateTime<Utc>,
+}
+
+/// When the members joined the voice channels they are in, as seen from the voice state updates.
+///
+/// Members who were already in voice when the bot started are unknown until they move.
+#[derive(Clone, Default)]
+pub struct PresenceTracker {
+    guilds: Arc<Mutex<HashMap<GuildId, HashMap<UserId, Presence>>>>,
+}
+
+impl PresenceTracker {
+    pub fn new() -> Self {
+        PresenceTracker::default()
+    }
+
+    /// Records the new voice channel of the member, or that they left voice if `channel_id` is
+    /// `None`. Staying in the same channel, such as muting, keeps the time they joined.
+    ///
+    /// Returns when the member joined the channel they have just left, if it is known.
+    pub async fn update(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+        channel_id: Option<ChannelId>,
+        time: DateTime<Utc>,
+    ) -> Option<DateTime<Utc>> {
+        let mut guilds = self.guilds.lock().await;
+        let members = guilds.entry(guild_id).or_default();
+        match channel_id {
+            None => members.remove(&user_id).map(|presence| presence.joined_at),
+            Some(channel_id) => {
+                if members
+                    .get(&user_id)
+                    .is_some_and(|presence| presence.channel_id == channel_id)
+                {
+                    return None;
+                }
+                let presence = Presence {
+                    channel_id,
+                    joined_at: time,
+                };
+                members
+                    .insert(user_id, presence)
+                    .map(|previous| previous.joined_at)
+            }
+        }
+    }
+
+    pub async fn joined_at(&self, guild_id: GuildId, user_id: UserId) -> Option<DateTime<Utc>> {
+        let guilds = self.guilds.lock().await;
+        guilds
+            .get(&guild_id)?
+            .get(&user_id)
+            .map(|presence| presence.joined_at)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PresenceTracker;
+
+    use chrono::{Duration, Utc};
+    use serenity::model::id::{ChannelId, GuildId, UserId};
+
+    const GUILD: GuildId = GuildId::new(1);
+    const USER: UserId = UserId::new(2);
+
+    #[tokio::test]
+    async fn test_update() {
+        let tracker = PresenceTracker::new();
+        let time = Utc::now();
+        assert_eq!(tracker.joined_at(GUILD, USER).await, None);
+
+        assert_eq!(
+            tracker
+                .update(GUILD, USER, Some(ChannelId::new(1)), time)
+                .await,
+            None
+        );
+        assert_eq!(
+            tracker
+                .update(
+                    GUILD,
+                    USER,
+                    Some(ChannelId::new(1)),
+                    time + Duration::minutes(1),
+                )
+                .await,
+            None
+        );
+        assert_eq!(tracker.joined_at(GUILD, USER).await, Some(time));
+
+        let moved = time + Duration::minutes(2);
+        assert_eq!(
+            tracker
+                .update(GUILD, USER, Some(ChannelId::new(2)), moved)
+                .await,
+            Some(time)
+        );
+        assert_eq!(tracker.joined_at(GUILD, USER).await, Some(moved));
+
+        assert_eq!(tracker.update(GUILD, USER, None, moved).await, Some(moved));
+        assert_eq!(tracker.joined_at(GUILD, USER).await, None);
+        assert_eq!(tracker.update(GUILD, USER, None, moved).await, None);
+    }
+}
@@ -0,0 +1,379 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use crate::database::DatabaseHandle;
+use crate::dispatcher::{DispatchPermit, DispatchStats, Dispatcher};
+use crate::error::Result;
+use crate::model::schedule::{Schedule, ScheduleId};
+use crate::soak::SoakMonitor;
+
+use anyhow::Context as _;
+use futures::lock::Mutex;
+use serenity::model::id::GuildId;
+use tokio::{sync::Notify, task::AbortHandle};
+
+const PERSISTED_SCHEDULES_KEY: &str = "schedules";
+
+struct Entry {
+    schedule: Schedule,
+    tasks: Vec<AbortHandle>,
+}
+
+impl Entry {
+    fn abort(&self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+/// Pending schedules of every guild, together with the tasks that carry them out.
+#[derive(Clone, Default)]
+pub struct ScheduleRegistry {
+    guilds: Arc<Mutex<HashMap<GuildId, BTreeMap<ScheduleId, Entry>>>>,
+    dispatcher: Dispatcher,
+    soak: SoakMonitor,
+    changed: Arc<Notify>,
+}
+
+impl ScheduleRegistry {
+    pub fn new() -> Self {
+        ScheduleRegistry::default()
+    }
+
+    pub fn with_dispatcher(dispatcher: Dispatcher) -> Self {
+        ScheduleRegistry {
+            guilds: Default::default(),
+            dispatcher,
+            soak: SoakMonitor::new(),
+            changed: Default::default(),
+        }
+    }
+
+    /// Waits for a slot to run the due work of the guild in.
+    pub async fn dispatch(&self, guild_id: GuildId) -> DispatchPermit {
+        self.dispatcher.acquire(guild_id).await
+    }
+
+    pub fn dispatch_stats(&self) -> DispatchStats {
+        self.dispatcher.stats()
+    }
+
+    /// The canary of the scheduled work, see [`crate::use_case::AdminSoak`].
+    pub fn soak(&self) -> &SoakMonitor {
+        &self.soak
+    }
+
+    /// Waits until a schedule is registered, changed or removed after the last call.
+    pub async fn changed(&self) {
+        self.changed.notified().await;
+    }
+
+    /// The schedule shown as the next kaisan of all the guilds, which is the earliest by
+    /// [`Schedule::public_time`].
+    pub async fn next_schedule(&self) -> Option<(GuildId, Schedule)> {
+        let guilds = self.guilds.lock().await;
+        guilds
+            .iter()
+            .flat_map(|(guild_id, entries)| {
+                entries
+                    .values()
+                    .map(move |entry| (*guild_id, &entry.schedule))
+            })
+            .min_by_key(|(_, schedule)| schedule.public_time())
+            .map(|(guild_id, schedule)| (guild_id, schedule.clone()))
+    }
+
+    pub async fn register(&self, guild_id: GuildId, schedule: Schedule) -> ScheduleId {
+        let mut guilds = self.guilds.lock().await;
+        let entries = guilds.entry(guild_id).or_default();
+        let id = (1..)
+            .map(ScheduleId::new)
+            .find(|id| !entries.contains_key(id))
+            .unwrap();
+        entries.insert(
+            id,
+            Entry {
+                schedule,
+                tasks: Vec::new(),
+            },
+        );
+        self.changed.notify_one();
+        id
+    }
+
+    pub async fn attach_tasks(&self, guild_id: GuildId, id: ScheduleId, tasks: Vec<AbortHandle>) {
+        let mut guilds = self.guilds.lock().await;
+        // the schedule may have already fired, in which case its tasks are left as they are
+        if let Some(entry) = guilds.get_mut(&guild_id).and_then(|e| e.get_mut(&id)) {
+            entry.tasks.extend(tasks);
+        }
+    }
+
+    pub async fn schedules(&self, guild_id: GuildId) -> Vec<(ScheduleId, Schedule)> {
+        match self.guilds.lock().await.get(&guild_id) {
+            Some(entries) => entries
+                .iter()
+                .map(|(id, entry)| (*id, entry.schedule.clone()))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Pending schedules of all the guilds, earliest first.
+    pub async fn all_schedules(&self) -> Vec<(GuildId, ScheduleId, Schedule)> {
+        let guilds = self.guilds.lock().await;
+        let mut schedules: Vec<_> = guilds
+            .iter()
+            .flat_map(|(guild_id, entries)| {
+                entries
+                    .iter()
+                    .map(|(id, entry)| (*guild_id, *id, entry.schedule.clone()))
+            })
+            .collect();
+        schedules.sort_by_key(|(_, _, schedule)| schedule.time);
+        schedules
+    }
+
+    /// Changes the pending schedule in place and returns the result, used for the changes that
+    /// are read when the schedule fires.
+    pub async fn update(
+        &self,
+        guild_id: GuildId,
+        id: ScheduleId,
+        f: impl FnOnce(&mut Schedule),
+    ) -> Option<Schedule> {
+        let mut guilds = self.guilds.lock().await;
+        let entry = guilds.get_mut(&guild_id)?.get_mut(&id)?;
+        f(&mut entry.schedule);
+        self.changed.notify_one();
+        Some(entry.schedule.clone())
+    }
+
+    /// Removes the schedule without aborting its tasks, used when the schedule fires.
+    pub async fn take(&self, guild_id: GuildId, id: ScheduleId) -> Option<Schedule> {
+        let mut guilds = self.guilds.lock().await;
+        let entry = guilds.get_mut(&guild_id)?.remove(&id)?;
+        self.changed.notify_one();
+        Some(entry.schedule)
+    }
+
+    /// Removes the schedule and aborts its pending tasks.
+    pub async fn cancel(&self, guild_id: GuildId, id: ScheduleId) -> Option<Schedule> {
+        let mut guilds = self.guilds.lock().await;
+        let entry = guilds.get_mut(&guild_id)?.remove(&id)?;
+        entry.abort();
+        self.changed.notify_one();
+        Some(entry.schedule)
+    }
+
+    /// Stops every pending schedule and writes them to the database so that they can be
+    /// restored with [`ScheduleRegistry::take_persisted`] on the next startup.
+    ///
+    /// A guild that cannot be written does not stop the others from being written. The first of
+    /// such errors is returned after all the guilds have been tried.
+    pub async fn persist<D: DatabaseHandle + Sync>(&self, database: &D) -> Result<()> {
+        let mut guilds = self.guilds.lock().await;
+        let mut result = Ok(());
+        for (guild_id, entries) in guilds.drain() {
+            let schedules: Vec<_> = entries
+                .into_values()
+                .map(|entry| {
+                    entry.abort();
+                    entry.schedule
+                })
+                .collect();
+            let written =
+                match serde_json::to_string(&schedules).context("cannot serialize schedules") {
+                    Ok(data) => database.set(guild_id, PERSISTED_SCHEDULES_KEY, data).await,
+                    Err(e) => Err(e.into()),
+                };
+            match written {
+                Ok(()) => {
+                    tracing::info!(%guild_id, count = schedules.len(), "persisted schedules");
+                }
+                Err(e) => {
+                    tracing::error!(
+                        %guild_id,
+                        count = schedules.len(),
+                        error = %e,
+                        "failed to persist schedules"
+                    );
+                    if result.is_ok() {
+                        result = Err(e);
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    pub async fn take_persisted<D: DatabaseHandle + Sync>(
+        database: &D,
+        guild_id: GuildId,
+    ) -> Result<Vec<Schedule>> {
+        let Some(data) = database
+            .get::<String>(guild_id, PERSISTED_SCHEDULES_KEY)
+            .await?
+        else {
+            return Ok(Vec::new());
+        };
+        // kept in the database if it cannot be read, so that it is not lost along the way
+        let schedules =
+            serde_json::from_str(&data).context("cannot deserialize persisted schedules")?;
+        database.delete(guild_id, PERSISTED_SCHEDULES_KEY).await?;
+        Ok(schedules)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ScheduleRegistry, PERSISTED_SCHEDULES_KEY};
+    use crate::database::{DatabaseHandle, InMemoryHandle};
+    use crate::model::{
+        kaisanee::KaisaneeSpecifier,
+        reminder::Reminder,
+        schedule::{Schedule, ScheduleId},
+    };
+    use crate::test::{mock_schedule, MOCK_AUTHOR_1, MOCK_MESSAGE_ID};
+
+    use chrono::{DateTime, Utc};
+    use serenity::model::id::GuildId;
+
+    const GUILD: GuildId = GuildId::new(1);
+
+    fn schedule() -> Schedule {
+        Schedule {
+            message_id: Some(MOCK_MESSAGE_ID),
+            kaisanee: KaisaneeSpecifier::Me,
+            reminders: vec![Reminder::before_minutes(5)],
+            ..mock_schedule(
+                MOCK_AUTHOR_1,
+                DateTime::parse_from_rfc3339("2024-07-20T13:15:00Z")
+                    .unwrap()
+                    .with_timezone(&Utc),
+            )
+        }
+    }
+
+    #[tokio::test]
+    async fn test_register() {
+        let registry = ScheduleRegistry::new();
+        let id1 = registry.register(GUILD, schedule()).await;
+        let id2 = registry.register(GUILD, schedule()).await;
+        assert_eq!(id1, ScheduleId::new(1));
+        assert_eq!(id2, ScheduleId::new(2));
+        assert_eq!(registry.schedules(GUILD).await.len(), 2);
+
+        assert_eq!(registry.take(GUILD, id1).await, Some(schedule()));
+        assert_eq!(registry.take(GUILD, id1).await, None);
+        assert_eq!(registry.register(GUILD, schedule()).await, id1);
+    }
+
+    #[tokio::test]
+    async fn test_all_schedules() {
+        let registry = ScheduleRegistry::new();
+        let later = Schedule {
+            time: schedule().time + chrono::Duration::hours(1),
+            ..schedule()
+        };
+        registry.register(GUILD, later.clone()).await;
+        registry.register(GuildId::new(2), schedule()).await;
+
+        assert_eq!(
+            registry.all_schedules().await,
+            vec![
+                (GuildId::new(2), ScheduleId::new(1), schedule()),
+                (GUILD, ScheduleId::new(1), later),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_next_schedule() {
+        let registry = ScheduleRegistry::new();
+        assert_eq!(registry.next_schedule().await, None);
+
+        // a random kaisan is shown by the end of its range
+        let random = Schedule {
+            time: schedule().time - chrono::Duration::hours(1),
+            random_until: Some(schedule().time + chrono::Duration::hours(1)),
+            ..schedule()
+        };
+        registry.register(GUILD, random).await;
+        registry.register(GuildId::new(2), schedule()).await;
+        assert_eq!(
+            registry.next_schedule().await,
+            Some((GuildId::new(2), schedule()))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_changed() {
+        let registry = ScheduleRegistry::new();
+        let id = registry.register(GUILD, schedule()).await;
+        // notified of the change made before waiting
+        registry.changed().await;
+
+        let waiting = tokio::spawn({
+            let registry = registry.clone();
+            async move { registry.changed().await }
+        });
+        registry.cancel(GUILD, id).await;
+        waiting.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_cancel() {
+        let registry = ScheduleRegistry::new();
+        let id = registry.register(GUILD, schedule()).await;
+        let task = tokio::spawn(futures::future::pending::<()>());
+        registry
+            .attach_tasks(GUILD, id, vec![task.abort_handle()])
+            .await;
+
+        assert_eq!(registry.cancel(GUILD, id).await, Some(schedule()));
+        assert!(task.await.unwrap_err().is_cancelled());
+        assert!(registry.schedules(GUILD).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_persist() {
+        let database = InMemoryHandle::new();
+        let registry = ScheduleRegistry::new();
+        registry.register(GUILD, schedule()).await;
+        registry.persist(&database).await.unwrap();
+        assert!(registry.schedules(GUILD).await.is_empty());
+
+        assert_eq!(
+            ScheduleRegistry::take_persisted(&database, GUILD)
+                .await
+                .unwrap(),
+            vec![schedule()]
+        );
+        assert!(ScheduleRegistry::take_persisted(&database, GUILD)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_take_persisted_invalid() {
+        let database = InMemoryHandle::new();
+        database
+            .set(GUILD, PERSISTED_SCHEDULES_KEY, "not json".to_owned())
+            .await
+            .unwrap();
+        assert!(ScheduleRegistry::take_persisted(&database, GUILD)
+            .await
+            .is_err());
+        assert_eq!(
+            database
+                .get::<String>(GUILD, PERSISTED_SCHEDULES_KEY)
+                .await
+                .unwrap()
+                .as_deref(),
+            Some("not json")
+        );
+    }
+}
@@ -52,9 +52,13 @@ impl Say for Duration {
         if self.num_hours() != 0 {
             write!(f, "{}時間", self.num_hours())?;
         }
-        if self.num_minutes() != 0 || self.num_hours() == 0 {
+        let seconds = self.num_seconds() % 60;
+        if self.num_minutes() != 0 || (self.num_hours() == 0 && seconds == 0) {
             write!(f, "{}分", self.num_minutes() % 60)?;
         }
+        if seconds != 0 {
+            write!(f, "{}秒", seconds)?;
+        }
         Ok(())
     }
 }
@@ -190,3 +194,93 @@ pub trait IntoIteratorSayExt: IntoIterator + Sized {
 }
 
 impl<I: IntoIterator> IntoIteratorSayExt for I {}
+
+/// A count followed by an English noun in the form the count requires, like `1 hour` or
+/// `2 hours`.
+pub struct Counted<'a> {
+    count: i64,
+    singular: &'a str,
+    plural: &'a str,
+}
+
+impl<'a> Counted<'a> {
+    pub fn new(count: i64, singular: &'a str, plural: &'a str) -> Counted<'a> {
+        Counted {
+            count,
+            singular,
+            plural,
+        }
+    }
+}
+
+impl Display for Counted<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let noun = if self.count.abs() == 1 {
+            self.singular
+        } else {
+            self.plural
+        };
+        write!(f, "{} {}", self.count, noun)
+    }
+}
+
+/// English counterpart of `Say for Duration`, like `1 hour 30 minutes`.
+pub struct EnglishDuration(pub Duration);
+
+impl Display for EnglishDuration {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let hours = self.0.num_hours();
+        let minutes = self.0.num_minutes() % 60;
+        let seconds = self.0.num_seconds() % 60;
+
+        let mut parts = Vec::new();
+        if hours != 0 {
+            parts.push(Counted::new(hours, "hour", "hours"));
+        }
+        if minutes != 0 || (hours == 0 && seconds == 0) {
+            parts.push(Counted::new(minutes, "minute", "minutes"));
+        }
+        if seconds != 0 {
+            parts.push(Counted::new(seconds, "second", "seconds"));
+        }
+
+        for (i, part) in parts.iter().enumerate() {
+            if i != 0 {
+                f.write_str(" ")?;
+            }
+            Display::fmt(part, f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Counted, EnglishDuration};
+    use chrono::Duration;
+
+    #[test]
+    fn test_counted() {
+        assert_eq!(Counted::new(1, "hour", "hours").to_string(), "1 hour");
+        assert_eq!(Counted::new(2, "hour", "hours").to_string(), "2 hours");
+        assert_eq!(
+            Counted::new(0, "minute", "minutes").to_string(),
+            "0 minutes"
+        );
+    }
+
+    #[test]
+    fn test_english_duration() {
+        let cases = [
+            (Duration::minutes(90), "1 hour 30 minutes"),
+            (Duration::hours(2), "2 hours"),
+            (Duration::minutes(1), "1 minute"),
+            (Duration::seconds(61), "1 minute 1 second"),
+            (Duration::seconds(30), "30 seconds"),
+            (Duration::zero(), "0 minutes"),
+        ];
+        for (duration, expected) in cases {
+            assert_eq!(EnglishDuration(duration).to_string(), expected);
+        }
+    }
+}
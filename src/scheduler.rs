@@ -0,0 +1,251 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use chrono::{DateTime, Duration, Utc};
+use tokio::sync::oneshot;
+
+/// The timer runs on the monotonic clock, which may stop while the host is suspended, so
+/// [`SystemScheduler`] wakes up now and then to look at the wall clock.
+const RECHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// How many times [`VirtualScheduler`] yields without seeing any task wake up or go to sleep
+/// before it takes the tasks as settled.
+const SETTLE_YIELDS: usize = 16;
+
+/// Where the scheduled work gets the time from and waits for it.
+#[async_trait::async_trait]
+pub trait Scheduler: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+    async fn sleep_until(&self, time: DateTime<Utc>);
+}
+
+/// The wall clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemScheduler;
+
+#[async_trait::async_trait]
+impl Scheduler for SystemScheduler {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+
+    async fn sleep_until(&self, time: DateTime<Utc>) {
+        while let Ok(duration) = (time - self.now()).to_std() {
+            if duration.is_zero() {
+                break;
+            }
+            tokio::time::sleep(duration.min(RECHECK_INTERVAL)).await;
+        }
+    }
+}
+
+type SleeperKey = (DateTime<Utc>, u64);
+
+struct State {
+    now: DateTime<Utc>,
+    next_id: u64,
+    sleepers: BTreeMap<SleeperKey, oneshot::Sender<()>>,
+    /// Sleepers that have been woken up but have not resumed yet.
+    waking: usize,
+    /// Bumped whenever a sleeper goes to sleep or resumes.
+    generation: u64,
+}
+
+/// A clock that only moves when told to, for tests.
+///
+/// Moving the clock wakes the tasks sleeping until then, and waits for them to run until they go
+/// to sleep again or finish. Tasks that wait for something else than the scheduler, such as I/O,
+/// are not waited for.
+#[derive(Clone)]
+pub struct VirtualScheduler {
+    state: Arc<Mutex<State>>,
+}
+
+impl VirtualScheduler {
+    pub fn new(now: DateTime<Utc>) -> Self {
+        VirtualScheduler {
+            state: Arc::new(Mutex::new(State {
+                now,
+                next_id: 0,
+                sleepers: BTreeMap::new(),
+                waking: 0,
+                generation: 0,
+            })),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap()
+    }
+
+    /// Number of the tasks sleeping until some time in the future.
+    pub fn pending_count(&self) -> usize {
+        self.state().sleepers.len()
+    }
+
+    /// The earliest time a task is sleeping until.
+    pub fn next_wake(&self) -> Option<DateTime<Utc>> {
+        self.state().sleepers.keys().next().map(|&(time, _)| time)
+    }
+
+    pub async fn advance(&self, duration: Duration) {
+        let time = self.now() + duration;
+        self.advance_to(time).await;
+    }
+
+    /// Moves the clock forward to `time`, stopping at every time a task sleeps until on the way,
+    /// as if the time passed as usual.
+    pub async fn advance_to(&self, time: DateTime<Utc>) {
+        while let Some(next) = self.next_wake().filter(|&next| next <= time) {
+            self.wake_until(next);
+            self.settle().await;
+        }
+        self.state().now = time;
+        self.settle().await;
+    }
+
+    /// Moves the clock to `time` at once, as if the host was suspended in the meantime. The tasks
+    /// that were sleeping until then all wake up late.
+    pub async fn jump_to(&self, time: DateTime<Utc>) {
+        self.state().now = time;
+        while self.next_wake().is_some_and(|next| next <= time) {
+            self.wake_until(time);
+            self.settle().await;
+        }
+        self.settle().await;
+    }
+
+    fn wake_until(&self, time: DateTime<Utc>) {
+        let mut state = self.state();
+        state.now = state.now.max(time);
+        let later = state.sleepers.split_off(&(time, u64::MAX));
+        let due = std::mem::replace(&mut state.sleepers, later);
+        state.waking += due.len();
+        for sender in due.into_values() {
+            let _ = sender.send(());
+        }
+    }
+
+    /// Lets the woken tasks run until they go to sleep again or finish.
+    pub async fn settle(&self) {
+        let mut idle = 0;
+        while idle < SETTLE_YIELDS {
+            let generation = self.state().generation;
+            tokio::task::yield_now().await;
+            let state = self.state();
+            if state.generation == generation && state.waking == 0 {
+                idle += 1;
+            } else {
+                idle = 0;
+            }
+        }
+    }
+}
+
+/// Removes the sleeper when the sleep is over or cancelled.
+struct Sleeper<'a> {
+    scheduler: &'a VirtualScheduler,
+    key: SleeperKey,
+}
+
+impl Drop for Sleeper<'_> {
+    fn drop(&mut self) {
+        let mut state = self.scheduler.state();
+        if state.sleepers.remove(&self.key).is_none() {
+            state.waking -= 1;
+        }
+        state.generation += 1;
+    }
+}
+
+#[async_trait::async_trait]
+impl Scheduler for VirtualScheduler {
+    fn now(&self) -> DateTime<Utc> {
+        self.state().now
+    }
+
+    async fn sleep_until(&self, time: DateTime<Utc>) {
+        let (sender, receiver) = oneshot::channel();
+        let sleeper = {
+            let mut state = self.state();
+            if state.now >= time {
+                return;
+            }
+            let key = (time, state.next_id);
+            state.next_id += 1;
+            state.sleepers.insert(key, sender);
+            state.generation += 1;
+            Sleeper {
+                scheduler: self,
+                key,
+            }
+        };
+        let _ = receiver.await;
+        drop(sleeper);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Scheduler, VirtualScheduler};
+
+    use std::sync::{Arc, Mutex};
+
+    use chrono::{Duration, Utc};
+
+    #[tokio::test]
+    async fn test_advance() {
+        let start = Utc::now();
+        let scheduler = VirtualScheduler::new(start);
+        let woken = Arc::new(Mutex::new(Vec::new()));
+        for minutes in [3, 1, 2] {
+            let scheduler = scheduler.clone();
+            let woken = Arc::clone(&woken);
+            tokio::spawn(async move {
+                scheduler
+                    .sleep_until(start + Duration::minutes(minutes))
+                    .await;
+                woken.lock().unwrap().push((minutes, scheduler.now()));
+            });
+        }
+        scheduler.settle().await;
+        assert_eq!(scheduler.pending_count(), 3);
+        assert_eq!(scheduler.next_wake(), Some(start + Duration::minutes(1)));
+
+        scheduler.advance(Duration::seconds(150)).await;
+        assert_eq!(scheduler.pending_count(), 1);
+        assert_eq!(scheduler.now(), start + Duration::seconds(150));
+        assert_eq!(
+            *woken.lock().unwrap(),
+            vec![
+                (1, start + Duration::minutes(1)),
+                (2, start + Duration::minutes(2))
+            ]
+        );
+
+        scheduler.jump_to(start + Duration::minutes(10)).await;
+        assert_eq!(scheduler.pending_count(), 0);
+        assert_eq!(
+            woken.lock().unwrap().last(),
+            Some(&(3, start + Duration::minutes(10)))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_sleep() {
+        let start = Utc::now();
+        let scheduler = VirtualScheduler::new(start);
+        let task = tokio::spawn({
+            let scheduler = scheduler.clone();
+            async move { scheduler.sleep_until(start + Duration::minutes(1)).await }
+        });
+        scheduler.settle().await;
+        assert_eq!(scheduler.pending_count(), 1);
+
+        task.abort();
+        scheduler.settle().await;
+        assert_eq!(scheduler.pending_count(), 0);
+        scheduler.advance(Duration::minutes(1)).await;
+        assert_eq!(scheduler.now(), start + Duration::minutes(1));
+    }
+}
@@ -0,0 +1,190 @@
+use std::net::{IpAddr, SocketAddr, ToSocketAddrs as _};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use crate::model::notification::Notification;
+
+use anyhow::{Context as _, Result};
+use hyper::client::connect::dns::Name;
+use reqwest::dns::{Addrs, Resolve, Resolving};
+use serde::Serialize;
+use serenity::model::id::GuildId;
+
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Destination of the notifications of a guild.
+#[async_trait::async_trait]
+pub trait NotificationSink {
+    async fn send(&self, guild_id: GuildId, notification: &Notification) -> Result<()>;
+}
+
+#[derive(Serialize)]
+struct Payload<'a> {
+    guild_id: GuildId,
+    #[serde(flatten)]
+    notification: &'a Notification,
+}
+
+/// Posts the notifications as JSON to a URL, which can be bridged to LINE, Slack and so on.
+#[derive(Debug, Clone)]
+pub struct WebhookSink {
+    url: String,
+}
+
+impl WebhookSink {
+    pub fn new(url: String) -> Self {
+        WebhookSink { url }
+    }
+}
+
+/// Whether the address is reachable from the internet, rather than one of the local network
+/// or the host the bot runs on.
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            // 100.64.0.0/10 is shared by the carrier-grade NATs
+            let shared = a == 100 && (b & 0xc0) == 64;
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || shared)
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public(ip.into()),
+            None => {
+                let first = ip.segments()[0];
+                let unique_local = (first & 0xfe00) == 0xfc00;
+                let link_local = (first & 0xffc0) == 0xfe80;
+                !(ip.is_loopback() || ip.is_unspecified() || unique_local || link_local)
+            }
+        },
+    }
+}
+
+/// Whether the notifications may be posted to the URL: https, to a host that is not a local
+/// address. The names are checked when they are resolved on each post, see [`PublicResolver`].
+pub fn is_allowed_url(url: &str) -> bool {
+    let Ok(url) = reqwest::Url::parse(url) else {
+        return false;
+    };
+    let Some(host) = url.host_str() else {
+        return false;
+    };
+    if url.scheme() != "https" || host.is_empty() || host.eq_ignore_ascii_case("localhost") {
+        return false;
+    }
+    // IPv6 addresses come in brackets
+    match host.trim_start_matches('[').trim_end_matches(']').parse() {
+        Ok(ip) => is_public(ip),
+        Err(_) => true,
+    }
+}
+
+/// Resolves the names only to public addresses, so that a webhook cannot reach the local
+/// network however its name is set up.
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let host = name.as_str().to_owned();
+        Box::pin(async move {
+            let resolved = tokio::task::spawn_blocking({
+                let host = host.clone();
+                move || (host.as_str(), 0).to_socket_addrs()
+            })
+            .await??;
+            let addrs: Vec<SocketAddr> = resolved.collect();
+            if addrs.iter().any(|addr| !is_public(addr.ip())) {
+                return Err(format!("{} resolves to a non-public address", host).into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+fn client() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .timeout(WEBHOOK_TIMEOUT)
+            .https_only(true)
+            // a redirect could lead to a local address
+            .redirect(reqwest::redirect::Policy::none())
+            .dns_resolver(Arc::new(PublicResolver))
+            .build()
+            .expect("cannot build http client")
+    })
+}
+
+#[async_trait::async_trait]
+impl NotificationSink for WebhookSink {
+    async fn send(&self, guild_id: GuildId, notification: &Notification) -> Result<()> {
+        let body = serde_json::to_vec(&Payload {
+            guild_id,
+            notification,
+        })
+        .context("cannot serialize notification")?;
+        // the URL may have been set before it was checked as it is now
+        if !is_allowed_url(&self.url) {
+            anyhow::bail!("webhook url is not allowed");
+        }
+        client()
+            .post(&self.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body)
+            .send()
+            .await
+            .context("cannot send webhook")?
+            .error_for_status()
+            .context("webhook returned an error")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_allowed_url, is_public};
+
+    #[test]
+    fn test_is_public() {
+        for ip in ["93.184.216.34", "2606:2800:220:1::1"] {
+            assert!(is_public(ip.parse().unwrap()), "{}", ip);
+        }
+        for ip in [
+            "127.0.0.1",
+            "10.0.0.1",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+        ] {
+            assert!(!is_public(ip.parse().unwrap()), "{}", ip);
+        }
+    }
+
+    #[test]
+    fn test_is_allowed_url() {
+        assert!(is_allowed_url("https://example.com/hooks/kaisan"));
+        assert!(is_allowed_url("https://93.184.216.34/hook"));
+        for url in [
+            "http://example.com/hook",
+            "ftp://example.com",
+            "https://",
+            "https://localhost:8080/hook",
+            "https://127.0.0.1/hook",
+            "https://169.254.169.254/latest/meta-data",
+            "https://[::1]/hook",
+        ] {
+            assert!(!is_allowed_url(url), "{}", url);
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::task::AbortHandle;
+
+/// A canary firing later or earlier than this is reported.
+pub const SOAK_TOLERANCE: Duration = Duration::from_secs(2);
+pub const MAX_SOAK_INTERVAL_MINUTES: u32 = 24 * 60;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SoakStats {
+    /// Minutes between the canaries, or `None` if the soak is not running.
+    pub interval_minutes: Option<u32>,
+    pub fired: u64,
+    /// Canaries that fired off their time by more than [`SOAK_TOLERANCE`].
+    pub off_time: u64,
+    pub mean_drift: Duration,
+    pub max_drift: Duration,
+}
+
+#[derive(Default)]
+struct State {
+    task: Option<AbortHandle>,
+    interval_minutes: Option<u32>,
+    fired: u64,
+    off_time: u64,
+    total_drift: Duration,
+    max_drift: Duration,
+}
+
+/// Keeps the soak, a no-op scheduled over and over to see that the scheduled work runs on time in
+/// production, along with how far off its time it has run.
+#[derive(Clone, Default)]
+pub struct SoakMonitor {
+    state: Arc<Mutex<State>>,
+}
+
+impl SoakMonitor {
+    pub fn new() -> Self {
+        SoakMonitor::default()
+    }
+
+    /// Replaces the running soak, if any, with the task, starting over the stats.
+    pub fn start(&self, interval_minutes: u32, task: AbortHandle) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(previous) = state.task.take() {
+            previous.abort();
+        }
+        *state = State {
+            task: Some(task),
+            interval_minutes: Some(interval_minutes),
+            ..State::default()
+        };
+    }
+
+    /// Returns `false` if the soak was not running. The stats are kept until the next start.
+    pub fn stop(&self) -> bool {
+        let mut state = self.state.lock().unwrap();
+        state.interval_minutes = None;
+        match state.task.take() {
+            Some(task) => {
+                task.abort();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Records a canary that fired `drift` off its time, returning whether it was within the
+    /// tolerance.
+    pub fn record(&self, drift: chrono::Duration) -> bool {
+        let drift = drift.abs().to_std().unwrap_or_default();
+        let mut state = self.state.lock().unwrap();
+        state.fired += 1;
+        state.total_drift += drift;
+        state.max_drift = state.max_drift.max(drift);
+        let on_time = drift <= SOAK_TOLERANCE;
+        if !on_time {
+            state.off_time += 1;
+        }
+        on_time
+    }
+
+    pub fn stats(&self) -> SoakStats {
+        let state = self.state.lock().unwrap();
+        SoakStats {
+            interval_minutes: state.interval_minutes,
+            fired: state.fired,
+            off_time: state.off_time,
+            mean_drift: Duration::from_micros(
+                state
+                    .total_drift
+                    .as_micros()
+                    .checked_div(state.fired.into())
+                    .unwrap_or(0) as u64,
+            ),
+            max_drift: state.max_drift,
+        }
+    }
+}
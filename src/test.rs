@@ -1,36 +1,86 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::{
-    atomic::{AtomicBool, Ordering},
+    atomic::{AtomicBool, AtomicU8, Ordering},
     Arc,
 };
+use std::time::Duration;
 
 use crate::context::{
-    BotContext, ChannelContext, GuildContext, MessageContext, RandomContext, SettingContext,
-    TimeContext,
+    BotContext, ChannelContext, EventContext, GuildContext, HealthContext, LinkContext,
+    MessageContext, NotificationContext, OwnerContext, PresenceContext, RandomContext,
+    ScheduleContext, SettingContext, TimeContext, VoiceContext, DEFAULT_LATE_GRACE_SECONDS,
 };
-use crate::error::Result;
-use crate::model::{message::Message, reminder::Reminder};
+use crate::database::{CacheStats, RetryStats, StorageUsage};
+use crate::dispatcher::{DispatchPermit, DispatchStats};
+use crate::error::{Error, Result};
+use crate::log::LogFilterHandle;
+use crate::model::{
+    event::DisconnectEvent,
+    kaisanee::KaisaneeSpecifier,
+    member::{self, MemberNames},
+    menu::SelectMenu,
+    message::Message,
+    notification::Notification,
+    reaction::Outcome,
+    reminder::Reminder,
+    schedule::{FrozenSchedule, Schedule, ScheduleId},
+    setting::{
+        AuthorLeavePolicy, ChannelOverride, ChannelOverrides, DstPolicy, DuplicatePolicy, Locale,
+        RandomDistribution, RevealRandom, Setting, WeekStart, DEFAULT_MAX_HORIZON_HOURS,
+        DEFAULT_MAX_SCHEDULES_PER_USER, DEFAULT_TONIGHT_HOUR, DEFAULT_UNDO_WINDOW_MINUTES,
+    },
+    template::ReminderTemplate,
+    time::Hour,
+};
+use crate::registry::ScheduleRegistry;
+use crate::scheduler::{Scheduler as _, VirtualScheduler};
+use crate::soak::SoakMonitor;
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use chrono_tz::Tz;
 use futures::lock::Mutex;
 use once_cell::sync::Lazy;
+use rand::{rngs::SmallRng, Rng, SeedableRng};
 use serenity::model::{
     channel::ReactionType,
-    id::{ChannelId, UserId},
+    id::{ChannelId, EmojiId, GuildId, MessageId, RoleId, UserId},
     permissions::Permissions,
 };
-use tokio::sync::{watch, Notify};
+use tokio::task::AbortHandle;
+
+mod load;
 
 pub const MOCK_BOT_ID: UserId = UserId::new(6455241911587596288);
+pub const MOCK_GUILD_ID: GuildId = GuildId::new(2904936186404814848);
 pub const MOCK_CHANNEL_ID: ChannelId = ChannelId::new(7933013268500803584);
+pub const MOCK_GUILD_NAME: &str = "mock guild";
+pub const MOCK_CHANNEL_NAME: &str = "mock-channel";
 pub const MOCK_VOICE_CHANNEL_ID: ChannelId = ChannelId::new(8549307414562138112);
+pub const MOCK_MESSAGE_ID: MessageId = MessageId::new(5261830174416322560);
 
 pub const MOCK_AUTHOR_1: UserId = UserId::new(17308610930080528384);
 pub const MOCK_AUTHOR_2: UserId = UserId::new(4081392650864611328);
 
 pub const FIXED_RANDOM: i64 = 12345;
 
+/// A schedule of everyone in the mock voice channel, for the tests to adjust with the struct update
+/// syntax.
+pub fn mock_schedule(author_id: UserId, time: DateTime<Utc>) -> Schedule {
+    Schedule {
+        author_id,
+        channel_id: MOCK_CHANNEL_ID,
+        message_id: None,
+        voice_channel_id: MOCK_VOICE_CHANNEL_ID,
+        kaisanee: KaisaneeSpecifier::All,
+        time,
+        random_until: None,
+        reminders: Vec::new(),
+        remind_only_me: false,
+        opted_out: Vec::new(),
+        created_at: None,
+    }
+}
+
 pub static MOCK_USERS: Lazy<HashMap<UserId, Permissions>> = Lazy::new(|| {
     let mut m = HashMap::new();
     m.insert(MOCK_AUTHOR_1, Permissions::empty());
@@ -45,22 +95,132 @@ pub static MOCK_VOICE_STATES: Lazy<HashMap<UserId, ChannelId>> = Lazy::new(|| {
     m
 });
 
+/// Source of the values returned from [`RandomContext::random_range`] once the scripted values
+/// run out.
+#[derive(Debug)]
+pub enum MockRandom {
+    /// `from + FIXED_RANDOM`, saturated at `to`
+    Fixed,
+    Seeded(SmallRng),
+}
+
+/// Latencies returned from [`HealthContext`], where `None` stands for a failure or a latency not
+/// measured yet.
+#[derive(Clone, Copy, Debug)]
+pub struct MockLatencies {
+    pub rest: Option<Duration>,
+    pub gateway: Option<Duration>,
+    pub database: Option<Duration>,
+}
+
+impl Default for MockLatencies {
+    fn default() -> Self {
+        MockLatencies {
+            rest: Some(Duration::from_millis(50)),
+            gateway: Some(Duration::from_millis(40)),
+            database: Some(Duration::from_millis(1)),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SentAttachment {
+    pub filename: String,
+    pub data: Vec<u8>,
+}
+
 #[derive(Clone)]
 pub struct MockContext {
+    pub guild_id: GuildId,
     pub author_id: UserId,
-    pub current_time_tx: Arc<watch::Sender<DateTime<Utc>>>,
-    pub current_time_rx: watch::Receiver<DateTime<Utc>>,
+    pub message_id: Option<MessageId>,
+    pub scheduler: VirtualScheduler,
     pub sent_messages: Arc<Mutex<Vec<Message>>>,
-    pub message_sent: Arc<Notify>,
+    pub direct_messages: Arc<Mutex<Vec<(UserId, Message)>>>,
+    /// Messages sent to other channels by [`ChannelContext::message_to`]
+    pub channel_messages: Arc<Mutex<Vec<(ChannelId, Message)>>>,
+    pub sent_attachments: Arc<Mutex<Vec<SentAttachment>>>,
+    /// Select menus attached to the messages in `sent_messages`
+    pub menus: Arc<Mutex<HashMap<MessageId, SelectMenu>>>,
     pub disconnected_users: Arc<Mutex<Vec<UserId>>>,
+    pub voice_states: Arc<Mutex<HashMap<UserId, ChannelId>>>,
+    /// When the users in `voice_states` joined, if known
+    pub joined_at: Arc<Mutex<HashMap<UserId, DateTime<Utc>>>>,
+    pub disconnect_events: Arc<Mutex<Vec<DisconnectEvent>>>,
+    pub complained_events: Arc<Mutex<HashSet<String>>>,
+    pub complaint_counts: Arc<Mutex<HashMap<UserId, u64>>>,
+    pub voice_times: Arc<Mutex<HashMap<(NaiveDate, UserId), u64>>>,
+    pub kaisaned_counts: Arc<Mutex<HashMap<(NaiveDate, UserId), u64>>>,
+    /// Commands counted for rate limiting, which are never reset
+    pub command_counts: Arc<Mutex<HashMap<UserId, u64>>>,
     pub added_reactions: Arc<Mutex<Vec<ReactionType>>>,
+    pub attachment: Arc<Mutex<Option<Vec<u8>>>>,
     pub requires_permission: Arc<AtomicBool>,
+    pub requires_permission_self: Arc<AtomicBool>,
     pub timezone: Arc<Mutex<Tz>>,
+    pub has_timezone: Arc<AtomicBool>,
+    pub timezone_notice_shown: Arc<AtomicBool>,
     pub reminders: Arc<Mutex<HashSet<Reminder>>>,
     pub reminds_random_kaisan: Arc<AtomicBool>,
+    pub auto_kaisan_hour: Arc<Mutex<Option<Hour>>>,
+    pub max_horizon_hours: Arc<AtomicU8>,
+    pub tonight_hour: Arc<Mutex<Hour>>,
+    pub max_schedules_per_user: Arc<AtomicU8>,
+    pub undo_window_minutes: Arc<AtomicU8>,
+    pub countdown: Arc<AtomicBool>,
+    pub author_leave_policy: Arc<Mutex<AuthorLeavePolicy>>,
+    pub reveal_random: Arc<Mutex<RevealRandom>>,
+    pub random_distribution: Arc<Mutex<RandomDistribution>>,
+    pub dst_policy: Arc<Mutex<DstPolicy>>,
+    pub week_start: Arc<Mutex<WeekStart>>,
+    pub locale: Arc<Mutex<Locale>>,
+    pub on_duplicate: Arc<Mutex<DuplicatePolicy>>,
+    pub allowed_channels: Arc<Mutex<HashSet<ChannelId>>>,
+    pub allowed_roles: Arc<Mutex<HashSet<RoleId>>>,
+    pub member_roles: Arc<Mutex<HashMap<UserId, Vec<RoleId>>>>,
+    pub command_prefix: Arc<Mutex<Option<String>>>,
+    pub api_token_hash: Arc<Mutex<Option<String>>>,
+    pub webhook_url: Arc<Mutex<Option<String>>>,
+    pub log_channel: Arc<Mutex<Option<ChannelId>>>,
+    pub reactions: Arc<Mutex<HashMap<Outcome, ReactionType>>>,
+    pub phrases: Arc<Mutex<HashMap<Outcome, String>>>,
+    /// Text channels of the guild the bot can post to
+    pub text_channels: Arc<Mutex<HashSet<ChannelId>>>,
+    /// Custom emojis of the guild
+    pub emojis: Arc<Mutex<HashSet<EmojiId>>>,
+    pub members: Arc<Mutex<Vec<MemberNames>>>,
+    pub notifications: Arc<std::sync::Mutex<Vec<Notification>>>,
+    pub cancel_links: Arc<AtomicBool>,
+    /// Channels the announcement is played in, or `None` if the bot has no announcement
+    pub voice_announcements: Arc<std::sync::Mutex<Option<Vec<ChannelId>>>>,
+    pub voice_join_fails: Arc<AtomicBool>,
+    pub reminder_template: Arc<Mutex<Option<ReminderTemplate>>>,
+    pub frozen_schedules: Arc<Mutex<Option<Vec<FrozenSchedule>>>>,
+    pub channel_overrides: Arc<Mutex<HashMap<ChannelId, ChannelOverrides>>>,
+    pub profiles: Arc<Mutex<HashMap<String, Setting>>>,
+    pub registry: ScheduleRegistry,
+    pub owners: Arc<std::sync::Mutex<HashSet<UserId>>>,
+    pub guild_ids: Arc<Mutex<Vec<GuildId>>>,
+    /// Guilds without a channel to announce in
+    pub silent_guilds: Arc<Mutex<HashSet<GuildId>>>,
+    pub announcements: Arc<Mutex<Vec<(GuildId, String)>>>,
+    pub storage_usage: Arc<Mutex<HashMap<GuildId, StorageUsage>>>,
+    pub late_grace: chrono::Duration,
+    pub log_filter: Option<LogFilterHandle>,
+    pub latencies: Arc<Mutex<MockLatencies>>,
+    pub random: Arc<Mutex<MockRandom>>,
+    pub scripted_random: Arc<Mutex<VecDeque<i64>>>,
 }
 
 impl MockContext {
+    async fn react_outcome(&self, outcome: Outcome) -> Result<()> {
+        self.react(self.reaction(outcome).await?).await?;
+        if let Some(phrase) = self.phrase(outcome).await? {
+            self.message(Message::Phrase(phrase)).await?;
+        }
+        Ok(())
+    }
+
     pub fn new() -> MockContext {
         MockContext::with_author(MOCK_AUTHOR_2)
     }
@@ -74,39 +234,97 @@ impl MockContext {
     }
 
     pub fn with_author_current_time(author_id: UserId, current_time: DateTime<Utc>) -> MockContext {
-        let (tx, rx) = watch::channel(current_time);
         MockContext {
+            guild_id: MOCK_GUILD_ID,
             author_id,
-            current_time_tx: Arc::new(tx),
-            current_time_rx: rx,
+            message_id: Some(MOCK_MESSAGE_ID),
+            scheduler: VirtualScheduler::new(current_time),
             sent_messages: Arc::new(Mutex::new(Vec::new())),
-            message_sent: Arc::new(Notify::new()),
+            direct_messages: Arc::new(Mutex::new(Vec::new())),
+            channel_messages: Arc::new(Mutex::new(Vec::new())),
+            sent_attachments: Arc::new(Mutex::new(Vec::new())),
+            menus: Arc::new(Mutex::new(HashMap::new())),
             disconnected_users: Arc::new(Mutex::new(Vec::new())),
+            voice_states: Arc::new(Mutex::new(MOCK_VOICE_STATES.clone())),
+            joined_at: Arc::new(Mutex::new(HashMap::new())),
+            disconnect_events: Arc::new(Mutex::new(Vec::new())),
+            complained_events: Arc::new(Mutex::new(HashSet::new())),
+            complaint_counts: Arc::new(Mutex::new(HashMap::new())),
+            voice_times: Arc::new(Mutex::new(HashMap::new())),
+            kaisaned_counts: Arc::new(Mutex::new(HashMap::new())),
+            command_counts: Arc::new(Mutex::new(HashMap::new())),
             added_reactions: Arc::new(Mutex::new(Vec::new())),
+            attachment: Arc::new(Mutex::new(None)),
             requires_permission: Arc::new(AtomicBool::new(true)),
+            requires_permission_self: Arc::new(AtomicBool::new(false)),
             timezone: Arc::new(Mutex::new(Tz::Japan)),
+            has_timezone: Arc::new(AtomicBool::new(false)),
+            timezone_notice_shown: Arc::new(AtomicBool::new(false)),
             reminders: Arc::new(Mutex::new(
                 vec![Reminder::before_minutes(5)].into_iter().collect(),
             )),
             reminds_random_kaisan: Arc::new(AtomicBool::new(false)),
+            auto_kaisan_hour: Arc::new(Mutex::new(None)),
+            max_horizon_hours: Arc::new(AtomicU8::new(DEFAULT_MAX_HORIZON_HOURS)),
+            tonight_hour: Arc::new(Mutex::new(Hour::from_u8(DEFAULT_TONIGHT_HOUR).unwrap())),
+            max_schedules_per_user: Arc::new(AtomicU8::new(DEFAULT_MAX_SCHEDULES_PER_USER)),
+            undo_window_minutes: Arc::new(AtomicU8::new(DEFAULT_UNDO_WINDOW_MINUTES)),
+            countdown: Arc::new(AtomicBool::new(false)),
+            author_leave_policy: Arc::new(Mutex::new(AuthorLeavePolicy::default())),
+            reveal_random: Arc::new(Mutex::new(RevealRandom::default())),
+            random_distribution: Arc::new(Mutex::new(RandomDistribution::default())),
+            dst_policy: Arc::new(Mutex::new(DstPolicy::default())),
+            week_start: Arc::new(Mutex::new(WeekStart::default())),
+            locale: Arc::new(Mutex::new(Locale::default())),
+            on_duplicate: Arc::new(Mutex::new(DuplicatePolicy::default())),
+            allowed_channels: Arc::new(Mutex::new(HashSet::new())),
+            allowed_roles: Arc::new(Mutex::new(HashSet::new())),
+            member_roles: Arc::new(Mutex::new(HashMap::new())),
+            command_prefix: Arc::new(Mutex::new(None)),
+            api_token_hash: Arc::new(Mutex::new(None)),
+            webhook_url: Arc::new(Mutex::new(None)),
+            log_channel: Arc::new(Mutex::new(None)),
+            reactions: Arc::new(Mutex::new(HashMap::new())),
+            phrases: Arc::new(Mutex::new(HashMap::new())),
+            text_channels: Arc::new(Mutex::new(HashSet::from([MOCK_CHANNEL_ID]))),
+            emojis: Arc::new(Mutex::new(HashSet::new())),
+            members: Arc::new(Mutex::new(Vec::new())),
+            notifications: Arc::new(std::sync::Mutex::new(Vec::new())),
+            cancel_links: Arc::new(AtomicBool::new(false)),
+            voice_announcements: Arc::new(std::sync::Mutex::new(None)),
+            voice_join_fails: Arc::new(AtomicBool::new(false)),
+            reminder_template: Arc::new(Mutex::new(None)),
+            frozen_schedules: Arc::new(Mutex::new(None)),
+            channel_overrides: Arc::new(Mutex::new(HashMap::new())),
+            profiles: Arc::new(Mutex::new(HashMap::new())),
+            registry: ScheduleRegistry::new(),
+            owners: Arc::new(std::sync::Mutex::new(HashSet::new())),
+            guild_ids: Arc::new(Mutex::new(vec![MOCK_GUILD_ID])),
+            silent_guilds: Arc::new(Mutex::new(HashSet::new())),
+            announcements: Arc::new(Mutex::new(Vec::new())),
+            storage_usage: Arc::new(Mutex::new(HashMap::new())),
+            late_grace: chrono::Duration::seconds(DEFAULT_LATE_GRACE_SECONDS),
+            log_filter: None,
+            latencies: Arc::new(Mutex::new(MockLatencies::default())),
+            random: Arc::new(Mutex::new(MockRandom::Fixed)),
+            scripted_random: Arc::new(Mutex::new(VecDeque::new())),
         }
     }
 
-    pub fn set_current_time(&self, time: DateTime<Utc>) {
-        let _ = self.current_time_tx.send(time);
+    /// Makes [`RandomContext::random_range`] return the given values in order.
+    pub async fn script_random(&self, values: impl IntoIterator<Item = i64>) {
+        self.scripted_random.lock().await.extend(values);
     }
 
-    pub async fn wait_for_message<F>(&self, f: F)
+    pub async fn seed_random(&self, seed: u64) {
+        *self.random.lock().await = MockRandom::Seeded(SmallRng::seed_from_u64(seed));
+    }
+
+    pub async fn has_sent<F>(&self, f: F) -> bool
     where
         F: Fn(&Message) -> bool,
     {
-        loop {
-            self.message_sent.notified().await;
-            let messages = self.sent_messages.lock().await.clone();
-            if messages.into_iter().find(&f).is_some() {
-                break;
-            }
-        }
+        self.sent_messages.lock().await.iter().any(f)
     }
 }
 
@@ -118,17 +336,35 @@ impl BotContext for MockContext {
 
 #[async_trait::async_trait]
 impl GuildContext for MockContext {
+    fn guild_id(&self) -> GuildId {
+        self.guild_id
+    }
+
+    async fn guild_name(&self) -> Result<String> {
+        Ok(MOCK_GUILD_NAME.to_owned())
+    }
+
     async fn member_permissions(&self, user_id: UserId) -> Result<Permissions> {
         Ok(MOCK_USERS[&user_id])
     }
 
+    async fn member_roles(&self, user_id: UserId) -> Result<Vec<RoleId>> {
+        Ok(self
+            .member_roles
+            .lock()
+            .await
+            .get(&user_id)
+            .cloned()
+            .unwrap_or_default())
+    }
+
     async fn connected_voice_channel(&self, user_id: UserId) -> Result<Option<ChannelId>> {
-        Ok(MOCK_VOICE_STATES.get(&user_id).copied())
+        Ok(self.voice_states.lock().await.get(&user_id).copied())
     }
 
     async fn voice_channel_users(&self, channel_id: ChannelId) -> Result<Vec<UserId>> {
         let mut users = Vec::new();
-        for (user_id, state_channel_id) in MOCK_VOICE_STATES.iter() {
+        for (user_id, state_channel_id) in self.voice_states.lock().await.iter() {
             if state_channel_id == &channel_id {
                 users.push(*user_id);
             }
@@ -140,6 +376,22 @@ impl GuildContext for MockContext {
         self.disconnected_users.lock().await.push(user_id);
         Ok(())
     }
+
+    async fn has_emoji(&self, emoji_id: EmojiId) -> Result<bool> {
+        Ok(self.emojis.lock().await.contains(&emoji_id))
+    }
+
+    async fn can_post_to(&self, channel_id: ChannelId) -> Result<bool> {
+        Ok(self.text_channels.lock().await.contains(&channel_id))
+    }
+
+    async fn find_member_by_name(&self, name: &str) -> Result<Vec<MemberNames>> {
+        let members = self.members.lock().await;
+        Ok(member::find_by_name(name, &members)
+            .into_iter()
+            .cloned()
+            .collect())
+    }
 }
 
 #[async_trait::async_trait]
@@ -148,11 +400,73 @@ impl ChannelContext for MockContext {
         MOCK_CHANNEL_ID
     }
 
+    async fn channel_name(&self) -> Result<String> {
+        Ok(MOCK_CHANNEL_NAME.to_owned())
+    }
+
     async fn message(&self, message: Message) -> Result<()> {
         self.sent_messages.lock().await.push(message);
-        self.message_sent.notify_one();
         Ok(())
     }
+
+    async fn post_message(&self, message: Message) -> Result<MessageId> {
+        let mut sent_messages = self.sent_messages.lock().await;
+        sent_messages.push(message);
+        Ok(MessageId::new(sent_messages.len() as u64))
+    }
+
+    async fn edit_message(&self, message_id: MessageId, message: Message) -> Result<()> {
+        let index = message_id.get() as usize - 1;
+        self.sent_messages.lock().await[index] = message;
+        Ok(())
+    }
+
+    async fn post_menu(&self, message: Message, menu: SelectMenu) -> Result<MessageId> {
+        let message_id = self.post_message(message).await?;
+        self.menus.lock().await.insert(message_id, menu);
+        Ok(message_id)
+    }
+
+    async fn edit_menu(
+        &self,
+        message_id: MessageId,
+        message: Message,
+        menu: Option<SelectMenu>,
+    ) -> Result<()> {
+        self.edit_message(message_id, message).await?;
+        let mut menus = self.menus.lock().await;
+        match menu {
+            Some(menu) => menus.insert(message_id, menu),
+            None => menus.remove(&message_id),
+        };
+        Ok(())
+    }
+
+    async fn message_to(&self, channel_id: ChannelId, message: Message) -> Result<()> {
+        self.channel_messages
+            .lock()
+            .await
+            .push((channel_id, message));
+        Ok(())
+    }
+
+    async fn direct_message(&self, user_id: UserId, message: Message) -> Result<()> {
+        self.direct_messages.lock().await.push((user_id, message));
+        Ok(())
+    }
+
+    async fn message_with_attachment(
+        &self,
+        message: Message,
+        filename: &str,
+        data: Vec<u8>,
+    ) -> Result<()> {
+        self.sent_attachments.lock().await.push(SentAttachment {
+            filename: filename.to_owned(),
+            data,
+        });
+        self.message(message).await
+    }
 }
 
 #[async_trait::async_trait]
@@ -161,20 +475,59 @@ impl MessageContext for MockContext {
         self.author_id
     }
 
+    fn message_id(&self) -> Option<MessageId> {
+        self.message_id
+    }
+
     async fn react(&self, reaction: impl Into<ReactionType> + 'async_trait + Send) -> Result<()> {
         self.added_reactions.lock().await.push(reaction.into());
         Ok(())
     }
+
+    async fn react_success(&self) -> Result<()> {
+        self.react_outcome(Outcome::Success).await
+    }
+
+    async fn react_failure(&self) -> Result<()> {
+        self.react_outcome(Outcome::Failure).await
+    }
+
+    async fn attachment(&self, max_bytes: u32) -> Result<Option<Vec<u8>>> {
+        let attachment = self.attachment.lock().await.clone();
+        if attachment
+            .as_ref()
+            .is_some_and(|data| data.len() > max_bytes as usize)
+        {
+            return Err(Error::AttachmentTooLarge { max_bytes });
+        }
+        Ok(attachment)
+    }
 }
 
 #[async_trait::async_trait]
 impl RandomContext for MockContext {
     async fn random_range(&self, from: i64, to: i64) -> i64 {
-        let r = from + FIXED_RANDOM;
-        if r >= to {
-            to
-        } else {
-            r
+        if let Some(value) = self.scripted_random.lock().await.pop_front() {
+            assert!(
+                (from..to).contains(&value),
+                "scripted random value {} is out of {}..{}",
+                value,
+                from,
+                to
+            );
+            return value;
+        }
+
+        match &mut *self.random.lock().await {
+            MockRandom::Fixed => {
+                let r = from + FIXED_RANDOM;
+                if r >= to {
+                    to
+                } else {
+                    r
+                }
+            }
+            MockRandom::Seeded(rng) => rng.gen_range(from..to),
         }
     }
 }
@@ -182,22 +535,15 @@ impl RandomContext for MockContext {
 #[async_trait::async_trait]
 impl TimeContext for MockContext {
     fn current_time(&self) -> DateTime<Utc> {
-        *self.current_time_rx.borrow()
+        self.scheduler.now()
     }
 
     async fn delay_until(&self, time: DateTime<Utc>) {
-        if self.current_time() >= time {
-            return;
-        }
+        self.scheduler.sleep_until(time).await;
+    }
 
-        let rx = self.current_time_rx.clone();
-        let mut rx = tokio_stream::wrappers::WatchStream::new(rx);
-        use futures::StreamExt as _;
-        while let Some(new_time) = rx.next().await {
-            if new_time >= time {
-                return;
-            }
-        }
+    fn late_grace(&self) -> chrono::Duration {
+        self.late_grace
     }
 }
 
@@ -205,13 +551,28 @@ impl TimeContext for MockContext {
 impl SettingContext for MockContext {
     async fn set_timezone(&self, timezone: Tz) -> Result<()> {
         *self.timezone.lock().await = timezone;
+        self.has_timezone.store(true, Ordering::SeqCst);
         Ok(())
     }
 
+    async fn replace_timezone(&self, current: Tz, timezone: Tz) -> Result<bool> {
+        let mut stored = self.timezone.lock().await;
+        if *stored != current {
+            return Ok(false);
+        }
+        *stored = timezone;
+        self.has_timezone.store(true, Ordering::SeqCst);
+        Ok(true)
+    }
+
     async fn timezone(&self) -> Result<Tz> {
         Ok(*self.timezone.lock().await)
     }
 
+    async fn has_timezone(&self) -> Result<bool> {
+        Ok(self.has_timezone.load(Ordering::SeqCst))
+    }
+
     async fn set_requires_permission(&self, requires_permission: bool) -> Result<()> {
         self.requires_permission
             .store(requires_permission, Ordering::SeqCst);
@@ -222,6 +583,16 @@ impl SettingContext for MockContext {
         Ok(self.requires_permission.load(Ordering::SeqCst))
     }
 
+    async fn set_requires_permission_self(&self, requires_permission_self: bool) -> Result<()> {
+        self.requires_permission_self
+            .store(requires_permission_self, Ordering::SeqCst);
+        Ok(())
+    }
+
+    async fn requires_permission_self(&self) -> Result<bool> {
+        Ok(self.requires_permission_self.load(Ordering::SeqCst))
+    }
+
     async fn reminders(&self) -> Result<HashSet<Reminder>> {
         Ok(self.reminders.lock().await.clone())
     }
@@ -243,4 +614,498 @@ impl SettingContext for MockContext {
     async fn reminds_random_kaisan(&self) -> Result<bool> {
         Ok(self.reminds_random_kaisan.load(Ordering::SeqCst))
     }
+
+    async fn auto_kaisan_hour(&self) -> Result<Option<Hour>> {
+        Ok(*self.auto_kaisan_hour.lock().await)
+    }
+
+    async fn set_auto_kaisan_hour(&self, hour: Option<Hour>) -> Result<()> {
+        *self.auto_kaisan_hour.lock().await = hour;
+        Ok(())
+    }
+
+    async fn max_horizon_hours(&self) -> Result<u8> {
+        Ok(self.max_horizon_hours.load(Ordering::SeqCst))
+    }
+
+    async fn set_max_horizon_hours(&self, hours: u8) -> Result<()> {
+        self.max_horizon_hours.store(hours, Ordering::SeqCst);
+        Ok(())
+    }
+
+    async fn tonight_hour(&self) -> Result<Hour> {
+        Ok(*self.tonight_hour.lock().await)
+    }
+
+    async fn set_tonight_hour(&self, hour: Hour) -> Result<()> {
+        *self.tonight_hour.lock().await = hour;
+        Ok(())
+    }
+
+    async fn max_schedules_per_user(&self) -> Result<u8> {
+        Ok(self.max_schedules_per_user.load(Ordering::SeqCst))
+    }
+
+    async fn set_max_schedules_per_user(&self, count: u8) -> Result<()> {
+        self.max_schedules_per_user.store(count, Ordering::SeqCst);
+        Ok(())
+    }
+
+    async fn undo_window_minutes(&self) -> Result<u8> {
+        Ok(self.undo_window_minutes.load(Ordering::SeqCst))
+    }
+
+    async fn set_undo_window_minutes(&self, minutes: u8) -> Result<()> {
+        self.undo_window_minutes.store(minutes, Ordering::SeqCst);
+        Ok(())
+    }
+
+    async fn countdown(&self) -> Result<bool> {
+        Ok(self.countdown.load(Ordering::SeqCst))
+    }
+
+    async fn set_countdown(&self, countdown: bool) -> Result<()> {
+        self.countdown.store(countdown, Ordering::SeqCst);
+        Ok(())
+    }
+
+    async fn author_leave_policy(&self) -> Result<AuthorLeavePolicy> {
+        Ok(*self.author_leave_policy.lock().await)
+    }
+
+    async fn set_author_leave_policy(&self, policy: AuthorLeavePolicy) -> Result<()> {
+        *self.author_leave_policy.lock().await = policy;
+        Ok(())
+    }
+
+    async fn reveal_random(&self) -> Result<RevealRandom> {
+        Ok(*self.reveal_random.lock().await)
+    }
+
+    async fn set_reveal_random(&self, reveal_random: RevealRandom) -> Result<()> {
+        *self.reveal_random.lock().await = reveal_random;
+        Ok(())
+    }
+
+    async fn random_distribution(&self) -> Result<RandomDistribution> {
+        Ok(*self.random_distribution.lock().await)
+    }
+
+    async fn set_random_distribution(&self, distribution: RandomDistribution) -> Result<()> {
+        *self.random_distribution.lock().await = distribution;
+        Ok(())
+    }
+
+    async fn dst_policy(&self) -> Result<DstPolicy> {
+        Ok(*self.dst_policy.lock().await)
+    }
+
+    async fn set_dst_policy(&self, policy: DstPolicy) -> Result<()> {
+        *self.dst_policy.lock().await = policy;
+        Ok(())
+    }
+
+    async fn week_start(&self) -> Result<WeekStart> {
+        Ok(*self.week_start.lock().await)
+    }
+
+    async fn set_week_start(&self, week_start: WeekStart) -> Result<()> {
+        *self.week_start.lock().await = week_start;
+        Ok(())
+    }
+
+    async fn locale(&self) -> Result<Locale> {
+        Ok(*self.locale.lock().await)
+    }
+
+    async fn set_locale(&self, locale: Locale) -> Result<()> {
+        *self.locale.lock().await = locale;
+        Ok(())
+    }
+
+    async fn on_duplicate(&self) -> Result<DuplicatePolicy> {
+        Ok(*self.on_duplicate.lock().await)
+    }
+
+    async fn set_on_duplicate(&self, on_duplicate: DuplicatePolicy) -> Result<()> {
+        *self.on_duplicate.lock().await = on_duplicate;
+        Ok(())
+    }
+
+    async fn allowed_channels(&self) -> Result<HashSet<ChannelId>> {
+        Ok(self.allowed_channels.lock().await.clone())
+    }
+
+    async fn allow_channel(&self, channel_id: ChannelId) -> Result<bool> {
+        Ok(self.allowed_channels.lock().await.insert(channel_id))
+    }
+
+    async fn deny_channel(&self, channel_id: ChannelId) -> Result<bool> {
+        Ok(self.allowed_channels.lock().await.remove(&channel_id))
+    }
+
+    async fn allowed_roles(&self) -> Result<HashSet<RoleId>> {
+        Ok(self.allowed_roles.lock().await.clone())
+    }
+
+    async fn allow_role(&self, role_id: RoleId) -> Result<bool> {
+        Ok(self.allowed_roles.lock().await.insert(role_id))
+    }
+
+    async fn deny_role(&self, role_id: RoleId) -> Result<bool> {
+        Ok(self.allowed_roles.lock().await.remove(&role_id))
+    }
+
+    async fn command_prefix(&self) -> Result<Option<String>> {
+        Ok(self.command_prefix.lock().await.clone())
+    }
+
+    async fn set_command_prefix(&self, prefix: Option<String>) -> Result<()> {
+        *self.command_prefix.lock().await = prefix;
+        Ok(())
+    }
+
+    async fn reminder_template(&self) -> Result<Option<ReminderTemplate>> {
+        Ok(self.reminder_template.lock().await.clone())
+    }
+
+    async fn set_reminder_template(&self, template: Option<ReminderTemplate>) -> Result<()> {
+        *self.reminder_template.lock().await = template;
+        Ok(())
+    }
+
+    async fn frozen_schedules(&self) -> Result<Option<Vec<FrozenSchedule>>> {
+        Ok(self.frozen_schedules.lock().await.clone())
+    }
+
+    async fn set_frozen_schedules(&self, frozen: Option<Vec<FrozenSchedule>>) -> Result<()> {
+        *self.frozen_schedules.lock().await = frozen;
+        Ok(())
+    }
+
+    async fn profile_names(&self) -> Result<HashSet<String>> {
+        Ok(self.profiles.lock().await.keys().cloned().collect())
+    }
+
+    async fn profile(&self, name: &str) -> Result<Option<Setting>> {
+        Ok(self.profiles.lock().await.get(name).cloned())
+    }
+
+    async fn save_profile(&self, name: &str, setting: &Setting) -> Result<()> {
+        self.profiles
+            .lock()
+            .await
+            .insert(name.to_owned(), setting.clone());
+        Ok(())
+    }
+
+    async fn channel_overrides(&self, voice_channel_id: ChannelId) -> Result<ChannelOverrides> {
+        Ok(self
+            .channel_overrides
+            .lock()
+            .await
+            .get(&voice_channel_id)
+            .copied()
+            .unwrap_or_default())
+    }
+
+    async fn set_channel_override(
+        &self,
+        voice_channel_id: ChannelId,
+        setting: ChannelOverride,
+    ) -> Result<()> {
+        let mut channel_overrides = self.channel_overrides.lock().await;
+        let overrides = channel_overrides.entry(voice_channel_id).or_default();
+        overrides.apply(setting);
+        if overrides.is_empty() {
+            channel_overrides.remove(&voice_channel_id);
+        }
+        Ok(())
+    }
+
+    async fn api_token_hash(&self) -> Result<Option<String>> {
+        Ok(self.api_token_hash.lock().await.clone())
+    }
+
+    async fn set_api_token_hash(&self, hash: Option<String>) -> Result<()> {
+        *self.api_token_hash.lock().await = hash;
+        Ok(())
+    }
+
+    async fn webhook_url(&self) -> Result<Option<String>> {
+        Ok(self.webhook_url.lock().await.clone())
+    }
+
+    async fn set_webhook_url(&self, url: Option<String>) -> Result<()> {
+        *self.webhook_url.lock().await = url;
+        Ok(())
+    }
+
+    async fn log_channel(&self) -> Result<Option<ChannelId>> {
+        Ok(*self.log_channel.lock().await)
+    }
+
+    async fn set_log_channel(&self, channel_id: Option<ChannelId>) -> Result<()> {
+        *self.log_channel.lock().await = channel_id;
+        Ok(())
+    }
+
+    async fn reaction(&self, outcome: Outcome) -> Result<ReactionType> {
+        Ok(self
+            .reactions
+            .lock()
+            .await
+            .get(&outcome)
+            .cloned()
+            .unwrap_or_else(|| outcome.default_reaction()))
+    }
+
+    async fn set_reaction(&self, outcome: Outcome, reaction: Option<ReactionType>) -> Result<()> {
+        let mut reactions = self.reactions.lock().await;
+        match reaction {
+            None => reactions.remove(&outcome),
+            Some(reaction) => reactions.insert(outcome, reaction),
+        };
+        Ok(())
+    }
+
+    async fn phrase(&self, outcome: Outcome) -> Result<Option<String>> {
+        Ok(self.phrases.lock().await.get(&outcome).cloned())
+    }
+
+    async fn set_phrase(&self, outcome: Outcome, phrase: Option<String>) -> Result<()> {
+        let mut phrases = self.phrases.lock().await;
+        match phrase {
+            None => phrases.remove(&outcome),
+            Some(phrase) => phrases.insert(outcome, phrase),
+        };
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl EventContext for MockContext {
+    async fn record_disconnect(&self, event: DisconnectEvent) -> Result<()> {
+        self.disconnect_events.lock().await.insert(0, event);
+        Ok(())
+    }
+
+    async fn disconnect_events(&self) -> Result<Vec<DisconnectEvent>> {
+        Ok(self.disconnect_events.lock().await.clone())
+    }
+
+    async fn add_complaint(&self, event: &DisconnectEvent, since: DateTime<Utc>) -> Result<bool> {
+        let mut complained_events = self.complained_events.lock().await;
+        complained_events.retain(|key| DisconnectEvent::key_time(key).is_some_and(|t| t >= since));
+        if !complained_events.insert(event.key()) {
+            return Ok(false);
+        }
+        *self
+            .complaint_counts
+            .lock()
+            .await
+            .entry(event.requester)
+            .or_default() += 1;
+        Ok(true)
+    }
+
+    async fn complaint_counts(&self) -> Result<HashMap<UserId, u64>> {
+        Ok(self.complaint_counts.lock().await.clone())
+    }
+
+    async fn record_command(&self, user_id: UserId, _window: std::time::Duration) -> Result<u64> {
+        let mut counts = self.command_counts.lock().await;
+        let count = counts.entry(user_id).or_default();
+        *count += 1;
+        Ok(*count)
+    }
+
+    async fn record_timezone_notice(&self) -> Result<bool> {
+        Ok(!self.timezone_notice_shown.swap(true, Ordering::SeqCst))
+    }
+
+    async fn record_voice_time(
+        &self,
+        user_id: UserId,
+        date: NaiveDate,
+        seconds: u64,
+    ) -> Result<()> {
+        *self
+            .voice_times
+            .lock()
+            .await
+            .entry((date, user_id))
+            .or_default() += seconds;
+        Ok(())
+    }
+
+    async fn voice_times(&self, date: NaiveDate) -> Result<HashMap<UserId, u64>> {
+        Ok(by_date(&*self.voice_times.lock().await, date))
+    }
+
+    async fn record_kaisaned(&self, user_id: UserId, date: NaiveDate) -> Result<()> {
+        *self
+            .kaisaned_counts
+            .lock()
+            .await
+            .entry((date, user_id))
+            .or_default() += 1;
+        Ok(())
+    }
+
+    async fn kaisaned_counts(&self, date: NaiveDate) -> Result<HashMap<UserId, u64>> {
+        Ok(by_date(&*self.kaisaned_counts.lock().await, date))
+    }
+}
+
+fn by_date(counts: &HashMap<(NaiveDate, UserId), u64>, date: NaiveDate) -> HashMap<UserId, u64> {
+    counts
+        .iter()
+        .filter(|((d, _), _)| *d == date)
+        .map(|((_, user_id), count)| (*user_id, *count))
+        .collect()
+}
+
+impl LinkContext for MockContext {
+    fn cancel_url(&self, id: ScheduleId, _schedule: &Schedule) -> Option<String> {
+        self.cancel_links
+            .load(Ordering::SeqCst)
+            .then(|| format!("https://kaisan.example.com/cancel/{}", id.as_u32()))
+    }
+}
+
+#[async_trait::async_trait]
+impl VoiceContext for MockContext {
+    async fn announce_in_voice(&self, voice_channel_id: ChannelId) -> Result<bool> {
+        let mut announcements = self.voice_announcements.lock().unwrap();
+        let Some(announcements) = announcements.as_mut() else {
+            return Ok(false);
+        };
+        if self.voice_join_fails.load(Ordering::SeqCst) {
+            return Err(anyhow::anyhow!("cannot join the voice channel").into());
+        }
+        announcements.push(voice_channel_id);
+        Ok(true)
+    }
+}
+
+impl NotificationContext for MockContext {
+    fn notify(&self, notification: Notification) {
+        self.notifications.lock().unwrap().push(notification);
+    }
+}
+
+#[async_trait::async_trait]
+impl OwnerContext for MockContext {
+    fn is_owner(&self, user_id: UserId) -> bool {
+        self.owners.lock().unwrap().contains(&user_id)
+    }
+
+    async fn all_schedules(&self) -> Vec<(GuildId, ScheduleId, Schedule)> {
+        self.registry.all_schedules().await
+    }
+
+    fn dispatch_stats(&self) -> DispatchStats {
+        self.registry.dispatch_stats()
+    }
+
+    fn soak(&self) -> SoakMonitor {
+        self.registry.soak().clone()
+    }
+
+    fn database_retry_stats(&self) -> RetryStats {
+        RetryStats::default()
+    }
+
+    fn database_cache_stats(&self) -> CacheStats {
+        CacheStats::default()
+    }
+
+    fn log_filter(&self) -> Option<LogFilterHandle> {
+        self.log_filter.clone()
+    }
+
+    async fn guild_ids(&self) -> Vec<GuildId> {
+        self.guild_ids.lock().await.clone()
+    }
+
+    async fn announce(&self, guild_id: GuildId, text: &str) -> Result<bool> {
+        if self.silent_guilds.lock().await.contains(&guild_id) {
+            return Ok(false);
+        }
+        self.announcements
+            .lock()
+            .await
+            .push((guild_id, text.to_owned()));
+        Ok(true)
+    }
+
+    async fn storage_usage(&self, guild_id: GuildId) -> Result<StorageUsage> {
+        Ok(self
+            .storage_usage
+            .lock()
+            .await
+            .get(&guild_id)
+            .copied()
+            .unwrap_or_default())
+    }
+}
+
+#[async_trait::async_trait]
+impl HealthContext for MockContext {
+    async fn rest_latency(&self) -> Result<Duration> {
+        let latency = self.latencies.lock().await.rest;
+        Ok(latency.ok_or_else(|| anyhow::anyhow!("Discord is not responding"))?)
+    }
+
+    async fn gateway_latency(&self) -> Option<Duration> {
+        self.latencies.lock().await.gateway
+    }
+
+    async fn database_latency(&self) -> Result<Duration> {
+        let latency = self.latencies.lock().await.database;
+        Ok(latency.ok_or_else(|| anyhow::anyhow!("the database is not responding"))?)
+    }
+}
+
+#[async_trait::async_trait]
+impl PresenceContext for MockContext {
+    async fn joined_at(&self, user_id: UserId) -> Option<DateTime<Utc>> {
+        self.joined_at.lock().await.get(&user_id).copied()
+    }
+}
+
+#[async_trait::async_trait]
+impl ScheduleContext for MockContext {
+    async fn register_schedule(&self, schedule: Schedule) -> ScheduleId {
+        self.registry.register(self.guild_id, schedule).await
+    }
+
+    async fn attach_schedule_tasks(&self, id: ScheduleId, tasks: Vec<AbortHandle>) {
+        self.registry.attach_tasks(self.guild_id, id, tasks).await
+    }
+
+    async fn schedules(&self) -> Vec<(ScheduleId, Schedule)> {
+        self.registry.schedules(self.guild_id).await
+    }
+
+    async fn update_schedule(
+        &self,
+        id: ScheduleId,
+        f: impl for<'s> FnOnce(&'s mut Schedule) + Send + 'async_trait,
+    ) -> Option<Schedule> {
+        self.registry.update(self.guild_id, id, f).await
+    }
+
+    async fn take_schedule(&self, id: ScheduleId) -> Option<Schedule> {
+        self.registry.take(self.guild_id, id).await
+    }
+
+    async fn cancel_schedule(&self, id: ScheduleId) -> Option<Schedule> {
+        self.registry.cancel(self.guild_id, id).await
+    }
+
+    async fn dispatch(&self) -> DispatchPermit {
+        self.registry.dispatch(self.guild_id).await
+    }
 }
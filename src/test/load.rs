@@ -0,0 +1,143 @@
+//! Drives the scheduler with many synthetic guilds, to see how it copes with a popular minute.
+//! Run it with `cargo test --release load_test -- --ignored --nocapture`, and set
+//! `KAISAN_LOAD_GUILDS` and `KAISAN_LOAD_SCHEDULES_PER_GUILD` to change the size.
+
+use std::sync::atomic::Ordering;
+use std::time::Instant;
+
+use super::{MockContext, MOCK_AUTHOR_2};
+use crate::database::InMemoryHandle;
+use crate::model::{
+    command::{KaisanOptions, TimeRangeSpecifier},
+    kaisanee::KaisaneeSpecifier,
+    time::TimeSpecifier,
+};
+use crate::registry::ScheduleRegistry;
+use crate::use_case::ScheduleKaisan;
+
+use chrono::{Duration, FixedOffset, Utc};
+use serenity::model::id::GuildId;
+
+const FIRING_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(120);
+
+fn env_or(name: &str, default: usize) -> usize {
+    std::env::var(name)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+/// Resident memory of the process, on Linux.
+fn resident_mib() -> Option<f64> {
+    let statm = std::fs::read_to_string("/proc/self/statm").ok()?;
+    let pages: u64 = statm.split_whitespace().nth(1)?.parse().ok()?;
+    Some((pages * 4096) as f64 / (1024.0 * 1024.0))
+}
+
+fn per_second(count: usize, elapsed: std::time::Duration) -> f64 {
+    count as f64 / elapsed.as_secs_f64()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore]
+async fn load_test() {
+    let guilds = env_or("KAISAN_LOAD_GUILDS", 2000);
+    let per_guild = env_or("KAISAN_LOAD_SCHEDULES_PER_GUILD", 5);
+    // the numbers are reported as logs
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter(format!("{}=info", module_path!()))
+        .with_test_writer()
+        .try_init();
+    let total = guilds * per_guild;
+
+    let now = Utc::now();
+    let base = MockContext::with_author_current_time(MOCK_AUTHOR_2, now);
+    base.max_schedules_per_user.store(u8::MAX, Ordering::SeqCst);
+    let memory_before = resident_mib();
+
+    let contexts: Vec<_> = (1..=guilds as u64)
+        .map(|id| MockContext {
+            guild_id: GuildId::new(id),
+            ..base.clone()
+        })
+        .collect();
+    let utc = FixedOffset::east_opt(0).unwrap();
+    let start = Instant::now();
+    for (i, ctx) in contexts.iter().enumerate() {
+        for j in 0..per_guild {
+            // everything falls within the same minute
+            let offset = Duration::seconds(60 + ((i * per_guild + j) % 60) as i64);
+            ctx.schedule_kaisan(
+                KaisaneeSpecifier::Me,
+                TimeRangeSpecifier::At(TimeSpecifier::Exactly((now + offset).with_timezone(&utc))),
+                KaisanOptions::default(),
+            )
+            .await
+            .unwrap();
+        }
+    }
+    let scheduling = start.elapsed();
+    let memory_scheduled = resident_mib();
+    let scheduled = base.registry.all_schedules().await;
+    assert_eq!(scheduled.len(), total);
+
+    let start = Instant::now();
+    base.scheduler.jump_to(now + Duration::minutes(2)).await;
+    while base.disconnected_users.lock().await.len() < total {
+        assert!(
+            start.elapsed() < FIRING_TIMEOUT,
+            "only {} of {} kaisans fired",
+            base.disconnected_users.lock().await.len(),
+            total
+        );
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    let firing = start.elapsed();
+    assert!(base.registry.all_schedules().await.is_empty());
+    let dispatch = base.registry.dispatch_stats();
+
+    // persisting on shutdown and restoring on startup
+    let registry = ScheduleRegistry::new();
+    for (guild_id, _, schedule) in scheduled {
+        registry.register(guild_id, schedule).await;
+    }
+    let database = InMemoryHandle::new();
+    let start = Instant::now();
+    registry.persist(&database).await.unwrap();
+    let mut restored = 0;
+    for ctx in &contexts {
+        restored += ScheduleRegistry::take_persisted(&database, ctx.guild_id)
+            .await
+            .unwrap()
+            .len();
+    }
+    let persistence = start.elapsed();
+    assert_eq!(restored, total);
+
+    tracing::info!(guilds, schedules = total, "load test finished");
+    tracing::info!(
+        elapsed = ?scheduling,
+        per_second = per_second(total, scheduling),
+        "scheduling"
+    );
+    tracing::info!(
+        elapsed = ?firing,
+        per_second = per_second(total, firing),
+        mean_wait = ?dispatch.mean_wait,
+        max_wait = ?dispatch.max_wait,
+        "firing"
+    );
+    tracing::info!(
+        elapsed = ?persistence,
+        per_second = per_second(total, persistence),
+        "persisting and restoring"
+    );
+    if let (Some(before), Some(scheduled)) = (memory_before, memory_scheduled) {
+        tracing::info!(
+            before_mib = before,
+            scheduled_mib = scheduled,
+            per_schedule_kib = (scheduled - before) * 1024.0 / total as f64,
+            "resident memory"
+        );
+    }
+}
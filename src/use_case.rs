@@ -1,17 +1,146 @@
+mod abort_all;
 mod add_reminder;
+mod admin_broadcast;
+mod admin_log_level;
+mod admin_soak;
+mod admin_stats;
+mod admin_storage;
+mod allow_channel;
+mod allow_role;
+mod author_left;
+mod auto_kaisan;
+mod cancel_by_link;
+mod cancel_mine;
+mod check_channel;
+mod check_paused;
+mod check_rate_limit;
+mod clear_reminders;
+mod complain;
+mod deny_channel;
+mod deny_role;
+mod export_setting;
 mod help;
+mod import_setting;
+mod kaisan_now;
+mod list_profiles;
+mod list_reminders;
+mod list_roles;
+mod load_profile;
+mod log_event;
+mod manage_api_token;
+mod opt_in;
+mod opt_out;
+mod pause;
+mod ping;
+mod preview_kaisan;
+mod record_voice_session;
 mod remove_reminder;
+mod restore_schedule;
+mod resume;
+mod save_profile;
 mod schedule_kaisan;
+mod set_author_leave_policy;
+mod set_auto_kaisan;
+mod set_channel_setting;
+mod set_command_prefix;
+mod set_countdown;
+mod set_dst_policy;
+mod set_locale;
+mod set_log_channel;
+mod set_max_horizon;
+mod set_max_schedules;
+mod set_on_duplicate;
+mod set_phrase;
+mod set_random_distribution;
+mod set_reaction;
+mod set_reminder_text;
 mod set_reminds_random_kaisan;
 mod set_requires_permission;
+mod set_requires_permission_self;
+mod set_reveal_random;
 mod set_timezone;
+mod set_tonight_hour;
+mod set_undo_window;
+mod set_webhook;
+mod set_week_start;
+mod show_complaints;
+mod show_pending_schedules;
 mod show_setting;
+mod show_stats;
+mod timezone_wizard;
+mod undo;
+mod when;
+mod who_kicked_me;
 
+pub use abort_all::AbortAll;
 pub use add_reminder::AddReminder;
+pub use admin_broadcast::AdminBroadcast;
+pub use admin_log_level::AdminLogLevel;
+pub use admin_soak::AdminSoak;
+pub use admin_stats::AdminStats;
+pub use admin_storage::AdminStorage;
+pub use allow_channel::AllowChannel;
+pub use allow_role::AllowRole;
+pub use author_left::AuthorLeft;
+pub use auto_kaisan::AutoKaisan;
+pub use cancel_by_link::CancelByLink;
+pub use cancel_mine::CancelMine;
+pub use check_channel::CheckChannel;
+pub use check_paused::CheckPaused;
+pub use check_rate_limit::CheckRateLimit;
+pub use clear_reminders::ClearReminders;
+pub use complain::Complain;
+pub use deny_channel::DenyChannel;
+pub use deny_role::DenyRole;
+pub use export_setting::ExportSetting;
 pub use help::Help;
+pub use import_setting::ImportSetting;
+pub use kaisan_now::KaisanNow;
+pub use list_profiles::ListProfiles;
+pub use list_reminders::ListReminders;
+pub use list_roles::ListRoles;
+pub use load_profile::LoadProfile;
+pub use manage_api_token::ManageApiToken;
+pub use opt_in::OptIn;
+pub use opt_out::OptOut;
+pub use pause::Pause;
+pub use ping::Ping;
+pub use preview_kaisan::PreviewKaisan;
+pub use record_voice_session::RecordVoiceSession;
 pub use remove_reminder::RemoveReminder;
+pub use restore_schedule::RestoreSchedule;
+pub use resume::Resume;
+pub use save_profile::SaveProfile;
 pub use schedule_kaisan::ScheduleKaisan;
+pub use set_author_leave_policy::SetAuthorLeavePolicy;
+pub use set_auto_kaisan::SetAutoKaisan;
+pub use set_channel_setting::SetChannelSetting;
+pub use set_command_prefix::SetCommandPrefix;
+pub use set_countdown::SetCountdown;
+pub use set_dst_policy::SetDstPolicy;
+pub use set_locale::SetLocale;
+pub use set_log_channel::SetLogChannel;
+pub use set_max_horizon::SetMaxHorizon;
+pub use set_max_schedules::SetMaxSchedules;
+pub use set_on_duplicate::SetOnDuplicate;
+pub use set_phrase::SetPhrase;
+pub use set_random_distribution::SetRandomDistribution;
+pub use set_reaction::SetReaction;
+pub use set_reminder_text::SetReminderText;
 pub use set_reminds_random_kaisan::SetRemindsRandomKaisan;
 pub use set_requires_permission::SetRequiresPermission;
+pub use set_requires_permission_self::SetRequiresPermissionSelf;
+pub use set_reveal_random::SetRevealRandom;
 pub use set_timezone::SetTimeZone;
+pub use set_tonight_hour::SetTonightHour;
+pub use set_undo_window::SetUndoWindow;
+pub use set_webhook::SetWebhook;
+pub use set_week_start::SetWeekStart;
+pub use show_complaints::ShowComplaints;
+pub use show_pending_schedules::ShowPendingSchedules;
 pub use show_setting::ShowSetting;
+pub use show_stats::ShowStats;
+pub use timezone_wizard::TimeZoneWizard;
+pub use undo::Undo;
+pub use when::When;
+pub use who_kicked_me::WhoKickedMe;
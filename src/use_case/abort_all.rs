@@ -0,0 +1,96 @@
+use super::log_event::log_event;
+use crate::context::{
+    ChannelContext, GuildContext, MessageContext, ScheduleContext, SettingContext,
+};
+use crate::error::{Error, Result};
+use crate::model::message::{LogEntry, Message};
+
+use serenity::model::permissions::Permissions;
+
+#[async_trait::async_trait]
+pub trait AbortAll:
+    ScheduleContext + SettingContext + GuildContext + MessageContext + ChannelContext
+{
+    #[tracing::instrument(skip(self))]
+    async fn abort_all(&self) -> Result<()> {
+        if !self
+            .member_permissions(self.author_id())
+            .await?
+            .manage_guild()
+        {
+            return Err(Error::InsufficientPermission(Permissions::MANAGE_GUILD));
+        }
+
+        let mut count = 0;
+        for (id, _) in self.schedules().await {
+            // the schedule may have been fired or cancelled in the meantime
+            if let Some(schedule) = self.cancel_schedule(id).await {
+                count += 1;
+                log_event(
+                    self,
+                    LogEntry::Cancelled {
+                        schedule_id: id,
+                        voice_channel_id: schedule.voice_channel_id,
+                        by: Some(self.author_id()),
+                    },
+                )
+                .await;
+            }
+        }
+        tracing::info!(count, "aborted all schedules");
+
+        self.message(Message::AbortedAll(count)).await
+    }
+}
+
+impl<T: ScheduleContext + SettingContext + GuildContext + MessageContext + ChannelContext> AbortAll
+    for T
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AbortAll;
+    use crate::{
+        context::ScheduleContext,
+        error::Error,
+        model::{message::Message, schedule::Schedule},
+        test::{mock_schedule, MockContext, MOCK_AUTHOR_1, MOCK_AUTHOR_2},
+    };
+    use chrono::{Duration, Utc};
+
+    fn schedule() -> Schedule {
+        mock_schedule(MOCK_AUTHOR_1, Utc::now() + Duration::minutes(10))
+    }
+
+    #[tokio::test]
+    async fn test_success() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        let id = ctx.register_schedule(schedule()).await;
+        let task = tokio::spawn(futures::future::pending::<()>());
+        ctx.attach_schedule_tasks(id, vec![task.abort_handle()])
+            .await;
+        ctx.register_schedule(schedule()).await;
+
+        ctx.abort_all().await.unwrap();
+
+        assert!(task.await.unwrap_err().is_cancelled());
+        assert!(ctx.schedules().await.is_empty());
+        assert!(matches!(
+            ctx.sent_messages.lock().await.as_slice(),
+            [Message::AbortedAll(2)]
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_insufficient_permission() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        ctx.register_schedule(schedule()).await;
+
+        assert!(matches!(
+            ctx.abort_all().await,
+            Err(Error::InsufficientPermission(_))
+        ));
+        assert_eq!(ctx.schedules().await.len(), 1);
+    }
+}
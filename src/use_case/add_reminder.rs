@@ -6,6 +6,7 @@ use serenity::model::permissions::Permissions;
 
 #[async_trait::async_trait]
 pub trait AddReminder: SettingContext + GuildContext + MessageContext {
+    #[tracing::instrument(skip(self))]
     async fn add_reminder(&self, reminder: Reminder) -> Result<()> {
         if !self
             .member_permissions(self.author_id())
@@ -18,7 +19,7 @@ pub trait AddReminder: SettingContext + GuildContext + MessageContext {
         if !SettingContext::add_reminder(self, reminder).await? {
             Err(Error::DuplicatedReminders(reminder))
         } else {
-            self.react('✅').await?;
+            self.react_success().await?;
             Ok(())
         }
     }
@@ -0,0 +1,93 @@
+use crate::context::{ChannelContext, MessageContext, OwnerContext};
+use crate::error::{Error, Result};
+use crate::model::message::Message;
+
+#[async_trait::async_trait]
+pub trait AdminBroadcast: OwnerContext + MessageContext + ChannelContext {
+    /// Posts the text to every guild the bot is in.
+    #[tracing::instrument(skip(self))]
+    async fn admin_broadcast(&self, text: String) -> Result<()> {
+        if !self.is_owner(self.author_id()) {
+            return Err(Error::NotOwner);
+        }
+
+        let mut delivered = 0;
+        let mut skipped = 0;
+        let mut failed = 0;
+        for guild_id in self.guild_ids().await {
+            match self.announce(guild_id, &text).await {
+                Ok(true) => delivered += 1,
+                Ok(false) => skipped += 1,
+                Err(e) => {
+                    tracing::warn!(%guild_id, error = %e, "failed to broadcast");
+                    failed += 1;
+                }
+            }
+        }
+        tracing::info!(delivered, skipped, failed, "broadcasted");
+
+        self.message(Message::Broadcasted {
+            delivered,
+            skipped,
+            failed,
+        })
+        .await
+    }
+}
+
+impl<T: OwnerContext + MessageContext + ChannelContext> AdminBroadcast for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::AdminBroadcast;
+    use crate::{
+        error::Error,
+        model::message::Message,
+        test::{MockContext, MOCK_AUTHOR_2, MOCK_GUILD_ID},
+    };
+    use serenity::model::id::GuildId;
+
+    const OTHER_GUILD_ID: GuildId = GuildId::new(1);
+    const SILENT_GUILD_ID: GuildId = GuildId::new(2);
+
+    #[tokio::test]
+    async fn test_success() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        ctx.owners.lock().unwrap().insert(MOCK_AUTHOR_2);
+        ctx.guild_ids
+            .lock()
+            .await
+            .extend([OTHER_GUILD_ID, SILENT_GUILD_ID]);
+        ctx.silent_guilds.lock().await.insert(SILENT_GUILD_ID);
+
+        ctx.admin_broadcast("maintenance at 3:00".to_owned())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            *ctx.announcements.lock().await,
+            vec![
+                (MOCK_GUILD_ID, "maintenance at 3:00".to_owned()),
+                (OTHER_GUILD_ID, "maintenance at 3:00".to_owned()),
+            ]
+        );
+        assert!(matches!(
+            ctx.sent_messages.lock().await.as_slice(),
+            [Message::Broadcasted {
+                delivered: 2,
+                skipped: 1,
+                failed: 0
+            }]
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_not_owner() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        assert!(matches!(
+            ctx.admin_broadcast("hello".to_owned()).await,
+            Err(Error::NotOwner)
+        ));
+        assert!(ctx.announcements.lock().await.is_empty());
+    }
+}
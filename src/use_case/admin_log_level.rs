@@ -0,0 +1,82 @@
+use crate::context::{ChannelContext, MessageContext, OwnerContext};
+use crate::error::{Error, Result};
+
+use anyhow::Context as _;
+
+#[async_trait::async_trait]
+pub trait AdminLogLevel: OwnerContext + MessageContext + ChannelContext {
+    /// Replaces the filter of the logs without restarting the bot, until the next restart.
+    #[tracing::instrument(skip(self))]
+    async fn admin_log_level(&self, directives: String) -> Result<()> {
+        if !self.is_owner(self.author_id()) {
+            return Err(Error::NotOwner);
+        }
+
+        let log_filter = self
+            .log_filter()
+            .context("the log filter cannot be changed")?;
+        if let Err(e) = log_filter.set(&directives) {
+            tracing::debug!("cannot set log filter: {:#}", e);
+            return Err(Error::InvalidLogFilter(directives));
+        }
+        tracing::info!(%directives, "changed log filter");
+        self.react_success().await
+    }
+}
+
+impl<T: OwnerContext + MessageContext + ChannelContext> AdminLogLevel for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::AdminLogLevel;
+    use crate::{
+        error::Error,
+        log::LogFilterHandle,
+        test::{MockContext, MOCK_AUTHOR_2},
+    };
+    use std::sync::{Arc, Mutex};
+
+    fn owner_with_log_filter() -> (MockContext, Arc<Mutex<Vec<String>>>) {
+        let mut ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        ctx.owners.lock().unwrap().insert(MOCK_AUTHOR_2);
+        let filters = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&filters);
+        ctx.log_filter = Some(LogFilterHandle::new(move |filter| {
+            recorded.lock().unwrap().push(filter.to_string());
+            Ok(())
+        }));
+        (ctx, filters)
+    }
+
+    #[tokio::test]
+    async fn test_success() {
+        let (ctx, filters) = owner_with_log_filter();
+        ctx.admin_log_level("info,kaisantantoudaijin=debug".to_owned())
+            .await
+            .unwrap();
+        assert_eq!(
+            *filters.lock().unwrap(),
+            vec!["kaisantantoudaijin=debug,info".to_owned()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_invalid() {
+        let (ctx, filters) = owner_with_log_filter();
+        assert!(matches!(
+            ctx.admin_log_level("kaisantantoudaijin=loud".to_owned())
+                .await,
+            Err(Error::InvalidLogFilter(_))
+        ));
+        assert!(filters.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_not_owner() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        assert!(matches!(
+            ctx.admin_log_level("debug".to_owned()).await,
+            Err(Error::NotOwner)
+        ));
+    }
+}
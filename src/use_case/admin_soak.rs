@@ -0,0 +1,130 @@
+use crate::context::{ChannelContext, MessageContext, OwnerContext, ScheduleContext, TimeContext};
+use crate::error::{Error, Result};
+
+use chrono::Duration;
+use tokio::{spawn, task::AbortHandle};
+use tracing::Instrument as _;
+
+#[async_trait::async_trait]
+pub trait AdminSoak:
+    OwnerContext
+    + MessageContext
+    + ChannelContext
+    + TimeContext
+    + ScheduleContext
+    + Clone
+    + Send
+    + 'static
+{
+    /// Schedules a no-op every `interval_minutes` and records how far off its time it fires,
+    /// shown in `admin stats`. Stops the soak if `None`.
+    #[tracing::instrument(skip(self))]
+    async fn admin_soak(&self, interval_minutes: Option<u32>) -> Result<()> {
+        if !self.is_owner(self.author_id()) {
+            return Err(Error::NotOwner);
+        }
+
+        match interval_minutes {
+            Some(interval_minutes) => {
+                let task = run_soak(self.clone(), Duration::minutes(interval_minutes.into()));
+                self.soak().start(interval_minutes, task);
+                tracing::info!(interval_minutes, "started soak");
+            }
+            None => {
+                if self.soak().stop() {
+                    tracing::info!("stopped soak");
+                }
+            }
+        }
+        self.react_success().await
+    }
+}
+
+impl<
+        T: OwnerContext
+            + MessageContext
+            + ChannelContext
+            + TimeContext
+            + ScheduleContext
+            + Clone
+            + Send
+            + 'static,
+    > AdminSoak for T
+{
+}
+
+fn run_soak<C: AdminSoak + Sync>(ctx: C, interval: Duration) -> AbortHandle {
+    let span = tracing::info_span!("soak", %interval);
+    spawn(
+        async move {
+            let soak = ctx.soak();
+            loop {
+                let time = ctx.current_time() + interval;
+                ctx.delay_until(time).await;
+                // the same path as the scheduled kaisans, so that a busy dispatcher shows up too
+                let _permit = ctx.dispatch().await;
+                let drift = ctx.current_time() - time;
+                if soak.record(drift) {
+                    tracing::debug!(%drift, "soak canary fired");
+                } else {
+                    tracing::warn!(%drift, "soak canary fired off its time");
+                }
+            }
+        }
+        .instrument(span),
+    )
+    .abort_handle()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AdminSoak;
+    use crate::{
+        context::{OwnerContext, TimeContext},
+        error::Error,
+        test::{MockContext, MOCK_AUTHOR_2},
+    };
+    use chrono::{Duration, Utc};
+
+    #[tokio::test]
+    async fn test_soak() {
+        let now = Utc::now();
+        let ctx = MockContext::with_author_current_time(MOCK_AUTHOR_2, now);
+        ctx.owners.lock().unwrap().insert(MOCK_AUTHOR_2);
+
+        ctx.admin_soak(Some(5)).await.unwrap();
+        ctx.scheduler.settle().await;
+        assert_eq!(ctx.scheduler.pending_count(), 1);
+
+        ctx.scheduler.advance(Duration::minutes(10)).await;
+        let stats = ctx.soak().stats();
+        assert_eq!(stats.interval_minutes, Some(5));
+        assert_eq!(stats.fired, 2);
+        assert_eq!(stats.off_time, 0);
+
+        // as if the host was suspended for a while
+        ctx.scheduler
+            .jump_to(ctx.current_time() + Duration::minutes(8))
+            .await;
+        let stats = ctx.soak().stats();
+        assert_eq!(stats.fired, 3);
+        assert_eq!(stats.off_time, 1);
+        assert_eq!(stats.max_drift, std::time::Duration::from_secs(3 * 60));
+
+        ctx.admin_soak(None).await.unwrap();
+        ctx.scheduler.settle().await;
+        assert_eq!(ctx.scheduler.pending_count(), 0);
+        assert_eq!(ctx.soak().stats().interval_minutes, None);
+        assert_eq!(ctx.soak().stats().fired, 3);
+    }
+
+    #[tokio::test]
+    async fn test_not_owner() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        assert!(matches!(
+            ctx.admin_soak(Some(5)).await,
+            Err(Error::NotOwner)
+        ));
+        assert_eq!(ctx.soak().stats().interval_minutes, None);
+    }
+}
@@ -0,0 +1,91 @@
+use std::collections::HashSet;
+
+use crate::context::{ChannelContext, MessageContext, OwnerContext, TimeContext};
+use crate::error::{Error, Result};
+use crate::model::message::Message;
+
+#[async_trait::async_trait]
+pub trait AdminStats: OwnerContext + MessageContext + ChannelContext + TimeContext {
+    /// Sends the numbers of guilds and schedules to the owner by DM.
+    #[tracing::instrument(skip(self))]
+    async fn admin_stats(&self) -> Result<()> {
+        if !self.is_owner(self.author_id()) {
+            return Err(Error::NotOwner);
+        }
+
+        let now = self.current_time();
+        let schedules = self.all_schedules().await;
+        let message = Message::BotStats {
+            guilds: self.guild_ids().await.len(),
+            schedules: schedules.len(),
+            scheduling_guilds: schedules
+                .iter()
+                .map(|(guild_id, _, _)| guild_id)
+                .collect::<HashSet<_>>()
+                .len(),
+            overdue_schedules: schedules
+                .iter()
+                .filter(|(_, _, s)| s.is_overdue(now))
+                .count(),
+            dispatch: self.dispatch_stats(),
+            retries: self.database_retry_stats(),
+            cache: self.database_cache_stats(),
+            soak: self.soak().stats(),
+        };
+        self.direct_message(self.author_id(), message).await?;
+        self.react_success().await
+    }
+}
+
+impl<T: OwnerContext + MessageContext + ChannelContext + TimeContext> AdminStats for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::AdminStats;
+    use crate::{
+        context::ScheduleContext,
+        error::Error,
+        model::{message::Message, schedule::Schedule},
+        test::{mock_schedule, MockContext, MOCK_AUTHOR_1, MOCK_AUTHOR_2},
+    };
+    use chrono::{Duration, Utc};
+    use serenity::model::id::GuildId;
+
+    fn schedule(time: chrono::DateTime<Utc>) -> Schedule {
+        mock_schedule(MOCK_AUTHOR_1, time)
+    }
+
+    #[tokio::test]
+    async fn test_success() {
+        let now = Utc::now();
+        let ctx = MockContext::with_author_current_time(MOCK_AUTHOR_2, now);
+        ctx.owners.lock().unwrap().insert(MOCK_AUTHOR_2);
+        ctx.guild_ids.lock().await.push(GuildId::new(1));
+        ctx.register_schedule(schedule(now + Duration::minutes(10)))
+            .await;
+        ctx.register_schedule(schedule(now - Duration::minutes(10)))
+            .await;
+
+        ctx.admin_stats().await.unwrap();
+
+        assert!(matches!(
+            ctx.direct_messages.lock().await.as_slice(),
+            [(
+                MOCK_AUTHOR_2,
+                Message::BotStats {
+                    guilds: 2,
+                    schedules: 2,
+                    scheduling_guilds: 1,
+                    overdue_schedules: 1,
+                    ..
+                }
+            )]
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_not_owner() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        assert!(matches!(ctx.admin_stats().await, Err(Error::NotOwner)));
+    }
+}
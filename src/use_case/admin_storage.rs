@@ -0,0 +1,95 @@
+use crate::context::{
+    ChannelContext, GuildContext, MessageContext, OwnerContext, DISCONNECT_EVENTS_CAPACITY,
+};
+use crate::error::{Error, Result};
+use crate::model::message::Message;
+
+use serenity::model::id::GuildId;
+
+#[async_trait::async_trait]
+pub trait AdminStorage: OwnerContext + MessageContext + ChannelContext + GuildContext {
+    /// Sends how much the guild takes in the database to the owner by DM. Defaults to the
+    /// current guild.
+    #[tracing::instrument(skip(self))]
+    async fn admin_storage(&self, guild_id: Option<GuildId>) -> Result<()> {
+        if !self.is_owner(self.author_id()) {
+            return Err(Error::NotOwner);
+        }
+
+        let guild_id = guild_id.unwrap_or_else(|| self.guild_id());
+        let usage = self.storage_usage(guild_id).await?;
+        let message = Message::StorageUsage {
+            guild_id,
+            keys: usage.keys,
+            bytes: usage.bytes,
+            event_capacity: DISCONNECT_EVENTS_CAPACITY,
+        };
+        self.direct_message(self.author_id(), message).await?;
+        self.react_success().await
+    }
+}
+
+impl<T: OwnerContext + MessageContext + ChannelContext + GuildContext> AdminStorage for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::AdminStorage;
+    use crate::{
+        database::StorageUsage,
+        error::Error,
+        model::message::Message,
+        test::{MockContext, MOCK_AUTHOR_2, MOCK_GUILD_ID},
+    };
+    use serenity::model::id::GuildId;
+
+    const OTHER_GUILD_ID: GuildId = GuildId::new(1);
+
+    #[tokio::test]
+    async fn test_success() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        ctx.owners.lock().unwrap().insert(MOCK_AUTHOR_2);
+        ctx.storage_usage.lock().await.insert(
+            OTHER_GUILD_ID,
+            StorageUsage {
+                keys: 3,
+                bytes: 512,
+            },
+        );
+
+        ctx.admin_storage(Some(OTHER_GUILD_ID)).await.unwrap();
+        ctx.admin_storage(None).await.unwrap();
+
+        assert!(matches!(
+            ctx.direct_messages.lock().await.as_slice(),
+            [
+                (
+                    MOCK_AUTHOR_2,
+                    Message::StorageUsage {
+                        guild_id: OTHER_GUILD_ID,
+                        keys: 3,
+                        bytes: 512,
+                        ..
+                    }
+                ),
+                (
+                    MOCK_AUTHOR_2,
+                    Message::StorageUsage {
+                        guild_id: MOCK_GUILD_ID,
+                        keys: 0,
+                        bytes: 0,
+                        ..
+                    }
+                ),
+            ]
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_not_owner() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        assert!(matches!(
+            ctx.admin_storage(None).await,
+            Err(Error::NotOwner)
+        ));
+    }
+}
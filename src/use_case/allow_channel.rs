@@ -0,0 +1,71 @@
+use crate::context::{ChannelContext, GuildContext, MessageContext, SettingContext};
+use crate::error::{Error, Result};
+
+use serenity::model::{id::ChannelId, permissions::Permissions};
+
+#[async_trait::async_trait]
+pub trait AllowChannel: SettingContext + GuildContext + MessageContext + ChannelContext {
+    #[tracing::instrument(skip(self))]
+    async fn allow_channel(&self, channel_id: Option<ChannelId>) -> Result<()> {
+        if !self
+            .member_permissions(self.author_id())
+            .await?
+            .manage_guild()
+        {
+            return Err(Error::InsufficientPermission(Permissions::MANAGE_GUILD));
+        }
+
+        let channel_id = channel_id.unwrap_or_else(|| self.channel_id());
+        if !SettingContext::allow_channel(self, channel_id).await? {
+            Err(Error::DuplicatedAllowedChannel(channel_id))
+        } else {
+            self.react_success().await?;
+            Ok(())
+        }
+    }
+}
+
+impl<T: SettingContext + GuildContext + MessageContext + ChannelContext> AllowChannel for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::AllowChannel;
+    use crate::{
+        error::Error,
+        test::{MockContext, MOCK_AUTHOR_1, MOCK_AUTHOR_2, MOCK_CHANNEL_ID},
+    };
+    use serenity::model::id::ChannelId;
+
+    #[tokio::test]
+    async fn test_success() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        ctx.allow_channel(Some(ChannelId::new(1))).await.unwrap();
+        ctx.allow_channel(None).await.unwrap();
+        assert_eq!(
+            *ctx.allowed_channels.lock().await,
+            vec![ChannelId::new(1), MOCK_CHANNEL_ID]
+                .into_iter()
+                .collect()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_duplicated() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        ctx.allow_channel(None).await.unwrap();
+        assert!(matches!(
+            ctx.allow_channel(Some(MOCK_CHANNEL_ID)).await,
+            Err(Error::DuplicatedAllowedChannel(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_insufficient_permission() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        assert!(matches!(
+            ctx.allow_channel(None).await,
+            Err(Error::InsufficientPermission(_))
+        ));
+        assert!(ctx.allowed_channels.lock().await.is_empty());
+    }
+}
@@ -0,0 +1,67 @@
+use crate::context::{GuildContext, MessageContext, SettingContext};
+use crate::error::{Error, Result};
+
+use serenity::model::{id::RoleId, permissions::Permissions};
+
+#[async_trait::async_trait]
+pub trait AllowRole: SettingContext + GuildContext + MessageContext {
+    #[tracing::instrument(skip(self))]
+    async fn allow_role(&self, role_id: RoleId) -> Result<()> {
+        if !self
+            .member_permissions(self.author_id())
+            .await?
+            .manage_guild()
+        {
+            return Err(Error::InsufficientPermission(Permissions::MANAGE_GUILD));
+        }
+
+        if !SettingContext::allow_role(self, role_id).await? {
+            Err(Error::DuplicatedAllowedRole(role_id))
+        } else {
+            self.react_success().await?;
+            Ok(())
+        }
+    }
+}
+
+impl<T: SettingContext + GuildContext + MessageContext> AllowRole for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::AllowRole;
+    use crate::{
+        error::Error,
+        test::{MockContext, MOCK_AUTHOR_1, MOCK_AUTHOR_2},
+    };
+    use serenity::model::id::RoleId;
+
+    #[tokio::test]
+    async fn test_success() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        ctx.allow_role(RoleId::new(1)).await.unwrap();
+        assert_eq!(
+            *ctx.allowed_roles.lock().await,
+            vec![RoleId::new(1)].into_iter().collect()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_duplicated() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        ctx.allow_role(RoleId::new(1)).await.unwrap();
+        assert!(matches!(
+            ctx.allow_role(RoleId::new(1)).await,
+            Err(Error::DuplicatedAllowedRole(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_insufficient_permission() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        assert!(matches!(
+            ctx.allow_role(RoleId::new(1)).await,
+            Err(Error::InsufficientPermission(_))
+        ));
+        assert!(ctx.allowed_roles.lock().await.is_empty());
+    }
+}
@@ -0,0 +1,146 @@
+use super::log_event::log_event;
+use super::schedule_kaisan::{draw_random_seconds, start_schedule, ScheduleKaisan};
+use crate::error::Result;
+use crate::model::{
+    message::{LogEntry, Message},
+    schedule::{Schedule, ScheduleId},
+    setting::AuthorLeavePolicy,
+};
+
+use chrono::Duration;
+
+#[async_trait::async_trait]
+pub trait AuthorLeft: ScheduleKaisan + Sync {
+    /// Applies the policy in the voice channel to a random schedule whose author has left it
+    /// before it is carried out.
+    #[tracing::instrument(skip(self))]
+    async fn author_left(&self, id: ScheduleId) -> Result<()> {
+        let Some((_, schedule)) = self.schedules().await.into_iter().find(|(i, _)| *i == id) else {
+            return Ok(());
+        };
+        let Some(random_until) = schedule.random_until else {
+            return Ok(());
+        };
+        let policy = self
+            .author_leave_policy_in(schedule.voice_channel_id)
+            .await?;
+        if policy == AuthorLeavePolicy::Keep {
+            return Ok(());
+        }
+        if self.cancel_schedule(id).await.is_none() {
+            // already carried out in the meantime
+            return Ok(());
+        }
+
+        if policy == AuthorLeavePolicy::Reroll {
+            let now = self.current_time();
+            let window = (random_until - now).num_seconds();
+            let time = if window > 0 {
+                now + Duration::seconds(draw_random_seconds(self, window).await?)
+            } else {
+                now
+            };
+            tracing::info!(%time, "rerolled schedule");
+            start_schedule(self, Schedule { time, ..schedule }).await;
+        } else {
+            log_event(
+                self,
+                LogEntry::Cancelled {
+                    schedule_id: id,
+                    voice_channel_id: schedule.voice_channel_id,
+                    by: Some(schedule.author_id),
+                },
+            )
+            .await;
+        }
+
+        self.message(Message::AuthorLeft {
+            author: schedule.author_id,
+            id,
+            policy,
+        })
+        .await
+    }
+}
+
+impl<T: ScheduleKaisan + Sync> AuthorLeft for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::AuthorLeft;
+    use crate::{
+        context::ScheduleContext,
+        model::{message::Message, schedule::Schedule, setting::AuthorLeavePolicy},
+        test::{mock_schedule, MockContext, FIXED_RANDOM, MOCK_AUTHOR_2},
+    };
+    use chrono::{DateTime, Duration, Utc};
+
+    fn schedule(now: DateTime<Utc>, random: bool) -> Schedule {
+        Schedule {
+            random_until: random.then(|| now + Duration::hours(5)),
+            ..mock_schedule(MOCK_AUTHOR_2, now + Duration::minutes(10))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_keep() {
+        let now = Utc::now();
+        let ctx = MockContext::with_author_current_time(MOCK_AUTHOR_2, now);
+        let id = ctx.register_schedule(schedule(now, true)).await;
+
+        ctx.author_left(id).await.unwrap();
+        assert_eq!(ctx.schedules().await, vec![(id, schedule(now, true))]);
+        assert!(ctx.sent_messages.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cancel() {
+        let now = Utc::now();
+        let ctx = MockContext::with_author_current_time(MOCK_AUTHOR_2, now);
+        *ctx.author_leave_policy.lock().await = AuthorLeavePolicy::Cancel;
+        let id = ctx.register_schedule(schedule(now, true)).await;
+
+        ctx.author_left(id).await.unwrap();
+        assert!(ctx.schedules().await.is_empty());
+        assert!(matches!(
+            ctx.sent_messages.lock().await.as_slice(),
+            [Message::AuthorLeft {
+                policy: AuthorLeavePolicy::Cancel,
+                ..
+            }]
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_reroll() {
+        let now = Utc::now();
+        let ctx = MockContext::with_author_current_time(MOCK_AUTHOR_2, now);
+        *ctx.author_leave_policy.lock().await = AuthorLeavePolicy::Reroll;
+        let id = ctx.register_schedule(schedule(now, true)).await;
+
+        ctx.author_left(id).await.unwrap();
+        let schedules = ctx.schedules().await;
+        let [(_, rerolled)] = schedules.as_slice() else {
+            panic!("expected exactly one schedule");
+        };
+        assert_eq!(rerolled.time, now + Duration::seconds(FIXED_RANDOM));
+        assert!(matches!(
+            ctx.sent_messages.lock().await.as_slice(),
+            [Message::AuthorLeft {
+                policy: AuthorLeavePolicy::Reroll,
+                ..
+            }]
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_not_random() {
+        let now = Utc::now();
+        let ctx = MockContext::with_author_current_time(MOCK_AUTHOR_2, now);
+        *ctx.author_leave_policy.lock().await = AuthorLeavePolicy::Cancel;
+        let id = ctx.register_schedule(schedule(now, false)).await;
+
+        ctx.author_left(id).await.unwrap();
+        assert_eq!(ctx.schedules().await.len(), 1);
+    }
+}
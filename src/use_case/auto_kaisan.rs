@@ -0,0 +1,168 @@
+use crate::context::{
+    BotContext, ChannelContext, EventContext, GuildContext, SettingContext, TimeContext,
+};
+use crate::error::Result;
+use crate::model::{event::DisconnectEvent, message::Message};
+
+use chrono::Timelike;
+use serenity::model::id::ChannelId;
+
+/// Automatic kaisan stays active from the configured hour until this hour in the morning.
+const AUTO_KAISAN_END_HOUR: u32 = 6;
+
+#[async_trait::async_trait]
+pub trait AutoKaisan:
+    GuildContext + ChannelContext + SettingContext + TimeContext + EventContext + BotContext + Sync
+{
+    /// Called when someone leaves the voice channel; disconnects the last one left alone in
+    /// the channel late at night.
+    #[tracing::instrument(skip(self))]
+    async fn auto_kaisan(&self, voice_channel_id: ChannelId) -> Result<()> {
+        let Some(start_hour) = self.auto_kaisan_hour().await? else {
+            return Ok(());
+        };
+        if self.frozen_schedules().await?.is_some() {
+            // paused
+            return Ok(());
+        }
+
+        let tz = self.timezone().await?;
+        let hour = self.current_time().with_timezone(&tz).hour();
+        if !is_active_hour(hour, start_hour.as_u32()) {
+            return Ok(());
+        }
+
+        let users = self.voice_channel_users(voice_channel_id).await?;
+        let [user_id] = users.as_slice() else {
+            return Ok(());
+        };
+
+        self.disconnect_user(*user_id).await?;
+        let event = DisconnectEvent {
+            time: self.current_time(),
+            channel_id: self.channel_id(),
+            voice_channel_id,
+            target: *user_id,
+            requester: self.bot_id(),
+            schedule_id: None,
+        };
+        tracing::info!(
+            target: "kaisantantoudaijin::disconnect",
+            guild_id = %self.guild_id(),
+            voice_channel_id = %voice_channel_id,
+            target_id = %user_id,
+            "auto disconnect"
+        );
+        if let Err(e) = self.record_disconnect(event).await {
+            tracing::warn!(error = %e, "failed to record disconnection");
+        }
+
+        self.message(Message::AutoKaisan(*user_id)).await
+    }
+}
+
+impl<
+        T: GuildContext
+            + ChannelContext
+            + SettingContext
+            + TimeContext
+            + EventContext
+            + BotContext
+            + Sync,
+    > AutoKaisan for T
+{
+}
+
+fn is_active_hour(hour: u32, start_hour: u32) -> bool {
+    if start_hour < AUTO_KAISAN_END_HOUR {
+        (start_hour..AUTO_KAISAN_END_HOUR).contains(&hour)
+    } else {
+        hour >= start_hour || hour < AUTO_KAISAN_END_HOUR
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_active_hour, AutoKaisan};
+    use crate::{
+        model::{message::Message, time::Hour},
+        test::{MockContext, MOCK_AUTHOR_1, MOCK_AUTHOR_2, MOCK_BOT_ID, MOCK_VOICE_CHANNEL_ID},
+    };
+    use chrono::{DateTime, Utc};
+    use chrono_tz::Tz;
+
+    fn at_utc_hour(hour: u32) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(&format!("2024-07-20T{:02}:30:00Z", hour))
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_active_hour() {
+        assert!(is_active_hour(23, 22));
+        assert!(is_active_hour(3, 22));
+        assert!(!is_active_hour(6, 22));
+        assert!(!is_active_hour(21, 22));
+        assert!(is_active_hour(2, 1));
+        assert!(!is_active_hour(0, 1));
+    }
+
+    #[tokio::test]
+    async fn test_disabled() {
+        let ctx = MockContext::with_current_time(at_utc_hour(23));
+        *ctx.timezone.lock().await = Tz::UTC;
+        ctx.auto_kaisan(MOCK_VOICE_CHANNEL_ID).await.unwrap();
+        assert!(ctx.disconnected_users.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_not_alone() {
+        // two mock users are in the voice channel
+        let ctx = MockContext::with_current_time(at_utc_hour(23));
+        *ctx.timezone.lock().await = Tz::UTC;
+        *ctx.auto_kaisan_hour.lock().await = Some(Hour::from_u8(22).unwrap());
+        ctx.auto_kaisan(MOCK_VOICE_CHANNEL_ID).await.unwrap();
+        assert!(ctx.disconnected_users.lock().await.is_empty());
+        assert!(ctx.sent_messages.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_alone() {
+        let ctx = MockContext::with_current_time(at_utc_hour(23));
+        *ctx.timezone.lock().await = Tz::UTC;
+        *ctx.auto_kaisan_hour.lock().await = Some(Hour::from_u8(22).unwrap());
+        ctx.voice_states.lock().await.remove(&MOCK_AUTHOR_1);
+        ctx.auto_kaisan(MOCK_VOICE_CHANNEL_ID).await.unwrap();
+
+        assert_eq!(*ctx.disconnected_users.lock().await, vec![MOCK_AUTHOR_2]);
+        assert!(matches!(
+            ctx.sent_messages.lock().await.as_slice(),
+            [Message::AutoKaisan(user_id)] if user_id == &MOCK_AUTHOR_2
+        ));
+        let events = ctx.disconnect_events.lock().await;
+        assert_eq!(events[0].requester, MOCK_BOT_ID);
+    }
+
+    #[tokio::test]
+    async fn test_daytime() {
+        let ctx = MockContext::with_current_time(at_utc_hour(12));
+        *ctx.timezone.lock().await = Tz::UTC;
+        *ctx.auto_kaisan_hour.lock().await = Some(Hour::from_u8(22).unwrap());
+        ctx.voice_states.lock().await.remove(&MOCK_AUTHOR_1);
+        ctx.auto_kaisan(MOCK_VOICE_CHANNEL_ID).await.unwrap();
+
+        assert!(ctx.disconnected_users.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_paused() {
+        let ctx = MockContext::with_current_time(at_utc_hour(23));
+        *ctx.timezone.lock().await = Tz::UTC;
+        *ctx.auto_kaisan_hour.lock().await = Some(Hour::from_u8(22).unwrap());
+        *ctx.frozen_schedules.lock().await = Some(Vec::new());
+        ctx.voice_states.lock().await.remove(&MOCK_AUTHOR_1);
+        ctx.auto_kaisan(MOCK_VOICE_CHANNEL_ID).await.unwrap();
+
+        assert!(ctx.disconnected_users.lock().await.is_empty());
+    }
+}
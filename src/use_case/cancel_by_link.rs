@@ -0,0 +1,110 @@
+use super::log_event::log_event;
+use crate::context::{ChannelContext, MessageContext, ScheduleContext, SettingContext};
+use crate::error::{Error, Result};
+use crate::model::{
+    cancel_link::CancelLink,
+    message::{LogEntry, Message},
+};
+
+#[async_trait::async_trait]
+pub trait CancelByLink: ScheduleContext + SettingContext + MessageContext + ChannelContext {
+    /// Cancels the schedule that the verified link points to, and tells the channel of the
+    /// schedule about it.
+    #[tracing::instrument(skip(self))]
+    async fn cancel_by_link(&self, link: CancelLink) -> Result<()> {
+        let id = link.schedule_id;
+        let matches = self
+            .schedules()
+            .await
+            .into_iter()
+            .any(|(i, schedule)| i == id && link.matches(&schedule));
+        let cancelled = match matches {
+            true => self.cancel_schedule(id).await,
+            false => None,
+        };
+        let Some(schedule) = cancelled else {
+            return Err(Error::NoSuchSchedule(id));
+        };
+        log_event(
+            self,
+            LogEntry::Cancelled {
+                schedule_id: id,
+                voice_channel_id: schedule.voice_channel_id,
+                by: Some(self.author_id()),
+            },
+        )
+        .await;
+
+        self.message(Message::CancelledByLink(id)).await
+    }
+}
+
+impl<T: ScheduleContext + SettingContext + MessageContext + ChannelContext> CancelByLink for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::CancelByLink;
+    use crate::{
+        context::ScheduleContext,
+        error::Error,
+        model::{
+            cancel_link::CancelLink, kaisanee::KaisaneeSpecifier, message::Message,
+            schedule::Schedule,
+        },
+        test::{mock_schedule, MockContext, MOCK_AUTHOR_1, MOCK_GUILD_ID},
+    };
+    use chrono::{Duration, Utc};
+
+    fn schedule() -> Schedule {
+        Schedule {
+            kaisanee: KaisaneeSpecifier::Me,
+            ..mock_schedule(MOCK_AUTHOR_1, Utc::now() + Duration::minutes(10))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cancel() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        let schedule = schedule();
+        let id = ctx.register_schedule(schedule.clone()).await;
+        let link = CancelLink::new(MOCK_GUILD_ID, id, &schedule);
+
+        ctx.cancel_by_link(link).await.unwrap();
+        assert!(ctx.schedules().await.is_empty());
+        assert!(matches!(
+            ctx.sent_messages.lock().await.as_slice(),
+            [Message::CancelledByLink(i)] if *i == id
+        ));
+
+        // only once
+        assert!(matches!(
+            ctx.cancel_by_link(link).await,
+            Err(Error::NoSuchSchedule(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_other_schedule() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        let old = schedule();
+        let id = ctx.register_schedule(old.clone()).await;
+        let link = CancelLink::new(MOCK_GUILD_ID, id, &old);
+        ctx.cancel_schedule(id).await;
+
+        // even if the same id is given to another schedule
+        let new = Schedule {
+            time: old.time + Duration::minutes(5),
+            ..old
+        };
+        let new_id = ctx.register_schedule(new).await;
+        let link = CancelLink {
+            schedule_id: new_id,
+            ..link
+        };
+        assert!(matches!(
+            ctx.cancel_by_link(link).await,
+            Err(Error::NoSuchSchedule(_))
+        ));
+        assert_eq!(ctx.schedules().await.len(), 1);
+    }
+}
@@ -0,0 +1,95 @@
+use super::log_event::log_event;
+use crate::context::{ChannelContext, MessageContext, ScheduleContext, SettingContext};
+use crate::error::Result;
+use crate::model::message::{LogEntry, Message};
+
+#[async_trait::async_trait]
+pub trait CancelMine: ScheduleContext + SettingContext + MessageContext + ChannelContext {
+    #[tracing::instrument(skip(self))]
+    async fn cancel_mine(&self) -> Result<()> {
+        let author_id = self.author_id();
+
+        let mut count = 0;
+        for (id, schedule) in self.schedules().await {
+            if schedule.author_id == author_id && self.cancel_schedule(id).await.is_some() {
+                count += 1;
+                log_event(
+                    self,
+                    LogEntry::Cancelled {
+                        schedule_id: id,
+                        voice_channel_id: schedule.voice_channel_id,
+                        by: Some(author_id),
+                    },
+                )
+                .await;
+            }
+        }
+
+        self.message(Message::CancelledMine(count)).await
+    }
+}
+
+impl<T: ScheduleContext + SettingContext + MessageContext + ChannelContext> CancelMine for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::CancelMine;
+    use crate::{
+        context::ScheduleContext,
+        model::{
+            kaisanee::KaisaneeSpecifier,
+            message::{LogEntry, Message},
+            schedule::Schedule,
+        },
+        test::{mock_schedule, MockContext, MOCK_AUTHOR_1, MOCK_AUTHOR_2},
+    };
+    use chrono::{Duration, Utc};
+    use serenity::model::id::{ChannelId, UserId};
+
+    fn schedule(author_id: UserId) -> Schedule {
+        Schedule {
+            kaisanee: KaisaneeSpecifier::Me,
+            ..mock_schedule(author_id, Utc::now() + Duration::minutes(10))
+        }
+    }
+
+    #[tokio::test]
+    async fn test() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        ctx.register_schedule(schedule(MOCK_AUTHOR_1)).await;
+        ctx.register_schedule(schedule(MOCK_AUTHOR_1)).await;
+        let other = ctx.register_schedule(schedule(MOCK_AUTHOR_2)).await;
+
+        ctx.cancel_mine().await.unwrap();
+
+        let remaining: Vec<_> = ctx
+            .schedules()
+            .await
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect();
+        assert_eq!(remaining, vec![other]);
+        assert!(matches!(
+            ctx.sent_messages.lock().await.as_slice(),
+            [Message::CancelledMine(2)]
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_log_channel() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        *ctx.log_channel.lock().await = Some(ChannelId::new(42));
+        let id = ctx.register_schedule(schedule(MOCK_AUTHOR_1)).await;
+
+        ctx.cancel_mine().await.unwrap();
+
+        assert!(matches!(
+            ctx.channel_messages.lock().await.as_slice(),
+            [(_, Message::Log(LogEntry::Cancelled {
+                schedule_id,
+                by: Some(by),
+                ..
+            }))] if *schedule_id == id && *by == MOCK_AUTHOR_1
+        ));
+    }
+}
@@ -0,0 +1,56 @@
+use crate::context::{ChannelContext, SettingContext};
+use crate::error::{Error, Result};
+
+#[async_trait::async_trait]
+pub trait CheckChannel: SettingContext + ChannelContext {
+    /// Rejects commands outside the allowed channels, if any channel is allowed.
+    #[tracing::instrument(skip(self))]
+    async fn check_channel(&self) -> Result<()> {
+        let allowed_channels = self.allowed_channels().await?;
+        if allowed_channels.is_empty() || allowed_channels.contains(&self.channel_id()) {
+            return Ok(());
+        }
+
+        let mut allowed_channels: Vec<_> = allowed_channels.into_iter().collect();
+        allowed_channels.sort();
+        Err(Error::ChannelNotAllowed(allowed_channels))
+    }
+}
+
+impl<T: SettingContext + ChannelContext> CheckChannel for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::CheckChannel;
+    use crate::{
+        error::Error,
+        test::{MockContext, MOCK_CHANNEL_ID},
+    };
+    use serenity::model::id::ChannelId;
+
+    #[tokio::test]
+    async fn test_no_allowlist() {
+        let ctx = MockContext::new();
+        ctx.check_channel().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_allowed() {
+        let ctx = MockContext::new();
+        ctx.allowed_channels
+            .lock()
+            .await
+            .extend([ChannelId::new(1), MOCK_CHANNEL_ID]);
+        ctx.check_channel().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_not_allowed() {
+        let ctx = MockContext::new();
+        ctx.allowed_channels.lock().await.insert(ChannelId::new(1));
+        assert!(matches!(
+            ctx.check_channel().await,
+            Err(Error::ChannelNotAllowed(ids)) if ids == vec![ChannelId::new(1)]
+        ));
+    }
+}
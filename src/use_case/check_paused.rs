@@ -0,0 +1,31 @@
+use crate::context::SettingContext;
+use crate::error::{Error, Result};
+
+#[async_trait::async_trait]
+pub trait CheckPaused: SettingContext {
+    /// Rejects commands while the guild is paused.
+    #[tracing::instrument(skip(self))]
+    async fn check_paused(&self) -> Result<()> {
+        if self.frozen_schedules().await?.is_some() {
+            return Err(Error::Paused);
+        }
+        Ok(())
+    }
+}
+
+impl<T: SettingContext> CheckPaused for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::CheckPaused;
+    use crate::{error::Error, test::MockContext};
+
+    #[tokio::test]
+    async fn test_check_paused() {
+        let ctx = MockContext::new();
+        ctx.check_paused().await.unwrap();
+
+        *ctx.frozen_schedules.lock().await = Some(Vec::new());
+        assert!(matches!(ctx.check_paused().await, Err(Error::Paused)));
+    }
+}
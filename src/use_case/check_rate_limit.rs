@@ -0,0 +1,72 @@
+use std::time::Duration;
+
+use crate::context::{EventContext, MessageContext, OwnerContext};
+use crate::error::{Error, Result};
+
+const MAX_COMMANDS: u64 = 5;
+const WINDOW: Duration = Duration::from_secs(30);
+
+#[async_trait::async_trait]
+pub trait CheckRateLimit: EventContext + MessageContext + OwnerContext {
+    /// Rejects commands from a user who has run too many of them recently. Only the first
+    /// rejection in a window is reported, not to answer spam with spam.
+    #[tracing::instrument(skip(self))]
+    async fn check_rate_limit(&self) -> Result<bool> {
+        if self.is_owner(self.author_id()) {
+            return Ok(true);
+        }
+
+        let count = self.record_command(self.author_id(), WINDOW).await?;
+        if count <= MAX_COMMANDS {
+            return Ok(true);
+        }
+        if count == MAX_COMMANDS + 1 {
+            return Err(Error::RateLimited {
+                max_commands: MAX_COMMANDS,
+                window_seconds: WINDOW.as_secs(),
+            });
+        }
+        tracing::debug!(count, "ignoring command from rate limited user");
+        Ok(false)
+    }
+}
+
+impl<T: EventContext + MessageContext + OwnerContext> CheckRateLimit for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::{CheckRateLimit, MAX_COMMANDS};
+    use crate::{
+        error::Error,
+        test::{MockContext, MOCK_AUTHOR_1, MOCK_AUTHOR_2},
+    };
+
+    #[tokio::test]
+    async fn test_limited() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        for _ in 0..MAX_COMMANDS {
+            assert!(ctx.check_rate_limit().await.unwrap());
+        }
+        assert!(matches!(
+            ctx.check_rate_limit().await,
+            Err(Error::RateLimited {
+                max_commands: MAX_COMMANDS,
+                window_seconds: 30
+            })
+        ));
+        assert!(!ctx.check_rate_limit().await.unwrap());
+
+        // other users are not affected
+        let other = MockContext::with_author(MOCK_AUTHOR_2);
+        assert!(other.check_rate_limit().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_owner() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        ctx.owners.lock().unwrap().insert(MOCK_AUTHOR_2);
+        for _ in 0..=MAX_COMMANDS {
+            assert!(ctx.check_rate_limit().await.unwrap());
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use crate::context::{GuildContext, MessageContext, SettingContext};
+use crate::error::{Error, Result};
+
+use serenity::model::permissions::Permissions;
+
+#[async_trait::async_trait]
+pub trait ClearReminders: SettingContext + GuildContext + MessageContext {
+    #[tracing::instrument(skip(self))]
+    async fn clear_reminders(&self) -> Result<()> {
+        if !self
+            .member_permissions(self.author_id())
+            .await?
+            .manage_guild()
+        {
+            return Err(Error::InsufficientPermission(Permissions::MANAGE_GUILD));
+        }
+
+        for reminder in self.reminders().await? {
+            self.remove_reminder(reminder).await?;
+        }
+        self.react_success().await?;
+        Ok(())
+    }
+}
+
+impl<T: SettingContext + GuildContext + MessageContext> ClearReminders for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::ClearReminders;
+    use crate::{
+        error::Error,
+        model::reminder::Reminder,
+        test::{MockContext, MOCK_AUTHOR_1, MOCK_AUTHOR_2},
+    };
+
+    #[tokio::test]
+    async fn test_success() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        ctx.reminders
+            .lock()
+            .await
+            .insert(Reminder::before_seconds(30));
+        ctx.clear_reminders().await.unwrap();
+        assert!(ctx.reminders.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_insufficient_permission() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        assert!(matches!(
+            ctx.clear_reminders().await,
+            Err(Error::InsufficientPermission(_))
+        ));
+        assert!(!ctx.reminders.lock().await.is_empty());
+    }
+}
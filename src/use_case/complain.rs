@@ -0,0 +1,141 @@
+use crate::context::{ChannelContext, EventContext, MessageContext, TimeContext};
+use crate::error::Result;
+use crate::model::message::Message;
+
+use chrono::Duration;
+
+/// Complaints are accepted only for this long after the disconnection.
+const COMPLAINT_WINDOW_HOURS: i64 = 1;
+
+#[async_trait::async_trait]
+pub trait Complain: EventContext + MessageContext + ChannelContext + TimeContext {
+    #[tracing::instrument(skip(self))]
+    async fn complain(&self) -> Result<()> {
+        let author_id = self.author_id();
+        let since = self.current_time() - Duration::hours(COMPLAINT_WINDOW_HOURS);
+        let events = self.disconnect_events().await?;
+
+        let Some(event) = events
+            .into_iter()
+            .take_while(|e| e.time >= since)
+            .find(|e| e.target == author_id && e.requester != author_id)
+        else {
+            return self.message(Message::NoComplaintTarget).await;
+        };
+
+        if !self.add_complaint(&event, since).await? {
+            return self.message(Message::AlreadyComplained).await;
+        }
+
+        let count = self
+            .complaint_counts()
+            .await?
+            .get(&event.requester)
+            .copied()
+            .unwrap_or_default();
+        self.message(Message::Complained {
+            requester: event.requester,
+            count,
+        })
+        .await
+    }
+}
+
+impl<T: EventContext + MessageContext + ChannelContext + TimeContext> Complain for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::Complain;
+    use crate::{
+        model::{event::DisconnectEvent, message::Message},
+        test::{MockContext, MOCK_AUTHOR_1, MOCK_AUTHOR_2, MOCK_CHANNEL_ID, MOCK_VOICE_CHANNEL_ID},
+    };
+    use chrono::{DateTime, Duration, Utc};
+    use serenity::model::id::UserId;
+
+    fn event(target: UserId, requester: UserId, time: DateTime<Utc>) -> DisconnectEvent {
+        DisconnectEvent {
+            time,
+            channel_id: MOCK_CHANNEL_ID,
+            voice_channel_id: MOCK_VOICE_CHANNEL_ID,
+            target,
+            requester,
+            schedule_id: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_complain() {
+        let now = Utc::now();
+        let ctx = MockContext::with_author_current_time(MOCK_AUTHOR_1, now);
+        *ctx.disconnect_events.lock().await = vec![event(
+            MOCK_AUTHOR_1,
+            MOCK_AUTHOR_2,
+            now - Duration::minutes(5),
+        )];
+
+        ctx.complain().await.unwrap();
+        ctx.complain().await.unwrap();
+
+        assert!(matches!(
+            ctx.sent_messages.lock().await.as_slice(),
+            [Message::Complained { requester, count: 1 }, Message::AlreadyComplained]
+              if requester == &MOCK_AUTHOR_2
+        ));
+        assert_eq!(ctx.complaint_counts.lock().await[&MOCK_AUTHOR_2], 1);
+    }
+
+    #[tokio::test]
+    async fn test_forget_old_complaints() {
+        let now = Utc::now();
+        let ctx = MockContext::with_author_current_time(MOCK_AUTHOR_1, now);
+        let old = event(MOCK_AUTHOR_1, MOCK_AUTHOR_2, now - Duration::hours(2));
+        let recent = event(MOCK_AUTHOR_1, MOCK_AUTHOR_2, now - Duration::minutes(5));
+        ctx.complained_events.lock().await.insert(old.key());
+        *ctx.disconnect_events.lock().await = vec![recent.clone(), old];
+
+        ctx.complain().await.unwrap();
+
+        assert_eq!(
+            *ctx.complained_events.lock().await,
+            [recent.key()].into_iter().collect()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_too_late() {
+        let now = Utc::now();
+        let ctx = MockContext::with_author_current_time(MOCK_AUTHOR_1, now);
+        *ctx.disconnect_events.lock().await = vec![event(
+            MOCK_AUTHOR_1,
+            MOCK_AUTHOR_2,
+            now - Duration::hours(2),
+        )];
+
+        ctx.complain().await.unwrap();
+
+        assert!(matches!(
+            ctx.sent_messages.lock().await.as_slice(),
+            [Message::NoComplaintTarget]
+        ));
+        assert!(ctx.complaint_counts.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_self_kaisan() {
+        let now = Utc::now();
+        let ctx = MockContext::with_author_current_time(MOCK_AUTHOR_1, now);
+        *ctx.disconnect_events.lock().await = vec![event(
+            MOCK_AUTHOR_1,
+            MOCK_AUTHOR_1,
+            now - Duration::minutes(5),
+        )];
+
+        ctx.complain().await.unwrap();
+
+        assert!(matches!(
+            ctx.sent_messages.lock().await.as_slice(),
+            [Message::NoComplaintTarget]
+        ));
+    }
+}
@@ -0,0 +1,72 @@
+use crate::context::{ChannelContext, GuildContext, MessageContext, SettingContext};
+use crate::error::{Error, Result};
+
+use serenity::model::{id::ChannelId, permissions::Permissions};
+
+#[async_trait::async_trait]
+pub trait DenyChannel: SettingContext + GuildContext + MessageContext + ChannelContext {
+    #[tracing::instrument(skip(self))]
+    async fn deny_channel(&self, channel_id: Option<ChannelId>) -> Result<()> {
+        if !self
+            .member_permissions(self.author_id())
+            .await?
+            .manage_guild()
+        {
+            return Err(Error::InsufficientPermission(Permissions::MANAGE_GUILD));
+        }
+
+        let channel_id = channel_id.unwrap_or_else(|| self.channel_id());
+        if !SettingContext::deny_channel(self, channel_id).await? {
+            Err(Error::NoSuchAllowedChannel(channel_id))
+        } else {
+            self.react_success().await?;
+            Ok(())
+        }
+    }
+}
+
+impl<T: SettingContext + GuildContext + MessageContext + ChannelContext> DenyChannel for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::DenyChannel;
+    use crate::{
+        error::Error,
+        test::{MockContext, MOCK_AUTHOR_1, MOCK_AUTHOR_2, MOCK_CHANNEL_ID},
+    };
+    use serenity::model::id::ChannelId;
+
+    #[tokio::test]
+    async fn test_success() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        ctx.allowed_channels
+            .lock()
+            .await
+            .extend([ChannelId::new(1), MOCK_CHANNEL_ID]);
+        ctx.deny_channel(None).await.unwrap();
+        assert_eq!(
+            *ctx.allowed_channels.lock().await,
+            vec![ChannelId::new(1)].into_iter().collect()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_no_such_channel() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        assert!(matches!(
+            ctx.deny_channel(Some(ChannelId::new(1))).await,
+            Err(Error::NoSuchAllowedChannel(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_insufficient_permission() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        ctx.allowed_channels.lock().await.insert(MOCK_CHANNEL_ID);
+        assert!(matches!(
+            ctx.deny_channel(None).await,
+            Err(Error::InsufficientPermission(_))
+        ));
+        assert_eq!(ctx.allowed_channels.lock().await.len(), 1);
+    }
+}
@@ -0,0 +1,71 @@
+use crate::context::{GuildContext, MessageContext, SettingContext};
+use crate::error::{Error, Result};
+
+use serenity::model::{id::RoleId, permissions::Permissions};
+
+#[async_trait::async_trait]
+pub trait DenyRole: SettingContext + GuildContext + MessageContext {
+    #[tracing::instrument(skip(self))]
+    async fn deny_role(&self, role_id: RoleId) -> Result<()> {
+        if !self
+            .member_permissions(self.author_id())
+            .await?
+            .manage_guild()
+        {
+            return Err(Error::InsufficientPermission(Permissions::MANAGE_GUILD));
+        }
+
+        if !SettingContext::deny_role(self, role_id).await? {
+            Err(Error::NoSuchAllowedRole(role_id))
+        } else {
+            self.react_success().await?;
+            Ok(())
+        }
+    }
+}
+
+impl<T: SettingContext + GuildContext + MessageContext> DenyRole for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::DenyRole;
+    use crate::{
+        error::Error,
+        test::{MockContext, MOCK_AUTHOR_1, MOCK_AUTHOR_2},
+    };
+    use serenity::model::id::RoleId;
+
+    #[tokio::test]
+    async fn test_success() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        ctx.allowed_roles
+            .lock()
+            .await
+            .extend([RoleId::new(1), RoleId::new(2)]);
+        ctx.deny_role(RoleId::new(2)).await.unwrap();
+        assert_eq!(
+            *ctx.allowed_roles.lock().await,
+            vec![RoleId::new(1)].into_iter().collect()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_no_such_role() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        assert!(matches!(
+            ctx.deny_role(RoleId::new(1)).await,
+            Err(Error::NoSuchAllowedRole(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_insufficient_permission() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        ctx.allowed_roles.lock().await.insert(RoleId::new(1));
+        assert!(matches!(
+            ctx.deny_role(RoleId::new(1)).await,
+            Err(Error::InsufficientPermission(_))
+        ));
+        assert_eq!(ctx.allowed_roles.lock().await.len(), 1);
+    }
+}
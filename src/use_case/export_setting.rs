@@ -0,0 +1,151 @@
+use crate::context::{ChannelContext, GuildContext, MessageContext, SettingContext};
+use crate::error::{Error, Result};
+use crate::model::{message::Message, setting::Setting};
+
+use anyhow::Context as _;
+use serenity::model::permissions::Permissions;
+
+pub const SETTING_FILE_NAME: &str = "kaisantantoudaijin-setting.json";
+
+#[async_trait::async_trait]
+pub trait ExportSetting: SettingContext + GuildContext + MessageContext + ChannelContext {
+    #[tracing::instrument(skip(self))]
+    async fn export_setting(&self) -> Result<()> {
+        if !self
+            .member_permissions(self.author_id())
+            .await?
+            .manage_guild()
+        {
+            return Err(Error::InsufficientPermission(Permissions::MANAGE_GUILD));
+        }
+
+        let setting = read_setting(self).await?;
+        let data = serde_json::to_vec_pretty(&setting).context("cannot serialize setting")?;
+        self.message_with_attachment(Message::ExportedSetting, SETTING_FILE_NAME, data)
+            .await
+    }
+}
+
+impl<T: SettingContext + GuildContext + MessageContext + ChannelContext> ExportSetting for T {}
+
+/// Reads all the settings of the guild, to be exported or saved as a profile.
+pub(super) async fn read_setting<C>(ctx: &C) -> Result<Setting>
+where
+    C: SettingContext + Sync + ?Sized,
+{
+    let (
+        requires_permission,
+        requires_permission_self,
+        timezone,
+        reminds_random_kaisan,
+        reminders,
+        auto_kaisan_hour,
+        max_horizon_hours,
+        tonight_hour,
+        max_schedules_per_user,
+        undo_window_minutes,
+        countdown,
+        author_leave_policy,
+        reveal_random,
+        random_distribution,
+        dst_policy,
+        week_start,
+        locale,
+        on_duplicate,
+        allowed_channels,
+        allowed_roles,
+        command_prefix,
+        reminder_text,
+    ) = futures::try_join!(
+        ctx.requires_permission(),
+        ctx.requires_permission_self(),
+        ctx.timezone(),
+        ctx.reminds_random_kaisan(),
+        ctx.reminders(),
+        ctx.auto_kaisan_hour(),
+        ctx.max_horizon_hours(),
+        ctx.tonight_hour(),
+        ctx.max_schedules_per_user(),
+        ctx.undo_window_minutes(),
+        ctx.countdown(),
+        ctx.author_leave_policy(),
+        ctx.reveal_random(),
+        ctx.random_distribution(),
+        ctx.dst_policy(),
+        ctx.week_start(),
+        ctx.locale(),
+        ctx.on_duplicate(),
+        ctx.allowed_channels(),
+        ctx.allowed_roles(),
+        ctx.command_prefix(),
+        ctx.reminder_template(),
+    )?;
+
+    Ok(Setting {
+        timezone,
+        requires_permission,
+        requires_permission_self,
+        reminders: reminders.into_iter().collect(),
+        reminds_random_kaisan,
+        auto_kaisan_hour,
+        max_horizon_hours,
+        tonight_hour,
+        max_schedules_per_user,
+        undo_window_minutes,
+        countdown,
+        author_leave_policy,
+        reveal_random,
+        random_distribution,
+        dst_policy,
+        week_start,
+        locale,
+        on_duplicate,
+        allowed_channels: allowed_channels.into_iter().collect(),
+        allowed_roles: allowed_roles.into_iter().collect(),
+        command_prefix,
+        reminder_text,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ExportSetting, SETTING_FILE_NAME};
+    use crate::{
+        error::Error,
+        model::{message::Message, reminder::Reminder, setting::Setting},
+        test::{MockContext, MOCK_AUTHOR_1, MOCK_AUTHOR_2},
+    };
+    use chrono_tz::Tz;
+
+    #[tokio::test]
+    async fn test_success() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        *ctx.timezone.lock().await = Tz::UTC;
+        ctx.export_setting().await.unwrap();
+
+        assert!(matches!(
+            ctx.sent_messages.lock().await.as_slice(),
+            [Message::ExportedSetting]
+        ));
+        let attachments = ctx.sent_attachments.lock().await;
+        let [attachment] = attachments.as_slice() else {
+            panic!("expected exactly one attachment");
+        };
+        assert_eq!(attachment.filename, SETTING_FILE_NAME);
+        let setting: Setting = serde_json::from_slice(&attachment.data).unwrap();
+        assert_eq!(setting.timezone, Tz::UTC);
+        assert_eq!(
+            setting.reminders,
+            vec![Reminder::before_minutes(5)].into_iter().collect()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_insufficient_permission() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        assert!(matches!(
+            ctx.export_setting().await,
+            Err(Error::InsufficientPermission(_))
+        ));
+    }
+}
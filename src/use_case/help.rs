@@ -4,6 +4,7 @@ use crate::model::message::Message;
 
 #[async_trait::async_trait]
 pub trait Help: ChannelContext {
+    #[tracing::instrument(skip(self))]
     async fn help(&self) -> Result<()> {
         self.message(Message::Help).await
     }
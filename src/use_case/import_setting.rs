@@ -0,0 +1,259 @@
+use std::sync::Arc;
+
+use crate::context::{GuildContext, MessageContext, SettingContext};
+use crate::error::{Error, Result};
+use crate::model::setting::SettingPatch;
+
+use serenity::model::permissions::Permissions;
+
+/// The settings take a few KiB at most, even with many allowed channels and roles.
+const MAX_SETTING_FILE_BYTES: u32 = 64 * 1024;
+
+#[async_trait::async_trait]
+pub trait ImportSetting: SettingContext + GuildContext + MessageContext {
+    #[tracing::instrument(skip(self))]
+    async fn import_setting(&self) -> Result<()> {
+        if !self
+            .member_permissions(self.author_id())
+            .await?
+            .manage_guild()
+        {
+            return Err(Error::InsufficientPermission(Permissions::MANAGE_GUILD));
+        }
+
+        let Some(data) = self.attachment(MAX_SETTING_FILE_BYTES).await? else {
+            return Err(Error::MissingAttachment);
+        };
+        let setting: SettingPatch =
+            serde_json::from_slice(&data).map_err(|e| Error::InvalidSettingFile(Arc::new(e)))?;
+
+        write_setting(self, setting).await?;
+        self.react_success().await?;
+        Ok(())
+    }
+}
+
+impl<T: SettingContext + GuildContext + MessageContext> ImportSetting for T {}
+
+/// Overwrites the settings of the guild given in `setting`, as imported or loaded from a profile.
+/// Nothing is written if any of the values is invalid.
+pub(super) async fn write_setting<C>(ctx: &C, setting: SettingPatch) -> Result<()>
+where
+    C: SettingContext + Sync + ?Sized,
+{
+    if let Some(field) = setting.invalid_field() {
+        return Err(Error::InvalidSettingValue(field));
+    }
+    ctx.overwrite_settings(setting).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ImportSetting;
+    use crate::{
+        error::Error,
+        model::{
+            reminder::Reminder,
+            setting::{
+                AuthorLeavePolicy, DstPolicy, DuplicatePolicy, Locale, RandomDistribution,
+                RevealRandom, WeekStart,
+            },
+            time::Hour,
+        },
+        test::{MockContext, MOCK_AUTHOR_1, MOCK_AUTHOR_2, MOCK_CHANNEL_ID},
+    };
+    use chrono_tz::Tz;
+    use serenity::model::id::RoleId;
+    use std::sync::atomic::Ordering;
+
+    #[tokio::test]
+    async fn test_success() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        *ctx.attachment.lock().await = Some(
+            br#"{
+                "timezone": "UTC",
+                "requires_permission": false,
+                "requires_permission_self": true,
+                "reminders": [1, 10],
+                "reminds_random_kaisan": true,
+                "auto_kaisan_hour": 23,
+                "max_horizon_hours": 24,
+                "tonight_hour": 22,
+                "max_schedules_per_user": 5,
+                "undo_window_minutes": 10,
+                "countdown": true,
+                "author_leave_policy": "reroll",
+                "reveal_random": "dm",
+                "random_distribution": "late-biased",
+                "dst_policy": "latest",
+                "week_start": "sunday",
+                "locale": "both",
+                "on_duplicate": "replace",
+                "allowed_channels": ["7933013268500803584"],
+                "allowed_roles": ["1234"],
+                "command_prefix": "!k",
+                "reminder_text": "{remaining} left"
+            }"#
+            .to_vec(),
+        );
+        ctx.import_setting().await.unwrap();
+
+        assert_eq!(*ctx.timezone.lock().await, Tz::UTC);
+        assert!(!ctx.requires_permission.load(Ordering::SeqCst));
+        assert!(ctx.requires_permission_self.load(Ordering::SeqCst));
+        assert!(ctx.reminds_random_kaisan.load(Ordering::SeqCst));
+        assert_eq!(
+            *ctx.auto_kaisan_hour.lock().await,
+            Some(Hour::from_u8(23).unwrap())
+        );
+        assert_eq!(ctx.max_horizon_hours.load(Ordering::SeqCst), 24);
+        assert_eq!(*ctx.tonight_hour.lock().await, Hour::from_u8(22).unwrap());
+        assert_eq!(ctx.max_schedules_per_user.load(Ordering::SeqCst), 5);
+        assert_eq!(ctx.undo_window_minutes.load(Ordering::SeqCst), 10);
+        assert!(ctx.countdown.load(Ordering::SeqCst));
+        assert_eq!(
+            *ctx.author_leave_policy.lock().await,
+            AuthorLeavePolicy::Reroll
+        );
+        assert_eq!(*ctx.reveal_random.lock().await, RevealRandom::DirectMessage);
+        assert_eq!(
+            *ctx.random_distribution.lock().await,
+            RandomDistribution::LateBiased
+        );
+        assert_eq!(*ctx.dst_policy.lock().await, DstPolicy::Latest);
+        assert_eq!(*ctx.week_start.lock().await, WeekStart::Sunday);
+        assert_eq!(*ctx.locale.lock().await, Locale::Both);
+        assert_eq!(*ctx.on_duplicate.lock().await, DuplicatePolicy::Replace);
+        assert_eq!(*ctx.command_prefix.lock().await, Some("!k".to_owned()));
+        assert_eq!(
+            ctx.reminder_template
+                .lock()
+                .await
+                .as_ref()
+                .map(|t| t.as_str().to_owned()),
+            Some("{remaining} left".to_owned())
+        );
+        assert_eq!(
+            *ctx.reminders.lock().await,
+            vec![Reminder::before_minutes(1), Reminder::before_minutes(10)]
+                .into_iter()
+                .collect()
+        );
+        assert_eq!(
+            *ctx.allowed_channels.lock().await,
+            vec![MOCK_CHANNEL_ID].into_iter().collect()
+        );
+        assert_eq!(
+            *ctx.allowed_roles.lock().await,
+            vec![RoleId::new(1234)].into_iter().collect()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_partial() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        ctx.requires_permission.store(false, Ordering::SeqCst);
+        ctx.reminders
+            .lock()
+            .await
+            .insert(Reminder::before_minutes(5));
+        *ctx.command_prefix.lock().await = Some("!k".to_owned());
+        *ctx.attachment.lock().await = Some(br#"{"timezone": "UTC"}"#.to_vec());
+        ctx.import_setting().await.unwrap();
+
+        assert_eq!(*ctx.timezone.lock().await, Tz::UTC);
+        // the settings missing in the file are left as they are
+        assert!(!ctx.requires_permission.load(Ordering::SeqCst));
+        assert_eq!(
+            *ctx.reminders.lock().await,
+            vec![Reminder::before_minutes(5)].into_iter().collect()
+        );
+        assert_eq!(*ctx.command_prefix.lock().await, Some("!k".to_owned()));
+    }
+
+    #[tokio::test]
+    async fn test_reset() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        ctx.reminders
+            .lock()
+            .await
+            .insert(Reminder::before_minutes(5));
+        *ctx.command_prefix.lock().await = Some("!k".to_owned());
+        *ctx.auto_kaisan_hour.lock().await = Some(Hour::from_u8(23).unwrap());
+        *ctx.attachment.lock().await =
+            Some(br#"{"reminders": [], "command_prefix": null}"#.to_vec());
+        ctx.import_setting().await.unwrap();
+
+        assert!(ctx.reminders.lock().await.is_empty());
+        assert_eq!(*ctx.command_prefix.lock().await, None);
+        assert_eq!(
+            *ctx.auto_kaisan_hour.lock().await,
+            Some(Hour::from_u8(23).unwrap())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_missing_attachment() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        assert!(matches!(
+            ctx.import_setting().await,
+            Err(Error::MissingAttachment)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_setting_file() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        *ctx.attachment.lock().await = Some(br#"{"timezone": "No/Such"}"#.to_vec());
+        assert!(matches!(
+            ctx.import_setting().await,
+            Err(Error::InvalidSettingFile(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_value() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        for (data, field) in [
+            (
+                r#"{"timezone": "UTC", "command_prefix": ""}"#,
+                "command_prefix",
+            ),
+            (r#"{"command_prefix": "! k"}"#, "command_prefix"),
+            (r#"{"max_schedules_per_user": 0}"#, "max_schedules_per_user"),
+            (r#"{"max_horizon_hours": 0}"#, "max_horizon_hours"),
+        ] {
+            *ctx.attachment.lock().await = Some(data.as_bytes().to_vec());
+            assert!(matches!(
+                ctx.import_setting().await,
+                Err(Error::InvalidSettingValue(f)) if f == field
+            ));
+        }
+        // nothing is written, not even the valid fields
+        assert_eq!(*ctx.timezone.lock().await, chrono_tz::Japan);
+        assert_eq!(*ctx.command_prefix.lock().await, None);
+    }
+
+    #[tokio::test]
+    async fn test_too_large() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        let mut data = br#"{"timezone": "UTC", "command_prefix": ""#.to_vec();
+        data.resize(data.len() + 64 * 1024, b'x');
+        data.extend_from_slice(br#""}"#);
+        *ctx.attachment.lock().await = Some(data);
+        assert!(matches!(
+            ctx.import_setting().await,
+            Err(Error::AttachmentTooLarge { .. })
+        ));
+        assert_eq!(*ctx.timezone.lock().await, chrono_tz::Japan);
+    }
+
+    #[tokio::test]
+    async fn test_insufficient_permission() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        assert!(matches!(
+            ctx.import_setting().await,
+            Err(Error::InsufficientPermission(_))
+        ));
+    }
+}
@@ -0,0 +1,125 @@
+use super::schedule_kaisan::{check_permission, kaisan, ScheduleKaisan};
+use crate::error::{Error, Result};
+use crate::model::{kaisanee::KaisaneeSpecifier, schedule::ScheduleId};
+
+#[async_trait::async_trait]
+pub trait KaisanNow: ScheduleKaisan + Sync {
+    /// Carries out a pending schedule ahead of its time.
+    #[tracing::instrument(skip(self))]
+    async fn kaisan_now(&self, id: ScheduleId) -> Result<()> {
+        let Some((_, schedule)) = self.schedules().await.into_iter().find(|(i, _)| *i == id) else {
+            return Err(Error::NoSuchSchedule(id));
+        };
+
+        // `Me` refers to the author of the schedule, not to whoever runs it
+        let kaisanee = match schedule.kaisanee {
+            KaisaneeSpecifier::Me if schedule.author_id != self.author_id() => {
+                KaisaneeSpecifier::Users(vec![schedule.author_id])
+            }
+            kaisanee => kaisanee,
+        };
+        let settings = self.settings_snapshot().await?;
+        check_permission(self, &kaisanee, &settings).await?;
+
+        if self.cancel_schedule(id).await.is_none() {
+            // already carried out in the meantime
+            return Err(Error::NoSuchSchedule(id));
+        }
+        tracing::info!(?kaisanee, "kaisan ahead of schedule");
+        kaisan(
+            self,
+            Some(id),
+            schedule.voice_channel_id,
+            &kaisanee,
+            &schedule.opted_out,
+        )
+        .await
+    }
+}
+
+impl<T: ScheduleKaisan + Sync> KaisanNow for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::KaisanNow;
+    use crate::{
+        context::ScheduleContext,
+        error::Error,
+        model::{
+            kaisanee::KaisaneeSpecifier,
+            message::Message,
+            schedule::{Schedule, ScheduleId},
+        },
+        test::{mock_schedule, MockContext, MOCK_AUTHOR_1, MOCK_AUTHOR_2},
+    };
+    use chrono::{Duration, Utc};
+    use serenity::model::id::UserId;
+
+    fn schedule(author_id: UserId, kaisanee: KaisaneeSpecifier) -> Schedule {
+        Schedule {
+            kaisanee,
+            ..mock_schedule(author_id, Utc::now() + Duration::minutes(10))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_success() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        let id = ctx
+            .register_schedule(schedule(MOCK_AUTHOR_1, KaisaneeSpecifier::All))
+            .await;
+
+        ctx.kaisan_now(id).await.unwrap();
+
+        assert!(ctx.schedules().await.is_empty());
+        let disconnected = ctx.disconnected_users.lock().await;
+        assert_eq!(disconnected.len(), 2);
+        assert!(disconnected.contains(&MOCK_AUTHOR_1) && disconnected.contains(&MOCK_AUTHOR_2));
+        assert!(matches!(
+            ctx.sent_messages.lock().await.as_slice(),
+            [
+                Message::Kaisan(_),
+                Message::KaisanSummary {
+                    count: 2,
+                    session: None
+                }
+            ]
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_me_of_other_author() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        let id = ctx
+            .register_schedule(schedule(MOCK_AUTHOR_1, KaisaneeSpecifier::Me))
+            .await;
+
+        ctx.kaisan_now(id).await.unwrap();
+
+        assert_eq!(*ctx.disconnected_users.lock().await, vec![MOCK_AUTHOR_1]);
+    }
+
+    #[tokio::test]
+    async fn test_no_such_schedule() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        assert!(matches!(
+            ctx.kaisan_now(ScheduleId::new(42)).await,
+            Err(Error::NoSuchSchedule(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_insufficient_permission() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        let id = ctx
+            .register_schedule(schedule(MOCK_AUTHOR_2, KaisaneeSpecifier::Me))
+            .await;
+
+        assert!(matches!(
+            ctx.kaisan_now(id).await,
+            Err(Error::InsufficientPermission(_))
+        ));
+        assert_eq!(ctx.schedules().await.len(), 1);
+        assert!(ctx.disconnected_users.lock().await.is_empty());
+    }
+}
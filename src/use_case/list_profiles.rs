@@ -0,0 +1,39 @@
+use crate::context::{ChannelContext, SettingContext};
+use crate::error::Result;
+use crate::model::message::Message;
+
+#[async_trait::async_trait]
+pub trait ListProfiles: SettingContext + ChannelContext {
+    #[tracing::instrument(skip(self))]
+    async fn list_profiles(&self) -> Result<()> {
+        let mut names: Vec<_> = self.profile_names().await?.into_iter().collect();
+        names.sort();
+        self.message(Message::Profiles(names)).await
+    }
+}
+
+impl<T: SettingContext + ChannelContext> ListProfiles for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::ListProfiles;
+    use crate::{
+        model::{message::Message, setting::Setting},
+        test::{MockContext, MOCK_AUTHOR_1},
+    };
+
+    #[tokio::test]
+    async fn test_list() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        ctx.profiles.lock().await.extend([
+            ("weekend".to_owned(), Setting::default()),
+            ("exam-week".to_owned(), Setting::default()),
+        ]);
+
+        ctx.list_profiles().await.unwrap();
+        assert!(matches!(
+            ctx.sent_messages.lock().await.as_slice(),
+            [Message::Profiles(names)] if names == &["exam-week", "weekend"]
+        ));
+    }
+}
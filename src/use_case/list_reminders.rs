@@ -0,0 +1,45 @@
+use crate::context::{ChannelContext, SettingContext};
+use crate::error::Result;
+use crate::model::message::Message;
+
+#[async_trait::async_trait]
+pub trait ListReminders: SettingContext + ChannelContext {
+    #[tracing::instrument(skip(self))]
+    async fn list_reminders(&self) -> Result<()> {
+        let mut reminders: Vec<_> = self.reminders().await?.into_iter().collect();
+        // in the order they are sent
+        reminders.sort_by(|a, b| b.cmp(a));
+        self.message(Message::Reminders(reminders)).await
+    }
+}
+
+impl<T: SettingContext + ChannelContext> ListReminders for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::ListReminders;
+    use crate::{
+        model::{message::Message, reminder::Reminder},
+        test::{MockContext, MOCK_AUTHOR_1},
+    };
+
+    #[tokio::test]
+    async fn test() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        ctx.reminders
+            .lock()
+            .await
+            .extend([Reminder::before_seconds(30), Reminder::before_minutes(10)]);
+        ctx.list_reminders().await.unwrap();
+
+        assert!(matches!(
+            ctx.sent_messages.lock().await.as_slice(),
+            [Message::Reminders(reminders)]
+              if reminders == &[
+                  Reminder::before_minutes(10),
+                  Reminder::before_minutes(5),
+                  Reminder::before_seconds(30),
+              ]
+        ));
+    }
+}
@@ -0,0 +1,40 @@
+use crate::context::{ChannelContext, SettingContext};
+use crate::error::Result;
+use crate::model::message::Message;
+
+#[async_trait::async_trait]
+pub trait ListRoles: SettingContext + ChannelContext {
+    #[tracing::instrument(skip(self))]
+    async fn list_roles(&self) -> Result<()> {
+        let mut roles: Vec<_> = self.allowed_roles().await?.into_iter().collect();
+        roles.sort();
+        self.message(Message::AllowedRoles(roles)).await
+    }
+}
+
+impl<T: SettingContext + ChannelContext> ListRoles for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::ListRoles;
+    use crate::{
+        model::message::Message,
+        test::{MockContext, MOCK_AUTHOR_1},
+    };
+    use serenity::model::id::RoleId;
+
+    #[tokio::test]
+    async fn test() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        ctx.allowed_roles
+            .lock()
+            .await
+            .extend([RoleId::new(2), RoleId::new(1)]);
+        ctx.list_roles().await.unwrap();
+
+        assert!(matches!(
+            ctx.sent_messages.lock().await.as_slice(),
+            [Message::AllowedRoles(roles)] if roles == &[RoleId::new(1), RoleId::new(2)]
+        ));
+    }
+}
@@ -0,0 +1,79 @@
+use super::import_setting::write_setting;
+use crate::context::{GuildContext, MessageContext, SettingContext};
+use crate::error::{Error, Result};
+
+use serenity::model::permissions::Permissions;
+
+#[async_trait::async_trait]
+pub trait LoadProfile: SettingContext + GuildContext + MessageContext + Sync {
+    /// Replaces the current settings with the ones saved under the name.
+    #[tracing::instrument(skip(self))]
+    async fn load_profile(&self, name: String) -> Result<()> {
+        if !self
+            .member_permissions(self.author_id())
+            .await?
+            .manage_guild()
+        {
+            return Err(Error::InsufficientPermission(Permissions::MANAGE_GUILD));
+        }
+
+        let Some(setting) = self.profile(&name).await? else {
+            return Err(Error::NoSuchProfile(name));
+        };
+        write_setting(self, setting.into()).await?;
+        self.react_success().await?;
+        Ok(())
+    }
+}
+
+impl<T: SettingContext + GuildContext + MessageContext + Sync> LoadProfile for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::LoadProfile;
+    use crate::{
+        error::Error,
+        model::setting::{DuplicatePolicy, Setting},
+        test::{MockContext, MOCK_AUTHOR_1, MOCK_AUTHOR_2},
+    };
+    use std::sync::atomic::Ordering;
+
+    #[tokio::test]
+    async fn test_success() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        ctx.profiles.lock().await.insert(
+            "exam-week".to_owned(),
+            Setting {
+                max_schedules_per_user: 1,
+                on_duplicate: DuplicatePolicy::Reject,
+                ..Setting::default()
+            },
+        );
+
+        ctx.load_profile("exam-week".to_owned()).await.unwrap();
+        assert_eq!(ctx.max_schedules_per_user.load(Ordering::SeqCst), 1);
+        assert_eq!(*ctx.on_duplicate.lock().await, DuplicatePolicy::Reject);
+    }
+
+    #[tokio::test]
+    async fn test_no_such_profile() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        assert!(matches!(
+            ctx.load_profile("weekend".to_owned()).await,
+            Err(Error::NoSuchProfile(name)) if name == "weekend"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_insufficient_permission() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        ctx.profiles
+            .lock()
+            .await
+            .insert("weekend".to_owned(), Setting::default());
+        assert!(matches!(
+            ctx.load_profile("weekend".to_owned()).await,
+            Err(Error::InsufficientPermission(_))
+        ));
+    }
+}
@@ -0,0 +1,60 @@
+use crate::context::{ChannelContext, SettingContext};
+use crate::model::message::{LogEntry, Message};
+
+/// Mirrors `entry` to the log channel of the guild, if any. Failing to do so is only logged, as
+/// the log channel is a record of what happens and never gets in the way of it.
+pub(super) async fn log_event<C>(ctx: &C, entry: LogEntry)
+where
+    C: SettingContext + ChannelContext + Sync + ?Sized,
+{
+    let channel_id = match ctx.log_channel().await {
+        Ok(Some(channel_id)) => channel_id,
+        Ok(None) => return,
+        Err(e) => {
+            tracing::warn!(error = %e, "failed to read log channel");
+            return;
+        }
+    };
+    if let Err(e) = ctx.message_to(channel_id, Message::Log(entry)).await {
+        tracing::warn!(error = %e, %channel_id, "failed to post to log channel");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::log_event;
+    use crate::{
+        model::message::{LogEntry, Message},
+        model::schedule::ScheduleId,
+        test::{MockContext, MOCK_AUTHOR_1, MOCK_VOICE_CHANNEL_ID},
+    };
+    use serenity::model::id::ChannelId;
+
+    fn entry() -> LogEntry {
+        LogEntry::Cancelled {
+            schedule_id: ScheduleId::new(1),
+            voice_channel_id: MOCK_VOICE_CHANNEL_ID,
+            by: Some(MOCK_AUTHOR_1),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_log_channel() {
+        let ctx = MockContext::new();
+        let log_channel = ChannelId::new(42);
+        *ctx.log_channel.lock().await = Some(log_channel);
+        log_event(&ctx, entry()).await;
+        assert!(matches!(
+            ctx.channel_messages.lock().await.as_slice(),
+            [(channel_id, Message::Log(LogEntry::Cancelled { .. }))] if *channel_id == log_channel
+        ));
+        assert!(ctx.sent_messages.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_no_log_channel() {
+        let ctx = MockContext::new();
+        log_event(&ctx, entry()).await;
+        assert!(ctx.channel_messages.lock().await.is_empty());
+    }
+}
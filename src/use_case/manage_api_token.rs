@@ -0,0 +1,89 @@
+use crate::context::{ChannelContext, GuildContext, MessageContext, SettingContext};
+use crate::error::{Error, Result};
+use crate::model::{api_token::ApiToken, message::Message};
+
+use serenity::model::permissions::Permissions;
+
+#[async_trait::async_trait]
+pub trait ManageApiToken: SettingContext + GuildContext + ChannelContext + MessageContext {
+    /// Replaces the API token of the guild with a new one, which is sent to the author by DM.
+    #[tracing::instrument(skip(self))]
+    async fn generate_api_token(&self) -> Result<()> {
+        if !self
+            .member_permissions(self.author_id())
+            .await?
+            .manage_guild()
+        {
+            return Err(Error::InsufficientPermission(Permissions::MANAGE_GUILD));
+        }
+
+        let token = ApiToken::generate();
+        self.set_api_token_hash(Some(token.hash())).await?;
+        self.direct_message(self.author_id(), Message::ApiTokenGenerated(token))
+            .await?;
+        self.react_success().await
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn revoke_api_token(&self) -> Result<()> {
+        if !self
+            .member_permissions(self.author_id())
+            .await?
+            .manage_guild()
+        {
+            return Err(Error::InsufficientPermission(Permissions::MANAGE_GUILD));
+        }
+
+        self.set_api_token_hash(None).await?;
+        self.react_success().await
+    }
+}
+
+impl<T: SettingContext + GuildContext + ChannelContext + MessageContext> ManageApiToken for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::ManageApiToken;
+    use crate::{
+        error::Error,
+        model::message::Message,
+        test::{MockContext, MOCK_AUTHOR_1, MOCK_AUTHOR_2},
+    };
+
+    #[tokio::test]
+    async fn test_generate_revoke() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        ctx.generate_api_token().await.unwrap();
+        ctx.generate_api_token().await.unwrap();
+
+        let hash = ctx.api_token_hash.lock().await.clone().unwrap();
+        let direct_messages = ctx.direct_messages.lock().await.clone();
+        assert!(matches!(
+            direct_messages.as_slice(),
+            [
+                (old_to, Message::ApiTokenGenerated(old)),
+                (new_to, Message::ApiTokenGenerated(new)),
+            ] if *old_to == MOCK_AUTHOR_2 && *new_to == MOCK_AUTHOR_2
+                && !old.matches(&hash) && new.matches(&hash)
+        ));
+        assert!(ctx.sent_messages.lock().await.is_empty());
+
+        ctx.revoke_api_token().await.unwrap();
+        assert_eq!(*ctx.api_token_hash.lock().await, None);
+    }
+
+    #[tokio::test]
+    async fn test_insufficient_permission() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        assert!(matches!(
+            ctx.generate_api_token().await,
+            Err(Error::InsufficientPermission(_))
+        ));
+        assert!(matches!(
+            ctx.revoke_api_token().await,
+            Err(Error::InsufficientPermission(_))
+        ));
+        assert_eq!(*ctx.api_token_hash.lock().await, None);
+        assert!(ctx.direct_messages.lock().await.is_empty());
+    }
+}
@@ -0,0 +1,149 @@
+use super::schedule_kaisan::check_permission;
+use crate::context::{GuildContext, MessageContext, ScheduleContext, SettingContext};
+use crate::error::Result;
+use crate::model::kaisanee::KaisaneeSpecifier;
+
+#[async_trait::async_trait]
+pub trait OptIn: ScheduleContext + GuildContext + MessageContext + SettingContext {
+    /// Adds the author to the targets of the kaisan scheduled by the message.
+    #[tracing::instrument(skip(self))]
+    async fn opt_in(&self) -> Result<()> {
+        let Some(message_id) = self.message_id() else {
+            return Ok(());
+        };
+        let Some((id, _)) = self
+            .schedules()
+            .await
+            .into_iter()
+            .find(|(_, schedule)| schedule.message_id == Some(message_id))
+        else {
+            return Ok(());
+        };
+
+        let author_id = self.author_id();
+        // joining is disconnecting only yourself, which may require the permission as well
+        let settings = self.settings_snapshot().await?;
+        check_permission(self, &KaisaneeSpecifier::Users(vec![author_id]), &settings).await?;
+
+        let updated = self
+            .update_schedule(id, move |schedule| {
+                schedule.opted_out.retain(|user_id| *user_id != author_id);
+                match &mut schedule.kaisanee {
+                    // names are resolved before a schedule is made
+                    KaisaneeSpecifier::All | KaisaneeSpecifier::Named(_) => {}
+                    KaisaneeSpecifier::Me if schedule.author_id != author_id => {
+                        schedule.kaisanee =
+                            KaisaneeSpecifier::Users(vec![schedule.author_id, author_id]);
+                    }
+                    KaisaneeSpecifier::Me => {}
+                    KaisaneeSpecifier::Users(users) => {
+                        if !users.contains(&author_id) {
+                            users.push(author_id);
+                        }
+                    }
+                    KaisaneeSpecifier::ThisChannel { except } => {
+                        except.retain(|user_id| *user_id != author_id);
+                    }
+                }
+            })
+            .await;
+        if updated.is_some() {
+            tracing::info!(?id, %author_id, "joined the kaisan");
+        }
+        Ok(())
+    }
+}
+
+impl<T: ScheduleContext + GuildContext + MessageContext + SettingContext> OptIn for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::OptIn;
+    use crate::{
+        context::ScheduleContext,
+        error::Error,
+        model::{
+            command::{KaisanOptions, TimeRangeSpecifier},
+            kaisanee::KaisaneeSpecifier,
+            message::Message,
+            time::{AfterTimeSpecifier, TimeSpecifier},
+        },
+        test::{MockContext, MOCK_AUTHOR_1, MOCK_AUTHOR_2},
+        use_case::{OptOut, ScheduleKaisan},
+    };
+    use chrono::{Duration, Utc};
+    use std::sync::atomic::Ordering;
+
+    fn after_10_minutes() -> TimeRangeSpecifier {
+        TimeRangeSpecifier::At(TimeSpecifier::After(AfterTimeSpecifier::Minute(10)))
+    }
+
+    #[tokio::test]
+    async fn test_join_me() {
+        let time = Utc::now();
+        let ctx = MockContext::with_author_current_time(MOCK_AUTHOR_2, time);
+        ctx.schedule_kaisan(
+            KaisaneeSpecifier::Me,
+            after_10_minutes(),
+            KaisanOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        let mut reacted = ctx.clone();
+        reacted.author_id = MOCK_AUTHOR_1;
+        reacted.opt_in().await.unwrap();
+        reacted.opt_in().await.unwrap();
+        assert_eq!(
+            ctx.schedules().await[0].1.kaisanee,
+            KaisaneeSpecifier::Users(vec![MOCK_AUTHOR_2, MOCK_AUTHOR_1])
+        );
+
+        ctx.scheduler.advance_to(time + Duration::minutes(10)).await;
+        assert!(ctx.has_sent(|m| matches!(m, Message::Kaisan(_))).await);
+        let users = ctx.disconnected_users.lock().await;
+        assert!(users.contains(&MOCK_AUTHOR_1));
+        assert!(users.contains(&MOCK_AUTHOR_2));
+    }
+
+    #[tokio::test]
+    async fn test_undo_opt_out() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        ctx.schedule_kaisan(
+            KaisaneeSpecifier::All,
+            after_10_minutes(),
+            KaisanOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        let mut reacted = ctx.clone();
+        reacted.author_id = MOCK_AUTHOR_1;
+        reacted.opt_out().await.unwrap();
+        reacted.opt_in().await.unwrap();
+        let schedule = &ctx.schedules().await[0].1;
+        assert!(schedule.opted_out.is_empty());
+        assert_eq!(schedule.kaisanee, KaisaneeSpecifier::All);
+    }
+
+    #[tokio::test]
+    async fn test_insufficient_permission() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        ctx.schedule_kaisan(
+            KaisaneeSpecifier::Me,
+            after_10_minutes(),
+            KaisanOptions::default(),
+        )
+        .await
+        .unwrap();
+        ctx.requires_permission_self.store(true, Ordering::SeqCst);
+
+        let mut reacted = ctx.clone();
+        reacted.author_id = MOCK_AUTHOR_1;
+        assert!(matches!(
+            reacted.opt_in().await,
+            Err(Error::InsufficientPermission(_))
+        ));
+        assert_eq!(ctx.schedules().await[0].1.kaisanee, KaisaneeSpecifier::Me);
+    }
+}
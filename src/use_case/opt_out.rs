@@ -0,0 +1,97 @@
+use crate::context::{MessageContext, ScheduleContext};
+use crate::error::Result;
+use crate::model::kaisanee::KaisaneeSpecifier;
+
+#[async_trait::async_trait]
+pub trait OptOut: ScheduleContext + MessageContext {
+    /// Leaves the author out of the kaisan for everyone, or everyone in the channel, scheduled by
+    /// the message.
+    #[tracing::instrument(skip(self))]
+    async fn opt_out(&self) -> Result<()> {
+        let Some(message_id) = self.message_id() else {
+            return Ok(());
+        };
+        let Some((id, _)) = self.schedules().await.into_iter().find(|(_, schedule)| {
+            schedule.message_id == Some(message_id)
+                && matches!(
+                    schedule.kaisanee,
+                    KaisaneeSpecifier::All | KaisaneeSpecifier::ThisChannel { .. }
+                )
+        }) else {
+            return Ok(());
+        };
+
+        let author_id = self.author_id();
+        let updated = self
+            .update_schedule(id, move |schedule| {
+                if !schedule.opted_out.contains(&author_id) {
+                    schedule.opted_out.push(author_id);
+                }
+            })
+            .await;
+        if updated.is_some() {
+            tracing::info!(?id, %author_id, "opted out of the kaisan");
+        }
+        Ok(())
+    }
+}
+
+impl<T: ScheduleContext + MessageContext> OptOut for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::OptOut;
+    use crate::{
+        context::ScheduleContext,
+        model::{
+            command::{KaisanOptions, TimeRangeSpecifier},
+            kaisanee::KaisaneeSpecifier,
+            message::Message,
+            time::{AfterTimeSpecifier, TimeSpecifier},
+        },
+        test::{MockContext, MOCK_AUTHOR_1, MOCK_AUTHOR_2},
+        use_case::ScheduleKaisan,
+    };
+    use chrono::{Duration, Utc};
+
+    #[tokio::test]
+    async fn test_opt_out() {
+        let time = Utc::now();
+        let ctx = MockContext::with_author_current_time(MOCK_AUTHOR_2, time);
+        ctx.schedule_kaisan(
+            KaisaneeSpecifier::All,
+            TimeRangeSpecifier::At(TimeSpecifier::After(AfterTimeSpecifier::Minute(10))),
+            KaisanOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        let mut reacted = ctx.clone();
+        reacted.author_id = MOCK_AUTHOR_1;
+        reacted.opt_out().await.unwrap();
+        reacted.opt_out().await.unwrap();
+        let schedules = ctx.schedules().await;
+        assert_eq!(schedules[0].1.opted_out, vec![MOCK_AUTHOR_1]);
+
+        ctx.scheduler.advance_to(time + Duration::minutes(10)).await;
+        assert!(ctx.has_sent(|m| matches!(m, Message::Kaisan(_))).await);
+        assert_eq!(*ctx.disconnected_users.lock().await, vec![MOCK_AUTHOR_2]);
+    }
+
+    #[tokio::test]
+    async fn test_not_for_everyone() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        ctx.schedule_kaisan(
+            KaisaneeSpecifier::Users(vec![MOCK_AUTHOR_1, MOCK_AUTHOR_2]),
+            TimeRangeSpecifier::At(TimeSpecifier::After(AfterTimeSpecifier::Minute(10))),
+            KaisanOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        let mut reacted = ctx.clone();
+        reacted.author_id = MOCK_AUTHOR_1;
+        reacted.opt_out().await.unwrap();
+        assert!(ctx.schedules().await[0].1.opted_out.is_empty());
+    }
+}
@@ -0,0 +1,104 @@
+use super::schedule_kaisan::{start_schedule, ScheduleKaisan};
+use crate::error::{Error, Result};
+use crate::model::{message::Message, schedule::FrozenSchedule};
+
+use serenity::model::permissions::Permissions;
+
+#[async_trait::async_trait]
+pub trait Pause: ScheduleKaisan + Sync {
+    /// Stops the bot in the guild until `resume`, holding the pending schedules with the time
+    /// left until them.
+    #[tracing::instrument(skip(self))]
+    async fn pause(&self) -> Result<()> {
+        if !self
+            .member_permissions(self.author_id())
+            .await?
+            .manage_guild()
+        {
+            return Err(Error::InsufficientPermission(Permissions::MANAGE_GUILD));
+        }
+
+        let now = self.current_time();
+        let mut frozen = Vec::new();
+        for (id, _) in self.schedules().await {
+            // the schedule may have been fired or cancelled in the meantime
+            if let Some(schedule) = self.cancel_schedule(id).await {
+                frozen.push(FrozenSchedule::freeze(schedule, now));
+            }
+        }
+        let count = frozen.len();
+
+        if let Err(e) = self.set_frozen_schedules(Some(frozen.clone())).await {
+            // put them back rather than losing them
+            for schedule in frozen {
+                start_schedule(self, schedule.thaw(now)).await;
+            }
+            return Err(e);
+        }
+        tracing::info!(count, "paused");
+
+        self.message(Message::Paused(count)).await
+    }
+}
+
+impl<T: ScheduleKaisan + Sync> Pause for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::Pause;
+    use crate::{
+        context::ScheduleContext,
+        error::Error,
+        model::{message::Message, schedule::Schedule},
+        test::{mock_schedule, MockContext, MOCK_AUTHOR_1, MOCK_AUTHOR_2},
+    };
+    use chrono::{Duration, Utc};
+
+    fn schedule(time: chrono::DateTime<Utc>) -> Schedule {
+        mock_schedule(MOCK_AUTHOR_2, time)
+    }
+
+    #[tokio::test]
+    async fn test_pause() {
+        let now = Utc::now();
+        let ctx = MockContext::with_current_time(now);
+        ctx.register_schedule(schedule(now + Duration::minutes(10)))
+            .await;
+        ctx.register_schedule(schedule(now + Duration::minutes(30)))
+            .await;
+
+        ctx.pause().await.unwrap();
+
+        assert!(ctx.schedules().await.is_empty());
+        let mut remaining: Vec<_> = ctx
+            .frozen_schedules
+            .lock()
+            .await
+            .clone()
+            .unwrap()
+            .into_iter()
+            .map(|frozen| frozen.remaining_seconds)
+            .collect();
+        remaining.sort();
+        assert_eq!(remaining, vec![600, 1800]);
+        assert!(matches!(
+            ctx.sent_messages.lock().await.as_slice(),
+            [Message::Paused(2)]
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_permission() {
+        let now = Utc::now();
+        let ctx = MockContext::with_author_current_time(MOCK_AUTHOR_1, now);
+        ctx.register_schedule(schedule(now + Duration::minutes(10)))
+            .await;
+
+        assert!(matches!(
+            ctx.pause().await,
+            Err(Error::InsufficientPermission(_))
+        ));
+        assert_eq!(ctx.schedules().await.len(), 1);
+        assert!(ctx.frozen_schedules.lock().await.is_none());
+    }
+}
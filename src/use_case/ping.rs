@@ -0,0 +1,97 @@
+use crate::context::{ChannelContext, HealthContext};
+use crate::error::Result;
+use crate::model::{
+    health::{
+        ComponentHealth, Health, SLOW_DATABASE_LATENCY, SLOW_GATEWAY_LATENCY, SLOW_REST_LATENCY,
+    },
+    message::Message,
+};
+
+#[async_trait::async_trait]
+pub trait Ping: HealthContext + ChannelContext + Sync {
+    /// Measures the latencies of Discord and the database at once, so that a slow one does not
+    /// delay the others.
+    #[tracing::instrument(skip(self))]
+    async fn ping(&self) -> Result<()> {
+        let (rest, gateway, database) = futures::join!(
+            self.rest_latency(),
+            self.gateway_latency(),
+            self.database_latency()
+        );
+
+        let health = Health {
+            rest: measured(rest, SLOW_REST_LATENCY),
+            gateway: match gateway {
+                Some(latency) => ComponentHealth::from_latency(latency, SLOW_GATEWAY_LATENCY),
+                None => ComponentHealth::Unknown,
+            },
+            database: measured(database, SLOW_DATABASE_LATENCY),
+        };
+        tracing::info!(?health, "measured health");
+        self.message(Message::Health(health)).await
+    }
+}
+
+impl<T: HealthContext + ChannelContext + Sync> Ping for T {}
+
+fn measured(latency: Result<std::time::Duration>, slow: std::time::Duration) -> ComponentHealth {
+    match latency {
+        Ok(latency) => ComponentHealth::from_latency(latency, slow),
+        Err(e) => {
+            tracing::warn!(error = %e, "health check failed");
+            ComponentHealth::Failing
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Ping;
+    use crate::{
+        model::{
+            health::{ComponentHealth, Health},
+            message::Message,
+        },
+        test::{MockContext, MockLatencies},
+    };
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_healthy() {
+        let ctx = MockContext::new();
+        ctx.ping().await.unwrap();
+        assert!(
+            ctx.has_sent(|m| matches!(
+                m,
+                Message::Health(Health {
+                    rest: ComponentHealth::Ok(_),
+                    gateway: ComponentHealth::Ok(_),
+                    database: ComponentHealth::Ok(_),
+                })
+            ))
+            .await
+        );
+    }
+
+    #[tokio::test]
+    async fn test_slow_and_failing() {
+        let ctx = MockContext::new();
+        *ctx.latencies.lock().await = MockLatencies {
+            rest: None,
+            gateway: None,
+            database: Some(Duration::from_secs(1)),
+        };
+        ctx.ping().await.unwrap();
+        assert!(
+            ctx.has_sent(|m| matches!(
+                m,
+                Message::Health(Health {
+                    rest: ComponentHealth::Failing,
+                    gateway: ComponentHealth::Unknown,
+                    database: ComponentHealth::Slow(_),
+                })
+            ))
+            .await
+        );
+    }
+}
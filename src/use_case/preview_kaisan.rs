@@ -0,0 +1,157 @@
+use super::schedule_kaisan::{
+    author_voice_channel, calculate_time, check_horizon, check_permission, collect_target_users,
+    resolve_kaisanee,
+};
+use crate::context::{ChannelContext, GuildContext, MessageContext, SettingContext, TimeContext};
+use crate::error::Result;
+use crate::model::{command::TimeRangeSpecifier, kaisanee::KaisaneeSpecifier, message::Message};
+
+#[async_trait::async_trait]
+pub trait PreviewKaisan:
+    GuildContext + ChannelContext + MessageContext + SettingContext + TimeContext + Sync
+{
+    #[tracing::instrument(skip(self))]
+    async fn preview_kaisan(
+        &self,
+        kaisanee: KaisaneeSpecifier,
+        time_range: TimeRangeSpecifier,
+    ) -> Result<()> {
+        let kaisanee = resolve_kaisanee(self, kaisanee).await?;
+        let settings = self.settings_snapshot().await?;
+        check_permission(self, &kaisanee, &settings).await?;
+        let voice_channel_id = author_voice_channel(self).await?;
+
+        let now = self.current_time();
+        let tz = settings.timezone;
+        let (time, is_random) = match time_range {
+            TimeRangeSpecifier::Now => (None, false),
+            TimeRangeSpecifier::At(spec) => {
+                (Some(calculate_time(self, spec, now, tz).await?), false)
+            }
+            TimeRangeSpecifier::By(spec) => {
+                (Some(calculate_time(self, spec, now, tz).await?), true)
+            }
+        };
+        if let Some(time) = time {
+            check_horizon(self, voice_channel_id, time, now).await?;
+        }
+
+        let target_users = collect_target_users(self, voice_channel_id, &kaisanee, &[]).await?;
+        self.message(Message::Preview {
+            time: time.map(|t| t.with_timezone(&tz)),
+            is_random,
+            kaisanee,
+            target_users,
+        })
+        .await
+    }
+}
+
+impl<T: GuildContext + ChannelContext + MessageContext + SettingContext + TimeContext + Sync>
+    PreviewKaisan for T
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PreviewKaisan;
+    use crate::{
+        error::Error,
+        model::{
+            command::TimeRangeSpecifier,
+            kaisanee::KaisaneeSpecifier,
+            member::MemberNames,
+            message::Message,
+            time::{AfterTimeSpecifier, TimeSpecifier},
+        },
+        test::{MockContext, MOCK_AUTHOR_1, MOCK_AUTHOR_2},
+    };
+    use chrono::{Duration, Utc};
+    use std::sync::atomic::Ordering;
+
+    #[tokio::test]
+    async fn test_at() {
+        let now = Utc::now();
+        let ctx = MockContext::with_author_current_time(MOCK_AUTHOR_2, now);
+
+        ctx.preview_kaisan(
+            KaisaneeSpecifier::Me,
+            TimeRangeSpecifier::At(TimeSpecifier::After(AfterTimeSpecifier::Minute(10))),
+        )
+        .await
+        .unwrap();
+
+        let expected = now + Duration::minutes(10);
+        assert!(matches!(
+            ctx.sent_messages.lock().await.as_slice(),
+            [Message::Preview { time: Some(time), is_random: false, target_users, .. }]
+              if *time == expected && target_users == &[MOCK_AUTHOR_2]
+        ));
+        assert!(ctx.disconnected_users.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_now() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+
+        ctx.preview_kaisan(KaisaneeSpecifier::All, TimeRangeSpecifier::Now)
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            ctx.sent_messages.lock().await.as_slice(),
+            [Message::Preview { time: None, target_users, .. }] if target_users.len() == 2
+        ));
+        assert!(ctx.disconnected_users.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_named() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        ctx.members.lock().await.push(MemberNames {
+            user_id: MOCK_AUTHOR_1,
+            username: "tanaka".to_owned(),
+            global_name: Some("田中".to_owned()),
+            nick: None,
+        });
+
+        ctx.preview_kaisan(
+            KaisaneeSpecifier::Named(vec!["田中".to_owned()]),
+            TimeRangeSpecifier::Now,
+        )
+        .await
+        .unwrap();
+
+        assert!(matches!(
+            ctx.sent_messages.lock().await.as_slice(),
+            [Message::Preview { target_users, .. }] if target_users == &[MOCK_AUTHOR_1]
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_time() {
+        let now = Utc::now();
+        let ctx = MockContext::with_current_time(now);
+
+        let res = ctx
+            .preview_kaisan(
+                KaisaneeSpecifier::Me,
+                TimeRangeSpecifier::By(TimeSpecifier::Exactly(
+                    (now - Duration::minutes(1)).fixed_offset(),
+                )),
+            )
+            .await;
+        assert!(matches!(res, Err(Error::UnreachableTime { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_insufficient_permission() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        ctx.requires_permission.store(true, Ordering::SeqCst);
+
+        let res = ctx
+            .preview_kaisan(KaisaneeSpecifier::All, TimeRangeSpecifier::Now)
+            .await;
+        assert!(matches!(res, Err(Error::InsufficientPermission(_))));
+    }
+}
@@ -0,0 +1,65 @@
+use crate::context::{EventContext, SettingContext};
+use crate::error::Result;
+
+use chrono::{DateTime, TimeZone, Utc};
+use serenity::model::id::UserId;
+
+#[async_trait::async_trait]
+pub trait RecordVoiceSession: EventContext + SettingContext {
+    /// Adds the time the user has spent in a voice channel to the statistics, split at the local
+    /// midnights of the guild.
+    #[tracing::instrument(skip(self))]
+    async fn record_voice_session(
+        &self,
+        user_id: UserId,
+        joined_at: DateTime<Utc>,
+        left_at: DateTime<Utc>,
+    ) -> Result<()> {
+        let timezone = self.timezone().await?;
+
+        let mut start = joined_at;
+        while start < left_at {
+            let date = start.with_timezone(&timezone).date_naive();
+            // a midnight skipped by DST leaves the rest of the session on the same day
+            let end = date
+                .succ_opt()
+                .and_then(|next| timezone.from_local_datetime(&next.into()).earliest())
+                .map_or(left_at, |midnight| midnight.to_utc().min(left_at));
+            let seconds = (end - start).num_seconds();
+            if seconds > 0 {
+                self.record_voice_time(user_id, date, seconds as u64)
+                    .await?;
+            }
+            start = end;
+        }
+
+        Ok(())
+    }
+}
+
+impl<T: EventContext + SettingContext> RecordVoiceSession for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::RecordVoiceSession;
+    use crate::test::{MockContext, MOCK_AUTHOR_1};
+
+    use chrono::{NaiveDate, TimeZone, Utc};
+
+    #[tokio::test]
+    async fn test_across_midnight() {
+        let ctx = MockContext::new();
+        // 23:30 to 01:15 in Japan
+        let joined_at = Utc.with_ymd_and_hms(2024, 1, 1, 14, 30, 0).unwrap();
+        let left_at = Utc.with_ymd_and_hms(2024, 1, 1, 16, 15, 0).unwrap();
+        ctx.record_voice_session(MOCK_AUTHOR_1, joined_at, left_at)
+            .await
+            .unwrap();
+
+        let voice_times = ctx.voice_times.lock().await;
+        let date = |d| NaiveDate::from_ymd_opt(2024, 1, d).unwrap();
+        assert_eq!(voice_times.len(), 2);
+        assert_eq!(voice_times[&(date(1), MOCK_AUTHOR_1)], 30 * 60);
+        assert_eq!(voice_times[&(date(2), MOCK_AUTHOR_1)], 75 * 60);
+    }
+}
@@ -6,6 +6,7 @@ use serenity::model::permissions::Permissions;
 
 #[async_trait::async_trait]
 pub trait RemoveReminder: SettingContext + GuildContext + MessageContext {
+    #[tracing::instrument(skip(self))]
     async fn remove_reminder(&self, reminder: Reminder) -> Result<()> {
         if !self
             .member_permissions(self.author_id())
@@ -18,7 +19,7 @@ pub trait RemoveReminder: SettingContext + GuildContext + MessageContext {
         if !SettingContext::remove_reminder(self, reminder).await? {
             Err(Error::NoSuchReminder(reminder))
         } else {
-            self.react('✅').await?;
+            self.react_success().await?;
             Ok(())
         }
     }
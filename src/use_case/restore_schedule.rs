@@ -0,0 +1,78 @@
+use super::schedule_kaisan::{start_schedule, ScheduleKaisan};
+use crate::context::{MessageContext, TimeContext};
+use crate::error::Result;
+use crate::model::schedule::Schedule;
+
+#[async_trait::async_trait]
+pub trait RestoreSchedule: ScheduleKaisan + MessageContext + TimeContext + Sync {
+    /// Resumes a schedule persisted on shutdown. Schedules that became due while the bot was
+    /// down are carried out immediately, or skipped if they are later than the grace window.
+    #[tracing::instrument(skip(self))]
+    async fn restore_schedule(&self, schedule: Schedule) -> Result<()> {
+        if schedule.time <= self.current_time() {
+            tracing::warn!(time = %schedule.time, "restoring overdue schedule");
+        }
+        start_schedule(self, schedule).await;
+        Ok(())
+    }
+}
+
+impl<T: ScheduleKaisan + Sync> RestoreSchedule for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::RestoreSchedule;
+    use crate::{
+        context::ScheduleContext,
+        model::{
+            kaisanee::KaisaneeSpecifier, message::Message, reminder::Reminder, schedule::Schedule,
+        },
+        test::{mock_schedule, MockContext, MOCK_AUTHOR_1, MOCK_AUTHOR_2, MOCK_MESSAGE_ID},
+    };
+    use chrono::{Duration, Utc};
+
+    fn schedule(time: chrono::DateTime<Utc>) -> Schedule {
+        Schedule {
+            message_id: Some(MOCK_MESSAGE_ID),
+            kaisanee: KaisaneeSpecifier::Users(vec![MOCK_AUTHOR_1]),
+            reminders: vec![Reminder::before_minutes(5)],
+            ..mock_schedule(MOCK_AUTHOR_2, time)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_restore() {
+        let now = Utc::now();
+        let ctx = MockContext::with_current_time(now);
+
+        ctx.restore_schedule(schedule(now + Duration::minutes(10)))
+            .await
+            .unwrap();
+        assert_eq!(ctx.schedules().await.len(), 1);
+
+        ctx.scheduler.advance_to(now + Duration::minutes(6)).await;
+        assert!(ctx.has_sent(|m| matches!(m, Message::Remind { .. })).await);
+
+        ctx.scheduler.advance_to(now + Duration::minutes(10)).await;
+        assert!(ctx.has_sent(|m| matches!(m, Message::Kaisan(_))).await);
+
+        assert_eq!(*ctx.disconnected_users.lock().await, vec![MOCK_AUTHOR_1]);
+        assert!(ctx.schedules().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_restore_overdue() {
+        let now = Utc::now();
+        let ctx = MockContext::with_current_time(now);
+
+        ctx.restore_schedule(schedule(now - Duration::minutes(1)))
+            .await
+            .unwrap();
+        ctx.scheduler.settle().await;
+        assert!(ctx.has_sent(|m| matches!(m, Message::Kaisan(_))).await);
+
+        let messages = ctx.sent_messages.lock().await;
+        assert!(!messages.iter().any(|m| matches!(m, Message::Remind { .. })));
+        assert_eq!(*ctx.disconnected_users.lock().await, vec![MOCK_AUTHOR_1]);
+    }
+}
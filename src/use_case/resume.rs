@@ -0,0 +1,87 @@
+use super::schedule_kaisan::{start_schedule, ScheduleKaisan};
+use crate::error::{Error, Result};
+use crate::model::message::Message;
+
+use serenity::model::permissions::Permissions;
+
+#[async_trait::async_trait]
+pub trait Resume: ScheduleKaisan + Sync {
+    /// Starts the schedules held by `pause` again, leaving the same time until them as when
+    /// they were paused.
+    #[tracing::instrument(skip(self))]
+    async fn resume(&self) -> Result<()> {
+        if !self
+            .member_permissions(self.author_id())
+            .await?
+            .manage_guild()
+        {
+            return Err(Error::InsufficientPermission(Permissions::MANAGE_GUILD));
+        }
+
+        let Some(frozen) = self.frozen_schedules().await? else {
+            return Err(Error::NotPaused);
+        };
+        // cleared first so that a failure leaves the guild paused rather than doubling them
+        self.set_frozen_schedules(None).await?;
+
+        let now = self.current_time();
+        let count = frozen.len();
+        for schedule in frozen {
+            start_schedule(self, schedule.thaw(now)).await;
+        }
+        tracing::info!(count, "resumed");
+
+        self.message(Message::Resumed(count)).await
+    }
+}
+
+impl<T: ScheduleKaisan + Sync> Resume for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::Resume;
+    use crate::{
+        context::ScheduleContext,
+        error::Error,
+        model::{
+            kaisanee::KaisaneeSpecifier,
+            message::Message,
+            schedule::{FrozenSchedule, Schedule},
+        },
+        test::{mock_schedule, MockContext, MOCK_AUTHOR_1, MOCK_AUTHOR_2},
+    };
+    use chrono::{Duration, Utc};
+
+    #[tokio::test]
+    async fn test_resume() {
+        let now = Utc::now();
+        let ctx = MockContext::with_current_time(now);
+        let schedule = Schedule {
+            kaisanee: KaisaneeSpecifier::Users(vec![MOCK_AUTHOR_1]),
+            // paused long ago with 10 minutes left
+            ..mock_schedule(MOCK_AUTHOR_2, now - Duration::hours(1))
+        };
+        *ctx.frozen_schedules.lock().await = Some(vec![FrozenSchedule {
+            schedule,
+            remaining_seconds: 600,
+        }]);
+
+        ctx.resume().await.unwrap();
+
+        assert!(ctx.frozen_schedules.lock().await.is_none());
+        let schedules = ctx.schedules().await;
+        assert_eq!(schedules.len(), 1);
+        assert_eq!(schedules[0].1.time, now + Duration::minutes(10));
+        assert!(ctx.has_sent(|m| matches!(m, Message::Resumed(1))).await);
+
+        ctx.scheduler.advance_to(now + Duration::minutes(10)).await;
+        assert!(ctx.has_sent(|m| matches!(m, Message::Kaisan(_))).await);
+        assert_eq!(*ctx.disconnected_users.lock().await, vec![MOCK_AUTHOR_1]);
+    }
+
+    #[tokio::test]
+    async fn test_not_paused() {
+        let ctx = MockContext::new();
+        assert!(matches!(ctx.resume().await, Err(Error::NotPaused)));
+    }
+}
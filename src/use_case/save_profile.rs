@@ -0,0 +1,83 @@
+use super::export_setting::read_setting;
+use crate::context::{GuildContext, MessageContext, SettingContext};
+use crate::error::{Error, Result};
+
+use serenity::model::permissions::Permissions;
+
+/// Number of profiles each guild can save.
+pub const MAX_PROFILES: usize = 10;
+
+#[async_trait::async_trait]
+pub trait SaveProfile: SettingContext + GuildContext + MessageContext + Sync {
+    /// Saves the current settings under the name, to be loaded later with `profile load`. Only
+    /// the fields of [`Setting`](crate::model::setting::Setting) are saved.
+    #[tracing::instrument(skip(self))]
+    async fn save_profile_as(&self, name: String) -> Result<()> {
+        if !self
+            .member_permissions(self.author_id())
+            .await?
+            .manage_guild()
+        {
+            return Err(Error::InsufficientPermission(Permissions::MANAGE_GUILD));
+        }
+
+        let names = self.profile_names().await?;
+        if !names.contains(&name) && names.len() >= MAX_PROFILES {
+            return Err(Error::TooManyProfiles(MAX_PROFILES));
+        }
+
+        let setting = read_setting(self).await?;
+        self.save_profile(&name, &setting).await?;
+        self.react_success().await?;
+        Ok(())
+    }
+}
+
+impl<T: SettingContext + GuildContext + MessageContext + Sync> SaveProfile for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::{SaveProfile, MAX_PROFILES};
+    use crate::{
+        error::Error,
+        model::setting::Setting,
+        test::{MockContext, MOCK_AUTHOR_1, MOCK_AUTHOR_2},
+    };
+    use std::sync::atomic::Ordering;
+
+    #[tokio::test]
+    async fn test_success() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        ctx.max_horizon_hours.store(48, Ordering::SeqCst);
+        ctx.save_profile_as("weekend".to_owned()).await.unwrap();
+
+        let profiles = ctx.profiles.lock().await;
+        assert_eq!(profiles["weekend"].max_horizon_hours, 48);
+    }
+
+    #[tokio::test]
+    async fn test_too_many() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        ctx.profiles
+            .lock()
+            .await
+            .extend((0..MAX_PROFILES).map(|i| (format!("profile{}", i), Setting::default())));
+
+        assert!(matches!(
+            ctx.save_profile_as("weekend".to_owned()).await,
+            Err(Error::TooManyProfiles(MAX_PROFILES))
+        ));
+        // overwriting one is fine
+        ctx.save_profile_as("profile0".to_owned()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_insufficient_permission() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        assert!(matches!(
+            ctx.save_profile_as("weekend".to_owned()).await,
+            Err(Error::InsufficientPermission(_))
+        ));
+        assert!(ctx.profiles.lock().await.is_empty());
+    }
+}
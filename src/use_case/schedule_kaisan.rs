@@ -1,21 +1,37 @@
+use super::log_event::log_event;
 use crate::context::{
-    ChannelContext, GuildContext, MessageContext, RandomContext, SettingContext, TimeContext,
+    ChannelContext, EventContext, GuildContext, LinkContext, MessageContext, NotificationContext,
+    PresenceContext, RandomContext, ScheduleContext, SettingContext, TimeContext, VoiceContext,
 };
 use crate::error::{Error, Result};
 use crate::model::{
-    command::TimeRangeSpecifier,
+    command::{KaisanOptions, TimeRangeSpecifier},
+    event::DisconnectEvent,
     kaisanee::KaisaneeSpecifier,
-    message::{CalculatedDateTime, Message},
+    message::{CalculatedDateTime, LogEntry, Message},
+    notification::Notification,
+    permission::PermissionPolicy,
     reminder::Reminder,
+    schedule::{Schedule, ScheduleId},
+    setting::{DuplicatePolicy, RandomDistribution, RevealRandom, SettingsSnapshot},
+    time::{AtTimeSpecifier, TimeSpecifier},
 };
 
 use chrono::{DateTime, Duration, Utc};
-use futures::future;
+use chrono_tz::Tz;
+use futures::{future, FutureExt as _};
 use serenity::model::{
     id::{ChannelId, UserId},
     permissions::Permissions,
 };
-use tokio::spawn;
+use tokio::{spawn, task::AbortHandle};
+use tracing::Instrument as _;
+
+const COUNTDOWN_SECONDS: i64 = 60;
+const COUNTDOWN_INTERVAL_SECONDS: i64 = 10;
+/// The announcement in the voice channel starts this long before the kaisan, so that a countdown
+/// of this length ends right at it.
+const ANNOUNCEMENT_SECONDS: i64 = 10;
 
 #[async_trait::async_trait]
 pub trait ScheduleKaisan:
@@ -25,310 +41,2073 @@ pub trait ScheduleKaisan:
     + SettingContext
     + TimeContext
     + RandomContext
+    + ScheduleContext
+    + EventContext
+    + PresenceContext
+    + NotificationContext
+    + LinkContext
+    + VoiceContext
     + Clone
     + Send
     + 'static
 {
+    #[tracing::instrument(skip(self))]
     async fn schedule_kaisan(
         &self,
         kaisanee: KaisaneeSpecifier,
         time_range: TimeRangeSpecifier,
+        options: KaisanOptions,
     ) -> Result<()> {
-        let author_id = self.author_id();
+        let checked = check(self, kaisanee, time_range, &[]).await?;
+        start_checked(self, checked, options).await
+    }
 
-        if kaisanee.may_include_others(author_id)
-            && self.requires_permission().await?
-            && !self.member_permissions(author_id).await?.move_members()
-        {
-            return Err(Error::InsufficientPermission(Permissions::MOVE_MEMBERS));
+    /// Schedules the stages of a command such as `me at 23:00, all at 24:00` in order. Every stage
+    /// is checked before any of them is scheduled, so that none of them is scheduled if one fails.
+    #[tracing::instrument(skip(self))]
+    async fn schedule_kaisan_stages(
+        &self,
+        stages: Vec<(KaisaneeSpecifier, TimeRangeSpecifier)>,
+        options: KaisanOptions,
+    ) -> Result<()> {
+        let mut checked = Vec::new();
+        for (kaisanee, time_range) in stages {
+            let stage = check(self, kaisanee, time_range, &checked).await?;
+            checked.push(stage);
+        }
+        for (index, stage) in checked.into_iter().enumerate() {
+            // the skipped words are reported only once
+            let options = KaisanOptions {
+                ignored_tokens: if index == 0 {
+                    options.ignored_tokens.clone()
+                } else {
+                    Vec::new()
+                },
+                ..options.clone()
+            };
+            start_checked(self, stage, options).await?;
         }
+        Ok(())
+    }
+}
+
+impl<
+        T: GuildContext
+            + ChannelContext
+            + MessageContext
+            + SettingContext
+            + TimeContext
+            + RandomContext
+            + ScheduleContext
+            + EventContext
+            + PresenceContext
+            + NotificationContext
+            + LinkContext
+            + VoiceContext
+            + Clone
+            + Send
+            + 'static,
+    > ScheduleKaisan for T
+{
+}
+
+/// A kaisan that has passed all the checks, which [`start_checked`] carries out or schedules.
+struct Checked {
+    kaisanee: KaisaneeSpecifier,
+    voice_channel_id: ChannelId,
+    settings: SettingsSnapshot,
+    now: DateTime<Utc>,
+    time: CheckedTime,
+    /// Schedules to be cancelled in favor of this one
+    replaced: Vec<ScheduleId>,
+}
+
+enum CheckedTime {
+    Now,
+    At {
+        spec: TimeSpecifier,
+        time: DateTime<Utc>,
+    },
+    /// A random kaisan until `by`
+    By {
+        spec: TimeSpecifier,
+        by: DateTime<Utc>,
+    },
+}
 
-        let voice_channel_id = match self.connected_voice_channel(author_id).await? {
-            Some(id) => id,
-            None => return Err(Error::NotInVoiceChannel),
+/// Checks a kaisan without sending anything or scheduling it. `earlier` are the stages of the
+/// same command that have been checked, which count towards the limit of the schedules and are
+/// not duplicates of this one.
+async fn check<C: ScheduleKaisan + Sync>(
+    ctx: &C,
+    kaisanee: KaisaneeSpecifier,
+    time_range: TimeRangeSpecifier,
+    earlier: &[Checked],
+) -> Result<Checked> {
+    let kaisanee = resolve_kaisanee(ctx, kaisanee).await?;
+    let settings = ctx.settings_snapshot().await?;
+    check_permission(ctx, &kaisanee, &settings).await?;
+    let voice_channel_id = author_voice_channel(ctx).await?;
+
+    let replaced = if matches!(time_range, TimeRangeSpecifier::Now) {
+        Vec::new()
+    } else {
+        let replaced = if earlier.is_empty() {
+            check_duplicate(ctx, voice_channel_id).await?
+        } else {
+            Vec::new()
         };
+        let all_replaced: Vec<_> = earlier
+            .iter()
+            .flat_map(|stage| stage.replaced.iter().copied())
+            .chain(replaced.iter().copied())
+            .collect();
+        let pending = earlier
+            .iter()
+            .filter(|stage| !matches!(stage.time, CheckedTime::Now))
+            .count();
+        check_schedule_limit(ctx, &all_replaced, pending).await?;
+        replaced
+    };
 
-        let now = self.current_time();
-        let tz = self.timezone().await?;
-        let (time, is_random) = match time_range {
-            TimeRangeSpecifier::Now => {
-                return kaisan(self, voice_channel_id, &kaisanee).await;
-            }
-            TimeRangeSpecifier::At(spec) => {
-                let Some(time) = spec.calculate_time(now, tz) else {
-                    return Err(Error::InvalidTime {
-                        specifier: spec,
-                        at: now,
-                        timezone: tz,
-                    });
-                };
-                if time < now {
-                    return Err(Error::UnreachableTime {
-                        specified: time,
-                        at: now,
-                    });
-                }
+    let now = ctx.current_time();
+    let tz = settings.timezone;
+    let time = match time_range {
+        TimeRangeSpecifier::Now => CheckedTime::Now,
+        TimeRangeSpecifier::At(spec) => {
+            let time = calculate_time(ctx, spec, now, tz).await?;
+            check_horizon(ctx, voice_channel_id, time, now).await?;
+            CheckedTime::At { spec, time }
+        }
+        TimeRangeSpecifier::By(spec) => {
+            let by = calculate_time(ctx, spec, now, tz).await?;
+            check_horizon(ctx, voice_channel_id, by, now).await?;
+            CheckedTime::By { spec, by }
+        }
+    };
 
-                self.message(Message::Scheduled {
-                    calculated_time: CalculatedDateTime {
-                        time: time.with_timezone(&tz),
-                        now: now.with_timezone(&tz),
-                        is_random: false,
-                        spec,
-                    },
-                    kaisanee: kaisanee.clone(),
-                })
+    Ok(Checked {
+        kaisanee,
+        voice_channel_id,
+        settings,
+        now,
+        time,
+        replaced,
+    })
+}
+
+async fn start_checked<C: ScheduleKaisan + Sync>(
+    ctx: &C,
+    checked: Checked,
+    options: KaisanOptions,
+) -> Result<()> {
+    let Checked {
+        kaisanee,
+        voice_channel_id,
+        settings,
+        now,
+        time,
+        replaced,
+    } = checked;
+    let tz = settings.timezone;
+    let (time, random_until) = match time {
+        CheckedTime::Now => {
+            return kaisan(ctx, None, voice_channel_id, &kaisanee, &[]).await;
+        }
+        CheckedTime::At { spec, time } => {
+            ctx.message(Message::Scheduled {
+                calculated_time: CalculatedDateTime {
+                    time: time.with_timezone(&tz),
+                    now: now.with_timezone(&tz),
+                    is_random: false,
+                    spec,
+                },
+                kaisanee: kaisanee.clone(),
+                head_count: collect_target_users(ctx, voice_channel_id, &kaisanee, &[])
+                    .await?
+                    .len(),
+                revealed_time: None,
+                ignored_tokens: options.ignored_tokens.clone(),
+            })
+            .await?;
+            (time, None)
+        }
+        CheckedTime::By { spec, by } => {
+            let duration = by - now;
+            if duration.num_seconds() <= 0 {
+                // there is nothing to draw from, and `random_range` panics on an empty range
+                tracing::info!(%by, "random window is empty, kaisan now");
+                return kaisan(ctx, None, voice_channel_id, &kaisanee, &[]).await;
+            }
+            let random_secs = draw_random_seconds(ctx, duration.num_seconds()).await?;
+            let random_duration = Duration::seconds(random_secs);
+            let time = now + random_duration;
+
+            let reveal_random = ctx.reveal_random().await?;
+            ctx.message(Message::Scheduled {
+                calculated_time: CalculatedDateTime {
+                    time: by.with_timezone(&tz),
+                    now: now.with_timezone(&tz),
+                    is_random: true,
+                    spec,
+                },
+                kaisanee: kaisanee.clone(),
+                head_count: collect_target_users(ctx, voice_channel_id, &kaisanee, &[])
+                    .await?
+                    .len(),
+                revealed_time: (reveal_random == RevealRandom::Channel)
+                    .then(|| time.with_timezone(&tz)),
+                ignored_tokens: options.ignored_tokens.clone(),
+            })
+            .await?;
+            if reveal_random == RevealRandom::DirectMessage {
+                ctx.direct_message(
+                    ctx.author_id(),
+                    Message::RevealedTime(time.with_timezone(&tz)),
+                )
                 .await?;
-                (time, false)
             }
-            TimeRangeSpecifier::By(spec) => {
-                let Some(by) = spec.calculate_time(now, tz) else {
-                    return Err(Error::InvalidTime {
-                        specifier: spec,
-                        at: now,
-                        timezone: tz,
-                    });
-                };
-                if by < now {
-                    return Err(Error::UnreachableTime {
-                        specified: by,
-                        at: now,
-                    });
-                }
+            (time, Some(by))
+        }
+    };
+    notify_default_timezone(ctx, tz).await?;
+
+    let reminders = if random_until.is_none() || settings.reminds_random_kaisan {
+        let mut reminders: Vec<_> = settings.reminders.into_iter().collect();
+        reminders.sort();
+        reminders
+    } else {
+        Vec::new()
+    };
+
+    let schedule = Schedule {
+        author_id: ctx.author_id(),
+        channel_id: ctx.channel_id(),
+        message_id: ctx.message_id(),
+        voice_channel_id,
+        kaisanee,
+        time,
+        random_until,
+        reminders,
+        remind_only_me: options.remind_only_me,
+        opted_out: Vec::new(),
+        created_at: Some(now),
+    };
 
-                let duration = by - now;
-                let random_secs = self.random_range(0, duration.num_seconds()).await;
-                let random_duration = Duration::seconds(random_secs);
-                let time = now + random_duration;
-
-                self.message(Message::Scheduled {
-                    calculated_time: CalculatedDateTime {
-                        time: by.with_timezone(&tz),
-                        now: now.with_timezone(&tz),
-                        is_random: true,
-                        spec,
+    if !replaced.is_empty() {
+        for &id in &replaced {
+            if ctx.cancel_schedule(id).await.is_some() {
+                log_event(
+                    ctx,
+                    LogEntry::Cancelled {
+                        schedule_id: id,
+                        voice_channel_id,
+                        by: Some(ctx.author_id()),
                     },
-                    kaisanee: kaisanee.clone(),
-                })
-                .await?;
-                (time, true)
+                )
+                .await;
             }
-        };
+        }
+        tracing::info!(?replaced, "replaced earlier schedules");
+        ctx.message(Message::ReplacedSchedules(replaced)).await?;
+    }
+    let id = start_schedule(ctx, schedule.clone()).await;
+    ctx.notify(Notification::Scheduled {
+        schedule_id: id,
+        voice_channel_id,
+        author_id: ctx.author_id(),
+        time: schedule.public_time(),
+        random: random_until.is_some(),
+    });
+    log_event(
+        ctx,
+        LogEntry::Scheduled {
+            schedule_id: id,
+            voice_channel_id,
+            author_id: ctx.author_id(),
+            time: schedule.public_time().with_timezone(&tz),
+            random: random_until.is_some(),
+        },
+    )
+    .await;
+    if let Some(url) = ctx.cancel_url(id, &schedule) {
+        // the kaisan is scheduled anyway, even if the author does not accept DMs
+        let message = Message::CancelLink { id, url };
+        if let Err(e) = ctx.direct_message(ctx.author_id(), message).await {
+            tracing::warn!(error = %e, "failed to send cancel link");
+        }
+    }
 
-        let ctx = self.clone();
-        schedule_kaisan_at(ctx.clone(), voice_channel_id, time, kaisanee.clone());
-        tracing::info!(?kaisanee, %time, "scheduled kaisan");
+    Ok(())
+}
 
-        if !is_random || self.reminds_random_kaisan().await? {
-            let reminders = self.reminders().await?;
-            for reminder in reminders {
-                let remind_time = time - reminder.before_duration();
-                if remind_time <= now {
-                    continue;
+/// Turns members referred to by their names into `Users`, leaving other specifiers as they are.
+pub(super) async fn resolve_kaisanee<C: GuildContext + Sync + ?Sized>(
+    ctx: &C,
+    kaisanee: KaisaneeSpecifier,
+) -> Result<KaisaneeSpecifier> {
+    let KaisaneeSpecifier::Named(names) = kaisanee else {
+        return Ok(kaisanee);
+    };
+    let mut users = Vec::new();
+    for name in names {
+        let found = ctx.find_member_by_name(&name).await?;
+        match found.as_slice() {
+            [] => return Err(Error::NoSuchMember(name)),
+            [member] => {
+                tracing::debug!(%name, user_id = %member.user_id, "resolved member name");
+                if !users.contains(&member.user_id) {
+                    users.push(member.user_id);
                 }
-
-                schedule_reminder_at(
-                    self.clone(),
-                    voice_channel_id,
-                    remind_time,
-                    kaisanee.clone(),
-                    reminder,
-                );
-                tracing::info!(?kaisanee, %remind_time, "scheduled remind");
+            }
+            candidates => {
+                let candidates = candidates
+                    .iter()
+                    .map(|m| format!("{} (@{})", m.display_name(), m.username))
+                    .collect();
+                return Err(Error::AmbiguousMember { name, candidates });
             }
         }
+    }
+    Ok(KaisaneeSpecifier::Users(users))
+}
 
-        Ok(())
+pub(super) async fn check_permission<C>(
+    ctx: &C,
+    kaisanee: &KaisaneeSpecifier,
+    settings: &SettingsSnapshot,
+) -> Result<()>
+where
+    C: GuildContext + MessageContext + SettingContext + Sync + ?Sized,
+{
+    let author_id = ctx.author_id();
+    let policy = PermissionPolicy {
+        requires_permission: settings.requires_permission,
+        requires_permission_self: settings.requires_permission_self,
+        allowed_roles: ctx.allowed_roles().await?,
+    };
+    if !policy.is_restricted(kaisanee, author_id) {
+        return Ok(());
     }
+
+    let permissions = ctx.member_permissions(author_id).await?;
+    let roles = if policy.allowed_roles.is_empty() {
+        Vec::new()
+    } else {
+        ctx.member_roles(author_id).await?
+    };
+    if !policy.grants(permissions, &roles) {
+        return Err(Error::InsufficientPermission(Permissions::MOVE_MEMBERS));
+    }
+
+    Ok(())
 }
 
-impl<
-        T: GuildContext
-            + ChannelContext
-            + MessageContext
-            + SettingContext
-            + TimeContext
-            + RandomContext
-            + Clone
-            + Send
-            + 'static,
-    > ScheduleKaisan for T
+pub(super) async fn author_voice_channel<C>(ctx: &C) -> Result<ChannelId>
+where
+    C: GuildContext + MessageContext + Sync + ?Sized,
+{
+    match ctx.connected_voice_channel(ctx.author_id()).await? {
+        Some(id) => Ok(id),
+        None => Err(Error::NotInVoiceChannel),
+    }
+}
+
+pub(super) async fn calculate_time<C>(
+    ctx: &C,
+    spec: TimeSpecifier,
+    now: DateTime<Utc>,
+    tz: Tz,
+) -> Result<DateTime<Utc>>
+where
+    C: SettingContext + Sync + ?Sized,
+{
+    let spec = match spec {
+        TimeSpecifier::At(AtTimeSpecifier::Tonight) => {
+            spec.with_tonight_hour(ctx.tonight_hour().await?)
+        }
+        spec => spec,
+    };
+    let policy = ctx.dst_policy().await?;
+    let week_start = ctx.week_start().await?;
+    let time = spec
+        .calculate_time_with_setting(now, tz, policy, week_start)
+        .map_err(|reason| Error::InvalidTime {
+            specifier: spec,
+            at: now,
+            timezone: tz,
+            reason,
+        })?;
+    if time < now {
+        return Err(Error::UnreachableTime {
+            specified: time,
+            at: now,
+        });
+    }
+    Ok(time)
+}
+
+pub(super) async fn check_horizon<C>(
+    ctx: &C,
+    voice_channel_id: ChannelId,
+    time: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> Result<()>
+where
+    C: SettingContext + Sync + ?Sized,
+{
+    let max_horizon_hours = ctx.max_horizon_hours_in(voice_channel_id).await?;
+    if time - now > Duration::hours(max_horizon_hours.into()) {
+        return Err(Error::TooFarInFuture {
+            specified: time,
+            at: now,
+            max_horizon_hours,
+        });
+    }
+    Ok(())
+}
+
+/// Draws an offset in `[0, window_secs)` seconds following the guild's random distribution.
+pub(super) async fn draw_random_seconds<C>(ctx: &C, window_secs: i64) -> Result<i64>
+where
+    C: SettingContext + RandomContext + Sync + ?Sized,
+{
+    let secs = match ctx.random_distribution().await? {
+        RandomDistribution::Uniform => ctx.random_range(0, window_secs).await,
+        RandomDistribution::LateBiased => {
+            let a = ctx.random_range(0, window_secs).await;
+            let b = ctx.random_range(0, window_secs).await;
+            a.max(b)
+        }
+        RandomDistribution::EarlyBiased => {
+            let a = ctx.random_range(0, window_secs).await;
+            let b = ctx.random_range(0, window_secs).await;
+            a.min(b)
+        }
+    };
+    Ok(secs)
+}
+
+/// Applies the [`DuplicatePolicy`] in the voice channel to the schedules the author already has in the voice
+/// channel, and returns the ones to be replaced by the new schedule.
+async fn check_duplicate<C>(ctx: &C, voice_channel_id: ChannelId) -> Result<Vec<ScheduleId>>
+where
+    C: MessageContext + SettingContext + ScheduleContext + Sync + ?Sized,
+{
+    let policy = ctx.on_duplicate_in(voice_channel_id).await?;
+    if policy == DuplicatePolicy::Stack {
+        return Ok(Vec::new());
+    }
+
+    let author_id = ctx.author_id();
+    let mut duplicates: Vec<_> = ctx
+        .schedules()
+        .await
+        .into_iter()
+        .filter(|(_, schedule)| {
+            schedule.author_id == author_id && schedule.voice_channel_id == voice_channel_id
+        })
+        .map(|(id, _)| id)
+        .collect();
+    duplicates.sort();
+
+    match policy {
+        DuplicatePolicy::Reject if !duplicates.is_empty() => {
+            Err(Error::DuplicateSchedule(duplicates))
+        }
+        DuplicatePolicy::Replace => Ok(duplicates),
+        _ => Ok(Vec::new()),
+    }
+}
+
+async fn notify_default_timezone<C>(ctx: &C, tz: Tz) -> Result<()>
+where
+    C: ChannelContext + SettingContext + EventContext + Sync + ?Sized,
+{
+    if !ctx.has_timezone().await? && ctx.record_timezone_notice().await? {
+        ctx.message(Message::DefaultTimezone(tz)).await?;
+    }
+    Ok(())
+}
+
+/// Counts the schedules of the author except the ones in `replaced`, which are about to be
+/// cancelled, along with `pending` ones about to be scheduled.
+async fn check_schedule_limit<C>(ctx: &C, replaced: &[ScheduleId], pending: usize) -> Result<()>
+where
+    C: GuildContext + MessageContext + SettingContext + ScheduleContext + Sync + ?Sized,
 {
+    let author_id = ctx.author_id();
+    if ctx.member_permissions(author_id).await?.manage_guild() {
+        return Ok(());
+    }
+
+    let max_schedules_per_user = ctx.max_schedules_per_user().await?;
+    let count = ctx
+        .schedules()
+        .await
+        .into_iter()
+        .filter(|(id, schedule)| schedule.author_id == author_id && !replaced.contains(id))
+        .count()
+        + pending;
+    if count >= max_schedules_per_user.into() {
+        return Err(Error::TooManySchedules {
+            max_schedules_per_user,
+        });
+    }
+    Ok(())
+}
+
+/// Registers the schedule and spawns the tasks that carry it out.
+pub(super) async fn start_schedule<C: ScheduleKaisan + Sync>(
+    ctx: &C,
+    schedule: Schedule,
+) -> ScheduleId {
+    let now = ctx.current_time();
+    let time = schedule.time;
+    let kaisanee = schedule.kaisanee.clone();
+    let id = ctx.register_schedule(schedule.clone()).await;
+
+    let mut tasks = Vec::new();
+    tasks.push(schedule_kaisan_at(ctx.clone(), id, &schedule));
+    tracing::info!(?kaisanee, %time, ?id, "scheduled kaisan");
+
+    // earliest first, so that a reminder is merged into the one just before it
+    let mut reminders = schedule.reminders.clone();
+    reminders.sort_by(|a, b| b.cmp(a));
+    let mut last_remind_time = None;
+    for reminder in reminders {
+        let remind_time = time - reminder.before_duration();
+        if remind_time <= now {
+            continue;
+        }
+        if last_remind_time.is_some_and(|last| remind_time - last < Duration::minutes(1)) {
+            tracing::info!(?kaisanee, %remind_time, ?id, "merged remind into the previous one");
+            continue;
+        }
+        last_remind_time = Some(remind_time);
+
+        tasks.push(schedule_reminder_at(
+            ctx.clone(),
+            id,
+            schedule.clone(),
+            remind_time,
+            reminder,
+        ));
+        tracing::info!(?kaisanee, %remind_time, ?id, "scheduled remind");
+    }
+
+    // the randomly chosen time should not be revealed even in the last minute
+    let countdown_start = time - Duration::seconds(COUNTDOWN_SECONDS);
+    if schedule.random_until.is_none() && countdown_start > now {
+        tasks.push(schedule_countdown_at(
+            ctx.clone(),
+            schedule.voice_channel_id,
+            countdown_start,
+            time,
+        ));
+    }
+    let announcement_start = time - Duration::seconds(ANNOUNCEMENT_SECONDS);
+    if schedule.random_until.is_none() && announcement_start > now {
+        tasks.push(schedule_announcement_at(
+            ctx.clone(),
+            id,
+            announcement_start,
+        ));
+    }
+
+    ctx.attach_schedule_tasks(id, tasks).await;
+    id
 }
 
+/// The spans of the scheduled work carry the IDs of the author and the message of the command,
+/// so that what happens at the time can be traced back to it even after a restart.
 fn schedule_kaisan_at<C: ScheduleKaisan + Send + Sync>(
+    ctx: C,
+    id: ScheduleId,
+    schedule: &Schedule,
+) -> AbortHandle {
+    let voice_channel_id = schedule.voice_channel_id;
+    let time = schedule.time;
+    let span = tracing::info_span!(
+        "scheduled_kaisan",
+        %time,
+        ?id,
+        author_id = schedule.author_id.get(),
+        message_id = schedule.message_id.map(|id| id.get())
+    );
+    spawn(
+        async move {
+            ctx.delay_until(time).await;
+            let _permit = ctx.dispatch().await;
+
+            // the targets may have changed by reactions since the schedule was made
+            let Some(schedule) = ctx.take_schedule(id).await else {
+                tracing::info!("schedule is no longer registered");
+                return;
+            };
+            tracing::info!(kaisanee = ?schedule.kaisanee, "firing scheduled kaisan");
+
+            if let Some(late) = lateness(&ctx, time) {
+                tracing::warn!(%late, "skipped kaisan past the grace window");
+                if let Err(e) = notify_skipped(&ctx, time, late).await {
+                    tracing::error!(error = %e, "failed to notify the skipped kaisan");
+                }
+                return;
+            }
+
+            if let Err(e) = kaisan(
+                &ctx,
+                Some(id),
+                voice_channel_id,
+                &schedule.kaisanee,
+                &schedule.opted_out,
+            )
+            .await
+            {
+                tracing::error!(error = %e, "failed to kaisan");
+                log_event(
+                    &ctx,
+                    LogEntry::Failed {
+                        schedule_id: id,
+                        voice_channel_id,
+                        error: e.clone(),
+                    },
+                )
+                .await;
+                let _ = future::try_join(ctx.react_failure(), ctx.message(Message::KaisanError(e)))
+                    .await;
+            }
+        }
+        .instrument(span),
+    )
+    .abort_handle()
+}
+
+fn schedule_countdown_at<C: ScheduleKaisan + Sync>(
     ctx: C,
     voice_channel_id: ChannelId,
+    countdown_start: DateTime<Utc>,
     time: DateTime<Utc>,
-    kaisanee: KaisaneeSpecifier,
-) {
-    spawn(async move {
-        ctx.delay_until(time).await;
+) -> AbortHandle {
+    let span = tracing::info_span!("scheduled_countdown", %time);
+    spawn(
+        async move {
+            ctx.delay_until(countdown_start).await;
+            if lateness(&ctx, countdown_start).is_some() {
+                tracing::warn!("skipped countdown past the grace window");
+                return;
+            }
+            tracing::debug!("firing scheduled countdown");
 
-        if let Err(e) = kaisan(&ctx, voice_channel_id, &kaisanee).await {
-            tracing::error!(error = %e, "failed to kaisan");
-            let _ = future::try_join(ctx.react('❌'), ctx.message(Message::KaisanError(e))).await;
+            if let Err(e) = countdown(&ctx, voice_channel_id, time).await {
+                tracing::error!(error = %e, "failed to count down");
+            }
         }
-    });
+        .instrument(span),
+    )
+    .abort_handle()
+}
+
+fn schedule_announcement_at<C: ScheduleKaisan + Sync>(
+    ctx: C,
+    id: ScheduleId,
+    announcement_start: DateTime<Utc>,
+) -> AbortHandle {
+    let span = tracing::info_span!("scheduled_announcement", %announcement_start, ?id);
+    spawn(
+        async move {
+            ctx.delay_until(announcement_start).await;
+            if lateness(&ctx, announcement_start).is_some() {
+                tracing::warn!("skipped announcement past the grace window");
+                return;
+            }
+            tracing::debug!("firing scheduled announcement");
+
+            if let Err(e) = announce(&ctx, id).await {
+                tracing::error!(error = %e, "failed to announce");
+            }
+        }
+        .instrument(span),
+    )
+    .abort_handle()
 }
 
 fn schedule_reminder_at<C: ScheduleKaisan + Sync>(
     ctx: C,
-    voice_channel_id: ChannelId,
+    id: ScheduleId,
+    schedule: Schedule,
     remind_time: DateTime<Utc>,
-    kaisanee: KaisaneeSpecifier,
     reminder: Reminder,
-) {
-    spawn(async move {
-        ctx.delay_until(remind_time).await;
+) -> AbortHandle {
+    let span = tracing::info_span!(
+        "scheduled_reminder",
+        %remind_time,
+        ?reminder,
+        ?id,
+        author_id = schedule.author_id.get(),
+        message_id = schedule.message_id.map(|id| id.get())
+    );
+    spawn(
+        async move {
+            ctx.delay_until(remind_time).await;
+            let _permit = ctx.dispatch().await;
+            if let Some(late) = lateness(&ctx, remind_time) {
+                tracing::warn!(%late, "skipped remind past the grace window");
+                return;
+            }
+            tracing::info!("firing scheduled remind");
+
+            if let Err(e) = remind(&ctx, id, &schedule, reminder).await {
+                tracing::error!(error = %e, "failed to remind");
+                let _ = future::try_join(ctx.react_failure(), ctx.message(Message::RemindError(e)))
+                    .await;
+            }
+        }
+        .instrument(span),
+    )
+    .abort_handle()
+}
+
+/// Returns how late it is, if it is too late to do what was scheduled at `time`.
+fn lateness<C: TimeContext>(ctx: &C, time: DateTime<Utc>) -> Option<Duration> {
+    let late = ctx.current_time() - time;
+    (late > ctx.late_grace()).then_some(late)
+}
+
+async fn notify_skipped<C: ScheduleKaisan + Sync>(
+    ctx: &C,
+    time: DateTime<Utc>,
+    late: Duration,
+) -> Result<()> {
+    let tz = ctx.timezone().await?;
+    ctx.message(Message::KaisanSkipped {
+        time: time.with_timezone(&tz),
+        late,
+    })
+    .await
+}
+
+pub(super) async fn kaisan<C: ScheduleKaisan + Sync>(
+    ctx: &C,
+    schedule_id: Option<ScheduleId>,
+    voice_channel_id: ChannelId,
+    kaisanee: &KaisaneeSpecifier,
+    opted_out: &[UserId],
+) -> Result<()> {
+    let target_users = collect_target_users(ctx, voice_channel_id, kaisanee, opted_out).await?;
+    // look up before disconnecting, which makes them leave the channel
+    let session = session_length(ctx, &target_users).await;
+    let count = target_users.len();
+
+    let mut futures = Vec::new();
+    for user_id in &target_users {
+        futures.push(disconnect(ctx, schedule_id, voice_channel_id, *user_id).boxed());
+    }
+
+    if !target_users.is_empty() {
+        ctx.notify(Notification::Kaisan {
+            schedule_id,
+            voice_channel_id,
+            users: target_users.clone(),
+        });
+        futures.push(ctx.message(Message::Kaisan(target_users.clone())));
+    }
+
+    future::try_join_all(futures).await?;
+
+    if count > 0 {
+        log_event(
+            ctx,
+            LogEntry::Kaisan {
+                schedule_id,
+                voice_channel_id,
+                users: target_users,
+            },
+        )
+        .await;
+        ctx.message(Message::KaisanSummary { count, session })
+            .await?;
+    }
+
+    ctx.react_success().await?;
+
+    Ok(())
+}
+
+/// How long the call has lasted since the first of the users joined, in whole minutes.
+async fn session_length<C: ScheduleKaisan + Sync>(ctx: &C, users: &[UserId]) -> Option<Duration> {
+    let mut first_joined_at = None;
+    for user_id in users {
+        if let Some(joined_at) = ctx.joined_at(*user_id).await {
+            first_joined_at =
+                Some(first_joined_at.map_or(joined_at, |t: DateTime<Utc>| t.min(joined_at)));
+        }
+    }
+    let session = ctx.current_time() - first_joined_at?;
+    Some(Duration::minutes(session.num_minutes()))
+}
+
+async fn countdown<C: ScheduleKaisan + Sync>(
+    ctx: &C,
+    voice_channel_id: ChannelId,
+    time: DateTime<Utc>,
+) -> Result<()> {
+    if !ctx.countdown_in(voice_channel_id).await? {
+        return Ok(());
+    }
+
+    // each step takes a slot of its own, not to hold one for the whole minute
+    let message_id = {
+        let _permit = ctx.dispatch().await;
+        ctx.post_message(Message::Countdown(COUNTDOWN_SECONDS))
+            .await?
+    };
+    for n in (1..COUNTDOWN_SECONDS / COUNTDOWN_INTERVAL_SECONDS).rev() {
+        let seconds = n * COUNTDOWN_INTERVAL_SECONDS;
+        ctx.delay_until(time - Duration::seconds(seconds)).await;
+        let _permit = ctx.dispatch().await;
+        ctx.edit_message(message_id, Message::Countdown(seconds))
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Plays the announcement in the voice channel, or posts the last seconds of the countdown if the
+/// bot cannot join it.
+async fn announce<C: ScheduleKaisan + Sync>(ctx: &C, id: ScheduleId) -> Result<()> {
+    let Some(schedule) = ctx
+        .schedules()
+        .await
+        .into_iter()
+        .find_map(|(i, schedule)| (i == id).then_some(schedule))
+    else {
+        return Ok(());
+    };
+    let Some(voice_channel_id) =
+        target_voice_channel(ctx, schedule.voice_channel_id, &schedule.kaisanee).await?
+    else {
+        return Ok(());
+    };
+    if ctx.voice_channel_users(voice_channel_id).await?.is_empty() {
+        return Ok(());
+    }
+
+    let e = match ctx.announce_in_voice(voice_channel_id).await {
+        Ok(_) => return Ok(()),
+        Err(e) => e,
+    };
+    tracing::warn!(error = %e, "failed to announce in the voice channel");
+    // the countdown in the text channel is already going on
+    if ctx.countdown_in(schedule.voice_channel_id).await? {
+        return Ok(());
+    }
+    let _permit = ctx.dispatch().await;
+    ctx.message(Message::Countdown(ANNOUNCEMENT_SECONDS)).await
+}
+
+async fn disconnect<C: ScheduleKaisan + Sync>(
+    ctx: &C,
+    schedule_id: Option<ScheduleId>,
+    voice_channel_id: ChannelId,
+    user_id: UserId,
+) -> Result<()> {
+    ctx.disconnect_user(user_id).await?;
+
+    let event = DisconnectEvent {
+        time: ctx.current_time(),
+        channel_id: ctx.channel_id(),
+        voice_channel_id,
+        target: user_id,
+        requester: ctx.author_id(),
+        schedule_id,
+    };
+    tracing::info!(
+        target: "kaisantantoudaijin::disconnect",
+        guild_id = %ctx.guild_id(),
+        channel_id = %event.channel_id,
+        voice_channel_id = %event.voice_channel_id,
+        target_id = %event.target,
+        requester_id = %event.requester,
+        schedule_id = ?event.schedule_id,
+        "disconnect"
+    );
+    let time = event.time;
+    if let Err(e) = ctx.record_disconnect(event).await {
+        tracing::warn!(error = %e, "failed to record disconnection");
+    }
+    if let Err(e) = record_kaisaned(ctx, user_id, time).await {
+        tracing::warn!(error = %e, "failed to count disconnection");
+    }
+
+    Ok(())
+}
+
+async fn record_kaisaned<C: ScheduleKaisan + Sync>(
+    ctx: &C,
+    user_id: UserId,
+    time: DateTime<Utc>,
+) -> Result<()> {
+    let date = time.with_timezone(&ctx.timezone().await?).date_naive();
+    ctx.record_kaisaned(user_id, date).await
+}
+
+async fn remind<C: ScheduleKaisan + Sync>(
+    ctx: &C,
+    id: ScheduleId,
+    schedule: &Schedule,
+    reminder: Reminder,
+) -> Result<()> {
+    // pick up the opt-outs made after the reminder was scheduled
+    let current = ctx
+        .schedules()
+        .await
+        .into_iter()
+        .find_map(|(i, schedule)| (i == id).then_some(schedule));
+    let schedule = current.as_ref().unwrap_or(schedule);
+    let target_users = collect_target_users(
+        ctx,
+        schedule.voice_channel_id,
+        &schedule.kaisanee,
+        &schedule.opted_out,
+    )
+    .await?;
+    if target_users.is_empty() {
+        // nobody is left to kaisan, so drop the remaining reminders along with the kaisan
+        if ctx
+            .voice_channel_users(schedule.voice_channel_id)
+            .await?
+            .is_empty()
+            && ctx.cancel_schedule(id).await.is_some()
+        {
+            tracing::info!(?id, "voice channel is empty, cancelled the schedule");
+            log_event(
+                ctx,
+                LogEntry::Cancelled {
+                    schedule_id: id,
+                    voice_channel_id: schedule.voice_channel_id,
+                    by: None,
+                },
+            )
+            .await;
+        }
+        return Ok(());
+    }
+
+    ctx.notify(Notification::Remind {
+        schedule_id: id,
+        voice_channel_id: schedule.voice_channel_id,
+        users: target_users.clone(),
+        time: schedule.time,
+    });
+    let mentioned_users = if schedule.remind_only_me {
+        vec![ctx.author_id()]
+    } else {
+        target_users
+    };
+    let (tz, template) = future::try_join(ctx.timezone(), ctx.reminder_template()).await?;
+    ctx.message(Message::Remind {
+        users: mentioned_users,
+        reminder,
+        time: schedule.time.with_timezone(&tz),
+        template,
+    })
+    .await?;
+
+    Ok(())
+}
+
+/// The voice channel to kaisan, or `None` if the author has left voice for `this channel`.
+async fn target_voice_channel<C: GuildContext + MessageContext + Sync + ?Sized>(
+    ctx: &C,
+    voice_channel_id: ChannelId,
+    kaisanee: &KaisaneeSpecifier,
+) -> Result<Option<ChannelId>> {
+    match kaisanee {
+        // follows the author, who may have moved since the kaisan was scheduled
+        KaisaneeSpecifier::ThisChannel { .. } => ctx.connected_voice_channel(ctx.author_id()).await,
+        _ => Ok(Some(voice_channel_id)),
+    }
+}
+
+pub(super) async fn collect_target_users<C: GuildContext + MessageContext + Sync + ?Sized>(
+    ctx: &C,
+    voice_channel_id: ChannelId,
+    kaisanee: &KaisaneeSpecifier,
+    opted_out: &[UserId],
+) -> Result<Vec<UserId>> {
+    let author_id = ctx.author_id();
+    let Some(voice_channel_id) = target_voice_channel(ctx, voice_channel_id, kaisanee).await?
+    else {
+        return Ok(Vec::new());
+    };
+    let in_users = ctx.voice_channel_users(voice_channel_id).await?;
+
+    let users = match kaisanee {
+        KaisaneeSpecifier::Me => {
+            if in_users.contains(&author_id) {
+                vec![author_id]
+            } else {
+                vec![]
+            }
+        }
+        KaisaneeSpecifier::All => in_users,
+        KaisaneeSpecifier::ThisChannel { except } => in_users
+            .into_iter()
+            .filter(|u| !except.contains(u))
+            .collect(),
+        KaisaneeSpecifier::Users(users) => users
+            .iter()
+            .filter(|u| in_users.contains(u))
+            .copied()
+            .collect(),
+        // resolved into `Users` by `resolve_kaisanee` before reaching here
+        KaisaneeSpecifier::Named(_) => Vec::new(),
+    };
+    Ok(users
+        .into_iter()
+        .filter(|u| !opted_out.contains(u))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ScheduleKaisan;
+    use crate::{
+        context::{ScheduleContext, SettingContext},
+        error::Error,
+        model::{
+            command::{KaisanOptions, TimeRangeSpecifier},
+            kaisanee::KaisaneeSpecifier,
+            member::MemberNames,
+            message::{LogEntry, Message},
+            notification::Notification,
+            reminder::Reminder,
+            setting::{DstPolicy, DuplicatePolicy, RandomDistribution, RevealRandom},
+            template::ReminderTemplate,
+            time::{
+                AfterTimeSpecifier, AtTimeSpecifier, CalculateTimeError, DateSpecifier, Hour,
+                Minute, TimeSpecifier,
+            },
+        },
+        test::{MockContext, FIXED_RANDOM, MOCK_AUTHOR_1, MOCK_AUTHOR_2, MOCK_VOICE_CHANNEL_ID},
+        use_case,
+    };
+    use chrono::{DateTime, Duration, FixedOffset, Utc};
+    use serenity::model::id::{ChannelId, RoleId, UserId};
+    use std::sync::atomic::Ordering;
+
+    #[tokio::test]
+    async fn test_all() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+
+        ctx.schedule_kaisan(
+            KaisaneeSpecifier::All,
+            TimeRangeSpecifier::Now,
+            KaisanOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        ctx.scheduler
+            .advance_to(Utc::now() + Duration::seconds(1))
+            .await;
+        assert!(ctx.has_sent(|m| matches!(m, Message::Kaisan(_))).await);
+
+        {
+            let users = &*ctx.disconnected_users.lock().await;
+            assert!(users.contains(&MOCK_AUTHOR_1));
+            assert!(users.contains(&MOCK_AUTHOR_2));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_summary() {
+        let time = Utc::now();
+        let ctx = MockContext::with_author_current_time(MOCK_AUTHOR_2, time);
+        {
+            let mut joined_at = ctx.joined_at.lock().await;
+            joined_at.insert(MOCK_AUTHOR_1, time - Duration::minutes(30));
+            joined_at.insert(MOCK_AUTHOR_2, time - Duration::seconds(90 * 60 + 20));
+        }
+
+        ctx.schedule_kaisan(
+            KaisaneeSpecifier::All,
+            TimeRangeSpecifier::Now,
+            KaisanOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        assert!(matches!(
+            ctx.sent_messages.lock().await.as_slice(),
+            [
+                Message::Kaisan(_),
+                Message::KaisanSummary {
+                    count: 2,
+                    session: Some(session)
+                }
+            ] if *session == Duration::minutes(90)
+        ));
+        let today = time.with_timezone(&chrono_tz::Japan).date_naive();
+        let kaisaned = ctx.kaisaned_counts.lock().await;
+        assert_eq!(kaisaned[&(today, MOCK_AUTHOR_1)], 1);
+        assert_eq!(kaisaned[&(today, MOCK_AUTHOR_2)], 1);
+        assert!(matches!(
+            ctx.notifications.lock().unwrap().as_slice(),
+            [Notification::Kaisan {
+                schedule_id: None,
+                users,
+                ..
+            }] if users.len() == 2
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_this_channel_follows_author() {
+        let time = Utc::now();
+        let ctx = MockContext::with_author_current_time(MOCK_AUTHOR_2, time);
+        let other = UserId::new(3);
+
+        ctx.schedule_kaisan(
+            KaisaneeSpecifier::ThisChannel {
+                except: vec![other],
+            },
+            TimeRangeSpecifier::At(TimeSpecifier::After(AfterTimeSpecifier::Minute(10))),
+            KaisanOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        // the author moves to another channel, where others are
+        {
+            let mut voice_states = ctx.voice_states.lock().await;
+            let moved_to = ChannelId::new(1);
+            voice_states.insert(MOCK_AUTHOR_2, moved_to);
+            voice_states.insert(other, moved_to);
+            voice_states.insert(UserId::new(4), moved_to);
+        }
+        ctx.scheduler.advance_to(time + Duration::minutes(10)).await;
+        assert!(ctx.has_sent(|m| matches!(m, Message::Kaisan(_))).await);
+
+        let mut users = ctx.disconnected_users.lock().await.clone();
+        users.sort();
+        assert_eq!(users, vec![UserId::new(4), MOCK_AUTHOR_2]);
+    }
+
+    #[tokio::test]
+    async fn test_me() {
+        let time = Utc::now();
+        let ctx = MockContext::with_author_current_time(MOCK_AUTHOR_2, time);
+
+        ctx.schedule_kaisan(
+            KaisaneeSpecifier::Me,
+            TimeRangeSpecifier::At(TimeSpecifier::Exactly(
+                time.with_timezone(&FixedOffset::east_opt(0).unwrap()) + Duration::minutes(10),
+            )),
+            KaisanOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        ctx.scheduler.advance_to(time + Duration::minutes(10)).await;
+        assert!(ctx.has_sent(|m| matches!(m, Message::Kaisan(_))).await);
+
+        {
+            let users = &*ctx.disconnected_users.lock().await;
+            assert!(!users.contains(&MOCK_AUTHOR_1));
+            assert!(users.contains(&MOCK_AUTHOR_2));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_disconnect_events() {
+        let time = Utc::now();
+        let ctx = MockContext::with_author_current_time(MOCK_AUTHOR_2, time);
+
+        ctx.schedule_kaisan(
+            KaisaneeSpecifier::Users(vec![MOCK_AUTHOR_1]),
+            TimeRangeSpecifier::At(TimeSpecifier::Exactly(
+                time.with_timezone(&FixedOffset::east_opt(0).unwrap()) + Duration::minutes(10),
+            )),
+            KaisanOptions::default(),
+        )
+        .await
+        .unwrap();
+        let (id, _) = ctx.schedules().await[0].clone();
+
+        ctx.scheduler.advance_to(time + Duration::minutes(10)).await;
+        assert!(ctx.has_sent(|m| matches!(m, Message::Kaisan(_))).await);
+
+        let events = ctx.disconnect_events.lock().await;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].target, MOCK_AUTHOR_1);
+        assert_eq!(events[0].requester, MOCK_AUTHOR_2);
+        assert_eq!(events[0].voice_channel_id, MOCK_VOICE_CHANNEL_ID);
+        assert_eq!(events[0].schedule_id, Some(id));
+    }
+
+    fn member(user_id: UserId, username: &str, nick: &str) -> MemberNames {
+        MemberNames {
+            user_id,
+            username: username.to_owned(),
+            global_name: None,
+            nick: Some(nick.to_owned()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_named() {
+        let time = Utc::now();
+        let ctx = MockContext::with_author_current_time(MOCK_AUTHOR_2, time);
+        *ctx.members.lock().await = vec![
+            member(MOCK_AUTHOR_1, "tanaka", "田中"),
+            member(MOCK_AUTHOR_2, "tanakayama", "田中山"),
+        ];
+
+        ctx.schedule_kaisan(
+            KaisaneeSpecifier::Named(vec!["田中".to_owned()]),
+            TimeRangeSpecifier::At(TimeSpecifier::After(AfterTimeSpecifier::Minute(10))),
+            KaisanOptions::default(),
+        )
+        .await
+        .unwrap();
+        let schedules = ctx.schedules().await;
+        assert_eq!(
+            schedules[0].1.kaisanee,
+            KaisaneeSpecifier::Users(vec![MOCK_AUTHOR_1])
+        );
+        assert!(
+            ctx.has_sent(|m| matches!(m, Message::Scheduled { head_count: 1, .. }))
+                .await
+        );
+    }
+
+    #[tokio::test]
+    async fn test_named_not_found() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        *ctx.members.lock().await = vec![member(MOCK_AUTHOR_1, "tanaka", "田中")];
+
+        let res = ctx
+            .schedule_kaisan(
+                KaisaneeSpecifier::Named(vec!["田中".to_owned(), "佐藤".to_owned()]),
+                TimeRangeSpecifier::At(TimeSpecifier::After(AfterTimeSpecifier::Minute(10))),
+                KaisanOptions::default(),
+            )
+            .await;
+        assert!(matches!(res, Err(Error::NoSuchMember(name)) if name == "佐藤"));
+        assert!(ctx.schedules().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_named_ambiguous() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        *ctx.members.lock().await = vec![
+            member(MOCK_AUTHOR_1, "tanaka", "田中"),
+            member(MOCK_AUTHOR_2, "tanakayama", "田中山"),
+        ];
+
+        let res = ctx
+            .schedule_kaisan(
+                KaisaneeSpecifier::Named(vec!["tana".to_owned()]),
+                TimeRangeSpecifier::At(TimeSpecifier::After(AfterTimeSpecifier::Minute(10))),
+                KaisanOptions::default(),
+            )
+            .await;
+        match res {
+            Err(Error::AmbiguousMember { name, candidates }) => {
+                assert_eq!(name, "tana");
+                assert_eq!(candidates, vec!["田中 (@tanaka)", "田中山 (@tanakayama)"]);
+            }
+            res => panic!("unexpected {:?}", res),
+        }
+        assert!(ctx.schedules().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_registered_schedule() {
+        let time = Utc::now();
+        let ctx = MockContext::with_author_current_time(MOCK_AUTHOR_2, time);
+
+        ctx.schedule_kaisan(
+            KaisaneeSpecifier::Me,
+            TimeRangeSpecifier::At(TimeSpecifier::Exactly(
+                time.with_timezone(&FixedOffset::east_opt(0).unwrap()) + Duration::minutes(10),
+            )),
+            KaisanOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        let schedules = ctx.schedules().await;
+        assert_eq!(schedules.len(), 1);
+        let (id, schedule) = &schedules[0];
+        assert_eq!(schedule.kaisanee, KaisaneeSpecifier::Me);
+        assert_eq!(schedule.time, time + Duration::minutes(10));
+        assert_eq!(schedule.reminders, vec![Reminder::before_minutes(5)]);
+
+        assert!(ctx.cancel_schedule(*id).await.is_some());
+        ctx.scheduler.advance_to(time + Duration::minutes(10)).await;
+        assert!(ctx.sent_messages.lock().await.len() == 2);
+        assert!(ctx.disconnected_users.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_log_channel() {
+        let time = Utc::now();
+        let ctx = MockContext::with_author_current_time(MOCK_AUTHOR_2, time);
+        let log_channel = ChannelId::new(42);
+        *ctx.log_channel.lock().await = Some(log_channel);
+
+        ctx.schedule_kaisan(
+            KaisaneeSpecifier::Me,
+            TimeRangeSpecifier::At(TimeSpecifier::Exactly(
+                time.with_timezone(&FixedOffset::east_opt(0).unwrap()) + Duration::minutes(10),
+            )),
+            KaisanOptions::default(),
+        )
+        .await
+        .unwrap();
+        ctx.scheduler.advance_to(time + Duration::minutes(10)).await;
+
+        let logged = ctx.channel_messages.lock().await;
+        assert!(logged
+            .iter()
+            .all(|(channel_id, _)| *channel_id == log_channel));
+        assert!(matches!(
+            logged.as_slice(),
+            [
+                (_, Message::Log(LogEntry::Scheduled { schedule_id: scheduled, author_id, random: false, .. })),
+                (_, Message::Log(LogEntry::Kaisan { schedule_id: Some(kaisaned), users, .. })),
+            ] if *author_id == MOCK_AUTHOR_2 && scheduled == kaisaned && users == &[MOCK_AUTHOR_2]
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_default_timezone_notice() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        let spec = TimeRangeSpecifier::At(TimeSpecifier::After(AfterTimeSpecifier::Minute(10)));
+
+        for _ in 0..2 {
+            ctx.schedule_kaisan(KaisaneeSpecifier::Me, spec, KaisanOptions::default())
+                .await
+                .unwrap();
+        }
+        assert!(matches!(
+            ctx.sent_messages.lock().await.as_slice(),
+            [
+                Message::Scheduled { .. },
+                Message::DefaultTimezone(chrono_tz::Japan),
+                Message::Scheduled { .. }
+            ]
+        ));
+
+        // no notice once the timezone is set
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        ctx.set_timezone(chrono_tz::Japan).await.unwrap();
+        ctx.schedule_kaisan(KaisaneeSpecifier::Me, spec, KaisanOptions::default())
+            .await
+            .unwrap();
+        assert!(matches!(
+            ctx.sent_messages.lock().await.as_slice(),
+            [Message::Scheduled { .. }]
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_skip_late() {
+        let time = Utc::now();
+        let ctx = MockContext::with_author_current_time(MOCK_AUTHOR_2, time);
+
+        ctx.schedule_kaisan(
+            KaisaneeSpecifier::Me,
+            TimeRangeSpecifier::At(TimeSpecifier::Exactly(
+                time.with_timezone(&FixedOffset::east_opt(0).unwrap()) + Duration::minutes(10),
+            )),
+            KaisanOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        // as if the host woke up from a long sleep
+        ctx.scheduler.jump_to(time + Duration::minutes(20)).await;
+        assert!(
+            ctx.has_sent(|m| matches!(m, Message::KaisanSkipped { .. }))
+                .await
+        );
+        assert!(ctx.disconnected_users.lock().await.is_empty());
+        assert!(ctx.schedules().await.is_empty());
+        assert!(!ctx
+            .sent_messages
+            .lock()
+            .await
+            .iter()
+            .any(|m| matches!(m, Message::Remind { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_time() {
+        let now = Utc::now();
+        let ctx = MockContext::with_current_time(now);
+
+        let now_with_tz = now.with_timezone(&FixedOffset::east_opt(3600).unwrap());
+        let res = ctx
+            .schedule_kaisan(
+                KaisaneeSpecifier::Me,
+                TimeRangeSpecifier::At(TimeSpecifier::Exactly(
+                    now_with_tz - chrono::Duration::minutes(1),
+                )),
+                KaisanOptions::default(),
+            )
+            .await;
+
+        assert!(matches!(res, Err(Error::UnreachableTime { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_head_count() {
+        let time = Utc::now();
+        let ctx = MockContext::with_author_current_time(MOCK_AUTHOR_2, time);
+
+        ctx.schedule_kaisan(
+            KaisaneeSpecifier::All,
+            TimeRangeSpecifier::At(TimeSpecifier::After(AfterTimeSpecifier::Minute(10))),
+            KaisanOptions::default(),
+        )
+        .await
+        .unwrap();
+        ctx.schedule_kaisan(
+            KaisaneeSpecifier::Me,
+            TimeRangeSpecifier::By(TimeSpecifier::After(AfterTimeSpecifier::Minute(10))),
+            KaisanOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        assert!(matches!(
+            ctx.sent_messages.lock().await.as_slice(),
+            [
+                Message::Scheduled { head_count: 2, .. },
+                Message::DefaultTimezone(_),
+                Message::Scheduled { head_count: 1, .. }
+            ]
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_random_within_window() {
+        for seed in 0..100 {
+            let now = Utc::now();
+            let ctx = MockContext::with_author_current_time(MOCK_AUTHOR_2, now);
+            ctx.seed_random(seed).await;
+            let minutes = (seed % 120 + 1) as u8;
+
+            ctx.schedule_kaisan(
+                KaisaneeSpecifier::Me,
+                TimeRangeSpecifier::By(TimeSpecifier::After(AfterTimeSpecifier::Minute(minutes))),
+                KaisanOptions::default(),
+            )
+            .await
+            .unwrap();
+
+            let by = now + Duration::minutes(minutes.into());
+            let schedules = ctx.schedules().await;
+            let [(_, schedule)] = schedules.as_slice() else {
+                panic!("expected exactly one schedule");
+            };
+            assert!(
+                now <= schedule.time && schedule.time < by,
+                "{} is out of [{}, {}) with seed {}",
+                schedule.time,
+                now,
+                by,
+                seed
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_empty_random_window() {
+        for millis in [0, 500] {
+            let now = Utc::now();
+            let ctx = MockContext::with_author_current_time(MOCK_AUTHOR_2, now);
+            // the seeded RNG panics on an empty range like the real one
+            ctx.seed_random(0).await;
+            let by = now + Duration::milliseconds(millis);
+
+            ctx.schedule_kaisan(
+                KaisaneeSpecifier::Me,
+                TimeRangeSpecifier::By(TimeSpecifier::Exactly(by.fixed_offset())),
+                KaisanOptions::default(),
+            )
+            .await
+            .unwrap();
+
+            assert!(ctx.schedules().await.is_empty());
+            assert_eq!(*ctx.disconnected_users.lock().await, vec![MOCK_AUTHOR_2]);
+        }
+
+        let now = Utc::now();
+        let ctx = MockContext::with_author_current_time(MOCK_AUTHOR_2, now);
+        ctx.seed_random(0).await;
+        ctx.schedule_kaisan(
+            KaisaneeSpecifier::Me,
+            TimeRangeSpecifier::By(TimeSpecifier::After(AfterTimeSpecifier::Second(0))),
+            KaisanOptions::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(*ctx.disconnected_users.lock().await, vec![MOCK_AUTHOR_2]);
+    }
+
+    #[tokio::test]
+    async fn test_random_scripted() {
+        let now = Utc::now();
+        let ctx = MockContext::with_author_current_time(MOCK_AUTHOR_2, now);
+        ctx.script_random([0, 599]).await;
+        let spec = TimeRangeSpecifier::By(TimeSpecifier::After(AfterTimeSpecifier::Minute(10)));
+
+        ctx.schedule_kaisan(KaisaneeSpecifier::Me, spec, KaisanOptions::default())
+            .await
+            .unwrap();
+        ctx.schedule_kaisan(KaisaneeSpecifier::Me, spec, KaisanOptions::default())
+            .await
+            .unwrap();
+
+        let times: Vec<_> = ctx
+            .schedules()
+            .await
+            .into_iter()
+            .map(|(_, schedule)| schedule.time)
+            .collect();
+        assert_eq!(times, vec![now, now + Duration::seconds(599)]);
+    }
+
+    #[tokio::test]
+    async fn test_random_distribution_scripted() {
+        let spec = TimeRangeSpecifier::By(TimeSpecifier::After(AfterTimeSpecifier::Minute(10)));
+        for (distribution, expected) in [
+            (RandomDistribution::Uniform, 10),
+            (RandomDistribution::LateBiased, 500),
+            (RandomDistribution::EarlyBiased, 10),
+        ] {
+            let now = Utc::now();
+            let ctx = MockContext::with_author_current_time(MOCK_AUTHOR_2, now);
+            *ctx.random_distribution.lock().await = distribution;
+            ctx.script_random([10, 500]).await;
+
+            ctx.schedule_kaisan(KaisaneeSpecifier::Me, spec, KaisanOptions::default())
+                .await
+                .unwrap();
+
+            let schedules = ctx.schedules().await;
+            let [(_, schedule)] = schedules.as_slice() else {
+                panic!("expected exactly one schedule");
+            };
+            assert_eq!(schedule.time, now + Duration::seconds(expected));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_random_distribution_within_window() {
+        let spec = TimeRangeSpecifier::By(TimeSpecifier::After(AfterTimeSpecifier::Minute(10)));
+        let mut sums = Vec::new();
+        for distribution in [
+            RandomDistribution::EarlyBiased,
+            RandomDistribution::Uniform,
+            RandomDistribution::LateBiased,
+        ] {
+            let mut sum = 0;
+            for seed in 0..100 {
+                let now = Utc::now();
+                let ctx = MockContext::with_author_current_time(MOCK_AUTHOR_2, now);
+                *ctx.random_distribution.lock().await = distribution;
+                ctx.seed_random(seed).await;
+
+                ctx.schedule_kaisan(KaisaneeSpecifier::Me, spec, KaisanOptions::default())
+                    .await
+                    .unwrap();
+
+                let schedules = ctx.schedules().await;
+                let [(_, schedule)] = schedules.as_slice() else {
+                    panic!("expected exactly one schedule");
+                };
+                let offset = (schedule.time - now).num_seconds();
+                assert!(
+                    (0..600).contains(&offset),
+                    "{} is out of the window with {:?} and seed {}",
+                    offset,
+                    distribution,
+                    seed
+                );
+                sum += offset;
+            }
+            sums.push(sum);
+        }
+        // the expected means are 200, 300 and 400 seconds
+        assert!(sums[0] < sums[1] && sums[1] < sums[2], "{:?}", sums);
+    }
+
+    #[tokio::test]
+    async fn test_reveal_random() {
+        let time = Utc::now();
+        let spec = TimeRangeSpecifier::By(TimeSpecifier::After(AfterTimeSpecifier::Hour(5)));
+        let expected = time + Duration::seconds(FIXED_RANDOM);
+
+        let ctx = MockContext::with_author_current_time(MOCK_AUTHOR_2, time);
+        ctx.schedule_kaisan(KaisaneeSpecifier::Me, spec, KaisanOptions::default())
+            .await
+            .unwrap();
+        assert!(matches!(
+            ctx.sent_messages.lock().await.as_slice(),
+            [
+                Message::Scheduled {
+                    revealed_time: None,
+                    ..
+                },
+                ..
+            ]
+        ));
+
+        let ctx = MockContext::with_author_current_time(MOCK_AUTHOR_2, time);
+        *ctx.reveal_random.lock().await = RevealRandom::Channel;
+        ctx.schedule_kaisan(KaisaneeSpecifier::Me, spec, KaisanOptions::default())
+            .await
+            .unwrap();
+        assert!(matches!(
+            ctx.sent_messages.lock().await.as_slice(),
+            [Message::Scheduled { revealed_time: Some(t), .. }, ..] if *t == expected
+        ));
+
+        let ctx = MockContext::with_author_current_time(MOCK_AUTHOR_2, time);
+        *ctx.reveal_random.lock().await = RevealRandom::DirectMessage;
+        ctx.schedule_kaisan(KaisaneeSpecifier::Me, spec, KaisanOptions::default())
+            .await
+            .unwrap();
+        assert!(matches!(
+            ctx.sent_messages.lock().await.as_slice(),
+            [
+                Message::Scheduled {
+                    revealed_time: None,
+                    ..
+                },
+                ..
+            ]
+        ));
+        assert!(matches!(
+            ctx.direct_messages.lock().await.as_slice(),
+            [(user, Message::RevealedTime(t))] if *user == MOCK_AUTHOR_2 && *t == expected
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_countdown() {
+        let time = Utc::now();
+        let ctx = MockContext::with_author_current_time(MOCK_AUTHOR_2, time);
+        ctx.countdown.store(true, Ordering::SeqCst);
+
+        ctx.schedule_kaisan(
+            KaisaneeSpecifier::Me,
+            TimeRangeSpecifier::At(TimeSpecifier::After(AfterTimeSpecifier::Minute(10))),
+            KaisanOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        ctx.scheduler
+            .advance_to(time + Duration::seconds(9 * 60 + 5))
+            .await;
+        assert!(ctx.has_sent(|m| matches!(m, Message::Countdown(60))).await);
+        ctx.scheduler
+            .advance_to(time + Duration::seconds(9 * 60 + 55))
+            .await;
+        assert!(ctx.has_sent(|m| matches!(m, Message::Countdown(10))).await);
+
+        let messages = ctx.sent_messages.lock().await;
+        let countdowns: Vec<_> = messages
+            .iter()
+            .filter(|m| matches!(m, Message::Countdown(_)))
+            .collect();
+        assert!(matches!(countdowns.as_slice(), [Message::Countdown(10)]));
+        assert!(ctx.disconnected_users.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_voice_announcement() {
+        let time = Utc::now();
+        let ctx = MockContext::with_author_current_time(MOCK_AUTHOR_2, time);
+        *ctx.voice_announcements.lock().unwrap() = Some(Vec::new());
+
+        ctx.schedule_kaisan(
+            KaisaneeSpecifier::All,
+            TimeRangeSpecifier::At(TimeSpecifier::After(AfterTimeSpecifier::Minute(10))),
+            KaisanOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        ctx.scheduler
+            .advance_to(time + Duration::seconds(9 * 60 + 55))
+            .await;
+        assert_eq!(
+            *ctx.voice_announcements.lock().unwrap(),
+            Some(vec![MOCK_VOICE_CHANNEL_ID])
+        );
+
+        ctx.scheduler.advance_to(time + Duration::minutes(10)).await;
+        assert!(ctx.has_sent(|m| matches!(m, Message::Kaisan(_))).await);
+        assert!(!ctx
+            .sent_messages
+            .lock()
+            .await
+            .iter()
+            .any(|m| matches!(m, Message::Countdown(_))));
+    }
+
+    #[tokio::test]
+    async fn test_voice_announcement_fallback() {
+        let time = Utc::now();
+        let ctx = MockContext::with_author_current_time(MOCK_AUTHOR_2, time);
+        *ctx.voice_announcements.lock().unwrap() = Some(Vec::new());
+        ctx.voice_join_fails.store(true, Ordering::SeqCst);
+
+        ctx.schedule_kaisan(
+            KaisaneeSpecifier::All,
+            TimeRangeSpecifier::At(TimeSpecifier::After(AfterTimeSpecifier::Minute(10))),
+            KaisanOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        ctx.scheduler
+            .advance_to(time + Duration::seconds(9 * 60 + 55))
+            .await;
+        assert!(ctx.has_sent(|m| matches!(m, Message::Countdown(10))).await);
+        ctx.scheduler.advance_to(time + Duration::minutes(10)).await;
+        assert!(ctx.has_sent(|m| matches!(m, Message::Kaisan(_))).await);
+        assert_eq!(*ctx.voice_announcements.lock().unwrap(), Some(Vec::new()));
+    }
+
+    #[tokio::test]
+    async fn test_countdown_disabled() {
+        let time = Utc::now();
+        let ctx = MockContext::with_author_current_time(MOCK_AUTHOR_2, time);
+
+        ctx.schedule_kaisan(
+            KaisaneeSpecifier::Me,
+            TimeRangeSpecifier::At(TimeSpecifier::After(AfterTimeSpecifier::Minute(10))),
+            KaisanOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        ctx.scheduler.advance_to(time + Duration::minutes(10)).await;
+        assert!(ctx.has_sent(|m| matches!(m, Message::Kaisan(_))).await);
+        assert!(!ctx
+            .sent_messages
+            .lock()
+            .await
+            .iter()
+            .any(|m| matches!(m, Message::Countdown(_))));
+    }
+
+    #[tokio::test]
+    async fn test_schedule_limit() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        ctx.max_schedules_per_user.store(1, Ordering::SeqCst);
+        let spec = TimeRangeSpecifier::At(TimeSpecifier::After(AfterTimeSpecifier::Minute(10)));
+
+        ctx.schedule_kaisan(KaisaneeSpecifier::Me, spec, KaisanOptions::default())
+            .await
+            .unwrap();
+        assert!(matches!(
+            ctx.schedule_kaisan(KaisaneeSpecifier::Me, spec, KaisanOptions::default())
+                .await,
+            Err(Error::TooManySchedules {
+                max_schedules_per_user: 1
+            })
+        ));
+
+        // the limit does not apply to admins
+        let admin_ctx = MockContext {
+            author_id: MOCK_AUTHOR_2,
+            ..ctx.clone()
+        };
+        admin_ctx
+            .schedule_kaisan(KaisaneeSpecifier::Me, spec, KaisanOptions::default())
+            .await
+            .unwrap();
+        admin_ctx
+            .schedule_kaisan(KaisaneeSpecifier::Me, spec, KaisanOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(ctx.schedules().await.len(), 3);
+    }
 
-        if let Err(e) = remind(&ctx, voice_channel_id, &kaisanee, reminder).await {
-            tracing::error!(error = %e, "failed to remind");
-            let _ = future::try_join(ctx.react('❌'), ctx.message(Message::RemindError(e))).await;
-        }
-    });
-}
+    #[tokio::test]
+    async fn test_stages() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        *ctx.on_duplicate.lock().await = DuplicatePolicy::Replace;
+        let after = |m| TimeRangeSpecifier::At(TimeSpecifier::After(AfterTimeSpecifier::Minute(m)));
 
-async fn kaisan<C: ScheduleKaisan + Sync>(
-    ctx: &C,
-    voice_channel_id: ChannelId,
-    kaisanee: &KaisaneeSpecifier,
-) -> Result<()> {
-    let target_users = collect_target_users(ctx, voice_channel_id, kaisanee).await?;
+        ctx.schedule_kaisan(KaisaneeSpecifier::All, after(5), KaisanOptions::default())
+            .await
+            .unwrap();
 
-    let mut futures = Vec::new();
-    for user_id in &target_users {
-        tracing::info!(?user_id, "disconnect");
-        futures.push(ctx.disconnect_user(*user_id));
+        // the earlier schedule is replaced, but the second stage does not replace the first one
+        ctx.schedule_kaisan_stages(
+            vec![
+                (KaisaneeSpecifier::Me, after(10)),
+                (KaisaneeSpecifier::All, after(20)),
+            ],
+            KaisanOptions {
+                ignored_tokens: vec!["garbage".to_owned()],
+                ..KaisanOptions::default()
+            },
+        )
+        .await
+        .unwrap();
+        let mut schedules = ctx.schedules().await;
+        schedules.sort_by_key(|(_, schedule)| schedule.time);
+        let kaisanees: Vec<_> = schedules.into_iter().map(|(_, s)| s.kaisanee).collect();
+        assert_eq!(
+            kaisanees,
+            vec![KaisaneeSpecifier::Me, KaisaneeSpecifier::All]
+        );
+        assert_eq!(
+            ctx.sent_messages
+                .lock()
+                .await
+                .iter()
+                .filter(|m| matches!(m, Message::Scheduled { ignored_tokens, .. } if !ignored_tokens.is_empty()))
+                .count(),
+            1
+        );
     }
 
-    if !target_users.is_empty() {
-        futures.push(ctx.message(Message::Kaisan(target_users)));
-    }
+    #[tokio::test]
+    async fn test_stages_stop_at_error() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        let after = |m| TimeRangeSpecifier::At(TimeSpecifier::After(AfterTimeSpecifier::Minute(m)));
 
-    future::try_join_all(futures).await?;
+        let res = ctx
+            .schedule_kaisan_stages(
+                vec![
+                    (KaisaneeSpecifier::Me, after(10)),
+                    (KaisaneeSpecifier::Named(vec!["田中".to_owned()]), after(20)),
+                    (KaisaneeSpecifier::All, after(30)),
+                ],
+                KaisanOptions::default(),
+            )
+            .await;
+        assert!(matches!(res, Err(Error::NoSuchMember(_))));
+        assert!(ctx.schedules().await.is_empty());
+        assert!(ctx.sent_messages.lock().await.is_empty());
+    }
 
-    ctx.react('✅').await?;
+    #[tokio::test]
+    async fn test_stages_rejected_later() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        ctx.max_schedules_per_user.store(1, Ordering::SeqCst);
+        let after = |m| TimeRangeSpecifier::At(TimeSpecifier::After(AfterTimeSpecifier::Minute(m)));
 
-    Ok(())
-}
+        // the first stage alone is within the limit, but not along with the second one
+        let res = ctx
+            .schedule_kaisan_stages(
+                vec![
+                    (KaisaneeSpecifier::Me, after(10)),
+                    (KaisaneeSpecifier::Me, after(20)),
+                ],
+                KaisanOptions::default(),
+            )
+            .await;
+        assert!(matches!(
+            res,
+            Err(Error::TooManySchedules {
+                max_schedules_per_user: 1
+            })
+        ));
+        assert!(ctx.schedules().await.is_empty());
+        assert!(ctx.sent_messages.lock().await.is_empty());
+    }
 
-async fn remind<C: ScheduleKaisan + Sync>(
-    ctx: &C,
-    voice_channel_id: ChannelId,
-    kaisanee: &KaisaneeSpecifier,
-    reminder: Reminder,
-) -> Result<()> {
-    let target_users = collect_target_users(ctx, voice_channel_id, kaisanee).await?;
+    #[tokio::test]
+    async fn test_on_duplicate_stack() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        let spec = TimeRangeSpecifier::At(TimeSpecifier::After(AfterTimeSpecifier::Minute(10)));
 
-    if !target_users.is_empty() {
-        ctx.message(Message::Remind(target_users, reminder)).await?;
+        for _ in 0..2 {
+            ctx.schedule_kaisan(KaisaneeSpecifier::Me, spec, KaisanOptions::default())
+                .await
+                .unwrap();
+        }
+        assert_eq!(ctx.schedules().await.len(), 2);
     }
 
-    Ok(())
-}
+    #[tokio::test]
+    async fn test_on_duplicate_replace() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        *ctx.on_duplicate.lock().await = DuplicatePolicy::Replace;
+        ctx.max_schedules_per_user.store(1, Ordering::SeqCst);
+        let spec = TimeRangeSpecifier::At(TimeSpecifier::After(AfterTimeSpecifier::Minute(10)));
 
-async fn collect_target_users<C: ScheduleKaisan + Sync>(
-    ctx: &C,
-    voice_channel_id: ChannelId,
-    kaisanee: &KaisaneeSpecifier,
-) -> Result<Vec<UserId>> {
-    let in_users = ctx.voice_channel_users(voice_channel_id).await?;
-    let author_id = ctx.author_id();
+        ctx.schedule_kaisan(KaisaneeSpecifier::Me, spec, KaisanOptions::default())
+            .await
+            .unwrap();
+        let first = ctx.schedules().await[0].0;
 
-    Ok(match kaisanee {
-        KaisaneeSpecifier::Me => {
-            if in_users.contains(&author_id) {
-                vec![author_id]
-            } else {
-                vec![]
-            }
-        }
-        KaisaneeSpecifier::All => in_users,
-        KaisaneeSpecifier::Users(users) => users
+        // replacing does not count towards the limit
+        ctx.schedule_kaisan(KaisaneeSpecifier::Me, spec, KaisanOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(ctx.schedules().await.len(), 1);
+        assert!(ctx
+            .sent_messages
+            .lock()
+            .await
             .iter()
-            .filter(|u| in_users.contains(u))
-            .copied()
-            .collect(),
-    })
-}
-
-#[cfg(test)]
-mod tests {
-    use super::ScheduleKaisan;
-    use crate::{
-        error::Error,
-        model::{
-            command::TimeRangeSpecifier,
-            kaisanee::KaisaneeSpecifier,
-            message::Message,
-            reminder::Reminder,
-            time::{AfterTimeSpecifier, TimeSpecifier},
-        },
-        test::{MockContext, MOCK_AUTHOR_1, MOCK_AUTHOR_2},
-        use_case,
-    };
-    use chrono::{Duration, FixedOffset, Utc};
-    use std::sync::atomic::Ordering;
+            .any(|m| matches!(m, Message::ReplacedSchedules(ids) if ids == &vec![first])));
+    }
 
     #[tokio::test]
-    async fn test_all() {
-        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+    async fn test_on_duplicate_reject() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        *ctx.on_duplicate.lock().await = DuplicatePolicy::Reject;
+        let spec = TimeRangeSpecifier::At(TimeSpecifier::After(AfterTimeSpecifier::Minute(10)));
 
-        ctx.schedule_kaisan(KaisaneeSpecifier::All, TimeRangeSpecifier::Now)
+        ctx.schedule_kaisan(KaisaneeSpecifier::Me, spec, KaisanOptions::default())
+            .await
+            .unwrap();
+        let first = ctx.schedules().await[0].0;
+        assert!(matches!(
+            ctx.schedule_kaisan(KaisaneeSpecifier::Me, spec, KaisanOptions::default())
+                .await,
+            Err(Error::DuplicateSchedule(ids)) if ids == vec![first]
+        ));
+
+        // schedules of other users do not count
+        let other_ctx = MockContext {
+            author_id: MOCK_AUTHOR_2,
+            ..ctx.clone()
+        };
+        other_ctx
+            .schedule_kaisan(KaisaneeSpecifier::Me, spec, KaisanOptions::default())
             .await
             .unwrap();
+        assert_eq!(ctx.schedules().await.len(), 2);
+    }
 
-        ctx.set_current_time(Utc::now() + Duration::seconds(1));
-        wait_a_little(ctx.wait_for_message(|m| matches!(m, Message::Kaisan(_)))).await;
+    #[tokio::test]
+    async fn test_too_far_in_future() {
+        let time = Utc::now();
+        let ctx = MockContext::with_author_current_time(MOCK_AUTHOR_2, time);
+        let spec = TimeSpecifier::Exactly(
+            time.with_timezone(&FixedOffset::east_opt(0).unwrap()) + Duration::hours(13),
+        );
 
-        {
-            let users = &*ctx.disconnected_users.lock().await;
-            assert!(users.contains(&MOCK_AUTHOR_1));
-            assert!(users.contains(&MOCK_AUTHOR_2));
-        }
+        assert!(matches!(
+            ctx.schedule_kaisan(
+                KaisaneeSpecifier::Me,
+                TimeRangeSpecifier::By(spec),
+                KaisanOptions::default()
+            )
+            .await,
+            Err(Error::TooFarInFuture {
+                max_horizon_hours: 12,
+                ..
+            })
+        ));
+
+        ctx.max_horizon_hours.store(24, Ordering::SeqCst);
+        ctx.schedule_kaisan(
+            KaisaneeSpecifier::Me,
+            TimeRangeSpecifier::At(spec),
+            KaisanOptions::default(),
+        )
+        .await
+        .unwrap();
     }
 
     #[tokio::test]
-    async fn test_me() {
+    async fn test_channel_override() {
+        let time = Utc::now();
+        let ctx = MockContext::with_author_current_time(MOCK_AUTHOR_2, time);
+        ctx.max_horizon_hours.store(24, Ordering::SeqCst);
+        ctx.channel_overrides
+            .lock()
+            .await
+            .entry(MOCK_VOICE_CHANNEL_ID)
+            .or_default()
+            .max_horizon_hours = Some(1);
+        let spec = TimeSpecifier::Exactly(
+            time.with_timezone(&FixedOffset::east_opt(0).unwrap()) + Duration::hours(2),
+        );
+
+        assert!(matches!(
+            ctx.schedule_kaisan(
+                KaisaneeSpecifier::Me,
+                TimeRangeSpecifier::At(spec),
+                KaisanOptions::default()
+            )
+            .await,
+            Err(Error::TooFarInFuture {
+                max_horizon_hours: 1,
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_remind_only_me() {
         let time = Utc::now();
         let ctx = MockContext::with_author_current_time(MOCK_AUTHOR_2, time);
 
         ctx.schedule_kaisan(
-            KaisaneeSpecifier::Me,
-            TimeRangeSpecifier::At(TimeSpecifier::Exactly(
-                time.with_timezone(&FixedOffset::east_opt(0).unwrap()) + Duration::minutes(10),
-            )),
+            KaisaneeSpecifier::All,
+            TimeRangeSpecifier::At(TimeSpecifier::After(AfterTimeSpecifier::Minute(10))),
+            KaisanOptions {
+                remind_only_me: true,
+                ..KaisanOptions::default()
+            },
         )
         .await
         .unwrap();
 
-        ctx.set_current_time(time + Duration::minutes(10));
-        wait_a_little(ctx.wait_for_message(|m| matches!(m, Message::Kaisan(_)))).await;
+        ctx.scheduler.advance_to(time + Duration::minutes(6)).await;
+        assert!(ctx.has_sent(|m| matches!(m, Message::Remind { .. })).await);
 
-        {
-            let users = &*ctx.disconnected_users.lock().await;
-            assert!(!users.contains(&MOCK_AUTHOR_1));
-            assert!(users.contains(&MOCK_AUTHOR_2));
-        }
+        assert!(ctx
+            .sent_messages
+            .lock()
+            .await
+            .iter()
+            .any(|m| matches!(m, Message::Remind { users, .. } if users == &[MOCK_AUTHOR_2])));
     }
 
     #[tokio::test]
-    async fn test_unreachable_time() {
-        let now = Utc::now();
-        let ctx = MockContext::with_current_time(now);
+    async fn test_reminder_template() {
+        let time = Utc::now();
+        let ctx = MockContext::with_author_current_time(MOCK_AUTHOR_2, time);
+        let template = ReminderTemplate::parse("あと{remaining}で解散だよ").unwrap();
+        *ctx.reminder_template.lock().await = Some(template.clone());
 
-        let now_with_tz = now.with_timezone(&FixedOffset::east_opt(3600).unwrap());
-        let res = ctx
-            .schedule_kaisan(
-                KaisaneeSpecifier::Me,
-                TimeRangeSpecifier::At(TimeSpecifier::Exactly(
-                    now_with_tz - chrono::Duration::minutes(1),
-                )),
-            )
-            .await;
+        ctx.schedule_kaisan(
+            KaisaneeSpecifier::Me,
+            TimeRangeSpecifier::At(TimeSpecifier::After(AfterTimeSpecifier::Minute(10))),
+            KaisanOptions::default(),
+        )
+        .await
+        .unwrap();
 
-        assert!(matches!(res, Err(Error::UnreachableTime { .. })));
+        ctx.scheduler.advance_to(time + Duration::minutes(6)).await;
+        assert!(ctx.has_sent(|m| matches!(m, Message::Remind { .. })).await);
+
+        assert!(ctx
+            .sent_messages
+            .lock()
+            .await
+            .iter()
+            .any(|m| matches!(m, Message::Remind { template: Some(t), .. } if t == &template)));
     }
 
     #[tokio::test]
@@ -348,24 +2127,25 @@ mod tests {
         ctx.schedule_kaisan(
             KaisaneeSpecifier::All,
             TimeRangeSpecifier::At(TimeSpecifier::After(AfterTimeSpecifier::Minute(5))),
+            KaisanOptions::default(),
         )
         .await
         .unwrap();
 
-        ctx.set_current_time(time + Duration::minutes(2));
-        wait_a_little(
-            ctx.wait_for_message(|m| matches!(m, Message::Remind(_, r) if r == &reminder1)),
-        )
-        .await;
+        ctx.scheduler.advance_to(time + Duration::minutes(2)).await;
+        assert!(
+            ctx.has_sent(|m| matches!(m, Message::Remind { reminder: r, .. } if r == &reminder1))
+                .await
+        );
 
-        ctx.set_current_time(time + Duration::minutes(4));
-        wait_a_little(
-            ctx.wait_for_message(|m| matches!(m, Message::Remind(_, r) if r == &reminder2)),
-        )
-        .await;
+        ctx.scheduler.advance_to(time + Duration::minutes(4)).await;
+        assert!(
+            ctx.has_sent(|m| matches!(m, Message::Remind { reminder: r, .. } if r == &reminder2))
+                .await
+        );
 
-        ctx.set_current_time(time + Duration::minutes(5));
-        wait_a_little(ctx.wait_for_message(|m| matches!(m, Message::Kaisan(_)))).await;
+        ctx.scheduler.advance_to(time + Duration::minutes(5)).await;
+        assert!(ctx.has_sent(|m| matches!(m, Message::Kaisan(_))).await);
 
         {
             let users = &*ctx.disconnected_users.lock().await;
@@ -374,6 +2154,165 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_merge_reminders() {
+        let time = Utc::now();
+        let ctx = MockContext::with_author_current_time(MOCK_AUTHOR_2, time);
+        ctx.reminders.lock().await.extend([
+            Reminder::before_seconds(270),
+            Reminder::before_minutes(4),
+            Reminder::before_minutes(3),
+        ]);
+
+        ctx.schedule_kaisan(
+            KaisaneeSpecifier::All,
+            TimeRangeSpecifier::At(TimeSpecifier::After(AfterTimeSpecifier::Minute(10))),
+            KaisanOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        for minutes in [5, 4, 3] {
+            let reminder = Reminder::before_minutes(minutes);
+            ctx.scheduler
+                .advance_to(time + Duration::minutes(10) - reminder.before_duration())
+                .await;
+            assert!(
+                ctx.has_sent(
+                    |m| matches!(m, Message::Remind { reminder: r, .. } if r == &reminder)
+                )
+                .await
+            );
+        }
+
+        let mut reminders: Vec<_> = ctx
+            .sent_messages
+            .lock()
+            .await
+            .iter()
+            .filter_map(|m| match m {
+                Message::Remind { reminder, .. } => Some(*reminder),
+                _ => None,
+            })
+            .collect();
+        reminders.sort_by(|a, b| b.cmp(a));
+        // 5 minutes and 4 minutes 30 seconds before are merged into one
+        assert_eq!(
+            reminders,
+            vec![
+                Reminder::before_minutes(5),
+                Reminder::before_minutes(4),
+                Reminder::before_minutes(3)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reminder_in_empty_channel() {
+        let time = Utc::now();
+        let ctx = MockContext::with_author_current_time(MOCK_AUTHOR_2, time);
+        ctx.reminders
+            .lock()
+            .await
+            .extend([Reminder::before_minutes(3), Reminder::before_minutes(1)]);
+
+        ctx.schedule_kaisan(
+            KaisaneeSpecifier::Me,
+            TimeRangeSpecifier::At(TimeSpecifier::After(AfterTimeSpecifier::Minute(10))),
+            KaisanOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        // only the target left, so the reminder is skipped but the schedule is kept
+        ctx.voice_states.lock().await.remove(&MOCK_AUTHOR_2);
+        ctx.scheduler.advance_to(time + Duration::minutes(5)).await;
+        assert_eq!(ctx.schedules().await.len(), 1);
+
+        // everyone left, so the schedule is moot
+        ctx.voice_states.lock().await.clear();
+        ctx.scheduler.advance_to(time + Duration::minutes(7)).await;
+        assert!(ctx.schedules().await.is_empty());
+
+        ctx.scheduler.advance_to(time + Duration::minutes(10)).await;
+        assert!(!ctx
+            .sent_messages
+            .lock()
+            .await
+            .iter()
+            .any(|m| matches!(m, Message::Remind { .. } | Message::Kaisan(_))));
+    }
+
+    #[tokio::test]
+    async fn test_tonight() {
+        let now = DateTime::parse_from_rfc3339("2024-07-20T18:00:00+09:00")
+            .unwrap()
+            .to_utc();
+        let ctx = MockContext::with_author_current_time(MOCK_AUTHOR_2, now);
+        *ctx.tonight_hour.lock().await = Hour::from_u8(23).unwrap();
+
+        ctx.schedule_kaisan(
+            KaisaneeSpecifier::Me,
+            TimeRangeSpecifier::At(TimeSpecifier::At(AtTimeSpecifier::Tonight)),
+            KaisanOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        let schedules = ctx.schedules().await;
+        let [(_, schedule)] = schedules.as_slice() else {
+            panic!("expected exactly one schedule");
+        };
+        assert_eq!(
+            schedule.time,
+            DateTime::parse_from_rfc3339("2024-07-20T23:00:00+09:00")
+                .unwrap()
+                .to_utc()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dst_policy() {
+        // 2024-11-03 01:30 happens twice in US/Eastern
+        let now = DateTime::parse_from_rfc3339("2024-11-03T00:00:00-04:00")
+            .unwrap()
+            .to_utc();
+        let spec = TimeRangeSpecifier::At(TimeSpecifier::At(AtTimeSpecifier::HourMinute {
+            hour: Hour::from_u8(1).unwrap(),
+            minute: Minute::from_u8(30).unwrap(),
+            date: DateSpecifier::Today,
+        }));
+
+        let ctx = MockContext::with_author_current_time(MOCK_AUTHOR_2, now);
+        *ctx.timezone.lock().await = chrono_tz::US::Eastern;
+        *ctx.dst_policy.lock().await = DstPolicy::Latest;
+        ctx.schedule_kaisan(KaisaneeSpecifier::Me, spec, KaisanOptions::default())
+            .await
+            .unwrap();
+        let schedules = ctx.schedules().await;
+        let [(_, schedule)] = schedules.as_slice() else {
+            panic!("expected exactly one schedule");
+        };
+        assert_eq!(
+            schedule.time,
+            DateTime::parse_from_rfc3339("2024-11-03T01:30:00-05:00")
+                .unwrap()
+                .to_utc()
+        );
+
+        let ctx = MockContext::with_author_current_time(MOCK_AUTHOR_2, now);
+        *ctx.timezone.lock().await = chrono_tz::US::Eastern;
+        *ctx.dst_policy.lock().await = DstPolicy::Reject;
+        assert!(matches!(
+            ctx.schedule_kaisan(KaisaneeSpecifier::Me, spec, KaisanOptions::default())
+                .await,
+            Err(Error::InvalidTime {
+                reason: CalculateTimeError::Ambiguous(_),
+                ..
+            })
+        ));
+    }
+
     #[tokio::test]
     async fn test_random() {
         let time = Utc::now();
@@ -382,12 +2321,13 @@ mod tests {
         ctx.schedule_kaisan(
             KaisaneeSpecifier::All,
             TimeRangeSpecifier::By(TimeSpecifier::After(AfterTimeSpecifier::Minute(5))),
+            KaisanOptions::default(),
         )
         .await
         .unwrap();
 
-        ctx.set_current_time(time + Duration::minutes(5));
-        wait_a_little(ctx.wait_for_message(|m| matches!(m, Message::Kaisan(_)))).await;
+        ctx.scheduler.advance_to(time + Duration::minutes(5)).await;
+        assert!(ctx.has_sent(|m| matches!(m, Message::Kaisan(_))).await);
 
         {
             let users = &*ctx.disconnected_users.lock().await;
@@ -410,18 +2350,18 @@ mod tests {
         ctx.schedule_kaisan(
             KaisaneeSpecifier::All,
             TimeRangeSpecifier::By(TimeSpecifier::After(AfterTimeSpecifier::Minute(10))),
+            KaisanOptions::default(),
         )
         .await
         .unwrap();
 
-        ctx.set_current_time(time + Duration::minutes(10));
-        wait_a_little(ctx.wait_for_message(|m| matches!(m, Message::Kaisan(_)))).await;
+        ctx.scheduler.advance_to(time + Duration::minutes(10)).await;
+        assert!(ctx.has_sent(|m| matches!(m, Message::Kaisan(_))).await);
 
         let messages = ctx.sent_messages.lock().await.clone();
-        assert!(messages
-            .into_iter()
-            .find(|m| matches!(m, Message::Remind(_, r) if r == &reminder))
-            .is_none());
+        assert!(!messages
+            .iter()
+            .any(|m| matches!(m, Message::Remind { reminder: r, .. } if r == &reminder)));
     }
 
     #[tokio::test]
@@ -438,15 +2378,16 @@ mod tests {
         ctx.schedule_kaisan(
             KaisaneeSpecifier::All,
             TimeRangeSpecifier::By(TimeSpecifier::After(AfterTimeSpecifier::Minute(10))),
+            KaisanOptions::default(),
         )
         .await
         .unwrap();
 
-        ctx.set_current_time(time + Duration::minutes(8));
-        wait_a_little(
-            ctx.wait_for_message(|m| matches!(m, Message::Remind(_, r) if r == &reminder)),
-        )
-        .await;
+        ctx.scheduler.advance_to(time + Duration::minutes(8)).await;
+        assert!(
+            ctx.has_sent(|m| matches!(m, Message::Remind { reminder: r, .. } if r == &reminder))
+                .await
+        );
     }
 
     #[tokio::test]
@@ -455,7 +2396,11 @@ mod tests {
         ctx.requires_permission.store(true, Ordering::SeqCst);
 
         let res = ctx
-            .schedule_kaisan(KaisaneeSpecifier::All, TimeRangeSpecifier::Now)
+            .schedule_kaisan(
+                KaisaneeSpecifier::All,
+                TimeRangeSpecifier::Now,
+                KaisanOptions::default(),
+            )
             .await;
         assert!(matches!(res, Err(Error::InsufficientPermission(_))));
     }
@@ -466,14 +2411,73 @@ mod tests {
         ctx.requires_permission.store(false, Ordering::SeqCst);
 
         let res = ctx
-            .schedule_kaisan(KaisaneeSpecifier::All, TimeRangeSpecifier::Now)
+            .schedule_kaisan(
+                KaisaneeSpecifier::All,
+                TimeRangeSpecifier::Now,
+                KaisanOptions::default(),
+            )
+            .await;
+        assert!(matches!(res, Ok(())));
+    }
+
+    #[tokio::test]
+    async fn test_requires_permission_self() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        ctx.requires_permission_self.store(true, Ordering::SeqCst);
+
+        let res = ctx
+            .schedule_kaisan(
+                KaisaneeSpecifier::Me,
+                TimeRangeSpecifier::Now,
+                KaisanOptions::default(),
+            )
+            .await;
+        assert!(matches!(res, Err(Error::InsufficientPermission(_))));
+
+        let ctx = MockContext {
+            author_id: MOCK_AUTHOR_2,
+            ..ctx
+        };
+        let res = ctx
+            .schedule_kaisan(
+                KaisaneeSpecifier::Me,
+                TimeRangeSpecifier::Now,
+                KaisanOptions::default(),
+            )
             .await;
         assert!(matches!(res, Ok(())));
     }
 
-    async fn wait_a_little<F: std::future::Future>(future: F) {
-        tokio::time::timeout(std::time::Duration::from_millis(100), future)
+    #[tokio::test]
+    async fn test_allowed_role() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        ctx.requires_permission.store(true, Ordering::SeqCst);
+        ctx.allowed_roles.lock().await.insert(RoleId::new(1));
+
+        ctx.member_roles
+            .lock()
             .await
-            .unwrap();
+            .insert(MOCK_AUTHOR_1, vec![RoleId::new(2)]);
+        let res = ctx
+            .schedule_kaisan(
+                KaisaneeSpecifier::All,
+                TimeRangeSpecifier::Now,
+                KaisanOptions::default(),
+            )
+            .await;
+        assert!(matches!(res, Err(Error::InsufficientPermission(_))));
+
+        ctx.member_roles
+            .lock()
+            .await
+            .insert(MOCK_AUTHOR_1, vec![RoleId::new(2), RoleId::new(1)]);
+        let res = ctx
+            .schedule_kaisan(
+                KaisaneeSpecifier::All,
+                TimeRangeSpecifier::Now,
+                KaisanOptions::default(),
+            )
+            .await;
+        assert!(matches!(res, Ok(())));
     }
 }
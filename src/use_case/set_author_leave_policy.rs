@@ -0,0 +1,56 @@
+use crate::context::{GuildContext, MessageContext, SettingContext};
+use crate::error::{Error, Result};
+use crate::model::setting::AuthorLeavePolicy;
+
+use serenity::model::permissions::Permissions;
+
+#[async_trait::async_trait]
+pub trait SetAuthorLeavePolicy: SettingContext + GuildContext + MessageContext {
+    #[tracing::instrument(skip(self))]
+    async fn set_author_leave_policy(&self, policy: AuthorLeavePolicy) -> Result<()> {
+        if !self
+            .member_permissions(self.author_id())
+            .await?
+            .manage_guild()
+        {
+            return Err(Error::InsufficientPermission(Permissions::MANAGE_GUILD));
+        }
+
+        SettingContext::set_author_leave_policy(self, policy).await?;
+        self.react_success().await?;
+        Ok(())
+    }
+}
+
+impl<T: SettingContext + GuildContext + MessageContext> SetAuthorLeavePolicy for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::SetAuthorLeavePolicy;
+    use crate::{
+        error::Error,
+        model::setting::AuthorLeavePolicy,
+        test::{MockContext, MOCK_AUTHOR_1, MOCK_AUTHOR_2},
+    };
+
+    #[tokio::test]
+    async fn test_success() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        ctx.set_author_leave_policy(AuthorLeavePolicy::Cancel)
+            .await
+            .unwrap();
+        assert_eq!(
+            *ctx.author_leave_policy.lock().await,
+            AuthorLeavePolicy::Cancel
+        );
+    }
+
+    #[tokio::test]
+    async fn test_insufficient_permission() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        assert!(matches!(
+            ctx.set_author_leave_policy(AuthorLeavePolicy::Cancel).await,
+            Err(Error::InsufficientPermission(_))
+        ));
+    }
+}
@@ -0,0 +1,54 @@
+use crate::context::{GuildContext, MessageContext, SettingContext};
+use crate::error::{Error, Result};
+use crate::model::time::Hour;
+
+use serenity::model::permissions::Permissions;
+
+#[async_trait::async_trait]
+pub trait SetAutoKaisan: SettingContext + GuildContext + MessageContext {
+    #[tracing::instrument(skip(self))]
+    async fn set_auto_kaisan(&self, hour: Option<Hour>) -> Result<()> {
+        if !self
+            .member_permissions(self.author_id())
+            .await?
+            .manage_guild()
+        {
+            return Err(Error::InsufficientPermission(Permissions::MANAGE_GUILD));
+        }
+
+        self.set_auto_kaisan_hour(hour).await?;
+        self.react_success().await?;
+        Ok(())
+    }
+}
+
+impl<T: SettingContext + GuildContext + MessageContext> SetAutoKaisan for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::SetAutoKaisan;
+    use crate::{
+        error::Error,
+        model::time::Hour,
+        test::{MockContext, MOCK_AUTHOR_1, MOCK_AUTHOR_2},
+    };
+
+    #[tokio::test]
+    async fn test_success() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        let hour = Hour::from_u8(23).unwrap();
+        ctx.set_auto_kaisan(Some(hour)).await.unwrap();
+        assert_eq!(*ctx.auto_kaisan_hour.lock().await, Some(hour));
+        ctx.set_auto_kaisan(None).await.unwrap();
+        assert_eq!(*ctx.auto_kaisan_hour.lock().await, None);
+    }
+
+    #[tokio::test]
+    async fn test_insufficient_permission() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        assert!(matches!(
+            ctx.set_auto_kaisan(None).await,
+            Err(Error::InsufficientPermission(_))
+        ));
+    }
+}
@@ -0,0 +1,132 @@
+use crate::context::{ChannelContext, GuildContext, MessageContext, SettingContext};
+use crate::error::{Error, Result};
+use crate::model::{message::Message, setting::ChannelOverride};
+
+use serenity::model::{id::ChannelId, permissions::Permissions};
+
+#[async_trait::async_trait]
+pub trait SetChannelSetting:
+    SettingContext + GuildContext + MessageContext + ChannelContext
+{
+    /// Overrides a setting of the guild in the voice channel, or shows the overrides in it if
+    /// `setting` is `None`.
+    #[tracing::instrument(skip(self))]
+    async fn set_channel_setting(
+        &self,
+        voice_channel_id: ChannelId,
+        setting: Option<ChannelOverride>,
+    ) -> Result<()> {
+        let Some(setting) = setting else {
+            let overrides = self.channel_overrides(voice_channel_id).await?;
+            return self
+                .message(Message::ChannelSetting {
+                    voice_channel_id,
+                    overrides,
+                })
+                .await;
+        };
+
+        if !self
+            .member_permissions(self.author_id())
+            .await?
+            .manage_guild()
+        {
+            return Err(Error::InsufficientPermission(Permissions::MANAGE_GUILD));
+        }
+
+        self.set_channel_override(voice_channel_id, setting).await?;
+        self.react_success().await?;
+        Ok(())
+    }
+}
+
+impl<T: SettingContext + GuildContext + MessageContext + ChannelContext> SetChannelSetting for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::SetChannelSetting;
+    use crate::{
+        context::SettingContext,
+        error::Error,
+        model::{
+            message::Message,
+            setting::{ChannelOverride, DuplicatePolicy},
+        },
+        test::{MockContext, MOCK_AUTHOR_1, MOCK_AUTHOR_2, MOCK_VOICE_CHANNEL_ID},
+    };
+    use serenity::model::id::ChannelId;
+    use std::sync::atomic::Ordering;
+
+    #[tokio::test]
+    async fn test_override() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        ctx.max_horizon_hours.store(12, Ordering::SeqCst);
+        ctx.set_channel_setting(
+            MOCK_VOICE_CHANNEL_ID,
+            Some(ChannelOverride::MaxHorizon(Some(48))),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            ctx.max_horizon_hours_in(MOCK_VOICE_CHANNEL_ID)
+                .await
+                .unwrap(),
+            48
+        );
+        assert_eq!(
+            ctx.max_horizon_hours_in(ChannelId::new(1)).await.unwrap(),
+            12
+        );
+        assert_eq!(ctx.max_horizon_hours().await.unwrap(), 12);
+
+        ctx.set_channel_setting(
+            MOCK_VOICE_CHANNEL_ID,
+            Some(ChannelOverride::MaxHorizon(None)),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            ctx.max_horizon_hours_in(MOCK_VOICE_CHANNEL_ID)
+                .await
+                .unwrap(),
+            12
+        );
+        assert!(ctx.channel_overrides.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_show() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        ctx.channel_overrides
+            .lock()
+            .await
+            .entry(MOCK_VOICE_CHANNEL_ID)
+            .or_default()
+            .on_duplicate = Some(DuplicatePolicy::Reject);
+
+        ctx.set_channel_setting(MOCK_VOICE_CHANNEL_ID, None)
+            .await
+            .unwrap();
+        assert!(matches!(
+            ctx.sent_messages.lock().await.as_slice(),
+            [Message::ChannelSetting { voice_channel_id, overrides }]
+                if *voice_channel_id == MOCK_VOICE_CHANNEL_ID
+                    && overrides.on_duplicate == Some(DuplicatePolicy::Reject)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_insufficient_permission() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        assert!(matches!(
+            ctx.set_channel_setting(
+                MOCK_VOICE_CHANNEL_ID,
+                Some(ChannelOverride::Countdown(Some(true)))
+            )
+            .await,
+            Err(Error::InsufficientPermission(_))
+        ));
+        assert!(ctx.channel_overrides.lock().await.is_empty());
+    }
+}
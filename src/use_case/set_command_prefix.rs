@@ -0,0 +1,55 @@
+use crate::context::{GuildContext, MessageContext, SettingContext};
+use crate::error::{Error, Result};
+
+use serenity::model::permissions::Permissions;
+
+#[async_trait::async_trait]
+pub trait SetCommandPrefix: SettingContext + GuildContext + MessageContext {
+    #[tracing::instrument(skip(self))]
+    async fn set_command_prefix(&self, prefix: Option<String>) -> Result<()> {
+        if !self
+            .member_permissions(self.author_id())
+            .await?
+            .manage_guild()
+        {
+            return Err(Error::InsufficientPermission(Permissions::MANAGE_GUILD));
+        }
+
+        SettingContext::set_command_prefix(self, prefix).await?;
+        self.react_success().await?;
+        Ok(())
+    }
+}
+
+impl<T: SettingContext + GuildContext + MessageContext> SetCommandPrefix for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::SetCommandPrefix;
+    use crate::{
+        error::Error,
+        test::{MockContext, MOCK_AUTHOR_1, MOCK_AUTHOR_2},
+    };
+
+    #[tokio::test]
+    async fn test_success() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        ctx.set_command_prefix(Some("?kaisan".to_owned()))
+            .await
+            .unwrap();
+        assert_eq!(*ctx.command_prefix.lock().await, Some("?kaisan".to_owned()));
+
+        ctx.set_command_prefix(None).await.unwrap();
+        assert_eq!(*ctx.command_prefix.lock().await, None);
+    }
+
+    #[tokio::test]
+    async fn test_insufficient_permission() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        assert!(matches!(
+            ctx.set_command_prefix(Some("?kaisan".to_owned())).await,
+            Err(Error::InsufficientPermission(_))
+        ));
+        assert_eq!(*ctx.command_prefix.lock().await, None);
+    }
+}
@@ -0,0 +1,52 @@
+use crate::context::{GuildContext, MessageContext, SettingContext};
+use crate::error::{Error, Result};
+
+use serenity::model::permissions::Permissions;
+
+#[async_trait::async_trait]
+pub trait SetCountdown: SettingContext + GuildContext + MessageContext {
+    #[tracing::instrument(skip(self))]
+    async fn set_countdown(&self, countdown: bool) -> Result<()> {
+        if !self
+            .member_permissions(self.author_id())
+            .await?
+            .manage_guild()
+        {
+            return Err(Error::InsufficientPermission(Permissions::MANAGE_GUILD));
+        }
+
+        SettingContext::set_countdown(self, countdown).await?;
+        self.react_success().await?;
+        Ok(())
+    }
+}
+
+impl<T: SettingContext + GuildContext + MessageContext> SetCountdown for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::SetCountdown;
+    use crate::{
+        error::Error,
+        test::{MockContext, MOCK_AUTHOR_1, MOCK_AUTHOR_2},
+    };
+    use std::sync::atomic::Ordering;
+
+    #[tokio::test]
+    async fn test_success() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        ctx.set_countdown(false).await.unwrap();
+        assert!(!ctx.countdown.load(Ordering::SeqCst));
+        ctx.set_countdown(true).await.unwrap();
+        assert!(ctx.countdown.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_insufficient_permission() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        assert!(matches!(
+            ctx.set_countdown(true).await,
+            Err(Error::InsufficientPermission(_))
+        ));
+    }
+}
@@ -0,0 +1,51 @@
+use crate::context::{GuildContext, MessageContext, SettingContext};
+use crate::error::{Error, Result};
+use crate::model::setting::DstPolicy;
+
+use serenity::model::permissions::Permissions;
+
+#[async_trait::async_trait]
+pub trait SetDstPolicy: SettingContext + GuildContext + MessageContext {
+    #[tracing::instrument(skip(self))]
+    async fn set_dst_policy(&self, policy: DstPolicy) -> Result<()> {
+        if !self
+            .member_permissions(self.author_id())
+            .await?
+            .manage_guild()
+        {
+            return Err(Error::InsufficientPermission(Permissions::MANAGE_GUILD));
+        }
+
+        SettingContext::set_dst_policy(self, policy).await?;
+        self.react_success().await?;
+        Ok(())
+    }
+}
+
+impl<T: SettingContext + GuildContext + MessageContext> SetDstPolicy for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::SetDstPolicy;
+    use crate::{
+        error::Error,
+        model::setting::DstPolicy,
+        test::{MockContext, MOCK_AUTHOR_1, MOCK_AUTHOR_2},
+    };
+
+    #[tokio::test]
+    async fn test_success() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        ctx.set_dst_policy(DstPolicy::Reject).await.unwrap();
+        assert_eq!(*ctx.dst_policy.lock().await, DstPolicy::Reject);
+    }
+
+    #[tokio::test]
+    async fn test_insufficient_permission() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        assert!(matches!(
+            ctx.set_dst_policy(DstPolicy::Reject).await,
+            Err(Error::InsufficientPermission(_))
+        ));
+    }
+}
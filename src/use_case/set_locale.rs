@@ -0,0 +1,51 @@
+use crate::context::{GuildContext, MessageContext, SettingContext};
+use crate::error::{Error, Result};
+use crate::model::setting::Locale;
+
+use serenity::model::permissions::Permissions;
+
+#[async_trait::async_trait]
+pub trait SetLocale: SettingContext + GuildContext + MessageContext {
+    #[tracing::instrument(skip(self))]
+    async fn set_locale(&self, locale: Locale) -> Result<()> {
+        if !self
+            .member_permissions(self.author_id())
+            .await?
+            .manage_guild()
+        {
+            return Err(Error::InsufficientPermission(Permissions::MANAGE_GUILD));
+        }
+
+        SettingContext::set_locale(self, locale).await?;
+        self.react_success().await?;
+        Ok(())
+    }
+}
+
+impl<T: SettingContext + GuildContext + MessageContext> SetLocale for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::SetLocale;
+    use crate::{
+        error::Error,
+        model::setting::Locale,
+        test::{MockContext, MOCK_AUTHOR_1, MOCK_AUTHOR_2},
+    };
+
+    #[tokio::test]
+    async fn test_success() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        ctx.set_locale(Locale::Both).await.unwrap();
+        assert_eq!(*ctx.locale.lock().await, Locale::Both);
+    }
+
+    #[tokio::test]
+    async fn test_insufficient_permission() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        assert!(matches!(
+            ctx.set_locale(Locale::Both).await,
+            Err(Error::InsufficientPermission(_))
+        ));
+    }
+}
@@ -0,0 +1,76 @@
+use crate::context::{GuildContext, MessageContext, SettingContext};
+use crate::error::{Error, Result};
+
+use serenity::model::{id::ChannelId, permissions::Permissions};
+
+#[async_trait::async_trait]
+pub trait SetLogChannel: SettingContext + GuildContext + MessageContext {
+    #[tracing::instrument(skip(self))]
+    async fn set_log_channel(&self, channel_id: Option<ChannelId>) -> Result<()> {
+        if !self
+            .member_permissions(self.author_id())
+            .await?
+            .manage_guild()
+        {
+            return Err(Error::InsufficientPermission(Permissions::MANAGE_GUILD));
+        }
+
+        if let Some(channel_id) = channel_id {
+            if !self.can_post_to(channel_id).await? {
+                return Err(Error::InvalidLogChannel(channel_id));
+            }
+        }
+
+        SettingContext::set_log_channel(self, channel_id).await?;
+        self.react_success().await?;
+        Ok(())
+    }
+}
+
+impl<T: SettingContext + GuildContext + MessageContext> SetLogChannel for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::SetLogChannel;
+    use crate::{
+        error::Error,
+        test::{MockContext, MOCK_AUTHOR_1, MOCK_AUTHOR_2},
+    };
+    use serenity::model::id::ChannelId;
+
+    #[tokio::test]
+    async fn test_success() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        let channel_id = ChannelId::new(42);
+        ctx.text_channels.lock().await.insert(channel_id);
+        SetLogChannel::set_log_channel(&ctx, Some(channel_id))
+            .await
+            .unwrap();
+        assert_eq!(*ctx.log_channel.lock().await, Some(channel_id));
+
+        SetLogChannel::set_log_channel(&ctx, None).await.unwrap();
+        assert_eq!(*ctx.log_channel.lock().await, None);
+    }
+
+    #[tokio::test]
+    async fn test_other_guild() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        // a channel of another guild the bot is in, which is not among those of this guild
+        let channel_id = ChannelId::new(42);
+        assert!(matches!(
+            SetLogChannel::set_log_channel(&ctx, Some(channel_id)).await,
+            Err(Error::InvalidLogChannel(id)) if id == channel_id
+        ));
+        assert_eq!(*ctx.log_channel.lock().await, None);
+    }
+
+    #[tokio::test]
+    async fn test_insufficient_permission() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        assert!(matches!(
+            SetLogChannel::set_log_channel(&ctx, Some(ChannelId::new(42))).await,
+            Err(Error::InsufficientPermission(_))
+        ));
+        assert_eq!(*ctx.log_channel.lock().await, None);
+    }
+}
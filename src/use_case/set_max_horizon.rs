@@ -0,0 +1,50 @@
+use crate::context::{GuildContext, MessageContext, SettingContext};
+use crate::error::{Error, Result};
+
+use serenity::model::permissions::Permissions;
+
+#[async_trait::async_trait]
+pub trait SetMaxHorizon: SettingContext + GuildContext + MessageContext {
+    #[tracing::instrument(skip(self))]
+    async fn set_max_horizon(&self, hours: u8) -> Result<()> {
+        if !self
+            .member_permissions(self.author_id())
+            .await?
+            .manage_guild()
+        {
+            return Err(Error::InsufficientPermission(Permissions::MANAGE_GUILD));
+        }
+
+        self.set_max_horizon_hours(hours).await?;
+        self.react_success().await?;
+        Ok(())
+    }
+}
+
+impl<T: SettingContext + GuildContext + MessageContext> SetMaxHorizon for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::SetMaxHorizon;
+    use crate::{
+        error::Error,
+        test::{MockContext, MOCK_AUTHOR_1, MOCK_AUTHOR_2},
+    };
+    use std::sync::atomic::Ordering;
+
+    #[tokio::test]
+    async fn test_success() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        ctx.set_max_horizon(24).await.unwrap();
+        assert_eq!(ctx.max_horizon_hours.load(Ordering::SeqCst), 24);
+    }
+
+    #[tokio::test]
+    async fn test_insufficient_permission() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        assert!(matches!(
+            ctx.set_max_horizon(24).await,
+            Err(Error::InsufficientPermission(_))
+        ));
+    }
+}
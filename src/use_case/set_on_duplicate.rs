@@ -0,0 +1,53 @@
+use crate::context::{GuildContext, MessageContext, SettingContext};
+use crate::error::{Error, Result};
+use crate::model::setting::DuplicatePolicy;
+
+use serenity::model::permissions::Permissions;
+
+#[async_trait::async_trait]
+pub trait SetOnDuplicate: SettingContext + GuildContext + MessageContext {
+    #[tracing::instrument(skip(self))]
+    async fn set_on_duplicate(&self, on_duplicate: DuplicatePolicy) -> Result<()> {
+        if !self
+            .member_permissions(self.author_id())
+            .await?
+            .manage_guild()
+        {
+            return Err(Error::InsufficientPermission(Permissions::MANAGE_GUILD));
+        }
+
+        SettingContext::set_on_duplicate(self, on_duplicate).await?;
+        self.react_success().await?;
+        Ok(())
+    }
+}
+
+impl<T: SettingContext + GuildContext + MessageContext> SetOnDuplicate for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::SetOnDuplicate;
+    use crate::{
+        error::Error,
+        model::setting::DuplicatePolicy,
+        test::{MockContext, MOCK_AUTHOR_1, MOCK_AUTHOR_2},
+    };
+
+    #[tokio::test]
+    async fn test_success() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        ctx.set_on_duplicate(DuplicatePolicy::Replace)
+            .await
+            .unwrap();
+        assert_eq!(*ctx.on_duplicate.lock().await, DuplicatePolicy::Replace);
+    }
+
+    #[tokio::test]
+    async fn test_insufficient_permission() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        assert!(matches!(
+            ctx.set_on_duplicate(DuplicatePolicy::Replace).await,
+            Err(Error::InsufficientPermission(_))
+        ));
+    }
+}
@@ -0,0 +1,87 @@
+use crate::context::{GuildContext, MessageContext, SettingContext};
+use crate::error::{Error, Result};
+use crate::model::reaction::{Outcome, MAX_PHRASE_LENGTH};
+
+use serenity::model::permissions::Permissions;
+
+#[async_trait::async_trait]
+pub trait SetPhrase: SettingContext + GuildContext + MessageContext {
+    #[tracing::instrument(skip(self))]
+    async fn set_phrase(&self, outcome: Outcome, phrase: Option<String>) -> Result<()> {
+        if !self
+            .member_permissions(self.author_id())
+            .await?
+            .manage_guild()
+        {
+            return Err(Error::InsufficientPermission(Permissions::MANAGE_GUILD));
+        }
+
+        if phrase
+            .as_ref()
+            .is_some_and(|phrase| phrase.chars().count() > MAX_PHRASE_LENGTH)
+        {
+            return Err(Error::PhraseTooLong {
+                max_length: MAX_PHRASE_LENGTH,
+            });
+        }
+
+        SettingContext::set_phrase(self, outcome, phrase).await?;
+        self.react_success().await
+    }
+}
+
+impl<T: SettingContext + GuildContext + MessageContext> SetPhrase for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::SetPhrase;
+    use crate::{
+        error::Error,
+        model::{message::Message, reaction::Outcome},
+        test::{MockContext, MOCK_AUTHOR_1, MOCK_AUTHOR_2},
+    };
+
+    #[tokio::test]
+    async fn test_success() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        ctx.set_phrase(Outcome::Success, Some("おつかれ".to_owned()))
+            .await
+            .unwrap();
+        assert_eq!(
+            ctx.phrases
+                .lock()
+                .await
+                .get(&Outcome::Success)
+                .map(String::as_str),
+            Some("おつかれ")
+        );
+        // said right away with the reaction
+        assert!(matches!(
+            ctx.sent_messages.lock().await.as_slice(),
+            [Message::Phrase(phrase)] if phrase == "おつかれ"
+        ));
+
+        ctx.set_phrase(Outcome::Success, None).await.unwrap();
+        assert!(ctx.phrases.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_too_long() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        assert!(matches!(
+            ctx.set_phrase(Outcome::Failure, Some("あ".repeat(101)))
+                .await,
+            Err(Error::PhraseTooLong { max_length: 100 })
+        ));
+        assert!(ctx.phrases.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_insufficient_permission() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        assert!(matches!(
+            ctx.set_phrase(Outcome::Success, None).await,
+            Err(Error::InsufficientPermission(_))
+        ));
+    }
+}
@@ -0,0 +1,57 @@
+use crate::context::{GuildContext, MessageContext, SettingContext};
+use crate::error::{Error, Result};
+use crate::model::setting::RandomDistribution;
+
+use serenity::model::permissions::Permissions;
+
+#[async_trait::async_trait]
+pub trait SetRandomDistribution: SettingContext + GuildContext + MessageContext {
+    #[tracing::instrument(skip(self))]
+    async fn set_random_distribution(&self, distribution: RandomDistribution) -> Result<()> {
+        if !self
+            .member_permissions(self.author_id())
+            .await?
+            .manage_guild()
+        {
+            return Err(Error::InsufficientPermission(Permissions::MANAGE_GUILD));
+        }
+
+        SettingContext::set_random_distribution(self, distribution).await?;
+        self.react_success().await?;
+        Ok(())
+    }
+}
+
+impl<T: SettingContext + GuildContext + MessageContext> SetRandomDistribution for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::SetRandomDistribution;
+    use crate::{
+        error::Error,
+        model::setting::RandomDistribution,
+        test::{MockContext, MOCK_AUTHOR_1, MOCK_AUTHOR_2},
+    };
+
+    #[tokio::test]
+    async fn test_success() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        ctx.set_random_distribution(RandomDistribution::LateBiased)
+            .await
+            .unwrap();
+        assert_eq!(
+            *ctx.random_distribution.lock().await,
+            RandomDistribution::LateBiased
+        );
+    }
+
+    #[tokio::test]
+    async fn test_insufficient_permission() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        assert!(matches!(
+            ctx.set_random_distribution(RandomDistribution::LateBiased)
+                .await,
+            Err(Error::InsufficientPermission(_))
+        ));
+    }
+}
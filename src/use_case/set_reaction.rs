@@ -0,0 +1,101 @@
+use crate::context::{GuildContext, MessageContext, SettingContext};
+use crate::error::{Error, Result};
+use crate::model::reaction::Outcome;
+
+use serenity::model::{channel::ReactionType, permissions::Permissions};
+
+#[async_trait::async_trait]
+pub trait SetReaction: SettingContext + GuildContext + MessageContext {
+    /// Reacts to the command with the new reaction, which also makes sure that it is usable.
+    #[tracing::instrument(skip(self))]
+    async fn set_reaction(&self, outcome: Outcome, reaction: Option<ReactionType>) -> Result<()> {
+        if !self
+            .member_permissions(self.author_id())
+            .await?
+            .manage_guild()
+        {
+            return Err(Error::InsufficientPermission(Permissions::MANAGE_GUILD));
+        }
+
+        let Some(reaction) = reaction else {
+            SettingContext::set_reaction(self, outcome, None).await?;
+            return self.react(outcome.default_reaction()).await;
+        };
+        if let ReactionType::Custom { id, .. } = &reaction {
+            if !self.has_emoji(*id).await? {
+                return Err(Error::UnusableEmoji(reaction));
+            }
+        }
+        // Discord refuses the emojis it does not know
+        if let Err(e) = self.react(reaction.clone()).await {
+            tracing::info!(error = %e, "cannot react with the new reaction");
+            return Err(Error::UnusableEmoji(reaction));
+        }
+        SettingContext::set_reaction(self, outcome, Some(reaction)).await
+    }
+}
+
+impl<T: SettingContext + GuildContext + MessageContext> SetReaction for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::SetReaction;
+    use crate::{
+        error::Error,
+        model::reaction::Outcome,
+        test::{MockContext, MOCK_AUTHOR_1, MOCK_AUTHOR_2},
+    };
+
+    use serenity::model::{channel::ReactionType, id::EmojiId};
+
+    #[tokio::test]
+    async fn test_unicode() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        let party = ReactionType::Unicode("🎉".to_owned());
+        ctx.set_reaction(Outcome::Success, Some(party.clone()))
+            .await
+            .unwrap();
+        assert_eq!(
+            ctx.reactions.lock().await.get(&Outcome::Success),
+            Some(&party)
+        );
+        assert_eq!(*ctx.added_reactions.lock().await, vec![party]);
+
+        ctx.set_reaction(Outcome::Success, None).await.unwrap();
+        assert!(ctx.reactions.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_custom_emoji() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        ctx.emojis.lock().await.insert(EmojiId::new(1));
+        let custom = |id| ReactionType::Custom {
+            animated: false,
+            id: EmojiId::new(id),
+            name: Some("kaisan".to_owned()),
+        };
+
+        ctx.set_reaction(Outcome::Failure, Some(custom(1)))
+            .await
+            .unwrap();
+        assert_eq!(
+            ctx.reactions.lock().await.get(&Outcome::Failure),
+            Some(&custom(1))
+        );
+
+        assert!(matches!(
+            ctx.set_reaction(Outcome::Success, Some(custom(2))).await,
+            Err(Error::UnusableEmoji(_))
+        ));
+        assert_eq!(ctx.reactions.lock().await.get(&Outcome::Success), None);
+    }
+
+    #[tokio::test]
+    async fn test_insufficient_permission() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        assert!(matches!(
+            ctx.set_reaction(Outcome::Success, None).await,
+            Err(Error::InsufficientPermission(_))
+        ));
+    }
+}
@@ -0,0 +1,72 @@
+use crate::context::{GuildContext, MessageContext, SettingContext};
+use crate::error::{Error, Result};
+use crate::model::template::ReminderTemplate;
+
+use serenity::model::permissions::Permissions;
+
+#[async_trait::async_trait]
+pub trait SetReminderText: SettingContext + GuildContext + MessageContext {
+    #[tracing::instrument(skip(self))]
+    async fn set_reminder_text(&self, text: Option<String>) -> Result<()> {
+        if !self
+            .member_permissions(self.author_id())
+            .await?
+            .manage_guild()
+        {
+            return Err(Error::InsufficientPermission(Permissions::MANAGE_GUILD));
+        }
+
+        let template = text
+            .map(|text| ReminderTemplate::parse(&text))
+            .transpose()?;
+        self.set_reminder_template(template).await?;
+        self.react_success().await?;
+        Ok(())
+    }
+}
+
+impl<T: SettingContext + GuildContext + MessageContext> SetReminderText for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::SetReminderText;
+    use crate::{
+        error::Error,
+        model::template::{InvalidTemplateError, ReminderTemplate},
+        test::{MockContext, MOCK_AUTHOR_1, MOCK_AUTHOR_2},
+    };
+
+    #[tokio::test]
+    async fn test_success() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        ctx.set_reminder_text(Some("あと{remaining}で解散だよ".to_owned()))
+            .await
+            .unwrap();
+        assert_eq!(
+            *ctx.reminder_template.lock().await,
+            Some(ReminderTemplate::parse("あと{remaining}で解散だよ").unwrap())
+        );
+
+        ctx.set_reminder_text(None).await.unwrap();
+        assert_eq!(*ctx.reminder_template.lock().await, None);
+    }
+
+    #[tokio::test]
+    async fn test_invalid() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        assert!(matches!(
+            ctx.set_reminder_text(Some("あと{remain}".to_owned())).await,
+            Err(Error::InvalidReminderText(InvalidTemplateError::UnknownPlaceholder(name))) if name == "remain"
+        ));
+        assert_eq!(*ctx.reminder_template.lock().await, None);
+    }
+
+    #[tokio::test]
+    async fn test_insufficient_permission() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        assert!(matches!(
+            ctx.set_reminder_text(None).await,
+            Err(Error::InsufficientPermission(_))
+        ));
+    }
+}
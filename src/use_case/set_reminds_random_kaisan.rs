@@ -5,6 +5,7 @@ use serenity::model::permissions::Permissions;
 
 #[async_trait::async_trait]
 pub trait SetRemindsRandomKaisan: SettingContext + GuildContext + MessageContext {
+    #[tracing::instrument(skip(self))]
     async fn set_reminds_random_kaisan(&self, reminds_random_kaisan: bool) -> Result<()> {
         if !self
             .member_permissions(self.author_id())
@@ -15,7 +16,7 @@ pub trait SetRemindsRandomKaisan: SettingContext + GuildContext + MessageContext
         }
 
         SettingContext::set_reminds_random_kaisan(self, reminds_random_kaisan).await?;
-        self.react('✅').await?;
+        self.react_success().await?;
         Ok(())
     }
 }
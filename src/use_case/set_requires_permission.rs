@@ -5,6 +5,7 @@ use serenity::model::permissions::Permissions;
 
 #[async_trait::async_trait]
 pub trait SetRequiresPermission: SettingContext + GuildContext + MessageContext {
+    #[tracing::instrument(skip(self))]
     async fn set_requires_permission(&self, requires_permission: bool) -> Result<()> {
         if !self
             .member_permissions(self.author_id())
@@ -15,7 +16,7 @@ pub trait SetRequiresPermission: SettingContext + GuildContext + MessageContext
         }
 
         SettingContext::set_requires_permission(self, requires_permission).await?;
-        self.react('✅').await?;
+        self.react_success().await?;
         Ok(())
     }
 }
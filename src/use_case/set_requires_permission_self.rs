@@ -0,0 +1,52 @@
+use crate::context::{GuildContext, MessageContext, SettingContext};
+use crate::error::{Error, Result};
+
+use serenity::model::permissions::Permissions;
+
+#[async_trait::async_trait]
+pub trait SetRequiresPermissionSelf: SettingContext + GuildContext + MessageContext {
+    #[tracing::instrument(skip(self))]
+    async fn set_requires_permission_self(&self, requires_permission_self: bool) -> Result<()> {
+        if !self
+            .member_permissions(self.author_id())
+            .await?
+            .manage_guild()
+        {
+            return Err(Error::InsufficientPermission(Permissions::MANAGE_GUILD));
+        }
+
+        SettingContext::set_requires_permission_self(self, requires_permission_self).await?;
+        self.react_success().await?;
+        Ok(())
+    }
+}
+
+impl<T: SettingContext + GuildContext + MessageContext> SetRequiresPermissionSelf for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::SetRequiresPermissionSelf;
+    use crate::{
+        error::Error,
+        test::{MockContext, MOCK_AUTHOR_1, MOCK_AUTHOR_2},
+    };
+    use std::sync::atomic::Ordering;
+
+    #[tokio::test]
+    async fn test_success() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        ctx.set_requires_permission_self(false).await.unwrap();
+        assert!(!ctx.requires_permission_self.load(Ordering::SeqCst));
+        ctx.set_requires_permission_self(true).await.unwrap();
+        assert!(ctx.requires_permission_self.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_insufficient_permission() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        assert!(matches!(
+            ctx.set_requires_permission_self(true).await,
+            Err(Error::InsufficientPermission(_))
+        ));
+    }
+}
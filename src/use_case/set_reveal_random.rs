@@ -0,0 +1,51 @@
+use crate::context::{GuildContext, MessageContext, SettingContext};
+use crate::error::{Error, Result};
+use crate::model::setting::RevealRandom;
+
+use serenity::model::permissions::Permissions;
+
+#[async_trait::async_trait]
+pub trait SetRevealRandom: SettingContext + GuildContext + MessageContext {
+    #[tracing::instrument(skip(self))]
+    async fn set_reveal_random(&self, reveal_random: RevealRandom) -> Result<()> {
+        if !self
+            .member_permissions(self.author_id())
+            .await?
+            .manage_guild()
+        {
+            return Err(Error::InsufficientPermission(Permissions::MANAGE_GUILD));
+        }
+
+        SettingContext::set_reveal_random(self, reveal_random).await?;
+        self.react_success().await?;
+        Ok(())
+    }
+}
+
+impl<T: SettingContext + GuildContext + MessageContext> SetRevealRandom for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::SetRevealRandom;
+    use crate::{
+        error::Error,
+        model::setting::RevealRandom,
+        test::{MockContext, MOCK_AUTHOR_1, MOCK_AUTHOR_2},
+    };
+
+    #[tokio::test]
+    async fn test_success() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        ctx.set_reveal_random(RevealRandom::Channel).await.unwrap();
+        assert_eq!(*ctx.reveal_random.lock().await, RevealRandom::Channel);
+    }
+
+    #[tokio::test]
+    async fn test_insufficient_permission() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        assert!(matches!(
+            ctx.set_reveal_random(RevealRandom::Channel).await,
+            Err(Error::InsufficientPermission(_))
+        ));
+    }
+}
@@ -6,6 +6,7 @@ use serenity::model::permissions::Permissions;
 
 #[async_trait::async_trait]
 pub trait SetTimeZone: SettingContext + GuildContext + MessageContext {
+    #[tracing::instrument(skip(self))]
     async fn set_timezone(&self, timezone: Tz) -> Result<()> {
         if !self
             .member_permissions(self.author_id())
@@ -16,7 +17,7 @@ pub trait SetTimeZone: SettingContext + GuildContext + MessageContext {
         }
 
         SettingContext::set_timezone(self, timezone).await?;
-        self.react('✅').await?;
+        self.react_success().await?;
         Ok(())
     }
 }
@@ -0,0 +1,53 @@
+use crate::context::{GuildContext, MessageContext, SettingContext};
+use crate::error::{Error, Result};
+use crate::model::time::Hour;
+
+use serenity::model::permissions::Permissions;
+
+#[async_trait::async_trait]
+pub trait SetTonightHour: SettingContext + GuildContext + MessageContext {
+    #[tracing::instrument(skip(self))]
+    async fn set_tonight_hour(&self, hour: Hour) -> Result<()> {
+        if !self
+            .member_permissions(self.author_id())
+            .await?
+            .manage_guild()
+        {
+            return Err(Error::InsufficientPermission(Permissions::MANAGE_GUILD));
+        }
+
+        SettingContext::set_tonight_hour(self, hour).await?;
+        self.react_success().await?;
+        Ok(())
+    }
+}
+
+impl<T: SettingContext + GuildContext + MessageContext> SetTonightHour for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::SetTonightHour;
+    use crate::{
+        error::Error,
+        model::time::Hour,
+        test::{MockContext, MOCK_AUTHOR_1, MOCK_AUTHOR_2},
+    };
+
+    #[tokio::test]
+    async fn test_success() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        ctx.set_tonight_hour(Hour::from_u8(23).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(*ctx.tonight_hour.lock().await, Hour::from_u8(23).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_insufficient_permission() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        assert!(matches!(
+            ctx.set_tonight_hour(Hour::from_u8(23).unwrap()).await,
+            Err(Error::InsufficientPermission(_))
+        ));
+    }
+}
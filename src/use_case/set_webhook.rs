@@ -0,0 +1,81 @@
+use crate::context::{GuildContext, MessageContext, SettingContext};
+use crate::error::{Error, Result};
+use crate::sink;
+
+use serenity::model::permissions::Permissions;
+
+#[async_trait::async_trait]
+pub trait SetWebhook: SettingContext + GuildContext + MessageContext {
+    #[tracing::instrument(skip(self))]
+    async fn set_webhook(&self, url: Option<String>) -> Result<()> {
+        if !self
+            .member_permissions(self.author_id())
+            .await?
+            .manage_guild()
+        {
+            return Err(Error::InsufficientPermission(Permissions::MANAGE_GUILD));
+        }
+
+        if let Some(url) = &url {
+            if !sink::is_allowed_url(url) {
+                return Err(Error::InvalidWebhookUrl(url.clone()));
+            }
+        }
+
+        self.set_webhook_url(url).await?;
+        self.react_success().await?;
+        Ok(())
+    }
+}
+
+impl<T: SettingContext + GuildContext + MessageContext> SetWebhook for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::SetWebhook;
+    use crate::{
+        error::Error,
+        test::{MockContext, MOCK_AUTHOR_1, MOCK_AUTHOR_2},
+    };
+
+    #[tokio::test]
+    async fn test_success() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        let url = "https://example.com/hooks/kaisan".to_owned();
+        ctx.set_webhook(Some(url.clone())).await.unwrap();
+        assert_eq!(*ctx.webhook_url.lock().await, Some(url));
+
+        ctx.set_webhook(None).await.unwrap();
+        assert_eq!(*ctx.webhook_url.lock().await, None);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_url() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        for url in [
+            "example.com",
+            "ftp://example.com",
+            "https://",
+            "http://example.com/hook",
+            "https://127.0.0.1:8080/hook",
+            "https://169.254.169.254/",
+        ] {
+            assert!(matches!(
+                ctx.set_webhook(Some(url.to_owned())).await,
+                Err(Error::InvalidWebhookUrl(_))
+            ));
+        }
+        assert_eq!(*ctx.webhook_url.lock().await, None);
+    }
+
+    #[tokio::test]
+    async fn test_insufficient_permission() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        assert!(matches!(
+            ctx.set_webhook(Some("https://example.com".to_owned()))
+                .await,
+            Err(Error::InsufficientPermission(_))
+        ));
+        assert_eq!(*ctx.webhook_url.lock().await, None);
+    }
+}
@@ -0,0 +1,51 @@
+use crate::context::{GuildContext, MessageContext, SettingContext};
+use crate::error::{Error, Result};
+use crate::model::setting::WeekStart;
+
+use serenity::model::permissions::Permissions;
+
+#[async_trait::async_trait]
+pub trait SetWeekStart: SettingContext + GuildContext + MessageContext {
+    #[tracing::instrument(skip(self))]
+    async fn set_week_start(&self, week_start: WeekStart) -> Result<()> {
+        if !self
+            .member_permissions(self.author_id())
+            .await?
+            .manage_guild()
+        {
+            return Err(Error::InsufficientPermission(Permissions::MANAGE_GUILD));
+        }
+
+        SettingContext::set_week_start(self, week_start).await?;
+        self.react_success().await?;
+        Ok(())
+    }
+}
+
+impl<T: SettingContext + GuildContext + MessageContext> SetWeekStart for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::SetWeekStart;
+    use crate::{
+        error::Error,
+        model::setting::WeekStart,
+        test::{MockContext, MOCK_AUTHOR_1, MOCK_AUTHOR_2},
+    };
+
+    #[tokio::test]
+    async fn test_success() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        ctx.set_week_start(WeekStart::Sunday).await.unwrap();
+        assert_eq!(*ctx.week_start.lock().await, WeekStart::Sunday);
+    }
+
+    #[tokio::test]
+    async fn test_insufficient_permission() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        assert!(matches!(
+            ctx.set_week_start(WeekStart::Sunday).await,
+            Err(Error::InsufficientPermission(_))
+        ));
+    }
+}
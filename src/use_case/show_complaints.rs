@@ -0,0 +1,47 @@
+use crate::context::{ChannelContext, EventContext};
+use crate::error::Result;
+use crate::model::message::Message;
+
+use std::cmp::Reverse;
+
+const RANKING_SIZE: usize = 10;
+
+#[async_trait::async_trait]
+pub trait ShowComplaints: EventContext + ChannelContext {
+    #[tracing::instrument(skip(self))]
+    async fn show_complaints(&self) -> Result<()> {
+        let mut ranking: Vec<_> = self.complaint_counts().await?.into_iter().collect();
+        ranking.sort_by_key(|(user_id, count)| (Reverse(*count), *user_id));
+        ranking.truncate(RANKING_SIZE);
+
+        self.message(Message::ComplaintRanking(ranking)).await
+    }
+}
+
+impl<T: EventContext + ChannelContext> ShowComplaints for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::ShowComplaints;
+    use crate::{
+        model::message::Message,
+        test::{MockContext, MOCK_AUTHOR_1, MOCK_AUTHOR_2},
+    };
+
+    #[tokio::test]
+    async fn test() {
+        let ctx = MockContext::new();
+        {
+            let mut counts = ctx.complaint_counts.lock().await;
+            counts.insert(MOCK_AUTHOR_1, 2);
+            counts.insert(MOCK_AUTHOR_2, 5);
+        }
+        ctx.show_complaints().await.unwrap();
+
+        assert!(matches!(
+            ctx.sent_messages.lock().await.as_slice(),
+            [Message::ComplaintRanking(ranking)]
+              if ranking == &[(MOCK_AUTHOR_2, 5), (MOCK_AUTHOR_1, 2)]
+        ));
+    }
+}
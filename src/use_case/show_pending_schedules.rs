@@ -0,0 +1,75 @@
+use crate::context::{ChannelContext, MessageContext, OwnerContext, TimeContext};
+use crate::error::{Error, Result};
+use crate::model::message::Message;
+
+#[async_trait::async_trait]
+pub trait ShowPendingSchedules:
+    OwnerContext + MessageContext + ChannelContext + TimeContext
+{
+    /// Sends the pending schedules of every guild to the owner by DM.
+    #[tracing::instrument(skip(self))]
+    async fn show_pending_schedules(&self) -> Result<()> {
+        if !self.is_owner(self.author_id()) {
+            return Err(Error::NotOwner);
+        }
+
+        let schedules = self.all_schedules().await;
+        tracing::info!(count = schedules.len(), "listing pending schedules");
+        let message = Message::PendingSchedules {
+            now: self.current_time(),
+            schedules,
+        };
+        self.direct_message(self.author_id(), message).await?;
+        self.react_success().await
+    }
+}
+
+impl<T: OwnerContext + MessageContext + ChannelContext + TimeContext> ShowPendingSchedules for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::ShowPendingSchedules;
+    use crate::{
+        context::ScheduleContext,
+        error::Error,
+        model::message::Message,
+        test::{mock_schedule, MockContext, MOCK_AUTHOR_1, MOCK_AUTHOR_2, MOCK_GUILD_ID},
+    };
+    use chrono::{Duration, Utc};
+
+    #[tokio::test]
+    async fn test_success() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        ctx.owners.lock().unwrap().insert(MOCK_AUTHOR_2);
+        let id = ctx
+            .register_schedule(mock_schedule(
+                MOCK_AUTHOR_1,
+                Utc::now() + Duration::minutes(10),
+            ))
+            .await;
+
+        ctx.show_pending_schedules().await.unwrap();
+
+        let direct_messages = ctx.direct_messages.lock().await;
+        let [(user_id, Message::PendingSchedules { schedules, .. })] = direct_messages.as_slice()
+        else {
+            panic!("expected a list of schedules");
+        };
+        assert_eq!(*user_id, MOCK_AUTHOR_2);
+        assert!(
+            matches!(schedules.as_slice(), [(guild_id, i, _)] if *guild_id == MOCK_GUILD_ID && *i == id)
+        );
+        assert!(ctx.sent_messages.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_not_owner() {
+        // even the members with every permission in the guild
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        assert!(matches!(
+            ctx.show_pending_schedules().await,
+            Err(Error::NotOwner)
+        ));
+        assert!(ctx.direct_messages.lock().await.is_empty());
+    }
+}
@@ -1,24 +1,80 @@
 use crate::context::{ChannelContext, SettingContext};
 use crate::error::Result;
-use crate::model::message::Message;
+use crate::model::{message::Message, setting::SettingsSnapshot};
 
 #[async_trait::async_trait]
 pub trait ShowSetting: SettingContext + ChannelContext {
+    #[tracing::instrument(skip(self))]
     async fn show_setting(&self) -> Result<()> {
-        let (requires_permission, timezone, reminds_random_kaisan, reminders) =
-            futures::future::try_join4(
-                self.requires_permission(),
-                self.timezone(),
-                self.reminds_random_kaisan(),
-                self.reminders(),
-            )
-            .await?;
+        let (
+            SettingsSnapshot {
+                timezone,
+                requires_permission,
+                requires_permission_self,
+                reminders,
+                reminds_random_kaisan,
+            },
+            auto_kaisan_hour,
+            max_horizon_hours,
+            tonight_hour,
+            max_schedules_per_user,
+            undo_window_minutes,
+            countdown,
+            author_leave_policy,
+            reveal_random,
+            random_distribution,
+            dst_policy,
+            week_start,
+            locale,
+            on_duplicate,
+            allowed_channels,
+            allowed_roles,
+            command_prefix,
+            reminder_text,
+        ) = futures::try_join!(
+            self.settings_snapshot(),
+            self.auto_kaisan_hour(),
+            self.max_horizon_hours(),
+            self.tonight_hour(),
+            self.max_schedules_per_user(),
+            self.undo_window_minutes(),
+            self.countdown(),
+            self.author_leave_policy(),
+            self.reveal_random(),
+            self.random_distribution(),
+            self.dst_policy(),
+            self.week_start(),
+            self.locale(),
+            self.on_duplicate(),
+            self.allowed_channels(),
+            self.allowed_roles(),
+            self.command_prefix(),
+            self.reminder_template(),
+        )?;
 
         let message = Message::Setting {
             requires_permission,
+            requires_permission_self,
             timezone,
             reminds_random_kaisan,
             reminders,
+            auto_kaisan_hour,
+            max_horizon_hours,
+            tonight_hour,
+            max_schedules_per_user,
+            undo_window_minutes,
+            countdown,
+            author_leave_policy,
+            reveal_random,
+            random_distribution,
+            dst_policy,
+            week_start,
+            locale,
+            on_duplicate,
+            allowed_channels,
+            allowed_roles,
+            command_prefix,
+            reminder_text,
         };
         self.message(message).await?;
 
@@ -31,7 +87,16 @@ impl<T: SettingContext + ChannelContext> ShowSetting for T {}
 #[cfg(test)]
 mod tests {
     use super::ShowSetting;
-    use crate::{model::message::Message, test::MockContext};
+    use crate::{
+        model::{
+            message::Message,
+            setting::{
+                AuthorLeavePolicy, DstPolicy, DuplicatePolicy, Locale, RandomDistribution,
+                RevealRandom, WeekStart,
+            },
+        },
+        test::MockContext,
+    };
     use std::sync::atomic::Ordering;
 
     #[tokio::test]
@@ -45,8 +110,8 @@ mod tests {
 
         assert!(matches!(
             ctx.sent_messages.lock().await.as_slice(),
-            [Message::Setting { requires_permission, timezone, reminders, reminds_random_kaisan }]
-              if requires_permission == &perm && timezone == &tz && reminders == &rms && reminds_random_kaisan == &random
+            [Message::Setting { requires_permission, requires_permission_self: false, timezone, reminders, reminds_random_kaisan, auto_kaisan_hour: None, max_horizon_hours: 12, tonight_hour: _, max_schedules_per_user: 3, undo_window_minutes: 2, countdown: false, author_leave_policy: AuthorLeavePolicy::Keep, reveal_random: RevealRandom::Off, random_distribution: RandomDistribution::Uniform, dst_policy: DstPolicy::Earliest, week_start: WeekStart::Monday, locale: Locale::Japanese, on_duplicate: DuplicatePolicy::Stack, allowed_channels, allowed_roles, command_prefix: None, reminder_text: None }]
+              if requires_permission == &perm && timezone == &tz && reminders == &rms && reminds_random_kaisan == &random && allowed_channels.is_empty() && allowed_roles.is_empty()
         ));
     }
 }
@@ -0,0 +1,95 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use crate::context::{ChannelContext, EventContext, SettingContext, TimeContext};
+use crate::error::Result;
+use crate::model::message::Message;
+
+use chrono::{Datelike, Duration};
+use serenity::model::id::UserId;
+
+const RANKING_SIZE: usize = 10;
+
+fn ranking(counts: HashMap<UserId, u64>) -> Vec<(UserId, u64)> {
+    let mut ranking: Vec<_> = counts.into_iter().filter(|(_, n)| *n > 0).collect();
+    ranking.sort_by_key(|(user_id, n)| (Reverse(*n), *user_id));
+    ranking.truncate(RANKING_SIZE);
+    ranking
+}
+
+#[async_trait::async_trait]
+pub trait ShowStats: EventContext + SettingContext + TimeContext + ChannelContext {
+    /// Shows the time spent in voice and the number of kaisans for each user since the start of
+    /// the week.
+    #[tracing::instrument(skip(self))]
+    async fn show_stats(&self) -> Result<()> {
+        let timezone = self.timezone().await?;
+        let week_start = self.week_start().await?;
+        let today = self.current_time().with_timezone(&timezone).date_naive();
+        let days = today.weekday().days_since(week_start.weekday());
+
+        let mut voice_seconds = HashMap::new();
+        let mut kaisaned_counts = HashMap::new();
+        for date in (0..=days).filter_map(|n| today.checked_sub_signed(Duration::days(n.into()))) {
+            for (user_id, seconds) in self.voice_times(date).await? {
+                *voice_seconds.entry(user_id).or_default() += seconds;
+            }
+            for (user_id, count) in self.kaisaned_counts(date).await? {
+                *kaisaned_counts.entry(user_id).or_default() += count;
+            }
+        }
+
+        let voice_times = ranking(voice_seconds)
+            .into_iter()
+            .map(|(user_id, seconds)| (user_id, Duration::seconds(seconds as i64)))
+            .collect();
+        self.message(Message::WeeklyStats {
+            voice_times,
+            kaisaned: ranking(kaisaned_counts),
+        })
+        .await
+    }
+}
+
+impl<T: EventContext + SettingContext + TimeContext + ChannelContext> ShowStats for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::ShowStats;
+    use crate::{
+        model::{message::Message, setting::WeekStart},
+        test::{MockContext, MOCK_AUTHOR_1, MOCK_AUTHOR_2},
+    };
+
+    use chrono::{Duration, NaiveDate, TimeZone, Utc};
+
+    #[tokio::test]
+    async fn test_this_week() {
+        // Wednesday in Japan
+        let ctx =
+            MockContext::with_current_time(Utc.with_ymd_and_hms(2024, 1, 3, 12, 0, 0).unwrap());
+        *ctx.week_start.lock().await = WeekStart::Monday;
+        let date = |d| NaiveDate::from_ymd_opt(2024, 1, d).unwrap();
+        {
+            let mut voice_times = ctx.voice_times.lock().await;
+            voice_times.insert((date(1), MOCK_AUTHOR_1), 3600);
+            voice_times.insert((date(3), MOCK_AUTHOR_1), 1800);
+            voice_times.insert((date(2), MOCK_AUTHOR_2), 7200);
+            // last week
+            let sunday = NaiveDate::from_ymd_opt(2023, 12, 31).unwrap();
+            voice_times.insert((sunday, MOCK_AUTHOR_2), 3600);
+            let mut kaisaned = ctx.kaisaned_counts.lock().await;
+            kaisaned.insert((date(2), MOCK_AUTHOR_1), 2);
+        }
+        ctx.show_stats().await.unwrap();
+
+        assert!(matches!(
+            ctx.sent_messages.lock().await.as_slice(),
+            [Message::WeeklyStats { voice_times, kaisaned }]
+              if voice_times == &[
+                  (MOCK_AUTHOR_2, Duration::hours(2)),
+                  (MOCK_AUTHOR_1, Duration::minutes(90)),
+              ] && kaisaned == &[(MOCK_AUTHOR_1, 2)]
+        ));
+    }
+}
@@ -0,0 +1,165 @@
+use crate::context::{ChannelContext, GuildContext, MessageContext, SettingContext};
+use crate::error::{Error, Result};
+use crate::model::{
+    menu::{SelectMenu, TimeZoneRegion},
+    message::Message,
+};
+
+use anyhow::Context as _;
+use chrono_tz::Tz;
+use serenity::model::permissions::Permissions;
+
+/// Walks through the region and the city of the timezone with select menus.
+#[async_trait::async_trait]
+pub trait TimeZoneWizard: SettingContext + GuildContext + ChannelContext + MessageContext {
+    #[tracing::instrument(skip(self))]
+    async fn start_timezone_wizard(&self) -> Result<()> {
+        if !self
+            .member_permissions(self.author_id())
+            .await?
+            .manage_guild()
+        {
+            return Err(Error::InsufficientPermission(Permissions::MANAGE_GUILD));
+        }
+
+        let current = self.timezone().await?;
+        self.post_menu(
+            Message::TimeZoneRegionMenu,
+            SelectMenu::timezone_regions(current),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Called with the message of the menu as the context.
+    #[tracing::instrument(skip(self))]
+    async fn select_timezone_region(&self, region: TimeZoneRegion, current: Tz) -> Result<()> {
+        if !self
+            .member_permissions(self.author_id())
+            .await?
+            .manage_guild()
+        {
+            return Err(Error::InsufficientPermission(Permissions::MANAGE_GUILD));
+        }
+
+        let message_id = self.message_id().context("no menu to update")?;
+        self.edit_menu(
+            message_id,
+            Message::TimeZoneCityMenu(region),
+            Some(SelectMenu::timezones(region, current)),
+        )
+        .await
+    }
+
+    /// Called with the message of the menu as the context. Fails if the timezone is no longer
+    /// `current`, the one when the wizard has started.
+    #[tracing::instrument(skip(self))]
+    async fn select_timezone(&self, timezone: Tz, current: Tz) -> Result<()> {
+        if !self
+            .member_permissions(self.author_id())
+            .await?
+            .manage_guild()
+        {
+            return Err(Error::InsufficientPermission(Permissions::MANAGE_GUILD));
+        }
+
+        let message_id = self.message_id().context("no menu to update")?;
+        if !self.replace_timezone(current, timezone).await? {
+            return Err(Error::SettingConflict);
+        }
+        self.edit_menu(message_id, Message::TimeZoneChosen(timezone), None)
+            .await
+    }
+}
+
+impl<T: SettingContext + GuildContext + ChannelContext + MessageContext> TimeZoneWizard for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::TimeZoneWizard;
+    use crate::{
+        error::Error,
+        model::{menu::MenuSelection, message::Message},
+        test::{MockContext, MOCK_AUTHOR_1, MOCK_AUTHOR_2},
+    };
+    use chrono_tz::Tz;
+
+    #[tokio::test]
+    async fn test_asia_tokyo() {
+        let mut ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        *ctx.timezone.lock().await = Tz::UTC;
+        ctx.start_timezone_wizard().await.unwrap();
+
+        let (&message_id, menu) = ctx
+            .menus
+            .lock()
+            .await
+            .iter()
+            .map(|(id, menu)| (id, menu.clone()))
+            .next()
+            .unwrap();
+        let asia = menu
+            .options
+            .iter()
+            .filter_map(
+                |option| match MenuSelection::parse(&menu.custom_id, &option.value) {
+                    Some(MenuSelection::TimeZoneRegion { region, current }) => {
+                        assert_eq!(current, Tz::UTC);
+                        Some(region)
+                    }
+                    _ => None,
+                },
+            )
+            .find(|region| region.timezones().contains(&Tz::Asia__Tokyo))
+            .unwrap();
+
+        ctx.message_id = Some(message_id);
+        ctx.select_timezone_region(asia, Tz::UTC).await.unwrap();
+        let menu = ctx.menus.lock().await[&message_id].clone();
+        assert_eq!(
+            MenuSelection::parse(&menu.custom_id, "Asia/Tokyo"),
+            Some(MenuSelection::TimeZone {
+                timezone: Tz::Asia__Tokyo,
+                current: Tz::UTC
+            })
+        );
+        assert!(matches!(
+            ctx.sent_messages.lock().await.as_slice(),
+            [Message::TimeZoneCityMenu(region)] if *region == asia
+        ));
+
+        ctx.select_timezone(Tz::Asia__Tokyo, Tz::UTC).await.unwrap();
+        assert_eq!(*ctx.timezone.lock().await, Tz::Asia__Tokyo);
+        assert!(ctx.menus.lock().await.is_empty());
+        assert!(matches!(
+            ctx.sent_messages.lock().await.as_slice(),
+            [Message::TimeZoneChosen(Tz::Asia__Tokyo)]
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_insufficient_permission() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        assert!(matches!(
+            ctx.start_timezone_wizard().await,
+            Err(Error::InsufficientPermission(_))
+        ));
+        assert!(matches!(
+            ctx.select_timezone(Tz::UTC, Tz::Japan).await,
+            Err(Error::InsufficientPermission(_))
+        ));
+        assert!(ctx.sent_messages.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_conflict() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        *ctx.timezone.lock().await = Tz::Europe__London;
+        assert!(matches!(
+            ctx.select_timezone(Tz::Asia__Tokyo, Tz::UTC).await,
+            Err(Error::SettingConflict)
+        ));
+        assert_eq!(*ctx.timezone.lock().await, Tz::Europe__London);
+        assert!(ctx.sent_messages.lock().await.is_empty());
+    }
+}
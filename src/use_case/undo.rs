@@ -0,0 +1,146 @@
+use super::log_event::log_event;
+use crate::context::{
+    ChannelContext, MessageContext, ScheduleContext, SettingContext, TimeContext,
+};
+use crate::error::{Error, Result};
+use crate::model::message::{LogEntry, Message};
+
+use chrono::Duration;
+
+#[async_trait::async_trait]
+pub trait Undo:
+    ScheduleContext + SettingContext + MessageContext + ChannelContext + TimeContext
+{
+    /// Cancels the latest schedule the author made, as long as it was made within the undo
+    /// window.
+    #[tracing::instrument(skip(self))]
+    async fn undo(&self) -> Result<()> {
+        let undo_window_minutes = self.undo_window_minutes().await?;
+        let since = self.current_time() - Duration::minutes(undo_window_minutes.into());
+        let author_id = self.author_id();
+
+        let latest = self
+            .schedules()
+            .await
+            .into_iter()
+            .filter(|(_, schedule)| schedule.author_id == author_id)
+            .filter_map(|(id, schedule)| Some((schedule.created_at?, id)))
+            .filter(|(created_at, _)| *created_at > since)
+            .max();
+        let Some((_, id)) = latest else {
+            return Err(Error::NothingToUndo {
+                undo_window_minutes,
+            });
+        };
+        let Some(schedule) = self.cancel_schedule(id).await else {
+            // carried out in the meantime
+            return Err(Error::NothingToUndo {
+                undo_window_minutes,
+            });
+        };
+        log_event(
+            self,
+            LogEntry::Cancelled {
+                schedule_id: id,
+                voice_channel_id: schedule.voice_channel_id,
+                by: Some(author_id),
+            },
+        )
+        .await;
+        tracing::info!(?id, "undid the schedule");
+        self.message(Message::Undone(id)).await
+    }
+}
+
+impl<T: ScheduleContext + SettingContext + MessageContext + ChannelContext + TimeContext> Undo
+    for T
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Undo;
+    use crate::{
+        context::ScheduleContext,
+        error::Error,
+        model::{kaisanee::KaisaneeSpecifier, message::Message, schedule::Schedule},
+        test::{mock_schedule, MockContext, MOCK_AUTHOR_1, MOCK_AUTHOR_2},
+    };
+    use chrono::{DateTime, Duration, Utc};
+    use serenity::model::id::UserId;
+    use std::sync::atomic::Ordering;
+
+    fn schedule(author_id: UserId, created_at: DateTime<Utc>) -> Schedule {
+        Schedule {
+            kaisanee: KaisaneeSpecifier::Me,
+            created_at: Some(created_at),
+            ..mock_schedule(author_id, created_at + Duration::minutes(30))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_latest() {
+        let now = Utc::now();
+        let ctx = MockContext::with_author_current_time(MOCK_AUTHOR_1, now);
+        let earlier = ctx
+            .register_schedule(schedule(MOCK_AUTHOR_1, now - Duration::seconds(90)))
+            .await;
+        let latest = ctx
+            .register_schedule(schedule(MOCK_AUTHOR_1, now - Duration::seconds(30)))
+            .await;
+        let others = ctx
+            .register_schedule(schedule(MOCK_AUTHOR_2, now - Duration::seconds(10)))
+            .await;
+
+        ctx.undo().await.unwrap();
+
+        let mut remaining: Vec<_> = ctx
+            .schedules()
+            .await
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect();
+        remaining.sort();
+        assert_eq!(remaining, vec![earlier, others]);
+        assert!(matches!(
+            ctx.sent_messages.lock().await.as_slice(),
+            [Message::Undone(id)] if *id == latest
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_too_late() {
+        let now = Utc::now();
+        let ctx = MockContext::with_author_current_time(MOCK_AUTHOR_1, now);
+        ctx.register_schedule(schedule(MOCK_AUTHOR_1, now - Duration::minutes(3)))
+            .await;
+
+        assert!(matches!(
+            ctx.undo().await,
+            Err(Error::NothingToUndo {
+                undo_window_minutes: 2
+            })
+        ));
+        assert_eq!(ctx.schedules().await.len(), 1);
+
+        ctx.undo_window_minutes.store(5, Ordering::SeqCst);
+        ctx.undo().await.unwrap();
+        assert!(ctx.schedules().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_disabled() {
+        let now = Utc::now();
+        let ctx = MockContext::with_author_current_time(MOCK_AUTHOR_1, now);
+        ctx.undo_window_minutes.store(0, Ordering::SeqCst);
+        ctx.register_schedule(schedule(MOCK_AUTHOR_1, now)).await;
+
+        assert!(matches!(
+            ctx.undo().await,
+            Err(Error::NothingToUndo {
+                undo_window_minutes: 0
+            })
+        ));
+        assert_eq!(ctx.schedules().await.len(), 1);
+    }
+}
@@ -0,0 +1,152 @@
+use super::schedule_kaisan::author_voice_channel;
+use crate::context::{
+    ChannelContext, GuildContext, MessageContext, ScheduleContext, SettingContext, TimeContext,
+};
+use crate::error::Result;
+use crate::model::message::Message;
+
+use chrono::Duration;
+
+#[async_trait::async_trait]
+pub trait When:
+    ScheduleContext
+    + GuildContext
+    + ChannelContext
+    + MessageContext
+    + SettingContext
+    + TimeContext
+    + Sync
+{
+    #[tracing::instrument(skip(self))]
+    async fn when(&self) -> Result<()> {
+        let voice_channel_id = author_voice_channel(self).await?;
+
+        let next = self
+            .schedules()
+            .await
+            .into_iter()
+            .filter(|(_, schedule)| schedule.voice_channel_id == voice_channel_id)
+            .min_by_key(|(_, schedule)| schedule.random_until.unwrap_or(schedule.time));
+        let Some((id, schedule)) = next else {
+            return self.message(Message::NoPendingKaisan).await;
+        };
+
+        let now = self.current_time();
+        let tz = self.timezone().await?;
+        // do not reveal the randomly chosen time
+        let time = schedule.random_until.unwrap_or(schedule.time);
+        // round up to minutes not to show seconds
+        let remaining = Duration::minutes(((time - now).num_seconds() + 59) / 60);
+        self.message(Message::NextKaisan {
+            id,
+            remaining,
+            time: time.with_timezone(&tz),
+            is_random: schedule.random_until.is_some(),
+        })
+        .await
+    }
+}
+
+impl<
+        T: ScheduleContext
+            + GuildContext
+            + ChannelContext
+            + MessageContext
+            + SettingContext
+            + TimeContext
+            + Sync,
+    > When for T
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use super::When;
+    use crate::{
+        context::ScheduleContext,
+        error::Error,
+        model::{message::Message, schedule::Schedule},
+        test::{mock_schedule, MockContext, MOCK_AUTHOR_1, MOCK_AUTHOR_2, MOCK_VOICE_CHANNEL_ID},
+    };
+    use chrono::{DateTime, Duration, Utc};
+    use serenity::model::id::{ChannelId, UserId};
+
+    fn schedule(
+        voice_channel_id: ChannelId,
+        time: DateTime<Utc>,
+        random_until: Option<DateTime<Utc>>,
+    ) -> Schedule {
+        Schedule {
+            voice_channel_id,
+            random_until,
+            ..mock_schedule(MOCK_AUTHOR_1, time)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_next() {
+        let now = Utc::now();
+        let ctx = MockContext::with_author_current_time(MOCK_AUTHOR_2, now);
+        ctx.register_schedule(schedule(
+            MOCK_VOICE_CHANNEL_ID,
+            now + Duration::minutes(60),
+            None,
+        ))
+        .await;
+        ctx.register_schedule(schedule(
+            MOCK_VOICE_CHANNEL_ID,
+            now + Duration::minutes(37),
+            None,
+        ))
+        .await;
+        ctx.register_schedule(schedule(
+            ChannelId::new(1),
+            now + Duration::minutes(5),
+            None,
+        ))
+        .await;
+        ctx.when().await.unwrap();
+
+        assert!(matches!(
+            ctx.sent_messages.lock().await.as_slice(),
+            [Message::NextKaisan { remaining, is_random: false, .. }]
+              if *remaining == Duration::minutes(37)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_random() {
+        let now = Utc::now();
+        let ctx = MockContext::with_author_current_time(MOCK_AUTHOR_2, now);
+        ctx.register_schedule(schedule(
+            MOCK_VOICE_CHANNEL_ID,
+            now + Duration::minutes(3),
+            Some(now + Duration::minutes(30)),
+        ))
+        .await;
+        ctx.when().await.unwrap();
+
+        assert!(matches!(
+            ctx.sent_messages.lock().await.as_slice(),
+            [Message::NextKaisan { remaining, is_random: true, .. }]
+              if *remaining == Duration::minutes(30)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_none() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_2);
+        ctx.when().await.unwrap();
+
+        assert!(matches!(
+            ctx.sent_messages.lock().await.as_slice(),
+            [Message::NoPendingKaisan]
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_not_in_voice_channel() {
+        let ctx = MockContext::with_author(UserId::new(999));
+        assert!(matches!(ctx.when().await, Err(Error::NotInVoiceChannel)));
+    }
+}
@@ -0,0 +1,77 @@
+use crate::context::{ChannelContext, EventContext, MessageContext, SettingContext};
+use crate::error::Result;
+use crate::model::message::Message;
+
+#[async_trait::async_trait]
+pub trait WhoKickedMe: EventContext + MessageContext + ChannelContext + SettingContext {
+    #[tracing::instrument(skip(self))]
+    async fn who_kicked_me(&self) -> Result<()> {
+        let author_id = self.author_id();
+        let events = self.disconnect_events().await?;
+
+        let Some(event) = events.into_iter().find(|e| e.target == author_id) else {
+            return self.message(Message::NoDisconnectRecord).await;
+        };
+
+        let tz = self.timezone().await?;
+        self.message(Message::LastDisconnect {
+            requester: event.requester,
+            time: event.time.with_timezone(&tz),
+        })
+        .await
+    }
+}
+
+impl<T: EventContext + MessageContext + ChannelContext + SettingContext> WhoKickedMe for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::WhoKickedMe;
+    use crate::{
+        model::{event::DisconnectEvent, message::Message},
+        test::{MockContext, MOCK_AUTHOR_1, MOCK_AUTHOR_2, MOCK_CHANNEL_ID, MOCK_VOICE_CHANNEL_ID},
+    };
+    use chrono::{Duration, Utc};
+    use serenity::model::id::UserId;
+
+    fn event(target: UserId, requester: UserId, minutes_ago: i64) -> DisconnectEvent {
+        DisconnectEvent {
+            time: Utc::now() - Duration::minutes(minutes_ago),
+            channel_id: MOCK_CHANNEL_ID,
+            voice_channel_id: MOCK_VOICE_CHANNEL_ID,
+            target,
+            requester,
+            schedule_id: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_found() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        let expected = event(MOCK_AUTHOR_1, MOCK_AUTHOR_2, 10);
+        *ctx.disconnect_events.lock().await = vec![
+            event(MOCK_AUTHOR_2, MOCK_AUTHOR_1, 5),
+            expected.clone(),
+            event(MOCK_AUTHOR_1, MOCK_AUTHOR_1, 20),
+        ];
+        ctx.who_kicked_me().await.unwrap();
+
+        assert!(matches!(
+            ctx.sent_messages.lock().await.as_slice(),
+            [Message::LastDisconnect { requester, time }]
+              if requester == &MOCK_AUTHOR_2 && time == &expected.time
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_not_found() {
+        let ctx = MockContext::with_author(MOCK_AUTHOR_1);
+        *ctx.disconnect_events.lock().await = vec![event(MOCK_AUTHOR_2, MOCK_AUTHOR_1, 5)];
+        ctx.who_kicked_me().await.unwrap();
+
+        assert!(matches!(
+            ctx.sent_messages.lock().await.as_slice(),
+            [Message::NoDisconnectRecord]
+        ));
+    }
+}
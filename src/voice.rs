@@ -0,0 +1,79 @@
+//! Announcement played in the voice channel before the kaisan, which needs the `voice` feature.
+
+#[cfg(feature = "voice")]
+use std::{path::PathBuf, sync::Arc, time::Duration};
+
+use anyhow::Result;
+use serenity::model::id::{ChannelId, GuildId};
+#[cfg(feature = "voice")]
+use songbird::{input::File, SerenityInit as _, Songbird};
+
+/// The bot stays in the channel at most this long, even if the audio is longer.
+#[cfg(feature = "voice")]
+const MAX_ANNOUNCEMENT_DURATION: Duration = Duration::from_secs(60);
+
+/// Plays an audio file, such as a countdown, in the voice channel.
+#[cfg(feature = "voice")]
+#[derive(Clone)]
+pub struct VoiceAnnouncer {
+    manager: Arc<Songbird>,
+    audio: PathBuf,
+}
+
+/// Never exists when the bot is built without the `voice` feature.
+#[cfg(not(feature = "voice"))]
+#[derive(Clone)]
+pub enum VoiceAnnouncer {}
+
+#[cfg(feature = "voice")]
+impl VoiceAnnouncer {
+    pub fn new(audio: PathBuf) -> Self {
+        VoiceAnnouncer {
+            manager: Songbird::serenity(),
+            audio,
+        }
+    }
+
+    /// Lets the voice connections go through the gateway of the client.
+    pub fn register(
+        &self,
+        builder: serenity::client::ClientBuilder,
+    ) -> serenity::client::ClientBuilder {
+        builder.register_songbird_with(Arc::clone(&self.manager))
+    }
+
+    /// Starts playing the audio and leaves the channel in the background once it is over.
+    pub async fn announce(&self, guild_id: GuildId, channel_id: ChannelId) -> Result<()> {
+        let call = self.manager.join(guild_id, channel_id).await?;
+        let track = call
+            .lock()
+            .await
+            .play_input(File::new(self.audio.clone()).into());
+
+        // leaves even if the kaisan is cancelled in the meantime
+        let manager = Arc::clone(&self.manager);
+        tokio::spawn(async move {
+            let playing = async {
+                while track
+                    .get_info()
+                    .await
+                    .is_ok_and(|state| !state.playing.is_done())
+                {
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                }
+            };
+            let _ = tokio::time::timeout(MAX_ANNOUNCEMENT_DURATION, playing).await;
+            if let Err(e) = manager.remove(guild_id).await {
+                tracing::warn!(error = %e, %guild_id, "failed to leave the voice channel");
+            }
+        });
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "voice"))]
+impl VoiceAnnouncer {
+    pub async fn announce(&self, _guild_id: GuildId, _channel_id: ChannelId) -> Result<()> {
+        match *self {}
+    }
+}
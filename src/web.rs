@@ -0,0 +1,160 @@
+use std::convert::Infallible;
+use std::net::SocketAddr;
+
+use crate::context::ContextBuilder;
+use crate::error::Error;
+use crate::model::cancel_link::{CancelLink, CancelLinkKey};
+use crate::registry::ScheduleRegistry;
+use crate::use_case::{CancelByLink, CheckRateLimit};
+
+use anyhow::{Context as _, Result};
+use hyper::{
+    header,
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+
+const CANCEL_PATH: &str = "/cancel/";
+
+/// Signs the links to cancel schedules, which point to the HTTP server.
+#[derive(Clone)]
+pub struct CancelLinks {
+    public_url: String,
+    key: CancelLinkKey,
+}
+
+impl CancelLinks {
+    pub fn new(public_url: String, secret: &str) -> Self {
+        CancelLinks {
+            public_url,
+            key: CancelLinkKey::new(secret.as_bytes()),
+        }
+    }
+
+    pub fn url(&self, link: &CancelLink) -> String {
+        format!(
+            "{}{}{}",
+            self.public_url.trim_end_matches('/'),
+            CANCEL_PATH,
+            link.token(&self.key)
+        )
+    }
+}
+
+/// Serves the pages to cancel schedules with the links sent to their authors.
+///
+/// A visit only shows a button, and the schedule is cancelled when it is pressed, so that link
+/// previews do not cancel anything.
+pub async fn serve(
+    addr: SocketAddr,
+    links: CancelLinks,
+    builder: ContextBuilder,
+    registry: ScheduleRegistry,
+) -> Result<()> {
+    let make_service = make_service_fn(move |_| {
+        let (links, builder, registry) = (links.clone(), builder.clone(), registry.clone());
+        async move {
+            Ok::<_, Infallible>(service_fn(move |req| {
+                let (links, builder, registry) = (links.clone(), builder.clone(), registry.clone());
+                async move { Ok::<_, Infallible>(handle(req, &links, &builder, &registry).await) }
+            }))
+        }
+    });
+    tracing::info!(%addr, "starting http server");
+    Server::try_bind(&addr)
+        .context("cannot bind http server")?
+        .serve(make_service)
+        .await
+        .context("http server error")
+}
+
+async fn handle(
+    req: Request<Body>,
+    links: &CancelLinks,
+    builder: &ContextBuilder,
+    registry: &ScheduleRegistry,
+) -> Response<Body> {
+    let link = req
+        .uri()
+        .path()
+        .strip_prefix(CANCEL_PATH)
+        .and_then(|token| CancelLink::verify(&links.key, token));
+    let Some(link) = link else {
+        return page(StatusCode::NOT_FOUND, "ページが見つかりません");
+    };
+
+    match *req.method() {
+        Method::GET => page(
+            StatusCode::OK,
+            r#"解散の予約を取り消しますか？<form method="post"><button>取り消す</button></form>"#,
+        ),
+        Method::POST => cancel(link, builder, registry).await,
+        _ => page(StatusCode::METHOD_NOT_ALLOWED, "ページが見つかりません"),
+    }
+}
+
+async fn cancel(
+    link: CancelLink,
+    builder: &ContextBuilder,
+    registry: &ScheduleRegistry,
+) -> Response<Body> {
+    let gone = || {
+        page(
+            StatusCode::GONE,
+            "この解散はすでに取り消されたか、実行されました",
+        )
+    };
+    let schedule = registry
+        .schedules(link.guild_id)
+        .await
+        .into_iter()
+        .find(|(id, schedule)| *id == link.schedule_id && link.matches(schedule));
+    let Some((_, schedule)) = schedule else {
+        return gone();
+    };
+    // acts as the author of the schedule, who is the one the link has been sent to
+    let Some(ctx) = builder
+        .clone()
+        .guild_id(link.guild_id)
+        .schedule(&schedule)
+        .build()
+    else {
+        return page(StatusCode::INTERNAL_SERVER_ERROR, "エラーが発生しました");
+    };
+
+    match ctx.check_rate_limit().await {
+        Ok(true) => {}
+        Ok(false) | Err(Error::RateLimited { .. }) => {
+            return page(
+                StatusCode::TOO_MANY_REQUESTS,
+                "少し待ってからもう一度どうぞ",
+            );
+        }
+        Err(e) => return internal_error(e),
+    }
+    match ctx.cancel_by_link(link).await {
+        Ok(()) => page(StatusCode::OK, "解散の予約を取り消しました"),
+        Err(Error::NoSuchSchedule(_)) => gone(),
+        Err(e) => internal_error(e),
+    }
+}
+
+fn internal_error(e: Error) -> Response<Body> {
+    tracing::error!("cannot cancel with link: {:#}", e);
+    page(StatusCode::INTERNAL_SERVER_ERROR, "エラーが発生しました")
+}
+
+fn page(status: StatusCode, body: &str) -> Response<Body> {
+    let html = format!(
+        r#"<!DOCTYPE html><html lang="ja"><head><meta charset="utf-8"><meta name="viewport" content="width=device-width"><title>解散担当大臣</title></head><body><p>{}</p></body></html>"#,
+        body
+    );
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "text/html; charset=utf-8")
+        .header(header::CACHE_CONTROL, "no-store")
+        // the token is in the path
+        .header(header::REFERRER_POLICY, "no-referrer")
+        .body(Body::from(html))
+        .unwrap()
+}